@@ -0,0 +1,261 @@
+//! `#[derive(Builder)]`: generates `<Name>Builder`, `<Name>BuilderError`, and `<Name>::builder()`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Attribute, Data, DataStruct, DeriveInput, Error, Expr, Field, Fields, Ident, Path, Token, Type};
+
+use crate::util::{attrs_named, option_inner};
+
+/// How a field is filled in when its setter was never called.
+enum FieldDefault {
+    /// `#[builder(default)]`: `Default::default()`.
+    Trait,
+    /// `#[builder(default = <expr>)]`.
+    Expr(Expr),
+}
+
+struct FieldSpec {
+    ident: Ident,
+    ty: Type,
+    /// `Some(inner)` for `Option<inner>` fields, which are optional and default to `None`.
+    option_inner: Option<Type>,
+    default: Option<FieldDefault>,
+    /// `#[builder(into)]`: the setter takes `impl Into<T>`.
+    into: bool,
+}
+
+impl FieldSpec {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let ident = field.ident.clone().expect("named fields have identifiers");
+        let mut spec = FieldSpec {
+            ident,
+            ty: field.ty.clone(),
+            option_inner: option_inner(&field.ty).cloned(),
+            default: None,
+            into: false,
+        };
+        for attr in attrs_named(&field.attrs, "builder") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    if spec.default.is_some() {
+                        return Err(meta.error("duplicate `default`"));
+                    }
+                    spec.default = Some(if meta.input.peek(Token![=]) {
+                        FieldDefault::Expr(meta.value()?.parse()?)
+                    } else {
+                        FieldDefault::Trait
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("into") {
+                    spec.into = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown field option; expected `default`, `default = <expr>`, or `into`"))
+                }
+            })?;
+        }
+        Ok(spec)
+    }
+
+    /// The type the builder stores and the setter accepts (`T` for `Option<T>` fields).
+    fn value_ty(&self) -> &Type {
+        self.option_inner.as_ref().unwrap_or(&self.ty)
+    }
+
+    fn is_required(&self) -> bool {
+        self.option_inner.is_none() && self.default.is_none()
+    }
+
+    fn setter(&self) -> TokenStream {
+        let ident = &self.ident;
+        let value_ty = self.value_ty();
+        let doc = format!("Sets `{}`.", ident.unraw());
+        let (param_ty, value) = if self.into {
+            (quote!(impl ::core::convert::Into<#value_ty>), quote!(value.into()))
+        } else {
+            (quote!(#value_ty), quote!(value))
+        };
+        quote! {
+            #[doc = #doc]
+            #[must_use]
+            pub fn #ident(mut self, value: #param_ty) -> Self {
+                self.#ident = ::core::option::Option::Some(#value);
+                self
+            }
+        }
+    }
+
+    /// The expression that produces this field's value inside `build`.
+    fn build_value(&self) -> TokenStream {
+        let ident = &self.ident;
+        let default = match &self.default {
+            None => None,
+            Some(FieldDefault::Trait) => Some(quote!(::core::default::Default::default())),
+            Some(FieldDefault::Expr(expr)) => Some(quote!(#expr)),
+        };
+        match (&self.option_inner, default) {
+            (Some(_), None) => quote!(self.#ident),
+            (Some(_), Some(default)) => quote!(self.#ident.or_else(|| #default)),
+            (None, Some(default)) => quote!(self.#ident.unwrap_or_else(|| #default)),
+            (None, None) => quote!(self.#ident.expect("required fields were checked above")),
+        }
+    }
+}
+
+/// Parses `#[builder(validate = path)]` on the struct.
+fn parse_validate(attrs: &[Attribute]) -> syn::Result<Option<Path>> {
+    let mut validate = None;
+    for attr in attrs_named(attrs, "builder") {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("validate") {
+                if validate.is_some() {
+                    return Err(meta.error("duplicate `validate`"));
+                }
+                validate = Some(meta.value()?.parse::<Path>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown struct option; expected `validate = <path to fn(&Self) -> Result<(), E>>`"))
+            }
+        })?;
+    }
+    Ok(validate)
+}
+
+/// Expands `#[derive(Builder)]` for a struct with named fields.
+///
+/// # Arguments
+///
+/// * `input` - The parsed struct the derive is attached to.
+///
+/// # Returns
+///
+/// The builder, its error type, and `builder()`, or every attribute error found.
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(DataStruct { fields: Fields::Named(named), .. }) => &named.named,
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`Builder` can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    // Report every bad attribute at once instead of one per compile.
+    let mut errors: Option<Error> = None;
+    let mut push_error = |error: Error| match &mut errors {
+        Some(existing) => existing.combine(error),
+        None => errors = Some(error),
+    };
+    let validate = parse_validate(&input.attrs).unwrap_or_else(|e| {
+        push_error(e);
+        None
+    });
+    let specs: Vec<FieldSpec> = fields
+        .iter()
+        .filter_map(|field| FieldSpec::parse(field).map_err(&mut push_error).ok())
+        .collect();
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let builder = format_ident!("{}Builder", name);
+    let error = format_ident!("{}BuilderError", name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let generics = &input.generics;
+
+    let idents: Vec<&Ident> = specs.iter().map(|spec| &spec.ident).collect();
+    let value_tys = specs.iter().map(FieldSpec::value_ty);
+    let setters = specs.iter().map(FieldSpec::setter);
+    let values = specs.iter().map(FieldSpec::build_value);
+
+    let required: Vec<&Ident> = specs.iter().filter(|spec| spec.is_required()).map(|spec| &spec.ident).collect();
+    let check_required = if required.is_empty() {
+        quote!()
+    } else {
+        let names = required.iter().map(|ident| ident.unraw().to_string());
+        quote! {
+            let mut missing = ::std::vec::Vec::new();
+            #(
+                if self.#required.is_none() {
+                    missing.push(#names);
+                }
+            )*
+            if !missing.is_empty() {
+                return ::core::result::Result::Err(#error::MissingFields(missing));
+            }
+        }
+    };
+    let run_validate = validate.map(|path| {
+        quote_spanned! {path.span()=>
+            if let ::core::result::Result::Err(e) = #path(&value) {
+                return ::core::result::Result::Err(#error::Invalid(::std::string::ToString::to_string(&e)));
+            }
+        }
+    });
+
+    let builder_doc = format!("Builder for [`{}`], created by [`{}::builder`].", name, name);
+    let error_doc = format!("Why [`{}::build`] failed.", builder);
+
+    Ok(quote! {
+        #[doc = #builder_doc]
+        #[must_use]
+        #vis struct #builder #generics #where_clause {
+            #( #idents: ::core::option::Option<#value_tys>, )*
+        }
+
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis enum #error {
+            /// Required fields whose setters were never called.
+            MissingFields(::std::vec::Vec<&'static str>),
+            /// The validation hook rejected the value.
+            Invalid(::std::string::String),
+        }
+
+        impl ::core::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    Self::MissingFields(fields) => {
+                        write!(f, "missing required field(s) for {}: {}", stringify!(#name), fields.join(", "))
+                    }
+                    Self::Invalid(reason) => write!(f, "invalid {}: {}", stringify!(#name), reason),
+                }
+            }
+        }
+
+        impl ::std::error::Error for #error {}
+
+        #[automatically_derived]
+        impl #impl_generics ::core::default::Default for #builder #ty_generics #where_clause {
+            fn default() -> Self {
+                Self { #( #idents: ::core::option::Option::None, )* }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #( #setters )*
+
+            /// Builds the value, reporting every missing required field, then runs the validation hook.
+            pub fn build(self) -> ::core::result::Result<#name #ty_generics, #error> {
+                #check_required
+                let value = #name { #( #idents: #values, )* };
+                #run_validate
+                ::core::result::Result::Ok(value)
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc = concat!("Starts a [`", stringify!(#builder), "`] with no fields set.")]
+            #vis fn builder() -> #builder #ty_generics {
+                ::core::default::Default::default()
+            }
+        }
+    })
+}
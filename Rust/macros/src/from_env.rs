@@ -0,0 +1,269 @@
+//! `#[derive(FromEnv)]`: generates `<Name>::from_env()`, `<Name>::from_env_with()`, and
+//! `<Name>EnvError`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Attribute, Data, DataStruct, DeriveInput, Error, Field, Fields, Ident, LitStr, Path, Type};
+
+use crate::util::{attrs_named, option_inner};
+
+/// What a field gets when its variable is unset.
+enum EnvDefault {
+    /// `#[env(default)]`: `Default::default()`.
+    Trait,
+    /// `#[env(default = "...")]`: parsed like a real value, so `"30"` works for a `u64`.
+    Raw(LitStr),
+}
+
+struct FieldSpec {
+    ident: Ident,
+    /// The full variable name: `rename`, or the prefix plus the upper-cased field name.
+    key: String,
+    /// The type the parser produces (`T` for `Option<T>` fields).
+    value_ty: Type,
+    optional: bool,
+    default: Option<EnvDefault>,
+    /// `#[env(parse = path)]`: a `fn(&str) -> Result<T, E>` used instead of `FromStr`.
+    parse: Option<Path>,
+}
+
+impl FieldSpec {
+    fn parse(field: &Field, prefix: &str) -> syn::Result<Self> {
+        let ident = field.ident.clone().expect("named fields have identifiers");
+        let mut rename: Option<LitStr> = None;
+        let mut default = None;
+        let mut parse = None;
+        for attr in attrs_named(&field.attrs, "env") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if rename.is_some() {
+                        return Err(meta.error("duplicate `rename`"));
+                    }
+                    rename = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("default") {
+                    if default.is_some() {
+                        return Err(meta.error("duplicate `default`"));
+                    }
+                    default = Some(if meta.input.peek(syn::Token![=]) {
+                        EnvDefault::Raw(meta.value()?.parse()?)
+                    } else {
+                        EnvDefault::Trait
+                    });
+                } else if meta.path.is_ident("parse") {
+                    if parse.is_some() {
+                        return Err(meta.error("duplicate `parse`"));
+                    }
+                    parse = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error(
+                        "unknown field option; expected `rename = \"VAR\"`, `default`, `default = \"value\"`, or `parse = <path>`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+        let key = match rename {
+            Some(name) => name.value(),
+            None => format!("{}{}", prefix, ident.unraw().to_string().to_uppercase()),
+        };
+        let optional = option_inner(&field.ty).is_some();
+        if optional && default.is_some() {
+            return Err(Error::new(field.ty.span(), "`Option` fields are already optional; remove `default`"));
+        }
+        Ok(FieldSpec {
+            ident,
+            key,
+            value_ty: option_inner(&field.ty).unwrap_or(&field.ty).clone(),
+            optional,
+            default,
+            parse,
+        })
+    }
+
+    /// A `let` binding of type `Option<FieldType>` that is `None` if a problem was recorded.
+    fn binding(&self) -> TokenStream {
+        let ident = &self.ident;
+        let key = &self.key;
+        let value_ty = &self.value_ty;
+        // Binding the parser to a typed local points signature mistakes at the `parse` path.
+        let parser = match &self.parse {
+            Some(path) => quote_spanned! {path.span()=>
+                let parse: fn(&str) -> ::core::result::Result<#value_ty, _> = #path;
+            },
+            None => quote!(let parse = <#value_ty as ::core::str::FromStr>::from_str;),
+        };
+        let wrap = if self.optional { quote!(::core::option::Option::Some(value)) } else { quote!(value) };
+        let on_missing = match (&self.default, self.optional) {
+            (_, true) => quote!(::core::option::Option::Some(::core::option::Option::None)),
+            (Some(EnvDefault::Trait), _) => quote!(::core::option::Option::Some(::core::default::Default::default())),
+            (Some(EnvDefault::Raw(raw)), _) => quote! {
+                match parse(#raw) {
+                    ::core::result::Result::Ok(value) => ::core::option::Option::Some(value),
+                    ::core::result::Result::Err(e) => {
+                        invalid.push((#key, ::std::format!("default {:?} is invalid: {}", #raw, e)));
+                        ::core::option::Option::None
+                    }
+                }
+            },
+            (None, false) => quote! {{
+                missing.push(#key);
+                ::core::option::Option::None
+            }},
+        };
+        // Values are never echoed into errors, because variables often hold secrets.
+        quote! {
+            let #ident = {
+                #parser
+                match lookup(#key) {
+                    ::core::option::Option::Some(raw) => match parse(&raw) {
+                        ::core::result::Result::Ok(value) => ::core::option::Option::Some(#wrap),
+                        ::core::result::Result::Err(e) => {
+                            invalid.push((#key, ::std::string::ToString::to_string(&e)));
+                            ::core::option::Option::None
+                        }
+                    },
+                    ::core::option::Option::None => #on_missing,
+                }
+            };
+        }
+    }
+}
+
+/// Parses `#[env(prefix = "APP_")]` on the struct.
+fn parse_prefix(attrs: &[Attribute]) -> syn::Result<String> {
+    let mut prefix: Option<LitStr> = None;
+    for attr in attrs_named(attrs, "env") {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("prefix") {
+                if prefix.is_some() {
+                    return Err(meta.error("duplicate `prefix`"));
+                }
+                prefix = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown struct option; expected `prefix = \"APP_\"`"))
+            }
+        })?;
+    }
+    Ok(prefix.map(|p| p.value()).unwrap_or_default())
+}
+
+/// Expands `#[derive(FromEnv)]` for a struct with named fields.
+///
+/// # Arguments
+///
+/// * `input` - The parsed struct the derive is attached to.
+///
+/// # Returns
+///
+/// `from_env`, `from_env_with`, and the error type, or every attribute error found.
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(DataStruct { fields: Fields::Named(named), .. }) => &named.named,
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`FromEnv` can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let mut errors: Option<Error> = None;
+    let mut push_error = |error: Error| match &mut errors {
+        Some(existing) => existing.combine(error),
+        None => errors = Some(error),
+    };
+    let prefix = parse_prefix(&input.attrs).unwrap_or_else(|e| {
+        push_error(e);
+        String::new()
+    });
+    let specs: Vec<FieldSpec> = fields
+        .iter()
+        .filter_map(|field| FieldSpec::parse(field, &prefix).map_err(&mut push_error).ok())
+        .collect();
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let error = format_ident!("{}EnvError", name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let idents: Vec<&Ident> = specs.iter().map(|spec| &spec.ident).collect();
+    let bindings = specs.iter().map(FieldSpec::binding);
+    let keys = specs.iter().map(|spec| &spec.key);
+    let body = if specs.is_empty() {
+        quote! {
+            let _ = lookup;
+            ::core::result::Result::Ok(Self {})
+        }
+    } else {
+        quote! {
+            let mut missing: ::std::vec::Vec<&'static str> = ::std::vec::Vec::new();
+            let mut invalid: ::std::vec::Vec<(&'static str, ::std::string::String)> = ::std::vec::Vec::new();
+            #( #bindings )*
+            match (#( #idents, )*) {
+                (#( ::core::option::Option::Some(#idents), )*) => ::core::result::Result::Ok(Self { #( #idents, )* }),
+                _ => ::core::result::Result::Err(#error { missing, invalid }),
+            }
+        }
+    };
+
+    let error_doc = format!("Every missing or invalid variable found by [`{}::from_env`].", name);
+
+    Ok(quote! {
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct #error {
+            /// Required variables that are not set.
+            pub missing: ::std::vec::Vec<&'static str>,
+            /// Variables (or defaults) that failed to parse, with the parser's message.
+            pub invalid: ::std::vec::Vec<(&'static str, ::std::string::String)>,
+        }
+
+        impl ::core::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(
+                    f,
+                    "{} has {} configuration problem(s):",
+                    stringify!(#name),
+                    self.missing.len() + self.invalid.len()
+                )?;
+                for key in &self.missing {
+                    write!(f, "\n  {}: not set", key)?;
+                }
+                for (key, reason) in &self.invalid {
+                    write!(f, "\n  {}: {}", key, reason)?;
+                }
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        impl ::std::error::Error for #error {}
+
+        #[automatically_derived]
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Reads every field from the process environment.
+            #vis fn from_env() -> ::core::result::Result<Self, #error> {
+                Self::from_env_with(|key| {
+                    ::std::env::var_os(key).map(|value| value.to_string_lossy().into_owned())
+                })
+            }
+
+            /// Reads every field through `lookup`, e.g. a map loaded from a `.env` file in tests.
+            #vis fn from_env_with(
+                lookup: impl Fn(&str) -> ::core::option::Option<::std::string::String>,
+            ) -> ::core::result::Result<Self, #error> {
+                #body
+            }
+
+            /// The variable names this struct reads, in field order.
+            #vis fn env_vars() -> &'static [&'static str] {
+                &[#( #keys ),*]
+            }
+        }
+    })
+}
@@ -0,0 +1,252 @@
+//! Procedural macros that accompany the snippets in `Rust/snippets`.
+//!
+//! `custom_derive_macro_concept.rs` explains why these can't live in a single snippet file:
+//! proc macros must be compiled as their own crate with `proc-macro = true`. Depend on this
+//! crate by path:
+//!
+//! ```toml
+//! [dependencies]
+//! snippet-macros = { path = "../macros" }
+//! ```
+//!
+//! Each macro is implemented in its own module; this file only declares the entry points.
+//! Every macro's behavior is covered by the compile tests in `tests/`.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemFn};
+
+mod builder;
+mod from_env;
+mod memoize;
+mod newtype_id;
+mod retry;
+mod util;
+
+/// Derives a builder for a struct with named fields.
+///
+/// Generates `<Name>Builder` with one setter per field, `<Name>::builder()`, and
+/// `<Name>BuilderError`. `build()` returns `Result<Name, <Name>BuilderError>` and reports all
+/// missing required fields at once.
+///
+/// Field options, in `#[builder(...)]`:
+/// * `Option<T>` fields are optional: the setter takes `T`, and unset fields are `None`.
+/// * `default` fills an unset field with `Default::default()`.
+/// * `default = <expr>` fills an unset field with `<expr>`.
+/// * `into` makes the setter take `impl Into<T>`, e.g. `&str` for a `String` field.
+///
+/// Any other field is required. Struct option: `#[builder(validate = path)]` calls
+/// `path(&value)` after building, where `path` is a `fn(&Name) -> Result<(), E>` and
+/// `E: Display`. An `Err` becomes `<Name>BuilderError::Invalid`.
+///
+/// ```
+/// use snippet_macros::Builder;
+///
+/// #[derive(Debug, Builder)]
+/// #[builder(validate = Server::check)]
+/// struct Server {
+///     #[builder(into)]
+///     host: String,
+///     #[builder(default = 8080)]
+///     port: u16,
+///     #[builder(default)]
+///     tags: Vec<String>,
+///     timeout_secs: Option<u64>,
+/// }
+///
+/// impl Server {
+///     fn check(&self) -> Result<(), String> {
+///         if self.port == 0 { Err("port must be non-zero".into()) } else { Ok(()) }
+///     }
+/// }
+///
+/// let server = Server::builder().host("localhost").timeout_secs(30).build().unwrap();
+/// assert_eq!((server.port, server.timeout_secs), (8080, Some(30)));
+///
+/// let err = Server::builder().port(0).build().unwrap_err();
+/// assert_eq!(err, ServerBuilderError::MissingFields(vec!["host"]));
+/// assert!(Server::builder().host("h").port(0).build().is_err());
+/// ```
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive_builder(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    builder::expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Derives loading a struct with named fields from environment variables.
+///
+/// Generates `<Name>::from_env()`, `<Name>::from_env_with(lookup)` for tests or `.env` maps,
+/// `<Name>::env_vars()`, and `<Name>EnvError`. Loading checks every field before returning,
+/// so one error lists all missing and invalid variables. Values are never included in
+/// messages, because they may be secrets.
+///
+/// Each field reads the variable named by the struct's `#[env(prefix = "APP_")]` plus the
+/// upper-cased field name, and parses it with `FromStr`. `Option<T>` fields may be unset.
+/// Field options, in `#[env(...)]`:
+/// * `rename = "VAR"` reads `VAR` exactly, ignoring the prefix.
+/// * `default = "value"` parses `"value"` when the variable is unset.
+/// * `default` uses `Default::default()` when the variable is unset.
+/// * `parse = path` parses with `path(&str) -> Result<T, E>` (`E: Display`) instead of `FromStr`.
+///
+/// ```
+/// use snippet_macros::FromEnv;
+/// use std::collections::HashMap;
+/// use std::time::Duration;
+///
+/// fn seconds(raw: &str) -> Result<Duration, std::num::ParseIntError> {
+///     raw.trim_end_matches('s').parse().map(Duration::from_secs)
+/// }
+///
+/// #[derive(Debug, FromEnv)]
+/// #[env(prefix = "APP_")]
+/// struct Config {
+///     #[env(rename = "DATABASE_URL")]
+///     database_url: String,
+///     #[env(default = "8080")]
+///     port: u16,
+///     #[env(parse = seconds, default = "30s")]
+///     timeout: Duration,
+///     log_filter: Option<String>,
+/// }
+///
+/// let vars = HashMap::from([("DATABASE_URL", "postgres://db"), ("APP_TIMEOUT", "5s")]);
+/// let config = Config::from_env_with(|key| vars.get(key).map(|v| v.to_string())).unwrap();
+/// assert_eq!((config.port, config.timeout, config.log_filter), (8080, Duration::from_secs(5), None));
+///
+/// let vars = HashMap::from([("APP_PORT", "http")]);
+/// let err = Config::from_env_with(|key| vars.get(key).map(|v| v.to_string())).unwrap_err();
+/// assert_eq!(err.missing, ["DATABASE_URL"]);
+/// assert_eq!(err.invalid, [("APP_PORT", "invalid digit found in string".to_string())]);
+/// ```
+#[proc_macro_derive(FromEnv, attributes(env))]
+pub fn derive_from_env(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_env::expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Retries a function while it returns a retryable `Err`, sleeping between attempts.
+///
+/// Works on sync functions (sleeping with `std::thread::sleep`) and async functions
+/// (sleeping with `tokio::time::sleep`, so the caller needs `tokio` with the `time` feature).
+/// The function must return a `Result`. Its body runs once per attempt, so it must not
+/// consume its arguments; clone them inside the body instead. A `return Err(..)` in the body
+/// counts as a failed attempt. Only annotate operations that are safe to repeat.
+///
+/// Options, all optional, in `#[retry(...)]`:
+/// * `max_attempts = 3`: total attempts, including the first.
+/// * `initial_backoff = "100ms"`: delay before the second attempt (`ms`, `s`, `m`, or `h`).
+/// * `max_backoff = "10s"`: cap for exponential backoff.
+/// * `backoff = "exponential"`: double the delay after each retry, or `"fixed"`.
+/// * `jitter`: sleep a random 50-100% of each delay.
+/// * `retry_if = path`: a `fn(&E) -> bool`. Errors it rejects are returned immediately.
+/// * `clock = expr`: sleep with `expr.sleep(delay)` instead of `std::thread::sleep`, e.g.
+///   `clock = self.clock` holding an `Arc<dyn Clock>` from `snippets/clock.rs`, so tests can
+///   pass a `MockClock` and check the delays without waiting. Sync functions only; for async
+///   ones, use `tokio::time::pause()` in tests.
+///
+/// ```
+/// use snippet_macros::retry;
+/// use std::cell::Cell;
+///
+/// thread_local!(static CALLS: Cell<u32> = Cell::new(0));
+///
+/// fn is_transient(error: &std::io::Error) -> bool {
+///     error.kind() == std::io::ErrorKind::TimedOut
+/// }
+///
+/// #[retry(max_attempts = 4, initial_backoff = "1ms", retry_if = is_transient)]
+/// fn flaky_read(path: &str) -> std::io::Result<String> {
+///     let calls = CALLS.with(|c| { c.set(c.get() + 1); c.get() });
+///     if calls < 3 {
+///         return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "slow disk"));
+///     }
+///     Ok(format!("{} after {} attempts", path, calls))
+/// }
+///
+/// assert_eq!(flaky_read("config.toml").unwrap(), "config.toml after 3 attempts");
+/// ```
+#[proc_macro_attribute]
+pub fn retry(args: TokenStream, input: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(input as ItemFn);
+    retry::expand(args.into(), function).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Caches a function's results by its arguments, with optional TTL and a capacity bound.
+///
+/// The cache is the `LruCache` from `snippets/cache.rs`, which the macro expects at
+/// `crate::cache::LruCache` (copy the file into your crate as `mod cache;`, or point
+/// `cache = path` at another type with the same API). Each memoized function `name` gets
+/// one static cache plus a `name_cache()` accessor for stats and invalidation.
+///
+/// * The key is the argument itself for one argument, or a tuple of all arguments. Owned
+///   arguments are cloned; `&T` arguments are stored as `T::Owned` (`&str` as `String`).
+///   Keys must be `Hash + Eq + Clone`, and the cached value must be `Clone`.
+/// * `Result<T, E>` returns cache only `Ok(T)`. Errors are returned and retried next call.
+/// * Works on sync and `async` free functions. Methods and generic functions are rejected,
+///   since one static cache can't tell `self` values or type parameters apart.
+/// * Concurrent misses for the same key may each compute it (see `cache.rs`).
+///
+/// Options, in `#[memoize(...)]`: `capacity = 1000`, `ttl = "60s"` (`ms`, `s`, `m`, or `h`;
+/// no expiry by default), and `cache = path`.
+///
+/// ```ignore
+/// mod cache; // snippets/cache.rs
+/// use snippet_macros::memoize;
+///
+/// #[memoize(capacity = 10_000)]
+/// fn fib(n: u64) -> u64 {
+///     if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+/// }
+///
+/// #[memoize(ttl = "5m", capacity = 100)]
+/// async fn fetch_rates(currency: &str) -> Result<f64, reqwest::Error> {
+///     reqwest::get(format!("https://rates.example.com/{}", currency)).await?.json().await
+/// }
+///
+/// assert_eq!(fib(90), 2_880_067_194_370_816_120);
+/// fetch_rates_cache().remove("EUR"); // Force a refresh on the next call.
+/// ```
+#[proc_macro_attribute]
+pub fn memoize(args: TokenStream, input: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(input as ItemFn);
+    memoize::expand(args.into(), function).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Derives the impls that make a one-field tuple struct a typed ID, like `struct UserId(u64);`.
+///
+/// Typed IDs stop a `UserId` from being passed where an `OrderId` is expected, which plain
+/// `u64`s and `String`s can't. Generates `new`, `as_inner`, `into_inner`, `From` in both
+/// directions, `Display`, and `FromStr` with a `<Name>ParseError`. Derive `Debug`, `Clone`,
+/// `Copy`, `PartialEq`, `Eq`, `Hash`, and `Ord` yourself as the inner type allows.
+///
+/// Options, in `#[id(...)]`:
+/// * `prefix = "usr_"`: `Display` writes `usr_42`, and `FromStr` requires the prefix. The
+///   database still stores the bare value.
+/// * `serde`: implements `Serialize` and `Deserialize` (needs `serde`). Prefixed IDs are
+///   strings; others use the inner type's encoding.
+/// * `rusqlite`: implements `ToSql` and `FromSql` with the inner type's encoding (needs
+///   `rusqlite`, plus its `uuid` feature for `Uuid` IDs).
+///
+/// ```
+/// use snippet_macros::NewtypeId;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NewtypeId)]
+/// #[id(prefix = "usr_", serde)]
+/// struct UserId(u64);
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NewtypeId)]
+/// #[id(serde)]
+/// struct OrderId(u64);
+///
+/// let user: UserId = "usr_42".parse().unwrap();
+/// assert_eq!((user.to_string(), *user.as_inner()), ("usr_42".to_string(), 42));
+/// assert_eq!(serde_json::to_string(&user).unwrap(), r#""usr_42""#);
+/// assert_eq!(serde_json::to_string(&OrderId::new(7)).unwrap(), "7");
+///
+/// let err = "42".parse::<UserId>().unwrap_err();
+/// assert_eq!(err.to_string(), r#"invalid UserId "42": expected the prefix "usr_""#);
+/// ```
+#[proc_macro_derive(NewtypeId, attributes(id))]
+pub fn derive_newtype_id(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    newtype_id::expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
@@ -0,0 +1,182 @@
+//! `#[memoize(...)]`: caches a function's results in the `LruCache` from `snippets/cache.rs`.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::meta::ParseNestedMeta;
+use syn::spanned::Spanned;
+use syn::{
+    Error, FnArg, GenericArgument, GenericParam, ItemFn, LitInt, Pat, PathArguments, ReturnType, Type, TypePath,
+};
+
+use crate::util::parse_duration_ms;
+
+struct MemoizeOptions {
+    capacity: usize,
+    ttl_ms: Option<u64>,
+    /// The cache type; it must offer the constructors and `*get_or_insert_with*` methods of
+    /// `LruCache` in `snippets/cache.rs`.
+    cache: syn::Path,
+}
+
+impl Default for MemoizeOptions {
+    fn default() -> Self {
+        Self { capacity: 1000, ttl_ms: None, cache: syn::parse_quote!(crate::cache::LruCache) }
+    }
+}
+
+impl MemoizeOptions {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("capacity") {
+            let lit: LitInt = meta.value()?.parse()?;
+            self.capacity = lit.base10_parse()?;
+            if self.capacity == 0 {
+                return Err(Error::new(lit.span(), "`capacity` must be at least 1"));
+            }
+        } else if meta.path.is_ident("ttl") {
+            self.ttl_ms = Some(parse_duration_ms(&meta.value()?.parse()?)?);
+        } else if meta.path.is_ident("cache") {
+            self.cache = meta.value()?.parse()?;
+        } else {
+            return Err(meta.error("unknown option; expected `capacity`, `ttl`, or `cache`"));
+        }
+        Ok(())
+    }
+}
+
+/// One key component: its owned type and the expression that produces it from the argument.
+fn key_part(arg: &FnArg) -> syn::Result<(Type, TokenStream)> {
+    let typed = match arg {
+        FnArg::Typed(typed) => typed,
+        FnArg::Receiver(receiver) => {
+            return Err(Error::new(
+                receiver.span(),
+                "`#[memoize]` can't key on `self`; memoize a free function that takes the needed fields",
+            ))
+        }
+    };
+    let Pat::Ident(pat) = &*typed.pat else {
+        return Err(Error::new(typed.pat.span(), "`#[memoize]` arguments must be plain identifiers"));
+    };
+    let ident = &pat.ident;
+    match &*typed.ty {
+        // `&str` is stored as `String`, `&[T]` as `Vec<T>`, and `&T` as `T`.
+        Type::Reference(reference) => {
+            let inner = &reference.elem;
+            Ok((
+                syn::parse_quote!(<#inner as ::std::borrow::ToOwned>::Owned),
+                quote!(::std::borrow::ToOwned::to_owned(#ident)),
+            ))
+        }
+        Type::ImplTrait(_) => Err(Error::new(typed.ty.span(), "`#[memoize]` arguments can't be `impl Trait`")),
+        ty => Ok((ty.clone(), quote!(::core::clone::Clone::clone(&#ident)))),
+    }
+}
+
+/// For a `Result<T, E>` (or `io::Result<T>`-style alias) return type, returns `T`.
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    let segment = path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match (segment.ident == "Result", args.args.first()) {
+        (true, Some(GenericArgument::Type(ok))) => Some(ok),
+        _ => None,
+    }
+}
+
+/// Expands `#[memoize]` on a sync or async free function.
+///
+/// # Arguments
+///
+/// * `args` - The tokens inside `#[memoize(...)]`.
+/// * `function` - The annotated function.
+///
+/// # Returns
+///
+/// The function rewritten to consult a static cache, plus a `<name>_cache()` accessor, or
+/// an error for bad options or unsupported signatures.
+pub fn expand(args: TokenStream, function: ItemFn) -> syn::Result<TokenStream> {
+    let mut options = MemoizeOptions::default();
+    let parser = syn::meta::parser(|meta| options.parse(meta));
+    syn::parse::Parser::parse2(parser, args)?;
+
+    let ItemFn { attrs, vis, sig, block } = function;
+    if let Some(param) = sig.generics.params.iter().find(|p| !matches!(p, GenericParam::Lifetime(_))) {
+        return Err(Error::new(
+            param.span(),
+            "`#[memoize]` functions can't be generic: each one shares a single static cache",
+        ));
+    }
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) => (**ty).clone(),
+        ReturnType::Default => {
+            return Err(Error::new(sig.ident.span(), "`#[memoize]` functions must return a value"));
+        }
+    };
+
+    let mut errors: Option<Error> = None;
+    let mut key_types = Vec::new();
+    let mut key_exprs = Vec::new();
+    for arg in &sig.inputs {
+        match key_part(arg) {
+            Ok((ty, expr)) => {
+                key_types.push(ty);
+                key_exprs.push(expr);
+            }
+            Err(e) => match &mut errors {
+                Some(existing) => existing.combine(e),
+                None => errors = Some(e),
+            },
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+    // One argument is its own key, so `fib_cache().remove(&10)` reads naturally.
+    let (key_type, key_expr) = match key_types.len() {
+        1 => (quote!(#(#key_types)*), quote!(#(#key_exprs)*)),
+        _ => (quote!((#(#key_types,)*)), quote!((#(#key_exprs,)*))),
+    };
+
+    // `Result` returns cache only `Ok` values; errors are returned and retried next call.
+    let ok_type = result_ok_type(&output);
+    let value_type = ok_type.unwrap_or(&output);
+    let is_async = sig.asyncness.is_some();
+    let method = format_ident!(
+        "{}get_or_insert_with{}",
+        if ok_type.is_some() { "try_" } else { "" },
+        if is_async { "_async" } else { "" }
+    );
+    let cache = &options.cache;
+    let capacity = options.capacity;
+    let constructor = match options.ttl_ms {
+        Some(ms) => quote!(#cache::with_ttl(#capacity, ::std::time::Duration::from_millis(#ms))),
+        None => quote!(#cache::new(#capacity)),
+    };
+    let cache_fn = format_ident!("{}_cache", sig.ident);
+    let cache_doc = format!(
+        "The cache behind [`{}`], keyed by its arguments; use it to inspect stats or invalidate entries.",
+        sig.ident
+    );
+    let cache_type = quote!(#cache<#key_type, #value_type>);
+    // A mixed-site name can't shadow anything the function body refers to.
+    let key = syn::Ident::new("key", Span::mixed_site());
+    let lookup = if is_async {
+        quote!(#method(#key, || async move #block).await)
+    } else {
+        quote!(#method(#key, || -> #output #block))
+    };
+
+    Ok(quote! {
+        #[doc = #cache_doc]
+        #vis fn #cache_fn() -> &'static #cache_type {
+            static CACHE: ::std::sync::OnceLock<#cache_type> = ::std::sync::OnceLock::new();
+            CACHE.get_or_init(|| #constructor)
+        }
+
+        #( #attrs )*
+        #vis #sig {
+            let #key = #key_expr;
+            #cache_fn().#lookup
+        }
+    })
+}
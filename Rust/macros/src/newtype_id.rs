@@ -0,0 +1,215 @@
+//! `#[derive(NewtypeId)]`: conversions, `Display`/`FromStr`, and optional serde and rusqlite
+//! impls for a single-field ID wrapper like `struct UserId(u64)`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DataStruct, DeriveInput, Error, Fields, LitStr, Type};
+
+use crate::util::attrs_named;
+
+#[derive(Default)]
+struct IdOptions {
+    /// `prefix = "usr_"`: written by `Display`, required by `FromStr`, and absent in the database.
+    prefix: Option<LitStr>,
+    /// `serde`: implement `Serialize`/`Deserialize`.
+    serde: bool,
+    /// `rusqlite`: implement `ToSql`/`FromSql`.
+    rusqlite: bool,
+}
+
+/// Parses `#[id(...)]` on the struct.
+fn parse_options(attrs: &[Attribute]) -> syn::Result<IdOptions> {
+    let mut options = IdOptions::default();
+    for attr in attrs_named(attrs, "id") {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("prefix") {
+                if options.prefix.is_some() {
+                    return Err(meta.error("duplicate `prefix`"));
+                }
+                let prefix: LitStr = meta.value()?.parse()?;
+                if prefix.value().is_empty() {
+                    return Err(Error::new(prefix.span(), "`prefix` can't be empty; remove it instead"));
+                }
+                options.prefix = Some(prefix);
+            } else if meta.path.is_ident("serde") {
+                options.serde = true;
+            } else if meta.path.is_ident("rusqlite") {
+                options.rusqlite = true;
+            } else {
+                return Err(meta.error("unknown option; expected `prefix = \"...\"`, `serde`, or `rusqlite`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// Expands `#[derive(NewtypeId)]` for a non-generic tuple struct with one field.
+///
+/// # Arguments
+///
+/// * `input` - The parsed struct the derive is attached to.
+///
+/// # Returns
+///
+/// The conversion, formatting, and opted-in encoding impls plus `<Name>ParseError`, or an
+/// error for bad options or an unsupported struct shape.
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let inner: &Type = match &input.data {
+        Data::Struct(DataStruct { fields: Fields::Unnamed(fields), .. }) if fields.unnamed.len() == 1 => {
+            &fields.unnamed[0].ty
+        }
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`NewtypeId` can only be derived for tuple structs with exactly one field, like `struct UserId(u64);`",
+            ))
+        }
+    };
+    if let Some(param) = input.generics.params.first() {
+        return Err(Error::new_spanned(param, "`NewtypeId` can't be derived for generic structs"));
+    }
+    let options = parse_options(&input.attrs)?;
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let error = format_ident!("{}ParseError", name);
+    let error_doc = format!("Why a string couldn't be parsed as a [`{}`].", name);
+
+    let (display, strip_prefix) = match &options.prefix {
+        Some(prefix) => (
+            quote!(write!(f, "{}{}", #prefix, self.0)),
+            quote! {
+                let raw = s.strip_prefix(#prefix).ok_or_else(|| #error {
+                    input: ::std::string::ToString::to_string(s),
+                    reason: ::std::format!("expected the prefix {:?}", #prefix),
+                })?;
+            },
+        ),
+        None => (quote!(::core::fmt::Display::fmt(&self.0, f)), quote!(let raw = s;)),
+    };
+
+    // Prefixed IDs travel as strings so the JSON matches `Display`; bare ones use the inner encoding.
+    let serde = options.serde.then(|| {
+        let (serialize, deserialize) = if options.prefix.is_some() {
+            (
+                quote!(serializer.collect_str(self)),
+                quote! {
+                    let raw = <::std::string::String as ::serde::Deserialize<'de>>::deserialize(deserializer)?;
+                    raw.parse().map_err(::serde::de::Error::custom)
+                },
+            )
+        } else {
+            (
+                quote!(::serde::Serialize::serialize(&self.0, serializer)),
+                quote!(<#inner as ::serde::Deserialize<'de>>::deserialize(deserializer).map(Self)),
+            )
+        };
+        quote! {
+            #[automatically_derived]
+            impl ::serde::Serialize for #name {
+                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                    #serialize
+                }
+            }
+
+            #[automatically_derived]
+            impl<'de> ::serde::Deserialize<'de> for #name {
+                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
+                    #deserialize
+                }
+            }
+        }
+    });
+
+    // The prefix is presentation only: columns hold the inner value so they index and compare natively.
+    let rusqlite = options.rusqlite.then(|| {
+        quote! {
+            #[automatically_derived]
+            impl ::rusqlite::types::ToSql for #name {
+                fn to_sql(&self) -> ::rusqlite::Result<::rusqlite::types::ToSqlOutput<'_>> {
+                    ::rusqlite::types::ToSql::to_sql(&self.0)
+                }
+            }
+
+            #[automatically_derived]
+            impl ::rusqlite::types::FromSql for #name {
+                fn column_result(value: ::rusqlite::types::ValueRef<'_>) -> ::rusqlite::types::FromSqlResult<Self> {
+                    <#inner as ::rusqlite::types::FromSql>::column_result(value).map(Self)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #[doc = #error_doc]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct #error {
+            input: ::std::string::String,
+            reason: ::std::string::String,
+        }
+
+        impl ::core::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "invalid {} {:?}: {}", stringify!(#name), self.input, self.reason)
+            }
+        }
+
+        impl ::std::error::Error for #error {}
+
+        #[automatically_derived]
+        impl #name {
+            /// Wraps a raw value.
+            #vis const fn new(value: #inner) -> Self {
+                Self(value)
+            }
+
+            /// Borrows the raw value.
+            #vis const fn as_inner(&self) -> &#inner {
+                &self.0
+            }
+
+            /// Unwraps the raw value.
+            #vis fn into_inner(self) -> #inner {
+                self.0
+            }
+        }
+
+        #[automatically_derived]
+        impl ::core::convert::From<#inner> for #name {
+            fn from(value: #inner) -> Self {
+                Self(value)
+            }
+        }
+
+        #[automatically_derived]
+        impl ::core::convert::From<#name> for #inner {
+            fn from(id: #name) -> Self {
+                id.0
+            }
+        }
+
+        #[automatically_derived]
+        impl ::core::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #display
+            }
+        }
+
+        #[automatically_derived]
+        impl ::core::str::FromStr for #name {
+            type Err = #error;
+
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                #strip_prefix
+                <#inner as ::core::str::FromStr>::from_str(raw).map(Self).map_err(|e| #error {
+                    input: ::std::string::ToString::to_string(s),
+                    reason: ::std::string::ToString::to_string(&e),
+                })
+            }
+        }
+
+        #serde
+        #rusqlite
+    })
+}
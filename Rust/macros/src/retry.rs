@@ -0,0 +1,169 @@
+//! `#[retry(...)]`: re-runs a function's body while it returns a retryable `Err`.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::meta::ParseNestedMeta;
+use syn::spanned::Spanned;
+use syn::{Error, Expr, ItemFn, LitInt, LitStr, Path, ReturnType};
+
+use crate::util::parse_duration_ms;
+
+enum Backoff {
+    Fixed,
+    Exponential,
+}
+
+/// The options in `#[retry(...)]`, documented on `retry` in `lib.rs`.
+struct RetryOptions {
+    /// Total attempts, including the first one.
+    max_attempts: u32,
+    initial_backoff_ms: u64,
+    max_backoff_ms: u64,
+    backoff: Backoff,
+    /// Sleep a random 50-100% of each delay so failing clients don't retry in lockstep.
+    jitter: bool,
+    /// `fn(&E) -> bool`; errors it rejects are returned immediately.
+    retry_if: Option<Path>,
+    /// An expression with a `sleep(Duration)` method, used instead of `std::thread::sleep`.
+    clock: Option<Expr>,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 10_000,
+            backoff: Backoff::Exponential,
+            jitter: false,
+            retry_if: None,
+            clock: None,
+        }
+    }
+}
+
+impl RetryOptions {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("max_attempts") {
+            let lit: LitInt = meta.value()?.parse()?;
+            self.max_attempts = lit.base10_parse()?;
+            if self.max_attempts == 0 {
+                return Err(Error::new(lit.span(), "`max_attempts` must be at least 1"));
+            }
+        } else if meta.path.is_ident("initial_backoff") {
+            self.initial_backoff_ms = parse_duration_ms(&meta.value()?.parse()?)?;
+        } else if meta.path.is_ident("max_backoff") {
+            self.max_backoff_ms = parse_duration_ms(&meta.value()?.parse()?)?;
+        } else if meta.path.is_ident("backoff") {
+            let lit: LitStr = meta.value()?.parse()?;
+            self.backoff = match lit.value().as_str() {
+                "fixed" => Backoff::Fixed,
+                "exponential" => Backoff::Exponential,
+                _ => return Err(Error::new(lit.span(), "expected \"fixed\" or \"exponential\"")),
+            };
+        } else if meta.path.is_ident("jitter") {
+            self.jitter = true;
+        } else if meta.path.is_ident("retry_if") {
+            self.retry_if = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("clock") {
+            self.clock = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error(
+                "unknown option; expected `max_attempts`, `initial_backoff`, `max_backoff`, `backoff`, `jitter`, `retry_if`, or `clock`",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Expands `#[retry]` on a sync or async function that returns a `Result`.
+///
+/// # Arguments
+///
+/// * `args` - The tokens inside `#[retry(...)]`.
+/// * `function` - The annotated function.
+///
+/// # Returns
+///
+/// The function with its body wrapped in a retry loop, or an error for bad options or a
+/// function without a return type.
+pub fn expand(args: TokenStream, function: ItemFn) -> syn::Result<TokenStream> {
+    let mut options = RetryOptions::default();
+    let parser = syn::meta::parser(|meta| options.parse(meta));
+    syn::parse::Parser::parse2(parser, args)?;
+    if options.max_backoff_ms < options.initial_backoff_ms {
+        return Err(Error::new(Span::call_site(), "`max_backoff` is shorter than `initial_backoff`"));
+    }
+
+    let ItemFn { attrs, vis, sig, block } = function;
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) => ty,
+        ReturnType::Default => {
+            return Err(Error::new(sig.ident.span(), "`#[retry]` functions must return a `Result`"));
+        }
+    };
+
+    // Mixed-site identifiers can't clash with names in the function body.
+    let attempt = Ident::new("attempt", Span::mixed_site());
+    let backoff = Ident::new("backoff", Span::mixed_site());
+    let result = Ident::new("result", Span::mixed_site());
+    let error = Ident::new("error", Span::mixed_site());
+
+    let RetryOptions { max_attempts, initial_backoff_ms, max_backoff_ms, .. } = options;
+    let run_attempt = if sig.asyncness.is_some() {
+        quote!(async #block.await)
+    } else {
+        quote!((|| -> #output #block)())
+    };
+    let (error_pattern, should_retry) = match &options.retry_if {
+        Some(path) => (quote!(ref #error), quote_spanned!(path.span()=> #path(#error))),
+        None => (quote!(_), quote!(true)),
+    };
+    let delay = if options.jitter {
+        quote! {{
+            use ::std::hash::{BuildHasher, Hasher};
+            let mut hasher = ::std::collections::hash_map::RandomState::new().build_hasher();
+            hasher.write_u32(#attempt);
+            #backoff.mul_f64(0.5 + (hasher.finish() as f64 / u64::MAX as f64) * 0.5)
+        }}
+    } else {
+        quote!(#backoff)
+    };
+    let sleep = match (&options.clock, sig.asyncness.is_some()) {
+        (Some(clock), true) => {
+            return Err(Error::new(
+                clock.span(),
+                "`clock` only works on sync functions; test async ones with `tokio::time::pause()`",
+            ));
+        }
+        (Some(clock), false) => quote_spanned!(clock.span()=> (#clock).sleep(#delay);),
+        (None, true) => quote!(::tokio::time::sleep(#delay).await;),
+        (None, false) => quote!(::std::thread::sleep(#delay);),
+    };
+    let (backoff_mut, next_backoff) = match options.backoff {
+        Backoff::Fixed => (quote!(), quote!()),
+        Backoff::Exponential => (
+            quote!(mut),
+            quote!(#backoff = (#backoff * 2).min(::std::time::Duration::from_millis(#max_backoff_ms));),
+        ),
+    };
+
+    Ok(quote! {
+        #( #attrs )*
+        #vis #sig {
+            let mut #attempt: u32 = 1;
+            let #backoff_mut #backoff = ::std::time::Duration::from_millis(#initial_backoff_ms);
+            loop {
+                let #result: #output = #run_attempt;
+                match #result {
+                    ::core::result::Result::Err(#error_pattern) if #attempt < #max_attempts && #should_retry => {
+                        #sleep
+                        #next_backoff
+                        #attempt += 1;
+                    }
+                    #result => return #result,
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,42 @@
+//! Parsing helpers shared by the derive and attribute macros.
+
+use syn::{Attribute, Error, GenericArgument, LitStr, PathArguments, Type};
+
+/// The attributes named `name`, e.g. every `#[builder(...)]` on an item.
+pub fn attrs_named<'a>(attrs: &'a [Attribute], name: &'a str) -> impl Iterator<Item = &'a Attribute> {
+    attrs.iter().filter(move |attr| attr.path().is_ident(name))
+}
+
+/// Returns `T` if `ty` is `Option<T>` (also matched as `std::option::Option<T>`).
+pub fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match (segment.ident == "Option", args.args.len(), args.args.first()) {
+        (true, 1, Some(GenericArgument::Type(inner))) => Some(inner),
+        _ => None,
+    }
+}
+
+/// Parses `"250ms"`, `"2s"`, `"1m"`, or `"1h"` into milliseconds at compile time.
+pub fn parse_duration_ms(lit: &LitStr) -> syn::Result<u64> {
+    let text = lit.value();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let scale = match unit {
+        "ms" => 1,
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => 0,
+    };
+    match number.parse::<u64>() {
+        Ok(n) if scale > 0 => n
+            .checked_mul(scale)
+            .ok_or_else(|| Error::new(lit.span(), "duration is too long to fit in u64 milliseconds")),
+        _ => Err(Error::new(lit.span(), "expected a duration like \"250ms\", \"2s\", \"1m\", or \"1h\"")),
+    }
+}
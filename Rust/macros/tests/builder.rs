@@ -0,0 +1,10 @@
+// Compile tests for `#[derive(Builder)]`. `pass_*` files must compile and run their `main`;
+// `fail_*` files must fail with the error in the matching `.stderr` file.
+// Regenerate expected errors after an intentional change with `TRYBUILD=overwrite cargo test`.
+
+#[test]
+fn builder_derive() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/builder/pass_*.rs");
+    cases.compile_fail("tests/ui/builder/fail_*.rs");
+}
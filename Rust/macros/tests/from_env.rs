@@ -0,0 +1,8 @@
+// Compile tests for `#[derive(FromEnv)]`; see `tests/builder.rs` for the layout.
+
+#[test]
+fn from_env_derive() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/from_env/pass_*.rs");
+    cases.compile_fail("tests/ui/from_env/fail_*.rs");
+}
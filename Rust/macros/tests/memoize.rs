@@ -0,0 +1,9 @@
+// Compile tests for `#[memoize]`; see `tests/builder.rs` for the layout. Passing cases
+// include `snippets/cache.rs` as `mod cache`, where the macro looks for `LruCache`.
+
+#[test]
+fn memoize_attribute() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/memoize/pass_*.rs");
+    cases.compile_fail("tests/ui/memoize/fail_*.rs");
+}
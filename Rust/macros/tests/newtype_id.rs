@@ -0,0 +1,9 @@
+// Compile tests for `#[derive(NewtypeId)]`; see `tests/builder.rs` for the layout. Passing
+// cases round-trip IDs through `serde_json` and an in-memory SQLite database.
+
+#[test]
+fn newtype_id_derive() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/newtype_id/pass_*.rs");
+    cases.compile_fail("tests/ui/newtype_id/fail_*.rs");
+}
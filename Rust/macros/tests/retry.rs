@@ -0,0 +1,8 @@
+// Compile tests for `#[retry]`; see `tests/builder.rs` for the layout.
+
+#[test]
+fn retry_attribute() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/retry/pass_*.rs");
+    cases.compile_fail("tests/ui/retry/fail_*.rs");
+}
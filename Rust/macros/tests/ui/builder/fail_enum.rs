@@ -0,0 +1,9 @@
+use snippet_macros::Builder;
+
+#[derive(Builder)]
+enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+fn main() {}
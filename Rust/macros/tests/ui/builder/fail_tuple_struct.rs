@@ -0,0 +1,6 @@
+use snippet_macros::Builder;
+
+#[derive(Builder)]
+struct Meters(f64);
+
+fn main() {}
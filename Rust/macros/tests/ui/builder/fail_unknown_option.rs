@@ -0,0 +1,13 @@
+use snippet_macros::Builder;
+
+// Every mistake is reported in one compile.
+#[derive(Builder)]
+#[builder(validator = check)]
+struct Settings {
+    #[builder(defualt = 5)]
+    level: u8,
+    #[builder(default, default)]
+    verbose: bool,
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+use snippet_macros::Builder;
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Request {
+    #[builder(into)]
+    url: String,
+    method: String,
+    #[builder(default = 3)]
+    retries: u32,
+    #[builder(default)]
+    headers: Vec<(String, String)>,
+    timeout_ms: Option<u64>,
+    #[builder(default = Some("agent/1.0".to_string()))]
+    user_agent: Option<String>,
+}
+
+fn main() {
+    let request = Request::builder().url("https://example.com").method("GET".to_string()).build().unwrap();
+    assert_eq!(
+        request,
+        Request {
+            url: "https://example.com".into(),
+            method: "GET".into(),
+            retries: 3,
+            headers: Vec::new(),
+            timeout_ms: None,
+            user_agent: Some("agent/1.0".into()),
+        }
+    );
+
+    let request = Request::builder()
+        .url("https://example.com")
+        .method("POST".into())
+        .retries(0)
+        .timeout_ms(500)
+        .headers(vec![("accept".into(), "*/*".into())])
+        .build()
+        .unwrap();
+    assert_eq!((request.retries, request.timeout_ms, request.headers.len()), (0, Some(500), 1));
+
+    // All missing required fields are reported together, in declaration order.
+    let err = Request::builder().build().unwrap_err();
+    assert_eq!(err, RequestBuilderError::MissingFields(vec!["url", "method"]));
+    assert_eq!(err.to_string(), "missing required field(s) for Request: url, method");
+    let _: &dyn std::error::Error = &err;
+}
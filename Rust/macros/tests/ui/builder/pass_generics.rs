@@ -0,0 +1,26 @@
+use snippet_macros::Builder;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Builder)]
+struct Cache<'a, K, V>
+where
+    K: Eq + Hash,
+{
+    name: &'a str,
+    #[builder(default)]
+    entries: HashMap<K, V>,
+    capacity: Option<usize>,
+    r#type: char,
+}
+
+// `V` needs no `Default` bound: the builder's `Default` impl is written by hand.
+struct NoDefault;
+
+fn main() {
+    let cache: Cache<'_, String, NoDefault> = Cache::builder().name("sessions").r#type('s').build().unwrap();
+    assert_eq!((cache.name, cache.entries.len(), cache.capacity, cache.r#type), ("sessions", 0, None, 's'));
+
+    let err = Cache::<u8, u8>::builder().build().unwrap_err();
+    assert_eq!(err, CacheBuilderError::MissingFields(vec!["name", "type"]));
+}
@@ -0,0 +1,42 @@
+use snippet_macros::Builder;
+
+#[derive(Debug, Builder)]
+#[builder(validate = Range::check)]
+struct Range {
+    start: i64,
+    end: i64,
+}
+
+impl Range {
+    fn check(&self) -> Result<(), String> {
+        if self.start <= self.end {
+            Ok(())
+        } else {
+            Err(format!("start {} is after end {}", self.start, self.end))
+        }
+    }
+}
+
+mod limits {
+    // Validators can be free functions anywhere in scope, with any `Display` error.
+    pub fn at_most_ten(config: &super::Config) -> Result<(), std::fmt::Error> {
+        if config.workers <= 10 { Ok(()) } else { Err(std::fmt::Error) }
+    }
+}
+
+#[derive(Debug, Builder)]
+#[builder(validate = limits::at_most_ten)]
+struct Config {
+    #[builder(default = 4)]
+    workers: usize,
+}
+
+fn main() {
+    assert!(Range::builder().start(1).end(2).build().is_ok());
+    let err = Range::builder().start(5).end(2).build().unwrap_err();
+    assert_eq!(err, RangeBuilderError::Invalid("start 5 is after end 2".into()));
+    assert_eq!(err.to_string(), "invalid Range: start 5 is after end 2");
+
+    assert_eq!(Config::builder().build().unwrap().workers, 4);
+    assert!(matches!(Config::builder().workers(11).build(), Err(ConfigBuilderError::Invalid(_))));
+}
@@ -0,0 +1,15 @@
+use snippet_macros::FromEnv;
+
+// Every mistake is reported in one compile.
+#[derive(FromEnv)]
+#[env(prefx = "APP_")]
+struct Config {
+    #[env(rename = DATABASE_URL)]
+    database_url: String,
+    #[env(default = "x")]
+    name: Option<String>,
+    #[env(required)]
+    port: u16,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use snippet_macros::FromEnv;
+
+#[derive(FromEnv)]
+enum Mode {
+    Dev,
+    Prod,
+}
+
+fn main() {}
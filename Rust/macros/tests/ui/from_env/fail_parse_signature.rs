@@ -0,0 +1,14 @@
+use snippet_macros::FromEnv;
+
+fn percent(raw: &str) -> Option<u8> {
+    raw.strip_suffix('%')?.parse().ok()
+}
+
+// `parse` functions must return `Result<T, E>` with `E: Display`.
+#[derive(FromEnv)]
+struct Limits {
+    #[env(parse = percent)]
+    cpu: u8,
+}
+
+fn main() {}
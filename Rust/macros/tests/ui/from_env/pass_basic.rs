@@ -0,0 +1,60 @@
+use snippet_macros::FromEnv;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+fn lookup<'a>(vars: &'a HashMap<&str, &str>) -> impl Fn(&str) -> Option<String> + 'a {
+    move |key| vars.get(key).map(|value| value.to_string())
+}
+
+fn list(raw: &str) -> Result<Vec<String>, std::convert::Infallible> {
+    Ok(raw.split(',').map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect())
+}
+
+#[derive(Debug, PartialEq, FromEnv)]
+#[env(prefix = "SVC_")]
+pub struct Settings {
+    host: IpAddr,
+    #[env(default = "8080")]
+    port: u16,
+    #[env(rename = "RUST_LOG")]
+    log: Option<String>,
+    #[env(default)]
+    debug: bool,
+    #[env(parse = list, default = "")]
+    allowed_origins: Vec<String>,
+    r#type: String,
+}
+
+fn main() {
+    assert_eq!(Settings::env_vars(), ["SVC_HOST", "SVC_PORT", "RUST_LOG", "SVC_DEBUG", "SVC_ALLOWED_ORIGINS", "SVC_TYPE"]);
+
+    let vars = HashMap::from([("SVC_HOST", "127.0.0.1"), ("SVC_TYPE", "api"), ("SVC_ALLOWED_ORIGINS", "a.com, b.com")]);
+    let settings = Settings::from_env_with(lookup(&vars)).unwrap();
+    assert_eq!(
+        settings,
+        Settings {
+            host: [127, 0, 0, 1].into(),
+            port: 8080,
+            log: None,
+            debug: false,
+            allowed_origins: vec!["a.com".into(), "b.com".into()],
+            r#type: "api".into(),
+        }
+    );
+
+    // Every problem is collected: two missing, two invalid, and the values are not echoed.
+    let vars = HashMap::from([("SVC_PORT", "99999"), ("SVC_DEBUG", "yes")]);
+    let err = Settings::from_env_with(lookup(&vars)).unwrap_err();
+    assert_eq!(err.missing, ["SVC_HOST", "SVC_TYPE"]);
+    assert_eq!(err.invalid.iter().map(|(key, _)| *key).collect::<Vec<_>>(), ["SVC_PORT", "SVC_DEBUG"]);
+    let report = err.to_string();
+    assert!(report.starts_with("Settings has 4 configuration problem(s):"), "{}", report);
+    assert!(report.contains("\n  SVC_HOST: not set") && !report.contains("99999"), "{}", report);
+
+    // The real environment works the same way.
+    std::env::set_var("SVC_HOST", "::1");
+    std::env::set_var("SVC_TYPE", "worker");
+    std::env::set_var("RUST_LOG", "debug");
+    let settings = Settings::from_env().unwrap();
+    assert_eq!((settings.host.is_ipv6(), settings.log.as_deref()), (true, Some("debug")));
+}
@@ -0,0 +1,34 @@
+// Structs with no required fields (or no fields at all) expand without warnings.
+#![deny(warnings)]
+
+use snippet_macros::FromEnv;
+
+#[derive(FromEnv)]
+struct Empty {}
+
+#[derive(FromEnv)]
+#[env(prefix = "OPT_")]
+struct Tuning {
+    #[env(default = "4")]
+    workers: usize,
+    cache_mb: Option<u32>,
+}
+
+#[derive(FromEnv)]
+struct BadDefault {
+    #[env(default = "lots")]
+    retries: u8,
+}
+
+fn main() {
+    assert!(Empty::from_env_with(|_| None).is_ok());
+    assert!(Empty::env_vars().is_empty());
+
+    let tuning = Tuning::from_env_with(|_| None).unwrap();
+    assert_eq!((tuning.workers, tuning.cache_mb), (4, None));
+
+    // A bad default is reported like a bad value.
+    let err = BadDefault::from_env_with(|_| None).err().unwrap();
+    assert_eq!(err.invalid[0].0, "RETRIES");
+    assert!(err.invalid[0].1.starts_with("default \"lots\" is invalid"));
+}
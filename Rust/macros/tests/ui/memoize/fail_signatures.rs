@@ -0,0 +1,30 @@
+use snippet_macros::memoize;
+
+struct Service;
+
+impl Service {
+    #[memoize]
+    fn lookup(&self, id: u32) -> u32 {
+        id
+    }
+}
+
+#[memoize]
+fn generic<T: Clone>(value: T) -> T {
+    value
+}
+
+#[memoize]
+fn destructured((a, b): (u8, u8), name: impl ToString) -> u8 {
+    a + b
+}
+
+#[memoize]
+fn nothing(id: u32) {}
+
+#[memoize(capacity = 0, ttl = "forever")]
+fn bad_options(id: u32) -> u32 {
+    id
+}
+
+fn main() {}
@@ -0,0 +1,48 @@
+#![deny(warnings)]
+
+#[allow(dead_code, unused_imports)] // The snippet also carries items for its own example.
+#[path = "../../../../snippets/cache.rs"]
+mod cache;
+
+use snippet_macros::memoize;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static FETCHES: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Clone, PartialEq)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+#[memoize(capacity = 100, ttl = "1m")]
+async fn load_user(id: u32) -> Result<User, String> {
+    FETCHES.fetch_add(1, Ordering::SeqCst);
+    tokio::task::yield_now().await;
+    if id == 0 {
+        return Err("no user 0".to_string());
+    }
+    Ok(User { id, name: format!("user-{}", id) })
+}
+
+#[memoize]
+async fn greeting(name: &str, excited: bool) -> String {
+    FETCHES.fetch_add(1, Ordering::SeqCst);
+    tokio::task::yield_now().await;
+    format!("hello {}{}", name, if excited { "!" } else { "." })
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    assert_eq!(load_user(7).await.unwrap().name, "user-7");
+    assert_eq!(load_user(7).await.unwrap().id, 7);
+    assert!(load_user(0).await.is_err());
+    assert!(load_user(0).await.is_err());
+    assert_eq!(FETCHES.load(Ordering::SeqCst), 3);
+
+    assert_eq!(greeting("ada", true).await, "hello ada!");
+    assert_eq!(greeting("ada", true).await, "hello ada!");
+    assert_eq!(greeting("ada", false).await, "hello ada.");
+    assert_eq!(FETCHES.load(Ordering::SeqCst), 5);
+    assert!(greeting_cache().get(&("ada".to_string(), true)).is_some());
+}
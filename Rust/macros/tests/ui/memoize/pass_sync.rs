@@ -0,0 +1,82 @@
+#![deny(warnings)]
+
+#[allow(dead_code, unused_imports)] // The snippet also carries items for its own example.
+#[path = "../../../../snippets/cache.rs"]
+mod cache;
+
+use snippet_macros::memoize;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+static LOOKUPS: AtomicU32 = AtomicU32::new(0);
+
+#[memoize]
+fn fib(n: u64) -> u64 {
+    if n < 2 {
+        n
+    } else {
+        fib(n - 1) + fib(n - 2)
+    }
+}
+
+/// Borrowed arguments are stored owned; the key is `(String, Vec<u8>, u8)`.
+#[memoize(capacity = 2)]
+pub fn checksum(label: &str, data: &[u8], seed: u8) -> String {
+    LOOKUPS.fetch_add(1, Ordering::SeqCst);
+    let sum = data.iter().fold(seed as u32, |acc, b| acc.wrapping_mul(31).wrapping_add(*b as u32));
+    format!("{}:{:08x}", label, sum)
+}
+
+/// Only `Ok` results are cached.
+#[memoize(ttl = "50ms")]
+fn parse_port(raw: String) -> Result<u16, std::num::ParseIntError> {
+    LOOKUPS.fetch_add(1, Ordering::SeqCst);
+    let port = raw.trim().parse()?;
+    Ok(port)
+}
+
+#[memoize]
+fn constant() -> &'static str {
+    LOOKUPS.fetch_add(1, Ordering::SeqCst);
+    "computed once"
+}
+
+fn lookups() -> u32 {
+    LOOKUPS.load(Ordering::SeqCst)
+}
+
+fn main() {
+    assert_eq!(fib(90), 2_880_067_194_370_816_120);
+    assert_eq!(fib_cache().len(), 91);
+    assert_eq!(fib_cache().get(&10), Some(55));
+
+    let first = checksum("a", b"hello", 7);
+    assert_eq!(checksum("a", b"hello", 7), first);
+    assert_eq!(lookups(), 1);
+    checksum("b", b"x", 0);
+    checksum("c", b"y", 0); // Capacity 2: evicts ("a", "hello", 7).
+    checksum("a", b"hello", 7);
+    assert_eq!(lookups(), 4);
+    assert_eq!(checksum_cache().stats().evictions, 2);
+
+    assert!(parse_port("http".into()).is_err());
+    assert!(parse_port("http".into()).is_err());
+    assert_eq!(lookups(), 6); // Errors are recomputed every time.
+    assert_eq!(parse_port("8080".into()), Ok(8080));
+    assert_eq!(parse_port("8080".into()), Ok(8080));
+    assert_eq!(lookups(), 7);
+    std::thread::sleep(Duration::from_millis(80));
+    assert_eq!(parse_port("8080".into()), Ok(8080)); // Expired: computed again.
+    assert_eq!(lookups(), 8);
+
+    // Invalidation through the cache handle.
+    parse_port_cache().remove("8080");
+    parse_port("8080".into()).unwrap();
+    assert_eq!(lookups(), 9);
+
+    assert_eq!((constant(), constant()), ("computed once", "computed once"));
+    assert_eq!(lookups(), 10);
+    constant_cache().clear();
+    constant();
+    assert_eq!(lookups(), 11);
+}
@@ -0,0 +1,15 @@
+use snippet_macros::NewtypeId;
+
+#[derive(NewtypeId)]
+#[id(prefix = "")]
+struct Empty(u64);
+
+#[derive(NewtypeId)]
+#[id(serde, postgres)]
+struct Unknown(u64);
+
+#[derive(NewtypeId)]
+#[id(prefix = "a_", prefix = "b_")]
+struct Twice(u64);
+
+fn main() {}
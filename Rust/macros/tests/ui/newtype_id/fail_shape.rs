@@ -0,0 +1,14 @@
+use snippet_macros::NewtypeId;
+
+#[derive(NewtypeId)]
+struct Named {
+    id: u64,
+}
+
+#[derive(NewtypeId)]
+struct TwoFields(u64, u64);
+
+#[derive(NewtypeId)]
+struct Generic<T>(T);
+
+fn main() {}
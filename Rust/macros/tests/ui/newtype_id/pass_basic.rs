@@ -0,0 +1,31 @@
+#![deny(warnings)]
+
+use snippet_macros::NewtypeId;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NewtypeId)]
+pub struct UserId(u64);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, NewtypeId)]
+#[id(prefix = "sku-")]
+struct Sku(String);
+
+fn main() {
+    let id = UserId::new(42);
+    assert_eq!(id.to_string(), "42");
+    assert_eq!("42".parse::<UserId>(), Ok(id));
+    assert_eq!(u64::from(id), 42);
+    assert_eq!(UserId::from(7).into_inner(), 7);
+
+    let err = "abc".parse::<UserId>().unwrap_err();
+    assert_eq!(err.to_string(), r#"invalid UserId "abc": invalid digit found in string"#);
+    let _: &dyn std::error::Error = &err;
+
+    let sku: Sku = "sku-A1".parse().unwrap();
+    assert_eq!(sku.as_inner(), "A1");
+    assert_eq!(sku.to_string(), "sku-A1");
+    assert!("A1".parse::<Sku>().is_err());
+
+    let seen: HashSet<UserId> = [1, 2, 1].into_iter().map(UserId::new).collect();
+    assert_eq!(seen.len(), 2);
+}
@@ -0,0 +1,39 @@
+#![deny(warnings)]
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use snippet_macros::NewtypeId;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, NewtypeId)]
+#[id(prefix = "usr_", serde, rusqlite)]
+struct UserId(i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, NewtypeId)]
+#[id(serde, rusqlite)]
+struct OrderId(Uuid);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Order {
+    id: OrderId,
+    user: UserId,
+}
+
+fn main() {
+    let order = Order { id: OrderId::new(Uuid::new_v4()), user: UserId::new(42) };
+    let json = serde_json::to_value(&order).unwrap();
+    assert_eq!(json["user"], "usr_42");
+    assert_eq!(json["id"], order.id.to_string());
+    assert_eq!(serde_json::from_value::<Order>(json).unwrap(), order);
+    assert!(serde_json::from_str::<UserId>("42").is_err());
+    assert!(serde_json::from_str::<UserId>(r#""ord_42""#).is_err());
+
+    // The prefix isn't stored, so the column stays a plain INTEGER.
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("CREATE TABLE orders (id BLOB PRIMARY KEY, user_id INTEGER NOT NULL)").unwrap();
+    conn.execute("INSERT INTO orders VALUES (?1, ?2)", (order.id, order.user)).unwrap();
+    let (id, user, raw): (OrderId, UserId, i64) = conn
+        .query_row("SELECT id, user_id, user_id FROM orders", [], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap();
+    assert_eq!((id, user, raw), (order.id, order.user, 42));
+}
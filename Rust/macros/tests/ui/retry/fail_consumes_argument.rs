@@ -0,0 +1,13 @@
+use snippet_macros::retry;
+
+fn send(_body: Vec<u8>) -> Result<(), String> {
+    Ok(())
+}
+
+// The body runs once per attempt, so it can't move its arguments.
+#[retry]
+fn upload(body: Vec<u8>) -> Result<(), String> {
+    send(body)
+}
+
+fn main() {}
@@ -0,0 +1,41 @@
+use snippet_macros::retry;
+
+#[retry(max_attempts = 0)]
+fn zero() -> Result<(), ()> {
+    Ok(())
+}
+
+#[retry(initial_backoff = "2 seconds")]
+fn bad_duration() -> Result<(), ()> {
+    Ok(())
+}
+
+#[retry(max_backoff = "99999999999999999h")]
+fn overflowing_duration() -> Result<(), ()> {
+    Ok(())
+}
+
+#[retry(backoff = "linear")]
+fn bad_backoff() -> Result<(), ()> {
+    Ok(())
+}
+
+#[retry(attempts = 3)]
+fn unknown() -> Result<(), ()> {
+    Ok(())
+}
+
+#[retry(initial_backoff = "5s", max_backoff = "1s")]
+fn inverted() -> Result<(), ()> {
+    Ok(())
+}
+
+#[retry]
+fn no_result() {}
+
+#[retry(clock = CLOCK)]
+async fn async_with_clock() -> Result<(), ()> {
+    Ok(())
+}
+
+fn main() {}
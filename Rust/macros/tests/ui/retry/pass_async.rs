@@ -0,0 +1,24 @@
+#![deny(warnings)]
+
+use snippet_macros::retry;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static CALLS: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+struct Unavailable;
+
+#[retry(max_attempts = 5, initial_backoff = "1ms", max_backoff = "4ms")]
+async fn connect(host: &str, port: u16) -> Result<String, Unavailable> {
+    let attempt = CALLS.fetch_add(1, Ordering::SeqCst) + 1;
+    tokio::task::yield_now().await;
+    if attempt < 4 {
+        return Err(Unavailable);
+    }
+    Ok(format!("{}:{} on attempt {}", host, port, attempt))
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    assert_eq!(connect("db", 5432).await.unwrap(), "db:5432 on attempt 4");
+}
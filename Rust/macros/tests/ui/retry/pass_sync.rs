@@ -0,0 +1,97 @@
+#![deny(warnings)]
+
+use snippet_macros::retry;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq)]
+enum FetchError {
+    Timeout,
+    NotFound,
+}
+
+fn is_transient(error: &FetchError) -> bool {
+    *error == FetchError::Timeout
+}
+
+/// Records sleeps instead of waiting, like `MockClock` in `snippets/clock.rs`.
+#[derive(Default)]
+struct RecordingClock {
+    sleeps: RefCell<Vec<Duration>>,
+}
+
+impl RecordingClock {
+    fn sleep(&self, duration: Duration) {
+        self.sleeps.borrow_mut().push(duration);
+    }
+}
+
+struct Client {
+    calls: u32,
+    fail_first: u32,
+    error: fn() -> FetchError,
+    clock: RecordingClock,
+}
+
+impl Client {
+    fn new(fail_first: u32, error: fn() -> FetchError) -> Self {
+        Client { calls: 0, fail_first, error, clock: RecordingClock::default() }
+    }
+
+    #[retry(max_attempts = 4, initial_backoff = "5ms", retry_if = is_transient)]
+    fn fetch(&mut self, key: &str) -> Result<String, FetchError> {
+        self.calls += 1;
+        if self.calls <= self.fail_first {
+            return Err((self.error)());
+        }
+        Ok(format!("{}={}", key, self.calls))
+    }
+
+    /// Fixed backoff, jitter, and `?` in the body.
+    #[retry(max_attempts = 3, initial_backoff = "2ms", backoff = "fixed", jitter)]
+    fn parse_count(&mut self, raw: &str) -> Result<u32, std::num::ParseIntError> {
+        self.calls += 1;
+        let value: u32 = raw.parse()?;
+        Ok(value + self.calls)
+    }
+
+    #[retry(max_attempts = 4, initial_backoff = "1s", max_backoff = "3s", clock = self.clock)]
+    fn fetch_on_clock(&mut self) -> Result<u32, FetchError> {
+        self.calls += 1;
+        if self.calls <= self.fail_first {
+            return Err((self.error)());
+        }
+        Ok(self.calls)
+    }
+}
+
+fn main() {
+    // Succeeds on the third attempt after sleeping 5ms and 10ms.
+    let mut client = Client::new(2, || FetchError::Timeout);
+    let started = Instant::now();
+    assert_eq!(client.fetch("a"), Ok("a=3".to_string()));
+    assert!(started.elapsed() >= Duration::from_millis(15));
+
+    // Gives up after `max_attempts` and returns the last error.
+    let mut client = Client::new(10, || FetchError::Timeout);
+    assert_eq!(client.fetch("b"), Err(FetchError::Timeout));
+    assert_eq!(client.calls, 4);
+
+    // Non-transient errors are not retried.
+    let mut client = Client::new(10, || FetchError::NotFound);
+    assert_eq!(client.fetch("c"), Err(FetchError::NotFound));
+    assert_eq!(client.calls, 1);
+
+    let mut client = Client::new(0, || FetchError::NotFound);
+    assert!(client.parse_count("x").is_err());
+    assert_eq!(client.calls, 3);
+    assert_eq!(client.parse_count("10"), Ok(14));
+
+    // A `clock` replaces the real sleep, so the delays are checked without waiting.
+    let mut client = Client::new(3, || FetchError::Timeout);
+    let started = Instant::now();
+    assert_eq!(client.fetch_on_clock(), Ok(4));
+    assert!(started.elapsed() < Duration::from_secs(1));
+    let secs = |s: &[u64]| s.iter().map(|&s| Duration::from_secs(s)).collect::<Vec<_>>();
+    assert_eq!(*client.clock.sleeps.borrow(), secs(&[1, 2, 3]));
+}
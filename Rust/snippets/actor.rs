@@ -0,0 +1,259 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+// thiserror = "1.0"
+
+use std::future::Future;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration, Instant};
+
+/// Implemented by any type that owns state and processes messages one at a time.
+/// The actor's state is only ever touched by its own task, so no locks are needed inside.
+trait Actor: Send + 'static {
+    /// The message enum this actor understands.
+    type Msg: Send + 'static;
+
+    /// Handles a single message. Messages are processed sequentially, in arrival order.
+    fn handle(&mut self, msg: Self::Msg) -> impl Future<Output = ()> + Send;
+
+    /// Called once before the first message (and again after every supervised restart).
+    fn started(&mut self) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+}
+
+/// Errors returned by `ActorHandle`.
+#[derive(Error, Debug, PartialEq)]
+enum ActorError {
+    #[error("actor has stopped")]
+    Stopped,
+    #[error("actor dropped the request without replying")]
+    NoReply,
+    #[error("actor did not reply within {0:?}")]
+    Timeout(Duration),
+}
+
+/// A cheap, cloneable handle used to talk to an actor.
+struct ActorHandle<M> {
+    tx: mpsc::Sender<M>,
+}
+
+impl<M> Clone for ActorHandle<M> {
+    fn clone(&self) -> Self {
+        Self { tx: self.tx.clone() }
+    }
+}
+
+impl<M: Send + 'static> ActorHandle<M> {
+    /// Fire-and-forget: enqueue a message without waiting for it to be processed.
+    /// Waits only if the mailbox is full (backpressure).
+    async fn tell(&self, msg: M) -> Result<(), ActorError> {
+        self.tx.send(msg).await.map_err(|_| ActorError::Stopped)
+    }
+
+    /// Request/response: builds a message carrying a oneshot reply channel and awaits the answer.
+    ///
+    /// # Arguments
+    /// * `make_msg` - Builds the message from the reply sender, e.g. `|reply| Msg::Get { reply }`.
+    async fn ask<R, F>(&self, make_msg: F) -> Result<R, ActorError>
+    where
+        F: FnOnce(oneshot::Sender<R>) -> M,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tell(make_msg(reply_tx)).await?;
+        reply_rx.await.map_err(|_| ActorError::NoReply)
+    }
+
+    /// Like `ask`, but gives up after `timeout`.
+    async fn ask_timeout<R, F>(&self, make_msg: F, timeout: Duration) -> Result<R, ActorError>
+    where
+        F: FnOnce(oneshot::Sender<R>) -> M,
+    {
+        tokio::time::timeout(timeout, self.ask(make_msg))
+            .await
+            .map_err(|_| ActorError::Timeout(timeout))?
+    }
+
+    /// True once the actor's task has exited and its mailbox is closed.
+    fn is_stopped(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+/// Spawns an actor on the tokio runtime and returns its handle plus the task's JoinHandle.
+/// The actor stops when every handle has been dropped (the mailbox closes).
+///
+/// # Arguments
+/// * `actor` - The initial actor state.
+/// * `mailbox_size` - Bounded mailbox capacity.
+fn spawn_actor<A: Actor>(mut actor: A, mailbox_size: usize) -> (ActorHandle<A::Msg>, JoinHandle<()>) {
+    let (tx, mut rx) = mpsc::channel(mailbox_size);
+    let task = tokio::spawn(async move {
+        actor.started().await;
+        while let Some(msg) = rx.recv().await {
+            actor.handle(msg).await;
+        }
+    });
+    (ActorHandle { tx }, task)
+}
+
+/// How a supervisor reacts to a panicking actor.
+#[derive(Clone, Debug)]
+struct RestartPolicy {
+    /// Maximum restarts allowed within `window` before giving up.
+    max_restarts: u32,
+    /// Sliding window for counting restarts.
+    window: Duration,
+    /// Delay before each restart.
+    backoff: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 3,
+            window: Duration::from_secs(60),
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Spawns an actor under a supervisor that recreates it (via `factory`) if it panics.
+///
+/// The mailbox survives restarts, so handles stay valid and queued messages are kept.
+/// Only the message being processed during the panic is lost (an `ask` for it
+/// returns `ActorError::NoReply`).
+///
+/// # Returns
+/// * The actor handle, and a JoinHandle resolving to the number of restarts performed
+///   once the actor stops for good (clean shutdown or restart budget exhausted).
+fn spawn_supervised<A, F>(
+    factory: F,
+    mailbox_size: usize,
+    policy: RestartPolicy,
+) -> (ActorHandle<A::Msg>, JoinHandle<u32>)
+where
+    A: Actor,
+    F: Fn() -> A + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(mailbox_size);
+    // Shared so the receiver outlives a panicking run. tokio's Mutex is not poisoned by panics.
+    let rx = Arc::new(Mutex::new(rx));
+
+    let supervisor = tokio::spawn(async move {
+        let mut restarts: Vec<Instant> = Vec::new();
+        loop {
+            let mut actor = factory();
+            let rx = Arc::clone(&rx);
+            let run = tokio::spawn(async move {
+                actor.started().await;
+                let mut rx = rx.lock().await;
+                while let Some(msg) = rx.recv().await {
+                    actor.handle(msg).await;
+                }
+            });
+
+            match run.await {
+                // Mailbox closed: all handles dropped, normal shutdown.
+                Ok(()) => return restarts.len() as u32,
+                Err(e) if e.is_panic() => {
+                    let now = Instant::now();
+                    restarts.retain(|t| now.duration_since(*t) < policy.window);
+                    if restarts.len() as u32 >= policy.max_restarts {
+                        eprintln!("Supervisor: restart budget exhausted, stopping actor");
+                        return restarts.len() as u32;
+                    }
+                    restarts.push(now);
+                    eprintln!("Supervisor: actor panicked, restarting (#{})", restarts.len());
+                    sleep(policy.backoff).await;
+                }
+                // Cancelled (runtime shutting down).
+                Err(_) => return restarts.len() as u32,
+            }
+        }
+    });
+
+    (ActorHandle { tx }, supervisor)
+}
+
+// --- Example actor ---
+
+/// Messages understood by `CounterActor`.
+enum CounterMsg {
+    /// tell: add to the counter.
+    Add(u64),
+    /// ask: read the current value.
+    Get { reply: oneshot::Sender<u64> },
+    /// ask: reset and return the previous value.
+    Reset { reply: oneshot::Sender<u64> },
+    /// Simulates a bug so the supervisor can be demonstrated.
+    Crash,
+}
+
+struct CounterActor {
+    value: u64,
+}
+
+impl Actor for CounterActor {
+    type Msg = CounterMsg;
+
+    async fn started(&mut self) {
+        println!("CounterActor started with value {}", self.value);
+    }
+
+    async fn handle(&mut self, msg: CounterMsg) {
+        match msg {
+            CounterMsg::Add(n) => self.value += n,
+            CounterMsg::Get { reply } => {
+                // The asker may have given up; ignore send errors.
+                let _ = reply.send(self.value);
+            }
+            CounterMsg::Reset { reply } => {
+                let _ = reply.send(std::mem::take(&mut self.value));
+            }
+            CounterMsg::Crash => panic!("counter actor crashed on purpose"),
+        }
+    }
+}
+
+// Example Usage (requires a Tokio runtime)
+/*
+#[tokio::main]
+async fn main() -> Result<(), ActorError> {
+    println!("--- Plain actor: tell / ask ---");
+    let (counter, task) = spawn_actor(CounterActor { value: 0 }, 32);
+    for i in 1..=5 {
+        counter.tell(CounterMsg::Add(i)).await?;
+    }
+    let value = counter.ask(|reply| CounterMsg::Get { reply }).await?;
+    println!("Counter value: {}", value); // 15
+
+    let previous = counter
+        .ask_timeout(|reply| CounterMsg::Reset { reply }, Duration::from_secs(1))
+        .await?;
+    println!("Reset; previous value was {}", previous);
+
+    drop(counter); // Last handle dropped -> mailbox closes -> actor stops
+    task.await.unwrap();
+
+    println!("\n--- Supervised actor: restart after panic ---");
+    let (counter, supervisor) = spawn_supervised(
+        || CounterActor { value: 100 }, // State is rebuilt from scratch on restart
+        32,
+        RestartPolicy::default(),
+    );
+    counter.tell(CounterMsg::Add(1)).await?;
+    counter.tell(CounterMsg::Crash).await?;
+    // Queued messages survive the restart; this one is handled by the new instance.
+    let value = counter.ask(|reply| CounterMsg::Get { reply }).await?;
+    println!("Value after restart: {}", value); // 100
+
+    drop(counter);
+    let restarts = supervisor.await.unwrap();
+    println!("Supervisor exited after {} restart(s)", restarts);
+    Ok(())
+}
+*/
@@ -0,0 +1,287 @@
+// This example only uses the standard library.
+//
+// A generational slot map: store values in a `Vec`, hand out small `Copy` keys instead of
+// references, and detect keys that outlive their value. This is the usual way to build
+// trees, graphs, scene graphs, ECS worlds, and interpreter heaps in Rust without
+// `Rc<RefCell<_>>`: nodes refer to each other by key, the map owns every node, and there are
+// no reference cycles to leak or borrow panics at runtime.
+//
+// Each slot carries a generation counter that is bumped when its value is removed. A key
+// remembers the generation it was issued with, so after the slot is reused for a new value,
+// the old key gets `None` instead of silently reading the new value (the "ABA" problem that
+// plain `Vec` indices have).
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A handle to a value in a `SlotMap`. 8 bytes, `Copy`, hashable, and cheap to store in
+/// other structures. Only meaningful for the map that issued it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Key {
+    index: u32,
+    generation: u32,
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Entry<T> {
+    Occupied(T),
+    /// Next slot in the free list.
+    Free { next_free: Option<u32> },
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    generation: u32,
+    entry: Entry<T>,
+}
+
+/// Values addressed by generational `Key`s. Insert, remove, and lookup are O(1); removed
+/// slots are reused, so memory tracks the peak number of live values.
+#[derive(Debug, Clone)]
+struct SlotMap<T> {
+    slots: Vec<Slot<T>>,
+    free_head: Option<u32>,
+    len: usize,
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self { slots: Vec::new(), free_head: None, len: 0 }
+    }
+}
+
+impl<T> SlotMap<T> {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self { slots: Vec::with_capacity(capacity), ..Self::default() }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Stores `value` and returns its key.
+    fn insert(&mut self, value: T) -> Key {
+        self.insert_with_key(|_| value)
+    }
+
+    /// Stores the value built by `make`, which receives the value's own key, for nodes that
+    /// need to know their id (e.g. to register themselves with a parent).
+    ///
+    /// # Returns
+    ///
+    /// The new key. Panics if more than `u32::MAX` slots are needed.
+    fn insert_with_key(&mut self, make: impl FnOnce(Key) -> T) -> Key {
+        if let Some(index) = self.free_head {
+            let slot = &mut self.slots[index as usize];
+            let Entry::Free { next_free } = slot.entry else {
+                unreachable!("free list points at an occupied slot");
+            };
+            let key = Key { index, generation: slot.generation };
+            slot.entry = Entry::Occupied(make(key));
+            self.free_head = next_free;
+            self.len += 1;
+            return key;
+        }
+        let index = u32::try_from(self.slots.len()).expect("SlotMap is limited to u32::MAX slots");
+        let key = Key { index, generation: 0 };
+        self.slots.push(Slot { generation: 0, entry: Entry::Occupied(make(key)) });
+        self.len += 1;
+        key
+    }
+
+    fn slot(&self, key: Key) -> Option<&Slot<T>> {
+        self.slots.get(key.index as usize).filter(|s| s.generation == key.generation)
+    }
+
+    /// The value for `key`, or `None` if it was removed (even if the slot now holds another value).
+    fn get(&self, key: Key) -> Option<&T> {
+        match &self.slot(key)?.entry {
+            Entry::Occupied(value) => Some(value),
+            Entry::Free { .. } => None,
+        }
+    }
+
+    fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        let slot = self.slots.get_mut(key.index as usize).filter(|s| s.generation == key.generation)?;
+        match &mut slot.entry {
+            Entry::Occupied(value) => Some(value),
+            Entry::Free { .. } => None,
+        }
+    }
+
+    /// Mutable access to two different values at once, e.g. to link two graph nodes.
+    /// `None` if either key is stale or both keys are the same.
+    fn get2_mut(&mut self, a: Key, b: Key) -> Option<(&mut T, &mut T)> {
+        if a.index == b.index {
+            return None;
+        }
+        self.get(a)?;
+        self.get(b)?;
+        let (low, high) = (a.index.min(b.index) as usize, a.index.max(b.index) as usize);
+        let (left, right) = self.slots.split_at_mut(high);
+        let (low_slot, high_slot) = (&mut left[low], &mut right[0]);
+        let (Entry::Occupied(low_value), Entry::Occupied(high_value)) = (&mut low_slot.entry, &mut high_slot.entry) else {
+            unreachable!("both keys were just checked");
+        };
+        Some(if a.index < b.index { (low_value, high_value) } else { (high_value, low_value) })
+    }
+
+    fn contains_key(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes and returns the value; `key` and any copies of it become stale.
+    fn remove(&mut self, key: Key) -> Option<T> {
+        self.get(key)?;
+        let slot = &mut self.slots[key.index as usize];
+        let Entry::Occupied(value) = std::mem::replace(&mut slot.entry, Entry::Free { next_free: None }) else {
+            unreachable!("key was just checked");
+        };
+        self.len -= 1;
+        // A slot whose generation would wrap is retired rather than reused, so an old key
+        // can never match again. That takes 4 billion reuses of one slot.
+        if let Some(next_generation) = slot.generation.checked_add(1) {
+            slot.generation = next_generation;
+            slot.entry = Entry::Free { next_free: self.free_head };
+            self.free_head = Some(key.index);
+        }
+        Some(value)
+    }
+
+    /// Keeps only the values for which `keep` returns `true`.
+    fn retain(&mut self, mut keep: impl FnMut(Key, &mut T) -> bool) {
+        for index in 0..self.slots.len() {
+            let slot = &mut self.slots[index];
+            let key = Key { index: index as u32, generation: slot.generation };
+            if let Entry::Occupied(value) = &mut slot.entry {
+                if !keep(key, value) {
+                    self.remove(key);
+                }
+            }
+        }
+    }
+
+    /// Removes every value. Existing keys become stale; slots are kept for reuse.
+    fn clear(&mut self) {
+        self.retain(|_, _| false);
+    }
+
+    /// Live `(key, value)` pairs in slot order (not insertion order once slots are reused).
+    fn iter(&self) -> impl Iterator<Item = (Key, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| match &slot.entry {
+            Entry::Occupied(value) => Some((Key { index: index as u32, generation: slot.generation }, value)),
+            Entry::Free { .. } => None,
+        })
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = (Key, &mut T)> {
+        self.slots.iter_mut().enumerate().filter_map(|(index, slot)| match &mut slot.entry {
+            Entry::Occupied(value) => Some((Key { index: index as u32, generation: slot.generation }, value)),
+            Entry::Free { .. } => None,
+        })
+    }
+
+    fn keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+/// `map[key]` for keys known to be live; panics on a stale key, like indexing a `Vec`.
+impl<T> Index<Key> for SlotMap<T> {
+    type Output = T;
+
+    fn index(&self, key: Key) -> &T {
+        self.get(key).unwrap_or_else(|| panic!("stale or foreign SlotMap key {}", key))
+    }
+}
+
+impl<T> IndexMut<Key> for SlotMap<T> {
+    fn index_mut(&mut self, key: Key) -> &mut T {
+        self.get_mut(key).unwrap_or_else(|| panic!("stale or foreign SlotMap key {}", key))
+    }
+}
+
+// Example Usage
+/*
+/// A scene-graph node: parent and children are keys, not references.
+#[derive(Debug)]
+struct Node {
+    name: String,
+    parent: Option<Key>,
+    children: Vec<Key>,
+}
+
+fn add_child(nodes: &mut SlotMap<Node>, parent: Key, name: &str) -> Key {
+    let child = nodes.insert(Node { name: name.to_string(), parent: Some(parent), children: Vec::new() });
+    nodes[parent].children.push(child);
+    child
+}
+
+/// Removes `key` and everything below it, unlinking it from its parent.
+fn remove_subtree(nodes: &mut SlotMap<Node>, key: Key) -> usize {
+    if let Some(parent) = nodes.get(key).and_then(|n| n.parent) {
+        nodes[parent].children.retain(|&c| c != key);
+    }
+    let mut stack = vec![key];
+    let mut removed = 0;
+    while let Some(key) = stack.pop() {
+        if let Some(node) = nodes.remove(key) {
+            stack.extend(node.children);
+            removed += 1;
+        }
+    }
+    removed
+}
+
+fn print_tree(nodes: &SlotMap<Node>, key: Key, depth: usize) {
+    println!("{}{} ({})", "  ".repeat(depth), nodes[key].name, key);
+    for &child in &nodes[key].children {
+        print_tree(nodes, child, depth + 1);
+    }
+}
+
+fn main() {
+    let mut nodes = SlotMap::new();
+    let root = nodes.insert(Node { name: "world".into(), parent: None, children: Vec::new() });
+    let player = add_child(&mut nodes, root, "player");
+    let sword = add_child(&mut nodes, player, "sword");
+    add_child(&mut nodes, player, "shield");
+    let enemy = add_child(&mut nodes, root, "enemy");
+    print_tree(&nodes, root, 0);
+
+    println!("removed {} nodes", remove_subtree(&mut nodes, player));
+    // The freed slots are reused, but old keys stay dead.
+    let chest = add_child(&mut nodes, root, "chest");
+    println!("sword key {} -> {:?}; chest reused slot as {}", sword, nodes.get(sword).map(|n| &n.name), chest);
+    print_tree(&nodes, root, 0);
+
+    // Two mutable borrows at once, checked at runtime instead of with RefCell:
+    // move the chest from the world to the enemy.
+    if let Some((world, enemy_node)) = nodes.get2_mut(root, enemy) {
+        world.children.retain(|&c| c != chest);
+        enemy_node.children.push(chest);
+    }
+    nodes[chest].parent = Some(enemy);
+    print_tree(&nodes, root, 0);
+    let names: Vec<&str> = nodes.values().map(|n| n.name.as_str()).collect();
+    println!("{} live nodes: {:?}", nodes.len(), names);
+}
+*/
@@ -0,0 +1,181 @@
+// Note: This example requires adding the `argon2`, `subtle`, and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// argon2 = { version = "0.5", features = ["std"] }
+// subtle = "2.5"
+// thiserror = "1.0"
+//
+// Hashes are stored as PHC strings (`$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`), which
+// carry the algorithm, cost parameters, and salt, so old hashes keep verifying after you
+// raise the costs. Hashing is deliberately slow (tens of milliseconds): in async code call
+// these functions from `tokio::task::spawn_blocking`.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use std::sync::OnceLock;
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum HashError {
+    #[error("Invalid Argon2 parameters: {0}")]
+    InvalidParams(argon2::Error),
+    #[error("Password hashing failed: {0}")]
+    Hash(argon2::password_hash::Error),
+    #[error("Stored hash is malformed: {0}")]
+    MalformedHash(argon2::password_hash::Error),
+}
+
+type Result<T> = std::result::Result<T, HashError>;
+
+/// Argon2id cost parameters.
+///
+/// The defaults follow the OWASP recommendation (19 MiB, 2 iterations, 1 lane). Raise
+/// `memory_kib` first if your servers can afford it; aim for 50-500 ms per hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HashConfig {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl Default for HashConfig {
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl HashConfig {
+    fn argon2(&self) -> Result<Argon2<'static>> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, None).map_err(HashError::InvalidParams)?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    /// Whether a stored hash was produced with exactly these settings (Argon2id, v19, same costs).
+    fn matches(&self, hash: &PasswordHash<'_>) -> bool {
+        let same_algorithm = Algorithm::try_from(hash.algorithm).ok() == Some(Algorithm::Argon2id)
+            && hash.version == Some(Version::V0x13.into());
+        match Params::try_from(hash) {
+            Ok(params) => {
+                same_algorithm
+                    && params.m_cost() == self.memory_kib
+                    && params.t_cost() == self.iterations
+                    && params.p_cost() == self.parallelism
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Result of checking a password against a stored hash.
+#[derive(Debug, PartialEq, Eq)]
+enum Verification {
+    /// Wrong password.
+    Invalid,
+    /// Correct password, hash is up to date.
+    Valid,
+    /// Correct password, but the stored hash used older parameters; persist the new hash.
+    ValidRehashed(String),
+}
+
+impl Verification {
+    fn is_valid(&self) -> bool {
+        !matches!(self, Verification::Invalid)
+    }
+}
+
+/// Hashes a password with Argon2id and a fresh random salt.
+///
+/// # Arguments
+///
+/// * `password` - The plaintext password.
+/// * `config` - Cost parameters.
+///
+/// # Returns
+///
+/// A PHC-format string to store in the user record.
+fn hash_password(password: &str, config: &HashConfig) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = config.argon2()?.hash_password(password.as_bytes(), &salt).map_err(HashError::Hash)?;
+    Ok(hash.to_string())
+}
+
+/// Verifies a password against a stored PHC hash and rehashes it if the parameters changed.
+///
+/// The hash comparison inside `argon2` is constant-time. Verification uses the parameters
+/// recorded in the stored hash, so hashes created with older settings still verify; when
+/// they do, a new hash with `config` is returned in `Verification::ValidRehashed` so the
+/// caller can upgrade the record transparently at login.
+///
+/// # Returns
+///
+/// `Err` only for malformed hashes or internal failures, never for a wrong password.
+fn verify_password(password: &str, stored_hash: &str, config: &HashConfig) -> Result<Verification> {
+    let parsed = PasswordHash::new(stored_hash).map_err(HashError::MalformedHash)?;
+    match Argon2::default().verify_password(password.as_bytes(), &parsed) {
+        Ok(()) => {}
+        Err(argon2::password_hash::Error::Password) => return Ok(Verification::Invalid),
+        Err(e) => return Err(HashError::Hash(e)),
+    }
+    if config.matches(&parsed) {
+        Ok(Verification::Valid)
+    } else {
+        Ok(Verification::ValidRehashed(hash_password(password, config)?))
+    }
+}
+
+/// Burns the same time as a real verification when the user does not exist.
+///
+/// Returning early for unknown users lets attackers enumerate accounts by timing the
+/// login endpoint; call this instead and return the same "invalid credentials" error.
+fn verify_dummy(password: &str, config: &HashConfig) {
+    // Computed once; assumes the process uses a single `HashConfig`.
+    static DUMMY_HASH: OnceLock<Option<String>> = OnceLock::new();
+    let dummy = DUMMY_HASH.get_or_init(|| hash_password("dummy-password-for-timing", config).ok());
+    if let Some(dummy) = dummy {
+        let _ = verify_password(password, dummy, config);
+    }
+}
+
+/// Constant-time equality for secrets that are compared directly (API keys, reset tokens,
+/// CSRF tokens). `==` on strings returns at the first differing byte and leaks how much of
+/// a guess was right. The length is not hidden, which is fine for fixed-length tokens.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    let config = HashConfig::default();
+
+    let started = std::time::Instant::now();
+    let stored = hash_password("correct horse battery staple", &config)?;
+    println!("Stored hash: {}", stored);
+    println!("Hashing took {:?}", started.elapsed());
+
+    println!("Right password: {:?}", verify_password("correct horse battery staple", &stored, &config)?);
+    println!("Wrong password: {:?}", verify_password("Tr0ub4dor&3", &stored, &config)?);
+
+    // Raise the cost later: old hashes still verify and get upgraded at the next login.
+    let stronger = HashConfig { memory_kib: 64 * 1024, iterations: 3, ..config };
+    match verify_password("correct horse battery staple", &stored, &stronger)? {
+        Verification::ValidRehashed(new_hash) => println!("Upgraded hash: {}", new_hash),
+        other => println!("Unexpected: {:?}", other),
+    }
+
+    // Unknown user: spend the same time, return the same error.
+    verify_dummy("warm-up", &config); // The first call computes the dummy hash
+    let started = std::time::Instant::now();
+    verify_dummy("guess", &config);
+    println!("Dummy verification took {:?}", started.elapsed());
+
+    println!("Token compare: {}", constant_time_eq(b"f3a9c1d2", b"f3a9c1d2"));
+    println!("Malformed hash: {}", verify_password("x", "not-a-hash", &config).unwrap_err());
+    Ok(())
+}
+*/
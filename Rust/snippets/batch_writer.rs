@@ -0,0 +1,480 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// reqwest = { version = "0.12", features = ["blocking", "json"] } // Only for `HttpBulkSink`
+//
+// `BatchWriter<T>` buffers items from any number of threads and hands them to a sink in batches,
+// for sinks where one call per item is too slow: a file, a database insert, an HTTP bulk
+// endpoint (Elasticsearch `_bulk`, a metrics or log ingestion API).
+// * A batch is flushed when it reaches `max_items`, or when its oldest item is `max_age` old,
+//   so a trickle of items still goes out promptly.
+// * `write` blocks once `max_buffered` items are waiting, which slows producers down when the
+//   sink can't keep up instead of growing memory without bound.
+// * A failed batch is retried with backoff. If it still fails, the `FailurePolicy` decides: drop
+//   it (counted in the stats), or spill it to a JSONL file that `replay_spilled` sends later.
+// * `flush` waits until everything written so far is handled; `shutdown` flushes and stops the
+//   background thread. Dropping the writer does the same, so no items are lost on a normal exit.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// --- Sinks ---
+
+/// Receives whole batches. An `Err` means none of the batch should be considered written;
+/// the batch may then be retried, so sinks should tolerate seeing items twice.
+trait BatchSink<T>: Send {
+    fn write_batch(&mut self, batch: &[T]) -> Result<(), String>;
+}
+
+impl<T, F> BatchSink<T> for F
+where
+    F: FnMut(&[T]) -> Result<(), String> + Send,
+{
+    fn write_batch(&mut self, batch: &[T]) -> Result<(), String> {
+        self(batch)
+    }
+}
+
+/// Appends each batch to a file as JSON lines, flushing after every batch.
+struct JsonlFileSink {
+    writer: BufWriter<File>,
+}
+
+impl JsonlFileSink {
+    fn append(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { writer: BufWriter::new(file) })
+    }
+}
+
+impl<T: Serialize> BatchSink<T> for JsonlFileSink {
+    fn write_batch(&mut self, batch: &[T]) -> Result<(), String> {
+        for item in batch {
+            serde_json::to_writer(&mut self.writer, item).map_err(|e| e.to_string())?;
+            self.writer.write_all(b"\n").map_err(|e| e.to_string())?;
+        }
+        self.writer.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// POSTs each batch as a JSON array; any non-2xx status fails the batch.
+struct HttpBulkSink {
+    client: reqwest::blocking::Client,
+    url: String,
+}
+
+impl HttpBulkSink {
+    fn new(url: &str) -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("HTTP client builds");
+        Self { client, url: url.to_string() }
+    }
+}
+
+impl<T: Serialize> BatchSink<T> for HttpBulkSink {
+    fn write_batch(&mut self, batch: &[T]) -> Result<(), String> {
+        let response = self.client.post(&self.url).json(batch).send().map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("{} returned {}", self.url, response.status()))
+        }
+    }
+}
+
+// --- Configuration ---
+
+/// What to do with a batch that failed every retry.
+#[derive(Debug, Clone)]
+enum FailurePolicy {
+    /// Discard it; `BatchStats::dropped` counts the items.
+    Drop,
+    /// Write it to a new `spill-*.jsonl` file in this directory, for `replay_spilled`.
+    Spill(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+struct BatchConfig {
+    /// Flush when this many items are buffered.
+    max_items: usize,
+    /// Flush when the oldest buffered item is this old.
+    max_age: Duration,
+    /// How many items `write` may queue ahead of the flusher before it blocks.
+    max_buffered: usize,
+    /// Attempts per batch, including the first.
+    max_attempts: u32,
+    /// The wait before the first retry, doubled for each one after.
+    retry_backoff: Duration,
+    on_failure: FailurePolicy,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_items: 500,
+            max_age: Duration::from_secs(1),
+            max_buffered: 10_000,
+            max_attempts: 3,
+            retry_backoff: Duration::from_millis(200),
+            on_failure: FailurePolicy::Drop,
+        }
+    }
+}
+
+/// Running totals, for metrics and for tests.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct BatchStats {
+    /// Items the sink accepted.
+    written: u64,
+    /// Successful `write_batch` calls.
+    batches: u64,
+    /// Failed attempts that were retried.
+    retries: u64,
+    /// Items from batches that failed every attempt and were dropped.
+    dropped: u64,
+    /// Items from batches that failed every attempt and went to a spill file.
+    spilled: u64,
+}
+
+// --- Writer ---
+
+enum Message<T> {
+    Item(T, Instant),
+    /// Flush now, then acknowledge.
+    Flush(SyncSender<()>),
+}
+
+/// Buffers items and flushes them to a sink on a background thread. Share it between threads
+/// with an `Arc`.
+struct BatchWriter<T> {
+    sender: Mutex<Option<SyncSender<Message<T>>>>,
+    flusher: Mutex<Option<JoinHandle<()>>>,
+    stats: Arc<Mutex<BatchStats>>,
+}
+
+impl<T: Serialize + Send + 'static> BatchWriter<T> {
+    /// Starts the flusher thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - Where batches go. It runs on the flusher thread only, so it needn't be `Sync`.
+    /// * `config` - Thresholds and the failure policy; `BatchConfig::default()` flushes every
+    ///   500 items or every second.
+    fn new(sink: impl BatchSink<T> + 'static, config: BatchConfig) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(config.max_buffered.max(1));
+        let stats = Arc::new(Mutex::new(BatchStats::default()));
+        let flusher = {
+            let stats = Arc::clone(&stats);
+            thread::Builder::new()
+                .name("batch-writer".to_string())
+                .spawn(move || Flusher { sink, config, stats, spill_seq: 0 }.run(receiver))
+                .expect("spawn batch writer thread")
+        };
+        Self { sender: Mutex::new(Some(sender)), flusher: Mutex::new(Some(flusher)), stats }
+    }
+
+    fn sender(&self) -> Option<SyncSender<Message<T>>> {
+        self.sender.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Queues one item, blocking while `max_buffered` items are already waiting.
+    ///
+    /// # Returns
+    ///
+    /// The item back, as `Err`, if the writer has been shut down.
+    fn write(&self, item: T) -> Result<(), T> {
+        let Some(sender) = self.sender() else { return Err(item) };
+        sender.send(Message::Item(item, Instant::now())).map_err(|mpsc::SendError(message)| match message {
+            Message::Item(item, _) => item,
+            Message::Flush(_) => unreachable!("only items are sent here"),
+        })
+    }
+
+    /// Flushes everything written before this call and waits until the sink (or the failure
+    /// policy) has handled it.
+    fn flush(&self) {
+        let Some(sender) = self.sender() else { return };
+        let (ack, done) = mpsc::sync_channel(1);
+        if sender.send(Message::Flush(ack)).is_ok() {
+            let _ = done.recv();
+        }
+    }
+
+    fn stats(&self) -> BatchStats {
+        *self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Flushes what's buffered, stops the flusher thread, and returns the final stats. Later
+    /// `write` calls fail.
+    fn shutdown(&self) -> BatchStats {
+        // Dropping the last sender ends the flusher's receive loop after it drains the queue.
+        self.sender.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(flusher) = self.flusher.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            let _ = flusher.join();
+        }
+        self.stats()
+    }
+}
+
+impl<T> Drop for BatchWriter<T> {
+    fn drop(&mut self) {
+        self.sender.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(flusher) = self.flusher.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            let _ = flusher.join();
+        }
+    }
+}
+
+struct Flusher<S> {
+    sink: S,
+    config: BatchConfig,
+    stats: Arc<Mutex<BatchStats>>,
+    spill_seq: u64,
+}
+
+impl<S> Flusher<S> {
+    fn run<T: Serialize>(mut self, receiver: Receiver<Message<T>>)
+    where
+        S: BatchSink<T>,
+    {
+        let mut buffer: Vec<T> = Vec::with_capacity(self.config.max_items);
+        let mut oldest: Option<Instant> = None;
+        loop {
+            let message = match oldest {
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                Some(first) => receiver.recv_timeout((first + self.config.max_age).saturating_duration_since(Instant::now())),
+            };
+            match message {
+                Ok(Message::Item(item, written_at)) => {
+                    oldest.get_or_insert(written_at);
+                    buffer.push(item);
+                    if buffer.len() >= self.config.max_items {
+                        self.flush(&mut buffer);
+                        oldest = None;
+                    }
+                }
+                Ok(Message::Flush(ack)) => {
+                    self.flush(&mut buffer);
+                    oldest = None;
+                    let _ = ack.send(());
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    self.flush(&mut buffer);
+                    oldest = None;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.flush(&mut buffer);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn flush<T: Serialize>(&mut self, buffer: &mut Vec<T>)
+    where
+        S: BatchSink<T>,
+    {
+        if buffer.is_empty() {
+            return;
+        }
+        let mut backoff = self.config.retry_backoff;
+        for attempt in 1..=self.config.max_attempts.max(1) {
+            match self.sink.write_batch(buffer) {
+                Ok(()) => {
+                    let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+                    stats.written += buffer.len() as u64;
+                    stats.batches += 1;
+                    buffer.clear();
+                    return;
+                }
+                Err(e) if attempt < self.config.max_attempts => {
+                    eprintln!("batch of {} failed (attempt {}): {}; retrying in {:?}", buffer.len(), attempt, e, backoff);
+                    self.stats.lock().unwrap_or_else(|e| e.into_inner()).retries += 1;
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                Err(e) => eprintln!("batch of {} failed for good: {}", buffer.len(), e),
+            }
+        }
+        let count = buffer.len() as u64;
+        let spilled = match self.config.on_failure.clone() {
+            FailurePolicy::Drop => false,
+            FailurePolicy::Spill(dir) => match self.spill(&dir, buffer) {
+                Ok(path) => {
+                    eprintln!("spilled {} items to {}", count, path.display());
+                    true
+                }
+                Err(e) => {
+                    eprintln!("could not spill {} items to {}: {}", count, dir.display(), e);
+                    false
+                }
+            },
+        };
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        if spilled {
+            stats.spilled += count;
+        } else {
+            stats.dropped += count;
+        }
+        buffer.clear();
+    }
+
+    /// Writes the batch to a temporary file and renames it, so `replay_spilled` never sees a
+    /// half-written spill.
+    fn spill<T: Serialize>(&mut self, dir: &Path, batch: &[T]) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        self.spill_seq += 1;
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+        let name = format!("spill-{}-{}-{}.jsonl", millis, std::process::id(), self.spill_seq);
+        let tmp = dir.join(format!(".{}.tmp", name));
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        for item in batch {
+            serde_json::to_writer(&mut writer, item)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        let path = dir.join(name);
+        fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+}
+
+/// Sends every spill file in `dir` to `sink`, oldest first, deleting each once it is written.
+/// Run it at startup or from a timer once the sink is healthy again.
+///
+/// # Returns
+///
+/// How many items were replayed. Stops at the first failing file, leaving it and any later
+/// ones in place.
+fn replay_spilled<T: DeserializeOwned>(dir: &Path, sink: &mut impl BatchSink<T>) -> io::Result<usize> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                name.starts_with("spill-") && name.ends_with(".jsonl")
+            })
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    // Names start with a millisecond timestamp, but not zero-padded; sort by it numerically.
+    files.sort_by_key(|path| {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+        let millis: u128 = name.split('-').nth(1).and_then(|m| m.parse().ok()).unwrap_or(0);
+        (millis, name)
+    });
+
+    let mut replayed = 0;
+    for path in files {
+        let items = BufReader::new(File::open(&path)?)
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+            .map(|line| serde_json::from_str(&line?).map_err(io::Error::from))
+            .collect::<io::Result<Vec<T>>>()?;
+        sink.write_batch(&items).map_err(|e| io::Error::other(format!("{}: {}", path.display(), e)))?;
+        fs::remove_file(&path)?;
+        replayed += items.len();
+    }
+    Ok(replayed)
+}
+
+// Example Usage
+/*
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Event {
+    id: u32,
+    kind: String,
+}
+
+fn main() -> io::Result<()> {
+    let dir = std::env::temp_dir().join(format!("batch-writer-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+
+    // Size- and age-based flushing into a JSONL file, from several producer threads.
+    let config = BatchConfig { max_items: 100, max_age: Duration::from_millis(50), ..Default::default() };
+    let writer = Arc::new(BatchWriter::new(JsonlFileSink::append(dir.join("events.jsonl"))?, config));
+    let producers: Vec<_> = (0..4)
+        .map(|t| {
+            let writer = Arc::clone(&writer);
+            thread::spawn(move || {
+                for i in 0..250 {
+                    writer.write(Event { id: t * 1000 + i, kind: "click".into() }).unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    writer.write(Event { id: 9999, kind: "straggler".into() }).unwrap();
+    thread::sleep(Duration::from_millis(120)); // The straggler goes out on its own after max_age.
+    println!("file sink: {:?}", writer.stats());
+    println!("shutdown: {:?}", writer.shutdown());
+    let lines = fs::read_to_string(dir.join("events.jsonl"))?.lines().count();
+    println!("events.jsonl has {} lines; write after shutdown: {:?}", lines, writer.write(Event { id: 0, kind: "late".into() }).is_err());
+
+    // A flaky bulk endpoint: one transient failure is retried; an outage spills to disk.
+    let endpoint_down = Arc::new(AtomicBool::new(false));
+    let mut failures_left = 1;
+    let received = Arc::new(Mutex::new(Vec::<u32>::new()));
+    let sink = {
+        let (endpoint_down, received) = (Arc::clone(&endpoint_down), Arc::clone(&received));
+        move |batch: &[Event]| -> Result<(), String> {
+            if endpoint_down.load(Ordering::SeqCst) {
+                return Err("503 Service Unavailable".to_string());
+            }
+            if failures_left > 0 {
+                failures_left -= 1;
+                return Err("connection reset".to_string());
+            }
+            received.lock().unwrap().extend(batch.iter().map(|e| e.id));
+            Ok(())
+        }
+    };
+    let spill_dir = dir.join("spill");
+    let config = BatchConfig {
+        max_items: 10,
+        max_attempts: 2,
+        retry_backoff: Duration::from_millis(10),
+        on_failure: FailurePolicy::Spill(spill_dir.clone()),
+        ..Default::default()
+    };
+    let writer = BatchWriter::new(sink, config);
+    for id in 0..10 {
+        writer.write(Event { id, kind: "order".into() }).unwrap();
+    }
+    writer.flush();
+    endpoint_down.store(true, Ordering::SeqCst);
+    for id in 10..25 {
+        writer.write(Event { id, kind: "order".into() }).unwrap();
+    }
+    let stats = writer.shutdown(); // Flushes the last partial batch, which spills too.
+    println!("bulk sink: {:?}", stats);
+
+    // Later, the endpoint is back: replay the spill files.
+    let mut replayed = Vec::new();
+    let mut recovery = |batch: &[Event]| -> Result<(), String> {
+        replayed.extend(batch.iter().map(|e| e.id));
+        Ok(())
+    };
+    println!("replayed {} items", replay_spilled(&spill_dir, &mut recovery)?);
+    println!("delivered live: {:?}\nreplayed: {:?}", received.lock().unwrap(), replayed);
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+*/
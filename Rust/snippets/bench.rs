@@ -0,0 +1,363 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+//
+// A small benchmark harness for comparing two implementations without pulling in criterion.
+// It goes beyond timing one `Instant::now()` run (as in `rayon_parallel_iteration.rs`):
+// * Warmup runs the code until caches, branch predictors, and CPU clocks settle, and picks
+//   how many iterations each timed sample needs to dwarf the timer's resolution.
+// * Many samples give a distribution (median, spread, outliers) instead of one number.
+// * `compare` runs Welch's t-test on two benchmarks and reports the change with a 95%
+//   confidence interval, so noise isn't mistaken for a speedup.
+// * Reports render as JSON (for CI artifacts) or a Markdown table (for PR comments).
+//
+// Always benchmark release builds (`cargo run --release`); debug timings are meaningless.
+
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// How long to warm up and measure each benchmark.
+#[derive(Debug, Clone)]
+struct BenchConfig {
+    warmup: Duration,
+    /// Total time spent on timed samples, split evenly across `samples`.
+    measurement: Duration,
+    /// Number of timed samples; at least 2, so the spread can be estimated.
+    samples: usize,
+    /// Changes smaller than this (e.g. 0.02 = 2%) count as unchanged even if significant.
+    noise_threshold: f64,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            warmup: Duration::from_millis(500),
+            measurement: Duration::from_secs(2),
+            samples: 50,
+            noise_threshold: 0.02,
+        }
+    }
+}
+
+/// Summary of one benchmark's per-iteration times, in nanoseconds.
+#[derive(Debug, Clone, Serialize)]
+struct Measurement {
+    name: String,
+    iterations_per_sample: u64,
+    samples: usize,
+    mean_ns: f64,
+    stddev_ns: f64,
+    median_ns: f64,
+    min_ns: f64,
+    max_ns: f64,
+    p95_ns: f64,
+    /// Samples outside Tukey's fences (1.5 IQR beyond the quartiles), usually from other
+    /// processes or frequency scaling. Many outliers mean the numbers deserve suspicion.
+    outliers: usize,
+    #[serde(skip)]
+    per_iteration_ns: Vec<f64>,
+}
+
+/// What a comparison concluded about the candidate relative to the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Verdict {
+    Faster,
+    Slower,
+    /// The confidence interval includes zero, or the change is within the noise threshold.
+    NoChange,
+}
+
+/// The candidate's mean time relative to the baseline's, with a 95% confidence interval.
+#[derive(Debug, Clone, Serialize)]
+struct Comparison {
+    baseline: String,
+    candidate: String,
+    /// `(candidate - baseline) / baseline`; negative means the candidate is faster.
+    change: f64,
+    ci_low: f64,
+    ci_high: f64,
+    verdict: Verdict,
+}
+
+/// Runs benchmarks and collects their measurements and comparisons into a report.
+struct Bench {
+    config: BenchConfig,
+    results: Vec<Measurement>,
+    comparisons: Vec<Comparison>,
+}
+
+impl Bench {
+    fn new(config: BenchConfig) -> Self {
+        let config = BenchConfig { samples: config.samples.max(2), ..config };
+        Self { config, results: Vec::new(), comparisons: Vec::new() }
+    }
+
+    /// Benchmarks `routine`, passing its output through `black_box` so it isn't optimized away.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Label used in reports and by `compare`.
+    /// * `routine` - The code to time; it runs many times.
+    ///
+    /// # Returns
+    ///
+    /// The measurement, which is also kept for the report.
+    fn run<O>(&mut self, name: &str, mut routine: impl FnMut() -> O) -> &Measurement {
+        self.run_batched(name, || (), |()| routine())
+    }
+
+    /// Benchmarks `routine` on fresh inputs from `setup`, timing only `routine`.
+    ///
+    /// Use this when the routine consumes or mutates its input, e.g. sorting a shuffled
+    /// vector: sorting the same vector twice would time the already-sorted case. Each sample
+    /// builds all of its inputs before starting the clock, so inputs must fit in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Label used in reports and by `compare`.
+    /// * `setup` - Builds one input; not timed.
+    /// * `routine` - The code to time.
+    ///
+    /// # Returns
+    ///
+    /// The measurement, which is also kept for the report.
+    fn run_batched<I, O>(
+        &mut self,
+        name: &str,
+        mut setup: impl FnMut() -> I,
+        mut routine: impl FnMut(I) -> O,
+    ) -> &Measurement {
+        let mut time_batch = |iterations: u64| -> Duration {
+            let inputs: Vec<I> = (0..iterations).map(|_| setup()).collect();
+            let start = Instant::now();
+            for input in inputs {
+                black_box(routine(black_box(input)));
+            }
+            start.elapsed()
+        };
+
+        // Warm up, doubling the batch size until one batch fills a sample's time slot.
+        let slot = self.config.measurement / self.config.samples as u32;
+        let warmup_end = Instant::now() + self.config.warmup;
+        let mut iterations = 1u64;
+        loop {
+            let elapsed = time_batch(iterations);
+            if Instant::now() >= warmup_end && elapsed >= slot {
+                break;
+            }
+            if elapsed < slot {
+                iterations = iterations.saturating_mul(2);
+            }
+        }
+        // The last batch overshot the slot by up to 2x; scale back to roughly fill it.
+        let per_iteration = time_batch(iterations).as_secs_f64() / iterations as f64;
+        let iterations = ((slot.as_secs_f64() / per_iteration.max(1e-12)) as u64).max(1);
+
+        let per_iteration_ns: Vec<f64> = (0..self.config.samples)
+            .map(|_| time_batch(iterations).as_nanos() as f64 / iterations as f64)
+            .collect();
+        self.results.push(summarize(name, iterations, per_iteration_ns));
+        self.results.last().expect("just pushed")
+    }
+
+    fn find(&self, name: &str) -> Option<&Measurement> {
+        self.results.iter().rev().find(|m| m.name == name)
+    }
+
+    /// Compares two benchmarks already run by name.
+    ///
+    /// # Returns
+    ///
+    /// The comparison, also added to the report, or `None` if either name is unknown.
+    fn compare(&mut self, baseline: &str, candidate: &str) -> Option<&Comparison> {
+        let comparison = compare(self.find(baseline)?, self.find(candidate)?, self.config.noise_threshold);
+        self.comparisons.push(comparison);
+        self.comparisons.last()
+    }
+
+    fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "results": self.results,
+            "comparisons": self.comparisons,
+        }))
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = String::from("| Benchmark | Median | Mean ± SD | p95 | Outliers |\n|---|---:|---:|---:|---:|\n");
+        for m in &self.results {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} ± {} | {} | {}/{} |",
+                m.name,
+                format_ns(m.median_ns),
+                format_ns(m.mean_ns),
+                format_ns(m.stddev_ns),
+                format_ns(m.p95_ns),
+                m.outliers,
+                m.samples
+            );
+        }
+        if !self.comparisons.is_empty() {
+            out.push_str("\n| Baseline | Candidate | Change | 95% CI | Verdict |\n|---|---|---:|---:|---|\n");
+            for c in &self.comparisons {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {:+.1}% | [{:+.1}%, {:+.1}%] | {:?} |",
+                    c.baseline,
+                    c.candidate,
+                    c.change * 100.0,
+                    c.ci_low * 100.0,
+                    c.ci_high * 100.0,
+                    c.verdict
+                );
+            }
+        }
+        out
+    }
+}
+
+fn mean_and_variance(data: &[f64]) -> (f64, f64) {
+    let mean = data.iter().sum::<f64>() / data.len() as f64;
+    let variance = data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (data.len() - 1).max(1) as f64;
+    (mean, variance)
+}
+
+/// Percentile (0-100) of sorted data, interpolating between the nearest ranks.
+fn percentile_sorted(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+fn summarize(name: &str, iterations: u64, per_iteration_ns: Vec<f64>) -> Measurement {
+    let mut sorted = per_iteration_ns.clone();
+    sorted.sort_by(f64::total_cmp);
+    let (mean, variance) = mean_and_variance(&sorted);
+    let (q1, q3) = (percentile_sorted(&sorted, 25.0), percentile_sorted(&sorted, 75.0));
+    let (low_fence, high_fence) = (q1 - 1.5 * (q3 - q1), q3 + 1.5 * (q3 - q1));
+    Measurement {
+        name: name.to_string(),
+        iterations_per_sample: iterations,
+        samples: sorted.len(),
+        mean_ns: mean,
+        stddev_ns: variance.sqrt(),
+        median_ns: percentile_sorted(&sorted, 50.0),
+        min_ns: sorted[0],
+        max_ns: sorted[sorted.len() - 1],
+        p95_ns: percentile_sorted(&sorted, 95.0),
+        outliers: sorted.iter().filter(|&&x| x < low_fence || x > high_fence).count(),
+        per_iteration_ns,
+    }
+}
+
+/// Two-sided 95% critical value of Student's t for `df` degrees of freedom, using the
+/// Cornish-Fisher expansion around the normal quantile (within 0.5% for df >= 3).
+fn t_critical_95(df: f64) -> f64 {
+    let z: f64 = 1.959_964;
+    let (z3, z5, z7) = (z.powi(3), z.powi(5), z.powi(7));
+    z + (z3 + z) / (4.0 * df)
+        + (5.0 * z5 + 16.0 * z3 + 3.0 * z) / (96.0 * df.powi(2))
+        + (3.0 * z7 + 19.0 * z5 + 17.0 * z3 - 15.0 * z) / (384.0 * df.powi(3))
+}
+
+/// Compares mean per-iteration times with Welch's t-test (no equal-variance assumption).
+///
+/// # Arguments
+///
+/// * `baseline` - The reference measurement, e.g. the current implementation.
+/// * `candidate` - The measurement being evaluated.
+/// * `noise_threshold` - Relative changes smaller than this are reported as `NoChange`.
+///
+/// # Returns
+///
+/// The relative change in mean time and its 95% confidence interval, both as fractions of
+/// the baseline mean.
+fn compare(baseline: &Measurement, candidate: &Measurement, noise_threshold: f64) -> Comparison {
+    let (mean_a, var_a) = mean_and_variance(&baseline.per_iteration_ns);
+    let (mean_b, var_b) = mean_and_variance(&candidate.per_iteration_ns);
+    let (n_a, n_b) = (baseline.per_iteration_ns.len() as f64, candidate.per_iteration_ns.len() as f64);
+    let (se_a, se_b) = (var_a / n_a, var_b / n_b);
+    let standard_error = (se_a + se_b).sqrt();
+    // Welch-Satterthwaite degrees of freedom; identical samples would divide by zero.
+    let df = if standard_error > 0.0 {
+        (se_a + se_b).powi(2) / (se_a.powi(2) / (n_a - 1.0) + se_b.powi(2) / (n_b - 1.0))
+    } else {
+        n_a + n_b - 2.0
+    };
+    let margin = t_critical_95(df.max(1.0)) * standard_error;
+    let diff = mean_b - mean_a;
+    let (change, ci_low, ci_high) = (diff / mean_a, (diff - margin) / mean_a, (diff + margin) / mean_a);
+    let verdict = if (ci_low <= 0.0 && ci_high >= 0.0) || change.abs() < noise_threshold {
+        Verdict::NoChange
+    } else if change < 0.0 {
+        Verdict::Faster
+    } else {
+        Verdict::Slower
+    };
+    Comparison {
+        baseline: baseline.name.clone(),
+        candidate: candidate.name.clone(),
+        change,
+        ci_low,
+        ci_high,
+        verdict,
+    }
+}
+
+fn format_ns(ns: f64) -> String {
+    match ns {
+        ns if ns < 1e3 => format!("{:.1} ns", ns),
+        ns if ns < 1e6 => format!("{:.2} µs", ns / 1e3),
+        ns if ns < 1e9 => format!("{:.2} ms", ns / 1e6),
+        ns => format!("{:.2} s", ns / 1e9),
+    }
+}
+
+// Example Usage
+/*
+fn main() {
+    // Short timings keep the example quick; use the defaults for real measurements.
+    let mut bench = Bench::new(BenchConfig {
+        warmup: Duration::from_millis(100),
+        measurement: Duration::from_millis(400),
+        samples: 30,
+        ..BenchConfig::default()
+    });
+
+    let data: Vec<u64> = (0..10_000).collect();
+    // `black_box` on each element stops the compiler from vectorizing the first loop.
+    bench.run("sum_opaque", || {
+        let mut total = 0u64;
+        for &x in &data {
+            total = total.wrapping_add(black_box(x) * 3);
+        }
+        total
+    });
+    bench.run("sum_iter", || data.iter().map(|&x| x * 3).fold(0u64, u64::wrapping_add));
+
+    // Sorting mutates its input, so each iteration gets a freshly shuffled copy.
+    let mut seed = 42u64;
+    let mut shuffled = move || {
+        (0..1_000)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed
+            })
+            .collect::<Vec<u64>>()
+    };
+    bench.run_batched("sort", &mut shuffled, |mut v| { v.sort(); v });
+    bench.run_batched("sort_unstable", &mut shuffled, |mut v| { v.sort_unstable(); v });
+
+    bench.compare("sum_opaque", "sum_iter");
+    let verdict = bench.compare("sort", "sort_unstable").map(|c| c.verdict);
+    println!("sort_unstable vs sort: {:?}\n", verdict);
+    println!("{}", bench.to_markdown());
+    println!("{}", bench.to_json().unwrap());
+}
+*/
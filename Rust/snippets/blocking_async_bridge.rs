@@ -0,0 +1,217 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+// reqwest = { version = "0.11", features = ["blocking"] } // Only for the blocking HTTP helper
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::{Arc, OnceLock};
+use std::{fs, io};
+use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::sync::Semaphore;
+
+// --- Part 1: Running blocking code from async code ---
+//
+// Blocking calls (std::fs, std::process, reqwest::blocking, heavy CPU work) must not run
+// directly on a tokio worker thread: they stall every other task scheduled on that thread.
+// `tokio::task::spawn_blocking` moves the call to a separate thread pool instead.
+//
+// Tokio's blocking pool is large (512 threads by default), so an unbounded burst of
+// blocking work can still exhaust file descriptors or hammer a remote server.
+// `BlockingPool` adds a semaphore so at most `max_concurrent` calls run at once;
+// the rest wait asynchronously (without occupying a thread).
+
+/// A bounded front-end to tokio's blocking thread pool.
+#[derive(Clone)]
+struct BlockingPool {
+    permits: Arc<Semaphore>,
+}
+
+impl BlockingPool {
+    /// Creates a pool that runs at most `max_concurrent` blocking closures at a time.
+    fn new(max_concurrent: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+        }
+    }
+
+    /// Runs `f` on the blocking pool once a permit is available.
+    ///
+    /// # Returns
+    /// * `io::Result<T>` - The closure's value, or an error if the closure panicked.
+    async fn run<F, T>(&self, f: F) -> io::Result<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        // Waiting for a permit is async: no thread is consumed while queued.
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| io::Error::other("blocking pool closed"))?;
+
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit; // Released when the closure finishes
+            f()
+        })
+        .await
+        .map_err(|e| io::Error::other(format!("blocking task failed: {}", e)))
+    }
+
+    /// Same as `run`, but for closures that already return `io::Result`.
+    async fn run_io<F, T>(&self, f: F) -> io::Result<T>
+    where
+        F: FnOnce() -> io::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.run(f).await?
+    }
+
+    /// Number of closures that could start right now without waiting.
+    fn available(&self) -> usize {
+        self.permits.available_permits()
+    }
+}
+
+/// Async wrapper around a blocking `std::process::Command` (see execute_command.rs).
+async fn execute_command_async(
+    pool: &BlockingPool,
+    program: &str,
+    args: &[&str],
+) -> io::Result<Output> {
+    // Owned copies so the closure is 'static.
+    let program = program.to_string();
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    pool.run_io(move || Command::new(program).args(args).output()).await
+}
+
+/// Async wrapper around blocking `std::fs` reads (see read_text_file.rs).
+async fn read_to_string_async(pool: &BlockingPool, path: impl Into<PathBuf>) -> io::Result<String> {
+    let path = path.into();
+    pool.run_io(move || fs::read_to_string(path)).await
+}
+
+/// Async wrapper around blocking `std::fs` writes (see write_text_file.rs).
+async fn write_async(pool: &BlockingPool, path: impl Into<PathBuf>, contents: Vec<u8>) -> io::Result<()> {
+    let path = path.into();
+    pool.run_io(move || fs::write(path, contents)).await
+}
+
+/// Async wrapper around `reqwest::blocking` (see http_get_request.rs).
+/// Handy when an existing blocking client must be reused from async code.
+/// Note: `reqwest::blocking::Client` must not be created or dropped on a tokio worker
+/// thread, which is another reason to keep it inside `spawn_blocking`.
+async fn http_get_text_blocking(pool: &BlockingPool, url: &str) -> io::Result<String> {
+    let url = url.to_string();
+    pool.run_io(move || {
+        let to_io = |e: reqwest::Error| io::Error::other(e);
+        reqwest::blocking::get(url)
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.text())
+            .map_err(to_io)
+    })
+    .await
+}
+
+// --- Part 2: Calling async code from sync code ---
+//
+// A lazily-created, process-wide runtime lets ordinary (non-async) functions call async
+// helpers without every caller building its own runtime.
+
+/// Returns the shared background runtime, creating it on first use.
+fn shared_runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("sync-facade")
+            .enable_all()
+            .build()
+            .expect("failed to build the shared tokio runtime")
+    })
+}
+
+/// Runs a future to completion from synchronous code.
+///
+/// Returns an error instead of panicking when called from inside an async context:
+/// blocking a runtime thread on another future would deadlock or panic
+/// ("Cannot start a runtime from within a runtime").
+fn block_on_sync<F>(future: F) -> io::Result<F::Output>
+where
+    F: Future,
+{
+    if Handle::try_current().is_ok() {
+        return Err(io::Error::other(
+            "block_on_sync called from within an async runtime; use .await instead",
+        ));
+    }
+    Ok(shared_runtime().block_on(future))
+}
+
+/// Example sync facade: a blocking API built on top of an async helper.
+fn read_many_files_sync(paths: Vec<PathBuf>) -> io::Result<Vec<io::Result<String>>> {
+    block_on_sync(async move {
+        let pool = BlockingPool::new(4);
+        let tasks: Vec<_> = paths
+            .into_iter()
+            .map(|p| {
+                let pool = pool.clone();
+                tokio::spawn(async move { read_to_string_async(&pool, p).await })
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            results.push(task.await.unwrap_or_else(|e| Err(io::Error::other(e))));
+        }
+        results
+    })
+}
+
+// Example Usage
+/*
+// Async side: blocking work kept off the runtime's worker threads.
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let pool = BlockingPool::new(2); // At most 2 blocking calls at a time
+
+    write_async(&pool, "bridge_demo.txt", b"hello from spawn_blocking\n".to_vec()).await?;
+    let text = read_to_string_async(&pool, "bridge_demo.txt").await?;
+    println!("Read back: {}", text.trim());
+
+    #[cfg(not(windows))]
+    let output = execute_command_async(&pool, "echo", &["hi", "there"]).await?;
+    #[cfg(windows)]
+    let output = execute_command_async(&pool, "cmd", &["/C", "echo hi there"]).await?;
+    println!("Command said: {}", String::from_utf8_lossy(&output.stdout).trim());
+
+    match http_get_text_blocking(&pool, "https://httpbin.org/get").await {
+        Ok(body) => println!("HTTP body is {} bytes", body.len()),
+        Err(e) => eprintln!("HTTP request failed: {}", e),
+    }
+
+    // Calling the sync facade from async code is rejected instead of deadlocking.
+    assert!(block_on_sync(async { 1 }).is_err());
+
+    fs::remove_file("bridge_demo.txt")?;
+    Ok(())
+}
+
+// Sync side: a plain (non-async) function using async helpers through the shared runtime.
+// Call it from a regular `fn main` (not from inside `#[tokio::main]`).
+fn sync_main() -> io::Result<()> {
+    fs::write("a.txt", "file a")?;
+    let results = read_many_files_sync(vec!["a.txt".into(), "missing.txt".into()])?;
+    for r in results {
+        match r {
+            Ok(text) => println!("OK: {}", text),
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+    fs::remove_file("a.txt")?;
+    Ok(())
+}
+*/
@@ -0,0 +1,458 @@
+// This example only uses the standard library. The async helper is runtime-agnostic
+// (works with tokio, async-std, ...); the example below uses tokio:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] } // Only for the async example
+//
+// `LruCache` and its methods are `pub` so the file can be used as a module (`mod cache;`),
+// which is where `#[memoize]` from the `snippet-macros` crate (`Rust/macros`) looks for it.
+//
+// Expiry reads the time from a `Clock` (default `SystemClock`), so TTL tests can pass the
+// `MockClock` from `clock.rs` to `with_clock` instead of sleeping.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Where the cache reads the time. The same trait as `Clock` in `clock.rs`; when both files
+/// are in one crate, delete this copy and `SystemClock` and `use crate::clock::Clock`.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+    fn wall(&self) -> SystemTime;
+    fn sleep(&self, duration: Duration);
+}
+
+/// The real clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// One cache slot in the intrusive doubly-linked recency list.
+struct Node<K, V> {
+    key: K,
+    value: V,
+    expires_at: Option<Instant>,
+    prev: Option<usize>, // Towards most-recently used
+    next: Option<usize>, // Towards least-recently used
+}
+
+/// The unsynchronized core: a HashMap pointing into a slab of nodes that form an LRU list.
+/// All operations are O(1).
+struct LruCore<K, V> {
+    map: HashMap<K, usize>,
+    slab: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    head: Option<usize>, // Most recently used
+    tail: Option<usize>, // Least recently used
+    capacity: usize,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCore<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            map: HashMap::with_capacity(capacity),
+            slab: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            head: None,
+            tail: None,
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn node(&self, idx: usize) -> &Node<K, V> {
+        self.slab[idx].as_ref().expect("linked node exists")
+    }
+
+    fn node_mut(&mut self, idx: usize) -> &mut Node<K, V> {
+        self.slab[idx].as_mut().expect("linked node exists")
+    }
+
+    fn unlink(&mut self, idx: usize) {
+        let (prev, next) = {
+            let n = self.node(idx);
+            (n.prev, n.next)
+        };
+        match prev {
+            Some(p) => self.node_mut(p).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(n) => self.node_mut(n).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    fn push_front(&mut self, idx: usize) {
+        let old_head = self.head;
+        {
+            let n = self.node_mut(idx);
+            n.prev = None;
+            n.next = old_head;
+        }
+        if let Some(h) = old_head {
+            self.node_mut(h).prev = Some(idx);
+        }
+        self.head = Some(idx);
+        if self.tail.is_none() {
+            self.tail = Some(idx);
+        }
+    }
+
+    fn remove_index(&mut self, idx: usize) -> Node<K, V> {
+        self.unlink(idx);
+        let node = self.slab[idx].take().expect("linked node exists");
+        self.map.remove(&node.key);
+        self.free.push(idx);
+        node
+    }
+
+    /// Outcome of a lookup: found, missing, or found-but-expired (and removed).
+    fn get<Q>(&mut self, key: &Q, now: Instant) -> Lookup<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(&idx) = self.map.get(key) else {
+            return Lookup::Missing;
+        };
+        if self.node(idx).expires_at.is_some_and(|t| t <= now) {
+            self.remove_index(idx);
+            return Lookup::Expired;
+        }
+        // Move to front (most recently used).
+        self.unlink(idx);
+        self.push_front(idx);
+        Lookup::Hit(&self.node(idx).value)
+    }
+
+    /// Inserts or replaces; returns true if an entry had to be evicted to make room.
+    fn insert(&mut self, key: K, value: V, expires_at: Option<Instant>) -> bool {
+        if let Some(&idx) = self.map.get(&key) {
+            let n = self.node_mut(idx);
+            n.value = value;
+            n.expires_at = expires_at;
+            self.unlink(idx);
+            self.push_front(idx);
+            return false;
+        }
+
+        let mut evicted = false;
+        if self.map.len() >= self.capacity {
+            if let Some(lru) = self.tail {
+                self.remove_index(lru);
+                evicted = true;
+            }
+        }
+
+        let node = Node {
+            key: key.clone(),
+            value,
+            expires_at,
+            prev: None,
+            next: None,
+        };
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                self.slab[idx] = Some(node);
+                idx
+            }
+            None => {
+                self.slab.push(Some(node));
+                self.slab.len() - 1
+            }
+        };
+        self.map.insert(key, idx);
+        self.push_front(idx);
+        evicted
+    }
+}
+
+enum Lookup<V> {
+    Hit(V),
+    Missing,
+    Expired,
+}
+
+/// Counters describing cache effectiveness.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// A thread-safe LRU cache with optional per-entry TTL and hit/miss metrics.
+///
+/// Values are cloned out on `get`, so store `Arc<T>` for large values.
+/// Share between threads with `Arc<LruCache<..>>` (all methods take `&self`).
+pub struct LruCache<K, V> {
+    core: Mutex<LruCore<K, V>>,
+    default_ttl: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
+    /// A cache holding at most `capacity` entries, without expiry.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            core: Mutex::new(LruCore::new(capacity)),
+            default_ttl: None,
+            clock: Arc::new(SystemClock),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            expirations: AtomicU64::new(0),
+        }
+    }
+
+    /// A cache whose entries expire `ttl` after insertion unless overridden per entry.
+    pub fn with_ttl(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            default_ttl: Some(ttl),
+            ..Self::new(capacity)
+        }
+    }
+
+    /// Replaces the clock used for TTLs, e.g. with a `MockClock` in tests.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns a clone of the cached value, marking it most recently used.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut core = self.core.lock().unwrap();
+        match core.get(key, self.clock.now()) {
+            Lookup::Hit(v) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(v.clone())
+            }
+            Lookup::Missing => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Lookup::Expired => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                self.expirations.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Inserts with the cache's default TTL.
+    pub fn insert(&self, key: K, value: V) {
+        self.insert_with_ttl(key, value, self.default_ttl);
+    }
+
+    /// Inserts with an explicit TTL (`None` = never expires, only evicted by LRU).
+    pub fn insert_with_ttl(&self, key: K, value: V, ttl: Option<Duration>) {
+        let expires_at = ttl.map(|t| self.clock.now() + t);
+        let evicted = self.core.lock().unwrap().insert(key, value, expires_at);
+        if evicted {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut core = self.core.lock().unwrap();
+        let idx = *core.map.get(key)?;
+        Some(core.remove_index(idx).value)
+    }
+
+    /// Returns the cached value or computes, stores, and returns it.
+    /// The lock is NOT held while `f` runs, so two threads missing the same key at the
+    /// same time may both compute it (last write wins). If that matters, put the
+    /// computation behind `singleflight.rs`.
+    pub fn get_or_insert_with<F>(&self, key: K, f: F) -> V
+    where
+        F: FnOnce() -> V,
+    {
+        if let Some(v) = self.get(&key) {
+            return v;
+        }
+        let value = f();
+        self.insert(key, value.clone());
+        value
+    }
+
+    /// Fallible variant: errors are returned and nothing is cached.
+    pub fn try_get_or_insert_with<F, E>(&self, key: K, f: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        if let Some(v) = self.get(&key) {
+            return Ok(v);
+        }
+        let value = f()?;
+        self.insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// Async variant. The mutex is a std `Mutex` that is never held across the `.await`.
+    pub async fn get_or_insert_with_async<F, Fut>(&self, key: K, f: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        if let Some(v) = self.get(&key) {
+            return v;
+        }
+        let value = f().await;
+        self.insert(key, value.clone());
+        value
+    }
+
+    /// Fallible async variant (e.g. an HTTP fetch); errors are not cached.
+    pub async fn try_get_or_insert_with_async<F, Fut, E>(&self, key: K, f: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        if let Some(v) = self.get(&key) {
+            return Ok(v);
+        }
+        let value = f().await?;
+        self.insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// Removes all expired entries. Expired entries are also dropped lazily on access,
+    /// so this is only needed to reclaim memory for keys that are never read again.
+    pub fn purge_expired(&self) -> usize {
+        let now = self.clock.now();
+        let mut core = self.core.lock().unwrap();
+        let expired: Vec<usize> = core
+            .map
+            .values()
+            .copied()
+            .filter(|&idx| core.node(idx).expires_at.is_some_and(|t| t <= now))
+            .collect();
+        for &idx in &expired {
+            core.remove_index(idx);
+        }
+        self.expirations.fetch_add(expired.len() as u64, Ordering::Relaxed);
+        expired.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.core.lock().unwrap().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.core.lock().unwrap().capacity
+    }
+
+    pub fn clear(&self) {
+        let mut core = self.core.lock().unwrap();
+        let capacity = core.capacity;
+        *core = LruCore::new(capacity);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// Example Usage
+/*
+fn main() {
+    println!("--- LRU eviction ---");
+    let cache: LruCache<String, u32> = LruCache::new(2);
+    cache.insert("a".into(), 1);
+    cache.insert("b".into(), 2);
+    cache.get("a"); // "a" is now most recently used
+    cache.insert("c".into(), 3); // Evicts "b", the least recently used
+    println!("a={:?} b={:?} c={:?}", cache.get("a"), cache.get("b"), cache.get("c"));
+
+    println!("\n--- TTL expiry ---");
+    let tokens: LruCache<&str, String> = LruCache::with_ttl(100, Duration::from_millis(50));
+    tokens.insert("service-a", "token-123".into());
+    tokens.insert_with_ttl("service-b", "token-456".into(), None); // Never expires
+    std::thread::sleep(Duration::from_millis(80));
+    println!("service-a: {:?}, service-b: {:?}", tokens.get("service-a"), tokens.get("service-b"));
+
+    println!("\n--- get_or_insert_with from many threads ---");
+    let dns = Arc::new(LruCache::<String, String>::with_ttl(1_000, Duration::from_secs(300)));
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let dns = Arc::clone(&dns);
+            std::thread::spawn(move || {
+                for i in 0..100 {
+                    let host = format!("host{}.example.com", i % 10);
+                    dns.get_or_insert_with(host.clone(), || format!("10.0.{}.{}", t, i));
+                }
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    let stats = dns.stats();
+    println!("{:?}, hit ratio {:.1}%", stats, stats.hit_ratio() * 100.0);
+
+    println!("\n--- Async loader ---");
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let http_cache: LruCache<String, Arc<String>> = LruCache::with_ttl(100, Duration::from_secs(60));
+        for _ in 0..3 {
+            let body: Result<Arc<String>, String> = http_cache
+                .try_get_or_insert_with_async("https://example.com/".to_string(), || async {
+                    println!("Fetching (cache miss)...");
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    Ok(Arc::new("<html>...</html>".to_string()))
+                })
+                .await;
+            println!("Body length: {}", body.unwrap().len());
+        }
+        println!("{:?}", http_cache.stats());
+    });
+}
+*/
@@ -0,0 +1,900 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// semver = "1"
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+// toml = "0.8"
+//
+// Dependency and license inventory for a Cargo project: what is in the build, at which
+// versions, which crates are compiled more than once, and under which licenses. This is the
+// data behind a "third-party notices" page, a CI gate that rejects GPL dependencies, or a
+// weekly "we ship three copies of `syn`" report.
+// * Two inputs, one report. `report_from_lock` works offline from `Cargo.lock` (plus any
+//   `Cargo.toml` files you have) and needs no toolchain. `report_from_metadata` runs
+//   `cargo metadata` through the same helper as `execute_command.rs` and gets the full
+//   resolved graph, including licenses.
+// * `Cargo.lock` does not record licenses or dependency kinds. A lock-only report knows the
+//   licenses of workspace members (read from their manifests, following
+//   `license.workspace = true`) and leaves every other crate's license as `None`. Use the
+//   metadata path when licenses matter.
+// * `duplicates` lists every crate resolved at more than one version, with the crates that
+//   pull in each version, which is usually the first thing you need to fix one.
+// * `LicensePolicy` evaluates SPDX expressions (`MIT OR Apache-2.0`,
+//   `(MIT OR Apache-2.0) AND Unicode-3.0`, the legacy `MIT/Apache-2.0`) against an allow list.
+// * `DependencyReport` is `Serialize`, so `serde_json::to_string_pretty` gives a stable,
+//   sorted document that diffs cleanly between commits.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum CargoMetaError {
+    #[error("failed to read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("failed to parse {path}: {source}")]
+    Toml { path: PathBuf, source: toml::de::Error },
+    #[error("failed to run cargo metadata: {0}")]
+    Spawn(io::Error),
+    #[error("cargo metadata exited with {status}: {stderr}")]
+    Command { status: String, stderr: String },
+    #[error("invalid cargo metadata output: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid license expression {expression:?}: {message}")]
+    License { expression: String, message: String },
+}
+
+type Result<T> = std::result::Result<T, CargoMetaError>;
+
+// --- Cargo.toml ---
+
+/// The parts of a manifest that matter for a dependency report. Unknown keys are ignored.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Manifest {
+    package: Option<ManifestPackage>,
+    workspace: Option<ManifestWorkspace>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, DependencySpec>,
+    #[serde(default)]
+    dev_dependencies: BTreeMap<String, DependencySpec>,
+    #[serde(default)]
+    build_dependencies: BTreeMap<String, DependencySpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ManifestPackage {
+    name: String,
+    version: Option<Inheritable<String>>,
+    license: Option<Inheritable<String>>,
+    license_file: Option<Inheritable<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ManifestWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+    package: Option<WorkspacePackage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct WorkspacePackage {
+    version: Option<String>,
+    license: Option<String>,
+    license_file: Option<String>,
+}
+
+/// A package field that is either set directly or inherited with `field.workspace = true`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Inheritable<T> {
+    Value(T),
+    Workspace { workspace: bool },
+}
+
+impl<T: Clone> Inheritable<T> {
+    fn resolve(&self, inherited: Option<&T>) -> Option<T> {
+        match self {
+            Inheritable::Value(value) => Some(value.clone()),
+            Inheritable::Workspace { workspace: true } => inherited.cloned(),
+            Inheritable::Workspace { workspace: false } => None,
+        }
+    }
+}
+
+/// `serde = "1"` or `serde = { version = "1", features = [...] }`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DependencySpec {
+    Simple(String),
+    Detailed(DetailedDependency),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DetailedDependency {
+    version: Option<String>,
+    path: Option<String>,
+    git: Option<String>,
+    /// The real crate name when the dependency is renamed (`json = { package = "serde_json" }`).
+    package: Option<String>,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    workspace: bool,
+    #[serde(default)]
+    features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum DepKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+impl Manifest {
+    /// Direct dependencies as `(crate name, kind)`, with renames resolved to the real crate
+    /// name so they line up with `Cargo.lock` entries.
+    fn direct_dependencies(&self) -> Vec<(String, DepKind)> {
+        let tables = [
+            (&self.dependencies, DepKind::Normal),
+            (&self.dev_dependencies, DepKind::Dev),
+            (&self.build_dependencies, DepKind::Build),
+        ];
+        let mut out = Vec::new();
+        for (table, kind) in tables {
+            for (key, spec) in table {
+                let name = match spec {
+                    DependencySpec::Detailed(DetailedDependency { package: Some(real), .. }) => real.clone(),
+                    _ => key.clone(),
+                };
+                out.push((name, kind));
+            }
+        }
+        out
+    }
+
+    /// The package's license expression (or `license-file`, as `"file:<path>"`), following
+    /// `license.workspace = true` into `workspace_root`'s `[workspace.package]`.
+    fn license(&self, workspace_root: Option<&Manifest>) -> Option<String> {
+        let package = self.package.as_ref()?;
+        let inherited = workspace_root
+            .or(Some(self))
+            .and_then(|root| root.workspace.as_ref())
+            .and_then(|ws| ws.package.as_ref());
+        let license = package
+            .license
+            .as_ref()
+            .and_then(|l| l.resolve(inherited.and_then(|p| p.license.as_ref())));
+        license.or_else(|| {
+            package
+                .license_file
+                .as_ref()
+                .and_then(|l| l.resolve(inherited.and_then(|p| p.license_file.as_ref())))
+                .map(|file| format!("file:{}", file))
+        })
+    }
+}
+
+fn load_manifest(path: &Path) -> Result<Manifest> {
+    let text = fs::read_to_string(path).map_err(|source| CargoMetaError::Io { path: path.to_path_buf(), source })?;
+    toml::from_str(&text).map_err(|source| CargoMetaError::Toml { path: path.to_path_buf(), source })
+}
+
+// --- Cargo.lock ---
+
+#[derive(Debug, Deserialize)]
+struct LockFile {
+    version: Option<u32>,
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    /// `registry+https://...`, `sparse+https://...`, or `git+https://...#<commit>`. Absent for
+    /// workspace members and path dependencies.
+    source: Option<String>,
+    checksum: Option<String>,
+    /// Entries are `"name"`, `"name version"`, or `"name version (source)"`; the extra parts
+    /// only appear when the name alone is ambiguous.
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+fn load_lock(path: &Path) -> Result<LockFile> {
+    let text = fs::read_to_string(path).map_err(|source| CargoMetaError::Io { path: path.to_path_buf(), source })?;
+    toml::from_str(&text).map_err(|source| CargoMetaError::Toml { path: path.to_path_buf(), source })
+}
+
+// --- cargo metadata ---
+
+/// A subset of `cargo metadata --format-version 1` output.
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    packages: Vec<MetadataPackage>,
+    workspace_members: Vec<String>,
+    /// Absent when run with `--no-deps`.
+    resolve: Option<Resolve>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetadataPackage {
+    id: String,
+    name: String,
+    version: String,
+    license: Option<String>,
+    license_file: Option<String>,
+    source: Option<String>,
+    repository: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Resolve {
+    nodes: Vec<ResolveNode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResolveNode {
+    id: String,
+    #[serde(default)]
+    deps: Vec<NodeDep>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeDep {
+    pkg: String,
+    #[serde(default)]
+    dep_kinds: Vec<DepKindInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DepKindInfo {
+    /// `null` for normal dependencies, otherwise `"dev"` or `"build"`.
+    kind: Option<String>,
+}
+
+/// Trimmed copy of the helper in `execute_command.rs` (no stdin).
+fn execute_command(program: &str, args: &[&str], current_dir: Option<&Path>) -> io::Result<Output> {
+    let mut command = Command::new(program);
+    command.args(args);
+    if let Some(dir) = current_dir {
+        command.current_dir(dir);
+    }
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    command.output()
+}
+
+/// Runs `cargo metadata --format-version 1` in `dir` and parses the result.
+///
+/// # Arguments
+/// * `dir` - Any directory inside the workspace.
+/// * `extra_args` - Passed through, e.g. `["--offline"]`, `["--locked"]`, or
+///   `["--filter-platform", "x86_64-unknown-linux-gnu"]`.
+///
+/// # Returns
+/// The parsed metadata, or `CargoMetaError::Command` with cargo's stderr if it failed.
+fn cargo_metadata(dir: &Path, extra_args: &[&str]) -> Result<Metadata> {
+    let mut args = vec!["metadata", "--format-version", "1"];
+    args.extend_from_slice(extra_args);
+    let output = execute_command("cargo", &args, Some(dir)).map_err(CargoMetaError::Spawn)?;
+    if !output.status.success() {
+        return Err(CargoMetaError::Command {
+            status: output.status.to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+// --- Report ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Origin {
+    /// A workspace member or path dependency.
+    Local,
+    Registry,
+    Git,
+}
+
+impl Origin {
+    fn from_source(source: Option<&str>) -> Origin {
+        match source {
+            None => Origin::Local,
+            Some(s) if s.starts_with("git+") => Origin::Git,
+            Some(_) => Origin::Registry,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ReportSource {
+    Lockfile,
+    Metadata,
+}
+
+#[derive(Debug, Serialize)]
+struct PackageEntry {
+    name: String,
+    version: String,
+    origin: Origin,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum: Option<String>,
+    license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repository: Option<String>,
+    /// How workspace members depend on this crate directly; empty for transitive crates.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    direct: BTreeSet<DepKind>,
+    /// `name version` of each crate this one depends on.
+    dependencies: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Duplicate {
+    name: String,
+    versions: Vec<DuplicateVersion>,
+}
+
+#[derive(Debug, Serialize)]
+struct DuplicateVersion {
+    version: String,
+    /// `name version` of each crate that pulls this version in.
+    required_by: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Summary {
+    packages: usize,
+    direct: usize,
+    registry: usize,
+    git: usize,
+    local: usize,
+    duplicated_crates: usize,
+    unknown_license: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct DependencyReport {
+    source: ReportSource,
+    workspace_members: Vec<String>,
+    /// Sorted by name, then version. Workspace members are not included.
+    packages: Vec<PackageEntry>,
+    duplicates: Vec<Duplicate>,
+    /// License expression → `name version` of each crate under it. Crates with no license
+    /// information are listed under `unknown_license` instead.
+    licenses: BTreeMap<String, Vec<String>>,
+    unknown_license: Vec<String>,
+    summary: Summary,
+}
+
+/// One resolved package, independent of where the graph came from.
+struct Node {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+    license: Option<String>,
+    repository: Option<String>,
+    member: bool,
+    /// Indices of dependencies, with the kinds this node uses them as.
+    deps: Vec<(usize, BTreeSet<DepKind>)>,
+}
+
+/// Builds a report from `Cargo.lock` alone. Pass the workspace's manifests (root first) to
+/// get member licenses and to tell dev/build dependencies apart; without them every direct
+/// dependency is reported as `normal` and no licenses are known.
+///
+/// Workspace members are the lock entries without a `source`, which also covers path
+/// dependencies outside the workspace.
+fn report_from_lock(lock: &LockFile, manifests: &[Manifest]) -> DependencyReport {
+    let mut index: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, package) in lock.package.iter().enumerate() {
+        index.insert((&package.name, &package.version), i);
+        by_name.entry(&package.name).or_default().push(i);
+    }
+
+    let root = manifests.first().filter(|m| m.workspace.is_some());
+    let manifest_for = |name: &str| {
+        manifests
+            .iter()
+            .find(|m| m.package.as_ref().is_some_and(|p| p.name == name))
+    };
+
+    let mut nodes: Vec<Node> = lock
+        .package
+        .iter()
+        .map(|package| {
+            let member = package.source.is_none();
+            let license = if member { manifest_for(&package.name).and_then(|m| m.license(root)) } else { None };
+            Node {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                source: package.source.clone(),
+                checksum: package.checksum.clone(),
+                license,
+                repository: None,
+                member,
+                deps: Vec::new(),
+            }
+        })
+        .collect();
+
+    for (i, package) in lock.package.iter().enumerate() {
+        let kinds_by_name: HashMap<String, BTreeSet<DepKind>> = match manifest_for(&package.name) {
+            Some(manifest) if nodes[i].member => {
+                let mut map: HashMap<String, BTreeSet<DepKind>> = HashMap::new();
+                for (name, kind) in manifest.direct_dependencies() {
+                    map.entry(name).or_default().insert(kind);
+                }
+                map
+            }
+            _ => HashMap::new(),
+        };
+        for entry in &package.dependencies {
+            let mut parts = entry.split_whitespace();
+            let name = parts.next().unwrap_or_default();
+            let target = match parts.next() {
+                Some(version) => index.get(&(name, version)).copied(),
+                None => by_name.get(name).and_then(|ids| ids.first().copied()),
+            };
+            if let Some(target) = target {
+                let kinds = kinds_by_name
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| BTreeSet::from([DepKind::Normal]));
+                nodes[i].deps.push((target, kinds));
+            }
+        }
+    }
+
+    build_report(ReportSource::Lockfile, nodes)
+}
+
+/// Builds a report from `cargo metadata` output, with licenses for every crate and
+/// dependency kinds taken from the resolved graph.
+fn report_from_metadata(metadata: &Metadata) -> DependencyReport {
+    let index: HashMap<&str, usize> = metadata
+        .packages
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.id.as_str(), i))
+        .collect();
+    let members: BTreeSet<&str> = metadata.workspace_members.iter().map(String::as_str).collect();
+
+    let mut nodes: Vec<Node> = metadata
+        .packages
+        .iter()
+        .map(|package| Node {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            source: package.source.clone(),
+            checksum: None,
+            license: package
+                .license
+                .clone()
+                .or_else(|| package.license_file.as_ref().map(|f| format!("file:{}", f))),
+            repository: package.repository.clone(),
+            member: members.contains(package.id.as_str()),
+            deps: Vec::new(),
+        })
+        .collect();
+
+    if let Some(resolve) = &metadata.resolve {
+        for node in &resolve.nodes {
+            let Some(&from) = index.get(node.id.as_str()) else { continue };
+            for dep in &node.deps {
+                let Some(&to) = index.get(dep.pkg.as_str()) else { continue };
+                let kinds = dep
+                    .dep_kinds
+                    .iter()
+                    .map(|k| match k.kind.as_deref() {
+                        Some("dev") => DepKind::Dev,
+                        Some("build") => DepKind::Build,
+                        _ => DepKind::Normal,
+                    })
+                    .collect();
+                nodes[from].deps.push((to, kinds));
+            }
+        }
+    }
+
+    build_report(ReportSource::Metadata, nodes)
+}
+
+fn build_report(source: ReportSource, nodes: Vec<Node>) -> DependencyReport {
+    let label = |i: usize| format!("{} {}", nodes[i].name, nodes[i].version);
+
+    let mut direct: Vec<BTreeSet<DepKind>> = vec![BTreeSet::new(); nodes.len()];
+    let mut dependents: Vec<BTreeSet<String>> = vec![BTreeSet::new(); nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        for (to, kinds) in &node.deps {
+            dependents[*to].insert(label(i));
+            if node.member && !nodes[*to].member {
+                direct[*to].extend(kinds.iter().copied());
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..nodes.len()).filter(|&i| !nodes[i].member).collect();
+    order.sort_by(|&a, &b| {
+        nodes[a]
+            .name
+            .cmp(&nodes[b].name)
+            .then_with(|| compare_versions(&nodes[a].version, &nodes[b].version))
+    });
+
+    let mut summary = Summary::default();
+    let mut licenses: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut unknown_license = Vec::new();
+    let mut versions_by_name: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut packages = Vec::with_capacity(order.len());
+
+    for &i in &order {
+        let node = &nodes[i];
+        let origin = Origin::from_source(node.source.as_deref());
+        summary.packages += 1;
+        match origin {
+            Origin::Registry => summary.registry += 1,
+            Origin::Git => summary.git += 1,
+            Origin::Local => summary.local += 1,
+        }
+        if !direct[i].is_empty() {
+            summary.direct += 1;
+        }
+        match &node.license {
+            Some(license) => licenses.entry(license.clone()).or_default().push(label(i)),
+            None => unknown_license.push(label(i)),
+        }
+        versions_by_name.entry(&node.name).or_default().push(i);
+
+        let mut dependencies: Vec<String> = node.deps.iter().map(|(to, _)| label(*to)).collect();
+        dependencies.sort();
+        dependencies.dedup();
+        packages.push(PackageEntry {
+            name: node.name.clone(),
+            version: node.version.clone(),
+            origin,
+            source: node.source.clone(),
+            checksum: node.checksum.clone(),
+            license: node.license.clone(),
+            repository: node.repository.clone(),
+            direct: std::mem::take(&mut direct[i]),
+            dependencies,
+        });
+    }
+
+    let duplicates: Vec<Duplicate> = versions_by_name
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|(name, ids)| Duplicate {
+            name: name.to_string(),
+            versions: ids
+                .into_iter()
+                .map(|i| DuplicateVersion {
+                    version: nodes[i].version.clone(),
+                    required_by: std::mem::take(&mut dependents[i]).into_iter().collect(),
+                })
+                .collect(),
+        })
+        .collect();
+    summary.duplicated_crates = duplicates.len();
+    summary.unknown_license = unknown_license.len();
+
+    let mut workspace_members: Vec<String> = nodes.iter().filter(|n| n.member).map(|n| n.name.clone()).collect();
+    workspace_members.sort();
+
+    DependencyReport { source, workspace_members, packages, duplicates, licenses, unknown_license, summary }
+}
+
+/// SemVer order when both parse (so `0.10.0 > 0.9.0`), string order otherwise.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    match (semver::Version::parse(a), semver::Version::parse(b)) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+// --- License policy ---
+
+#[derive(Debug, Serialize)]
+struct LicenseViolation {
+    package: String,
+    license: Option<String>,
+    reason: String,
+}
+
+/// An allow list of SPDX identifiers. `MIT OR GPL-3.0` passes if either side is allowed;
+/// `MIT AND Zlib` needs both. `WITH` exceptions are accepted when the base license is allowed.
+struct LicensePolicy {
+    allowed: BTreeSet<String>,
+    allow_unknown: bool,
+}
+
+impl LicensePolicy {
+    fn new<I, S>(allowed: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self { allowed: allowed.into_iter().map(Into::into).collect(), allow_unknown: false }
+    }
+
+    /// Don't report crates with no license information (e.g. from a lock-only report).
+    fn allow_unknown(mut self, allow: bool) -> Self {
+        self.allow_unknown = allow;
+        self
+    }
+
+    /// Whether `expression` is satisfied by the allow list.
+    fn permits(&self, expression: &str) -> Result<bool> {
+        let tokens = tokenize_license(expression);
+        let mut pos = 0;
+        let value = self.eval_or(&tokens, &mut pos).map_err(|message| CargoMetaError::License {
+            expression: expression.to_string(),
+            message,
+        })?;
+        if pos != tokens.len() {
+            return Err(CargoMetaError::License {
+                expression: expression.to_string(),
+                message: format!("unexpected {:?}", tokens[pos]),
+            });
+        }
+        Ok(value)
+    }
+
+    /// Every non-member crate in `report` whose license is not permitted.
+    fn check(&self, report: &DependencyReport) -> Vec<LicenseViolation> {
+        let mut violations = Vec::new();
+        for package in &report.packages {
+            let reason = match package.license.as_deref() {
+                None if self.allow_unknown => continue,
+                None => "no license information".to_string(),
+                Some(license) if license.starts_with("file:") => "custom license file".to_string(),
+                Some(license) => match self.permits(license) {
+                    Ok(true) => continue,
+                    Ok(false) => "not in the allow list".to_string(),
+                    Err(err) => err.to_string(),
+                },
+            };
+            violations.push(LicenseViolation {
+                package: format!("{} {}", package.name, package.version),
+                license: package.license.clone(),
+                reason,
+            });
+        }
+        violations
+    }
+
+    fn eval_or(&self, tokens: &[String], pos: &mut usize) -> std::result::Result<bool, String> {
+        let mut value = self.eval_and(tokens, pos)?;
+        while tokens.get(*pos).is_some_and(|t| t == "OR") {
+            *pos += 1;
+            value |= self.eval_and(tokens, pos)?;
+        }
+        Ok(value)
+    }
+
+    fn eval_and(&self, tokens: &[String], pos: &mut usize) -> std::result::Result<bool, String> {
+        let mut value = self.eval_atom(tokens, pos)?;
+        while tokens.get(*pos).is_some_and(|t| t == "AND") {
+            *pos += 1;
+            value &= self.eval_atom(tokens, pos)?;
+        }
+        Ok(value)
+    }
+
+    fn eval_atom(&self, tokens: &[String], pos: &mut usize) -> std::result::Result<bool, String> {
+        let token = tokens.get(*pos).ok_or("unexpected end of expression")?;
+        *pos += 1;
+        match token.as_str() {
+            "(" => {
+                let value = self.eval_or(tokens, pos)?;
+                if tokens.get(*pos).map(String::as_str) != Some(")") {
+                    return Err("missing closing parenthesis".to_string());
+                }
+                *pos += 1;
+                Ok(value)
+            }
+            ")" | "AND" | "OR" | "WITH" => Err(format!("unexpected {:?}", token)),
+            id => {
+                if tokens.get(*pos).is_some_and(|t| t == "WITH") {
+                    *pos += 2;
+                    if *pos > tokens.len() {
+                        return Err("missing exception after WITH".to_string());
+                    }
+                }
+                Ok(self.allowed.contains(id.trim_end_matches('+')))
+            }
+        }
+    }
+}
+
+/// Splits an SPDX expression into identifiers, operators, and parentheses. The legacy `/`
+/// separator found in older crates (`MIT/Apache-2.0`) is read as `OR`.
+fn tokenize_license(expression: &str) -> Vec<String> {
+    expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('/', " OR ")
+        .split_whitespace()
+        .map(|t| match t {
+            "or" => "OR".to_string(),
+            "and" => "AND".to_string(),
+            "with" => "WITH".to_string(),
+            other => other.to_string(),
+        })
+        .collect()
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    let dir = std::env::temp_dir().join("cargo_meta_example");
+    fs::create_dir_all(&dir).map_err(|source| CargoMetaError::Io { path: dir.clone(), source })?;
+
+    // A two-member workspace that ends up with two versions of `syn`.
+    let root_manifest = r#"
+        [workspace]
+        members = ["app", "derive"]
+        [workspace.package]
+        license = "MIT OR Apache-2.0"
+    "#;
+    let app_manifest = r#"
+        [package]
+        name = "app"
+        version = "0.1.0"
+        license.workspace = true
+        [dependencies]
+        json = { package = "serde_json", version = "1" }
+        derive = { path = "../derive" }
+        [dev-dependencies]
+        tempfile = "3"
+    "#;
+    let derive_manifest = r#"
+        [package]
+        name = "derive"
+        version = "0.1.0"
+        license = "MIT"
+        [dependencies]
+        syn = "1"
+        quote = "1"
+    "#;
+    let lock = r#"
+version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["derive", "serde_json", "tempfile"]
+
+[[package]]
+name = "derive"
+version = "0.1.0"
+dependencies = ["quote", "syn 1.0.109"]
+
+[[package]]
+name = "quote"
+version = "1.0.40"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "1885c039570dc00dcb4ff087a89e185fd56bae234ddc7f056a945bf36467248d"
+
+[[package]]
+name = "serde_json"
+version = "1.0.140"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["serde"]
+
+[[package]]
+name = "serde"
+version = "1.0.219"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["serde_derive"]
+
+[[package]]
+name = "serde_derive"
+version = "1.0.219"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["quote", "syn 2.0.100"]
+
+[[package]]
+name = "syn"
+version = "1.0.109"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "syn"
+version = "2.0.100"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "tempfile"
+version = "3.19.1"
+source = "git+https://github.com/Stebalien/tempfile#abc123"
+"#;
+    for (name, text) in [
+        ("Cargo.toml", root_manifest),
+        ("app.toml", app_manifest),
+        ("derive.toml", derive_manifest),
+        ("Cargo.lock", lock),
+    ] {
+        let path = dir.join(name);
+        fs::write(&path, text).map_err(|source| CargoMetaError::Io { path, source })?;
+    }
+
+    let manifests = vec![
+        load_manifest(&dir.join("Cargo.toml"))?,
+        load_manifest(&dir.join("app.toml"))?,
+        load_manifest(&dir.join("derive.toml"))?,
+    ];
+    let lock = load_lock(&dir.join("Cargo.lock"))?;
+    println!("Cargo.lock format v{}", lock.version.unwrap_or(1));
+    let report = report_from_lock(&lock, &manifests);
+
+    println!("--- Lock-only report ---");
+    println!("members: {:?}", report.workspace_members);
+    for package in &report.packages {
+        println!(
+            "  {:<14} {:<9} {:<8} direct={:?}",
+            package.name,
+            package.version,
+            format!("{:?}", package.origin).to_lowercase(),
+            package.direct
+        );
+    }
+    for duplicate in &report.duplicates {
+        println!("duplicate {}:", duplicate.name);
+        for version in &duplicate.versions {
+            println!("  {} <- {}", version.version, version.required_by.join(", "));
+        }
+    }
+    println!("{} crates without license data (lock files don't record it)", report.summary.unknown_license);
+
+    // The full picture, including licenses, comes from cargo itself.
+    println!("\n--- cargo metadata for the current directory ---");
+    match cargo_metadata(Path::new("."), &["--offline"]) {
+        Ok(metadata) => {
+            let report = report_from_metadata(&metadata);
+            println!("{:?}", report.summary);
+            for (license, crates) in report.licenses.iter().take(5) {
+                println!("  {:<30} {} crate(s)", license, crates.len());
+            }
+
+            let policy = LicensePolicy::new(["MIT", "Apache-2.0", "BSD-3-Clause", "Unicode-3.0", "Zlib"]);
+            let violations = policy.check(&report);
+            println!("{} license policy violation(s)", violations.len());
+            for violation in violations.iter().take(5) {
+                println!("  {} ({:?}): {}", violation.package, violation.license, violation.reason);
+            }
+
+            let json = serde_json::to_string_pretty(&report)?;
+            println!("JSON report: {} bytes", json.len());
+        }
+        Err(err) => println!("skipped: {}", err),
+    }
+
+    let policy = LicensePolicy::new(["MIT", "Apache-2.0"]);
+    for expression in ["MIT/Apache-2.0", "(MIT OR Apache-2.0) AND Unicode-3.0", "GPL-2.0 WITH Classpath-exception-2.0"] {
+        println!("{:<40} allowed={:?}", expression, policy.permits(expression)?);
+    }
+
+    fs::remove_dir_all(&dir).ok();
+    Ok(())
+}
+*/
@@ -0,0 +1,512 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// rand = "0.8"
+// rand_chacha = "0.3"
+// reqwest = { version = "0.12", features = ["blocking"] } // Only for the `HttpClient` impl.
+//
+// Fault injection for testing resilience code (retries, timeouts, circuit breakers) without
+// a flaky network or a broken disk:
+// * `Chaos` decides, from a seed, whether each call passes, is delayed, is cut short, or
+//   fails. The same seed gives the same sequence of faults on every run.
+// * `ChaosReader` / `ChaosWriter` wrap any `Read` / `Write` and return short reads, short
+//   writes, and `io::Error`s.
+// * `ChaosHttp` wraps any `HttpClient` and injects timeouts, connection errors, 5xx
+//   responses, and truncated bodies.
+// * `script` queues exact faults ahead of the random ones ("fail twice, then succeed"), and
+//   `stats` counts what was injected, so tests can assert on both.
+//
+// A `Chaos` is cheap to clone and clones share their state, so one seed can drive several
+// wrappers. Keep each seeded `Chaos` on one thread: with several threads the order of
+// decisions, and so the faults each call sees, depends on scheduling.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// --- Fault decisions ---
+
+/// What happens to one call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fault {
+    Pass,
+    /// Sleep, then perform the call normally.
+    Delay(Duration),
+    /// Transfer only this fraction (0.0-1.0) of the bytes; at least one byte is transferred.
+    Partial(f64),
+    /// Fail without touching the inner reader, writer, or client.
+    Error(io::ErrorKind),
+    /// HTTP only: answer with this status and an empty body instead of calling the server.
+    Status(u16),
+}
+
+/// How often each fault happens. Rates are probabilities per call and are checked in
+/// field order, so their sum should stay at or below 1.0.
+#[derive(Debug, Clone)]
+struct FaultConfig {
+    error_rate: f64,
+    /// Picked uniformly for injected errors.
+    error_kinds: Vec<io::ErrorKind>,
+    /// HTTP only; ignored by the I/O wrappers.
+    status_rate: f64,
+    statuses: Vec<u16>,
+    partial_rate: f64,
+    delay_rate: f64,
+    max_delay: Duration,
+}
+
+impl FaultConfig {
+    /// No faults at all; a starting point for the setters below.
+    fn none() -> Self {
+        Self {
+            error_rate: 0.0,
+            error_kinds: vec![io::ErrorKind::ConnectionReset, io::ErrorKind::TimedOut],
+            status_rate: 0.0,
+            statuses: vec![500, 502, 503],
+            partial_rate: 0.0,
+            delay_rate: 0.0,
+            max_delay: Duration::from_millis(10),
+        }
+    }
+
+    fn errors(mut self, rate: f64, kinds: &[io::ErrorKind]) -> Self {
+        self.error_rate = rate;
+        if !kinds.is_empty() {
+            self.error_kinds = kinds.to_vec();
+        }
+        self
+    }
+
+    fn statuses(mut self, rate: f64, statuses: &[u16]) -> Self {
+        self.status_rate = rate;
+        if !statuses.is_empty() {
+            self.statuses = statuses.to_vec();
+        }
+        self
+    }
+
+    fn partial(mut self, rate: f64) -> Self {
+        self.partial_rate = rate;
+        self
+    }
+
+    /// Delays of up to `max` (uniform). Keep `max` small: the sleeps are real.
+    fn delays(mut self, rate: f64, max: Duration) -> Self {
+        self.delay_rate = rate;
+        self.max_delay = max;
+        self
+    }
+}
+
+/// Counts of what `Chaos` has decided so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ChaosStats {
+    calls: u64,
+    delays: u64,
+    partials: u64,
+    errors: u64,
+    statuses: u64,
+}
+
+struct ChaosState {
+    rng: ChaCha8Rng,
+    config: FaultConfig,
+    script: VecDeque<Fault>,
+    enabled: bool,
+    stats: ChaosStats,
+}
+
+/// A seeded source of fault decisions, shared by every wrapper built from it.
+#[derive(Clone)]
+struct Chaos {
+    state: Arc<Mutex<ChaosState>>,
+    seed: u64,
+}
+
+impl Chaos {
+    /// Creates a fault source.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Determines the whole sequence of faults; print it when a test fails.
+    /// * `config` - How often each kind of fault happens.
+    fn new(seed: u64, config: FaultConfig) -> Self {
+        let state = ChaosState {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            config,
+            script: VecDeque::new(),
+            enabled: true,
+            stats: ChaosStats::default(),
+        };
+        Self { state: Arc::new(Mutex::new(state)), seed }
+    }
+
+    /// Seed from `CHAOS_SEED` if set, otherwise a random one (printed, so failures can be replayed).
+    fn from_env(config: FaultConfig) -> Self {
+        let seed = std::env::var("CHAOS_SEED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        eprintln!("chaos seed: {} (rerun with CHAOS_SEED={})", seed, seed);
+        Self::new(seed, config)
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChaosState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queues faults that are used, in order, before any random ones.
+    fn script(&self, faults: impl IntoIterator<Item = Fault>) -> &Self {
+        self.lock().script.extend(faults);
+        self
+    }
+
+    /// Turns random faults on or off, e.g. to set up state before the part under test.
+    /// Scripted faults still apply.
+    fn set_enabled(&self, enabled: bool) {
+        self.lock().enabled = enabled;
+    }
+
+    fn stats(&self) -> ChaosStats {
+        self.lock().stats
+    }
+
+    /// Decides the next fault. `Status` faults are only drawn for HTTP calls.
+    fn next_fault(&self, http: bool) -> Fault {
+        let mut state = self.lock();
+        let state = &mut *state;
+        let fault = match state.script.pop_front() {
+            Some(fault) => fault,
+            None if !state.enabled => Fault::Pass,
+            None => {
+                let config = &state.config;
+                let rng = &mut state.rng;
+                // Always draw the same number of values so earlier decisions don't shift later ones.
+                let roll: f64 = rng.gen();
+                let pick: usize = rng.gen();
+                let amount: f64 = rng.gen();
+                // Cumulative thresholds over the rates, in `FaultConfig` field order.
+                let errors_end = config.error_rate;
+                let statuses_end = errors_end + if http { config.status_rate } else { 0.0 };
+                let partials_end = statuses_end + config.partial_rate;
+                let delays_end = partials_end + config.delay_rate;
+                if roll < errors_end {
+                    Fault::Error(config.error_kinds[pick % config.error_kinds.len()])
+                } else if roll < statuses_end {
+                    Fault::Status(config.statuses[pick % config.statuses.len()])
+                } else if roll < partials_end {
+                    Fault::Partial(amount)
+                } else if roll < delays_end {
+                    Fault::Delay(config.max_delay.mul_f64(amount))
+                } else {
+                    Fault::Pass
+                }
+            }
+        };
+        let stats = &mut state.stats;
+        stats.calls += 1;
+        match fault {
+            Fault::Pass => {}
+            Fault::Delay(_) => stats.delays += 1,
+            Fault::Partial(_) => stats.partials += 1,
+            Fault::Error(_) => stats.errors += 1,
+            Fault::Status(_) => stats.statuses += 1,
+        }
+        fault
+    }
+
+    /// Applies a fault to an I/O call over `len` bytes: returns how many bytes to transfer.
+    fn io_fault(&self, len: usize) -> io::Result<usize> {
+        match self.next_fault(false) {
+            Fault::Pass | Fault::Status(_) => Ok(len),
+            Fault::Delay(delay) => {
+                std::thread::sleep(delay);
+                Ok(len)
+            }
+            Fault::Partial(fraction) => Ok(((len as f64 * fraction) as usize).clamp(1, len.max(1))),
+            Fault::Error(kind) => Err(io::Error::new(kind, "injected fault")),
+        }
+    }
+}
+
+// --- Read / Write wrappers ---
+
+/// A `Read` that returns short reads and errors according to its `Chaos`.
+///
+/// `read_to_end`, `read_exact`, and `BufReader` already cope with short reads and retry
+/// `ErrorKind::Interrupted`; code that calls `read` once and assumes a full buffer doesn't.
+struct ChaosReader<R> {
+    inner: R,
+    chaos: Chaos,
+}
+
+impl<R: Read> ChaosReader<R> {
+    fn new(inner: R, chaos: Chaos) -> Self {
+        Self { inner, chaos }
+    }
+
+    fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ChaosReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.read(buf);
+        }
+        let len = self.chaos.io_fault(buf.len())?;
+        self.inner.read(&mut buf[..len])
+    }
+}
+
+/// A `Write` that accepts only part of each buffer, or fails, according to its `Chaos`.
+/// `flush` is passed through unchanged.
+struct ChaosWriter<W> {
+    inner: W,
+    chaos: Chaos,
+}
+
+impl<W: Write> ChaosWriter<W> {
+    fn new(inner: W, chaos: Chaos) -> Self {
+        Self { inner, chaos }
+    }
+
+    fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for ChaosWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.write(buf);
+        }
+        let len = self.chaos.io_fault(buf.len())?;
+        self.inner.write(&buf[..len])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// --- HTTP ---
+
+#[derive(Debug, Clone)]
+struct HttpRequest {
+    method: String,
+    url: String,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn get(url: impl Into<String>) -> Self {
+        Self { method: "GET".into(), url: url.into(), body: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HttpResponse {
+    status: u16,
+    body: Vec<u8>,
+}
+
+#[derive(Debug)]
+enum HttpError {
+    Timeout,
+    /// Connection refused, reset, DNS failure, ...
+    Connect(String),
+    Other(String),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::Timeout => write!(f, "request timed out"),
+            HttpError::Connect(reason) => write!(f, "connection failed: {}", reason),
+            HttpError::Other(reason) => write!(f, "request failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// The seam between code that makes HTTP calls and the client that performs them. Take a
+/// `&dyn HttpClient` (or a generic) instead of a concrete client to make the code testable.
+trait HttpClient {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, HttpError>;
+}
+
+impl HttpClient for reqwest::blocking::Client {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, HttpError> {
+        let method = reqwest::Method::from_bytes(request.method.as_bytes()).map_err(|e| HttpError::Other(e.to_string()))?;
+        let response = self.request(method, &request.url).body(request.body.clone()).send().map_err(|e| {
+            if e.is_timeout() {
+                HttpError::Timeout
+            } else if e.is_connect() {
+                HttpError::Connect(e.to_string())
+            } else {
+                HttpError::Other(e.to_string())
+            }
+        })?;
+        let status = response.status().as_u16();
+        let body = response.bytes().map_err(|e| HttpError::Other(e.to_string()))?.to_vec();
+        Ok(HttpResponse { status, body })
+    }
+}
+
+/// Closures make quick stub clients: `|req: &HttpRequest| Ok(HttpResponse { .. })`.
+impl<F> HttpClient for F
+where
+    F: Fn(&HttpRequest) -> Result<HttpResponse, HttpError>,
+{
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, HttpError> {
+        self(request)
+    }
+}
+
+/// An `HttpClient` that injects faults in front of another one.
+///
+/// * `Error(TimedOut)` becomes `HttpError::Timeout`; other error kinds become `Connect`.
+///   Neither reaches the inner client.
+/// * `Status(code)` returns that status with an empty body, without reaching the inner client.
+/// * `Partial(f)` calls the inner client and truncates the response body, as if the
+///   connection dropped mid-transfer and the client didn't notice.
+/// * `Delay(d)` sleeps before calling the inner client, to trip client-side deadlines.
+struct ChaosHttp<C> {
+    inner: C,
+    chaos: Chaos,
+}
+
+impl<C: HttpClient> ChaosHttp<C> {
+    fn new(inner: C, chaos: Chaos) -> Self {
+        Self { inner, chaos }
+    }
+}
+
+impl<C: HttpClient> HttpClient for ChaosHttp<C> {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, HttpError> {
+        match self.chaos.next_fault(true) {
+            Fault::Pass => self.inner.send(request),
+            Fault::Delay(delay) => {
+                std::thread::sleep(delay);
+                self.inner.send(request)
+            }
+            Fault::Partial(fraction) => {
+                let mut response = self.inner.send(request)?;
+                response.body.truncate((response.body.len() as f64 * fraction) as usize);
+                Ok(response)
+            }
+            Fault::Error(io::ErrorKind::TimedOut) => Err(HttpError::Timeout),
+            Fault::Error(kind) => Err(HttpError::Connect(format!("injected {:?}", kind))),
+            Fault::Status(status) => Ok(HttpResponse { status, body: Vec::new() }),
+        }
+    }
+}
+
+// Example Usage
+/*
+/// The code under test: retries transient failures with a small fixed backoff.
+fn fetch_with_retry(client: &dyn HttpClient, url: &str, max_attempts: u32) -> Result<HttpResponse, String> {
+    let mut last_error = String::new();
+    for attempt in 1..=max_attempts {
+        match client.send(&HttpRequest::get(url)) {
+            Ok(response) if response.status < 500 => return Ok(response),
+            Ok(response) => last_error = format!("HTTP {}", response.status),
+            Err(HttpError::Other(e)) => return Err(e), // Not worth retrying.
+            Err(e) => last_error = e.to_string(),
+        }
+        if attempt < max_attempts {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+    Err(format!("gave up after {} attempts: {}", max_attempts, last_error))
+}
+
+/// Opens after `threshold` consecutive failures and rejects calls while open.
+struct CircuitBreaker {
+    failures: u32,
+    threshold: u32,
+}
+
+impl CircuitBreaker {
+    fn call(&mut self, client: &dyn HttpClient, url: &str) -> Result<HttpResponse, String> {
+        if self.failures >= self.threshold {
+            return Err("circuit open".into());
+        }
+        match client.send(&HttpRequest::get(url)) {
+            Ok(response) if response.status < 500 => {
+                self.failures = 0;
+                Ok(response)
+            }
+            Ok(response) => {
+                self.failures += 1;
+                Err(format!("HTTP {}", response.status))
+            }
+            Err(e) => {
+                self.failures += 1;
+                Err(e.to_string())
+            }
+        }
+    }
+}
+
+fn main() {
+    let backend = |request: &HttpRequest| -> Result<HttpResponse, HttpError> {
+        Ok(HttpResponse { status: 200, body: format!("hello from {}", request.url).into_bytes() })
+    };
+
+    // Exact scenario: two transient failures, then success.
+    let chaos = Chaos::new(1, FaultConfig::none());
+    chaos.script([Fault::Error(io::ErrorKind::TimedOut), Fault::Status(503)]);
+    let client = ChaosHttp::new(backend, chaos.clone());
+    let result = fetch_with_retry(&client, "http://api.test/users", 3);
+    println!("scripted: {:?} after {:?}", result.map(|r| r.status), chaos.stats());
+
+    // Random faults: the same seed reproduces the same outcomes on every run.
+    for seed in [7, 7, 8] {
+        let config = FaultConfig::none().errors(0.3, &[]).statuses(0.2, &[503]);
+        let chaos = Chaos::new(seed, config);
+        let client = ChaosHttp::new(backend, chaos.clone());
+        let outcomes: Vec<bool> = (0..10).map(|_| fetch_with_retry(&client, "http://api.test/", 3).is_ok()).collect();
+        println!("seed {}: {:?} {:?}", seed, outcomes, chaos.stats());
+    }
+
+    // The breaker opens once the backend is down, and stops sending it traffic.
+    let chaos = Chaos::new(3, FaultConfig::none().errors(1.0, &[io::ErrorKind::ConnectionRefused]));
+    let client = ChaosHttp::new(backend, chaos.clone());
+    let mut breaker = CircuitBreaker { failures: 0, threshold: 3 };
+    let results: Vec<String> = (0..5).map(|_| breaker.call(&client, "http://api.test/").unwrap_err()).collect();
+    println!("breaker: {:?}; only {} calls reached the client", results, chaos.stats().calls);
+
+    // Short reads and Interrupted errors: `read_to_end` still gets every byte.
+    let data: Vec<u8> = (0..=255).collect();
+    let chaos = Chaos::new(42, FaultConfig::none().partial(0.7).errors(0.2, &[io::ErrorKind::Interrupted]));
+    let mut reader = ChaosReader::new(&data[..], chaos.clone());
+    let mut copy = Vec::new();
+    reader.read_to_end(&mut copy).unwrap();
+    println!("read_to_end intact: {} {:?}", copy == data, chaos.stats());
+
+    // Code that assumes one `read` fills the buffer breaks under the same faults.
+    let mut reader = ChaosReader::new(&data[..], Chaos::new(42, FaultConfig::none().partial(1.0)));
+    let mut header = [0u8; 16];
+    let n = reader.read(&mut header).unwrap();
+    println!("single read of a 16-byte header got {} bytes", n);
+
+    // `write_all` loops over short writes but gives up on real errors.
+    let chaos = Chaos::new(5, FaultConfig::none().partial(0.8));
+    let mut writer = ChaosWriter::new(Vec::new(), chaos.clone());
+    writer.write_all(b"all of this must arrive").unwrap();
+    println!("write_all: {:?} in {} writes", String::from_utf8(writer.into_inner()).unwrap(), chaos.stats().calls);
+    let mut writer = ChaosWriter::new(Vec::new(), Chaos::new(5, FaultConfig::none().errors(1.0, &[io::ErrorKind::BrokenPipe])));
+    println!("broken pipe: {:?}", writer.write_all(b"lost").map_err(|e| e.kind()));
+}
+*/
@@ -0,0 +1,388 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// clap = { version = "4.0", features = ["derive"] }
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+//
+// A `--json` mode for command-line tools, so scripts can drive them without scraping text:
+// * `OutputArgs` is a clap flag group (`--json`) to `#[command(flatten)]` into any CLI, like the
+//   ones in `clap_basic_args.rs`, `job_queue.rs`, and `plugins.rs`.
+// * With `--json`, stdout carries exactly one envelope: `{"ok":true,"data":...}` on success or
+//   `{"ok":false,"error":{"code":"...","message":"..."}}` on failure. `code` is a fixed
+//   snake_case identifier meant for `if` statements; `message` is for people and may change.
+// * Long operations stream NDJSON instead: one `{"type":"<event>","data":...}` line per event,
+//   flushed as it happens, then a final `{"type":"result","ok":...}` line with the envelope.
+// * Without `--json`, the same data is printed with its `Display` impl and errors go to stderr.
+//   Notes and warnings always go to stderr, so they never corrupt the JSON on stdout.
+// * Exit codes follow the envelope: 0 for `ok`, the error's exit code otherwise (2 for usage
+//   errors, as clap does). `parse_args` reports clap's own errors in the envelope too.
+// * A closed stdout (`mytool --json | head -1`) ends the program quietly instead of panicking.
+
+use clap::{Args, Parser};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+// --- Errors ---
+
+/// An error with a stable, machine-readable code.
+#[derive(Debug, Clone, Serialize)]
+struct CliError {
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
+    #[serde(skip)]
+    exit_code: u8,
+}
+
+impl CliError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        CliError { code, message: message.into(), details: None, exit_code: 1 }
+    }
+
+    fn usage(message: impl Into<String>) -> Self {
+        Self::new("usage", message).exit_code(2)
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new("not_found", message).exit_code(3)
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self::new("internal", message).exit_code(70)
+    }
+
+    /// Structured context for scripts, e.g. `{"path": "..."}`; shown only in JSON mode.
+    fn details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    fn exit_code(mut self, code: u8) -> Self {
+        self.exit_code = code;
+        self
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        let code = match e.kind() {
+            io::ErrorKind::NotFound => "not_found",
+            io::ErrorKind::PermissionDenied => "permission_denied",
+            _ => "io",
+        };
+        CliError::new(code, e.to_string()).exit_code(if code == "not_found" { 3 } else { 74 })
+    }
+}
+
+type Result<T> = std::result::Result<T, CliError>;
+
+// --- Envelopes ---
+
+#[derive(Serialize)]
+struct Envelope<'a, T: Serialize> {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<&'a T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a CliError>,
+}
+
+impl<'a, T: Serialize> Envelope<'a, T> {
+    fn from_result(result: &'a Result<T>) -> Self {
+        match result {
+            Ok(data) => Envelope { ok: true, data: Some(data), error: None },
+            Err(error) => Envelope { ok: false, data: None, error: Some(error) },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StreamLine<'a, T: Serialize> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    data: &'a T,
+}
+
+#[derive(Serialize)]
+struct ResultLine<'a, T: Serialize> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(flatten)]
+    envelope: Envelope<'a, T>,
+}
+
+// --- Output ---
+
+/// The `--json` flag; add to a CLI with `#[command(flatten)] output: OutputArgs`.
+#[derive(Args, Debug, Clone, Copy, Default)]
+struct OutputArgs {
+    /// Print a JSON envelope on stdout instead of human-readable output
+    #[arg(long, global = true)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Output {
+    json: bool,
+}
+
+impl From<OutputArgs> for Output {
+    fn from(args: OutputArgs) -> Self {
+        Output { json: args.json }
+    }
+}
+
+/// Writes a line to stdout, exiting quietly if the reader has gone away.
+fn write_stdout(line: &str) {
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = writeln!(stdout, "{}", line).and_then(|_| stdout.flush()) {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            std::process::exit(0);
+        }
+        eprintln!("error writing output: {}", e);
+        std::process::exit(74);
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|e| {
+        // Only a broken `Serialize` impl (e.g. a map with non-string keys) gets here.
+        let error = CliError::internal(format!("cannot serialize output: {}", e));
+        serde_json::to_string(&Envelope::<()> { ok: false, data: None, error: Some(&error) }).unwrap()
+    })
+}
+
+fn exit_code_for<T>(result: &Result<T>) -> ExitCode {
+    match result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => ExitCode::from(e.exit_code),
+    }
+}
+
+impl Output {
+    /// Prints a command's result and returns the matching exit code; return it from `main`.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The data to print, formatted with `Display` in human mode, or the error.
+    fn finish<T: Serialize + fmt::Display>(&self, result: Result<T>) -> ExitCode {
+        if self.json {
+            write_stdout(&to_json(&Envelope::from_result(&result)));
+        } else {
+            match &result {
+                Ok(data) => write_stdout(&data.to_string()),
+                Err(e) => eprintln!("error: {}", e),
+            }
+        }
+        exit_code_for(&result)
+    }
+
+    /// Progress and warnings for people; stderr in both modes.
+    fn note(&self, message: impl fmt::Display) {
+        eprintln!("{}", message);
+    }
+
+    /// Starts an NDJSON stream for an operation that reports as it goes.
+    fn stream(&self) -> EventStream {
+        EventStream { json: self.json, events: 0 }
+    }
+}
+
+/// Emits one line per event; finish with `EventStream::finish`.
+struct EventStream {
+    json: bool,
+    events: u64,
+}
+
+impl EventStream {
+    /// Reports one event, e.g. `emit("file", &entry)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The `type` field: a stable snake_case name scripts can filter on.
+    /// * `data` - Serialized in JSON mode; printed with `Display` otherwise.
+    fn emit<T: Serialize + fmt::Display>(&mut self, kind: &str, data: &T) {
+        debug_assert!(kind != "result", "\"result\" is reserved for the final line");
+        self.events += 1;
+        if self.json {
+            write_stdout(&to_json(&StreamLine { kind, data }));
+        } else {
+            write_stdout(&data.to_string());
+        }
+    }
+
+    fn events(&self) -> u64 {
+        self.events
+    }
+
+    /// Writes the final `{"type":"result",...}` line and returns the exit code.
+    fn finish<T: Serialize + fmt::Display>(self, result: Result<T>) -> ExitCode {
+        if self.json {
+            write_stdout(&to_json(&ResultLine { kind: "result", envelope: Envelope::from_result(&result) }));
+        } else {
+            match &result {
+                Ok(summary) => write_stdout(&summary.to_string()),
+                Err(e) => eprintln!("error: {}", e),
+            }
+        }
+        exit_code_for(&result)
+    }
+}
+
+// --- Argument parsing ---
+
+/// Like `C::parse_from(args)`, but a usage error becomes an envelope when `--json` was passed.
+/// `--help` and `--version` still print normally.
+///
+/// # Returns
+///
+/// The parsed CLI, or the exit code to return from `main`.
+fn parse_args<C, I>(args: I) -> std::result::Result<C, ExitCode>
+where
+    C: Parser,
+    I: IntoIterator,
+    I::Item: Into<std::ffi::OsString> + Clone,
+{
+    let args: Vec<std::ffi::OsString> = args.into_iter().map(Into::into).collect();
+    match C::try_parse_from(&args) {
+        Ok(cli) => Ok(cli),
+        Err(e) if !e.use_stderr() => {
+            let _ = e.print();
+            Err(ExitCode::SUCCESS)
+        }
+        Err(e) if args.iter().skip(1).any(|arg| arg == "--json") => {
+            // Drop clap's "Usage: ..." and "For more information" paragraphs.
+            let rendered = e.render().to_string();
+            let first = rendered.split("\n\n").next().unwrap_or("").trim_start_matches("error: ");
+            let error = CliError::usage(first.split_whitespace().collect::<Vec<_>>().join(" ")).details(serde_json::json!({ "kind": format!("{:?}", e.kind()) }));
+            Err(Output { json: true }.finish::<String>(Err(error)))
+        }
+        Err(e) => {
+            let _ = e.print();
+            Err(ExitCode::from(2))
+        }
+    }
+}
+
+// Example Usage
+/*
+use clap::Subcommand;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "dirtool")]
+struct Cli {
+    #[command(flatten)]
+    output: OutputArgs,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Count files and bytes in a directory
+    Stat { dir: PathBuf },
+    /// List files one by one, then a summary
+    Scan { dir: PathBuf },
+}
+
+#[derive(Serialize)]
+struct DirStats {
+    files: u64,
+    bytes: u64,
+}
+
+impl fmt::Display for DirStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} files, {} bytes", self.files, self.bytes)
+    }
+}
+
+#[derive(Serialize)]
+struct FileEvent {
+    path: String,
+    bytes: u64,
+}
+
+impl fmt::Display for FileEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>8}  {}", self.bytes, self.path)
+    }
+}
+
+fn read_dir(dir: &PathBuf) -> Result<Vec<fs::DirEntry>> {
+    let entries = fs::read_dir(dir).map_err(|e| CliError::from(e).details(serde_json::json!({ "path": dir })))?;
+    let mut entries: Vec<_> = entries.collect::<io::Result<_>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    Ok(entries)
+}
+
+fn stat(dir: &PathBuf) -> Result<DirStats> {
+    let mut stats = DirStats { files: 0, bytes: 0 };
+    for entry in read_dir(dir)? {
+        stats.files += 1;
+        stats.bytes += entry.metadata()?.len();
+    }
+    Ok(stats)
+}
+
+fn run(args: &[&str]) -> ExitCode {
+    eprintln!("$ dirtool {}", args[1..].join(" "));
+    let cli: Cli = match parse_args(args) {
+        Ok(cli) => cli,
+        Err(code) => return code,
+    };
+    let out = Output::from(cli.output);
+    match cli.command {
+        Command::Stat { dir } => out.finish(stat(&dir)),
+        Command::Scan { dir } => {
+            out.note(format!("scanning {}", dir.display()));
+            let mut stream = out.stream();
+            let result = read_dir(&dir).and_then(|entries| {
+                let mut bytes = 0;
+                for entry in entries {
+                    let len = entry.metadata()?.len();
+                    bytes += len;
+                    stream.emit("file", &FileEvent { path: entry.file_name().to_string_lossy().into_owned(), bytes: len });
+                }
+                Ok(DirStats { files: stream.events(), bytes })
+            });
+            stream.finish(result)
+        }
+    }
+}
+
+fn main() {
+    let dir = std::env::temp_dir().join(format!("cli-output-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.txt"), "hello").unwrap();
+    fs::write(dir.join("b.log"), "x".repeat(1200)).unwrap();
+    let d = dir.to_str().unwrap();
+
+    for args in [
+        vec!["dirtool", "stat", d],
+        vec!["dirtool", "--json", "stat", d],
+        vec!["dirtool", "scan", d],
+        vec!["dirtool", "scan", d, "--json"],
+        vec!["dirtool", "--json", "stat", "/does/not/exist"],
+        vec!["dirtool", "--json", "stat"],
+    ] {
+        let code = run(&args);
+        eprintln!("exit: {:?}\n", code);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+*/
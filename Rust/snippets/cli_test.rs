@@ -0,0 +1,433 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dev-dependencies]
+// regex = "1"
+// similar = "2"
+//
+// Integration tests for command-line binaries, in the style of `execute_command.rs` but with
+// the pieces a test needs around it:
+// * `cargo_bin!("name")` finds the binary Cargo built for this package's `[[bin]]` target.
+//   Integration tests in `tests/` get `CARGO_BIN_EXE_<name>` at compile time; `cargo_bin`
+//   is the runtime fallback (next to the test executable, in `target/<profile>/`).
+// * `CliTest` runs it with args, stdin, env vars, a working directory, and a timeout.
+//   stdin is written on its own thread, so a child that fills its stdout pipe before reading
+//   all of its input can't deadlock the test.
+// * `TempHome` gives each test its own `HOME` and XDG config/data/cache directories. They are
+//   set only on the child, so tests can run in parallel without the `TestEnv` lock from
+//   `testing.rs`.
+// * `CliOutput`'s `assert_*` methods check the exit code, stdout, and stderr with substrings,
+//   regexes, or golden files (`UPDATE_SNAPSHOTS=1` rewrites them, as in `testing.rs`). A
+//   failure prints the command line and everything it wrote.
+
+use regex::Regex;
+use similar::TextDiff;
+use std::ffi::OsStr;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// --- Locating the binary ---
+
+/// Path of a binary built from this package. Only works in integration tests (`tests/*.rs`),
+/// where Cargo sets `CARGO_BIN_EXE_<name>` and builds the binary first.
+macro_rules! cargo_bin {
+    ($name:literal) => {
+        std::path::PathBuf::from(env!(concat!("CARGO_BIN_EXE_", $name)))
+    };
+}
+
+/// Finds `name` in the directory Cargo builds binaries into, for code that can't use the macro
+/// (unit tests, or a binary from another package in the workspace). Build it first.
+fn cargo_bin(name: &str) -> io::Result<PathBuf> {
+    // Test executables live in `target/<profile>/deps/`; binaries one level up.
+    let exe = std::env::current_exe()?;
+    let mut dir = exe.parent().map(Path::to_path_buf).unwrap_or_default();
+    if dir.ends_with("deps") {
+        dir.pop();
+    }
+    let path = dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX));
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} not found; run `cargo build --bin {}` first", path.display(), name),
+        ))
+    }
+}
+
+// --- Per-test home directory ---
+
+/// A throwaway home directory with XDG config, data, and cache directories inside it.
+/// Removed on drop.
+struct TempHome {
+    root: PathBuf,
+}
+
+impl TempHome {
+    fn new() -> io::Result<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let root = std::env::temp_dir().join(format!("cli-test-{}-{}", std::process::id(), n));
+        for dir in ["home", "config", "data", "cache"] {
+            fs::create_dir_all(root.join(dir))?;
+        }
+        Ok(Self { root })
+    }
+
+    fn home(&self) -> PathBuf {
+        self.root.join("home")
+    }
+
+    /// `$XDG_CONFIG_HOME`; put `<app>/config.toml` here for tools that use the `dirs` crate.
+    fn config_dir(&self) -> PathBuf {
+        self.root.join("config")
+    }
+
+    fn data_dir(&self) -> PathBuf {
+        self.root.join("data")
+    }
+
+    /// Writes a file relative to the config directory, creating parent directories.
+    fn write_config(&self, relative: impl AsRef<Path>, contents: &str) -> io::Result<PathBuf> {
+        let path = self.config_dir().join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    /// The variables that point a child process at this home (Unix and Windows names).
+    fn env_vars(&self) -> Vec<(&'static str, PathBuf)> {
+        vec![
+            ("HOME", self.home()),
+            ("USERPROFILE", self.home()),
+            ("XDG_CONFIG_HOME", self.config_dir()),
+            ("APPDATA", self.config_dir()),
+            ("XDG_DATA_HOME", self.data_dir()),
+            ("LOCALAPPDATA", self.data_dir()),
+            ("XDG_CACHE_HOME", self.root.join("cache")),
+        ]
+    }
+}
+
+impl Drop for TempHome {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+// --- Running the binary ---
+
+/// One invocation of a binary under test. Configure with the builder methods, then `run`.
+struct CliTest<'a> {
+    command: Command,
+    display: String,
+    stdin: Option<Vec<u8>>,
+    timeout: Duration,
+    home: Option<&'a TempHome>,
+}
+
+impl<'a> CliTest<'a> {
+    fn new(program: impl AsRef<OsStr>) -> Self {
+        let program = program.as_ref();
+        let mut command = Command::new(program);
+        // Keep output stable: no colors, no localized messages.
+        command.env("NO_COLOR", "1").env("LC_ALL", "C");
+        Self {
+            command,
+            display: program.to_string_lossy().into_owned(),
+            stdin: None,
+            timeout: Duration::from_secs(30),
+            home: None,
+        }
+    }
+
+    fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        let text = arg.as_ref().to_string_lossy();
+        if text.is_empty() || text.contains(char::is_whitespace) {
+            let _ = write!(self.display, " {:?}", text);
+        } else {
+            let _ = write!(self.display, " {}", text);
+        }
+        self.command.arg(arg);
+        self
+    }
+
+    fn args<I, S>(self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        args.into_iter().fold(self, |test, arg| test.arg(arg))
+    }
+
+    fn stdin(mut self, input: impl Into<Vec<u8>>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
+    fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.command.env(key, value);
+        self
+    }
+
+    fn env_remove(mut self, key: impl AsRef<OsStr>) -> Self {
+        self.command.env_remove(key);
+        self
+    }
+
+    fn current_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.command.current_dir(dir);
+        self
+    }
+
+    /// Kills the child and fails the test if it runs longer than this (default 30s).
+    fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Points `HOME` and the XDG/AppData directories at `home`, and lets the golden-file
+    /// assertions replace its path with `[HOME]`.
+    fn home(mut self, home: &'a TempHome) -> Self {
+        for (key, value) in home.env_vars() {
+            self.command.env(key, value);
+        }
+        self.home = Some(home);
+        self
+    }
+
+    /// Runs the command to completion.
+    ///
+    /// # Returns
+    ///
+    /// The captured output, or an error if the program couldn't start or hit the timeout.
+    fn run(mut self) -> io::Result<CliOutput> {
+        self.command
+            .stdin(if self.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let started = Instant::now();
+        let mut child = self
+            .command
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("could not start `{}`: {}", self.display, e)))?;
+
+        let writer = match (self.stdin.take(), child.stdin.take()) {
+            (Some(input), Some(mut pipe)) => Some(thread::spawn(move || {
+                // A child that exits without reading everything closes the pipe; that's not a test failure.
+                let _ = pipe.write_all(&input);
+            })),
+            _ => None,
+        };
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let status = wait_with_timeout(&mut child, self.timeout).map_err(|e| {
+            io::Error::new(e.kind(), format!("`{}` {}", self.display, e))
+        })?;
+        if let Some(writer) = writer {
+            let _ = writer.join();
+        }
+        Ok(CliOutput {
+            command: self.display,
+            status,
+            stdout: String::from_utf8_lossy(&stdout.join().unwrap_or_default()).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr.join().unwrap_or_default()).into_owned(),
+            duration: started.elapsed(),
+            home: self.home.map(|home| home.root.clone()),
+        })
+    }
+}
+
+/// Reads a pipe to the end on its own thread.
+fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> io::Result<ExitStatus> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(io::ErrorKind::TimedOut, format!("timed out after {:?}", timeout)));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+// --- Assertions ---
+
+/// What a finished command wrote and how it exited.
+#[derive(Debug)]
+struct CliOutput {
+    command: String,
+    status: ExitStatus,
+    stdout: String,
+    stderr: String,
+    duration: Duration,
+    home: Option<PathBuf>,
+}
+
+impl CliOutput {
+    #[track_caller]
+    fn fail(&self, message: &str) -> ! {
+        panic!(
+            "{}\n  command: {}\n  exit: {}\n--- stdout ---\n{}\n--- stderr ---\n{}",
+            message, self.command, self.status, self.stdout, self.stderr
+        )
+    }
+
+    #[track_caller]
+    fn assert_success(&self) -> &Self {
+        if !self.status.success() {
+            self.fail("expected the command to succeed");
+        }
+        self
+    }
+
+    /// Exit code `code`. Fails if the process was killed by a signal.
+    #[track_caller]
+    fn assert_code(&self, code: i32) -> &Self {
+        if self.status.code() != Some(code) {
+            self.fail(&format!("expected exit code {}", code));
+        }
+        self
+    }
+
+    #[track_caller]
+    fn assert_stdout_contains(&self, needle: &str) -> &Self {
+        if !self.stdout.contains(needle) {
+            self.fail(&format!("expected stdout to contain {:?}", needle));
+        }
+        self
+    }
+
+    #[track_caller]
+    fn assert_stderr_contains(&self, needle: &str) -> &Self {
+        if !self.stderr.contains(needle) {
+            self.fail(&format!("expected stderr to contain {:?}", needle));
+        }
+        self
+    }
+
+    /// Matches `pattern` anywhere in stdout; use `(?m)^...$` to match whole lines.
+    #[track_caller]
+    fn assert_stdout_matches(&self, pattern: &str) -> &Self {
+        let regex = Regex::new(pattern).unwrap_or_else(|e| panic!("invalid pattern {:?}: {}", pattern, e));
+        if !regex.is_match(&self.stdout) {
+            self.fail(&format!("expected stdout to match /{}/", pattern));
+        }
+        self
+    }
+
+    #[track_caller]
+    fn assert_stderr_matches(&self, pattern: &str) -> &Self {
+        let regex = Regex::new(pattern).unwrap_or_else(|e| panic!("invalid pattern {:?}: {}", pattern, e));
+        if !regex.is_match(&self.stderr) {
+            self.fail(&format!("expected stderr to match /{}/", pattern));
+        }
+        self
+    }
+
+    /// Compares stdout with a golden file, after replacing the temp home path with `[HOME]`
+    /// and Windows line endings with `\n`. With `UPDATE_SNAPSHOTS=1`, writes the file instead.
+    #[track_caller]
+    fn assert_stdout_golden(&self, path: impl AsRef<Path>) -> &Self {
+        let path = path.as_ref();
+        let mut actual = self.stdout.replace("\r\n", "\n");
+        if let Some(home) = &self.home {
+            actual = actual.replace(&*home.to_string_lossy(), "[HOME]");
+        }
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some_and(|v| v != "0") {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).expect("create golden file directory");
+            }
+            fs::write(path, &actual).expect("write golden file");
+            return self;
+        }
+        let expected = match fs::read_to_string(path) {
+            Ok(expected) => expected.replace("\r\n", "\n"),
+            Err(e) => self.fail(&format!("golden file {}: {} (rerun with UPDATE_SNAPSHOTS=1)", path.display(), e)),
+        };
+        if expected != actual {
+            let diff = TextDiff::from_lines(&expected, &actual);
+            self.fail(&format!(
+                "stdout differs from {} (rerun with UPDATE_SNAPSHOTS=1 to accept):\n{}",
+                path.display(),
+                diff.unified_diff().header("expected", "actual")
+            ));
+        }
+        self
+    }
+}
+
+/// Shorthand for a `CliTest` with `args`.
+fn cli<I, S>(program: impl AsRef<OsStr>, args: I) -> CliTest<'static>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    CliTest::new(program).args(args)
+}
+
+// Example Usage
+/*
+// In a real integration test the program is `cargo_bin!("mytool")`; `sh` stands in for it here.
+fn main() -> io::Result<()> {
+    let tool = "sh";
+    let script = r#"
+        case "$1" in
+            greet) echo "Hello, ${NAME:-world}!" ;;
+            config) cat "$XDG_CONFIG_HOME/mytool/config.toml"; echo "home=$HOME" ;;
+            upper) tr a-z A-Z ;;
+            *) echo "error: unknown command '$1'" >&2; exit 2 ;;
+        esac
+    "#;
+    let run = |args: &[&str]| CliTest::new(tool).arg("-c").arg(script).arg("mytool").args(args);
+
+    run(&["greet"]).env("NAME", "Ada").run()?.assert_success().assert_stdout_contains("Hello, Ada!");
+    run(&["upper"]).stdin("quiet please\n").run()?.assert_success().assert_stdout_matches(r"(?m)^QUIET PLEASE$");
+    let out = run(&["frobnicate"]).run()?;
+    out.assert_code(2).assert_stderr_matches(r"unknown command '\w+'");
+    println!("usage error in {:?}: {}", out.duration, out.stderr.trim());
+
+    // A private home per test, with a config file in it.
+    let home = TempHome::new()?;
+    home.write_config("mytool/config.toml", "theme = \"dark\"\n")?;
+    let golden = std::env::temp_dir().join("cli-test-example/config.stdout");
+    std::env::set_var("UPDATE_SNAPSHOTS", "1"); // First run records the golden file...
+    run(&["config"]).home(&home).run()?.assert_success().assert_stdout_golden(&golden);
+    std::env::remove_var("UPDATE_SNAPSHOTS");
+    run(&["config"]).home(&home).run()?.assert_stdout_golden(&golden); // ...later runs compare.
+    print!("golden file:\n{}", fs::read_to_string(&golden)?);
+
+    // Timeouts kill hung processes instead of hanging the test suite.
+    let hung = cli("sleep", ["5"]).timeout(Duration::from_millis(200)).run();
+    println!("hung: {}", hung.unwrap_err());
+
+    // Failures show the whole picture.
+    let failure = std::panic::catch_unwind(|| {
+        run(&["greet"]).run().unwrap().assert_stdout_contains("Goodbye");
+    });
+    println!("failed as expected: {}", failure.is_err());
+    println!("cargo_bin lookup: {:?}", cargo_bin("mytool").map_err(|e| e.to_string()));
+    Ok(())
+}
+*/
@@ -0,0 +1,337 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// arboard = { version = "3", features = ["wayland-data-control"] }
+// base64 = "0.22" // Only for `osc52_copy`
+// thiserror = "1.0"
+//
+// Copy and paste for command-line tools, on the desktop and off it:
+// * `Clipboard::open()` uses the system clipboard through `arboard` (X11 and Wayland on Linux,
+//   macOS, Windows) and falls back to a plain file when there is no clipboard to talk to: an
+//   SSH session, a container, CI, or a Linux box without `DISPLAY`/`WAYLAND_DISPLAY`. Setting
+//   `CLIPBOARD_FILE` forces the file backend, which also makes scripts and tests deterministic.
+// * `get_text` returns `Ok(None)` for an empty clipboard or one holding something that isn't
+//   text, so "nothing to paste" is not an error. `set_text`, `clear`, and the RGBA
+//   `get_image`/`set_image` pair cover the rest; images need the system backend.
+// * On X11 and Wayland the process that copied owns the contents, which vanish when it exits.
+//   `set_text_and_wait` keeps serving them for a while so `mytool --copy` works as users
+//   expect. It is a no-op wait on macOS and Windows, where the OS keeps a copy.
+// * `osc52_copy` asks the terminal itself to copy, which reaches the user's local clipboard
+//   even through SSH and tmux (when the terminal allows it).
+//
+// The fallback file lives in `$XDG_RUNTIME_DIR` (or the temp dir), is written atomically, and is
+// readable only by the current user on Unix. Pair with `cli_output.rs` for `--copy` style flags
+// and `clap_basic_args.rs` for the argument parsing.
+
+use std::borrow::Cow;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum ClipboardError {
+    #[error("the {backend} clipboard does not support {what}")]
+    Unsupported { backend: &'static str, what: &'static str },
+    #[error("clipboard busy or unavailable: {0}")]
+    System(#[from] arboard::Error),
+    #[error("clipboard file {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+type Result<T> = std::result::Result<T, ClipboardError>;
+
+/// An RGBA8 image, four bytes per pixel, rows top to bottom.
+#[derive(Debug, Clone, PartialEq)]
+struct Image {
+    width: usize,
+    height: usize,
+    rgba: Vec<u8>,
+}
+
+// --- Backend Selection ---
+
+enum Backend {
+    System(arboard::Clipboard),
+    File(PathBuf),
+}
+
+struct Clipboard {
+    backend: Backend,
+    /// Why the system clipboard was not used, for `--verbose` output.
+    fallback_reason: Option<String>,
+}
+
+impl fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Clipboard");
+        match &self.backend {
+            Backend::System(_) => s.field("backend", &"system"),
+            Backend::File(path) => s.field("backend", &"file").field("path", path),
+        };
+        s.field("fallback_reason", &self.fallback_reason).finish()
+    }
+}
+
+/// Default location of the fallback file: `$CLIPBOARD_FILE`, else `$XDG_RUNTIME_DIR/clipboard.txt`,
+/// else a per-user file in the temp dir.
+fn default_file() -> PathBuf {
+    if let Some(path) = env::var_os("CLIPBOARD_FILE").filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+    if let Some(dir) = env::var_os("XDG_RUNTIME_DIR").filter(|p| !p.is_empty()) {
+        return PathBuf::from(dir).join("clipboard.txt");
+    }
+    let user = env::var("USER").or_else(|_| env::var("USERNAME")).unwrap_or_else(|_| "user".into());
+    env::temp_dir().join(format!("clipboard-{}.txt", user))
+}
+
+/// Returns a reason when this is a Linux/BSD session with no display server to own a clipboard.
+fn headless_reason() -> Option<String> {
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        let set = |name| env::var_os(name).is_some_and(|v| !v.is_empty());
+        if !set("DISPLAY") && !set("WAYLAND_DISPLAY") {
+            return Some("no DISPLAY or WAYLAND_DISPLAY".to_string());
+        }
+    }
+    None
+}
+
+impl Clipboard {
+    /// Opens the system clipboard, or the file fallback when there isn't one.
+    ///
+    /// # Returns
+    /// A clipboard that always works; check `is_system()` or `fallback_reason()` to tell
+    /// the user their copy only went to a file.
+    fn open() -> Self {
+        if env::var_os("CLIPBOARD_FILE").is_some_and(|p| !p.is_empty()) {
+            return Self::file(default_file(), Some("CLIPBOARD_FILE is set".to_string()));
+        }
+        if let Some(reason) = headless_reason() {
+            return Self::file(default_file(), Some(reason));
+        }
+        match arboard::Clipboard::new() {
+            Ok(clipboard) => Self { backend: Backend::System(clipboard), fallback_reason: None },
+            Err(e) => Self::file(default_file(), Some(e.to_string())),
+        }
+    }
+
+    /// Uses `path` as the clipboard, regardless of what the system offers.
+    fn with_file(path: impl Into<PathBuf>) -> Self {
+        Self::file(path.into(), None)
+    }
+
+    fn file(path: PathBuf, fallback_reason: Option<String>) -> Self {
+        Self { backend: Backend::File(path), fallback_reason }
+    }
+
+    fn is_system(&self) -> bool {
+        matches!(self.backend, Backend::System(_))
+    }
+
+    fn backend_name(&self) -> &'static str {
+        match self.backend {
+            Backend::System(_) => "system",
+            Backend::File(_) => "file",
+        }
+    }
+
+    fn fallback_reason(&self) -> Option<&str> {
+        self.fallback_reason.as_deref()
+    }
+
+    // --- Text ---
+
+    /// Reads the clipboard as text.
+    ///
+    /// # Returns
+    /// `Ok(None)` when the clipboard is empty or holds something other than text.
+    fn get_text(&mut self) -> Result<Option<String>> {
+        match &mut self.backend {
+            Backend::System(clipboard) => match clipboard.get_text() {
+                Ok(text) if text.is_empty() => Ok(None),
+                Ok(text) => Ok(Some(text)),
+                Err(arboard::Error::ContentNotAvailable) => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            Backend::File(path) => match fs::read(&*path) {
+                Ok(bytes) if bytes.is_empty() => Ok(None),
+                Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(source) => Err(ClipboardError::Io { path: path.clone(), source }),
+            },
+        }
+    }
+
+    /// Replaces the clipboard contents with `text`.
+    ///
+    /// On X11/Wayland the contents disappear when this process exits; short-lived tools
+    /// should use `set_text_and_wait` instead.
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        match &mut self.backend {
+            Backend::System(clipboard) => Ok(clipboard.set_text(text)?),
+            Backend::File(path) => write_atomic(path, text.as_bytes()),
+        }
+    }
+
+    /// Sets `text`, then on Linux keeps serving it to other applications until something
+    /// else is copied or `max_wait` passes, whichever comes first.
+    ///
+    /// # Arguments
+    /// * `max_wait` - How long a CLI may linger after copying. Pasting after this returns
+    ///   nothing on X11/Wayland, so pick something the user will reasonably beat.
+    fn set_text_and_wait(&mut self, text: &str, max_wait: Duration) -> Result<()> {
+        match &mut self.backend {
+            #[cfg(all(unix, not(target_os = "macos")))]
+            Backend::System(clipboard) => {
+                use arboard::SetExtLinux;
+                let deadline = std::time::Instant::now() + max_wait;
+                Ok(clipboard.set().wait_until(deadline).text(text)?)
+            }
+            _ => {
+                let _ = max_wait;
+                self.set_text(text)
+            }
+        }
+    }
+
+    /// Empties the clipboard.
+    fn clear(&mut self) -> Result<()> {
+        match &mut self.backend {
+            Backend::System(clipboard) => Ok(clipboard.clear()?),
+            Backend::File(path) => match fs::remove_file(&*path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(ClipboardError::Io { path: path.clone(), source: e }),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    // --- Images ---
+
+    /// Reads an image from the clipboard, converted to RGBA8 by the platform.
+    ///
+    /// # Returns
+    /// `Ok(None)` when the clipboard holds no image.
+    fn get_image(&mut self) -> Result<Option<Image>> {
+        match &mut self.backend {
+            Backend::System(clipboard) => match clipboard.get_image() {
+                Ok(img) => Ok(Some(Image { width: img.width, height: img.height, rgba: img.bytes.into_owned() })),
+                Err(arboard::Error::ContentNotAvailable) => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            Backend::File(_) => Err(ClipboardError::Unsupported { backend: "file", what: "images" }),
+        }
+    }
+
+    /// Puts an RGBA8 image on the clipboard. Decode PNG/JPEG first, e.g. with the `image` crate.
+    fn set_image(&mut self, image: &Image) -> Result<()> {
+        match &mut self.backend {
+            Backend::System(clipboard) => {
+                if image.rgba.len() != image.width * image.height * 4 {
+                    return Err(arboard::Error::ConversionFailure.into());
+                }
+                let data = arboard::ImageData { width: image.width, height: image.height, bytes: Cow::Borrowed(&image.rgba) };
+                Ok(clipboard.set_image(data)?)
+            }
+            Backend::File(_) => Err(ClipboardError::Unsupported { backend: "file", what: "images" }),
+        }
+    }
+}
+
+/// Writes via a temp file and rename so a concurrent paste never sees half a value.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let io_err = |source| ClipboardError::Io { path: path.to_path_buf(), source };
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(io_err)?;
+    }
+    let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let result = options
+        .open(&tmp)
+        .and_then(|mut f| f.write_all(bytes).and_then(|_| f.sync_all()))
+        .and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map_err(io_err)
+}
+
+// --- Terminal Copy (OSC 52) ---
+
+/// Asks the terminal to put `text` on the user's local clipboard with an OSC 52 escape.
+///
+/// This works over SSH, where the system clipboard belongs to the wrong machine, as long as
+/// the terminal allows it (iTerm2, kitty, WezTerm, Windows Terminal, and xterm with
+/// `allowWindowOps`). Inside tmux, enable `set -g set-clipboard on`. There's no
+/// acknowledgement, so treat it as best effort and keep the file fallback as well.
+///
+/// # Arguments
+/// * `out` - Normally stdout; only write when it is a terminal (`io::IsTerminal`), or the
+///   escape ends up in a pipe or file.
+fn osc52_copy(out: &mut impl Write, text: &str) -> io::Result<()> {
+    use base64::Engine;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    write!(out, "\x1b]52;c;{}\x07", encoded)?;
+    out.flush()
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    use std::io::IsTerminal;
+
+    // --- What a `--copy` flag would do ---
+    let mut clipboard = Clipboard::open();
+    match clipboard.fallback_reason() {
+        None => println!("using the system clipboard"),
+        Some(reason) => println!("using the file clipboard ({})", reason),
+    }
+    let previous = clipboard.get_text()?;
+    println!("clipboard before: {:?}", previous.as_deref().map(|t| t.chars().take(40).collect::<String>()));
+
+    clipboard.set_text_and_wait("deploy-token-1234", Duration::from_millis(200))?;
+    println!("clipboard now: {:?}", clipboard.get_text()?);
+
+    // Images work on the system backend only; the fallback says so instead of failing obscurely.
+    let pixel = Image { width: 1, height: 1, rgba: vec![255, 0, 0, 255] };
+    match clipboard.set_image(&pixel) {
+        Ok(()) => println!("image copied: {:?}", clipboard.get_image()?.map(|i| (i.width, i.height))),
+        Err(e) => println!("image skipped: {}", e),
+    }
+
+    // Restore what the user had.
+    match previous {
+        Some(text) => clipboard.set_text(&text)?,
+        None => clipboard.clear()?,
+    }
+    println!("restored, backend = {}, system = {}", clipboard.backend_name(), clipboard.is_system());
+
+    // --- A file clipboard for tests and scripts ---
+    let path = env::temp_dir().join(format!("clipboard-demo-{}.txt", std::process::id()));
+    let mut file_clipboard = Clipboard::with_file(&path);
+    println!("empty file clipboard: {:?}", file_clipboard.get_text()?);
+    file_clipboard.set_text("line one\nline two")?;
+    println!("after set: {:?}", file_clipboard.get_text()?);
+    file_clipboard.clear()?;
+    println!("after clear: {:?}, file exists = {}", file_clipboard.get_text()?, path.exists());
+    println!("{:?}", file_clipboard);
+
+    // --- Over SSH: let the terminal do it ---
+    let mut stdout = io::stdout();
+    if stdout.is_terminal() && env::var_os("SSH_TTY").is_some() {
+        osc52_copy(&mut stdout, "copied through the terminal").map_err(|source| ClipboardError::Io { path: "<stdout>".into(), source })?;
+    }
+    let mut escape = Vec::new();
+    osc52_copy(&mut escape, "hi").map_err(|source| ClipboardError::Io { path: "<buffer>".into(), source })?;
+    println!("OSC 52 sequence: {:?}", String::from_utf8_lossy(&escape));
+    Ok(())
+}
+*/
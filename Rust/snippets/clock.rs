@@ -0,0 +1,322 @@
+// This example only uses the standard library.
+//
+// A time source that tests can control. Code that calls `Instant::now()` and
+// `thread::sleep` directly can only be tested by really waiting, which makes TTL, backoff,
+// and rate-limit tests slow and flaky. Take an `Arc<dyn Clock>` instead:
+// * `SystemClock` is the real thing: `Instant::now()`, `SystemTime::now()`, `thread::sleep`.
+// * `MockClock` only moves when told to. `advance` jumps time forward; `sleep` either
+//   advances immediately and records the duration (the default), or, with `manual()`, blocks
+//   the sleeping thread until another thread advances past its deadline.
+// * Other snippets take the clock too: `LruCache::with_clock` in `cache.rs` (TTL expiry) and
+//   `#[retry(clock = ...)]` in `Rust/macros` (backoff sleeps). `CronSchedule::next_after` in
+//   `cron.rs` already takes the current time as an argument; pass it `clock.wall()`.
+// * `TokenBucket` and `IntervalScheduler` below show how to write new code against the trait.
+//
+// For async code on tokio, use `tokio::time::pause()` and `tokio::time::advance()` in tests
+// instead: tokio's timers and `tokio::time::Instant` then run on virtual time.
+
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+
+// --- The trait ---
+
+/// Where code gets the current time and how it waits.
+trait Clock: Send + Sync {
+    /// Monotonic time, for measuring durations and deadlines.
+    fn now(&self) -> Instant;
+
+    /// Wall-clock time, for timestamps and calendar schedules.
+    fn wall(&self) -> SystemTime;
+
+    fn sleep(&self, duration: Duration);
+}
+
+/// The real clock.
+struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+// --- Mock clock ---
+
+struct MockState {
+    elapsed: Duration,
+    sleeps: Vec<Duration>,
+}
+
+/// A clock that stands still until advanced.
+///
+/// `now()` is a fixed `Instant` taken at creation plus the total advanced so far, and
+/// `wall()` is the starting wall time plus the same amount, so both move together.
+/// Time stops at the far future instead of overflowing, so code that sleeps `Duration::MAX`
+/// (e.g. `TokenBucket::acquire` at a tiny rate) doesn't panic.
+struct MockClock {
+    start: Instant,
+    wall_start: SystemTime,
+    /// The most `elapsed` can reach with `now()` and `wall()` still representable.
+    limit: Duration,
+    /// When true, `sleep` blocks until another thread advances the clock far enough.
+    manual: bool,
+    state: Mutex<MockState>,
+    advanced: Condvar,
+}
+
+impl MockClock {
+    /// A clock whose `sleep` advances time instantly; right for single-threaded code.
+    fn new() -> Self {
+        Self::starting_at(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    }
+
+    /// Like `new`, with `wall()` starting at `wall_start` (e.g. just before midnight).
+    fn starting_at(wall_start: SystemTime) -> Self {
+        let start = Instant::now();
+        // Neither `Instant` nor `SystemTime` has a maximum; halve until both additions fit.
+        let mut limit = Duration::MAX;
+        while start.checked_add(limit).is_none() || wall_start.checked_add(limit).is_none() {
+            limit /= 2;
+        }
+        Self {
+            start,
+            wall_start,
+            limit,
+            manual: false,
+            state: Mutex::new(MockState { elapsed: Duration::ZERO, sleeps: Vec::new() }),
+            advanced: Condvar::new(),
+        }
+    }
+
+    /// A clock whose `sleep` blocks until the test calls `advance`, for code under test
+    /// running on its own thread (a background refresher, a scheduler loop).
+    fn manual() -> Self {
+        Self { manual: true, ..Self::new() }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Moves time forward and wakes sleepers whose deadline has passed.
+    fn advance(&self, duration: Duration) {
+        let mut state = self.lock();
+        state.elapsed = state.elapsed.saturating_add(duration).min(self.limit);
+        drop(state);
+        self.advanced.notify_all();
+    }
+
+    /// Total time advanced since creation.
+    fn elapsed(&self) -> Duration {
+        self.lock().elapsed
+    }
+
+    /// Every duration passed to `sleep`, in call order.
+    fn sleeps(&self) -> Vec<Duration> {
+        self.lock().sleeps.clone()
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn wall(&self) -> SystemTime {
+        self.wall_start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let mut state = self.lock();
+        state.sleeps.push(duration);
+        if !self.manual {
+            state.elapsed = state.elapsed.saturating_add(duration).min(self.limit);
+            return;
+        }
+        let deadline = state.elapsed.saturating_add(duration).min(self.limit);
+        while state.elapsed < deadline {
+            state = self.advanced.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+// --- Rate limiter ---
+
+/// A token bucket: up to `capacity` calls at once, refilled at `per_second` tokens a second.
+struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    refilled_at: Instant,
+    clock: Arc<dyn Clock>,
+}
+
+impl TokenBucket {
+    /// A full bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The largest burst allowed.
+    /// * `per_second` - The sustained rate.
+    /// * `clock` - `Arc::new(SystemClock)` in production.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero or `per_second` is not a positive, finite number. Either would
+    /// make `acquire` wait forever.
+    fn new(capacity: u32, per_second: f64, clock: Arc<dyn Clock>) -> Self {
+        assert!(capacity > 0, "TokenBucket capacity must be at least 1");
+        assert!(per_second.is_finite() && per_second > 0.0, "TokenBucket rate must be positive, got {}", per_second);
+        let refilled_at = clock.now();
+        Self { capacity: f64::from(capacity), per_second, tokens: f64::from(capacity), refilled_at, clock }
+    }
+
+    fn refill(&mut self) {
+        let now = self.clock.now();
+        let earned = now.duration_since(self.refilled_at).as_secs_f64() * self.per_second;
+        self.tokens = (self.tokens + earned).min(self.capacity);
+        self.refilled_at = now;
+    }
+
+    /// Takes a token if one is available.
+    fn try_acquire(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Waits (on the clock) until a token is available, then takes it.
+    fn acquire(&mut self) {
+        while !self.try_acquire() {
+            let wait = (1.0 - self.tokens) / self.per_second;
+            // A tiny rate can need a wait longer than `Duration` holds.
+            self.clock.sleep(Duration::try_from_secs_f64(wait).unwrap_or(Duration::MAX));
+        }
+    }
+}
+
+// --- Interval scheduler ---
+
+struct Job {
+    name: String,
+    every: Duration,
+    next_run: Instant,
+    task: Box<dyn FnMut() + Send>,
+}
+
+/// Runs named jobs at fixed intervals; `run_due` is meant to be called from a loop.
+struct IntervalScheduler {
+    jobs: Vec<Job>,
+    clock: Arc<dyn Clock>,
+}
+
+impl IntervalScheduler {
+    fn new(clock: Arc<dyn Clock>) -> Self {
+        Self { jobs: Vec::new(), clock }
+    }
+
+    /// Adds a job whose first run is one interval from now.
+    fn every(&mut self, name: &str, every: Duration, task: impl FnMut() + Send + 'static) {
+        let next_run = self.clock.now() + every;
+        self.jobs.push(Job { name: name.to_string(), every, next_run, task: Box::new(task) });
+    }
+
+    /// Runs every job that is due and returns their names. A job that fell several
+    /// intervals behind runs once, then continues on its original cadence.
+    fn run_due(&mut self) -> Vec<String> {
+        let now = self.clock.now();
+        let mut ran = Vec::new();
+        for job in self.jobs.iter_mut().filter(|job| job.next_run <= now) {
+            (job.task)();
+            while job.next_run <= now {
+                job.next_run += job.every;
+            }
+            ran.push(job.name.clone());
+        }
+        ran
+    }
+
+    /// Sleeps until the next job is due, then runs whatever is due.
+    fn run_next(&mut self) -> Vec<String> {
+        if let Some(next) = self.jobs.iter().map(|job| job.next_run).min() {
+            self.clock.sleep(next.saturating_duration_since(self.clock.now()));
+        }
+        self.run_due()
+    }
+}
+
+// Example Usage
+/*
+use std::sync::atomic::{AtomicU32, Ordering};
+
+fn main() {
+    // Rate limiting, tested in microseconds instead of seconds.
+    let clock = Arc::new(MockClock::new());
+    let mut bucket = TokenBucket::new(3, 1.0, clock.clone());
+    let burst: Vec<bool> = (0..4).map(|_| bucket.try_acquire()).collect();
+    println!("burst of 4: {:?}", burst);
+    clock.advance(Duration::from_millis(1500));
+    println!("after 1.5s: {} {}", bucket.try_acquire(), bucket.try_acquire());
+    bucket.acquire(); // "Sleeps" on the mock clock, which advances it.
+    println!("acquire waited {:?}; sleeps {:?}", clock.elapsed(), clock.sleeps());
+
+    // Interval jobs, run deterministically.
+    let clock = Arc::new(MockClock::new());
+    let mut scheduler = IntervalScheduler::new(clock.clone());
+    let flushes = Arc::new(AtomicU32::new(0));
+    let counter = flushes.clone();
+    scheduler.every("flush", Duration::from_secs(10), move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    scheduler.every("report", Duration::from_secs(60), || {});
+    for _ in 0..7 {
+        let ran = scheduler.run_next();
+        println!("t={:>3}s ran {:?}", clock.elapsed().as_secs(), ran);
+    }
+    println!("flushes: {}", flushes.load(Ordering::Relaxed));
+
+    // A manual clock drives code running on another thread.
+    let clock = Arc::new(MockClock::manual());
+    let worker_clock = clock.clone();
+    let worker = std::thread::spawn(move || {
+        worker_clock.sleep(Duration::from_secs(30));
+        "woke up"
+    });
+    while clock.sleeps().is_empty() {
+        std::thread::yield_now(); // Wait until the worker is asleep.
+    }
+    clock.advance(Duration::from_secs(10));
+    println!("after 10s, finished: {}", worker.is_finished());
+    clock.advance(Duration::from_secs(20));
+    println!("after 30s: {}", worker.join().unwrap());
+
+    // With cache.rs, TTLs expire without sleeping:
+    // let cache = LruCache::with_ttl(100, Duration::from_secs(60)).with_clock(clock.clone());
+    // cache.insert("k", 1);
+    // clock.advance(Duration::from_secs(61));
+    // assert_eq!(cache.get("k"), None);
+
+    let real: Arc<dyn Clock> = Arc::new(SystemClock);
+    let started = real.now();
+    real.sleep(Duration::from_millis(5));
+    println!("system clock slept {:?}", started.elapsed());
+}
+*/
@@ -0,0 +1,273 @@
+// This example only uses the standard library.
+//
+// Consistent hashing: map keys (cache keys, tenant ids, job ids) to one of N nodes so that
+// adding or removing a node moves only about 1/N of the keys, instead of nearly all of them
+// as with `hash(key) % n`. Each node is placed on the ring many times ("virtual nodes") so
+// the load evens out; `weight` gives bigger nodes proportionally more of the ring.
+//
+// Every process must compute the same placement, so this uses its own fixed hash (FNV-1a
+// with a final mix) instead of `DefaultHasher`, whose algorithm may change between Rust
+// releases.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+// --- Stable hashing ---
+
+/// FNV-1a over the bytes a `Hash` impl writes, finished with the SplitMix64 mixer so that
+/// similar keys ("user-1", "user-2") land far apart on the ring.
+#[derive(Debug, Clone)]
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut z = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Position of any hashable key on the ring. Stable across runs, machines, and Rust versions
+/// for strings, integers, and tuples of them (but not `usize` across 32/64-bit targets).
+fn ring_hash<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = StableHasher::default();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+// --- Ring ---
+
+/// How the keyspace changed after adding or removing a node.
+#[derive(Debug, Clone)]
+struct Rebalance<N> {
+    /// Fraction of all keys that now map to a different node (0.0-1.0).
+    moved_fraction: f64,
+    /// Share of the keyspace per node before and after, for nodes present in either.
+    shares: BTreeMap<N, (f64, f64)>,
+}
+
+impl<N: fmt::Display> fmt::Display for Rebalance<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "moved {:.1}% of keys", self.moved_fraction * 100.0)?;
+        for (node, (before, after)) in &self.shares {
+            writeln!(f, "  {:<12} {:>5.1}% -> {:>5.1}%", node.to_string(), before * 100.0, after * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// A consistent-hash ring of nodes of type `N` (a name, an address, a shard id).
+#[derive(Debug, Clone)]
+struct HashRing<N> {
+    /// Ring position -> node. Each node appears `vnodes * weight` times.
+    ring: BTreeMap<u64, N>,
+    /// Node -> weight.
+    nodes: BTreeMap<N, u32>,
+    vnodes: u32,
+}
+
+impl<N: Clone + Ord + Hash> HashRing<N> {
+    /// Creates an empty ring with `vnodes` virtual nodes per unit of weight. With `v` virtual
+    /// nodes a node's share typically deviates from fair by about 1/sqrt(v): ~8% at 150,
+    /// ~3% at 1000. More costs memory and slower add/remove, not slower lookups.
+    fn new(vnodes: u32) -> Self {
+        Self { ring: BTreeMap::new(), nodes: BTreeMap::new(), vnodes: vnodes.max(1) }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.keys()
+    }
+
+    /// Adds `node` with weight 1. See `add_weighted_node`.
+    fn add_node(&mut self, node: N) -> Rebalance<N> {
+        self.add_weighted_node(node, 1)
+    }
+
+    /// Adds `node` (or changes its weight) and reports how much of the keyspace moved.
+    /// A node with weight 2 receives about twice the keys of a weight-1 node.
+    fn add_weighted_node(&mut self, node: N, weight: u32) -> Rebalance<N> {
+        let before = self.clone();
+        self.remove_points(&node);
+        let weight = weight.max(1);
+        for i in 0..self.vnodes * weight {
+            // On the rare position collision the smaller node keeps the point, whatever
+            // order nodes were added in, so every process builds the same ring.
+            let point = ring_hash(&(&node, i));
+            match self.ring.get(&point) {
+                Some(existing) if *existing < node => {}
+                _ => {
+                    self.ring.insert(point, node.clone());
+                }
+            }
+        }
+        self.nodes.insert(node, weight);
+        before.rebalance_to(self)
+    }
+
+    /// Removes `node`; its keys spread over the remaining nodes. Returns `None` if it was
+    /// not on the ring.
+    fn remove_node(&mut self, node: &N) -> Option<Rebalance<N>> {
+        let before = self.clone();
+        self.nodes.remove(node)?;
+        self.remove_points(node);
+        // Restore other nodes' points that had collided with the removed node's (in
+        // ascending node order, so the smaller node wins again).
+        let others: Vec<(N, u32)> = self.nodes.iter().map(|(n, w)| (n.clone(), *w)).collect();
+        for (other, weight) in others {
+            for i in 0..self.vnodes * weight {
+                self.ring.entry(ring_hash(&(&other, i))).or_insert_with(|| other.clone());
+            }
+        }
+        Some(before.rebalance_to(self))
+    }
+
+    fn remove_points(&mut self, node: &N) {
+        self.ring.retain(|_, n| n != node);
+    }
+
+    /// The node responsible for `key`: the first ring point clockwise from the key's hash.
+    /// O(log(nodes * vnodes)). `None` only when the ring is empty.
+    fn node_for<K: Hash + ?Sized>(&self, key: &K) -> Option<&N> {
+        self.owner_of(ring_hash(key))
+    }
+
+    fn owner_of(&self, point: u64) -> Option<&N> {
+        self.ring.range(point..).next().or_else(|| self.ring.iter().next()).map(|(_, n)| n)
+    }
+
+    /// Up to `count` *distinct* nodes for `key`, in preference order: the owner first, then
+    /// the next different nodes clockwise. Use for replicas or fallbacks when the owner is down.
+    fn nodes_for<K: Hash + ?Sized>(&self, key: &K, count: usize) -> Vec<&N> {
+        let point = ring_hash(key);
+        let mut picked: Vec<&N> = Vec::with_capacity(count.min(self.nodes.len()));
+        let clockwise = self.ring.range(point..).chain(self.ring.range(..point));
+        for (_, node) in clockwise {
+            if picked.len() == count.min(self.nodes.len()) {
+                break;
+            }
+            if !picked.contains(&node) {
+                picked.push(node);
+            }
+        }
+        picked
+    }
+
+    /// Each node's share of the keyspace (0.0-1.0, summing to 1.0). Ideal is
+    /// weight / total weight; the gap shrinks as `vnodes` grows.
+    fn shares(&self) -> BTreeMap<N, f64> {
+        let mut shares: BTreeMap<N, f64> = self.nodes.keys().map(|n| (n.clone(), 0.0)).collect();
+        let mut previous = match self.ring.iter().next_back() {
+            Some((&last, _)) => last,
+            None => return shares,
+        };
+        // Each point owns the arc from the previous point (exclusive) up to itself.
+        for (&point, node) in &self.ring {
+            let arc = point.wrapping_sub(previous);
+            let arc = if arc == 0 && self.ring.len() == 1 { u64::MAX } else { arc };
+            *shares.get_mut(node).expect("ring node is registered") += arc as f64 / u64::MAX as f64;
+            previous = point;
+        }
+        shares
+    }
+
+    /// Exact fraction of the keyspace whose owner differs between `self` and `after`,
+    /// found by walking the union of both rings' points.
+    fn rebalance_to(&self, after: &HashRing<N>) -> Rebalance<N> {
+        let points: BTreeSet<u64> = self.ring.keys().chain(after.ring.keys()).copied().collect();
+        let mut moved = 0.0;
+        if let Some(&last) = points.iter().next_back() {
+            let mut previous = last;
+            for &point in &points {
+                // Every key in (previous, point] has the same owner as `point` in each ring.
+                if self.owner_of(point) != after.owner_of(point) {
+                    let arc = point.wrapping_sub(previous);
+                    moved += if arc == 0 { 1.0 } else { arc as f64 / u64::MAX as f64 };
+                }
+                previous = point;
+            }
+        }
+        let before_shares = self.shares();
+        let after_shares = after.shares();
+        let mut shares = BTreeMap::new();
+        for node in before_shares.keys().chain(after_shares.keys()) {
+            let pair = (before_shares.get(node).copied().unwrap_or(0.0), after_shares.get(node).copied().unwrap_or(0.0));
+            shares.insert(node.clone(), pair);
+        }
+        Rebalance { moved_fraction: moved.min(1.0), shares }
+    }
+}
+
+/// Counts how many of `keys` each node receives, e.g. to check balance on real key samples.
+fn key_distribution<N, K>(ring: &HashRing<N>, keys: impl IntoIterator<Item = K>) -> BTreeMap<&N, usize>
+where
+    N: Clone + Ord + Hash,
+    K: Hash,
+{
+    let mut counts = BTreeMap::new();
+    for key in keys {
+        if let Some(node) = ring.node_for(&key) {
+            *counts.entry(node).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+// Example Usage
+/*
+fn main() {
+    let mut ring = HashRing::new(160);
+    for node in ["cache-a", "cache-b", "cache-c"] {
+        ring.add_node(node.to_string());
+    }
+    println!("{} nodes", ring.len());
+
+    for key in ["user:1001", "user:1002", "session:9f3c", "cart:77"] {
+        let replicas: Vec<&String> = ring.nodes_for(key, 2);
+        println!("{:<13} -> {:?} (replicas {:?})", key, ring.node_for(key), replicas);
+    }
+
+    let keys: Vec<String> = (0..30_000).map(|i| format!("user:{i}")).collect();
+    println!("distribution: {:?}", key_distribution(&ring, &keys));
+
+    // Adding a fourth node moves about a quarter of the keys, all of them to the new node.
+    let owners_before: Vec<String> = keys.iter().map(|k| ring.node_for(k).unwrap().clone()).collect();
+    let rebalance = ring.add_node("cache-d".to_string());
+    print!("add cache-d: {}", rebalance);
+    let moved = keys.iter().zip(&owners_before).filter(|(k, before)| ring.node_for(*k) != Some(*before)).count();
+    println!("  sampled keys moved: {:.1}%", moved as f64 * 100.0 / keys.len() as f64);
+
+    // A node with weight 2 takes about twice the share of the others.
+    print!("cache-e weight 2: {}", ring.add_weighted_node("cache-e".to_string(), 2));
+    print!("remove cache-b: {}", ring.remove_node(&"cache-b".to_string()).unwrap());
+
+    // Integer shard ids work too; any `Hash` key maps onto them.
+    let mut shards = HashRing::new(100);
+    for shard in 0u32..4 {
+        shards.add_node(shard);
+    }
+    println!("tenant 42 -> shard {:?}", shards.node_for(&42u64));
+}
+*/
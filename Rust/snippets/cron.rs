@@ -0,0 +1,371 @@
+// Note: This example requires adding the `chrono` and `chrono-tz` crates to your Cargo.toml:
+// [dependencies]
+// chrono = "0.4"
+// chrono-tz = "0.10"
+//
+// A small, dependency-light cron parser: 5-field (`min hour dom month dow`) or 6-field
+// (`sec min hour dom month dow`) expressions, `@daily`-style shortcuts, month/weekday names,
+// and errors that point at the exact column. Occurrences are computed in a named time zone
+// so "every day at 09:00 Europe/Berlin" stays at 09:00 across daylight-saving changes.
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// A parse error with the 0-based character position where the problem starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronError {
+    expression: String,
+    position: usize,
+    message: String,
+}
+
+impl CronError {
+    /// The expression with a caret under the offending position, for CLI output:
+    ///
+    /// ```text
+    /// 0 25 * * *
+    ///   ^ hour 25 is out of range 0-23
+    /// ```
+    fn render(&self) -> String {
+        format!("{}\n{}^ {}", self.expression, " ".repeat(self.position), self.message)
+    }
+}
+
+impl fmt::Display for CronError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (column {} of '{}')", self.message, self.position + 1, self.expression)
+    }
+}
+
+impl std::error::Error for CronError {}
+
+/// Static description of one cron field.
+struct FieldSpec {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+}
+
+const SECOND: FieldSpec = FieldSpec { name: "second", min: 0, max: 59, names: &[] };
+const MINUTE: FieldSpec = FieldSpec { name: "minute", min: 0, max: 59, names: &[] };
+const HOUR: FieldSpec = FieldSpec { name: "hour", min: 0, max: 23, names: &[] };
+const DAY_OF_MONTH: FieldSpec = FieldSpec { name: "day-of-month", min: 1, max: 31, names: &[] };
+const MONTH: FieldSpec = FieldSpec {
+    name: "month",
+    min: 1,
+    max: 12,
+    names: &["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"],
+};
+// 0-7, where both 0 and 7 are Sunday.
+const DAY_OF_WEEK: FieldSpec = FieldSpec {
+    name: "day-of-week",
+    min: 0,
+    max: 7,
+    names: &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"],
+};
+
+/// A parsed schedule. Each field is a bitmask where bit `n` means "value `n` matches".
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CronSchedule {
+    source: String,
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Classic cron rule: if both day fields are restricted, a day matches if *either* does.
+    /// As in Vixie cron, a field starting with `*` (including `*/2`) is not restricted.
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+fn expand_shortcut(expr: &str) -> Option<&'static str> {
+    Some(match expr.to_ascii_lowercase().as_str() {
+        "@yearly" | "@annually" => "0 0 1 1 *",
+        "@monthly" => "0 0 1 * *",
+        "@weekly" => "0 0 * * 0",
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@hourly" => "0 * * * *",
+        _ => return None,
+    })
+}
+
+/// Parses a single value (number or name) that starts at `offset` in the expression.
+fn parse_value(token: &str, offset: usize, spec: &FieldSpec, err: &dyn Fn(usize, String) -> CronError) -> Result<u32, CronError> {
+    if let Ok(n) = token.parse::<u32>() {
+        if n < spec.min || n > spec.max {
+            return Err(err(offset, format!("{} {} is out of range {}-{}", spec.name, n, spec.min, spec.max)));
+        }
+        return Ok(n);
+    }
+    let upper = token.to_ascii_uppercase();
+    match spec.names.iter().position(|name| *name == upper) {
+        // Month names map to 1-12 (min 1), weekday names to 0-6 (min 0).
+        Some(i) => Ok(i as u32 + spec.min),
+        None if token.is_empty() => Err(err(offset, format!("missing {} value", spec.name))),
+        None => Err(err(offset, format!("invalid {} value '{}'", spec.name, token))),
+    }
+}
+
+/// Parses one field such as `*/15`, `1-5`, `MON-FRI`, or `0,30`.
+///
+/// # Returns
+///
+/// The bitmask and whether the field was restricted (it doesn't start with `*`).
+fn parse_field(field: &str, offset: usize, spec: &FieldSpec, err: &dyn Fn(usize, String) -> CronError) -> Result<(u64, bool), CronError> {
+    let mut mask = 0u64;
+    let mut item_offset = offset;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step_str)) => {
+                let step_offset = item_offset + range.len() + 1;
+                let step = step_str
+                    .parse::<u32>()
+                    .map_err(|_| err(step_offset, format!("invalid step '{}'", step_str)))?;
+                if step == 0 {
+                    return Err(err(step_offset, "step must be at least 1".to_string()));
+                }
+                (range, step)
+            }
+            None => (item, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (spec.min, spec.max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            let start = parse_value(a, item_offset, spec, err)?;
+            let end = parse_value(b, item_offset + a.len() + 1, spec, err)?;
+            if start > end {
+                return Err(err(item_offset, format!("range {}-{} is backwards", start, end)));
+            }
+            (start, end)
+        } else {
+            let start = parse_value(range, item_offset, spec, err)?;
+            // `5/15` means "from 5 to the end, every 15".
+            (start, if step > 1 { spec.max } else { start })
+        };
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+        item_offset += item.len() + 1;
+    }
+    Ok((mask, !field.starts_with('*')))
+}
+
+fn days_in_month(month: u32, year: i32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(31)
+}
+
+impl CronSchedule {
+    /// Parses a 5- or 6-field expression or a shortcut (`@hourly`, `@daily`, `@weekly`,
+    /// `@monthly`, `@yearly`).
+    ///
+    /// # Returns
+    ///
+    /// A `CronError` whose `position` points at the offending character.
+    fn parse(expression: &str) -> Result<Self, CronError> {
+        let original = expression.trim().to_string();
+        let expanded = expand_shortcut(&original);
+        let text = expanded.unwrap_or(&original);
+        // Positions refer to the expanded text only for shortcuts, which never fail.
+        let err = |position: usize, message: String| CronError {
+            expression: original.clone(),
+            position,
+            message,
+        };
+
+        if text.starts_with('@') {
+            return Err(err(0, format!("unknown shortcut '{}'", text)));
+        }
+        // Collect fields with their starting column.
+        let mut fields: Vec<(usize, &str)> = Vec::new();
+        let mut start = None;
+        for (i, c) in text.char_indices() {
+            match (c.is_whitespace(), start) {
+                (false, None) => start = Some(i),
+                (true, Some(s)) => {
+                    fields.push((s, &text[s..i]));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            fields.push((s, &text[s..]));
+        }
+
+        let specs: &[&FieldSpec] = match fields.len() {
+            5 => &[&MINUTE, &HOUR, &DAY_OF_MONTH, &MONTH, &DAY_OF_WEEK],
+            6 => &[&SECOND, &MINUTE, &HOUR, &DAY_OF_MONTH, &MONTH, &DAY_OF_WEEK],
+            n => {
+                let position = fields.get(6).map(|(p, _)| *p).unwrap_or(text.len());
+                return Err(err(position, format!("expected 5 or 6 fields, found {}", n)));
+            }
+        };
+
+        let mut masks = Vec::with_capacity(6);
+        let mut restricted = Vec::with_capacity(6);
+        for ((offset, field), spec) in fields.iter().zip(specs) {
+            let (mask, is_restricted) = parse_field(field, *offset, spec, &err)?;
+            masks.push(mask);
+            restricted.push(is_restricted);
+        }
+        if fields.len() == 5 {
+            masks.insert(0, 1); // Second 0
+            restricted.insert(0, true);
+        }
+
+        let mut days_of_week = masks[5];
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7); // 7 is Sunday too
+        }
+        let schedule = CronSchedule {
+            source: original.clone(),
+            seconds: masks[0],
+            minutes: masks[1],
+            hours: masks[2],
+            days_of_month: masks[3],
+            months: masks[4],
+            days_of_week,
+            dom_restricted: restricted[3],
+            dow_restricted: restricted[5],
+        };
+
+        // Catch schedules that can never fire, like "0 0 30 2 *" (February 30th).
+        if !schedule.dow_restricted {
+            let possible = (1..=12u32)
+                .filter(|m| schedule.months & (1 << m) != 0)
+                .any(|m| (1..=days_in_month(m, 2024)).any(|d| schedule.days_of_month & (1 << d) != 0));
+            if !possible {
+                let (offset, _) = fields[fields.len() - 3];
+                return Err(err(offset, "day-of-month never occurs in the selected month(s)".to_string()));
+            }
+        }
+        Ok(schedule)
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = self.days_of_month & (1 << date.day()) != 0;
+        let dow = self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            _ => dom && dow,
+        }
+    }
+
+    /// The next wall-clock time (strictly after `after`) matching the schedule, ignoring zones.
+    fn next_naive(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_nanosecond(0)? + Duration::seconds(1);
+        // Feb 29 on a given weekday can be 28 years away, or 40 across a skipped leap year
+        // like 2100; searching the whole 400-year Gregorian cycle covers every schedule.
+        let limit = after + Duration::days(146_097);
+        while t <= limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (year, month) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)?.with_second(0)? + Duration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << t.minute()) == 0 {
+                t = t.with_second(0)? + Duration::minutes(1);
+                continue;
+            }
+            if self.seconds & (1 << t.second()) == 0 {
+                t += Duration::seconds(1);
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+
+    /// The next occurrence strictly after `after`, in `after`'s time zone.
+    ///
+    /// Wall-clock times skipped by a daylight-saving change do not fire; times that occur
+    /// twice fire once, at the earlier instant.
+    fn next_after(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let mut local = after.naive_local();
+        loop {
+            local = self.next_naive(local)?;
+            let candidate = match tz.from_local_datetime(&local) {
+                LocalResult::Single(dt) => dt,
+                LocalResult::Ambiguous(earliest, latest) => {
+                    if earliest > *after {
+                        earliest
+                    } else {
+                        latest
+                    }
+                }
+                LocalResult::None => continue, // Skipped by DST
+            };
+            if candidate > *after {
+                return Some(candidate);
+            }
+        }
+    }
+
+    /// The next `n` occurrences after `after`.
+    fn next_n(&self, after: &DateTime<Tz>, n: usize) -> Vec<DateTime<Tz>> {
+        let mut out = Vec::with_capacity(n);
+        let mut cursor = *after;
+        while out.len() < n {
+            match self.next_after(&cursor) {
+                Some(next) => {
+                    out.push(next);
+                    cursor = next;
+                }
+                None => break,
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+// Example Usage
+/*
+fn main() {
+    let tz: Tz = "Europe/Berlin".parse().unwrap();
+    // Just before the 2024 autumn DST change in Berlin (03:00 CEST -> 02:00 CET on Oct 27).
+    let start = tz.with_ymd_and_hms(2024, 10, 26, 22, 0, 0).unwrap();
+
+    for expr in ["0/15 9-17 * * MON-FRI", "30 2 * * *", "0 0 9 1,15 * *", "@weekly", "0 12 13 * FRI"] {
+        match CronSchedule::parse(expr) {
+            Ok(schedule) => {
+                println!("{}", schedule);
+                for next in schedule.next_n(&start, 4) {
+                    println!("  {}", next.format("%a %Y-%m-%d %H:%M:%S %Z"));
+                }
+            }
+            Err(e) => println!("{}", e),
+        }
+    }
+
+    println!();
+    for bad in ["0 25 * * *", "0/0 * * * *", "0 0 * JANUARY *", "0 0 30 2 *", "5-1 * * * *", "* * *", "@fortnightly"] {
+        let err = CronSchedule::parse(bad).unwrap_err();
+        println!("{}\n{}\n", err, err.render());
+    }
+}
+*/
@@ -0,0 +1,577 @@
+// Note: This example requires adding the `csv`, `serde_json`, and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// csv = "1"
+// serde_json = "1.0"
+// thiserror = "1.0"
+//
+// A small column-oriented table for quick data wrangling in scripts and CLIs: load a CSV
+// or JSON export, filter, sort, group, aggregate, and write the result back out. Column
+// types (integer, float, bool, text) are inferred on load; empty cells are nulls.
+//
+// It is deliberately simple (rows are copied on every operation, no lazy evaluation). For
+// millions of rows or joins across large tables, reach for `polars` or SQLite instead.
+
+use serde_json::{Map, Number, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Write};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TableError {
+    #[error("Unknown column '{0}'")]
+    UnknownColumn(String),
+    #[error("Column '{column}' has {actual} values, expected {expected}")]
+    LengthMismatch { column: String, expected: usize, actual: usize },
+    #[error("Column '{column}' is {actual}, expected a numeric column")]
+    NotNumeric { column: String, actual: &'static str },
+    #[error("JSON input must be an array of objects")]
+    NotAnArrayOfObjects,
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+type Result<T> = std::result::Result<T, TableError>;
+
+// --- Cells and columns ---
+
+/// One value. Floats compare and hash by bit pattern so cells can be group-by keys.
+#[derive(Debug, Clone)]
+enum Cell {
+    Null,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+}
+
+impl Cell {
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Cell::Int(n) => Some(*n as f64),
+            Cell::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Cell::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(self, Cell::Null)
+    }
+
+    /// Ordering for `sort_by`: numbers numerically (ints and floats mixed), text
+    /// lexicographically, nulls last.
+    fn compare(&self, other: &Cell) -> Ordering {
+        match (self, other) {
+            (Cell::Null, Cell::Null) => Ordering::Equal,
+            (Cell::Null, _) => Ordering::Greater,
+            (_, Cell::Null) => Ordering::Less,
+            (Cell::Text(a), Cell::Text(b)) => a.cmp(b),
+            (Cell::Bool(a), Cell::Bool(b)) => a.cmp(b),
+            (a, b) => match (a.as_f64(), b.as_f64()) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                _ => a.to_string().cmp(&b.to_string()),
+            },
+        }
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Cell::Null, Cell::Null) => true,
+            (Cell::Int(a), Cell::Int(b)) => a == b,
+            (Cell::Float(a), Cell::Float(b)) => a.to_bits() == b.to_bits(),
+            (Cell::Bool(a), Cell::Bool(b)) => a == b,
+            (Cell::Text(a), Cell::Text(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Cell {}
+
+impl Hash for Cell {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Cell::Null => {}
+            Cell::Int(n) => n.hash(state),
+            Cell::Float(f) => f.to_bits().hash(state),
+            Cell::Bool(b) => b.hash(state),
+            Cell::Text(s) => s.hash(state),
+        }
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cell::Null => Ok(()),
+            Cell::Int(n) => write!(f, "{}", n),
+            Cell::Float(x) => write!(f, "{}", x),
+            Cell::Bool(b) => write!(f, "{}", b),
+            Cell::Text(s) => f.write_str(s),
+        }
+    }
+}
+
+/// A typed column. Every value in a column has the same type (or is null).
+#[derive(Debug, Clone, PartialEq)]
+enum Column {
+    Int(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Bool(Vec<Option<bool>>),
+    Text(Vec<Option<String>>),
+}
+
+impl Column {
+    fn len(&self) -> usize {
+        match self {
+            Column::Int(v) => v.len(),
+            Column::Float(v) => v.len(),
+            Column::Bool(v) => v.len(),
+            Column::Text(v) => v.len(),
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Column::Int(_) => "int",
+            Column::Float(_) => "float",
+            Column::Bool(_) => "bool",
+            Column::Text(_) => "text",
+        }
+    }
+
+    fn get(&self, row: usize) -> Cell {
+        match self {
+            Column::Int(v) => v[row].map_or(Cell::Null, Cell::Int),
+            Column::Float(v) => v[row].map_or(Cell::Null, Cell::Float),
+            Column::Bool(v) => v[row].map_or(Cell::Null, Cell::Bool),
+            Column::Text(v) => v[row].clone().map_or(Cell::Null, Cell::Text),
+        }
+    }
+
+    /// A new column holding the given rows, in the given order.
+    fn take(&self, rows: &[usize]) -> Column {
+        match self {
+            Column::Int(v) => Column::Int(rows.iter().map(|&i| v[i]).collect()),
+            Column::Float(v) => Column::Float(rows.iter().map(|&i| v[i]).collect()),
+            Column::Bool(v) => Column::Bool(rows.iter().map(|&i| v[i]).collect()),
+            Column::Text(v) => Column::Text(rows.iter().map(|&i| v[i].clone()).collect()),
+        }
+    }
+
+    /// Builds the narrowest column type that fits every cell: int, then float (ints are
+    /// widened), then bool, otherwise text (everything stringified).
+    fn from_cells(cells: Vec<Cell>) -> Column {
+        let present = || cells.iter().filter(|c| !c.is_null());
+        if present().all(|c| matches!(c, Cell::Int(_))) {
+            Column::Int(cells.iter().map(|c| if let Cell::Int(n) = c { Some(*n) } else { None }).collect())
+        } else if present().all(|c| c.as_f64().is_some()) {
+            Column::Float(cells.iter().map(Cell::as_f64).collect())
+        } else if present().all(|c| matches!(c, Cell::Bool(_))) {
+            Column::Bool(cells.iter().map(|c| if let Cell::Bool(b) = c { Some(*b) } else { None }).collect())
+        } else {
+            Column::Text(cells.iter().map(|c| (!c.is_null()).then(|| c.to_string())).collect())
+        }
+    }
+}
+
+/// Infers a cell from CSV text: `""` -> null, then int, float, bool, text.
+fn parse_cell(text: &str) -> Cell {
+    let t = text.trim();
+    if t.is_empty() {
+        Cell::Null
+    } else if let Ok(n) = t.parse::<i64>() {
+        Cell::Int(n)
+    } else if let Some(f) = t.parse::<f64>().ok().filter(|_| t.bytes().any(|b| b.is_ascii_digit())) {
+        Cell::Float(f) // The digit check keeps words like "nan" and "infinity" as text
+    } else if t.eq_ignore_ascii_case("true") || t.eq_ignore_ascii_case("false") {
+        Cell::Bool(t.eq_ignore_ascii_case("true"))
+    } else {
+        Cell::Text(text.to_string())
+    }
+}
+
+// --- Table ---
+
+#[derive(Debug, Clone, PartialEq)]
+struct DataTable {
+    names: Vec<String>,
+    columns: Vec<Column>,
+}
+
+/// Read access to one row inside `filter` predicates.
+struct Row<'a> {
+    table: &'a DataTable,
+    index: usize,
+}
+
+impl Row<'_> {
+    /// The cell in `column`, or `Cell::Null` if there is no such column.
+    fn get(&self, column: &str) -> Cell {
+        self.table.position(column).map_or(Cell::Null, |c| self.table.columns[c].get(self.index))
+    }
+}
+
+impl DataTable {
+    /// Builds a table from named columns, which must all have the same length.
+    fn new(columns: Vec<(&str, Column)>) -> Result<Self> {
+        let expected = columns.first().map_or(0, |(_, c)| c.len());
+        for (name, column) in &columns {
+            if column.len() != expected {
+                return Err(TableError::LengthMismatch { column: name.to_string(), expected, actual: column.len() });
+            }
+        }
+        let (names, columns) = columns.into_iter().map(|(n, c)| (n.to_string(), c)).unzip();
+        Ok(Self { names, columns })
+    }
+
+    fn num_rows(&self) -> usize {
+        self.columns.first().map_or(0, Column::len)
+    }
+
+    fn column_names(&self) -> &[String] {
+        &self.names
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    fn column(&self, name: &str) -> Result<&Column> {
+        self.position(name)
+            .map(|i| &self.columns[i])
+            .ok_or_else(|| TableError::UnknownColumn(name.to_string()))
+    }
+
+    /// A table with the given rows (by index), in the given order.
+    fn take_rows(&self, rows: &[usize]) -> DataTable {
+        DataTable {
+            names: self.names.clone(),
+            columns: self.columns.iter().map(|c| c.take(rows)).collect(),
+        }
+    }
+
+    // --- Operations ---
+
+    /// Keeps only the named columns, in the order given.
+    fn select(&self, names: &[&str]) -> Result<DataTable> {
+        let columns = names.iter().map(|n| Ok((*n, self.column(n)?.clone()))).collect::<Result<Vec<_>>>()?;
+        DataTable::new(columns)
+    }
+
+    /// Keeps rows for which `predicate` returns `true`.
+    fn filter(&self, predicate: impl Fn(&Row) -> bool) -> DataTable {
+        let keep: Vec<usize> = (0..self.num_rows())
+            .filter(|&index| predicate(&Row { table: self, index }))
+            .collect();
+        self.take_rows(&keep)
+    }
+
+    /// Sorts by one column (stable, nulls last).
+    fn sort_by(&self, column: &str, descending: bool) -> Result<DataTable> {
+        let col = self.column(column)?;
+        let mut order: Vec<usize> = (0..self.num_rows()).collect();
+        order.sort_by(|&a, &b| {
+            let (x, y) = (col.get(a), col.get(b));
+            match (x.is_null(), y.is_null(), descending) {
+                (false, false, true) => y.compare(&x),
+                _ => x.compare(&y), // Nulls stay last in both directions
+            }
+        });
+        Ok(self.take_rows(&order))
+    }
+
+    /// The first `n` rows.
+    fn head(&self, n: usize) -> DataTable {
+        self.take_rows(&(0..n.min(self.num_rows())).collect::<Vec<_>>())
+    }
+
+    /// Adds (or replaces) a column computed from each row.
+    fn with_column(&self, name: &str, f: impl Fn(&Row) -> Cell) -> DataTable {
+        let cells = (0..self.num_rows()).map(|index| f(&Row { table: self, index })).collect();
+        let mut table = self.clone();
+        let column = Column::from_cells(cells);
+        match table.position(name) {
+            Some(i) => table.columns[i] = column,
+            None => {
+                table.names.push(name.to_string());
+                table.columns.push(column);
+            }
+        }
+        table
+    }
+
+    /// Groups rows by the values of `keys`; groups keep first-seen order.
+    fn group_by(&self, keys: &[&str]) -> Result<GroupBy<'_>> {
+        let key_columns = keys.iter().map(|k| self.column(k)).collect::<Result<Vec<_>>>()?;
+        let mut index: HashMap<Vec<Cell>, usize> = HashMap::new();
+        let mut groups: Vec<(Vec<Cell>, Vec<usize>)> = Vec::new();
+        for row in 0..self.num_rows() {
+            let key: Vec<Cell> = key_columns.iter().map(|c| c.get(row)).collect();
+            let slot = *index.entry(key.clone()).or_insert_with(|| {
+                groups.push((key, Vec::new()));
+                groups.len() - 1
+            });
+            groups[slot].1.push(row);
+        }
+        Ok(GroupBy { table: self, keys: keys.iter().map(|k| k.to_string()).collect(), groups })
+    }
+
+    // --- CSV and JSON ---
+
+    /// Reads CSV with a header row, inferring each column's type.
+    fn from_csv(reader: impl Read) -> Result<DataTable> {
+        let mut csv = csv::Reader::from_reader(reader);
+        let names: Vec<String> = csv.headers()?.iter().map(str::to_string).collect();
+        let mut cells: Vec<Vec<Cell>> = vec![Vec::new(); names.len()];
+        for record in csv.records() {
+            let record = record?;
+            for (c, column) in cells.iter_mut().enumerate() {
+                column.push(record.get(c).map_or(Cell::Null, parse_cell));
+            }
+        }
+        let columns = cells.into_iter().map(Column::from_cells).collect();
+        Ok(DataTable { names, columns })
+    }
+
+    fn to_csv(&self, writer: impl Write) -> Result<()> {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(&self.names)?;
+        for row in 0..self.num_rows() {
+            csv.write_record(self.columns.iter().map(|c| c.get(row).to_string()))?;
+        }
+        csv.flush().map_err(csv::Error::from)?;
+        Ok(())
+    }
+
+    /// Reads a JSON array of objects. Columns are the union of keys in first-seen order;
+    /// nested arrays and objects are stored as JSON text.
+    fn from_json(json: &str) -> Result<DataTable> {
+        let Value::Array(items) = serde_json::from_str(json)? else {
+            return Err(TableError::NotAnArrayOfObjects);
+        };
+        let mut names: Vec<String> = Vec::new();
+        let mut rows: Vec<Map<String, Value>> = Vec::with_capacity(items.len());
+        for item in items {
+            let Value::Object(map) = item else {
+                return Err(TableError::NotAnArrayOfObjects);
+            };
+            for key in map.keys() {
+                if !names.contains(key) {
+                    names.push(key.clone());
+                }
+            }
+            rows.push(map);
+        }
+        let columns = names
+            .iter()
+            .map(|name| {
+                Column::from_cells(
+                    rows.iter()
+                        .map(|row| match row.get(name) {
+                            None | Some(Value::Null) => Cell::Null,
+                            Some(Value::Bool(b)) => Cell::Bool(*b),
+                            Some(Value::Number(n)) => n.as_i64().map_or_else(|| Cell::Float(n.as_f64().unwrap_or(f64::NAN)), Cell::Int),
+                            Some(Value::String(s)) => Cell::Text(s.clone()),
+                            Some(other) => Cell::Text(other.to_string()),
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        Ok(DataTable { names, columns })
+    }
+
+    /// Writes a JSON array of objects; nulls are included as `null`.
+    fn to_json(&self) -> Value {
+        let rows = (0..self.num_rows())
+            .map(|row| {
+                let object = self.names.iter().zip(&self.columns).map(|(name, column)| {
+                    let value = match column.get(row) {
+                        Cell::Null => Value::Null,
+                        Cell::Int(n) => Value::from(n),
+                        Cell::Float(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+                        Cell::Bool(b) => Value::Bool(b),
+                        Cell::Text(s) => Value::String(s),
+                    };
+                    (name.clone(), value)
+                });
+                Value::Object(object.collect())
+            })
+            .collect();
+        Value::Array(rows)
+    }
+}
+
+/// Prints an aligned text table, numbers right-aligned. Floats are shown with at most six
+/// decimals so summation noise (`159.92000000000002`) doesn't widen columns; CSV and JSON
+/// output keep full precision.
+impl fmt::Display for DataTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |cell: Cell| match cell {
+            Cell::Float(x) if x.is_finite() => {
+                let text = format!("{:.6}", x);
+                text.trim_end_matches('0').trim_end_matches('.').to_string()
+            }
+            other => other.to_string(),
+        };
+        let cells: Vec<Vec<String>> = (0..self.num_rows())
+            .map(|row| self.columns.iter().map(|c| show(c.get(row))).collect())
+            .collect();
+        let widths: Vec<usize> = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(c, name)| cells.iter().map(|row| row[c].chars().count()).chain([name.chars().count()]).max().unwrap_or(0))
+            .collect();
+        let numeric: Vec<bool> = self.columns.iter().map(|c| matches!(c, Column::Int(_) | Column::Float(_))).collect();
+        let line = |values: Vec<&str>| -> String {
+            values
+                .iter()
+                .enumerate()
+                .map(|(c, v)| if numeric[c] { format!("{:>w$}", v, w = widths[c]) } else { format!("{:<w$}", v, w = widths[c]) })
+                .collect::<Vec<_>>()
+                .join("  ")
+        };
+        writeln!(f, "{}", line(self.names.iter().map(String::as_str).collect()).trim_end())?;
+        writeln!(f, "{}", widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("  "))?;
+        for row in &cells {
+            writeln!(f, "{}", line(row.iter().map(String::as_str).collect()).trim_end())?;
+        }
+        write!(f, "({} rows)", self.num_rows())
+    }
+}
+
+// --- Grouping and aggregation ---
+
+/// An aggregate over one group. Output columns are named `count`, `sum_<col>`, `mean_<col>`, ...
+#[derive(Debug, Clone, Copy)]
+enum Agg<'a> {
+    /// Rows in the group.
+    Count,
+    /// Non-null values in the column.
+    CountNonNull(&'a str),
+    Sum(&'a str),
+    Mean(&'a str),
+    Min(&'a str),
+    Max(&'a str),
+}
+
+struct GroupBy<'t> {
+    table: &'t DataTable,
+    keys: Vec<String>,
+    groups: Vec<(Vec<Cell>, Vec<usize>)>,
+}
+
+impl GroupBy<'_> {
+    /// One row per group: the key columns followed by one column per aggregate.
+    /// `Sum`/`Mean` require a numeric column; `Min`/`Max` work on any type.
+    fn aggregate(&self, aggs: &[Agg]) -> Result<DataTable> {
+        let mut names = self.keys.clone();
+        let mut cells: Vec<Vec<Cell>> = (0..self.keys.len())
+            .map(|k| self.groups.iter().map(|(key, _)| key[k].clone()).collect())
+            .collect();
+
+        for agg in aggs {
+            let (name, column) = match agg {
+                Agg::Count => ("count".to_string(), None),
+                Agg::CountNonNull(c) => (format!("count_{}", c), Some(*c)),
+                Agg::Sum(c) => (format!("sum_{}", c), Some(*c)),
+                Agg::Mean(c) => (format!("mean_{}", c), Some(*c)),
+                Agg::Min(c) => (format!("min_{}", c), Some(*c)),
+                Agg::Max(c) => (format!("max_{}", c), Some(*c)),
+            };
+            let column = column.map(|c| self.table.column(c).map(|col| (c, col))).transpose()?;
+            if let (Agg::Sum(_) | Agg::Mean(_), Some((c, col))) = (agg, column) {
+                if !matches!(col, Column::Int(_) | Column::Float(_)) {
+                    return Err(TableError::NotNumeric { column: c.to_string(), actual: col.type_name() });
+                }
+            }
+
+            let values = self.groups.iter().map(|(_, rows)| {
+                let present = || rows.iter().map(|&r| column.map_or(Cell::Null, |(_, col)| col.get(r))).filter(|c| !c.is_null());
+                match agg {
+                    Agg::Count => Cell::Int(rows.len() as i64),
+                    Agg::CountNonNull(_) => Cell::Int(present().count() as i64),
+                    Agg::Sum(_) => match column {
+                        Some((_, Column::Int(_))) => Cell::Int(present().filter_map(|c| if let Cell::Int(n) = c { Some(n) } else { None }).sum()),
+                        _ => Cell::Float(present().filter_map(|c| c.as_f64()).sum()),
+                    },
+                    Agg::Mean(_) => {
+                        let values: Vec<f64> = present().filter_map(|c| c.as_f64()).collect();
+                        if values.is_empty() {
+                            Cell::Null
+                        } else {
+                            Cell::Float(values.iter().sum::<f64>() / values.len() as f64)
+                        }
+                    }
+                    Agg::Min(_) => present().min_by(|a, b| a.compare(b)).unwrap_or(Cell::Null),
+                    Agg::Max(_) => present().max_by(|a, b| a.compare(b)).unwrap_or(Cell::Null),
+                }
+            });
+            names.push(name);
+            cells.push(values.collect());
+        }
+
+        let columns = cells.into_iter().map(Column::from_cells).collect();
+        Ok(DataTable { names, columns })
+    }
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    let csv = "\
+date,region,product,units,price,returned
+2024-05-01,EU,widget,3,19.99,false
+2024-05-01,NA,gadget,1,250.00,false
+2024-05-02,EU,gadget,2,245.00,true
+2024-05-02,APAC,widget,10,18.50,false
+2024-05-03,NA,widget,,19.99,false
+2024-05-03,EU,widget,5,19.99,false
+";
+    let sales = DataTable::from_csv(csv.as_bytes())?;
+    let types: Vec<String> = sales.column_names().iter().map(|n| format!("{}: {}", n, sales.column(n).unwrap().type_name())).collect();
+    println!("{}\n{}\n", types.join(", "), sales);
+
+    let sales = sales.with_column("revenue", |row| match (row.get("units").as_f64(), row.get("price").as_f64()) {
+        (Some(units), Some(price)) => Cell::Float((units * price * 100.0).round() / 100.0),
+        _ => Cell::Null,
+    });
+    let kept = sales.filter(|row| matches!(row.get("returned"), Cell::Bool(false)));
+    println!("{}\n", kept.select(&["date", "region", "product", "revenue"])?.sort_by("revenue", true)?);
+
+    let summary = kept
+        .group_by(&["region"])?
+        .aggregate(&[Agg::Count, Agg::Sum("units"), Agg::Sum("revenue"), Agg::Mean("price"), Agg::Max("date")])?
+        .sort_by("sum_revenue", true)?;
+    println!("{}\n", summary);
+
+    println!("{}\n", kept.group_by(&["region", "product"])?.aggregate(&[Agg::CountNonNull("units")])?);
+    println!("{}", kept.group_by(&["region"])?.aggregate(&[Agg::Sum("product")]).unwrap_err());
+    println!("{}\n", sales.select(&["region", "nope"]).unwrap_err());
+
+    let mut out = Vec::new();
+    summary.head(2).to_csv(&mut out)?;
+    println!("{}", String::from_utf8_lossy(&out));
+    println!("{}", serde_json::to_string(&summary.head(1).to_json())?);
+
+    let from_json = DataTable::from_json(r#"[{"name": "a", "score": 1}, {"name": "b", "score": 2.5, "tags": ["x"]}, {"name": "c"}]"#)?;
+    println!("\n{}", from_json);
+    Ok(())
+}
+*/
@@ -0,0 +1,254 @@
+// Note: This example requires adding the `chrono`, `chrono-tz`, and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// chrono = "0.4"
+// chrono-tz = "0.10"
+// thiserror = "1.0"
+//
+// Conventions used here: store and compute in UTC (`DateTime<Utc>`), convert to a named
+// time zone (`chrono_tz::Tz`, not a fixed offset) only for display or for interpreting
+// user input, and use `std::time::Duration` for timeouts and intervals.
+
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DateTimeError {
+    #[error("Could not parse '{input}' as a date/time (tried: {tried})")]
+    Parse { input: String, tried: String },
+    #[error("Unknown time zone '{0}' (use an IANA name like \"Europe/Berlin\")")]
+    UnknownTimezone(String),
+    #[error("{local} does not exist in {tz} (skipped by a daylight-saving change)")]
+    NonexistentLocalTime { local: NaiveDateTime, tz: Tz },
+    #[error("Invalid duration '{input}': {reason}")]
+    InvalidDuration { input: String, reason: String },
+}
+
+type Result<T> = std::result::Result<T, DateTimeError>;
+
+// --- Parsing ---
+
+/// Parses an RFC 3339 / ISO 8601 timestamp such as `2024-05-01T12:30:00+02:00`.
+fn parse_rfc3339(input: &str) -> Result<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(input.trim()).map_err(|e| DateTimeError::Parse {
+        input: input.to_string(),
+        tried: format!("RFC 3339 ({})", e),
+    })
+}
+
+/// Parses an RFC 2822 timestamp as used in email and HTTP headers: `Wed, 01 May 2024 12:30:00 +0000`.
+fn parse_rfc2822(input: &str) -> Result<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc2822(input.trim()).map_err(|e| DateTimeError::Parse {
+        input: input.to_string(),
+        tried: format!("RFC 2822 ({})", e),
+    })
+}
+
+/// Naive formats accepted by `parse_flexible`; they carry no offset and are read in `tz`.
+const NAIVE_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%d/%m/%Y %H:%M"];
+
+/// Parses the formats people actually paste into CLIs and config files.
+///
+/// Tries, in order: RFC 3339, RFC 2822, Unix seconds (`1714566600`), the naive formats in
+/// `NAIVE_FORMATS`, and a bare date (`2024-05-01`, midnight). Inputs without an offset
+/// are interpreted in `tz`.
+///
+/// # Returns
+///
+/// The instant in UTC, or an error listing every format that was tried.
+fn parse_flexible(input: &str, tz: Tz) -> Result<DateTime<Utc>> {
+    let s = input.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc2822(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if s.len() >= 9 && s.bytes().all(|b| b.is_ascii_digit()) {
+        if let Some(dt) = s.parse::<i64>().ok().and_then(|secs| DateTime::from_timestamp(secs, 0)) {
+            return Ok(dt);
+        }
+    }
+    for format in NAIVE_FORMATS {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, format) {
+            return local_to_utc(naive, tz);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return local_to_utc(date.and_hms_opt(0, 0, 0).expect("midnight is valid"), tz);
+    }
+    Err(DateTimeError::Parse {
+        input: input.to_string(),
+        tried: format!("RFC 3339, RFC 2822, unix seconds, {}, %Y-%m-%d", NAIVE_FORMATS.join(", ")),
+    })
+}
+
+// --- Time zones ---
+
+fn parse_tz(name: &str) -> Result<Tz> {
+    name.parse::<Tz>().map_err(|_| DateTimeError::UnknownTimezone(name.to_string()))
+}
+
+/// Interprets a wall-clock time in `tz` and converts it to UTC.
+///
+/// Daylight-saving transitions make this partial: in the autumn an hour repeats (we pick
+/// the earlier instant), and in the spring an hour is skipped (an error, since e.g.
+/// 02:30 on that day never happened).
+fn local_to_utc(local: NaiveDateTime, tz: Tz) -> Result<DateTime<Utc>> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(dt) => Ok(dt.with_timezone(&Utc)),
+        LocalResult::Ambiguous(earliest, _) => Ok(earliest.with_timezone(&Utc)),
+        LocalResult::None => Err(DateTimeError::NonexistentLocalTime { local, tz }),
+    }
+}
+
+/// Formats a UTC instant in a named zone, e.g. `2024-05-01 14:30 CEST`.
+fn format_in_tz(dt: DateTime<Utc>, tz: Tz, format: &str) -> String {
+    dt.with_timezone(&tz).format(format).to_string()
+}
+
+// --- Human-readable relative times ---
+
+/// Formats `then` relative to `now`: "just now", "5 minutes ago", "in 3 hours", "yesterday".
+fn format_relative(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let delta = now.signed_duration_since(then);
+    let future = delta.num_seconds() < 0;
+    let secs = delta.num_seconds().unsigned_abs();
+
+    let (amount, unit) = match secs {
+        0..=44 => return "just now".to_string(),
+        45..=89 => (1, "minute"),
+        90..=2_699 => ((secs + 30) / 60, "minute"),
+        2_700..=5_399 => (1, "hour"),
+        5_400..=79_199 => ((secs + 1_800) / 3_600, "hour"),
+        79_200..=172_799 => return if future { "tomorrow" } else { "yesterday" }.to_string(),
+        172_800..=2_591_999 => ((secs + 43_200) / 86_400, "day"),
+        2_592_000..=31_535_999 => ((secs + 1_296_000) / 2_592_000, "month"),
+        _ => ((secs + 15_768_000) / 31_536_000, "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    if future {
+        format!("in {} {}{}", amount, unit, plural)
+    } else {
+        format!("{} {}{} ago", amount, unit, plural)
+    }
+}
+
+// --- Durations ---
+
+/// Parses durations like `1h30m`, `90s`, `250ms`, `2d`, `1w 2d`, or a bare number of seconds.
+///
+/// Units: `ms`, `s`, `m`, `h`, `d`, `w`. Components may be separated by spaces and must
+/// not repeat a unit.
+fn parse_duration(input: &str) -> Result<Duration> {
+    let invalid = |reason: &str| DateTimeError::InvalidDuration {
+        input: input.to_string(),
+        reason: reason.to_string(),
+    };
+    let s: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if s.is_empty() {
+        return Err(invalid("empty"));
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut seen_units: Vec<&str> = Vec::new();
+    let mut rest = s.as_str();
+    while !rest.is_empty() {
+        let digits_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits_end == 0 {
+            return Err(invalid("expected a number"));
+        }
+        let value: u64 = rest[..digits_end].parse().map_err(|_| invalid("number too large"))?;
+        rest = &rest[digits_end..];
+        let unit_end = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit = &rest[..unit_end];
+        rest = &rest[unit_end..];
+
+        let millis_per_unit: u64 = match unit {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            "w" => 604_800_000,
+            "" => return Err(invalid("missing unit after number")),
+            other => return Err(invalid(&format!("unknown unit '{}' (use ms, s, m, h, d, w)", other))),
+        };
+        if seen_units.contains(&unit) {
+            return Err(invalid(&format!("unit '{}' appears twice", unit)));
+        }
+        seen_units.push(unit);
+        let millis = value.checked_mul(millis_per_unit).ok_or_else(|| invalid("overflow"))?;
+        total = total.checked_add(Duration::from_millis(millis)).ok_or_else(|| invalid("overflow"))?;
+    }
+    Ok(total)
+}
+
+/// `parse_duration` with the signature clap expects:
+/// `#[arg(long, value_parser = parse_duration_arg)] timeout: Duration`.
+fn parse_duration_arg(input: &str) -> std::result::Result<Duration, String> {
+    parse_duration(input).map_err(|e| e.to_string())
+}
+
+/// Formats a duration compactly in the same syntax `parse_duration` accepts: `1h30m`, `250ms`.
+fn format_duration(duration: Duration) -> String {
+    let mut millis = duration.as_millis();
+    if millis == 0 {
+        return "0s".to_string();
+    }
+    let mut out = String::new();
+    for (unit, size) in [("d", 86_400_000u128), ("h", 3_600_000), ("m", 60_000), ("s", 1_000), ("ms", 1)] {
+        if millis >= size {
+            out.push_str(&format!("{}{}", millis / size, unit));
+            millis %= size;
+        }
+    }
+    out
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    let berlin = parse_tz("Europe/Berlin")?;
+    let inputs = [
+        "2024-05-01T12:30:00+02:00",
+        "Wed, 01 May 2024 10:30:00 +0000",
+        "1714559400",
+        "2024-05-01 12:30:00",
+        "2024-05-01",
+        "next tuesday",
+    ];
+    for input in inputs {
+        match parse_flexible(input, berlin) {
+            Ok(dt) => println!("{:<34} -> {}  ({})", input, dt.to_rfc3339(), format_in_tz(dt, berlin, "%Y-%m-%d %H:%M %Z")),
+            Err(e) => println!("{:<34} -> {}", input, e),
+        }
+    }
+    println!("{}", parse_tz("Mars/Olympus").unwrap_err());
+
+    // DST edge cases in Berlin: 2024-03-31 02:30 never happened; 2024-10-27 02:30 happened twice.
+    let gap = NaiveDateTime::parse_from_str("2024-03-31 02:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    println!("\n{}", local_to_utc(gap, berlin).unwrap_err());
+    let overlap = NaiveDateTime::parse_from_str("2024-10-27 02:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    println!("Ambiguous 02:30 resolves to {}", local_to_utc(overlap, berlin)?);
+
+    let now = Utc::now();
+    println!();
+    for secs in [-10i64, -120, -4_000, -30_000, -100_000, -400_000, -5_000_000, -80_000_000, 7_200] {
+        println!("{:>10}s -> {}", secs, format_relative(now + chrono::Duration::seconds(secs), now));
+    }
+
+    println!();
+    for input in ["1h30m", "90s", "250ms", "1w 2d", "45", "2h2h", "10 years", "h"] {
+        match parse_duration(input) {
+            Ok(d) => println!("{:<8} -> {:?} ({})", input, d, format_duration(d)),
+            Err(e) => println!("{:<8} -> {}", input, e),
+        }
+    }
+    Ok(())
+}
+*/
@@ -0,0 +1,151 @@
+// Note: This example requires adding the `tokio` crate to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::task::{Id, JoinSet};
+use tokio::time::{sleep, timeout_at, Duration, Instant};
+
+/// The outcome of `run_all_with_deadline`.
+/// Every input future ends up in exactly one of the three lists, identified by its
+/// position in the input.
+#[derive(Debug)]
+struct PartialResults<T> {
+    /// Futures that finished before the deadline, in completion order.
+    completed: Vec<(usize, T)>,
+    /// Futures that panicked before the deadline (index, panic message).
+    failed: Vec<(usize, String)>,
+    /// Futures still running at the deadline. They have been aborted.
+    cancelled: Vec<usize>,
+}
+
+impl<T> PartialResults<T> {
+    /// True when every future completed successfully.
+    fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.cancelled.is_empty()
+    }
+
+    /// Completed values sorted back into input order.
+    fn into_ordered_values(mut self) -> Vec<(usize, T)> {
+        self.completed.sort_by_key(|(index, _)| *index);
+        self.completed
+    }
+}
+
+/// Runs all futures concurrently and collects whatever finishes before `deadline`.
+///
+/// Unlike `tokio::join!` (all-or-nothing, no deadline) or wrapping the whole join in
+/// `timeout` (which throws away results that already arrived), this returns the partial
+/// results and reports exactly which tasks were cut off, so callers can degrade gracefully
+/// (e.g. render a page with the 4 of 5 backends that answered in time).
+///
+/// # Arguments
+/// * `futures` - The futures to run. Each is spawned as its own task.
+/// * `deadline` - Absolute point in time after which remaining tasks are aborted.
+///
+/// # Returns
+/// * `PartialResults<T>` - Completed values, panicked tasks, and cancelled task indices.
+async fn run_all_with_deadline<I, F, T>(futures: I, deadline: Instant) -> PartialResults<T>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let mut set = JoinSet::new();
+    // Map tokio task ids back to the caller's input positions.
+    let mut pending: HashMap<Id, usize> = HashMap::new();
+    for (index, fut) in futures.into_iter().enumerate() {
+        let handle = set.spawn(fut);
+        pending.insert(handle.id(), index);
+    }
+
+    let mut results = PartialResults {
+        completed: Vec::with_capacity(pending.len()),
+        failed: Vec::new(),
+        cancelled: Vec::new(),
+    };
+
+    loop {
+        match timeout_at(deadline, set.join_next_with_id()).await {
+            // A task finished normally.
+            Ok(Some(Ok((id, value)))) => {
+                if let Some(index) = pending.remove(&id) {
+                    results.completed.push((index, value));
+                }
+            }
+            // A task panicked (it cannot have been cancelled: we only abort after the loop).
+            Ok(Some(Err(join_err))) => {
+                if let Some(index) = pending.remove(&join_err.id()) {
+                    results.failed.push((index, join_err.to_string()));
+                }
+            }
+            // All tasks finished before the deadline.
+            Ok(None) => break,
+            // Deadline reached: whatever is still pending gets cancelled.
+            Err(_elapsed) => break,
+        }
+    }
+
+    // Abort stragglers. Dropping the JoinSet would also abort them, but doing it
+    // explicitly documents the intent and lets them start unwinding immediately.
+    set.abort_all();
+    results.cancelled = pending.into_values().collect();
+    results.cancelled.sort_unstable();
+    results
+}
+
+/// Convenience wrapper taking a relative timeout instead of an absolute deadline.
+async fn run_all_with_timeout<I, F, T>(futures: I, timeout: Duration) -> PartialResults<T>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    run_all_with_deadline(futures, Instant::now() + timeout).await
+}
+
+/// Simulates an API call with a given latency.
+async fn fake_backend_call(name: &'static str, latency_ms: u64) -> String {
+    sleep(Duration::from_millis(latency_ms)).await;
+    format!("{} responded after {}ms", name, latency_ms)
+}
+
+// Example Usage (requires a Tokio runtime)
+/*
+#[tokio::main]
+async fn main() {
+    let backends = [("users", 50), ("orders", 120), ("recommendations", 900), ("inventory", 80)];
+
+    let calls = backends
+        .iter()
+        .map(|&(name, latency)| fake_backend_call(name, latency));
+
+    let deadline = Instant::now() + Duration::from_millis(300);
+    let outcome = run_all_with_deadline(calls, deadline).await;
+
+    println!("Complete: {}", outcome.is_complete());
+    for index in &outcome.cancelled {
+        println!("Cancelled at deadline: {}", backends[*index].0);
+    }
+    for (index, message) in &outcome.failed {
+        println!("Failed: {} ({})", backends[*index].0, message);
+    }
+    for (index, value) in outcome.into_ordered_values() {
+        println!("[{}] {}", index, value);
+    }
+
+    println!("\n--- Panicking task is reported, not propagated ---");
+    // Boxing lets futures of different concrete types share one Vec.
+    let futures: Vec<std::pin::Pin<Box<dyn Future<Output = u32> + Send>>> = vec![
+        Box::pin(async { 1 }),
+        Box::pin(async { panic!("backend exploded") }),
+        Box::pin(async {
+            sleep(Duration::from_secs(5)).await;
+            3
+        }),
+    ];
+    let outcome = run_all_with_timeout(futures, Duration::from_millis(100)).await;
+    println!("{:?}", outcome);
+}
+*/
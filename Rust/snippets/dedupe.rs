@@ -0,0 +1,324 @@
+// This example only uses the standard library.
+//
+// Removing duplicates from data too big to load at once:
+// * `Dedup` - an iterator adapter that drops records whose key was already seen. Choose how
+//   much memory to spend: exact (8 bytes per distinct key), a sliding window of recent keys,
+//   or a fixed-size Bloom filter that may drop a small fraction of unique records.
+// * `merge_sorted` - intersection, difference, union, and symmetric difference of two
+//   *sorted* line files by streaming both at once, in constant memory. Sort the inputs
+//   with `LC_ALL=C sort -u` (byte order, the same order Rust's `str` comparison uses).
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, Write};
+
+// --- Streaming dedup ---
+
+/// 64-bit hash of a key. `DefaultHasher::new()` uses fixed keys, so hashes are stable across
+/// runs of the same binary (but not guaranteed across Rust versions; don't persist them).
+fn hash_key<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// How `Dedup` remembers keys it has seen.
+#[derive(Debug, Clone, Copy)]
+enum Strategy {
+    /// Every distinct key's 64-bit hash (~8-16 bytes each). Exact unless two keys' hashes
+    /// collide; the chance of any collision stays under 1% up to about 500 million keys.
+    Exact,
+    /// Only the last `n` distinct keys. Catches duplicates that arrive close together
+    /// (retries, replayed messages, adjacent rows) in fixed memory.
+    Window(usize),
+    /// A Bloom filter sized for `expected_items` at `false_positive_rate`. Fixed memory
+    /// (about 1.2 bytes per item at 1%), but a unique record is wrongly dropped with
+    /// that probability.
+    Bloom { expected_items: usize, false_positive_rate: f64 },
+}
+
+/// A minimal fixed-size Bloom filter over pre-hashed keys.
+#[derive(Debug)]
+struct BloomBits {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomBits {
+    fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        let num_bits = (-(n * p.ln()) / (2f64.ln().powi(2))).ceil().max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * 2f64.ln()).round().clamp(1.0, 16.0) as u32;
+        Self { bits: vec![0; num_bits.div_ceil(64) as usize], num_bits, num_hashes }
+    }
+
+    /// Sets the key's bits and returns whether they were all already set.
+    fn check_and_set(&mut self, hash: u64) -> bool {
+        // Double hashing: bit_i = h1 + i * h2 simulates k independent hash functions.
+        let (h1, h2) = (hash & 0xFFFF_FFFF, (hash >> 32) | 1);
+        let mut present = true;
+        for i in 0..u64::from(self.num_hashes) {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % self.num_bits;
+            let (word, mask) = ((bit / 64) as usize, 1u64 << (bit % 64));
+            present &= self.bits[word] & mask != 0;
+            self.bits[word] |= mask;
+        }
+        present
+    }
+}
+
+#[derive(Debug)]
+enum Seen {
+    Exact(HashSet<u64>),
+    Window { set: HashSet<u64>, order: VecDeque<u64>, capacity: usize },
+    Bloom(BloomBits),
+}
+
+impl Seen {
+    fn new(strategy: Strategy) -> Self {
+        match strategy {
+            Strategy::Exact => Seen::Exact(HashSet::new()),
+            Strategy::Window(capacity) => Seen::Window {
+                set: HashSet::with_capacity(capacity),
+                order: VecDeque::with_capacity(capacity),
+                capacity: capacity.max(1),
+            },
+            Strategy::Bloom { expected_items, false_positive_rate } => {
+                Seen::Bloom(BloomBits::new(expected_items, false_positive_rate))
+            }
+        }
+    }
+
+    /// Records `hash`; returns `true` if it was (probably) seen before.
+    fn check_and_insert(&mut self, hash: u64) -> bool {
+        match self {
+            Seen::Exact(set) => !set.insert(hash),
+            Seen::Window { set, order, capacity } => {
+                if set.contains(&hash) {
+                    return true;
+                }
+                if order.len() == *capacity {
+                    if let Some(oldest) = order.pop_front() {
+                        set.remove(&oldest);
+                    }
+                }
+                set.insert(hash);
+                order.push_back(hash);
+                false
+            }
+            Seen::Bloom(bloom) => bloom.check_and_set(hash),
+        }
+    }
+}
+
+/// Iterator adapter yielding only the first item for each key. Order is preserved.
+struct Dedup<I, F> {
+    inner: I,
+    key: F,
+    seen: Seen,
+    duplicates: u64,
+}
+
+impl<I, F, K> Iterator for Dedup<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> K,
+    K: Hash,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        for item in self.inner.by_ref() {
+            if self.seen.check_and_insert(hash_key(&(self.key)(&item))) {
+                self.duplicates += 1;
+            } else {
+                return Some(item);
+            }
+        }
+        None
+    }
+}
+
+impl<I, F> Dedup<I, F> {
+    /// Items dropped so far.
+    fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+}
+
+trait DedupExt: Iterator + Sized {
+    /// Drops items whose `key` was already seen: `rows.dedup_by_key_with(|r| r.email.clone(), Strategy::Exact)`.
+    fn dedup_by_key_with<K: Hash, F: FnMut(&Self::Item) -> K>(self, key: F, strategy: Strategy) -> Dedup<Self, F> {
+        Dedup { inner: self, key, seen: Seen::new(strategy), duplicates: 0 }
+    }
+}
+
+impl<I: Iterator> DedupExt for I {}
+
+// --- Set operations on sorted files ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetOp {
+    /// Lines in both inputs (`comm -12`).
+    Intersection,
+    /// Lines in `a` but not `b` (`comm -23`).
+    Difference,
+    /// Lines in either input, once each.
+    Union,
+    /// Lines in exactly one input.
+    SymmetricDifference,
+}
+
+/// Lines read from each input (an intersection stops reading once either side ends) and
+/// lines written.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct MergeStats {
+    lines_a: u64,
+    lines_b: u64,
+    written: u64,
+}
+
+/// Reads lines and checks they are in strictly increasing byte order, so an unsorted or
+/// non-deduplicated input fails loudly instead of silently producing a wrong result.
+struct SortedLines<R> {
+    lines: io::Lines<R>,
+    previous: Option<String>,
+    name: &'static str,
+    count: u64,
+}
+
+impl<R: BufRead> SortedLines<R> {
+    fn new(reader: R, name: &'static str) -> Self {
+        Self { lines: reader.lines(), previous: None, name, count: 0 }
+    }
+
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        let Some(line) = self.lines.next().transpose()? else {
+            return Ok(None);
+        };
+        self.count += 1;
+        if let Some(previous) = &self.previous {
+            if line.as_str() <= previous.as_str() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "input {} is not sorted and unique at line {}: {:?} after {:?} (sort with `LC_ALL=C sort -u`)",
+                        self.name, self.count, line, previous
+                    ),
+                ));
+            }
+        }
+        self.previous = Some(line.clone());
+        Ok(Some(line))
+    }
+}
+
+/// Applies `op` to two sorted, de-duplicated line streams, writing result lines to `out`.
+///
+/// Memory use is two lines regardless of input size; each input is read exactly once.
+///
+/// # Arguments
+///
+/// * `a`, `b` - Line-oriented inputs sorted in byte order with no repeated lines.
+/// * `op` - Which set operation to compute.
+/// * `out` - Receives the result, also sorted, one line per entry.
+///
+/// # Returns
+///
+/// Line counts, or `InvalidData` if either input turns out not to be sorted and unique.
+fn merge_sorted(a: impl BufRead, b: impl BufRead, op: SetOp, mut out: impl Write) -> io::Result<MergeStats> {
+    let (mut a, mut b) = (SortedLines::new(a, "a"), SortedLines::new(b, "b"));
+    let (mut x, mut y) = (a.next_line()?, b.next_line()?);
+    let mut written = 0;
+    let mut emit = |line: &str| -> io::Result<()> {
+        written += 1;
+        writeln!(out, "{}", line)
+    };
+
+    loop {
+        match (&x, &y) {
+            (None, None) => break,
+            (Some(l), None) => {
+                if matches!(op, SetOp::Difference | SetOp::Union | SetOp::SymmetricDifference) {
+                    emit(l)?;
+                }
+                x = a.next_line()?;
+            }
+            (None, Some(r)) => {
+                if matches!(op, SetOp::Union | SetOp::SymmetricDifference) {
+                    emit(r)?;
+                }
+                y = b.next_line()?;
+            }
+            (Some(l), Some(r)) => match l.cmp(r) {
+                std::cmp::Ordering::Less => {
+                    if matches!(op, SetOp::Difference | SetOp::Union | SetOp::SymmetricDifference) {
+                        emit(l)?;
+                    }
+                    x = a.next_line()?;
+                }
+                std::cmp::Ordering::Greater => {
+                    if matches!(op, SetOp::Union | SetOp::SymmetricDifference) {
+                        emit(r)?;
+                    }
+                    y = b.next_line()?;
+                }
+                std::cmp::Ordering::Equal => {
+                    if matches!(op, SetOp::Intersection | SetOp::Union) {
+                        emit(l)?;
+                    }
+                    x = a.next_line()?;
+                    y = b.next_line()?;
+                }
+            },
+        }
+        // Intersection can stop as soon as either side is exhausted.
+        if op == SetOp::Intersection && (x.is_none() || y.is_none()) {
+            break;
+        }
+    }
+    Ok(MergeStats { lines_a: a.count, lines_b: b.count, written })
+}
+
+// Example Usage
+/*
+use std::io::BufReader;
+
+fn main() -> io::Result<()> {
+    let events = ["login:alice", "view:bob", "login:alice", "buy:carol", "view:bob", "login:dave", "login:alice"];
+
+    let mut exact = events.iter().dedup_by_key_with(|e| e.to_string(), Strategy::Exact);
+    let unique: Vec<_> = exact.by_ref().collect();
+    println!("exact:  {:?} ({} dropped)", unique, exact.duplicates());
+
+    // Window of 2: "login:alice" reappears after falling out of the window, so it is kept.
+    let windowed: Vec<_> = events.iter().dedup_by_key_with(|e| e.to_string(), Strategy::Window(2)).collect();
+    println!("window: {:?}", windowed);
+
+    // Key on part of a record: first event per user.
+    let per_user: Vec<_> = events.iter().dedup_by_key_with(|e| e.split(':').nth(1).map(str::to_string), Strategy::Exact).collect();
+    println!("per user: {:?}", per_user);
+
+    // Bloom filter: 1M distinct IDs plus 1M repeats in ~1.2 MB instead of ~16+ MB.
+    let ids = (0..1_000_000u64).chain(0..1_000_000u64);
+    let strategy = Strategy::Bloom { expected_items: 1_000_000, false_positive_rate: 0.01 };
+    let mut bloom = ids.dedup_by_key_with(|id| *id, strategy);
+    let kept = bloom.by_ref().count();
+    println!("bloom:  kept {} of 1000000 unique ids ({} wrongly dropped), dropped {}\n", kept, 1_000_000 - kept, bloom.duplicates());
+
+    // Sorted-file set operations (in-memory readers stand in for large files here).
+    let yesterday = "alice\nbob\ncarol\ndave\n";
+    let today = "bob\ndave\nerin\nfrank\n";
+    for op in [SetOp::Intersection, SetOp::Difference, SetOp::Union, SetOp::SymmetricDifference] {
+        let mut out = Vec::new();
+        let stats = merge_sorted(BufReader::new(yesterday.as_bytes()), BufReader::new(today.as_bytes()), op, &mut out)?;
+        println!("{:?}: {:?} {:?}", op, String::from_utf8_lossy(&out).lines().collect::<Vec<_>>(), stats);
+    }
+    let unsorted = merge_sorted(BufReader::new("b\na\n".as_bytes()), BufReader::new("a\n".as_bytes()), SetOp::Union, io::sink());
+    println!("{}", unsorted.unwrap_err());
+    Ok(())
+}
+*/
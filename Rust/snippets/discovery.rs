@@ -0,0 +1,232 @@
+// Note: This example requires adding the `mdns-sd`, `tokio`, and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// mdns-sd = "0.13"
+// tokio = { version = "1", features = ["full"] }
+// thiserror = "1.0"
+//
+// mDNS/DNS-SD ("zeroconf", "Bonjour") lets processes on the same LAN find each other without
+// a registry: each instance advertises `<instance>._myapp._tcp.local.` with a port and TXT
+// metadata, and peers browse for that service type. Pair it with the servers in `tcp.rs`
+// or the WebSocket snippets: advertise the port you bound, connect to the peers you find.
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    #[error("mDNS error: {0}")]
+    Mdns(#[from] mdns_sd::Error),
+    #[error("Invalid service type '{0}': expected something like \"_myapp._tcp.local.\"")]
+    InvalidServiceType(String),
+}
+
+type Result<T> = std::result::Result<T, DiscoveryError>;
+
+/// Service types must look like `_name._tcp.local.` (or `_udp`).
+fn check_service_type(service_type: &str) -> Result<()> {
+    let valid = service_type.starts_with('_')
+        && (service_type.ends_with("._tcp.local.") || service_type.ends_with("._udp.local."));
+    if valid {
+        Ok(())
+    } else {
+        Err(DiscoveryError::InvalidServiceType(service_type.to_string()))
+    }
+}
+
+/// A resolved peer.
+#[derive(Debug, Clone, PartialEq)]
+struct Peer {
+    /// Full instance name, e.g. `node-a._myapp._tcp.local.`; unique per peer.
+    instance: String,
+    hostname: String,
+    addrs: Vec<IpAddr>,
+    port: u16,
+    /// TXT record metadata, e.g. `version=1.4.0`, `role=worker`.
+    txt: HashMap<String, String>,
+}
+
+impl Peer {
+    /// Addresses to try when connecting, IPv4 first (link-local IPv6 needs a scope id).
+    fn socket_addrs(&self) -> Vec<SocketAddr> {
+        let mut addrs: Vec<SocketAddr> = self.addrs.iter().map(|ip| SocketAddr::new(*ip, self.port)).collect();
+        addrs.sort_by_key(|a| !a.is_ipv4());
+        addrs
+    }
+
+    fn from_info(info: &ServiceInfo) -> Self {
+        let mut addrs: Vec<IpAddr> = info.get_addresses().iter().copied().collect();
+        addrs.sort();
+        Self {
+            instance: info.get_fullname().to_string(),
+            hostname: info.get_hostname().to_string(),
+            addrs,
+            port: info.get_port(),
+            txt: info
+                .get_properties()
+                .iter()
+                .map(|p| (p.key().to_string(), p.val_str().to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// A change in the set of visible peers.
+#[derive(Debug, Clone)]
+enum PeerEvent {
+    /// A peer appeared, or its address/port/TXT changed.
+    Up(Peer),
+    /// A peer said goodbye or its records expired.
+    Down { instance: String },
+}
+
+/// Keeps a service advertised; unregisters (sends an mDNS "goodbye") when dropped so
+/// peers see it leave immediately instead of waiting for the records to expire.
+struct Advertisement {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl Advertisement {
+    fn fullname(&self) -> &str {
+        &self.fullname
+    }
+}
+
+impl Drop for Advertisement {
+    fn drop(&mut self) {
+        if let Ok(status) = self.daemon.unregister(&self.fullname) {
+            // Wait briefly so the goodbye packet actually goes out before shutdown.
+            let _ = status.recv_timeout(std::time::Duration::from_secs(1));
+        }
+    }
+}
+
+/// Advertises this process on the LAN.
+///
+/// # Arguments
+///
+/// * `daemon` - Shared mDNS daemon (one per process is enough).
+/// * `service_type` - e.g. `"_myapp._tcp.local."`.
+/// * `instance_name` - Unique name for this instance, e.g. the machine or node id.
+/// * `port` - The port your server is listening on.
+/// * `txt` - Small key/value metadata (keep it under a few hundred bytes).
+///
+/// # Returns
+///
+/// An `Advertisement` guard; the service stays visible while it is alive.
+fn advertise(
+    daemon: &ServiceDaemon,
+    service_type: &str,
+    instance_name: &str,
+    port: u16,
+    txt: &[(&str, &str)],
+) -> Result<Advertisement> {
+    check_service_type(service_type)?;
+    let hostname = format!("{}.local.", instance_name);
+    // Empty address + `enable_addr_auto` = advertise every interface address, and keep
+    // the records up to date when interfaces change.
+    let info = ServiceInfo::new(service_type, instance_name, &hostname, "", port, txt)?.enable_addr_auto();
+    let fullname = info.get_fullname().to_string();
+    daemon.register(info)?;
+    Ok(Advertisement {
+        daemon: daemon.clone(),
+        fullname,
+    })
+}
+
+/// Browses for peers of `service_type` and streams `PeerEvent`s through a channel.
+///
+/// Our own advertisement (if `exclude_instance` is given) is filtered out. Browsing stops
+/// when the returned receiver is dropped.
+fn browse_peers(daemon: &ServiceDaemon, service_type: &str, exclude_instance: Option<String>) -> Result<mpsc::Receiver<PeerEvent>> {
+    check_service_type(service_type)?;
+    let events = daemon.browse(service_type)?;
+    let (tx, rx) = mpsc::channel(64);
+    let daemon = daemon.clone();
+    let service_type = service_type.to_string();
+
+    tokio::spawn(async move {
+        while let Ok(event) = events.recv_async().await {
+            let peer_event = match event {
+                ServiceEvent::ServiceResolved(info) => PeerEvent::Up(Peer::from_info(&info)),
+                ServiceEvent::ServiceRemoved(_, fullname) => PeerEvent::Down { instance: fullname },
+                // SearchStarted / ServiceFound (not yet resolved) / SearchStopped
+                _ => continue,
+            };
+            let instance = match &peer_event {
+                PeerEvent::Up(peer) => &peer.instance,
+                PeerEvent::Down { instance } => instance,
+            };
+            if exclude_instance.as_deref() == Some(instance.as_str()) {
+                continue;
+            }
+            if tx.send(peer_event).await.is_err() {
+                break; // Receiver dropped
+            }
+        }
+        let _ = daemon.stop_browse(&service_type);
+    });
+    Ok(rx)
+}
+
+/// Maintains the current peer set from a `PeerEvent` stream.
+#[derive(Default)]
+struct PeerTable {
+    peers: HashMap<String, Peer>,
+}
+
+impl PeerTable {
+    /// Applies an event; returns `true` if the set of peers (or a peer's details) changed.
+    fn apply(&mut self, event: PeerEvent) -> bool {
+        match event {
+            PeerEvent::Up(peer) => self.peers.insert(peer.instance.clone(), peer.clone()) != Some(peer),
+            PeerEvent::Down { instance } => self.peers.remove(&instance).is_some(),
+        }
+    }
+
+    /// Peers with a matching TXT value, e.g. `with_txt("role", "worker")`.
+    fn with_txt<'a>(&'a self, key: &'a str, value: &'a str) -> impl Iterator<Item = &'a Peer> + 'a {
+        self.peers.values().filter(move |p| p.txt.get(key).map(String::as_str) == Some(value))
+    }
+}
+
+// Example Usage (run it twice on one machine or on two machines on the same LAN)
+/*
+#[tokio::main]
+async fn main() -> Result<()> {
+    const SERVICE: &str = "_myapp._tcp.local.";
+    let daemon = ServiceDaemon::new()?;
+
+    // In a real service this is the port your TCP/WebSocket server bound.
+    let port = 7000 + (std::process::id() % 1000) as u16;
+    let name = format!("node-{}", std::process::id());
+    let me = advertise(&daemon, SERVICE, &name, port, &[("version", env!("CARGO_PKG_VERSION")), ("role", "worker")])?;
+    println!("Advertising {} on port {}", me.fullname(), port);
+
+    let mut events = browse_peers(&daemon, SERVICE, Some(me.fullname().to_string()))?;
+    let mut table = PeerTable::default();
+    let deadline = tokio::time::sleep(std::time::Duration::from_secs(10));
+    tokio::pin!(deadline);
+
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            Some(event) = events.recv() => {
+                println!("{:?}", event);
+                if table.apply(event) {
+                    for peer in table.with_txt("role", "worker") {
+                        println!("  worker {} -> {:?}", peer.instance, peer.socket_addrs());
+                    }
+                }
+            }
+        }
+    }
+
+    drop(me); // Sends goodbye packets
+    let _ = daemon.shutdown();
+    Ok(())
+}
+*/
@@ -0,0 +1,378 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// fs4 = "1"
+// thiserror = "1.0"
+//
+// Checks free disk space before large writes, so a job fails in the first second with
+// "need 4.2 GiB, 1.1 GiB available" instead of an hour in with ENOSPC and a truncated file:
+// * `SpaceGuard::check(path, bytes)` compares a planned write against the space available on
+//   the filesystem holding `path` (which need not exist yet), keeping a configurable headroom
+//   free for logs, databases, and the rest of the system.
+// * `SpaceGuard::reserve` also counts space already promised to other writers in this process,
+//   so two concurrent 5 GiB downloads can't both pass a check against 6 GiB free. The
+//   `Reservation` hands the space back when dropped.
+// * `SpaceGuard::create` returns a `GuardedFile` for writes of known or unknown size (a
+//   download without Content-Length, a compressed archive). A known size is preallocated, which
+//   claims the blocks from the OS up front; an unknown size is re-checked every `check_every`
+//   bytes. Unless `finish`ed, the partial file is deleted when dropped.
+// * Failures are `SpaceError::InsufficientSpace { required, available, headroom, .. }`. Inside
+//   `io::Write` they surface as `io::ErrorKind::StorageFull` wrapping that error.
+//
+// Free space can still vanish between the check and the write, to another process or a
+// snapshot; the guard makes running out early and explicit, not impossible. The large writes
+// in `write_text_file.rs`, `write_json_file.rs`, `ssh.rs` (downloads), and `backup.rs` point
+// here.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum SpaceError {
+    #[error(
+        "not enough disk space for {}: need {} plus {} headroom, {} available",
+        path.display(), Bytes(*required), Bytes(*headroom), Bytes(*available)
+    )]
+    InsufficientSpace { path: PathBuf, required: u64, available: u64, headroom: u64 },
+    #[error("cannot check disk space for {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl From<SpaceError> for io::Error {
+    fn from(e: SpaceError) -> Self {
+        match e {
+            SpaceError::InsufficientSpace { .. } => io::Error::new(io::ErrorKind::StorageFull, e),
+            SpaceError::Io { source, .. } => source,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, SpaceError>;
+
+/// Binary units for error messages; see `humanize.rs` for the full formatter.
+struct Bytes(u64);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let mut value = self.0 as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            write!(f, "{} B", self.0)
+        } else {
+            write!(f, "{:.1} {}", value, UNITS[unit])
+        }
+    }
+}
+
+// --- Filesystem lookups ---
+
+/// The nearest existing ancestor of `path`, since a file about to be written doesn't exist yet.
+fn existing_ancestor(path: &Path) -> io::Result<PathBuf> {
+    let absolute = if path.is_absolute() { path.to_path_buf() } else { std::env::current_dir()?.join(path) };
+    absolute
+        .ancestors()
+        .find(|p| p.exists())
+        .map(Path::to_path_buf)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no existing parent for {}", path.display())))
+}
+
+/// Identifies the filesystem holding `path`, so reservations on one disk don't count against
+/// another.
+#[cfg(unix)]
+fn filesystem_id(existing: &Path) -> io::Result<String> {
+    use std::os::unix::fs::MetadataExt;
+    Ok(fs::metadata(existing)?.dev().to_string())
+}
+
+#[cfg(not(unix))]
+fn filesystem_id(existing: &Path) -> io::Result<String> {
+    // The drive or UNC share, e.g. `C:\`.
+    let canonical = existing.canonicalize()?;
+    Ok(canonical.components().next().map(|c| c.as_os_str().to_string_lossy().into_owned()).unwrap_or_default())
+}
+
+/// Reads the filesystem's numbers for `path`; the caller holds the reservations lock.
+fn space_locked(path: &Path, reserved: &HashMap<String, u64>) -> Result<(String, SpaceInfo)> {
+    let io_err = |source| SpaceError::Io { path: path.to_path_buf(), source };
+    let existing = existing_ancestor(path).map_err(io_err)?;
+    let id = filesystem_id(&existing).map_err(io_err)?;
+    let stats = fs4::statvfs(&existing).map_err(io_err)?;
+    let reserved = reserved.get(&id).copied().unwrap_or(0);
+    Ok((id, SpaceInfo { total: stats.total_space(), available: stats.available_space(), reserved }))
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SpaceInfo {
+    total: u64,
+    /// Free to unprivileged users, after the filesystem's own root reserve.
+    available: u64,
+    /// Promised to `Reservation`s in this process and not yet released.
+    reserved: u64,
+}
+
+// --- The guard ---
+
+/// Free-space checks with a headroom policy, shared by every writer in the process.
+#[derive(Clone)]
+struct SpaceGuard {
+    min_free_bytes: u64,
+    min_free_percent: f64,
+    check_every: u64,
+    /// Outstanding reservations per filesystem.
+    reserved: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl Default for SpaceGuard {
+    fn default() -> Self {
+        SpaceGuard { min_free_bytes: 256 * 1024 * 1024, min_free_percent: 0.0, check_every: 64 * 1024 * 1024, reserved: Arc::default() }
+    }
+}
+
+impl SpaceGuard {
+    /// Keeps 256 MiB free by default.
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Space that must stay free after the write.
+    fn min_free_bytes(mut self, bytes: u64) -> Self {
+        self.min_free_bytes = bytes;
+        self
+    }
+
+    /// Space that must stay free, as a percentage of the filesystem; the larger of this and
+    /// `min_free_bytes` applies.
+    fn min_free_percent(mut self, percent: f64) -> Self {
+        self.min_free_percent = percent.clamp(0.0, 100.0);
+        self
+    }
+
+    /// How often a `GuardedFile` of unknown size re-checks as it grows.
+    fn check_every(mut self, bytes: u64) -> Self {
+        self.check_every = bytes.max(1);
+        self
+    }
+
+    fn space(&self, path: &Path) -> Result<(String, SpaceInfo)> {
+        space_locked(path, &self.reserved.lock().unwrap())
+    }
+
+    /// Current numbers for the filesystem holding `path`, e.g. for a status page.
+    fn info(&self, path: impl AsRef<Path>) -> Result<SpaceInfo> {
+        self.space(path.as_ref()).map(|(_, info)| info)
+    }
+
+    fn headroom(&self, total: u64) -> u64 {
+        self.min_free_bytes.max((total as f64 * self.min_free_percent / 100.0) as u64)
+    }
+
+    /// Fails with `InsufficientSpace` unless `bytes` more fit on the filesystem holding `path`
+    /// with the headroom left over. Space reserved by other writers counts as used.
+    fn check(&self, path: impl AsRef<Path>, bytes: u64) -> Result<()> {
+        let path = path.as_ref();
+        let (_, info) = self.space(path)?;
+        self.fits(path, bytes, info)
+    }
+
+    fn fits(&self, path: &Path, bytes: u64, info: SpaceInfo) -> Result<()> {
+        let headroom = self.headroom(info.total);
+        let available = info.available.saturating_sub(info.reserved);
+        if bytes.saturating_add(headroom) > available {
+            return Err(SpaceError::InsufficientSpace { path: path.to_path_buf(), required: bytes, available, headroom });
+        }
+        Ok(())
+    }
+
+    /// Like `check`, and holds the space for this writer until the `Reservation` is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Where the data will go; the file and its directories may not exist yet.
+    /// * `bytes` - An upper bound on what will be written.
+    fn reserve(&self, path: impl AsRef<Path>, bytes: u64) -> Result<Reservation> {
+        let path = path.as_ref();
+        // Check and record under one lock so two writers can't both take the last gigabyte.
+        let mut reserved = self.reserved.lock().unwrap();
+        let (id, info) = space_locked(path, &reserved)?;
+        self.fits(path, bytes, info)?;
+        *reserved.entry(id.clone()).or_insert(0) += bytes;
+        Ok(Reservation { guard: self.clone(), filesystem: id, bytes })
+    }
+
+    /// Creates `path` for writing, reserving `expected_len` if it's known.
+    ///
+    /// # Returns
+    ///
+    /// A `GuardedFile` that checks space as it grows and deletes itself unless `finish`ed.
+    fn create(&self, path: impl AsRef<Path>, expected_len: Option<u64>) -> Result<GuardedFile> {
+        let path = path.as_ref().to_path_buf();
+        let io_err = |source| SpaceError::Io { path: path.clone(), source };
+        let reservation = self.reserve(&path, expected_len.unwrap_or(self.check_every))?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        let file = File::create(&path).map_err(io_err)?;
+        if let Some(len) = expected_len {
+            // Claims the blocks now (fallocate / SetFileInformationByHandle), so another
+            // process can't take them mid-write. This also sets the length; `finish` trims it
+            // back to what was written.
+            if let Err(e) = fs4::FileExt::allocate(&file, len) {
+                if e.kind() != io::ErrorKind::Unsupported {
+                    let _ = fs::remove_file(&path);
+                    return Err(io_err(e));
+                }
+            }
+        }
+        Ok(GuardedFile { file: Some(file), path, reservation, written: 0, finished: false })
+    }
+}
+
+/// Space held for one writer; released on drop.
+struct Reservation {
+    guard: SpaceGuard,
+    filesystem: String,
+    bytes: u64,
+}
+
+impl Reservation {
+    fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Asks for `additional` more bytes on the same filesystem.
+    fn grow(&mut self, path: &Path, additional: u64) -> Result<()> {
+        let mut reserved = self.guard.reserved.lock().unwrap();
+        let (_, mut info) = space_locked(path, &reserved)?;
+        // Our own reservation is about to be written into; don't count it against us twice.
+        info.reserved = info.reserved.saturating_sub(self.bytes);
+        self.guard.fits(path, self.bytes + additional, info)?;
+        *reserved.entry(self.filesystem.clone()).or_insert(0) += additional;
+        self.bytes += additional;
+        Ok(())
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        let mut reserved = self.guard.reserved.lock().unwrap();
+        if let Some(total) = reserved.get_mut(&self.filesystem) {
+            *total = total.saturating_sub(self.bytes);
+        }
+    }
+}
+
+/// A file whose writes are checked against the `SpaceGuard` that created it.
+struct GuardedFile {
+    file: Option<File>,
+    path: PathBuf,
+    reservation: Reservation,
+    written: u64,
+    finished: bool,
+}
+
+impl GuardedFile {
+    fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Flushes and syncs the file, keeps it on disk, and releases the reservation.
+    fn finish(mut self) -> io::Result<u64> {
+        let file = self.file.take().expect("file is only taken here");
+        file.sync_all()?;
+        // Trim any preallocation beyond what was written.
+        file.set_len(self.written)?;
+        self.finished = true;
+        Ok(self.written)
+    }
+}
+
+impl Write for GuardedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let after = self.written + buf.len() as u64;
+        if after > self.reservation.bytes() {
+            let step = self.reservation.guard.check_every.max(after - self.reservation.bytes());
+            self.reservation.grow(&self.path, step)?;
+        }
+        let n = self.file.as_mut().expect("file is present until finish").write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().expect("file is present until finish").flush()
+    }
+}
+
+impl Drop for GuardedFile {
+    fn drop(&mut self) {
+        if !self.finished {
+            // A truncated download or archive is worse than none at all.
+            self.file.take();
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+// Example Usage
+/*
+fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("disk-space-{}", std::process::id()));
+    let guard = SpaceGuard::new().min_free_bytes(512 * 1024 * 1024).check_every(1024 * 1024);
+    let info = guard.info(&dir)?;
+    println!("{}: {} available of {}", dir.display(), Bytes(info.available), Bytes(info.total));
+
+    // A small config write: check first, then write as usual (`write_text_file.rs`).
+    guard.check(dir.join("config.json"), 4096)?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("config.json"), "{}")?;
+
+    // A write that can't fit fails before touching the disk.
+    let huge = info.available;
+    match guard.check(dir.join("huge.bin"), huge) {
+        Err(e @ SpaceError::InsufficientSpace { .. }) => println!("refused: {}", e),
+        other => println!("unexpected: {:?}", other),
+    }
+
+    // Reservations: the second writer sees the first one's space as taken.
+    let half = info.available.saturating_sub(guard.headroom(info.total)) / 2 + 1;
+    let first = guard.reserve(dir.join("a.bin"), half)?;
+    println!("reserved {} for a.bin", Bytes(first.bytes()));
+    if let Err(e) = guard.reserve(dir.join("b.bin"), half) {
+        println!("second reservation refused: {}", e);
+    }
+    drop(first);
+    println!("after release: {} reserved", Bytes(guard.info(&dir)?.reserved));
+
+    // A download with a known Content-Length: reserved and preallocated up front.
+    let body = vec![7u8; 3 * 1024 * 1024];
+    let mut file = guard.create(dir.join("downloads/archive.bin"), Some(body.len() as u64))?;
+    io::copy(&mut body.as_slice(), &mut file)?;
+    println!("downloaded {}", Bytes(file.finish()?));
+
+    // Unknown length: re-checked every `check_every` bytes. An interrupted write leaves nothing.
+    let mut file = guard.create(dir.join("downloads/stream.bin"), None)?;
+    file.write_all(&body[..1500 * 1024])?;
+    println!("wrote {} then gave up", Bytes(file.written()));
+    drop(file);
+    println!("partial file removed: {}", !dir.join("downloads/stream.bin").exists());
+
+    // Inside `io::Write`, running out is `ErrorKind::StorageFull`.
+    let available = guard.info(&dir)?.available;
+    let tight = SpaceGuard::new().min_free_bytes(available.saturating_sub(3 * 1024 * 1024)).check_every(1024 * 1024);
+    let mut file = tight.create(dir.join("downloads/too-big.bin"), None)?;
+    let err = io::copy(&mut vec![0u8; 8 * 1024 * 1024].as_slice(), &mut file).unwrap_err();
+    println!("{:?}: {}", err.kind(), err);
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+*/
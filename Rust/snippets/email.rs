@@ -0,0 +1,325 @@
+// Note: This example requires adding the `lettre` and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// lettre = { version = "0.11", default-features = false, features = ["builder", "smtp-transport", "pool", "hostname", "rustls-tls"] }
+// thiserror = "1.0"
+//
+// The SMTP transport here is blocking, which suits scheduled jobs and CLI tools. From async
+// code, wrap `Mailer::send` in `spawn_blocking` (see `blocking_async_bridge.rs`) or switch to
+// lettre's `AsyncSmtpTransport<Tokio1Executor>` (feature "tokio1-rustls-tls").
+
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment as MimeAttachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::stub::StubTransport;
+use lettre::{Message, SmtpTransport, Transport};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EmailError {
+    #[error("SMTP error: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+    #[error("Invalid address: {0}")]
+    Address(#[from] lettre::address::AddressError),
+    #[error("Failed to build message: {0}")]
+    Build(#[from] lettre::error::Error),
+    #[error("Invalid content type: {0}")]
+    ContentType(String),
+    #[error("Template variable '{0}' was not provided")]
+    MissingVariable(String),
+    #[error("Missing configuration: {0}")]
+    Config(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+type Result<T> = std::result::Result<T, EmailError>;
+
+// --- Configuration ---
+
+/// SMTP connection settings.
+struct SmtpConfig {
+    host: String,
+    /// 465 = implicit TLS, 587 = STARTTLS (anything else is treated as STARTTLS).
+    port: u16,
+    username: String,
+    password: String,
+    /// Default sender, e.g. `"Reports <reports@example.com>"`.
+    from: String,
+}
+
+impl SmtpConfig {
+    /// Reads `SMTP_HOST`, `SMTP_PORT` (default 587), `SMTP_USERNAME`, `SMTP_PASSWORD`, and `SMTP_FROM`.
+    fn from_env() -> Result<Self> {
+        let var = |name: &str| env::var(name).map_err(|_| EmailError::Config(name.to_string()));
+        Ok(Self {
+            host: var("SMTP_HOST")?,
+            port: env::var("SMTP_PORT").ok().and_then(|p| p.parse().ok()).unwrap_or(587),
+            username: var("SMTP_USERNAME")?,
+            password: var("SMTP_PASSWORD")?,
+            from: var("SMTP_FROM")?,
+        })
+    }
+}
+
+/// Builds a pooled, authenticated SMTP transport that always uses TLS.
+fn build_smtp_transport(config: &SmtpConfig) -> Result<SmtpTransport> {
+    let builder = if config.port == 465 {
+        SmtpTransport::relay(&config.host)? // TLS from the first byte
+    } else {
+        SmtpTransport::starttls_relay(&config.host)? // Plain connect, then mandatory upgrade
+    };
+    Ok(builder
+        .port(config.port)
+        .credentials(Credentials::new(config.username.clone(), config.password.clone()))
+        .timeout(Some(Duration::from_secs(30)))
+        .build())
+}
+
+// --- Templates ---
+
+/// Replaces `{{name}}` placeholders with values from `vars`.
+///
+/// A placeholder without a value is an error rather than an empty string, so a typo
+/// doesn't silently send "Hello ," to customers. With `escape_html`, values are
+/// HTML-escaped (use for the HTML body; never for the plain-text body).
+/// For loops and conditionals, use a real engine (see `templates.rs`).
+fn render_template(template: &str, vars: &HashMap<&str, String>, escape_html: bool) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            // Unterminated placeholder: keep the text as-is.
+            out.push_str(&rest[start..]);
+            return Ok(out);
+        };
+        let name = after[..end].trim();
+        let value = vars.get(name).ok_or_else(|| EmailError::MissingVariable(name.to_string()))?;
+        if escape_html {
+            out.push_str(&html_escape(value));
+        } else {
+            out.push_str(value);
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// A subject plus text and HTML bodies, all templated.
+struct EmailTemplate {
+    subject: &'static str,
+    text: &'static str,
+    html: &'static str,
+}
+
+/// The rendered result of an `EmailTemplate`.
+struct RenderedEmail {
+    subject: String,
+    text: String,
+    html: String,
+}
+
+impl EmailTemplate {
+    fn render(&self, vars: &HashMap<&str, String>) -> Result<RenderedEmail> {
+        Ok(RenderedEmail {
+            subject: render_template(self.subject, vars, false)?,
+            text: render_template(self.text, vars, false)?,
+            html: render_template(self.html, vars, true)?,
+        })
+    }
+}
+
+// --- Message building ---
+
+/// A file to attach to an email.
+struct Attachment {
+    filename: String,
+    content_type: String,
+    data: Vec<u8>,
+}
+
+impl Attachment {
+    fn new(filename: &str, content_type: &str, data: Vec<u8>) -> Self {
+        Self {
+            filename: filename.to_string(),
+            content_type: content_type.to_string(),
+            data,
+        }
+    }
+
+    /// Reads a file from disk, guessing the content type from its extension.
+    fn from_path(path: &Path) -> Result<Self> {
+        let content_type = match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("csv") => "text/csv",
+            Some("txt") | Some("log") => "text/plain",
+            Some("json") => "application/json",
+            Some("pdf") => "application/pdf",
+            Some("png") => "image/png",
+            Some("jpg") | Some("jpeg") => "image/jpeg",
+            Some("zip") => "application/zip",
+            _ => "application/octet-stream",
+        };
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "attachment".to_string());
+        Ok(Self::new(&filename, content_type, fs::read(path)?))
+    }
+}
+
+/// Builds a multipart message: text + HTML alternatives, followed by any attachments.
+///
+/// # Arguments
+///
+/// * `from` - Sender mailbox, e.g. `"Reports <reports@example.com>"`.
+/// * `to` - One or more recipients.
+/// * `email` - Rendered subject and bodies.
+/// * `attachments` - Files to attach (may be empty).
+fn build_message(from: &str, to: &[&str], email: &RenderedEmail, attachments: &[Attachment]) -> Result<Message> {
+    let mut builder = Message::builder().from(from.parse::<Mailbox>()?).subject(&email.subject);
+    for recipient in to {
+        builder = builder.to(recipient.parse::<Mailbox>()?);
+    }
+
+    // Clients show the best alternative they support; plain text must come first.
+    let body = MultiPart::alternative()
+        .singlepart(SinglePart::plain(email.text.clone()))
+        .singlepart(SinglePart::html(email.html.clone()));
+
+    if attachments.is_empty() {
+        return Ok(builder.multipart(body)?);
+    }
+
+    let mut mixed = MultiPart::mixed().multipart(body);
+    for attachment in attachments {
+        let content_type = ContentType::parse(&attachment.content_type)
+            .map_err(|e| EmailError::ContentType(format!("{}: {}", attachment.content_type, e)))?;
+        mixed = mixed.singlepart(MimeAttachment::new(attachment.filename.clone()).body(attachment.data.clone(), content_type));
+    }
+    Ok(builder.multipart(mixed)?)
+}
+
+// --- Sending ---
+
+/// Sends via SMTP, or records messages in memory when in dry-run mode.
+enum Mailer {
+    Smtp(SmtpTransport),
+    DryRun(StubTransport),
+}
+
+impl Mailer {
+    fn smtp(config: &SmtpConfig) -> Result<Self> {
+        Ok(Mailer::Smtp(build_smtp_transport(config)?))
+    }
+
+    /// A mailer that never opens a connection. Use in tests and `--dry-run` modes.
+    fn dry_run() -> Self {
+        Mailer::DryRun(StubTransport::new_ok())
+    }
+
+    fn send(&self, message: &Message) -> Result<()> {
+        match self {
+            Mailer::Smtp(transport) => {
+                transport.send(message)?;
+            }
+            Mailer::DryRun(stub) => {
+                // The stub transport is infallible when built with `new_ok`.
+                let _ = stub.send(message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Messages "sent" in dry-run mode as (recipients, raw RFC 5322 text).
+    fn sent_messages(&self) -> Vec<(Vec<String>, String)> {
+        match self {
+            Mailer::Smtp(_) => Vec::new(),
+            Mailer::DryRun(stub) => stub
+                .messages()
+                .into_iter()
+                .map(|(envelope, raw)| (envelope.to().iter().map(|a| a.to_string()).collect(), raw))
+                .collect(),
+        }
+    }
+
+    /// Checks that the SMTP server is reachable and accepts our credentials.
+    fn test_connection(&self) -> Result<bool> {
+        match self {
+            Mailer::Smtp(transport) => Ok(transport.test_connection()?),
+            Mailer::DryRun(_) => Ok(true),
+        }
+    }
+}
+
+/// Renders a template and sends it to `to` with optional attachments; the one-call
+/// entry point for alerting from scheduled jobs.
+fn send_templated(
+    mailer: &Mailer,
+    from: &str,
+    to: &[&str],
+    template: &EmailTemplate,
+    vars: &HashMap<&str, String>,
+    attachments: &[Attachment],
+) -> Result<()> {
+    let rendered = template.render(vars)?;
+    let message = build_message(from, to, &rendered, attachments)?;
+    mailer.send(&message)
+}
+
+const JOB_FAILED: EmailTemplate = EmailTemplate {
+    subject: "[{{env}}] Job '{{job}}' failed",
+    text: "The job {{job}} failed at {{time}}.\n\nError: {{error}}\n\nThe full log is attached.",
+    html: "<h2>Job <code>{{job}}</code> failed</h2>\
+           <p>Time: {{time}}</p><pre>{{error}}</pre><p>The full log is attached.</p>",
+};
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    // Use real SMTP when configured, otherwise fall back to a dry run.
+    let (mailer, from) = match SmtpConfig::from_env() {
+        Ok(config) => (Mailer::smtp(&config)?, config.from.clone()),
+        Err(e) => {
+            println!("{}; using dry-run transport", e);
+            (Mailer::dry_run(), "Scheduler <scheduler@example.com>".to_string())
+        }
+    };
+
+    let vars = HashMap::from([
+        ("env", "prod".to_string()),
+        ("job", "nightly-export".to_string()),
+        ("time", "2024-05-01 02:00 UTC".to_string()),
+        ("error", "connection refused <db:5432>".to_string()), // Escaped in the HTML body
+    ]);
+    let log = Attachment::new("export.log", "text/plain", b"step 1 ok\nstep 2 FAILED\n".to_vec());
+
+    send_templated(&mailer, &from, &["oncall@example.com"], &JOB_FAILED, &vars, &[log])?;
+
+    for (recipients, raw) in mailer.sent_messages() {
+        println!("To: {:?}\n{}", recipients, raw.lines().take(12).collect::<Vec<_>>().join("\n"));
+    }
+
+    // A missing variable is an error, not a blank.
+    let partial = HashMap::from([("job", "x".to_string())]);
+    match JOB_FAILED.render(&partial) {
+        Err(e) => println!("\nExpected error: {}", e),
+        Ok(_) => unreachable!(),
+    }
+    Ok(())
+}
+*/
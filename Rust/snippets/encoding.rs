@@ -0,0 +1,251 @@
+// Note: This example requires adding the `base64`, `hex`, `data-encoding`, `percent-encoding`,
+// and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// base64 = "0.22"
+// hex = "0.4"
+// data-encoding = "2"
+// percent-encoding = "2"
+// thiserror = "1.0"
+//
+// One place for the text encodings that show up everywhere: signatures (`signing.rs`),
+// tokens (`rand_utils.rs`), HTTP query strings and headers, and binary WebSocket payloads.
+// Picking the variant explicitly (`UrlSafeNoPad` for JWTs and URLs, `Standard` for MIME)
+// avoids the classic "works until the data contains a `+`" bug.
+
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
+use std::io::{self, Read, Write};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EncodingError {
+    #[error("Invalid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("Invalid hex: {0}")]
+    Hex(#[from] hex::FromHexError),
+    #[error("Invalid base32: {0}")]
+    Base32(#[from] data_encoding::DecodeError),
+    #[error("Decoded bytes are not valid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+}
+
+type Result<T> = std::result::Result<T, EncodingError>;
+
+// --- Base64 ---
+
+/// The four base64 flavours you'll meet in practice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Base64Variant {
+    /// `+/` with `=` padding: MIME, PEM, HTTP Basic auth, most JSON APIs.
+    Standard,
+    StandardNoPad,
+    /// `-_` with `=` padding.
+    UrlSafe,
+    /// `-_` without padding: JWTs, URLs, file names, WebAuthn.
+    UrlSafeNoPad,
+}
+
+impl Base64Variant {
+    fn engine(self) -> &'static GeneralPurpose {
+        match self {
+            Base64Variant::Standard => &STANDARD,
+            Base64Variant::StandardNoPad => &STANDARD_NO_PAD,
+            Base64Variant::UrlSafe => &URL_SAFE,
+            Base64Variant::UrlSafeNoPad => &URL_SAFE_NO_PAD,
+        }
+    }
+}
+
+fn b64_encode(data: impl AsRef<[u8]>, variant: Base64Variant) -> String {
+    variant.engine().encode(data)
+}
+
+/// Strict decode: the input must match `variant` exactly, padding included.
+fn b64_decode(encoded: &str, variant: Base64Variant) -> Result<Vec<u8>> {
+    Ok(variant.engine().decode(encoded)?)
+}
+
+/// Decodes base64 from an unknown source: either alphabet, with or without padding, and
+/// with embedded whitespace/newlines (as in PEM bodies or wrapped email).
+fn b64_decode_lenient(encoded: &str) -> Result<Vec<u8>> {
+    const INDIFFERENT: GeneralPurposeConfig = GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+    const STANDARD_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, INDIFFERENT);
+    const URL_SAFE_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, INDIFFERENT);
+
+    let compact: String = encoded.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    if compact.contains(['-', '_']) {
+        Ok(URL_SAFE_LENIENT.decode(compact)?)
+    } else {
+        Ok(STANDARD_LENIENT.decode(compact)?)
+    }
+}
+
+// --- Hex ---
+
+fn hex_encode(data: impl AsRef<[u8]>) -> String {
+    hex::encode(data)
+}
+
+fn hex_encode_upper(data: impl AsRef<[u8]>) -> String {
+    hex::encode_upper(data)
+}
+
+/// Decodes hex in either case, with an optional `0x` prefix.
+fn hex_decode(encoded: &str) -> Result<Vec<u8>> {
+    let trimmed = encoded.trim();
+    let digits = trimmed.strip_prefix("0x").or_else(|| trimmed.strip_prefix("0X")).unwrap_or(trimmed);
+    Ok(hex::decode(digits)?)
+}
+
+// --- Base32 (RFC 4648) ---
+
+/// Padded base32, e.g. `MZXW6===`.
+fn base32_encode(data: impl AsRef<[u8]>) -> String {
+    data_encoding::BASE32.encode(data.as_ref())
+}
+
+/// Unpadded base32, the form used for TOTP secrets in authenticator apps.
+fn base32_encode_nopad(data: impl AsRef<[u8]>) -> String {
+    data_encoding::BASE32_NOPAD.encode(data.as_ref())
+}
+
+/// Decodes base32 as users type it: any case, optional padding, spaces and dashes ignored.
+fn base32_decode(encoded: &str) -> Result<Vec<u8>> {
+    let normalized: String = encoded
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '='))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    Ok(data_encoding::BASE32_NOPAD.decode(normalized.as_bytes())?)
+}
+
+// --- Percent-encoding ---
+
+/// Everything except unreserved characters (`A-Z a-z 0-9 - _ . ~`), like JavaScript's
+/// `encodeURIComponent`. Safe for query keys/values and any single component.
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+/// Characters that must be escaped inside one path segment (note: `/` is escaped).
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'/')
+    .add(b'%');
+
+/// Encodes a query key or value: `a b&c` -> `a%20b%26c`.
+fn encode_component(value: &str) -> String {
+    utf8_percent_encode(value, COMPONENT).to_string()
+}
+
+/// Encodes one path segment: `reports/2024 Q1` -> `reports%2F2024%20Q1`.
+fn encode_path_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
+}
+
+/// Decodes `%XX` escapes. Fails if the result is not UTF-8.
+fn percent_decode(encoded: &str) -> Result<String> {
+    Ok(percent_decode_str(encoded).decode_utf8()?.into_owned())
+}
+
+/// Decodes an `application/x-www-form-urlencoded` value, where `+` means space.
+fn form_decode(encoded: &str) -> Result<String> {
+    percent_decode(&encoded.replace('+', " "))
+}
+
+/// Builds a query string from pairs: `[("q", "rust & go")]` -> `q=rust%20%26%20go`.
+fn build_query(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", encode_component(k), encode_component(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+// --- Streaming ---
+//
+// For large payloads (file uploads in JSON, attachments) encode while copying instead of
+// loading everything into memory. Memory use stays at a few KiB regardless of input size.
+
+/// Base64-encodes everything from `reader` into `writer`.
+///
+/// # Returns
+///
+/// The number of input bytes consumed.
+fn b64_encode_stream<R: Read, W: Write>(reader: &mut R, writer: W, variant: Base64Variant) -> io::Result<u64> {
+    let mut encoder = base64::write::EncoderWriter::new(writer, variant.engine());
+    let copied = io::copy(reader, &mut encoder)?;
+    encoder.finish()?; // Flushes the final partial block and padding
+    Ok(copied)
+}
+
+/// Base64-decodes everything from `reader` into `writer` (input must not contain newlines).
+fn b64_decode_stream<R: Read, W: Write>(reader: R, writer: &mut W, variant: Base64Variant) -> io::Result<u64> {
+    let mut decoder = base64::read::DecoderReader::new(reader, variant.engine());
+    io::copy(&mut decoder, writer)
+}
+
+/// Hex-encodes `reader` into `writer` in 8 KiB chunks.
+fn hex_encode_stream<R: Read, W: Write>(reader: &mut R, writer: &mut W) -> io::Result<u64> {
+    let mut buf = [0u8; 8192];
+    let mut out = vec![0u8; buf.len() * 2];
+    let mut total = 0u64;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            return Ok(total);
+        }
+        hex::encode_to_slice(&buf[..n], &mut out[..n * 2]).map_err(io::Error::other)?;
+        writer.write_all(&out[..n * 2])?;
+        total += n as u64;
+    }
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    let data = b"\xfb\xff hello";
+    for variant in [Base64Variant::Standard, Base64Variant::StandardNoPad, Base64Variant::UrlSafe, Base64Variant::UrlSafeNoPad] {
+        let encoded = b64_encode(data, variant);
+        println!("{:<14} {}", format!("{:?}", variant), encoded);
+        assert_eq!(b64_decode_lenient(&encoded)?, data);
+    }
+    println!("Strict decode of unpadded input as Standard: {}", b64_decode("aGk", Base64Variant::Standard).unwrap_err());
+    println!("Lenient decode of wrapped input: {:?}", String::from_utf8_lossy(&b64_decode_lenient("aGVs\nbG8g\nd29y bGQ")?));
+
+    println!("\nhex: {} / {}", hex_encode(b"\x00\xab\xff"), hex_encode_upper(b"\x00\xab\xff"));
+    println!("hex decode 0xDEADbeef: {:?}", hex_decode("0xDEADbeef")?);
+    println!("hex decode error: {}", hex_decode("abc").unwrap_err());
+
+    let totp_secret = b"12345678901234567890";
+    let shown = base32_encode_nopad(totp_secret);
+    println!("\nbase32: {} (padded: {})", shown, base32_encode(b"foo"));
+    println!("base32 as typed by a user: {:?}", base32_decode("gezd-gnbv gy3t qojq gezd gnbv gy3t qojq")? == totp_secret);
+
+    println!("\nquery: {}", build_query(&[("q", "rust & go"), ("page", "2"), ("tag", "café")]));
+    println!("path:  /files/{}", encode_path_segment("reports/2024 Q1.pdf"));
+    println!("decode: {}", percent_decode("caf%C3%A9%20au%20lait")?);
+    println!("form:   {}", form_decode("hello+world%21")?);
+
+    // Streaming a 1 MiB payload without building the encoded string in memory first.
+    let payload = vec![42u8; 1024 * 1024];
+    let mut encoded = Vec::new();
+    let consumed = b64_encode_stream(&mut payload.as_slice(), &mut encoded, Base64Variant::Standard).unwrap();
+    let mut decoded = Vec::new();
+    b64_decode_stream(encoded.as_slice(), &mut decoded, Base64Variant::Standard).unwrap();
+    println!("\nstreamed {} bytes -> {} base64 chars, round trip ok: {}", consumed, encoded.len(), decoded == payload);
+    let mut hex_out = Vec::new();
+    hex_encode_stream(&mut &payload[..16], &mut hex_out).unwrap();
+    println!("hex stream: {}", String::from_utf8_lossy(&hex_out));
+    Ok(())
+}
+*/
@@ -0,0 +1,599 @@
+// This example only uses the standard library.
+//
+// Startup configuration from `.env` files and the process environment, checked against a
+// declared schema so a misconfigured deploy fails immediately with one message that lists every
+// problem, instead of one at a time or halfway through the first request.
+// * `parse_dotenv` reads the usual `.env` syntax: `KEY=value`, `export KEY=value`, `#` comments,
+//   single quotes (literal), double quotes (escapes, multi-line values such as PEM keys), and
+//   `${VAR}` expansion from earlier lines or the process environment.
+// * `EnvLayers::load(dir, profile)` stacks `.env`, `.env.<profile>`, `.env.local`, and
+//   `.env.<profile>.local`, later files overriding earlier ones; missing files are skipped. Real
+//   environment variables override every file, so a deploy can always set a value explicitly.
+//   Commit `.env` and `.env.<profile>`; keep the `.local` files out of git.
+// * `EnvSchema` declares each variable's type, whether it is required, a default, and whether it
+//   is a secret. `validate` returns typed `EnvConfig` values or an `EnvErrors` listing every
+//   missing and malformed variable, naming the file each bad value came from.
+// * `EnvConfig::summary` prints where each value came from, with secrets redacted.
+//
+// Nothing is written back into the process environment: `std::env::set_var` is unsound while
+// other threads read the environment. Hand `EnvLayers::lookup` to code that needs raw lookups,
+// such as `from_env_with` from `#[derive(FromEnv)]` in the `macros` crate.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// --- Parsing .env files ---
+
+#[derive(Debug)]
+struct DotenvError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for DotenvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && !key.starts_with(|c: char| c.is_ascii_digit()) && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Expands `$VAR` and `${VAR}` using `lookup`; unknown variables expand to nothing, as in a shell.
+fn expand(value: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'$') => out.push(chars.next().unwrap()),
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                out.push_str(&lookup(&name).unwrap_or_default());
+            }
+            '$' if chars.peek().is_some_and(|c| c.is_ascii_alphabetic() || *c == '_') => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_') {
+                    name.push(c);
+                    chars.next();
+                }
+                out.push_str(&lookup(&name).unwrap_or_default());
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Parses the contents of a `.env` file into `(key, value)` pairs in file order.
+///
+/// # Arguments
+///
+/// * `text` - The file contents.
+/// * `lookup` - Resolves `${VAR}` references to variables not defined earlier in this file
+///   (earlier layers, then the process environment).
+fn parse_dotenv(text: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<Vec<(String, String)>, DotenvError> {
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    let mut lines = text.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let line_no = index + 1;
+        let err = |message: &str| DotenvError { line: line_no, message: message.to_string() };
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let trimmed = trimmed.strip_prefix("export ").map(str::trim_start).unwrap_or(trimmed);
+        let (key, raw) = trimmed.split_once('=').ok_or_else(|| err("expected KEY=value"))?;
+        let key = key.trim();
+        if !is_valid_key(key) {
+            return Err(err(&format!("invalid variable name {:?}", key)));
+        }
+        let raw = raw.trim_start();
+
+        // Earlier keys in this file take part in expansion.
+        let resolve = |name: &str| pairs.iter().rev().find(|(k, _)| k == name).map(|(_, v)| v.clone()).or_else(|| lookup(name));
+        let value = if let Some(rest) = raw.strip_prefix('\'') {
+            let end = rest.find('\'').ok_or_else(|| err("unterminated single quote"))?;
+            rest[..end].to_string()
+        } else if let Some(rest) = raw.strip_prefix('"') {
+            // Double-quoted values may span lines until the closing quote.
+            let mut body = String::new();
+            let mut current = rest.to_string();
+            let closed = loop {
+                let mut escaped = false;
+                let mut close_at = None;
+                for (i, c) in current.char_indices() {
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => {
+                            close_at = Some(i);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(i) = close_at {
+                    body.push_str(&current[..i]);
+                    break true;
+                }
+                body.push_str(&current);
+                match lines.next() {
+                    Some((_, next)) => {
+                        body.push('\n');
+                        current = next.to_string();
+                    }
+                    None => break false,
+                }
+            };
+            if !closed {
+                return Err(err("unterminated double quote"));
+            }
+            let unescaped = body.replace("\\n", "\n").replace("\\t", "\t").replace("\\\"", "\"");
+            expand(&unescaped, &resolve).replace("\\\\", "\\")
+        } else {
+            // Unquoted: a ` #` starts a comment.
+            let value = raw.find(" #").map_or(raw, |i| &raw[..i]).trim_end();
+            expand(value, &resolve)
+        };
+        pairs.push((key.to_string(), value));
+    }
+    Ok(pairs)
+}
+
+// --- Layers ---
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    File(PathBuf),
+    Process,
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Process => f.pad("environment"),
+            Source::Default => f.pad("default"),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum LoadError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, DotenvError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            LoadError::Parse(path, e) => write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// The merged values of every `.env` layer, with the process environment on top.
+#[derive(Debug, Default)]
+struct EnvLayers {
+    files: BTreeMap<String, (String, PathBuf)>,
+    loaded: Vec<PathBuf>,
+}
+
+impl EnvLayers {
+    /// Loads the standard layers from `dir`.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Where the `.env` files live, usually the working directory.
+    /// * `profile` - e.g. `Some("production")` to include `.env.production` and
+    ///   `.env.production.local`.
+    fn load(dir: impl AsRef<Path>, profile: Option<&str>) -> Result<Self, LoadError> {
+        let dir = dir.as_ref();
+        let mut names = vec![".env".to_string()];
+        if let Some(profile) = profile {
+            names.push(format!(".env.{}", profile));
+        }
+        names.push(".env.local".to_string());
+        if let Some(profile) = profile {
+            names.push(format!(".env.{}.local", profile));
+        }
+        let mut layers = Self::default();
+        for name in names {
+            layers.add_file(dir.join(name))?;
+        }
+        Ok(layers)
+    }
+
+    /// Adds one file on top of the layers so far; a missing file is not an error.
+    fn add_file(&mut self, path: PathBuf) -> Result<(), LoadError> {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(LoadError::Io(path, e)),
+        };
+        let pairs = parse_dotenv(&text, &|name| self.get(name)).map_err(|e| LoadError::Parse(path.clone(), e))?;
+        for (key, value) in pairs {
+            self.files.insert(key, (value, path.clone()));
+        }
+        self.loaded.push(path);
+        Ok(())
+    }
+
+    /// The effective value: the process environment first, then the highest file layer.
+    fn get(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok().or_else(|| self.files.get(name).map(|(value, _)| value.clone()))
+    }
+
+    fn get_with_source(&self, name: &str) -> Option<(String, Source)> {
+        match std::env::var(name) {
+            Ok(value) => Some((value, Source::Process)),
+            Err(_) => self.files.get(name).map(|(value, path)| (value.clone(), Source::File(path.clone()))),
+        }
+    }
+
+    /// A lookup function over the layers, e.g. for `Config::from_env_with(layers.lookup())`.
+    fn lookup(&self) -> impl Fn(&str) -> Option<String> + '_ {
+        move |name| self.get(name)
+    }
+}
+
+// --- Schema and validation ---
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Str,
+    /// `true/false`, `1/0`, `yes/no`, `on/off`, case-insensitive.
+    Bool,
+    Int { min: i64, max: i64 },
+    Float,
+    /// A number plus `ms`, `s`, `m`, `h`, or `d`; see `parse_duration` in `datetime.rs` for
+    /// compound values like `1h30m`.
+    Duration,
+    /// Something with a scheme and a host: `postgres://db:5432/app`.
+    Url,
+    /// One of a fixed set, case-sensitive.
+    OneOf(&'static [&'static str]),
+    /// Comma-separated, trimmed, empty items dropped.
+    List,
+}
+
+impl Kind {
+    const PORT: Kind = Kind::Int { min: 1, max: 65535 };
+
+    fn describe(&self) -> String {
+        match self {
+            Kind::Str => "string".to_string(),
+            Kind::Bool => "bool".to_string(),
+            Kind::Int { min, max } => format!("integer {}..={}", min, max),
+            Kind::Float => "number".to_string(),
+            Kind::Duration => "duration".to_string(),
+            Kind::Url => "URL".to_string(),
+            Kind::OneOf(options) => format!("one of {}", options.join("|")),
+            Kind::List => "comma-separated list".to_string(),
+        }
+    }
+
+    fn parse(&self, raw: &str) -> Result<EnvValue, String> {
+        let raw = raw.trim();
+        match self {
+            Kind::Str => Ok(EnvValue::Str(raw.to_string())),
+            Kind::Bool => match raw.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Ok(EnvValue::Bool(true)),
+                "false" | "0" | "no" | "off" => Ok(EnvValue::Bool(false)),
+                _ => Err("expected true or false".to_string()),
+            },
+            Kind::Int { min, max } => {
+                let n: i64 = raw.parse().map_err(|_| "expected an integer".to_string())?;
+                if n < *min || n > *max {
+                    return Err(format!("must be between {} and {}", min, max));
+                }
+                Ok(EnvValue::Int(n))
+            }
+            Kind::Float => raw.parse().map(EnvValue::Float).map_err(|_| "expected a number".to_string()),
+            Kind::Duration => {
+                let split = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+                let n: u64 = raw[..split].parse().map_err(|_| "expected a number followed by ms, s, m, h, or d".to_string())?;
+                let millis = match raw[split..].trim() {
+                    "ms" => 1,
+                    "s" | "" => 1_000,
+                    "m" => 60_000,
+                    "h" => 3_600_000,
+                    "d" => 86_400_000,
+                    unit => return Err(format!("unknown unit {:?} (use ms, s, m, h, d)", unit)),
+                };
+                Ok(EnvValue::Duration(Duration::from_millis(n.saturating_mul(millis))))
+            }
+            Kind::Url => match raw.split_once("://") {
+                Some((scheme, rest))
+                    if !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)) && !rest.is_empty() =>
+                {
+                    Ok(EnvValue::Str(raw.to_string()))
+                }
+                _ => Err("expected a URL like scheme://host".to_string()),
+            },
+            Kind::OneOf(options) if options.contains(&raw) => Ok(EnvValue::Str(raw.to_string())),
+            Kind::OneOf(options) => Err(format!("expected one of {}", options.join(", "))),
+            Kind::List => Ok(EnvValue::List(raw.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum EnvValue {
+    Str(String),
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Duration(Duration),
+    List(Vec<String>),
+}
+
+/// One declared variable; build with `Var::required` or `Var::optional`.
+#[derive(Debug, Clone)]
+struct Var {
+    name: &'static str,
+    kind: Kind,
+    required: bool,
+    default: Option<&'static str>,
+    secret: bool,
+    help: &'static str,
+}
+
+impl Var {
+    fn required(name: &'static str, kind: Kind) -> Self {
+        Var { name, kind, required: true, default: None, secret: false, help: "" }
+    }
+
+    fn optional(name: &'static str, kind: Kind) -> Self {
+        Var { required: false, ..Var::required(name, kind) }
+    }
+
+    /// Used when the variable is unset; parsed and validated like a real value.
+    fn default(mut self, value: &'static str) -> Self {
+        self.default = Some(value);
+        self.required = false;
+        self
+    }
+
+    /// Redacts the value in `summary` and never echoes it in errors.
+    fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+
+    /// Shown next to a missing variable, e.g. where to get it.
+    fn help(mut self, text: &'static str) -> Self {
+        self.help = text;
+        self
+    }
+}
+
+#[derive(Debug)]
+struct InvalidVar {
+    name: &'static str,
+    source: Source,
+    message: String,
+    /// `None` for secrets.
+    value: Option<String>,
+}
+
+/// Every problem found by `EnvSchema::validate`.
+#[derive(Debug, Default)]
+struct EnvErrors {
+    missing: Vec<(&'static str, String, &'static str)>,
+    invalid: Vec<InvalidVar>,
+}
+
+impl fmt::Display for EnvErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "invalid configuration ({} problem(s)):", self.missing.len() + self.invalid.len())?;
+        for (name, expected, help) in &self.missing {
+            write!(f, "  missing {} ({})", name, expected)?;
+            if !help.is_empty() {
+                write!(f, " - {}", help)?;
+            }
+            writeln!(f)?;
+        }
+        for var in &self.invalid {
+            write!(f, "  invalid {} from {}: {}", var.name, var.source, var.message)?;
+            if let Some(value) = &var.value {
+                write!(f, " (got {:?})", value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for EnvErrors {}
+
+#[derive(Debug, Default)]
+struct EnvSchema {
+    vars: Vec<Var>,
+}
+
+impl EnvSchema {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn var(mut self, var: Var) -> Self {
+        self.vars.push(var);
+        self
+    }
+
+    /// Checks every declared variable against the layers.
+    ///
+    /// # Returns
+    ///
+    /// Typed values for every variable that is set or has a default, or all the problems at once.
+    fn validate(&self, layers: &EnvLayers) -> Result<EnvConfig, EnvErrors> {
+        let mut errors = EnvErrors::default();
+        let mut values = BTreeMap::new();
+        for var in &self.vars {
+            let (raw, source) = match layers.get_with_source(var.name).filter(|(value, _)| !value.is_empty()) {
+                Some(found) => found,
+                None => match var.default {
+                    Some(default) => (default.to_string(), Source::Default),
+                    None if var.required => {
+                        errors.missing.push((var.name, var.kind.describe(), var.help));
+                        continue;
+                    }
+                    None => continue,
+                },
+            };
+            match var.kind.parse(&raw) {
+                Ok(value) => {
+                    values.insert(var.name, (value, source, var.secret));
+                }
+                Err(message) => errors.invalid.push(InvalidVar {
+                    name: var.name,
+                    source,
+                    message,
+                    value: (!var.secret).then_some(raw),
+                }),
+            }
+        }
+        if errors.missing.is_empty() && errors.invalid.is_empty() {
+            Ok(EnvConfig { values })
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Validated values. The getters panic on names that weren't declared with a matching kind,
+/// which is a bug in the program rather than in the environment.
+#[derive(Debug)]
+struct EnvConfig {
+    values: BTreeMap<&'static str, (EnvValue, Source, bool)>,
+}
+
+impl EnvConfig {
+    fn value(&self, name: &str) -> Option<&EnvValue> {
+        self.values.get(name).map(|(value, _, _)| value)
+    }
+
+    fn str(&self, name: &str) -> Option<&str> {
+        match self.value(name)? {
+            EnvValue::Str(s) => Some(s),
+            other => panic!("{} is {:?}, not a string", name, other),
+        }
+    }
+
+    fn bool(&self, name: &str) -> Option<bool> {
+        match self.value(name)? {
+            EnvValue::Bool(b) => Some(*b),
+            other => panic!("{} is {:?}, not a bool", name, other),
+        }
+    }
+
+    fn int(&self, name: &str) -> Option<i64> {
+        match self.value(name)? {
+            EnvValue::Int(n) => Some(*n),
+            other => panic!("{} is {:?}, not an integer", name, other),
+        }
+    }
+
+    fn float(&self, name: &str) -> Option<f64> {
+        match self.value(name)? {
+            EnvValue::Float(n) => Some(*n),
+            other => panic!("{} is {:?}, not a number", name, other),
+        }
+    }
+
+    fn duration(&self, name: &str) -> Option<Duration> {
+        match self.value(name)? {
+            EnvValue::Duration(d) => Some(*d),
+            other => panic!("{} is {:?}, not a duration", name, other),
+        }
+    }
+
+    fn list(&self, name: &str) -> Option<&[String]> {
+        match self.value(name)? {
+            EnvValue::List(items) => Some(items),
+            other => panic!("{} is {:?}, not a list", name, other),
+        }
+    }
+
+    /// One line per variable with its source, secrets redacted; log it at startup.
+    fn summary(&self) -> String {
+        let width = self.values.keys().map(|k| k.len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for (name, (value, source, secret)) in &self.values {
+            let shown = if *secret { "********".to_string() } else { format!("{:?}", value) };
+            out.push_str(&format!("{:<width$} = {} ({})\n", name, shown, source, width = width));
+        }
+        out
+    }
+}
+
+// Example Usage
+/*
+fn main() {
+    let dir = std::env::temp_dir().join(format!("env-demo-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join(".env"),
+        r#"# Defaults shared by everyone (committed)
+export APP_HOST=0.0.0.0
+APP_PORT=8080
+APP_LOG_LEVEL=info # overridden per profile
+DB_HOST=localhost
+DATABASE_URL="postgres://app@${DB_HOST}:5432/app"
+ALLOWED_ORIGINS=https://example.com, https://admin.example.com
+TLS_CERT="-----BEGIN CERTIFICATE-----
+MIIBszCCAVmgAwIBAgIU
+-----END CERTIFICATE-----"
+"#,
+    )
+    .unwrap();
+    fs::write(dir.join(".env.production"), "APP_LOG_LEVEL=warn\nDB_HOST=db.internal\nDATABASE_URL=postgres://app@${DB_HOST}:5432/app\n").unwrap();
+    fs::write(dir.join(".env.local"), "API_KEY='sk_live_123$notexpanded'\n").unwrap();
+
+    let schema = EnvSchema::new()
+        .var(Var::optional("APP_HOST", Kind::Str).default("127.0.0.1"))
+        .var(Var::required("APP_PORT", Kind::PORT))
+        .var(Var::optional("APP_LOG_LEVEL", Kind::OneOf(&["debug", "info", "warn", "error"])).default("info"))
+        .var(Var::optional("REQUEST_TIMEOUT", Kind::Duration).default("30s"))
+        .var(Var::required("DATABASE_URL", Kind::Url))
+        .var(Var::optional("ALLOWED_ORIGINS", Kind::List))
+        .var(Var::required("API_KEY", Kind::Str).secret().help("create one in the dashboard under Settings > API"))
+        .var(Var::optional("TLS_CERT", Kind::Str).secret());
+
+    let layers = EnvLayers::load(&dir, Some("production")).unwrap();
+    println!("loaded: {:?}", layers.loaded.iter().map(|p| p.file_name().unwrap()).collect::<Vec<_>>());
+    match schema.validate(&layers) {
+        Ok(config) => {
+            print!("{}", config.summary());
+            println!("port {:?}, timeout {:?}, origins {:?}", config.int("APP_PORT"), config.duration("REQUEST_TIMEOUT"), config.list("ALLOWED_ORIGINS"));
+            println!("API key is {} chars; cert has {} lines", config.str("API_KEY").unwrap().len(), config.str("TLS_CERT").unwrap().lines().count());
+        }
+        Err(errors) => eprint!("{}", errors),
+    }
+
+    // A broken deploy: every problem is reported at once.
+    fs::write(dir.join(".env.staging.local"), "APP_PORT=80800\nAPP_LOG_LEVEL=verbose\nDATABASE_URL=db.internal:5432\nREQUEST_TIMEOUT=5 minutes\n").unwrap();
+    fs::remove_file(dir.join(".env.local")).unwrap();
+    let layers = EnvLayers::load(&dir, Some("staging")).unwrap();
+    if let Err(errors) = schema.validate(&layers) {
+        eprint!("{}", errors);
+    }
+
+    fs::write(dir.join(".env.local"), "OOPS\n").unwrap();
+    if let Err(e) = EnvLayers::load(&dir, None) {
+        eprintln!("{}", e);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+*/
@@ -0,0 +1,654 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// csv = "1"
+// serde_json = "1.0"
+// rusqlite = { version = "0.32", features = ["bundled"] }
+// reqwest = { version = "0.12", features = ["blocking", "json"] }
+// crossbeam-channel = "0.5"
+// thiserror = "1.0"
+//
+// A small streaming ETL framework: a `Source` yields records, `Transform`s map, filter, or
+// enrich them, and a `Sink` writes them out. Records are JSON objects, so any source can
+// feed any sink and transforms can be written as plain closures.
+//
+// Failures are split into two kinds:
+// * Row errors (`EtlError::Row`): one bad line or record. The row is written to the reject
+//   file with the reason, and the pipeline carries on (up to `max_rejects`).
+// * Everything else (I/O, database, HTTP): the pipeline stops and returns the error.
+//
+// Memory use is bounded: records flow through a bounded channel, so a slow sink applies
+// backpressure to the source instead of buffering the whole input.
+
+use crossbeam_channel::{bounded, Receiver, Sender};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::Connection;
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EtlError {
+    /// A single bad record. Sent to the reject file; the pipeline continues.
+    #[error("{message}")]
+    Row { message: String, raw: Option<String> },
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Aborted: {rejected} rows rejected (limit {limit})")]
+    TooManyRejects { rejected: u64, limit: u64 },
+}
+
+impl EtlError {
+    /// A row-level error for use in transforms: `return Err(EtlError::row("missing email"))`.
+    fn row(message: impl Into<String>) -> Self {
+        EtlError::Row { message: message.into(), raw: None }
+    }
+}
+
+type Result<T> = std::result::Result<T, EtlError>;
+
+/// One record: a JSON object keyed by column/field name.
+type Record = Map<String, Value>;
+
+// --- Traits ---
+
+/// Produces records one at a time. Returning `Some(Err(EtlError::Row { .. }))` rejects that
+/// row and keeps reading; any other error stops the pipeline.
+trait Source: Send {
+    fn next_record(&mut self) -> Option<Result<Record>>;
+}
+
+/// Turns one record into zero or one records. `Ok(None)` drops the record (filtered out).
+trait Transform: Send + Sync {
+    fn apply(&self, record: Record) -> Result<Option<Record>>;
+}
+
+impl<F> Transform for F
+where
+    F: Fn(Record) -> Result<Option<Record>> + Send + Sync,
+{
+    fn apply(&self, record: Record) -> Result<Option<Record>> {
+        self(record)
+    }
+}
+
+/// Consumes records. `finish` is called once after the last record to flush buffers.
+trait Sink: Send {
+    fn write(&mut self, record: &Record) -> Result<()>;
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// --- Transform constructors ---
+
+/// Rewrites each record: `map(|mut r| { r.insert(..); Ok(r) })`.
+fn map<F>(f: F) -> Box<dyn Transform>
+where
+    F: Fn(Record) -> Result<Record> + Send + Sync + 'static,
+{
+    Box::new(move |record| f(record).map(Some))
+}
+
+/// Keeps records for which `predicate` returns `true`.
+fn filter<F>(predicate: F) -> Box<dyn Transform>
+where
+    F: Fn(&Record) -> bool + Send + Sync + 'static,
+{
+    Box::new(move |record: Record| Ok(predicate(&record).then_some(record)))
+}
+
+/// Merges extra fields from a lookup (a cache, another table, an API) into each record.
+/// Returning `Ok(None)` from `lookup` leaves the record unchanged.
+fn enrich<F>(lookup: F) -> Box<dyn Transform>
+where
+    F: Fn(&Record) -> Result<Option<Record>> + Send + Sync + 'static,
+{
+    Box::new(move |mut record: Record| {
+        if let Some(extra) = lookup(&record)? {
+            record.extend(extra);
+        }
+        Ok(Some(record))
+    })
+}
+
+// --- Sources ---
+
+/// Reads a CSV file with a header row. All values are strings; convert types in a transform.
+struct CsvSource {
+    reader: csv::Reader<File>,
+    headers: Vec<String>,
+}
+
+impl CsvSource {
+    fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut reader = csv::Reader::from_path(path)?;
+        let headers = reader.headers()?.iter().map(str::to_string).collect();
+        Ok(Self { reader, headers })
+    }
+}
+
+impl Source for CsvSource {
+    fn next_record(&mut self) -> Option<Result<Record>> {
+        let mut row = csv::StringRecord::new();
+        match self.reader.read_record(&mut row) {
+            Ok(false) => None,
+            Ok(true) => Some(Ok(self
+                .headers
+                .iter()
+                .zip(row.iter())
+                .map(|(h, v)| (h.clone(), Value::String(v.to_string())))
+                .collect())),
+            // Wrong field count etc.: reject the row. I/O errors stop the pipeline.
+            Err(e) if !matches!(e.kind(), csv::ErrorKind::Io(_)) => Some(Err(EtlError::Row {
+                message: e.to_string(),
+                raw: None,
+            })),
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
+
+/// Reads one JSON object per line; blank lines are skipped.
+struct JsonlSource {
+    lines: io::Lines<BufReader<File>>,
+}
+
+impl JsonlSource {
+    fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self { lines: BufReader::new(File::open(path)?).lines() })
+    }
+}
+
+impl Source for JsonlSource {
+    fn next_record(&mut self) -> Option<Result<Record>> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(match serde_json::from_str::<Record>(&line) {
+                Ok(record) => Ok(record),
+                Err(e) => Err(EtlError::Row { message: format!("invalid JSON: {}", e), raw: Some(line) }),
+            });
+        }
+    }
+}
+
+/// Any in-memory batch of records; also the building block for the HTTP and SQL sources.
+struct VecSource {
+    records: VecDeque<Record>,
+}
+
+impl VecSource {
+    fn new(records: impl IntoIterator<Item = Record>) -> Self {
+        Self { records: records.into_iter().collect() }
+    }
+}
+
+impl Source for VecSource {
+    fn next_record(&mut self) -> Option<Result<Record>> {
+        self.records.pop_front().map(Ok)
+    }
+}
+
+/// Fetches a JSON array of objects from `url` (one request; non-object elements are skipped).
+fn http_json_source(client: &reqwest::blocking::Client, url: &str) -> Result<VecSource> {
+    let body: Vec<Value> = client.get(url).send()?.error_for_status()?.json()?;
+    let records = body.into_iter().filter_map(|value| match value {
+        Value::Object(record) => Some(record),
+        _ => None,
+    });
+    Ok(VecSource::new(records))
+}
+
+/// Runs `sql` and loads the result rows. rusqlite statements borrow the connection, so the
+/// rows are read up front; page with `LIMIT`/`OFFSET` or a key range for very large tables.
+fn sqlite_source(conn: &Connection, sql: &str) -> Result<VecSource> {
+    let mut stmt = conn.prepare(sql)?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+    let rows = stmt.query_map([], |row| {
+        let mut record = Record::new();
+        for (i, name) in columns.iter().enumerate() {
+            let value = match row.get::<_, SqlValue>(i)? {
+                SqlValue::Null => Value::Null,
+                SqlValue::Integer(n) => Value::from(n),
+                SqlValue::Real(f) => Value::from(f),
+                SqlValue::Text(s) => Value::String(s),
+                SqlValue::Blob(b) => Value::from(b),
+            };
+            record.insert(name.clone(), value);
+        }
+        Ok(record)
+    })?;
+    Ok(VecSource::new(rows.collect::<rusqlite::Result<Vec<_>>>()?))
+}
+
+// --- Sinks ---
+
+/// Writes a CSV file. Columns come from the first record; later records missing a column
+/// get an empty cell, and extra fields are ignored.
+struct CsvSink {
+    writer: csv::Writer<File>,
+    columns: Option<Vec<String>>,
+}
+
+impl CsvSink {
+    fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self { writer: csv::Writer::from_path(path)?, columns: None })
+    }
+}
+
+impl Sink for CsvSink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        let columns = match &self.columns {
+            Some(columns) => columns,
+            None => {
+                let columns: Vec<String> = record.keys().cloned().collect();
+                self.writer.write_record(&columns)?;
+                self.columns.insert(columns)
+            }
+        };
+        let cells = columns.iter().map(|c| match record.get(c) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        });
+        self.writer.write_record(cells)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Writes one JSON object per line.
+struct JsonlSink {
+    writer: BufWriter<File>,
+}
+
+impl JsonlSink {
+    fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self { writer: BufWriter::new(File::create(path)?) })
+    }
+}
+
+impl Sink for JsonlSink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Inserts into a SQLite table, committing every `batch_size` rows. Record fields must match
+/// column names; the table must already exist.
+struct SqliteSink {
+    conn: Connection,
+    table: String,
+    batch_size: usize,
+    pending: usize,
+}
+
+impl SqliteSink {
+    fn new(conn: Connection, table: &str, batch_size: usize) -> Self {
+        Self { conn, table: table.to_string(), batch_size: batch_size.max(1), pending: 0 }
+    }
+}
+
+impl Sink for SqliteSink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        if self.pending == 0 {
+            self.conn.execute_batch("BEGIN")?;
+        }
+        let columns: Vec<String> = record.keys().map(|k| format!("\"{}\"", k.replace('"', "\"\""))).collect();
+        let sql = format!(
+            "INSERT INTO \"{}\" ({}) VALUES ({})",
+            self.table.replace('"', "\"\""),
+            columns.join(", "),
+            vec!["?"; columns.len()].join(", ")
+        );
+        let values = record.values().map(|v| match v {
+            Value::Null => SqlValue::Null,
+            Value::Bool(b) => SqlValue::Integer(i64::from(*b)),
+            Value::Number(n) => n.as_i64().map(SqlValue::Integer).unwrap_or_else(|| SqlValue::Real(n.as_f64().unwrap_or(0.0))),
+            Value::String(s) => SqlValue::Text(s.clone()),
+            other => SqlValue::Text(other.to_string()),
+        });
+        // Cached: the same column set reuses one prepared statement.
+        self.conn.prepare_cached(&sql)?.execute(rusqlite::params_from_iter(values))?;
+        self.pending += 1;
+        if self.pending >= self.batch_size {
+            self.conn.execute_batch("COMMIT")?;
+            self.pending = 0;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.pending > 0 {
+            self.conn.execute_batch("COMMIT")?;
+            self.pending = 0;
+        }
+        Ok(())
+    }
+}
+
+/// POSTs records as JSON arrays of up to `batch_size` records each.
+struct HttpSink {
+    client: reqwest::blocking::Client,
+    url: String,
+    batch_size: usize,
+    buffer: Vec<Record>,
+}
+
+impl HttpSink {
+    fn new(client: reqwest::blocking::Client, url: &str, batch_size: usize) -> Self {
+        Self { client, url: url.to_string(), batch_size: batch_size.max(1), buffer: Vec::new() }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if !self.buffer.is_empty() {
+            self.client.post(&self.url).json(&self.buffer).send()?.error_for_status()?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+impl Sink for HttpSink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        self.buffer.push(record.clone());
+        if self.buffer.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush()
+    }
+}
+
+// --- Pipeline ---
+
+/// Counters reported to the progress callback and returned by `Pipeline::run`.
+#[derive(Debug, Clone, Default)]
+struct Progress {
+    read: u64,
+    written: u64,
+    filtered: u64,
+    rejected: u64,
+    elapsed: Duration,
+}
+
+type ProgressFn = Box<dyn Fn(&Progress) + Send>;
+
+/// What the workers send back for each input row.
+enum Outcome {
+    Write(Record),
+    Filtered,
+    Reject { row: u64, error: EtlError, record: Option<Record> },
+}
+
+struct Pipeline {
+    source: Box<dyn Source>,
+    transforms: Vec<Box<dyn Transform>>,
+    sink: Box<dyn Sink>,
+    workers: usize,
+    reject_path: Option<std::path::PathBuf>,
+    max_rejects: Option<u64>,
+    progress: Option<(u64, ProgressFn)>,
+}
+
+impl Pipeline {
+    fn new(source: impl Source + 'static, sink: impl Sink + 'static) -> Self {
+        Self {
+            source: Box::new(source),
+            transforms: Vec::new(),
+            sink: Box::new(sink),
+            workers: 1,
+            reject_path: None,
+            max_rejects: None,
+            progress: None,
+        }
+    }
+
+    /// Adds a step; steps run in the order added.
+    fn transform(mut self, transform: Box<dyn Transform>) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// Runs transforms on `n` threads. With more than one worker, output order is not
+    /// guaranteed to match input order.
+    fn workers(mut self, n: usize) -> Self {
+        self.workers = n.max(1);
+        self
+    }
+
+    /// Writes rejected rows as JSON lines: `{"row": 7, "error": "...", "record": {...}}`.
+    fn reject_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.reject_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Aborts with `TooManyRejects` once more than `limit` rows have been rejected.
+    fn max_rejects(mut self, limit: u64) -> Self {
+        self.max_rejects = Some(limit);
+        self
+    }
+
+    /// Calls `callback` every `every` rows read and once at the end.
+    fn on_progress(mut self, every: u64, callback: impl Fn(&Progress) + Send + 'static) -> Self {
+        self.progress = Some((every.max(1), Box::new(callback)));
+        self
+    }
+
+    /// Runs the pipeline to completion.
+    ///
+    /// The source runs on one thread, transforms on `workers` threads, and the sink on the
+    /// calling thread, connected by bounded channels.
+    ///
+    /// # Returns
+    ///
+    /// Final counts, or the first fatal error (the sink is not `finish`ed in that case).
+    fn run(self) -> Result<Progress> {
+        let Pipeline { mut source, transforms, mut sink, workers, reject_path, max_rejects, progress } = self;
+        let started = Instant::now();
+        let mut rejects = reject_path.map(|p| File::create(p).map(BufWriter::new)).transpose()?;
+        let mut stats = Progress::default();
+
+        let (raw_tx, raw_rx) = bounded::<(u64, Result<Record>)>(1024);
+        let (out_tx, out_rx) = bounded::<Result<Outcome>>(1024);
+        let transforms = &transforms;
+
+        std::thread::scope(|scope| -> Result<()> {
+            // Owned by this closure so an early `return Err` drops it before the scope joins
+            // the threads; blocked workers then see a closed channel and exit.
+            let out_rx = out_rx;
+            scope.spawn(move || read_source(source.as_mut(), raw_tx));
+            for _ in 0..workers {
+                let (rx, tx) = (raw_rx.clone(), out_tx.clone());
+                scope.spawn(move || run_transforms(transforms, rx, tx));
+            }
+            // Close our copies so the loop below ends when the workers are done.
+            drop((raw_rx, out_tx));
+
+            for outcome in out_rx.iter() {
+                match outcome? {
+                    Outcome::Write(record) => {
+                        sink.write(&record)?;
+                        stats.written += 1;
+                    }
+                    Outcome::Filtered => stats.filtered += 1,
+                    Outcome::Reject { row, error, record } => {
+                        stats.rejected += 1;
+                        if let Some(rejects) = rejects.as_mut() {
+                            write_reject(rejects, row, &error, record)?;
+                        }
+                        if let Some(limit) = max_rejects.filter(|&limit| stats.rejected > limit) {
+                            return Err(EtlError::TooManyRejects { rejected: stats.rejected, limit });
+                        }
+                    }
+                }
+                stats.read += 1;
+                if let Some((every, callback)) = &progress {
+                    if stats.read.is_multiple_of(*every) {
+                        stats.elapsed = started.elapsed();
+                        callback(&stats);
+                    }
+                }
+            }
+            Ok(())
+        })?;
+
+        sink.finish()?;
+        if let Some(mut rejects) = rejects {
+            rejects.flush()?;
+        }
+        stats.elapsed = started.elapsed();
+        if let Some((_, callback)) = &progress {
+            callback(&stats);
+        }
+        Ok(stats)
+    }
+}
+
+fn read_source(source: &mut dyn Source, tx: Sender<(u64, Result<Record>)>) {
+    let mut row = 0;
+    while let Some(item) = source.next_record() {
+        row += 1;
+        let fatal = matches!(item, Err(ref e) if !matches!(e, EtlError::Row { .. }));
+        // A send error means the pipeline stopped early; nothing left to do.
+        if tx.send((row, item)).is_err() || fatal {
+            break;
+        }
+    }
+}
+
+fn run_transforms(transforms: &[Box<dyn Transform>], rx: Receiver<(u64, Result<Record>)>, tx: Sender<Result<Outcome>>) {
+    for (row, item) in rx.iter() {
+        let outcome = match item {
+            Ok(record) => apply_all(transforms, row, record),
+            Err(error @ EtlError::Row { .. }) => Ok(Outcome::Reject { row, error, record: None }),
+            Err(fatal) => Err(fatal),
+        };
+        if tx.send(outcome).is_err() {
+            break;
+        }
+    }
+}
+
+fn apply_all(transforms: &[Box<dyn Transform>], row: u64, record: Record) -> Result<Outcome> {
+    let mut current = record;
+    for transform in transforms {
+        // The reject file shows the record as it was before the failing step.
+        let before = current.clone();
+        match transform.apply(current) {
+            Ok(Some(next)) => current = next,
+            Ok(None) => return Ok(Outcome::Filtered),
+            Err(error @ EtlError::Row { .. }) => return Ok(Outcome::Reject { row, error, record: Some(before) }),
+            Err(fatal) => return Err(fatal),
+        }
+    }
+    Ok(Outcome::Write(current))
+}
+
+fn write_reject(out: &mut impl Write, row: u64, error: &EtlError, record: Option<Record>) -> Result<()> {
+    let mut entry = Record::new();
+    entry.insert("row".into(), row.into());
+    entry.insert("error".into(), error.to_string().into());
+    if let EtlError::Row { raw: Some(raw), .. } = error {
+        entry.insert("raw".into(), raw.clone().into());
+    }
+    if let Some(record) = record {
+        entry.insert("record".into(), Value::Object(record));
+    }
+    serde_json::to_writer(&mut *out, &entry)?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    let dir = std::env::temp_dir().join("etl-example");
+    std::fs::create_dir_all(&dir)?;
+    let input = dir.join("orders.csv");
+    std::fs::write(
+        &input,
+        "id,customer,amount,country\n\
+         1,alice,19.99,DE\n\
+         2,bob,not-a-number,US\n\
+         3,carol,250.00,FR\n\
+         4,dave,5.00,US\n\
+         5,erin,99.50\n\
+         6,frank,1200.00,DE\n",
+    )?;
+
+    // Lookup table for enrichment, e.g. loaded from another file or service at startup.
+    let regions: Map<String, Value> = serde_json::from_str(r#"{"DE": "EU", "FR": "EU", "US": "NA"}"#)?;
+
+    let report = Pipeline::new(CsvSource::open(&input)?, JsonlSink::create(dir.join("orders.jsonl"))?)
+        .transform(map(|mut r| {
+            let amount = r.get("amount").and_then(Value::as_str).unwrap_or_default();
+            let amount: f64 = amount.parse().map_err(|_| EtlError::row(format!("bad amount '{}'", amount)))?;
+            r.insert("amount".into(), amount.into());
+            Ok(r)
+        }))
+        .transform(filter(|r| r["amount"].as_f64().unwrap_or(0.0) >= 10.0))
+        .transform(enrich(move |r| {
+            let country = r.get("country").and_then(Value::as_str).unwrap_or_default();
+            Ok(regions.get(country).map(|region| Map::from_iter([("region".to_string(), region.clone())])))
+        }))
+        .workers(2)
+        .reject_file(dir.join("rejects.jsonl"))
+        .max_rejects(10)
+        .on_progress(2, |p| println!("progress: {:?}", p))
+        .run()?;
+    println!("\nfinal: {:?}", report);
+    println!("\norders.jsonl:\n{}", std::fs::read_to_string(dir.join("orders.jsonl"))?);
+    println!("rejects.jsonl:\n{}", std::fs::read_to_string(dir.join("rejects.jsonl"))?);
+
+    // JSONL -> SQLite, then SQLite -> CSV.
+    let db = dir.join("orders.db");
+    let _ = std::fs::remove_file(&db);
+    let conn = Connection::open(&db)?;
+    conn.execute_batch("CREATE TABLE orders (id TEXT, customer TEXT, amount REAL, country TEXT, region TEXT)")?;
+    Pipeline::new(JsonlSource::open(dir.join("orders.jsonl"))?, SqliteSink::new(conn, "orders", 500)).run()?;
+
+    let conn = Connection::open(&db)?;
+    let source = sqlite_source(&conn, "SELECT region, COUNT(*) AS orders, SUM(amount) AS total FROM orders GROUP BY region")?;
+    Pipeline::new(source, CsvSink::create(dir.join("by_region.csv"))?).run()?;
+    println!("by_region.csv:\n{}", std::fs::read_to_string(dir.join("by_region.csv"))?);
+
+    // HTTP sources and sinks plug in the same way:
+    // let client = reqwest::blocking::Client::new();
+    // let source = http_json_source(&client, "https://api.example.com/orders")?;
+    // let sink = HttpSink::new(client, "https://warehouse.example.com/ingest", 100);
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+*/
@@ -0,0 +1,389 @@
+// Note: This example requires adding the `tokio` crate to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] } // "sync" and "rt" are enough for the bus itself
+//
+// An in-process publish/subscribe bus where the *type* of an event is its topic: publishing
+// a `UserSignedUp` reaches every subscriber of `UserSignedUp` and nobody else, with no
+// string topic names to misspell and no downcasting in handlers. Each topic is a
+// `tokio::sync::broadcast` channel created on first use; events are wrapped in `Arc`, so they
+// need not be `Clone` and are never copied per subscriber.
+//
+// Subscribers can pull (`Subscription::recv` from async code, `blocking_recv` from plain
+// threads) or register a handler that runs on its own task. Dropping the handle returned
+// for a handler unsubscribes it, and weak subscriptions end by themselves once their owner
+// is gone, so a torn-down component never keeps receiving events.
+//
+// Broadcast channels are bounded: a subscriber that falls more than `capacity` events behind
+// skips the oldest ones, and the skips are counted in the topic's metrics, instead of
+// slowing the publisher down.
+
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, Weak};
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+use tokio::task::JoinHandle;
+
+// --- Metrics ---
+
+#[derive(Debug, Default)]
+struct TopicMetrics {
+    published: AtomicU64,
+    delivered: AtomicU64,
+    unheard: AtomicU64,
+    lagged: AtomicU64,
+    filtered: AtomicU64,
+}
+
+/// A point-in-time copy of one topic's counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct TopicStats {
+    /// Events published.
+    published: u64,
+    /// Event copies handed to subscriber queues (one event to 3 subscribers counts 3).
+    delivered: u64,
+    /// Events published while nobody was subscribed.
+    unheard: u64,
+    /// Events subscribers skipped because they fell more than `capacity` behind.
+    lagged: u64,
+    /// Events subscribers received but their filter discarded.
+    filtered: u64,
+}
+
+impl TopicMetrics {
+    fn snapshot(&self) -> TopicStats {
+        TopicStats {
+            published: self.published.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            unheard: self.unheard.load(Ordering::Relaxed),
+            lagged: self.lagged.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// --- Bus ---
+
+struct Topic<T> {
+    sender: broadcast::Sender<Arc<T>>,
+    metrics: Arc<TopicMetrics>,
+}
+
+struct TopicEntry {
+    name: &'static str,
+    metrics: Arc<TopicMetrics>,
+    /// An `Arc<Topic<T>>` for the `T` this entry's `TypeId` belongs to.
+    topic: Arc<dyn Any + Send + Sync>,
+}
+
+/// A cloneable handle to the bus; clones share the same topics.
+#[derive(Clone)]
+struct EventBus {
+    inner: Arc<BusInner>,
+}
+
+struct BusInner {
+    capacity: usize,
+    topics: RwLock<HashMap<TypeId, TopicEntry>>,
+}
+
+impl EventBus {
+    /// Creates a bus whose topics each buffer up to `capacity` events per slow subscriber.
+    fn new(capacity: usize) -> Self {
+        Self { inner: Arc::new(BusInner { capacity: capacity.max(1), topics: RwLock::new(HashMap::new()) }) }
+    }
+
+    fn topic<T: Send + Sync + 'static>(&self) -> Arc<Topic<T>> {
+        let id = TypeId::of::<T>();
+        let existing = self.inner.topics.read().unwrap_or_else(|e| e.into_inner()).get(&id).map(|e| Arc::clone(&e.topic));
+        let topic = match existing {
+            Some(topic) => topic,
+            None => {
+                let mut topics = self.inner.topics.write().unwrap_or_else(|e| e.into_inner());
+                let entry = topics.entry(id).or_insert_with(|| {
+                    let metrics = Arc::new(TopicMetrics::default());
+                    let (sender, _) = broadcast::channel(self.inner.capacity);
+                    let topic: Arc<Topic<T>> = Arc::new(Topic { sender, metrics: Arc::clone(&metrics) });
+                    TopicEntry { name: type_name::<T>(), metrics, topic }
+                });
+                Arc::clone(&entry.topic)
+            }
+        };
+        topic.downcast::<Topic<T>>().expect("topic is keyed by its TypeId")
+    }
+
+    /// Sends `event` to every current subscriber of `T`. Never blocks.
+    ///
+    /// # Returns
+    ///
+    /// How many subscribers it was queued for (0 if none are listening).
+    fn publish<T: Send + Sync + 'static>(&self, event: T) -> usize {
+        let topic = self.topic::<T>();
+        topic.metrics.published.fetch_add(1, Ordering::Relaxed);
+        match topic.sender.send(Arc::new(event)) {
+            Ok(receivers) => {
+                topic.metrics.delivered.fetch_add(receivers as u64, Ordering::Relaxed);
+                receivers
+            }
+            Err(_) => {
+                topic.metrics.unheard.fetch_add(1, Ordering::Relaxed);
+                0
+            }
+        }
+    }
+
+    /// Subscribes to every `T` published from now on.
+    fn subscribe<T: Send + Sync + 'static>(&self) -> Subscription<T> {
+        let topic = self.topic::<T>();
+        Subscription { receiver: topic.sender.subscribe(), filter: None, metrics: Arc::clone(&topic.metrics) }
+    }
+
+    /// Subscribes to the `T`s for which `filter` returns `true`.
+    fn subscribe_filtered<T: Send + Sync + 'static>(
+        &self,
+        filter: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Subscription<T> {
+        Subscription { filter: Some(Box::new(filter)), ..self.subscribe() }
+    }
+
+    /// Runs the async `handler` for each `T` on its own task, one event at a time.
+    ///
+    /// # Returns
+    ///
+    /// A handle that unsubscribes (aborts the task) when dropped. Call `detach` to keep the
+    /// handler for the life of the bus.
+    fn on<T, F, Fut>(&self, mut handler: F) -> SubscriptionHandle
+    where
+        T: Send + Sync + 'static,
+        F: FnMut(Arc<T>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut subscription = self.subscribe::<T>();
+        let task = tokio::spawn(async move {
+            while let Some(event) = subscription.recv().await {
+                handler(event).await;
+            }
+        });
+        SubscriptionHandle { task: Some(task) }
+    }
+
+    /// Like `on`, for a plain closure. It runs on the async runtime, so keep it quick; move
+    /// blocking work to `tokio::task::spawn_blocking` or use `blocking_recv` on a thread.
+    fn on_sync<T: Send + Sync + 'static>(&self, mut handler: impl FnMut(&T) + Send + 'static) -> SubscriptionHandle {
+        self.on(move |event: Arc<T>| {
+            handler(&event);
+            std::future::ready(())
+        })
+    }
+
+    /// Calls `handler(owner, event)` for each `T` for as long as `owner` is alive. The bus
+    /// holds only a `Weak`, so it never keeps the owner alive; the subscription ends at the
+    /// first event after the owner is dropped.
+    fn subscribe_weak<O, T>(&self, owner: &Arc<O>, handler: impl Fn(&O, &T) + Send + 'static)
+    where
+        O: Send + Sync + 'static,
+        T: Send + Sync + 'static,
+    {
+        let owner: Weak<O> = Arc::downgrade(owner);
+        let mut subscription = self.subscribe::<T>();
+        tokio::spawn(async move {
+            while let Some(event) = subscription.recv().await {
+                let Some(owner) = owner.upgrade() else { break };
+                handler(&owner, &event);
+            }
+        });
+    }
+
+    /// Current subscriber count for `T`.
+    fn subscriber_count<T: Send + Sync + 'static>(&self) -> usize {
+        self.topic::<T>().sender.receiver_count()
+    }
+
+    /// Counters for the `T` topic.
+    fn metrics<T: Send + Sync + 'static>(&self) -> TopicStats {
+        self.topic::<T>().metrics.snapshot()
+    }
+
+    /// Counters for every topic used so far, by event type name, sorted by name.
+    fn all_metrics(&self) -> Vec<(&'static str, TopicStats)> {
+        let topics = self.inner.topics.read().unwrap_or_else(|e| e.into_inner());
+        let mut all: Vec<_> = topics.values().map(|e| (e.name, e.metrics.snapshot())).collect();
+        all.sort_by_key(|(name, _)| *name);
+        all
+    }
+}
+
+// --- Subscribers ---
+
+type Filter<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// A pull-style subscription. Dropping it unsubscribes.
+struct Subscription<T> {
+    receiver: broadcast::Receiver<Arc<T>>,
+    filter: Option<Filter<T>>,
+    metrics: Arc<TopicMetrics>,
+}
+
+impl<T> Subscription<T> {
+    fn accepts(&self, event: &T) -> bool {
+        let keep = self.filter.as_ref().is_none_or(|filter| filter(event));
+        if !keep {
+            self.metrics.filtered.fetch_add(1, Ordering::Relaxed);
+        }
+        keep
+    }
+
+    fn record_lag(&self, skipped: u64) {
+        self.metrics.lagged.fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    /// The next matching event. Skips past lag (counted in metrics).
+    ///
+    /// # Returns
+    ///
+    /// `None` once the bus has been dropped.
+    async fn recv(&mut self) -> Option<Arc<T>>
+    where
+        T: Send + Sync,
+    {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if self.accepts(&event) => return Some(event),
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => self.record_lag(skipped),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// The next matching event if one is already queued, without waiting.
+    fn try_recv(&mut self) -> Option<Arc<T>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) if self.accepts(&event) => return Some(event),
+                Ok(_) => {}
+                Err(TryRecvError::Lagged(skipped)) => self.record_lag(skipped),
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Waits for the next matching event from a plain (non-async) thread. Panics if called
+    /// from inside an async task; use `recv` there.
+    fn blocking_recv(&mut self) -> Option<Arc<T>> {
+        loop {
+            match self.receiver.blocking_recv() {
+                Ok(event) if self.accepts(&event) => return Some(event),
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => self.record_lag(skipped),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Keeps a handler registered with `EventBus::on` running; dropping it unsubscribes.
+#[must_use = "dropping the handle unsubscribes immediately"]
+struct SubscriptionHandle {
+    task: Option<JoinHandle<()>>,
+}
+
+impl SubscriptionHandle {
+    /// Keeps the handler running until the bus is dropped.
+    fn detach(mut self) {
+        self.task.take();
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+// Example Usage
+/*
+use std::sync::Mutex;
+use tokio::time::{sleep, Duration};
+
+#[derive(Debug)]
+struct UserSignedUp {
+    user_id: u64,
+    email: String,
+}
+
+#[derive(Debug)]
+struct OrderPlaced {
+    order_id: u64,
+    total_cents: u64,
+}
+
+/// A UI component that should stop listening when it is closed.
+struct Dashboard {
+    orders_seen: Mutex<u32>,
+}
+
+async fn settle() {
+    sleep(Duration::from_millis(20)).await;
+}
+
+#[tokio::main]
+async fn main() {
+    let bus = EventBus::new(64);
+
+    // Async handler: send a welcome email for every signup.
+    let welcome = bus.on(|e: Arc<UserSignedUp>| async move {
+        println!("[email] welcome {} (user {})", e.email, e.user_id);
+    });
+
+    // Sync handler, detached for the life of the bus.
+    let revenue = Arc::new(AtomicU64::new(0));
+    let total = Arc::clone(&revenue);
+    bus.on_sync(move |e: &OrderPlaced| {
+        total.fetch_add(e.total_cents, Ordering::Relaxed);
+    })
+    .detach();
+
+    // Filtered pull subscription: only large orders.
+    let mut big_orders = bus.subscribe_filtered(|e: &OrderPlaced| e.total_cents >= 10_000);
+
+    // Weak subscription tied to a dashboard's lifetime.
+    let dashboard = Arc::new(Dashboard { orders_seen: Mutex::new(0) });
+    bus.subscribe_weak(&dashboard, |d: &Dashboard, _: &OrderPlaced| *d.orders_seen.lock().unwrap() += 1);
+
+    // A plain thread consuming signups with blocking_recv.
+    let mut audit = bus.subscribe::<UserSignedUp>();
+    let audit_thread = std::thread::spawn(move || {
+        let event = audit.blocking_recv().expect("bus alive");
+        println!("[audit thread] signup {}", event.user_id);
+    });
+
+    bus.publish(UserSignedUp { user_id: 1, email: "ada@example.com".into() });
+    for (order_id, total_cents) in [(100, 2_500), (101, 15_000), (102, 9_900)] {
+        bus.publish(OrderPlaced { order_id, total_cents });
+    }
+    settle().await;
+    audit_thread.join().unwrap();
+
+    while let Some(order) = big_orders.try_recv() {
+        println!("[big order] {} for ${:.2}", order.order_id, order.total_cents as f64 / 100.0);
+    }
+    println!("revenue: {} cents; dashboard saw {}", revenue.load(Ordering::Relaxed), dashboard.orders_seen.lock().unwrap());
+
+    // Closing the dashboard and dropping the welcome handle unsubscribes both.
+    drop(dashboard);
+    drop(welcome);
+    bus.publish(OrderPlaced { order_id: 103, total_cents: 1_000 }); // Weak task notices and exits.
+    settle().await;
+    bus.publish(UserSignedUp { user_id: 2, email: "grace@example.com".into() }); // No email printed.
+    settle().await;
+    println!("OrderPlaced subscribers now: {}", bus.subscriber_count::<OrderPlaced>());
+
+    for (topic, stats) in bus.all_metrics() {
+        println!("{topic}: {stats:?}");
+    }
+}
+*/
@@ -0,0 +1,378 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// chrono = { version = "0.4", features = ["serde"] } # "serde" only for the example
+// rand = "0.8"
+// rand_chacha = "0.3"
+// uuid = "1"
+// serde = { version = "1.0", features = ["derive"] } # Only for the example
+// serde_json = "1.0" # Only for the example
+//
+// Realistic fake values for seeding databases and exercising APIs in tests: names, emails,
+// usernames, phone numbers, IP addresses, timestamps, UUIDs, and filler text.
+// * Everything comes from one seeded `ChaCha8Rng` (see `rand_utils.rs`), so a seed always
+//   reproduces the same records on every platform; `Faker::from_env` honors `TEST_SEED`.
+// * Generated contact data can never reach a real person: emails use the reserved
+//   `example.*` domains (RFC 2606), phone numbers use the fictional 555-01xx range, and IPs
+//   come from the documentation blocks (RFC 5737 and RFC 3849).
+// * Implement `Fake` for your own structs to get `faker.make::<T>()` and `faker.many::<T>(n)`.
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use uuid::Uuid;
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Amara", "Barbara", "Carlos", "Chen", "Dennis", "Elena", "Fatima", "Grace", "Hiro", "Ines",
+    "James", "Katherine", "Leila", "Linus", "Margaret", "Mateo", "Nadia", "Olu", "Priya", "Ravi", "Sofia", "Tomas",
+];
+const LAST_NAMES: &[&str] = &[
+    "Allen", "Bauer", "Costa", "Dubois", "Eriksen", "Fernandes", "Garcia", "Hamilton", "Hopper", "Ivanova", "Johnson",
+    "Kim", "Lovelace", "Mensah", "Nakamura", "Okafor", "Patel", "Ritchie", "Silva", "Turing", "Wang", "Zhou",
+];
+const EMAIL_DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+const COMPANY_WORDS: &[&str] = &[
+    "Acme", "Blue", "Cedar", "Delta", "Ember", "Granite", "Harbor", "Nimbus", "Orbit", "Pioneer", "Summit", "Vertex",
+];
+const COMPANY_SUFFIXES: &[&str] = &["Labs", "Systems", "Logistics", "Analytics", "Works", "Inc.", "GmbH", "Ltd."];
+const STREETS: &[&str] = &["Main St", "Oak Ave", "Maple Dr", "Harbor Rd", "Elm St", "Station Rd", "Park Ln"];
+const CITIES: &[&str] = &["Springfield", "Riverton", "Lakeside", "Fairview", "Greenville", "Kingston", "Milton"];
+const COUNTRY_CODES: &[&str] = &["US", "GB", "DE", "FR", "JP", "BR", "IN", "NG", "CA", "AU"];
+const LOREM: &[&str] = &[
+    "lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit", "sed", "do", "eiusmod", "tempor",
+    "incididunt", "ut", "labore", "et", "dolore", "magna", "aliqua", "enim", "minim", "veniam", "quis", "nostrud",
+];
+
+/// A seeded source of fake values.
+struct Faker {
+    rng: ChaCha8Rng,
+    seed: u64,
+}
+
+impl Faker {
+    /// A faker that produces the same values for the same seed, on every platform.
+    fn seeded(seed: u64) -> Self {
+        Self { rng: ChaCha8Rng::seed_from_u64(seed), seed }
+    }
+
+    /// Seeds from `TEST_SEED` if set, otherwise from the OS, printing the seed either way so a
+    /// failing test can be rerun with the same data.
+    fn from_env() -> Self {
+        let seed = std::env::var("TEST_SEED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(|| rand::rngs::OsRng.next_u64());
+        eprintln!("Using TEST_SEED={}", seed);
+        Self::seeded(seed)
+    }
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Direct access to the RNG for anything not covered here.
+    fn rng(&mut self) -> &mut ChaCha8Rng {
+        &mut self.rng
+    }
+
+    /// A value of any type that implements `Fake`.
+    fn make<T: Fake>(&mut self) -> T {
+        T::fake(self)
+    }
+
+    fn many<T: Fake>(&mut self, count: usize) -> Vec<T> {
+        (0..count).map(|_| T::fake(self)).collect()
+    }
+
+    /// `count` distinct values from `generate`, for columns with a `UNIQUE` constraint.
+    ///
+    /// # Returns
+    ///
+    /// `None` if `generate` can't produce `count` distinct values within `count * 10 + 100`
+    /// attempts, e.g. when asking for 1000 unique country codes.
+    fn unique<T: Eq + Hash + Clone>(&mut self, count: usize, mut generate: impl FnMut(&mut Self) -> T) -> Option<Vec<T>> {
+        let mut seen = HashSet::with_capacity(count);
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count * 10 + 100 {
+            if values.len() == count {
+                break;
+            }
+            let value = generate(self);
+            if seen.insert(value.clone()) {
+                values.push(value);
+            }
+        }
+        (values.len() == count).then_some(values)
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        *items.choose(&mut self.rng).expect("pick from a non-empty slice")
+    }
+
+    /// `true` with probability `p` (0.0 to 1.0).
+    fn chance(&mut self, p: f64) -> bool {
+        self.rng.gen_bool(p.clamp(0.0, 1.0))
+    }
+
+    // --- People ---
+
+    fn first_name(&mut self) -> &'static str {
+        self.pick(FIRST_NAMES)
+    }
+
+    fn last_name(&mut self) -> &'static str {
+        self.pick(LAST_NAMES)
+    }
+
+    fn full_name(&mut self) -> String {
+        format!("{} {}", self.first_name(), self.last_name())
+    }
+
+    /// An address at a reserved `example.*` domain, built from `name` when given.
+    ///
+    /// The numeric suffix keeps collisions rare; use `unique` when the column must be unique.
+    fn email(&mut self, name: Option<&str>) -> String {
+        let local = match name {
+            Some(name) => name.to_lowercase().split_whitespace().collect::<Vec<_>>().join("."),
+            None => format!("{}.{}", self.first_name(), self.last_name()).to_lowercase(),
+        };
+        let number: u16 = self.rng.gen_range(1..10_000);
+        format!("{}{}@{}", local, number, self.pick(EMAIL_DOMAINS))
+    }
+
+    fn username(&mut self) -> String {
+        let (first, last) = (self.first_name().to_lowercase(), self.last_name().to_lowercase());
+        match self.rng.gen_range(0..3) {
+            0 => format!("{}{}", first, self.rng.gen_range(1..1000)),
+            1 => format!("{}_{}", first, last),
+            _ => format!("{}{}", &first[..1], last),
+        }
+    }
+
+    /// A North American number in the 555-0100 to 555-0199 range reserved for fiction.
+    fn phone(&mut self) -> String {
+        format!("+1-{}-555-01{:02}", self.rng.gen_range(201..990), self.rng.gen_range(0..100))
+    }
+
+    /// An age in years, inclusive of both bounds.
+    fn age(&mut self, min: u8, max: u8) -> u8 {
+        self.rng.gen_range(min..=max)
+    }
+
+    /// A birth date for someone aged between `min_age` and `max_age` on `today`.
+    fn date_of_birth(&mut self, today: NaiveDate, min_age: u32, max_age: u32) -> NaiveDate {
+        let days = self.rng.gen_range(min_age as i64 * 365..=max_age as i64 * 365 + 364);
+        today - Duration::days(days)
+    }
+
+    // --- Organizations and places ---
+
+    fn company(&mut self) -> String {
+        format!("{} {}", self.pick(COMPANY_WORDS), self.pick(COMPANY_SUFFIXES))
+    }
+
+    fn street_address(&mut self) -> String {
+        format!("{} {}", self.rng.gen_range(1..2000), self.pick(STREETS))
+    }
+
+    fn city(&mut self) -> &'static str {
+        self.pick(CITIES)
+    }
+
+    /// An ISO 3166-1 alpha-2 code.
+    fn country_code(&mut self) -> &'static str {
+        self.pick(COUNTRY_CODES)
+    }
+
+    // --- Network ---
+
+    /// An address from one of the three IPv4 documentation blocks (RFC 5737).
+    fn ipv4(&mut self) -> Ipv4Addr {
+        let [a, b, c] = self.pick(&[[192, 0, 2], [198, 51, 100], [203, 0, 113]]);
+        Ipv4Addr::new(a, b, c, self.rng.gen_range(1..255))
+    }
+
+    /// An address from a private range (RFC 1918), for internal-network test data.
+    fn private_ipv4(&mut self) -> Ipv4Addr {
+        match self.rng.gen_range(0..3) {
+            0 => Ipv4Addr::new(10, self.rng.gen(), self.rng.gen(), self.rng.gen_range(1..255)),
+            1 => Ipv4Addr::new(172, self.rng.gen_range(16..32), self.rng.gen(), self.rng.gen_range(1..255)),
+            _ => Ipv4Addr::new(192, 168, self.rng.gen(), self.rng.gen_range(1..255)),
+        }
+    }
+
+    /// An address in the IPv6 documentation prefix `2001:db8::/32` (RFC 3849).
+    fn ipv6(&mut self) -> Ipv6Addr {
+        let r: [u16; 6] = self.rng.gen();
+        Ipv6Addr::new(0x2001, 0x0db8, r[0], r[1], r[2], r[3], r[4], r[5])
+    }
+
+    fn ip(&mut self) -> IpAddr {
+        if self.chance(0.8) { IpAddr::V4(self.ipv4()) } else { IpAddr::V6(self.ipv6()) }
+    }
+
+    fn user_agent(&mut self) -> String {
+        let browser = self.pick(&["Firefox/128.0", "Chrome/127.0.0.0 Safari/537.36", "Version/17.5 Safari/605.1.15"]);
+        let platform = self.pick(&["Windows NT 10.0; Win64; x64", "Macintosh; Intel Mac OS X 14_5", "X11; Linux x86_64"]);
+        format!("Mozilla/5.0 ({}) {}", platform, browser)
+    }
+
+    // --- Identifiers and time ---
+
+    /// A version 4 UUID built from the seeded RNG, so it is reproducible too.
+    fn uuid(&mut self) -> Uuid {
+        uuid::Builder::from_random_bytes(self.rng.gen()).into_uuid()
+    }
+
+    /// A timestamp uniformly between `start` and `end` (inclusive), to the second.
+    fn timestamp_between(&mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> DateTime<Utc> {
+        let (start, end) = (start.timestamp().min(end.timestamp()), start.timestamp().max(end.timestamp()));
+        Utc.timestamp_opt(self.rng.gen_range(start..=end), 0).single().expect("in chrono's range")
+    }
+
+    /// A timestamp within the `days` days before `now`, e.g. for `created_at` columns.
+    fn recent(&mut self, now: DateTime<Utc>, days: i64) -> DateTime<Utc> {
+        self.timestamp_between(now - Duration::days(days), now)
+    }
+
+    // --- Text ---
+
+    fn word(&mut self) -> &'static str {
+        self.pick(LOREM)
+    }
+
+    /// A capitalized sentence of `min..=max` lorem ipsum words, ending in a period.
+    fn sentence(&mut self, min: usize, max: usize) -> String {
+        let len = self.rng.gen_range(min.max(1)..=max.max(min.max(1)));
+        let mut sentence = (0..len).map(|_| self.word()).collect::<Vec<_>>().join(" ");
+        sentence[..1].make_ascii_uppercase();
+        sentence + "."
+    }
+
+    fn paragraph(&mut self, sentences: usize) -> String {
+        (0..sentences).map(|_| self.sentence(6, 14)).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Types that can produce a fake value of themselves.
+///
+/// Implement it for record structs by calling the `Faker` method that fits each field; the
+/// impls below cover primitives and common field types.
+trait Fake: Sized {
+    fn fake(faker: &mut Faker) -> Self;
+}
+
+macro_rules! fake_via_gen {
+    ($($ty:ty),*) => {
+        $(impl Fake for $ty {
+            fn fake(faker: &mut Faker) -> Self {
+                faker.rng.gen()
+            }
+        })*
+    };
+}
+
+fake_via_gen!(bool, u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl Fake for String {
+    fn fake(faker: &mut Faker) -> Self {
+        faker.sentence(3, 8)
+    }
+}
+
+impl Fake for Uuid {
+    fn fake(faker: &mut Faker) -> Self {
+        faker.uuid()
+    }
+}
+
+impl Fake for IpAddr {
+    fn fake(faker: &mut Faker) -> Self {
+        faker.ip()
+    }
+}
+
+/// Within the last year before the Unix time 1_735_689_600 (2025-01-01), so results don't
+/// depend on when the test runs; use `Faker::recent` to be relative to the real clock.
+impl Fake for DateTime<Utc> {
+    fn fake(faker: &mut Faker) -> Self {
+        let end = Utc.timestamp_opt(1_735_689_600, 0).single().expect("valid timestamp");
+        faker.recent(end, 365)
+    }
+}
+
+/// `Some` 80% of the time, so nullable columns get some `NULL`s.
+impl<T: Fake> Fake for Option<T> {
+    fn fake(faker: &mut Faker) -> Self {
+        faker.chance(0.8).then(|| T::fake(faker))
+    }
+}
+
+/// Zero to four elements.
+impl<T: Fake> Fake for Vec<T> {
+    fn fake(faker: &mut Faker) -> Self {
+        let len = faker.rng.gen_range(0..5);
+        faker.many(len)
+    }
+}
+
+// Example Usage
+/*
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct User {
+    id: Uuid,
+    name: String,
+    email: String,
+    username: String,
+    phone: Option<String>,
+    company: String,
+    last_login_ip: IpAddr,
+    created_at: DateTime<Utc>,
+    bio: String,
+}
+
+impl Fake for User {
+    fn fake(f: &mut Faker) -> Self {
+        let name = f.full_name();
+        User {
+            id: f.uuid(),
+            email: f.email(Some(&name)),
+            name,
+            username: f.username(),
+            phone: f.chance(0.7).then(|| f.phone()),
+            company: f.company(),
+            last_login_ip: f.ip(),
+            created_at: f.make(),
+            bio: f.paragraph(2),
+        }
+    }
+}
+
+fn main() {
+    let mut faker = Faker::seeded(42);
+    let users: Vec<User> = faker.many(3);
+    println!("{}", serde_json::to_string_pretty(&users[0]).unwrap());
+    for user in &users[1..] {
+        println!("{} <{}> from {} at {}", user.name, user.email, user.last_login_ip, user.created_at);
+    }
+
+    // The same seed gives the same data.
+    let again: Vec<User> = Faker::seeded(42).many(3);
+    println!("reproducible: {}", users.iter().zip(&again).all(|(a, b)| a.id == b.id && a.email == b.email));
+
+    // Unique values for a UNIQUE column, and a clear failure when the pool is too small.
+    let emails = faker.unique(5, |f| f.email(None)).unwrap();
+    println!("unique emails: {:?}", emails);
+    println!("20 unique countries: {:?}", faker.unique(20, |f| f.country_code()));
+
+    let dob = faker.date_of_birth(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(), 18, 65);
+    println!("{} {} | {} | {} | {} | {}", faker.street_address(), faker.city(), dob, faker.private_ipv4(), faker.ipv6(), faker.user_agent());
+    let tags: Vec<Option<u8>> = faker.make();
+    println!("tags: {:?}, seed {}", tags, faker.seed());
+}
+*/
@@ -0,0 +1,367 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+//
+// Feature flags without a flag service:
+// * Flags come from a JSON file, `FEATURE_<NAME>` environment variables, and an optional
+//   local override file (e.g. `flags.local.json`, gitignored) for development. Later sources
+//   win: file < environment < local overrides.
+// * A flag is either a plain boolean or a rule with a percentage rollout and allow/deny lists:
+//     { "dark_mode": true,
+//       "new_checkout": { "rollout": 25, "allow": ["qa-team"], "deny": ["big-customer"] } }
+// * Rollouts bucket users deterministically: the same user key always gets the same answer,
+//   raising the percentage only adds users, and each flag buckets independently. The hash is
+//   the fixed FNV-1a + SplitMix64 from `consistent_hash.rs`, so every process agrees.
+// * `reload_if_changed` re-reads the files when their modification time changes, and `watch`
+//   does that on a background thread. A broken edit keeps the last good flags.
+// * `is_enabled(flag, &context)` answers `false` for unknown flags, so removing a flag from
+//   the file turns it off rather than failing.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+// --- Flag definitions ---
+
+/// A flag as written in JSON: `true`/`false`, or a rule object.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum FlagSpec {
+    Bool(bool),
+    Rule(FlagRule),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FlagRule {
+    /// Master switch; `false` turns the flag off for everyone, including `allow`.
+    #[serde(default = "default_true")]
+    enabled: bool,
+    /// Percentage of user keys (0-100) that get the flag. Missing means everyone.
+    rollout: Option<f64>,
+    /// Keys that always get the flag (testers, internal accounts).
+    #[serde(default)]
+    allow: HashSet<String>,
+    /// Keys that never get the flag; checked before `allow`.
+    #[serde(default)]
+    deny: HashSet<String>,
+    /// Changes which users fall inside the rollout; defaults to the flag name.
+    salt: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl FlagSpec {
+    fn evaluate(&self, name: &str, context: &Context) -> bool {
+        let rule = match self {
+            FlagSpec::Bool(on) => return *on,
+            FlagSpec::Rule(rule) => rule,
+        };
+        if !rule.enabled {
+            return false;
+        }
+        let key = context.key.as_deref();
+        if let Some(key) = key {
+            if rule.deny.contains(key) {
+                return false;
+            }
+            if rule.allow.contains(key) {
+                return true;
+            }
+        }
+        match (rule.rollout, key) {
+            (None, _) => true,
+            (Some(percent), _) if percent >= 100.0 => true,
+            // Anonymous users can't be bucketed consistently, so they stay out of partial rollouts.
+            (Some(_), None) => false,
+            (Some(percent), Some(key)) => {
+                let salt = rule.salt.as_deref().unwrap_or(name);
+                f64::from(bucket(salt, key)) < percent * 100.0
+            }
+        }
+    }
+}
+
+/// Who a flag is being evaluated for.
+#[derive(Debug, Clone, Default)]
+struct Context {
+    /// Stable identifier for bucketing: a user id, account id, or device id.
+    key: Option<String>,
+}
+
+impl Context {
+    fn user(key: impl Into<String>) -> Self {
+        Self { key: Some(key.into()) }
+    }
+
+    fn anonymous() -> Self {
+        Self::default()
+    }
+}
+
+// --- Deterministic bucketing ---
+
+/// A bucket in `0..10_000` (basis points) for `key` under `salt`, stable across processes,
+/// platforms, and releases: FNV-1a over `salt:key`, finished with SplitMix64.
+fn bucket(salt: &str, key: &str) -> u32 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in salt.as_bytes().iter().chain(b":").chain(key.as_bytes()) {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    let mut z = hash.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z % 10_000) as u32
+}
+
+// --- Sources ---
+
+#[derive(Debug)]
+enum FlagError {
+    Io { path: PathBuf, source: io::Error },
+    Parse { path: PathBuf, source: serde_json::Error },
+    Env { var: String, value: String },
+}
+
+impl fmt::Display for FlagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlagError::Io { path, source } => write!(f, "reading {}: {}", path.display(), source),
+            FlagError::Parse { path, source } => write!(f, "parsing {}: {}", path.display(), source),
+            FlagError::Env { var, value } => {
+                write!(f, "{}={:?}: expected true/false/on/off/1/0 or a percentage like 25%", var, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FlagError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FlagError::Io { source, .. } => Some(source),
+            FlagError::Parse { source, .. } => Some(source),
+            FlagError::Env { .. } => None,
+        }
+    }
+}
+
+/// Where flags are loaded from; every source is optional.
+#[derive(Debug, Clone, Default)]
+struct FlagSources {
+    /// The checked-in or deployed flag file. Required to exist when set.
+    file: Option<PathBuf>,
+    /// `FEATURE_NEW_CHECKOUT=25%` sets `new_checkout`. Usually `Some("FEATURE_")`.
+    env_prefix: Option<String>,
+    /// A developer's local overrides. Ignored when the file doesn't exist.
+    local_override: Option<PathBuf>,
+}
+
+fn read_flag_file(path: &Path) -> Result<BTreeMap<String, FlagSpec>, FlagError> {
+    let text = fs::read_to_string(path).map_err(|source| FlagError::Io { path: path.to_path_buf(), source })?;
+    serde_json::from_str(&text).map_err(|source| FlagError::Parse { path: path.to_path_buf(), source })
+}
+
+fn parse_env_flag(var: &str, value: &str) -> Result<FlagSpec, FlagError> {
+    let normalized = value.trim().to_ascii_lowercase();
+    match normalized.as_str() {
+        "1" | "true" | "on" | "yes" => return Ok(FlagSpec::Bool(true)),
+        "0" | "false" | "off" | "no" => return Ok(FlagSpec::Bool(false)),
+        _ => {}
+    }
+    match normalized.strip_suffix('%').and_then(|p| p.trim().parse::<f64>().ok()) {
+        Some(percent) if (0.0..=100.0).contains(&percent) => Ok(FlagSpec::Rule(FlagRule {
+            enabled: true,
+            rollout: Some(percent),
+            allow: HashSet::new(),
+            deny: HashSet::new(),
+            salt: None,
+        })),
+        _ => Err(FlagError::Env { var: var.to_string(), value: value.to_string() }),
+    }
+}
+
+/// Merges all sources, later ones replacing whole flags from earlier ones.
+fn load_flags(sources: &FlagSources) -> Result<BTreeMap<String, FlagSpec>, FlagError> {
+    let mut flags = match &sources.file {
+        Some(path) => read_flag_file(path)?,
+        None => BTreeMap::new(),
+    };
+    if let Some(prefix) = &sources.env_prefix {
+        // `vars_os`, because `vars` panics on any variable that isn't valid Unicode.
+        for (var, value) in std::env::vars_os() {
+            let (Some(var), Some(value)) = (var.to_str(), value.to_str()) else { continue };
+            if let Some(name) = var.strip_prefix(prefix.as_str()) {
+                flags.insert(name.to_ascii_lowercase(), parse_env_flag(var, value)?);
+            }
+        }
+    }
+    if let Some(path) = sources.local_override.as_deref().filter(|path| path.exists()) {
+        flags.extend(read_flag_file(path)?);
+    }
+    Ok(flags)
+}
+
+fn modified(path: &Option<PathBuf>) -> Option<SystemTime> {
+    path.as_ref().and_then(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+}
+
+// --- The store ---
+
+/// The current flags, safe to share between threads and swap out on reload.
+struct FlagStore {
+    sources: FlagSources,
+    flags: RwLock<Arc<BTreeMap<String, FlagSpec>>>,
+    /// Modification times of (file, local override) at the last successful load.
+    seen: Mutex<(Option<SystemTime>, Option<SystemTime>)>,
+}
+
+impl FlagStore {
+    /// Loads every source once.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - The file, environment prefix, and local override file to use.
+    ///
+    /// # Returns
+    ///
+    /// The store, or the first unreadable file, invalid JSON, or invalid env value.
+    fn load(sources: FlagSources) -> Result<Self, FlagError> {
+        let seen = (modified(&sources.file), modified(&sources.local_override));
+        let flags = load_flags(&sources)?;
+        Ok(Self { sources, flags: RwLock::new(Arc::new(flags)), seen: Mutex::new(seen) })
+    }
+
+    /// Whether `flag` is on for `context`. Unknown flags are off.
+    fn is_enabled(&self, flag: &str, context: &Context) -> bool {
+        self.is_enabled_or(flag, context, false)
+    }
+
+    /// Like `is_enabled`, with an explicit answer for flags that aren't defined anywhere.
+    fn is_enabled_or(&self, flag: &str, context: &Context, default: bool) -> bool {
+        let flags = self.flags.read().unwrap_or_else(|e| e.into_inner()).clone();
+        flags.get(flag).map_or(default, |spec| spec.evaluate(flag, context))
+    }
+
+    /// Names of all defined flags.
+    fn names(&self) -> Vec<String> {
+        self.flags.read().unwrap_or_else(|e| e.into_inner()).keys().cloned().collect()
+    }
+
+    /// Re-reads every source. On error the current flags stay in place.
+    fn reload(&self) -> Result<(), FlagError> {
+        *self.seen.lock().unwrap_or_else(|e| e.into_inner()) =
+            (modified(&self.sources.file), modified(&self.sources.local_override));
+        let flags = load_flags(&self.sources)?;
+        *self.flags.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(flags);
+        Ok(())
+    }
+
+    /// Reloads if either file's modification time changed (or it appeared or disappeared).
+    /// Returns whether a reload happened. A file that fails to load isn't retried until it
+    /// changes again. Environment changes need an explicit `reload`.
+    fn reload_if_changed(&self) -> Result<bool, FlagError> {
+        let now = (modified(&self.sources.file), modified(&self.sources.local_override));
+        if *self.seen.lock().unwrap_or_else(|e| e.into_inner()) == now {
+            return Ok(false);
+        }
+        self.reload().map(|()| true)
+    }
+
+    /// Polls the files every `interval` on a background thread until the `Watcher` drops.
+    /// Reload errors are printed to stderr; the last good flags stay active.
+    fn watch(self: &Arc<Self>, interval: Duration) -> Watcher {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (store, stopped) = (Arc::clone(self), Arc::clone(&stop));
+        let handle = std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                std::thread::sleep(interval);
+                match store.reload_if_changed() {
+                    Ok(true) => eprintln!("feature flags reloaded: {} flags", store.names().len()),
+                    Ok(false) => {}
+                    Err(e) => eprintln!("feature flags not reloaded, keeping previous: {}", e),
+                }
+            }
+        });
+        Watcher { stop, handle: Some(handle) }
+    }
+}
+
+/// Stops the reload thread when dropped.
+struct Watcher {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// Example Usage
+/*
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("flags-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let file = dir.join("flags.json");
+    let local = dir.join("flags.local.json");
+    fs::write(&file, r#"{
+        "dark_mode": true,
+        "beta_search": false,
+        "new_checkout": { "rollout": 25, "allow": ["qa-1"], "deny": ["vip-9"] }
+    }"#)?;
+    std::env::set_var("FEATURE_BETA_SEARCH", "on");
+
+    let sources = FlagSources { file: Some(file.clone()), env_prefix: Some("FEATURE_".into()), local_override: Some(local.clone()) };
+    let store = Arc::new(FlagStore::load(sources)?);
+
+    let users: Vec<String> = (0..1000).map(|i| format!("user-{}", i)).collect();
+    let share = |store: &FlagStore, flag: &str| users.iter().filter(|u| store.is_enabled(flag, &Context::user(u.as_str()))).count();
+    println!("new_checkout for {}/1000 users", share(&store, "new_checkout"));
+    println!("qa-1: {}, vip-9: {}, anonymous: {}", store.is_enabled("new_checkout", &Context::user("qa-1")),
+        store.is_enabled("new_checkout", &Context::user("vip-9")), store.is_enabled("new_checkout", &Context::anonymous()));
+    println!("beta_search (env override): {}, unknown: {}", store.is_enabled("beta_search", &Context::anonymous()),
+        store.is_enabled("no_such_flag", &Context::anonymous()));
+
+    // Same user, same answer; widening the rollout keeps everyone who already had it.
+    let before: Vec<bool> = users.iter().map(|u| store.is_enabled("new_checkout", &Context::user(u.as_str()))).collect();
+    let watcher = store.watch(Duration::from_millis(20));
+    std::thread::sleep(Duration::from_millis(30)); // Ensure a newer modification time.
+    fs::write(&file, r#"{ "dark_mode": true, "new_checkout": { "rollout": 50 } }"#)?;
+    std::thread::sleep(Duration::from_millis(100));
+    let after: Vec<bool> = users.iter().map(|u| store.is_enabled("new_checkout", &Context::user(u.as_str()))).collect();
+    let kept = before.iter().zip(&after).filter(|(b, a)| **b && **a).count();
+    println!("after reload: {}/1000, kept {} of {}", share(&store, "new_checkout"), kept, before.iter().filter(|b| **b).count());
+
+    // A developer turns a flag off locally; a broken edit is rejected and the old flags stay.
+    fs::write(&local, r#"{ "dark_mode": false }"#)?;
+    std::thread::sleep(Duration::from_millis(100));
+    println!("dark_mode with local override: {}", store.is_enabled("dark_mode", &Context::anonymous()));
+    std::thread::sleep(Duration::from_millis(30));
+    fs::write(&file, "{ not json")?;
+    std::thread::sleep(Duration::from_millis(100));
+    println!("still serving {} flags", store.names().len());
+
+    drop(watcher);
+    fs::write(&file, r#"{ "dark_mode": true }"#)?;
+    std::env::set_var("FEATURE_BROKEN", "maybe");
+    println!("{}", store.reload().unwrap_err());
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+*/
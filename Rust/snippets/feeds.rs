@@ -0,0 +1,435 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// chrono = { version = "0.4", features = ["serde"] }
+// feed-rs = "3"
+// reqwest = { version = "0.12", features = ["blocking"] }
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+// redis = "0.27" // Only for `RedisKvStore`
+//
+// Polling RSS and Atom feeds and handing on only what's new, for notifiers, aggregators, and
+// "watch this changelog" jobs:
+// * `parse_feed` reads RSS 0.9x/1.0/2.0, Atom, and JSON Feed (via `feed-rs`) into one
+//   `FeedItem` shape: id, title, link, summary, content, authors, categories, and dates.
+//   Items without a `<guid>`/`<id>` get a stable id hashed from their link and title.
+// * Fetches are conditional. The `ETag` and `Last-Modified` of the last response are sent
+//   back as `If-None-Match`/`If-Modified-Since`, so an unchanged feed costs a `304` and no
+//   parsing.
+// * Seen item ids live in a `KvStore` with a TTL, so a restart doesn't re-announce the whole
+//   feed. `MemoryKvStore` is for one process and tests; `RedisKvStore` shares state between
+//   instances and survives restarts. An id is claimed with `SET NX` before the item is sent,
+//   so two pollers on the same feed never both emit it (at most once: an item claimed by a
+//   poller that then crashes is not retried).
+// * New items go out on a `std::sync::mpsc` channel, oldest first. Run `poll_all` from a
+//   scheduler (`IntervalScheduler` in `clock.rs`, or a `cron.rs` schedule) and consume the
+//   receiver wherever the items are needed.
+// * On the first poll of a feed, `backfill(false)` marks the existing items as seen without
+//   sending them, so subscribing to a feed with 50 old entries doesn't produce 50 messages.
+
+use chrono::{DateTime, Utc};
+use feed_rs::model::{Entry, Text};
+use reqwest::blocking::Client;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum FeedError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("{url} returned HTTP {status}")]
+    Status { url: String, status: u16 },
+    #[error("{url} is not a valid feed: {source}")]
+    Parse { url: String, source: feed_rs::parser::ParseFeedError },
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+    #[error("stored value is corrupt: {0}")]
+    Corrupt(#[from] serde_json::Error),
+    #[error("the item receiver was dropped")]
+    ChannelClosed,
+}
+
+type Result<T> = std::result::Result<T, FeedError>;
+
+// --- Items ---
+
+/// One entry from any feed format.
+#[derive(Debug, Clone, Serialize)]
+struct FeedItem {
+    feed_url: String,
+    feed_title: Option<String>,
+    /// The entry's `<guid>`/`<id>`, or a hash of its link and title when it has none.
+    id: String,
+    title: Option<String>,
+    link: Option<String>,
+    summary: Option<String>,
+    /// Full content (`<content:encoded>`, Atom `<content>`), usually HTML.
+    content: Option<String>,
+    authors: Vec<String>,
+    categories: Vec<String>,
+    published: Option<DateTime<Utc>>,
+    updated: Option<DateTime<Utc>>,
+}
+
+impl FeedItem {
+    fn from_entry(feed_url: &str, feed_title: Option<&str>, entry: Entry) -> Self {
+        let text = |t: Option<Text>| t.map(|t| t.content.trim().to_string()).filter(|s| !s.is_empty());
+        // Atom entries can carry several links; the `alternate` one is the article itself.
+        let link = entry
+            .links
+            .iter()
+            .find(|l| l.rel.as_deref().is_none_or(|rel| rel == "alternate"))
+            .or(entry.links.first())
+            .map(|l| l.href.clone());
+        FeedItem {
+            feed_url: feed_url.to_string(),
+            feed_title: feed_title.map(String::from),
+            id: entry.id,
+            title: text(entry.title),
+            link,
+            summary: text(entry.summary),
+            content: entry.content.and_then(|c| c.body),
+            authors: entry.authors.into_iter().filter_map(|p| p.name.or(p.email)).collect(),
+            categories: entry.categories.into_iter().map(|c| c.label.unwrap_or(c.term)).collect(),
+            published: entry.published,
+            updated: entry.updated,
+        }
+    }
+
+    /// When the item appeared: its published date, or its updated date if that's all it has.
+    fn date(&self) -> Option<DateTime<Utc>> {
+        self.published.or(self.updated)
+    }
+}
+
+/// Parses a feed document, oldest item first.
+///
+/// # Arguments
+///
+/// * `url` - Where the feed came from; relative links in it are resolved against this.
+/// * `body` - The raw bytes. Don't decode them first: the XML declaration names the encoding.
+fn parse_feed(url: &str, body: &[u8]) -> Result<Vec<FeedItem>> {
+    let feed = feed_rs::parser::Builder::new()
+        .base_uri(Some(url))
+        .build()
+        .parse(body)
+        .map_err(|source| FeedError::Parse { url: url.to_string(), source })?;
+    let title = feed.title.map(|t| t.content);
+    let mut items: Vec<FeedItem> = feed
+        .entries
+        .into_iter()
+        .map(|entry| FeedItem::from_entry(url, title.as_deref(), entry))
+        .collect();
+    // Feeds list newest first by convention; reverse, then sort by date where there is one.
+    items.reverse();
+    items.sort_by_key(FeedItem::date);
+    Ok(items)
+}
+
+// --- Key-value store ---
+
+/// Where pollers keep seen ids and HTTP validators.
+trait KvStore: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<String>>;
+
+    fn set(&self, key: &str, value: &str) -> Result<()>;
+
+    /// Stores `value` under `key` for `ttl` unless the key already exists.
+    ///
+    /// # Returns
+    ///
+    /// `true` if this call stored it, `false` if the key was already there.
+    fn set_if_absent(&self, key: &str, value: &str, ttl: Duration) -> Result<bool>;
+}
+
+/// Entries in a map with optional expiry times; for a single process, and for tests.
+#[derive(Default)]
+struct MemoryKvStore {
+    entries: Mutex<HashMap<String, (String, Option<Instant>)>>,
+}
+
+impl KvStore for MemoryKvStore {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let live = entries.get(key).filter(|(_, expires_at)| expires_at.is_none_or(|at| at > Instant::now()));
+        Ok(live.map(|(value, _)| value.clone()))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(key.to_string(), (value.to_string(), None));
+        Ok(())
+    }
+
+    fn set_if_absent(&self, key: &str, value: &str, ttl: Duration) -> Result<bool> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, expires_at)) = entries.get(key) {
+            if expires_at.is_none_or(|at| at > now) {
+                return Ok(false);
+            }
+        }
+        entries.insert(key.to_string(), (value.to_string(), Some(now + ttl)));
+        Ok(true)
+    }
+}
+
+/// Entries as Redis strings under `{prefix}:{key}`.
+struct RedisKvStore {
+    client: redis::Client,
+    prefix: String,
+}
+
+impl RedisKvStore {
+    fn new(client: redis::Client, prefix: &str) -> Self {
+        Self { client, prefix: prefix.to_string() }
+    }
+
+    fn redis_key(&self, key: &str) -> String {
+        format!("{}:{}", self.prefix, key)
+    }
+}
+
+impl KvStore for RedisKvStore {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(redis::cmd("GET").arg(self.redis_key(key)).query(&mut self.client.get_connection()?)?)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+        let _: () = redis::cmd("SET").arg(self.redis_key(key)).arg(value).query(&mut self.client.get_connection()?)?;
+        Ok(())
+    }
+
+    fn set_if_absent(&self, key: &str, value: &str, ttl: Duration) -> Result<bool> {
+        let stored: Option<String> = redis::cmd("SET")
+            .arg(self.redis_key(key))
+            .arg(value)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis() as u64)
+            .query(&mut self.client.get_connection()?)?;
+        Ok(stored.is_some())
+    }
+}
+
+// --- Polling ---
+
+/// The `ETag` and `Last-Modified` of the last `200`, sent back on the next request.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PollResult {
+    /// The server answered `304 Not Modified`.
+    NotModified,
+    /// The feed was fetched; `new` of its `items` were sent on the channel.
+    Fetched { items: usize, new: usize },
+}
+
+/// Fetches feeds, filters out items it has already sent, and sends the rest on a channel.
+struct FeedPoller<S> {
+    client: Client,
+    store: S,
+    feeds: Vec<String>,
+    sender: Sender<FeedItem>,
+    seen_ttl: Duration,
+    backfill: bool,
+}
+
+impl<S: KvStore> FeedPoller<S> {
+    /// # Arguments
+    ///
+    /// * `store` - Seen ids and validators. Pollers sharing a store never send the same item twice.
+    /// * `sender` - Receives each new item once.
+    fn new(store: S, sender: Sender<FeedItem>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Self {
+            client,
+            store,
+            feeds: Vec::new(),
+            sender,
+            seen_ttl: Duration::from_secs(90 * 24 * 60 * 60),
+            backfill: true,
+        })
+    }
+
+    fn add_feed(mut self, url: &str) -> Self {
+        self.feeds.push(url.to_string());
+        self
+    }
+
+    /// How long an item id is remembered (default 90 days). Keep it longer than an item stays
+    /// in the feed, or it will be sent again once its id expires.
+    fn seen_ttl(mut self, ttl: Duration) -> Self {
+        self.seen_ttl = ttl;
+        self
+    }
+
+    /// Whether the first poll of a feed sends the items already in it (default `true`).
+    fn backfill(mut self, backfill: bool) -> Self {
+        self.backfill = backfill;
+        self
+    }
+
+    /// Polls every added feed once. One failing feed doesn't stop the others.
+    fn poll_all(&self) -> Vec<(String, Result<PollResult>)> {
+        self.feeds.iter().map(|url| (url.clone(), self.poll(url))).collect()
+    }
+
+    /// Fetches `url` conditionally and sends its unseen items.
+    ///
+    /// # Returns
+    ///
+    /// `PollResult::NotModified` on a `304`, otherwise how many items the feed had and how
+    /// many were new.
+    fn poll(&self, url: &str) -> Result<PollResult> {
+        let validators_key = format!("validators:{}", url);
+        let validators: Validators = match self.store.get(&validators_key)? {
+            Some(json) => serde_json::from_str(&json)?,
+            None => Validators::default(),
+        };
+
+        let mut request = self.client.get(url);
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        let response = request.send()?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(PollResult::NotModified);
+        }
+        if !response.status().is_success() {
+            return Err(FeedError::Status { url: url.to_string(), status: response.status().as_u16() });
+        }
+        let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).map(String::from);
+        let validators = Validators { etag: header(ETAG), last_modified: header(LAST_MODIFIED) };
+        let body = response.bytes()?;
+
+        let items = parse_feed(url, &body)?;
+        let total = items.len();
+        let new = self.ingest(url, items)?;
+        // Saved only once the items are handled: if this poll fails part-way, the next one
+        // fetches the full feed again instead of getting a 304 and missing the rest.
+        self.store.set(&validators_key, &serde_json::to_string(&validators)?)?;
+        Ok(PollResult::Fetched { items: total, new })
+    }
+
+    /// Sends the items from `items` that haven't been seen before. `poll` calls this after
+    /// fetching; call it directly for feeds that arrive another way (a webhook, a file).
+    ///
+    /// # Returns
+    ///
+    /// The number of items sent.
+    fn ingest(&self, feed_url: &str, items: Vec<FeedItem>) -> Result<usize> {
+        let polled_key = format!("polled:{}", feed_url);
+        let first_poll = self.store.get(&polled_key)?.is_none();
+        let send = self.backfill || !first_poll;
+        let mut sent = 0;
+        for item in items {
+            let seen_key = format!("seen:{}:{}", feed_url, item.id);
+            if self.store.set_if_absent(&seen_key, "1", self.seen_ttl)? && send {
+                self.sender.send(item).map_err(|_| FeedError::ChannelClosed)?;
+                sent += 1;
+            }
+        }
+        if first_poll {
+            self.store.set(&polled_key, &Utc::now().to_rfc3339())?;
+        }
+        Ok(sent)
+    }
+}
+
+// Example Usage
+/*
+use std::sync::mpsc;
+use std::thread;
+
+const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0"><channel>
+  <title>Example Releases</title>
+  <link>https://example.com/</link>
+  <item>
+    <title>v1.1.0</title>
+    <link>/releases/1.1.0</link>
+    <guid>release-1.1.0</guid>
+    <pubDate>Tue, 08 Oct 2024 09:00:00 GMT</pubDate>
+    <category>release</category>
+  </item>
+  <item>
+    <title>v1.0.0</title>
+    <link>/releases/1.0.0</link>
+    <guid>release-1.0.0</guid>
+    <pubDate>Mon, 02 Sep 2024 09:00:00 GMT</pubDate>
+  </item>
+</channel></rss>"#;
+
+const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Example Blog</title>
+  <id>urn:example:blog</id>
+  <updated>2024-10-10T12:00:00Z</updated>
+  <entry>
+    <title>Announcing 1.1</title>
+    <id>urn:example:post:2</id>
+    <link rel="alternate" href="https://example.com/blog/announcing-1-1"/>
+    <updated>2024-10-10T12:00:00Z</updated>
+    <author><name>Ada</name></author>
+    <summary>What's new in 1.1.</summary>
+  </entry>
+</feed>"#;
+
+fn main() -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let poller = FeedPoller::new(MemoryKvStore::default(), tx)?
+        .add_feed("https://blog.rust-lang.org/feed.xml")
+        .backfill(false);
+
+    // Items from any format come out in the same shape, oldest first.
+    for item in parse_feed("https://example.com/releases.xml", RSS.as_bytes())? {
+        println!("{} | {:?} | {:?} | {:?}", item.id, item.title, item.link, item.date().map(|d| d.date_naive()));
+    }
+    let atom = parse_feed("https://example.com/blog.atom", ATOM.as_bytes())?;
+    println!("atom: {:?} by {:?} in {:?}", atom[0].title, atom[0].authors, atom[0].feed_title);
+
+    // Dedup across polls. With backfill off, the first sight of a feed only records its items.
+    let rss_url = "https://example.com/releases.xml";
+    println!("first ingest sent {}", poller.ingest(rss_url, parse_feed(rss_url, RSS.as_bytes())?)?);
+    let newer = RSS.replacen(
+        "<item>",
+        "<item><title>v1.2.0</title><guid>release-1.2.0</guid><pubDate>Fri, 01 Nov 2024 09:00:00 GMT</pubDate></item>\n  <item>",
+        1,
+    );
+    println!("second ingest sent {}", poller.ingest(rss_url, parse_feed(rss_url, newer.as_bytes())?)?);
+    for item in rx.try_iter() {
+        println!("new: {} {:?}", item.id, item.title);
+    }
+
+    // In a service: poll on a schedule (see `IntervalScheduler` in `clock.rs`) and consume
+    // the receiver elsewhere.
+    let worker = thread::spawn(move || {
+        for (url, result) in poller.poll_all() {
+            match result {
+                Ok(outcome) => println!("{}: {:?}", url, outcome),
+                Err(err) => println!("{}: {}", url, err),
+            }
+        }
+    });
+    worker.join().expect("poller thread panicked");
+    println!("{} item(s) waiting", rx.try_iter().count());
+    Ok(())
+}
+*/
@@ -0,0 +1,484 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+//
+// A filesystem seam so file-manipulating code can be unit tested without touching the disk:
+// * `FsProvider` is the handful of operations the file I/O snippets need (read, write,
+//   append, list, rename, remove). Code takes a `&dyn FsProvider` instead of calling `std::fs`.
+// * `RealFs` passes everything through to `std::fs`; use it in production.
+// * `MemFs` keeps files in a map behind a mutex. It mirrors the disk's error behavior for the
+//   common cases (missing parent directory, writing over a directory, removing a missing
+//   file) so tests catch the same mistakes.
+// * `MemFs::snapshot` / `restore` / `FsSnapshot::diff` let a test capture the tree before an
+//   operation and assert exactly which files it added, removed, or modified.
+// * `read_lines`, `write_lines`, `read_json`, and `write_json_pretty` are the helpers from
+//   `read_text_file.rs`, `write_text_file.rs`, `read_json_file.rs`, and `write_json_file.rs`,
+//   rewritten against the trait.
+//
+// `MemFs` paths are resolved lexically: relative paths start at `/`, `.` is dropped, and `..`
+// pops a component. There are no symlinks, permissions, or timestamps.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+// --- The trait ---
+
+/// The filesystem operations file-handling code needs, as a mockable trait.
+trait FsProvider: Send + Sync {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Creates or truncates the file; the parent directory must exist.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Appends to the file, creating it if needed; the parent directory must exist.
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Moves a file, replacing any file already at `to`.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// The entries directly inside a directory, sorted.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    fn exists(&self, path: &Path) -> bool;
+
+    fn is_dir(&self, path: &Path) -> bool;
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+// --- Real disk ---
+
+/// Passes every call through to `std::fs`.
+struct RealFs;
+
+impl FsProvider for RealFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::OpenOptions::new().create(true).append(true).open(path)?.write_all(contents)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut entries = fs::read_dir(path)?.map(|entry| entry.map(|e| e.path())).collect::<io::Result<Vec<_>>>()?;
+        entries.sort();
+        Ok(entries)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+}
+
+// --- In memory ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    File(Vec<u8>),
+    Dir,
+}
+
+/// An in-memory filesystem. Starts with just the root directory `/`.
+struct MemFs {
+    nodes: Mutex<BTreeMap<PathBuf, Node>>,
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{}: no such file or directory", path.display()))
+}
+
+fn is_a_directory(path: &Path) -> io::Error {
+    io::Error::other(format!("{}: is a directory", path.display()))
+}
+
+/// Resolves `path` to an absolute path without `.` or `..` components.
+fn normalize(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::from("/");
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => resolved.push(name),
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+    resolved
+}
+
+impl MemFs {
+    fn new() -> Self {
+        let mut nodes = BTreeMap::new();
+        nodes.insert(PathBuf::from("/"), Node::Dir);
+        Self { nodes: Mutex::new(nodes) }
+    }
+
+    /// A filesystem holding the given files, with their parent directories created.
+    fn with_files<P: AsRef<Path>, C: AsRef<[u8]>>(files: impl IntoIterator<Item = (P, C)>) -> io::Result<Self> {
+        let fs = Self::new();
+        for (path, contents) in files {
+            let path = path.as_ref();
+            if let Some(parent) = path.parent() {
+                fs.create_dir_all(parent)?;
+            }
+            fs.write(path, contents.as_ref())?;
+        }
+        Ok(fs)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Node>> {
+        self.nodes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Checks that `path`'s parent is an existing directory.
+    fn check_parent(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> io::Result<()> {
+        match path.parent().map(|parent| (parent, nodes.get(parent))) {
+            None | Some((_, Some(Node::Dir))) => Ok(()),
+            Some((parent, Some(Node::File(_)))) => {
+                Err(io::Error::other(format!("{}: not a directory", parent.display())))
+            }
+            Some((parent, None)) => Err(not_found(parent)),
+        }
+    }
+
+    /// Copies every file's path and contents. Directories that hold files are implied.
+    fn snapshot(&self) -> FsSnapshot {
+        let files = self
+            .lock()
+            .iter()
+            .filter_map(|(path, node)| match node {
+                Node::File(contents) => Some((path.clone(), contents.clone())),
+                Node::Dir => None,
+            })
+            .collect();
+        FsSnapshot { files }
+    }
+
+    /// Replaces the whole tree with a snapshot's files (and their parent directories).
+    fn restore(&self, snapshot: &FsSnapshot) {
+        let mut nodes = self.lock();
+        nodes.clear();
+        nodes.insert(PathBuf::from("/"), Node::Dir);
+        for (path, contents) in &snapshot.files {
+            for ancestor in path.ancestors().skip(1) {
+                nodes.insert(ancestor.to_path_buf(), Node::Dir);
+            }
+            nodes.insert(path.clone(), Node::File(contents.clone()));
+        }
+    }
+}
+
+impl Default for MemFs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FsProvider for MemFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let path = normalize(path);
+        match self.lock().get(&path) {
+            Some(Node::File(contents)) => Ok(contents.clone()),
+            Some(Node::Dir) => Err(is_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        let mut nodes = self.lock();
+        Self::check_parent(&nodes, &path)?;
+        if let Some(Node::Dir) = nodes.get(&path) {
+            return Err(is_a_directory(&path));
+        }
+        nodes.insert(path, Node::File(contents.to_vec()));
+        Ok(())
+    }
+
+    fn append(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        let mut nodes = self.lock();
+        Self::check_parent(&nodes, &path)?;
+        match nodes.entry(path).or_insert_with(|| Node::File(Vec::new())) {
+            Node::File(existing) => {
+                existing.extend_from_slice(contents);
+                Ok(())
+            }
+            Node::Dir => Err(io::Error::other("cannot append to a directory")),
+        }
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut nodes = self.lock();
+        // Root first, so a file in the way is reported at the shallowest point.
+        let ancestors: Vec<&Path> = path.ancestors().collect();
+        for dir in ancestors.into_iter().rev() {
+            match nodes.get(dir) {
+                Some(Node::Dir) => {}
+                Some(Node::File(_)) => {
+                    return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{}: is a file", dir.display())))
+                }
+                None => {
+                    nodes.insert(dir.to_path_buf(), Node::Dir);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut nodes = self.lock();
+        match nodes.get(&path) {
+            Some(Node::File(_)) => {
+                nodes.remove(&path);
+                Ok(())
+            }
+            Some(Node::Dir) => Err(is_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (from, to) = (normalize(from), normalize(to));
+        let mut nodes = self.lock();
+        Self::check_parent(&nodes, &to)?;
+        match (nodes.get(&from), nodes.get(&to)) {
+            (None, _) => Err(not_found(&from)),
+            (Some(Node::Dir), _) => Err(io::Error::other("MemFs only renames files")),
+            (_, Some(Node::Dir)) => Err(is_a_directory(&to)),
+            (Some(Node::File(_)), _) => {
+                let node = nodes.remove(&from).expect("checked above");
+                nodes.insert(to, node);
+                Ok(())
+            }
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let path = normalize(path);
+        let nodes = self.lock();
+        match nodes.get(&path) {
+            Some(Node::Dir) => {}
+            Some(Node::File(_)) => {
+                return Err(io::Error::other(format!("{}: not a directory", path.display())))
+            }
+            None => return Err(not_found(&path)),
+        }
+        // Keys are sorted, so a directory's descendants follow it in one contiguous run.
+        Ok(nodes
+            .range(path.clone()..)
+            .skip(1)
+            .take_while(|(child, _)| child.starts_with(&path))
+            .filter(|(child, _)| child.parent() == Some(path.as_path()))
+            .map(|(child, _)| child.clone())
+            .collect())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.lock().contains_key(&normalize(path))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        matches!(self.lock().get(&normalize(path)), Some(Node::Dir))
+    }
+}
+
+// --- Snapshots and diffs ---
+
+/// The files in a `MemFs` at one moment.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct FsSnapshot {
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FsChange {
+    Added(PathBuf),
+    Removed(PathBuf),
+    Modified(PathBuf),
+}
+
+impl fmt::Display for FsChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsChange::Added(path) => write!(f, "+ {}", path.display()),
+            FsChange::Removed(path) => write!(f, "- {}", path.display()),
+            FsChange::Modified(path) => write!(f, "~ {}", path.display()),
+        }
+    }
+}
+
+impl FsSnapshot {
+    /// The file changes that turn `self` into `after`, sorted by path.
+    fn diff(&self, after: &FsSnapshot) -> Vec<FsChange> {
+        let mut changes = Vec::new();
+        for (path, before) in &self.files {
+            match after.files.get(path) {
+                None => changes.push(FsChange::Removed(path.clone())),
+                Some(now) if now != before => changes.push(FsChange::Modified(path.clone())),
+                Some(_) => {}
+            }
+        }
+        for path in after.files.keys().filter(|path| !self.files.contains_key(*path)) {
+            changes.push(FsChange::Added(path.clone()));
+        }
+        changes.sort_by(|a, b| change_path(a).cmp(change_path(b)));
+        changes
+    }
+
+    fn get(&self, path: impl AsRef<Path>) -> Option<&[u8]> {
+        self.files.get(&normalize(path.as_ref())).map(Vec::as_slice)
+    }
+}
+
+fn change_path(change: &FsChange) -> &Path {
+    match change {
+        FsChange::Added(path) | FsChange::Removed(path) | FsChange::Modified(path) => path,
+    }
+}
+
+// --- File helpers over the trait ---
+
+/// Reads a text file into its lines (without line endings).
+///
+/// # Arguments
+///
+/// * `fs` - `&RealFs` in production, a `MemFs` in tests.
+/// * `path` - The file to read.
+///
+/// # Returns
+///
+/// The lines, or the `io::Error` from reading or UTF-8 decoding.
+fn read_lines(fs: &dyn FsProvider, path: impl AsRef<Path>) -> io::Result<Vec<String>> {
+    Ok(fs.read_to_string(path.as_ref())?.lines().map(String::from).collect())
+}
+
+/// Writes each line followed by `\n`, replacing the file or appending to it.
+fn write_lines(fs: &dyn FsProvider, path: impl AsRef<Path>, lines: &[&str], overwrite: bool) -> io::Result<()> {
+    let mut text = String::new();
+    for line in lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    if overwrite {
+        fs.write(path.as_ref(), text.as_bytes())
+    } else {
+        fs.append(path.as_ref(), text.as_bytes())
+    }
+}
+
+/// Reads and deserializes a JSON file.
+fn read_json<T: DeserializeOwned>(fs: &dyn FsProvider, path: impl AsRef<Path>) -> Result<T, Box<dyn std::error::Error>> {
+    Ok(serde_json::from_slice(&fs.read(path.as_ref())?)?)
+}
+
+/// Serializes `data` as pretty-printed JSON with a trailing newline.
+///
+/// Writes a `.tmp` sibling first and renames it over the target, so readers never see a
+/// half-written file on disk.
+fn write_json_pretty<T: Serialize>(fs: &dyn FsProvider, path: impl AsRef<Path>, data: &T) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let mut json = serde_json::to_vec_pretty(data)?;
+    json.push(b'\n');
+    let tmp = path.with_extension("json.tmp");
+    fs.write(&tmp, &json)?;
+    fs.rename(&tmp, path)?;
+    Ok(())
+}
+
+// Example Usage
+/*
+use serde::Deserialize;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Settings {
+    theme: String,
+    recent: Vec<String>,
+}
+
+/// The code under test: records an opened file in the settings and the history log.
+fn remember_opened(fs: &dyn FsProvider, file: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut settings: Settings = read_json(fs, "/app/settings.json")?;
+    settings.recent.retain(|f| f != file);
+    settings.recent.insert(0, file.to_string());
+    settings.recent.truncate(3);
+    write_json_pretty(fs, "/app/settings.json", &settings)?;
+    fs.create_dir_all(Path::new("/app/logs"))?;
+    write_lines(fs, "/app/logs/history.log", &[&format!("opened {}", file)], false)?;
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let fs = MemFs::with_files([
+        ("/app/settings.json", r#"{ "theme": "dark", "recent": ["a.txt", "b.txt", "c.txt"] }"#),
+        ("/app/README", "notes"),
+    ])?;
+
+    let before = fs.snapshot();
+    remember_opened(&fs, "c.txt")?;
+    remember_opened(&fs, "d.txt")?;
+    let after = fs.snapshot();
+
+    for change in before.diff(&after) {
+        println!("{}", change); // ~ /app/settings.json, + /app/logs/history.log; no leftover .tmp file.
+    }
+    let settings: Settings = read_json(&fs, "/app/settings.json")?;
+    println!("recent: {:?}", settings.recent);
+    println!("history: {:?}", read_lines(&fs, "/app/logs/history.log")?);
+    println!("/app holds {:?}", fs.read_dir(Path::new("/app"))?);
+
+    // Same error behavior as the disk.
+    println!("missing parent: {}", fs.write(Path::new("/nope/x.txt"), b"x").unwrap_err());
+    println!("missing file: {}", read_lines(&fs, "/app/missing.txt").unwrap_err());
+
+    // Roll back and run the operation against a broken settings file.
+    fs.restore(&before);
+    fs.write(Path::new("/app/settings.json"), b"{ not json")?;
+    println!("corrupt settings: {}", remember_opened(&fs, "e.txt").unwrap_err());
+    println!("changed since the snapshot: {:?}", before.diff(&fs.snapshot()));
+
+    // The same helpers against the real disk.
+    let dir = std::env::temp_dir().join(format!("fs-provider-{}", std::process::id()));
+    RealFs.create_dir_all(&dir)?;
+    write_lines(&RealFs, dir.join("out.txt"), &["one", "two"], true)?;
+    println!("on disk: {:?}", read_lines(&RealFs, dir.join("out.txt"))?);
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+*/
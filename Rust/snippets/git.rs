@@ -0,0 +1,570 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// git2 = "0.20" // Bundles libgit2; the default "https" and "ssh" features need OpenSSL and libssh2
+// thiserror = "1.0"
+//
+// Typed git operations through libgit2 instead of `execute_command("git", ...)` (see
+// `execute_command.rs`). Shelling out works until it doesn't: the output format depends on the
+// installed git version and the user's config (aliases, `color.ui`, localized messages),
+// errors are strings on stderr, and prompts for credentials hang a headless process.
+// * `GitRepo::open` / `init` wrap a repository; `status`, `diff`, `log`, and `branches` return
+//   plain structs instead of text to parse.
+// * `add_all` + `commit` stage and commit, including the first commit on an unborn branch.
+// * `clone_repo` and `GitRepo::fetch` report progress through a callback and authenticate
+//   through `Auth`: the SSH agent and git credential helpers by default, or an explicit SSH
+//   key or HTTPS token. libgit2 retries a rejected credential forever, so attempts are capped.
+//
+// HTTPS certificates are checked against the system trust store and SSH host keys against
+// `~/.ssh/known_hosts`, as with the git CLI. Hooks (`pre-commit` etc.) are not run.
+
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{
+    BranchType, Cred, CredentialType, Delta, Diff, DiffFormat, DiffOptions, ErrorCode, FetchOptions, IndexAddOption, Oid,
+    RemoteCallbacks, Repository, Signature, Sort, Status, StatusOptions,
+};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum GitError {
+    #[error("git error: {0}")]
+    Git(#[from] git2::Error),
+    #[error("working tree has uncommitted changes in {0} file(s)")]
+    Dirty(usize),
+    #[error("nothing to commit")]
+    NothingToCommit,
+}
+
+type Result<T> = std::result::Result<T, GitError>;
+
+// --- Status ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+    TypeChanged,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Change::Added => "added",
+            Change::Modified => "modified",
+            Change::Deleted => "deleted",
+            Change::Renamed => "renamed",
+            Change::TypeChanged => "typechange",
+        })
+    }
+}
+
+/// One path from `git status`, split into its staged and unstaged halves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StatusEntry {
+    path: String,
+    /// Change between HEAD and the index (what `commit` would record).
+    staged: Option<Change>,
+    /// Change between the index and the working tree; untracked files are `Added`.
+    unstaged: Option<Change>,
+    conflicted: bool,
+}
+
+fn staged_change(status: Status) -> Option<Change> {
+    [
+        (Status::INDEX_NEW, Change::Added),
+        (Status::INDEX_MODIFIED, Change::Modified),
+        (Status::INDEX_DELETED, Change::Deleted),
+        (Status::INDEX_RENAMED, Change::Renamed),
+        (Status::INDEX_TYPECHANGE, Change::TypeChanged),
+    ]
+    .into_iter()
+    .find_map(|(flag, change)| status.contains(flag).then_some(change))
+}
+
+fn unstaged_change(status: Status) -> Option<Change> {
+    [
+        (Status::WT_NEW, Change::Added),
+        (Status::WT_MODIFIED, Change::Modified),
+        (Status::WT_DELETED, Change::Deleted),
+        (Status::WT_RENAMED, Change::Renamed),
+        (Status::WT_TYPECHANGE, Change::TypeChanged),
+    ]
+    .into_iter()
+    .find_map(|(flag, change)| status.contains(flag).then_some(change))
+}
+
+// --- Diff ---
+
+/// What to compare; mirrors the common `git diff` invocations.
+#[derive(Debug, Clone, Copy)]
+enum DiffTarget<'a> {
+    /// `git diff`: unstaged changes.
+    Unstaged,
+    /// `git diff --cached`: what the next commit would contain.
+    Staged,
+    /// `git diff HEAD`: everything since the last commit.
+    SinceHead,
+    /// `git diff <from> <to>` for any two revisions (`main`, `HEAD~2`, a hash).
+    Revisions(&'a str, &'a str),
+}
+
+#[derive(Debug, Clone)]
+struct FileDiff {
+    old_path: Option<PathBuf>,
+    new_path: Option<PathBuf>,
+    change: Delta,
+    additions: usize,
+    deletions: usize,
+    binary: bool,
+}
+
+#[derive(Debug, Clone)]
+struct DiffSummary {
+    files: Vec<FileDiff>,
+    /// Unified diff text, as `git diff` prints it without color.
+    patch: String,
+}
+
+impl DiffSummary {
+    fn from_diff(diff: &mut Diff<'_>) -> Result<Self> {
+        diff.find_similar(None)?; // Report renames instead of an add plus a delete.
+        let mut files = Vec::new();
+        for index in 0..diff.deltas().len() {
+            let delta = diff.get_delta(index).expect("index is in range");
+            let (additions, deletions) = match git2::Patch::from_diff(diff, index)? {
+                Some(patch) => {
+                    let (_, additions, deletions) = patch.line_stats()?;
+                    (additions, deletions)
+                }
+                None => (0, 0),
+            };
+            files.push(FileDiff {
+                old_path: delta.old_file().path().map(Path::to_path_buf),
+                new_path: delta.new_file().path().map(Path::to_path_buf),
+                change: delta.status(),
+                additions,
+                deletions,
+                binary: delta.flags().is_binary(),
+            });
+        }
+        let mut patch = String::new();
+        diff.print(DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin());
+            }
+            patch.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })?;
+        Ok(Self { files, patch })
+    }
+}
+
+// --- Log and branches ---
+
+#[derive(Debug, Clone)]
+struct CommitInfo {
+    id: Oid,
+    summary: String,
+    message: String,
+    author: String,
+    email: String,
+    /// Seconds since the Unix epoch, and the author's UTC offset in minutes.
+    time: i64,
+    offset_minutes: i32,
+    parents: Vec<Oid>,
+}
+
+impl CommitInfo {
+    fn from_commit(commit: &git2::Commit<'_>) -> Self {
+        let author = commit.author();
+        CommitInfo {
+            id: commit.id(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            message: commit.message().unwrap_or_default().to_string(),
+            author: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+            time: author.when().seconds(),
+            offset_minutes: author.when().offset_minutes(),
+            parents: commit.parent_ids().collect(),
+        }
+    }
+
+    /// The 7-character abbreviation `git log --oneline` shows.
+    fn short_id(&self) -> String {
+        self.id.to_string()[..7].to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BranchInfo {
+    name: String,
+    is_head: bool,
+    target: Option<Oid>,
+    upstream: Option<String>,
+    /// Commits ahead of and behind the upstream, when there is one.
+    ahead_behind: Option<(usize, usize)>,
+}
+
+// --- Remotes and authentication ---
+
+/// How to answer libgit2's credential requests for fetch and clone.
+#[derive(Clone, Default)]
+enum Auth {
+    /// The SSH agent for SSH URLs, the configured git credential helper for HTTPS.
+    #[default]
+    Default,
+    SshKey { private_key: PathBuf, passphrase: Option<String> },
+    /// HTTPS with a personal access token. GitHub accepts any username with a token;
+    /// GitLab expects `oauth2`.
+    Token { username: String, token: String },
+}
+
+// Keep secrets out of logs (see `secrets.rs` for a `Secret<T>` wrapper).
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Auth::Default => write!(f, "Auth::Default"),
+            Auth::SshKey { private_key, .. } => write!(f, "Auth::SshKey({})", private_key.display()),
+            Auth::Token { username, .. } => write!(f, "Auth::Token({}, ***)", username),
+        }
+    }
+}
+
+const MAX_AUTH_ATTEMPTS: u32 = 3;
+
+/// Progress of a clone or fetch.
+#[derive(Debug, Clone, Copy, Default)]
+struct TransferProgress {
+    received_objects: usize,
+    total_objects: usize,
+    indexed_deltas: usize,
+    total_deltas: usize,
+    received_bytes: usize,
+}
+
+fn remote_callbacks<'a>(auth: &'a Auth, progress: &'a mut dyn FnMut(TransferProgress)) -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+    let mut attempts = 0;
+    callbacks.credentials(move |url, username_from_url, allowed| {
+        attempts += 1;
+        if attempts > MAX_AUTH_ATTEMPTS {
+            return Err(git2::Error::from_str(&format!("authentication to {} failed after {} attempts", url, MAX_AUTH_ATTEMPTS)));
+        }
+        let username = username_from_url.unwrap_or("git");
+        match auth {
+            Auth::Default if allowed.contains(CredentialType::SSH_KEY) => Cred::ssh_key_from_agent(username),
+            Auth::Default if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) => {
+                Cred::credential_helper(&git2::Config::open_default()?, url, username_from_url)
+            }
+            Auth::Default => Cred::default(),
+            Auth::SshKey { private_key, passphrase } => Cred::ssh_key(username, None, private_key, passphrase.as_deref()),
+            Auth::Token { username, token } => Cred::userpass_plaintext(username, token),
+        }
+    });
+    callbacks.transfer_progress(move |stats| {
+        progress(TransferProgress {
+            received_objects: stats.received_objects(),
+            total_objects: stats.total_objects(),
+            indexed_deltas: stats.indexed_deltas(),
+            total_deltas: stats.total_deltas(),
+            received_bytes: stats.received_bytes(),
+        });
+        true
+    });
+    callbacks
+}
+
+#[derive(Debug, Default)]
+struct CloneOptions {
+    /// Check out this branch instead of the remote's default.
+    branch: Option<String>,
+    /// Shallow clone with this many commits of history.
+    depth: Option<i32>,
+    auth: Auth,
+}
+
+/// Clones `url` into `dest`, which must not exist or be empty.
+///
+/// # Arguments
+///
+/// * `url` - An HTTPS, SSH (`git@host:org/repo.git`), or local path URL.
+/// * `dest` - The directory to create.
+/// * `options` - Branch, shallow depth, and credentials.
+/// * `progress` - Called as objects arrive; return quickly, it runs on the transfer thread.
+fn clone_repo(url: &str, dest: &Path, options: &CloneOptions, progress: &mut dyn FnMut(TransferProgress)) -> Result<GitRepo> {
+    let mut fetch = FetchOptions::new();
+    fetch.remote_callbacks(remote_callbacks(&options.auth, progress));
+    if let Some(depth) = options.depth {
+        fetch.depth(depth);
+    }
+    let mut builder = RepoBuilder::new();
+    builder.fetch_options(fetch);
+    if let Some(branch) = &options.branch {
+        builder.branch(branch);
+    }
+    Ok(GitRepo { repo: builder.clone(url, dest)? })
+}
+
+// --- Repository ---
+
+struct GitRepo {
+    repo: Repository,
+}
+
+impl GitRepo {
+    /// Opens the repository containing `path`, searching parent directories like `git` does.
+    fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self { repo: Repository::discover(path)? })
+    }
+
+    fn init(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self { repo: Repository::init(path)? })
+    }
+
+    /// The working tree root; `None` for a bare repository.
+    fn workdir(&self) -> Option<&Path> {
+        self.repo.workdir()
+    }
+
+    /// `git status`, including untracked files but not ignored ones.
+    fn status(&self) -> Result<Vec<StatusEntry>> {
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true).renames_head_to_index(true);
+        let statuses = self.repo.statuses(Some(&mut options))?;
+        Ok(statuses
+            .iter()
+            .map(|entry| StatusEntry {
+                path: entry.path().unwrap_or_default().to_string(),
+                staged: staged_change(entry.status()),
+                unstaged: unstaged_change(entry.status()),
+                conflicted: entry.status().contains(Status::CONFLICTED),
+            })
+            .collect())
+    }
+
+    fn is_clean(&self) -> Result<bool> {
+        Ok(self.status()?.is_empty())
+    }
+
+    /// Fails with `GitError::Dirty` if anything is uncommitted, e.g. before a release script
+    /// tags or switches branches.
+    fn ensure_clean(&self) -> Result<()> {
+        match self.status()?.len() {
+            0 => Ok(()),
+            n => Err(GitError::Dirty(n)),
+        }
+    }
+
+    fn head_tree(&self) -> Result<Option<git2::Tree<'_>>> {
+        match self.repo.head() {
+            Ok(head) => Ok(Some(head.peel_to_tree()?)),
+            Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn diff(&self, target: DiffTarget<'_>) -> Result<DiffSummary> {
+        let mut options = DiffOptions::new();
+        options.include_untracked(false);
+        let mut diff = match target {
+            DiffTarget::Unstaged => self.repo.diff_index_to_workdir(None, Some(&mut options))?,
+            DiffTarget::Staged => self.repo.diff_tree_to_index(self.head_tree()?.as_ref(), None, Some(&mut options))?,
+            DiffTarget::SinceHead => self.repo.diff_tree_to_workdir_with_index(self.head_tree()?.as_ref(), Some(&mut options))?,
+            DiffTarget::Revisions(from, to) => {
+                let from = self.repo.revparse_single(from)?.peel_to_tree()?;
+                let to = self.repo.revparse_single(to)?.peel_to_tree()?;
+                self.repo.diff_tree_to_tree(Some(&from), Some(&to), Some(&mut options))?
+            }
+        };
+        DiffSummary::from_diff(&mut diff)
+    }
+
+    /// `git log`: the newest `limit` commits reachable from `from` (`None` for HEAD).
+    fn log(&self, from: Option<&str>, limit: usize) -> Result<Vec<CommitInfo>> {
+        let mut walk = self.repo.revwalk()?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        match from {
+            Some(rev) => walk.push(self.repo.revparse_single(rev)?.peel_to_commit()?.id())?,
+            None if self.head_tree()?.is_none() => return Ok(Vec::new()),
+            None => walk.push_head()?,
+        }
+        walk.take(limit).map(|id| Ok(CommitInfo::from_commit(&self.repo.find_commit(id?)?))).collect()
+    }
+
+    /// `git add -A`: stages new, modified, and deleted files matching `pathspecs`
+    /// (`&["."]` for everything), honoring `.gitignore`.
+    fn add_all(&self, pathspecs: &[&str]) -> Result<()> {
+        let mut index = self.repo.index()?;
+        index.add_all(pathspecs, IndexAddOption::DEFAULT, None)?;
+        index.update_all(pathspecs, None)?;
+        index.write()?;
+        Ok(())
+    }
+
+    /// Commits the index on the current branch.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The commit message.
+    /// * `author` - `(name, email)`, or `None` to use `user.name` / `user.email` from git config.
+    ///
+    /// # Returns
+    ///
+    /// The new commit's id, or `GitError::NothingToCommit` if the index matches HEAD.
+    fn commit(&self, message: &str, author: Option<(&str, &str)>) -> Result<Oid> {
+        let signature = match author {
+            Some((name, email)) => Signature::now(name, email)?,
+            None => self.repo.signature()?,
+        };
+        let mut index = self.repo.index()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+        let parent = match self.repo.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => None,
+            Err(e) => return Err(e.into()),
+        };
+        if parent.as_ref().is_some_and(|p| p.tree_id() == tree.id()) {
+            return Err(GitError::NothingToCommit);
+        }
+        let parents: Vec<&git2::Commit<'_>> = parent.iter().collect();
+        Ok(self.repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)?)
+    }
+
+    /// The checked-out branch, or `None` when HEAD is detached.
+    fn current_branch(&self) -> Result<Option<String>> {
+        match self.repo.head() {
+            Ok(head) if head.is_branch() => Ok(head.shorthand().map(str::to_string)),
+            Ok(_) => Ok(None),
+            // A fresh repository: HEAD names a branch that has no commits yet.
+            Err(e) if e.code() == ErrorCode::UnbornBranch => {
+                let head = self.repo.find_reference("HEAD")?;
+                Ok(head.symbolic_target().and_then(|t| t.strip_prefix("refs/heads/")).map(str::to_string))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn branches(&self) -> Result<Vec<BranchInfo>> {
+        let mut branches = Vec::new();
+        for branch in self.repo.branches(Some(BranchType::Local))? {
+            let (branch, _) = branch?;
+            let target = branch.get().target();
+            let upstream = branch.upstream().ok();
+            let ahead_behind = match (target, upstream.as_ref().and_then(|u| u.get().target())) {
+                (Some(local), Some(remote)) => Some(self.repo.graph_ahead_behind(local, remote)?),
+                _ => None,
+            };
+            branches.push(BranchInfo {
+                name: branch.name()?.unwrap_or_default().to_string(),
+                is_head: branch.is_head(),
+                target,
+                upstream: upstream.and_then(|u| u.name().ok().flatten().map(str::to_string)),
+                ahead_behind,
+            });
+        }
+        Ok(branches)
+    }
+
+    /// `git branch <name> <from>`.
+    fn create_branch(&self, name: &str, from: &str) -> Result<()> {
+        let commit = self.repo.revparse_single(from)?.peel_to_commit()?;
+        self.repo.branch(name, &commit, false)?;
+        Ok(())
+    }
+
+    /// `git switch <name>`. Refuses if the switch would overwrite local changes.
+    fn checkout_branch(&self, name: &str) -> Result<()> {
+        let refname = format!("refs/heads/{}", name);
+        let target = self.repo.revparse_single(&refname)?;
+        self.repo.checkout_tree(&target, Some(CheckoutBuilder::new().safe()))?;
+        self.repo.set_head(&refname)?;
+        Ok(())
+    }
+
+    /// Fetches from a configured remote (e.g. `origin`) using its refspecs.
+    ///
+    /// # Returns
+    ///
+    /// The final transfer statistics; zero objects means nothing was new.
+    fn fetch(&self, remote: &str, auth: &Auth, progress: &mut dyn FnMut(TransferProgress)) -> Result<TransferProgress> {
+        let mut remote = self.repo.find_remote(remote)?;
+        let mut options = FetchOptions::new();
+        options.remote_callbacks(remote_callbacks(auth, progress));
+        remote.fetch::<&str>(&[], Some(&mut options), None)?;
+        let stats = remote.stats();
+        Ok(TransferProgress {
+            received_objects: stats.received_objects(),
+            total_objects: stats.total_objects(),
+            indexed_deltas: stats.indexed_deltas(),
+            total_deltas: stats.total_deltas(),
+            received_bytes: stats.received_bytes(),
+        })
+    }
+}
+
+// Example Usage
+/*
+use std::fs;
+
+fn main() -> Result<()> {
+    let base = std::env::temp_dir().join(format!("git-demo-{}", std::process::id()));
+    let origin_dir = base.join("origin");
+    fs::create_dir_all(&origin_dir).unwrap();
+    let me = Some(("Demo Bot", "bot@example.com"));
+
+    // A repository with two commits.
+    let origin = GitRepo::init(&origin_dir)?;
+    println!("new repo on branch {:?}, clean: {}", origin.current_branch()?, origin.is_clean()?);
+    fs::write(origin_dir.join("README.md"), "# Demo\n").unwrap();
+    fs::write(origin_dir.join("main.rs"), "fn main() {}\n").unwrap();
+    origin.add_all(&["."])?;
+    let first = origin.commit("Initial commit", me)?;
+    fs::write(origin_dir.join("main.rs"), "fn main() {\n    println!(\"hi\");\n}\n").unwrap();
+    fs::write(origin_dir.join("notes.txt"), "todo\n").unwrap();
+    for entry in origin.status()? {
+        println!("  {:<10} staged: {:<9} unstaged: {}", entry.path, format!("{:?}", entry.staged), format!("{:?}", entry.unstaged));
+    }
+    println!("ensure_clean: {:?}", origin.ensure_clean().map_err(|e| e.to_string()));
+    let unstaged = origin.diff(DiffTarget::Unstaged)?;
+    for file in &unstaged.files {
+        println!("  {:?} {:?} +{} -{}", file.change, file.new_path, file.additions, file.deletions);
+    }
+    print!("{}", unstaged.patch);
+    origin.add_all(&["."])?;
+    origin.commit("Say hi\n\nAlso add notes.", me)?;
+    println!("commit again: {:?}", origin.commit("Nothing", me).map_err(|e| e.to_string()));
+
+    let branch = origin.current_branch()?.unwrap();
+    for commit in origin.log(None, 10)? {
+        println!("  {} {} <{}> {}", commit.short_id(), commit.author, commit.email, commit.summary);
+    }
+    let changed = origin.diff(DiffTarget::Revisions(&first.to_string(), "HEAD"))?;
+    println!("{} file(s) changed since the first commit", changed.files.len());
+
+    // Clone it (a file:// URL here; an https:// or git@ URL works the same) and watch progress.
+    let mut last = TransferProgress::default();
+    let url = format!("file://{}", origin_dir.display());
+    let clone = clone_repo(&url, &base.join("clone"), &CloneOptions::default(), &mut |p| last = p)?;
+    println!("cloned {} objects into {:?}", last.received_objects, clone.workdir());
+
+    // New upstream work, then fetch it and compare.
+    fs::write(origin_dir.join("CHANGELOG.md"), "- hi\n").unwrap();
+    origin.add_all(&["."])?;
+    origin.commit("Add changelog", me)?;
+    let stats = clone.fetch("origin", &Auth::Default, &mut |_| {})?;
+    println!("fetched {} objects", stats.received_objects);
+    for b in clone.branches()? {
+        println!("  branch {} head={} upstream={:?} ahead/behind={:?}", b.name, b.is_head, b.upstream, b.ahead_behind);
+    }
+
+    clone.create_branch("feature/x", "HEAD~1")?;
+    clone.checkout_branch("feature/x")?;
+    println!("clone now on {:?} (was {})", clone.current_branch()?, branch);
+
+    fs::remove_dir_all(&base).unwrap();
+    Ok(())
+}
+*/
@@ -0,0 +1,26 @@
+// build.rs for the gRPC example (place next to Cargo.toml).
+//
+// Cargo.toml:
+// [dependencies]
+// tonic = "0.12"
+// prost = "0.13"
+// tokio = { version = "1", features = ["full"] }
+// tokio-stream = "0.1"
+//
+// [build-dependencies]
+// tonic-build = "0.12"
+//
+// tonic-build shells out to `protoc`. Install it (`apt install protobuf-compiler`,
+// `brew install protobuf`) or point the PROTOC environment variable at a binary.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure()
+        // Generate both halves; a client-only crate can set `build_server(false)`.
+        .build_server(true)
+        .build_client(true)
+        .compile_protos(&["proto/jobs.proto"], &["proto"])?;
+
+    // Re-run codegen only when the schema changes.
+    println!("cargo:rerun-if-changed=proto/jobs.proto");
+    Ok(())
+}
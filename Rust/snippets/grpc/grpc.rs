@@ -0,0 +1,308 @@
+// Note: This example is a small crate: put `build.rs` next to Cargo.toml, `proto/jobs.proto`
+// in a `proto/` folder, and this file at `src/main.rs`. Dependencies are listed in build.rs.
+//
+// It is the strongly-typed RPC counterpart to the HTTP (`http_get_request.rs`) and
+// WebSocket (`websocket_client_tungstenite.rs`) snippets: the schema is shared, so client
+// and server cannot drift apart, and calls get deadlines and status codes for free.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, Endpoint, Server};
+use tonic::{Code, Request, Response, Status};
+
+/// Code generated from `proto/jobs.proto` by build.rs.
+pub mod jobs {
+    tonic::include_proto!("jobs.v1");
+}
+
+use jobs::job_service_client::JobServiceClient;
+use jobs::job_service_server::{JobService, JobServiceServer};
+use jobs::{GetJobRequest, Job, JobState, SubmitJobRequest, SubmitJobResponse};
+
+// --- Server ---
+
+/// Identity of the authenticated caller, attached to requests by `auth_interceptor`.
+#[derive(Clone, Debug)]
+struct Caller(String);
+
+/// Server-side auth: every request must carry `authorization: Bearer <token>`.
+///
+/// Interceptors run before the handler and can reject a call with a `Status` or
+/// attach data to the request's extensions for the handler to read.
+#[allow(clippy::result_large_err)] // `Status` is large, but tonic fixes the interceptor signature
+fn auth_interceptor(tokens: Arc<HashMap<String, String>>) -> impl Interceptor + Clone {
+    move |mut request: Request<()>| {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+        let caller = tokens
+            .get(token)
+            .ok_or_else(|| Status::unauthenticated("invalid token"))?
+            .clone();
+        request.extensions_mut().insert(Caller(caller));
+        Ok(request)
+    }
+}
+
+/// In-memory job service. Each job's state lives in a `watch` channel so `WatchJob`
+/// streams can follow it.
+#[derive(Default)]
+struct JobServiceImpl {
+    jobs: Mutex<HashMap<String, watch::Sender<Job>>>,
+    next_id: AtomicU64,
+}
+
+impl JobServiceImpl {
+    #[allow(clippy::result_large_err)]
+    fn job_sender(&self, job_id: &str) -> Result<watch::Sender<Job>, Status> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .get(job_id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("job {} not found", job_id)))
+    }
+}
+
+fn is_finished(job: &Job) -> bool {
+    matches!(job.state(), JobState::Succeeded | JobState::Failed)
+}
+
+/// Simulates a worker advancing a job to completion.
+async fn simulate_job(tx: watch::Sender<Job>) {
+    for progress in [0u32, 25, 50, 75, 100] {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        tx.send_modify(|job| {
+            job.progress_percent = progress;
+            job.set_state(if progress == 100 { JobState::Succeeded } else { JobState::Running });
+        });
+    }
+}
+
+#[tonic::async_trait]
+impl JobService for JobServiceImpl {
+    async fn submit_job(&self, request: Request<SubmitJobRequest>) -> Result<Response<SubmitJobResponse>, Status> {
+        let caller = request.extensions().get::<Caller>().map(|c| c.0.clone()).unwrap_or_default();
+        let req = request.into_inner();
+        if req.name.trim().is_empty() {
+            return Err(Status::invalid_argument("name must not be empty"));
+        }
+
+        let job_id = format!("job-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let mut job = Job {
+            job_id: job_id.clone(),
+            name: req.name,
+            progress_percent: 0,
+            ..Default::default()
+        };
+        job.set_state(JobState::Queued);
+        println!("[server] {} submitted {} ({})", caller, job.name, job_id);
+
+        let (tx, _rx) = watch::channel(job);
+        self.jobs.lock().unwrap().insert(job_id.clone(), tx.clone());
+        tokio::spawn(simulate_job(tx));
+        Ok(Response::new(SubmitJobResponse { job_id }))
+    }
+
+    async fn get_job(&self, request: Request<GetJobRequest>) -> Result<Response<Job>, Status> {
+        let job = self.job_sender(&request.get_ref().job_id)?.borrow().clone();
+        Ok(Response::new(job))
+    }
+
+    type WatchJobStream = ReceiverStream<Result<Job, Status>>;
+
+    async fn watch_job(&self, request: Request<GetJobRequest>) -> Result<Response<Self::WatchJobStream>, Status> {
+        let mut updates = self.job_sender(&request.get_ref().job_id)?.subscribe();
+        let (tx, rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            loop {
+                let job = updates.borrow_and_update().clone();
+                let done = is_finished(&job);
+                // A send error means the client went away; stop following.
+                if tx.send(Ok(job)).await.is_err() || done {
+                    break;
+                }
+                if updates.changed().await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Serves the job service until `shutdown` resolves, then drains in-flight calls.
+///
+/// # Arguments
+///
+/// * `addr` - Address to bind, e.g. `127.0.0.1:50051`.
+/// * `tokens` - Map of accepted bearer tokens to caller names.
+/// * `shutdown` - Future that triggers graceful shutdown (e.g. Ctrl-C).
+async fn run_server(
+    addr: SocketAddr,
+    tokens: HashMap<String, String>,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), tonic::transport::Error> {
+    let service = JobServiceServer::with_interceptor(JobServiceImpl::default(), auth_interceptor(Arc::new(tokens)));
+    Server::builder()
+        .timeout(Duration::from_secs(30)) // Upper bound even if the client sets no deadline
+        .add_service(service)
+        .serve_with_shutdown(addr, shutdown)
+        .await
+}
+
+// --- Client ---
+
+/// Client-side interceptor that attaches the bearer token to every call.
+#[derive(Clone)]
+struct BearerAuth {
+    header: MetadataValue<Ascii>,
+}
+
+impl Interceptor for BearerAuth {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request.metadata_mut().insert("authorization", self.header.clone());
+        Ok(request)
+    }
+}
+
+type JobClient = JobServiceClient<InterceptedService<Channel, BearerAuth>>;
+
+/// Builds an authenticated client.
+///
+/// The channel connects lazily and reconnects on its own after failures, so it can be
+/// created before the server is up and cloned freely (clones share the connection).
+fn connect_client(url: &str, token: &str) -> Result<JobClient, Box<dyn std::error::Error>> {
+    let channel = Endpoint::from_shared(url.to_string())?
+        .connect_timeout(Duration::from_secs(5))
+        .tcp_keepalive(Some(Duration::from_secs(30)))
+        .http2_keep_alive_interval(Duration::from_secs(30))
+        .connect_lazy();
+    let header = format!("Bearer {}", token).parse()?;
+    Ok(JobServiceClient::with_interceptor(channel, BearerAuth { header }))
+}
+
+/// Deadline and retry settings for client calls.
+#[derive(Clone, Debug)]
+struct RetryPolicy {
+    max_attempts: u32,
+    /// Deadline for each attempt; sent to the server as the `grpc-timeout` header.
+    per_attempt_timeout: Duration,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            per_attempt_timeout: Duration::from_secs(2),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wraps a message in a request carrying this policy's per-attempt deadline.
+    fn request<T>(&self, message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.set_timeout(self.per_attempt_timeout);
+        request
+    }
+}
+
+/// Status codes worth retrying: the server was unreachable, overloaded, or too slow.
+/// Everything else (invalid argument, not found, unauthenticated, ...) fails fast.
+fn is_retryable(code: Code) -> bool {
+    matches!(code, Code::Unavailable | Code::DeadlineExceeded | Code::ResourceExhausted)
+}
+
+/// Calls `call` until it succeeds, fails with a non-retryable status, or runs out of
+/// attempts, with exponential backoff between attempts.
+///
+/// Only retry idempotent RPCs (like `GetJob`): a `DeadlineExceeded` on `SubmitJob` may mean
+/// the job *was* created and the response was lost.
+async fn with_retry<T, F, Fut>(policy: &RetryPolicy, mut call: F) -> Result<T, Status>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Response<T>, Status>>,
+{
+    let mut backoff = policy.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Ok(response) => return Ok(response.into_inner()),
+            Err(status) if attempt < policy.max_attempts && is_retryable(status.code()) => {
+                eprintln!("[client] attempt {} failed ({:?}); retrying in {:?}", attempt, status.code(), backoff);
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(policy.max_backoff);
+                attempt += 1;
+            }
+            Err(status) => return Err(status),
+        }
+    }
+}
+
+// Example Usage (runs the server and client in one process)
+/*
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let addr: SocketAddr = "127.0.0.1:50051".parse()?;
+    let tokens = HashMap::from([("s3cret".to_string(), "reporting-service".to_string())]);
+
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let client = connect_client("http://127.0.0.1:50051", "s3cret")?;
+    let policy = RetryPolicy::default();
+
+    // Start the server slightly late: the client's first attempts fail with
+    // `Unavailable` and are retried. `GetJob` is safe to repeat; once the server is up,
+    // the unknown id fails immediately with the non-retryable `NotFound`.
+    let server = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        run_server(addr, tokens, async { stop_rx.await.ok(); }).await
+    });
+
+    let missing = with_retry(&policy, || {
+        let mut client = client.clone();
+        let request = policy.request(GetJobRequest { job_id: "job-999".into() });
+        async move { client.get_job(request).await }
+    })
+    .await;
+    println!("[client] missing job -> {:?}", missing.map_err(|s| s.code()));
+
+    // `SubmitJob` is sent once: a retry after a lost response would create a second job.
+    let mut params = HashMap::new();
+    params.insert("date".to_string(), "2024-05-01".to_string());
+    let request = policy.request(SubmitJobRequest { name: "daily-report".into(), params });
+    let job_id = client.clone().submit_job(request).await?.into_inner().job_id;
+    println!("[client] submitted {}", job_id);
+
+    // Server streaming: follow the job until it finishes.
+    let mut stream = client.clone().watch_job(GetJobRequest { job_id: job_id.clone() }).await?.into_inner();
+    while let Some(job) = stream.message().await? {
+        println!("[client] {} is {:?} ({}%)", job.job_id, job.state(), job.progress_percent);
+    }
+
+    // Wrong token is rejected by the interceptor.
+    let mut intruder = connect_client("http://127.0.0.1:50051", "wrong")?;
+    let denied = intruder.get_job(GetJobRequest { job_id }).await;
+    println!("[client] bad token -> {:?}", denied.map_err(|s| s.code()));
+
+    let _ = stop_tx.send(());
+    server.await??;
+    Ok(())
+}
+*/
@@ -0,0 +1,384 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+//
+// A process-wide health check registry:
+// * Subsystems (a database pool, a WebSocket connection, a scheduler) register named probes
+//   when they start: async closures returning `Health::up()`, `degraded(..)`, or `down(..)`.
+// * Each probe counts toward liveness ("restart me if this fails"), readiness ("don't send me
+//   traffic yet"), or both. Keep liveness probes to things a restart fixes, such as a stuck
+//   scheduler; a database outage belongs in readiness, or every replica restarts at once.
+// * `check` runs the matching probes concurrently, each with its own timeout, and aggregates
+//   them: any critical probe down => down; anything else not up => degraded.
+// * The report serializes to JSON. `serve_health` answers `GET /health/live`,
+//   `/health/ready`, and `/health` (200, or 503 when down), and `print_report` formats it for a
+//   `health` subcommand.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinSet;
+
+// --- Probe results ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Up,
+    Degraded,
+    Down,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Status::Up => "up",
+            Status::Degraded => "degraded",
+            Status::Down => "down",
+        })
+    }
+}
+
+/// What one probe reports.
+#[derive(Debug, Clone, Serialize)]
+struct Health {
+    status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl Health {
+    fn up() -> Self {
+        Self { status: Status::Up, detail: None }
+    }
+
+    /// Working, but worth a look: a nearly full pool, a lagging replica.
+    fn degraded(detail: impl Into<String>) -> Self {
+        Self { status: Status::Degraded, detail: Some(detail.into()) }
+    }
+
+    fn down(detail: impl Into<String>) -> Self {
+        Self { status: Status::Down, detail: Some(detail.into()) }
+    }
+
+    /// Adds a detail (e.g. `"12/20 connections in use"`) to a healthy result.
+    fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+// --- Registry ---
+
+/// Which endpoint a probe counts toward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Liveness,
+    Readiness,
+    Both,
+}
+
+impl Kind {
+    fn includes(self, requested: Kind) -> bool {
+        requested == Kind::Both || self == Kind::Both || self == requested
+    }
+}
+
+type ProbeFuture = Pin<Box<dyn Future<Output = Health> + Send>>;
+
+#[derive(Clone)]
+struct Probe {
+    name: String,
+    kind: Kind,
+    /// When false, this probe being down only degrades the aggregate status.
+    critical: bool,
+    timeout: Duration,
+    run: Arc<dyn Fn() -> ProbeFuture + Send + Sync>,
+}
+
+/// Registered probes. Cheap to clone; clones share the same set.
+#[derive(Clone, Default)]
+struct HealthRegistry {
+    probes: Arc<RwLock<Vec<Probe>>>,
+}
+
+/// The registry shared by the whole process.
+fn global() -> &'static HealthRegistry {
+    static REGISTRY: OnceLock<HealthRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(HealthRegistry::default)
+}
+
+impl HealthRegistry {
+    /// Registers a probe, replacing any probe with the same name (e.g. after a reconnect).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Key in the report, e.g. `"database"`.
+    /// * `kind` - Whether the probe counts toward liveness, readiness, or both.
+    /// * `critical` - Whether the probe being down takes the whole service down.
+    /// * `timeout` - A probe that takes longer is reported down.
+    /// * `probe` - Called on every check. Capture an `Arc` or a cheap handle, not a lock guard.
+    fn register<F, Fut>(&self, name: &str, kind: Kind, critical: bool, timeout: Duration, probe: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Health> + Send + 'static,
+    {
+        let probe = Probe {
+            name: name.to_string(),
+            kind,
+            critical,
+            timeout,
+            run: Arc::new(move || Box::pin(probe()) as ProbeFuture),
+        };
+        let mut probes = self.probes.write().unwrap_or_else(|e| e.into_inner());
+        probes.retain(|p| p.name != name);
+        probes.push(probe);
+    }
+
+    /// Removes a probe, e.g. when its subsystem shuts down. Returns whether it existed.
+    fn deregister(&self, name: &str) -> bool {
+        let mut probes = self.probes.write().unwrap_or_else(|e| e.into_inner());
+        let before = probes.len();
+        probes.retain(|p| p.name != name);
+        probes.len() != before
+    }
+
+    /// Runs every probe of the requested kind concurrently and aggregates the results.
+    ///
+    /// A probe that times out or panics is reported down. Must be called inside a tokio runtime.
+    async fn check(&self, kind: Kind) -> HealthReport {
+        let started = Instant::now();
+        let probes: Vec<Probe> = self
+            .probes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|p| p.kind.includes(kind))
+            .cloned()
+            .collect();
+
+        let mut tasks = JoinSet::new();
+        for probe in &probes {
+            let (run, timeout) = (Arc::clone(&probe.run), probe.timeout);
+            let name = probe.name.clone();
+            tasks.spawn(async move {
+                let probe_started = Instant::now();
+                let health = match tokio::time::timeout(timeout, run()).await {
+                    Ok(health) => health,
+                    Err(_) => Health::down(format!("timed out after {:?}", timeout)),
+                };
+                (name, health, probe_started.elapsed())
+            });
+        }
+
+        let mut checks = BTreeMap::new();
+        while let Some(joined) = tasks.join_next().await {
+            // A panicking probe is lost here; the loop below reports it by name.
+            if let Ok((name, health, elapsed)) = joined {
+                checks.insert(name, (health, elapsed));
+            }
+        }
+
+        let mut report = BTreeMap::new();
+        let mut status = Status::Up;
+        for probe in probes {
+            let (health, elapsed) = checks
+                .remove(&probe.name)
+                .unwrap_or_else(|| (Health::down("probe panicked"), Duration::ZERO));
+            status = status.max(match (health.status, probe.critical) {
+                (Status::Down, false) => Status::Degraded,
+                (probe_status, _) => probe_status,
+            });
+            let check = CheckResult { health, critical: probe.critical, duration_ms: elapsed.as_millis() as u64 };
+            report.insert(probe.name, check);
+        }
+        HealthReport {
+            status,
+            checked_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            duration_ms: started.elapsed().as_millis() as u64,
+            checks: report,
+        }
+    }
+}
+
+// --- Reports ---
+
+#[derive(Debug, Clone, Serialize)]
+struct CheckResult {
+    #[serde(flatten)]
+    health: Health,
+    critical: bool,
+    duration_ms: u64,
+}
+
+/// The aggregate result, serialized as the response body.
+#[derive(Debug, Clone, Serialize)]
+struct HealthReport {
+    status: Status,
+    /// Unix seconds.
+    checked_at: u64,
+    duration_ms: u64,
+    checks: BTreeMap<String, CheckResult>,
+}
+
+impl HealthReport {
+    /// 503 only when down: a degraded service still takes traffic.
+    fn http_status(&self) -> u16 {
+        if self.status == Status::Down {
+            503
+        } else {
+            200
+        }
+    }
+
+    /// Exit code for a `health` subcommand: 0 up, 1 down, 2 degraded.
+    fn exit_code(&self) -> i32 {
+        match self.status {
+            Status::Up => 0,
+            Status::Down => 1,
+            Status::Degraded => 2,
+        }
+    }
+}
+
+/// Prints one line per check, aligned, with the aggregate status first.
+fn print_report(report: &HealthReport) {
+    println!("status: {} ({} ms)", report.status, report.duration_ms);
+    let width = report.checks.keys().map(String::len).max().unwrap_or(0);
+    for (name, check) in &report.checks {
+        let detail = check.health.detail.as_deref().unwrap_or("");
+        let optional = if check.critical { "" } else { " (non-critical)" };
+        let line = format!("  {:<width$}  {:<8}  {:>5} ms  {}{}", name, check.health.status, check.duration_ms, detail, optional);
+        println!("{}", line.trim_end());
+    }
+}
+
+// --- HTTP endpoint ---
+
+/// Serves `/health/live`, `/health/ready`, and `/health` (every probe) as JSON until the task
+/// is dropped. Minimal HTTP/1.1, one request per connection; mount the same three routes
+/// in your web framework instead if the service already has one.
+///
+/// # Arguments
+///
+/// * `listener` - A bound listener, e.g. on a separate admin port.
+/// * `registry` - Usually `global().clone()`.
+async fn serve_health(listener: TcpListener, registry: HealthRegistry) -> std::io::Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let registry = registry.clone();
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 1024];
+            let n = match stream.read(&mut buffer).await {
+                Ok(n) => n,
+                Err(_) => return,
+            };
+            let request = String::from_utf8_lossy(&buffer[..n]);
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            let (status, body) = match path {
+                "/health/live" | "/health/ready" | "/health" => {
+                    let kind = match path {
+                        "/health/live" => Kind::Liveness,
+                        "/health/ready" => Kind::Readiness,
+                        _ => Kind::Both,
+                    };
+                    let report = registry.check(kind).await;
+                    let body = serde_json::to_string_pretty(&report).unwrap_or_default();
+                    (report.http_status(), body)
+                }
+                _ => (404, r#"{"error":"not found"}"#.to_string()),
+            };
+            let reason = match status {
+                200 => "OK",
+                503 => "Service Unavailable",
+                _ => "Not Found",
+            };
+            let response = format!(
+                "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nCache-Control: no-store\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                reason,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+// Example Usage
+/*
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let registry = global();
+
+    // A database pool: readiness only, so an outage stops traffic without restarts.
+    let db_up = Arc::new(AtomicBool::new(true));
+    let db = db_up.clone();
+    registry.register("database", Kind::Readiness, true, Duration::from_millis(200), move || {
+        let up = db.load(Ordering::Relaxed);
+        async move {
+            tokio::time::sleep(Duration::from_millis(5)).await; // SELECT 1
+            if up { Health::up().with_detail("12/20 connections in use") } else { Health::down("connection refused") }
+        }
+    });
+
+    // A scheduler heartbeat: liveness, because a restart fixes a stuck loop.
+    let last_tick = Arc::new(AtomicU64::new(0));
+    let ticks = last_tick.clone();
+    registry.register("scheduler", Kind::Liveness, true, Duration::from_millis(50), move || {
+        let age = ticks.load(Ordering::Relaxed);
+        async move {
+            match age {
+                0..=60 => Health::up(),
+                _ => Health::down(format!("no tick for {}s", age)),
+            }
+        }
+    });
+
+    // A WebSocket feed nobody depends on critically, and a probe that hangs.
+    registry.register("price_feed", Kind::Readiness, false, Duration::from_millis(50), || async {
+        Health::degraded("reconnecting (attempt 3)")
+    });
+    registry.register("search_index", Kind::Readiness, false, Duration::from_millis(50), || async {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        Health::up()
+    });
+
+    // `mytool health` prints and exits with the status code.
+    let report = registry.check(Kind::Both).await;
+    print_report(&report);
+    println!("exit code: {}", report.exit_code());
+
+    // Over HTTP, as a load balancer or Kubernetes would see it.
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(serve_health(listener, registry.clone()));
+    let get = |path: &'static str| async move {
+        let mut stream = tokio::net::TcpStream::connect(addr).await?;
+        stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok::<_, std::io::Error>(response)
+    };
+    let live = get("/health/live").await?;
+    println!("\n/health/live -> {}", live.lines().next().unwrap_or(""));
+
+    db_up.store(false, Ordering::Relaxed);
+    let ready = get("/health/ready").await?;
+    println!("/health/ready -> {}", ready.lines().next().unwrap_or(""));
+    println!("{}", ready.split("\r\n\r\n").nth(1).unwrap_or(""));
+
+    registry.deregister("search_index");
+    Ok(())
+}
+*/
@@ -0,0 +1,281 @@
+// Note: This example requires adding the `thiserror` crate to your Cargo.toml:
+// [dependencies]
+// thiserror = "1.0"
+//
+// Numbers formatted for people (progress bars, download status, CLI tables, log lines) and
+// the inverse parsers for values people type into flags and config files (`--max-size 10MB`).
+// Binary units (KiB = 1024) are used for memory and file sizes; SI units (kB = 1000) for
+// network rates and disk vendor sizes. `parse_bytes` accepts both.
+//
+// For `1h30m`-style duration parsing and `DateTime` relative times ("5 minutes ago"),
+// see `datetime.rs`.
+
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum HumanizeError {
+    #[error("Invalid size '{input}': {reason}")]
+    InvalidSize { input: String, reason: String },
+    #[error("Invalid count '{input}': {reason}")]
+    InvalidCount { input: String, reason: String },
+}
+
+type Result<T> = std::result::Result<T, HumanizeError>;
+
+const BINARY_UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+const SI_UNITS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+
+// --- Bytes ---
+
+fn format_scaled(value: u64, base: f64, units: &[&str]) -> String {
+    if (value as f64) < base {
+        return format!("{} {}", value, units[0]);
+    }
+    let mut scaled = value as f64;
+    let mut unit = 0;
+    // `- 0.5` so 1023.7 KiB rolls over to "1 MiB" instead of printing "1024 KiB".
+    while scaled >= base - 0.5 && unit < units.len() - 1 {
+        scaled /= base;
+        unit += 1;
+    }
+    // One decimal below 10 ("1.5 KiB"), none above ("512 MiB"); drop a trailing ".0".
+    let text = if scaled < 10.0 { format!("{:.1}", scaled) } else { format!("{:.0}", scaled) };
+    format!("{} {}", text.strip_suffix(".0").unwrap_or(&text), units[unit])
+}
+
+/// Formats a byte count with binary units: `1536` -> `"1.5 KiB"`, `0` -> `"0 B"`.
+fn format_bytes(bytes: u64) -> String {
+    format_scaled(bytes, 1024.0, &BINARY_UNITS)
+}
+
+/// Formats a byte count with SI units: `1_500_000` -> `"1.5 MB"`.
+fn format_bytes_si(bytes: u64) -> String {
+    format_scaled(bytes, 1000.0, &SI_UNITS)
+}
+
+/// Formats a transfer rate in SI units per second, e.g. `"12 MB/s"`.
+fn format_rate(bytes: u64, elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return "- B/s".to_string();
+    }
+    format!("{}/s", format_bytes_si((bytes as f64 / secs) as u64))
+}
+
+/// Parses a human-entered size: `"10MB"`, `"1.5 GiB"`, `"512k"`, `"4096"`.
+///
+/// Units are case-insensitive. `KiB`/`MiB`/... are powers of 1024; `kB`/`MB`/... are powers
+/// of 1000; single letters (`k`, `M`, `G`) follow the common CLI convention of 1024.
+///
+/// # Returns
+///
+/// The size in bytes, or an error naming the problem (unknown unit, negative, overflow).
+fn parse_bytes(input: &str) -> Result<u64> {
+    let invalid = |reason: &str| HumanizeError::InvalidSize {
+        input: input.to_string(),
+        reason: reason.to_string(),
+    };
+    let s = input.trim();
+    if s.starts_with('-') {
+        return Err(invalid("negative"));
+    }
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, unit) = (&s[..split], s[split..].trim());
+    if number.is_empty() {
+        return Err(invalid("expected a number"));
+    }
+    let value: f64 = number.parse().map_err(|_| invalid("not a number"))?;
+
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        "p" | "pib" => 1 << 50,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        "pb" => 1_000_000_000_000_000,
+        other => return Err(invalid(&format!("unknown unit '{}' (use B, KB, MB, GB, KiB, MiB, GiB, ...)", other))),
+    };
+    let bytes = value * multiplier as f64;
+    if bytes > u64::MAX as f64 {
+        return Err(invalid("too large"));
+    }
+    if multiplier == 1 && value.fract() != 0.0 {
+        return Err(invalid("fractional bytes"));
+    }
+    Ok(bytes.round() as u64)
+}
+
+/// `parse_bytes` with the signature clap expects:
+/// `#[arg(long, value_parser = parse_bytes_arg)] max_size: u64`.
+fn parse_bytes_arg(input: &str) -> std::result::Result<u64, String> {
+    parse_bytes(input).map_err(|e| e.to_string())
+}
+
+// --- Counts ---
+
+/// Inserts thousands separators: `1234567` -> `"1,234,567"`, `-9876` -> `"-9,876"`.
+fn format_thousands(n: i64) -> String {
+    let sign = if n < 0 { "-" } else { "" };
+    format!("{}{}", sign, group_digits(n.unsigned_abs()))
+}
+
+/// `format_thousands` for unsigned counts, which may not fit in an `i64`.
+fn group_digits(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Abbreviates large counts: `950` -> `"950"`, `1_500` -> `"1.5k"`, `2_300_000` -> `"2.3M"`.
+/// For follower counts, dashboard tiles, and narrow table columns.
+fn format_count(n: u64) -> String {
+    let units = ["", "k", "M", "B", "T"];
+    let mut scaled = n as f64;
+    let mut unit = 0;
+    while scaled >= 999.95 && unit < units.len() - 1 {
+        scaled /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        return n.to_string();
+    }
+    let text = if scaled < 10.0 { format!("{:.1}", scaled) } else { format!("{:.0}", scaled) };
+    format!("{}{}", text.strip_suffix(".0").unwrap_or(&text), units[unit])
+}
+
+/// Parses an abbreviated count: `"1.5k"` -> `1500`, `"2M"` -> `2_000_000`, `"10,000"` -> `10000`.
+fn parse_count(input: &str) -> Result<u64> {
+    let invalid = |reason: &str| HumanizeError::InvalidCount {
+        input: input.to_string(),
+        reason: reason.to_string(),
+    };
+    let s: String = input.trim().chars().filter(|&c| c != ',' && c != '_').collect();
+    let (number, multiplier) = match s.chars().last() {
+        Some('k' | 'K') => (&s[..s.len() - 1], 1e3),
+        Some('m' | 'M') => (&s[..s.len() - 1], 1e6),
+        Some('b' | 'B' | 'g' | 'G') => (&s[..s.len() - 1], 1e9),
+        Some(c) if c.is_ascii_digit() => (s.as_str(), 1.0),
+        Some(c) => return Err(invalid(&format!("unknown suffix '{}' (use k, M, B)", c))),
+        None => return Err(invalid("empty")),
+    };
+    let value: f64 = number.parse().map_err(|_| invalid("not a number"))?;
+    if value < 0.0 {
+        return Err(invalid("negative"));
+    }
+    let count = value * multiplier;
+    if count > u64::MAX as f64 {
+        return Err(invalid("too large"));
+    }
+    Ok(count.round() as u64)
+}
+
+/// English ordinal: `1` -> `"1st"`, `2` -> `"2nd"`, `11` -> `"11th"`, `23` -> `"23rd"`.
+fn ordinal(n: u64) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// `"1 file"`, `"3 files"`; pass the plural explicitly for irregular words.
+fn pluralize(count: u64, singular: &str, plural: &str) -> String {
+    format!("{} {}", group_digits(count), if count == 1 { singular } else { plural })
+}
+
+// --- Durations ---
+
+/// Formats a duration for status lines, keeping the two most significant units:
+/// `"850ms"`, `"4.2s"`, `"2m 05s"`, `"3h 20m"`, `"2d 4h"`.
+fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1_000 {
+        return format!("{}ms", millis);
+    }
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{:.1}s", duration.as_secs_f64()),
+        60..=3_599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        3_600..=86_399 => format!("{}h {:02}m", secs / 3_600, secs % 3_600 / 60),
+        _ => format!("{}d {}h", secs / 86_400, secs % 86_400 / 3_600),
+    }
+}
+
+/// A clock-style duration for progress bars and elapsed timers: `"05:07"`, `"1:02:03"`.
+fn format_clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3_600, secs % 3_600 / 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{:02}:{:02}", m, s)
+    }
+}
+
+/// Estimated time remaining, from progress so far. `None` until some progress is made, or if
+/// the estimate is too large for a `Duration`.
+fn eta(done: u64, total: u64, elapsed: Duration) -> Option<Duration> {
+    if done == 0 || done > total {
+        return None;
+    }
+    let per_unit = elapsed.as_secs_f64() / done as f64;
+    Duration::try_from_secs_f64(per_unit * (total - done) as f64).ok()
+}
+
+// Example Usage
+/*
+fn main() {
+    for bytes in [0u64, 512, 1536, 10 * 1024 * 1024, 5_368_709_120, u64::MAX] {
+        println!("{:>20} -> {:>9} | {:>8}", bytes, format_bytes(bytes), format_bytes_si(bytes));
+    }
+    println!("rate: {}", format_rate(48_000_000, Duration::from_secs(4)));
+
+    println!();
+    for input in ["10MB", "1.5 GiB", "512k", "4096", "2 tb", "1.5", "10 parsecs", "MB", "-5MB"] {
+        match parse_bytes(input) {
+            Ok(bytes) => println!("{:<10} -> {} bytes ({})", input, group_digits(bytes), format_bytes(bytes)),
+            Err(e) => println!("{:<10} -> {}", input, e),
+        }
+    }
+
+    println!();
+    for n in [7u64, 950, 1_500, 999_999, 2_300_000, 7_800_000_000] {
+        println!("{:>13} -> {:>6}  {}", group_digits(n), format_count(n), parse_count(&format_count(n)).unwrap());
+    }
+    println!("{:?} {:?}", parse_count("10,000"), parse_count("3x"));
+    println!("{}", [1, 2, 3, 4, 11, 12, 13, 21, 22, 101, 111].map(ordinal).join(" "));
+    println!("{}, {}, {}", pluralize(1, "file", "files"), pluralize(1234, "file", "files"), pluralize(2, "child", "children"));
+
+    println!();
+    for ms in [850u64, 4_200, 125_000, 12_000_000, 190_000_000] {
+        let d = Duration::from_millis(ms);
+        println!("{:>10}ms -> {:<8} {}", ms, format_duration(d), format_clock(d));
+    }
+
+    // A download status line.
+    let (done, total, elapsed) = (36_700_160u64, 104_857_600u64, Duration::from_secs(7));
+    println!(
+        "\n{} / {} ({:.0}%) at {}, ETA {}",
+        format_bytes(done),
+        format_bytes(total),
+        done as f64 / total as f64 * 100.0,
+        format_rate(done, elapsed),
+        eta(done, total, elapsed).map(format_clock).unwrap_or_else(|| "--:--".into())
+    );
+}
+*/
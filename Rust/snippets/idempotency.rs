@@ -0,0 +1,546 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// sha2 = "0.10"
+// hex = "0.4"
+// base64 = "0.22"
+// thiserror = "1.0"
+// redis = "0.27" // Only for `RedisIdempotencyStore`
+//
+// Idempotency keys for HTTP handlers and webhook receivers, so a retried request doesn't charge
+// a card twice:
+// * The client sends `Idempotency-Key: <uuid>` (webhook senders have their own delivery id,
+//   e.g. `X-GitHub-Delivery`). The first request with a key runs the handler; its response is
+//   stored for `ttl`, and replays of that key get the stored response without running it again.
+// * A key arriving while its first request is still running gets `409 Conflict` instead of a
+//   second concurrent execution. The in-progress marker has its own shorter TTL, so a crashed
+//   handler doesn't block the key forever.
+// * Each key is tied to a fingerprint of the request (method, path, body). Reusing a key for a
+//   different request is a client bug and gets `422`.
+// * The in-progress marker names its owner. `complete` and `release` only touch a marker they
+//   own, so a handler that outlived its marker can't overwrite or free the key for the request
+//   that claimed it after the marker expired.
+// * 5xx responses aren't stored: the key is released, and the client's retry runs again.
+// * Stores: `MemoryIdempotencyStore` for one process, `RedisIdempotencyStore` (`SET NX PX`, the
+//   same primitive as the lock in `redis.rs`) when several instances serve the same clients.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+enum IdempotencyError {
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("Stored record is corrupt: {0}")]
+    Corrupt(#[from] serde_json::Error),
+}
+
+type Result<T> = std::result::Result<T, IdempotencyError>;
+
+// --- Requests and responses ---
+
+/// The parts of a request the middleware needs, in the shape of `RecordedRequest` from
+/// `test_http_server.rs`.
+#[derive(Debug, Clone)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    /// Header names are lower-cased.
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// SHA-256 over method, path, and body, so a key can't be reused for a different request.
+    fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.method.as_bytes());
+        hasher.update([0]);
+        hasher.update(self.path.as_bytes());
+        hasher.update([0]);
+        hasher.update(&self.body);
+        hex::encode(hasher.finalize())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    #[serde(with = "base64_bytes")]
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    fn json(status: u16, value: &serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Stores bodies as base64 strings rather than JSON arrays of numbers.
+mod base64_bytes {
+    use super::{Engine, BASE64};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        BASE64.decode(text).map_err(serde::de::Error::custom)
+    }
+}
+
+// --- Stores ---
+
+/// What the store holds under a key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum Record {
+    InProgress { fingerprint: String, owner: String },
+    Completed { fingerprint: String, response: HttpResponse },
+}
+
+/// The outcome of trying to start a request with a key.
+#[derive(Debug, Clone, PartialEq)]
+enum Begin {
+    /// The key was free and is now held; run the handler, then `complete` or `release`.
+    Proceed,
+    /// The same request already finished; send this instead of running it again.
+    Replay(HttpResponse),
+    /// The same request is running right now.
+    InProgress,
+    /// The key was used for a different request.
+    Mismatch,
+}
+
+fn classify(record: Record, fingerprint: &str) -> Begin {
+    match record {
+        Record::InProgress { fingerprint: stored, .. } | Record::Completed { fingerprint: stored, .. } if stored != fingerprint => {
+            Begin::Mismatch
+        }
+        Record::InProgress { .. } => Begin::InProgress,
+        Record::Completed { response, .. } => Begin::Replay(response),
+    }
+}
+
+/// Storage for idempotency records. `begin` must claim a free key atomically.
+trait IdempotencyStore: Send + Sync {
+    /// Claims `key` as in progress for `lock_ttl` on behalf of `owner` (a fresh
+    /// `unique_owner_id()`), or reports what already holds it.
+    fn begin(&self, key: &str, fingerprint: &str, owner: &str, lock_ttl: Duration) -> Result<Begin>;
+
+    /// Replaces `owner`'s in-progress marker with the response, kept for `ttl`. Returns `false`,
+    /// storing nothing, if the marker is gone or belongs to someone else.
+    fn complete(&self, key: &str, owner: &str, fingerprint: &str, response: &HttpResponse, ttl: Duration) -> Result<bool>;
+
+    /// Drops `owner`'s in-progress marker so the next request with the key runs the handler
+    /// again. Returns `false`, deleting nothing, if `owner` no longer holds the key.
+    fn release(&self, key: &str, owner: &str) -> Result<bool>;
+}
+
+/// Identifies one claim on a key. Unique within the process and, with the pid, across the
+/// instances on one host; use a UUID when instances on several hosts share a store.
+fn unique_owner_id() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    format!("{}-{}-{}", std::process::id(), nanos, NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+fn is_owned_by(record: &Record, owner: &str) -> bool {
+    matches!(record, Record::InProgress { owner: held_by, .. } if held_by == owner)
+}
+
+/// Records in a map with expiry times; for a single process, and for tests.
+#[derive(Default)]
+struct MemoryIdempotencyStore {
+    records: Mutex<HashMap<String, (Record, Instant)>>,
+}
+
+impl MemoryIdempotencyStore {
+    /// Drops expired records; call now and then (e.g. from a timer) to bound memory.
+    fn purge_expired(&self) -> usize {
+        let now = Instant::now();
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        let before = records.len();
+        records.retain(|_, (_, expires_at)| *expires_at > now);
+        before - records.len()
+    }
+}
+
+impl IdempotencyStore for MemoryIdempotencyStore {
+    fn begin(&self, key: &str, fingerprint: &str, owner: &str, lock_ttl: Duration) -> Result<Begin> {
+        let now = Instant::now();
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((record, expires_at)) = records.get(key) {
+            if *expires_at > now {
+                return Ok(classify(record.clone(), fingerprint));
+            }
+        }
+        let record = Record::InProgress { fingerprint: fingerprint.to_string(), owner: owner.to_string() };
+        records.insert(key.to_string(), (record, now + lock_ttl));
+        Ok(Begin::Proceed)
+    }
+
+    fn complete(&self, key: &str, owner: &str, fingerprint: &str, response: &HttpResponse, ttl: Duration) -> Result<bool> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        match records.get_mut(key) {
+            Some(entry) if is_owned_by(&entry.0, owner) => {
+                let record = Record::Completed { fingerprint: fingerprint.to_string(), response: response.clone() };
+                *entry = (record, Instant::now() + ttl);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn release(&self, key: &str, owner: &str) -> Result<bool> {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        match records.get(key) {
+            Some((record, _)) if is_owned_by(record, owner) => {
+                records.remove(key);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// Store the response only if `ARGV[1]` still owns the in-progress marker.
+const COMPLETE_SCRIPT: &str = r"
+local record = redis.call('GET', KEYS[1])
+if record and cjson.decode(record).owner == ARGV[1] then
+    redis.call('SET', KEYS[1], ARGV[2], 'PX', ARGV[3])
+    return 1
+end
+return 0
+";
+
+/// Delete the in-progress marker only if `ARGV[1]` still owns it.
+const RELEASE_SCRIPT: &str = r"
+local record = redis.call('GET', KEYS[1])
+if record and cjson.decode(record).owner == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+";
+
+/// Records as JSON strings under `{prefix}:{key}` with Redis expiry.
+struct RedisIdempotencyStore {
+    client: redis::Client,
+    prefix: String,
+}
+
+impl RedisIdempotencyStore {
+    fn new(client: redis::Client, prefix: &str) -> Self {
+        Self { client, prefix: prefix.to_string() }
+    }
+
+    fn redis_key(&self, key: &str) -> String {
+        format!("{}:{}", self.prefix, key)
+    }
+}
+
+impl IdempotencyStore for RedisIdempotencyStore {
+    fn begin(&self, key: &str, fingerprint: &str, owner: &str, lock_ttl: Duration) -> Result<Begin> {
+        let mut conn = self.client.get_connection()?;
+        let marker = serde_json::to_string(&Record::InProgress { fingerprint: fingerprint.to_string(), owner: owner.to_string() })?;
+        // The existing record may expire between SET NX and GET; then simply try again.
+        loop {
+            let claimed: Option<String> = redis::cmd("SET")
+                .arg(self.redis_key(key))
+                .arg(&marker)
+                .arg("NX")
+                .arg("PX")
+                .arg(lock_ttl.as_millis() as u64)
+                .query(&mut conn)?;
+            if claimed.is_some() {
+                return Ok(Begin::Proceed);
+            }
+            let existing: Option<String> = redis::cmd("GET").arg(self.redis_key(key)).query(&mut conn)?;
+            if let Some(json) = existing {
+                return Ok(classify(serde_json::from_str(&json)?, fingerprint));
+            }
+        }
+    }
+
+    fn complete(&self, key: &str, owner: &str, fingerprint: &str, response: &HttpResponse, ttl: Duration) -> Result<bool> {
+        let record = serde_json::to_string(&Record::Completed { fingerprint: fingerprint.to_string(), response: response.clone() })?;
+        let stored: i64 = redis::Script::new(COMPLETE_SCRIPT)
+            .key(self.redis_key(key))
+            .arg(owner)
+            .arg(record)
+            .arg(ttl.as_millis() as u64)
+            .invoke(&mut self.client.get_connection()?)?;
+        Ok(stored == 1)
+    }
+
+    fn release(&self, key: &str, owner: &str) -> Result<bool> {
+        let deleted: i64 = redis::Script::new(RELEASE_SCRIPT)
+            .key(self.redis_key(key))
+            .arg(owner)
+            .invoke(&mut self.client.get_connection()?)?;
+        Ok(deleted == 1)
+    }
+}
+
+// --- Middleware ---
+
+/// Settings for `Idempotency::handle`.
+struct Idempotency<S> {
+    store: S,
+    /// The request header carrying the key.
+    header: String,
+    /// How long responses are replayed. Clients must not retry with the same key after this.
+    ttl: Duration,
+    /// How long an in-progress marker blocks the key; longer than the slowest handler.
+    lock_ttl: Duration,
+    /// Reject requests without a key (`400`) instead of running them unprotected.
+    require_key: bool,
+}
+
+impl<S: IdempotencyStore> Idempotency<S> {
+    fn new(store: S) -> Self {
+        Self {
+            store,
+            header: "idempotency-key".to_string(),
+            ttl: Duration::from_secs(24 * 3600),
+            lock_ttl: Duration::from_secs(60),
+            require_key: false,
+        }
+    }
+
+    /// Use a different header, e.g. `x-github-delivery` for a webhook receiver.
+    fn header(mut self, name: &str) -> Self {
+        self.header = name.to_ascii_lowercase();
+        self
+    }
+
+    fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    fn lock_ttl(mut self, lock_ttl: Duration) -> Self {
+        self.lock_ttl = lock_ttl;
+        self
+    }
+
+    fn require_key(mut self) -> Self {
+        self.require_key = true;
+        self
+    }
+
+    /// Runs `handler` at most once per key and returns its response, or the stored one.
+    ///
+    /// # Arguments
+    ///
+    /// * `scope` - Whose key space this is (an API key id or tenant), so two clients
+    ///   choosing the same key don't see each other's responses. Webhooks: the sender's name.
+    ///
+    /// # Returns
+    ///
+    /// The handler's response; a replay marked `Idempotent-Replayed: true`; `409` with
+    /// `Retry-After` while the first request runs; `422` for a reused key; `400` for a missing
+    /// key if required; or `503` if the store is unreachable, rather than risking a double run.
+    fn handle(&self, scope: &str, request: &HttpRequest, handler: impl FnOnce(&HttpRequest) -> HttpResponse) -> HttpResponse {
+        let Some(key) = request.header(&self.header).map(str::trim).filter(|k| !k.is_empty() && k.len() <= 255) else {
+            if self.require_key {
+                let message = format!("missing or invalid {} header", self.header);
+                return HttpResponse::json(400, &serde_json::json!({ "error": message }));
+            }
+            return handler(request);
+        };
+        let key = format!("{}:{}", scope, key);
+        let fingerprint = request.fingerprint();
+        let owner = unique_owner_id();
+
+        match self.store.begin(&key, &fingerprint, &owner, self.lock_ttl) {
+            Err(e) => {
+                eprintln!("idempotency store unavailable: {}", e);
+                HttpResponse::json(503, &serde_json::json!({ "error": "try again later" })).header("Retry-After", "5")
+            }
+            Ok(Begin::Replay(response)) => response.header("Idempotent-Replayed", "true"),
+            Ok(Begin::InProgress) => {
+                HttpResponse::json(409, &serde_json::json!({ "error": "a request with this key is in progress" }))
+                    .header("Retry-After", "1")
+            }
+            Ok(Begin::Mismatch) => HttpResponse::json(
+                422,
+                &serde_json::json!({ "error": "this idempotency key was used for a different request" }),
+            ),
+            Ok(Begin::Proceed) => {
+                // Release the key if the handler panics, so the client's retry isn't locked out.
+                let mut guard = ReleaseOnDrop { store: &self.store, key: &key, owner: &owner, armed: true };
+                let response = handler(request);
+                let stored = if response.status >= 500 {
+                    self.store.release(&key, &owner)
+                } else {
+                    self.store.complete(&key, &owner, &fingerprint, &response, self.ttl)
+                };
+                match stored {
+                    Ok(true) => {}
+                    // Another request claimed the key after `lock_ttl`; its record wins.
+                    Ok(false) => eprintln!("in-progress marker for {} expired before the handler finished", key),
+                    Err(e) => eprintln!("failed to record idempotent response for {}: {}", key, e),
+                }
+                guard.armed = false;
+                response
+            }
+        }
+    }
+}
+
+struct ReleaseOnDrop<'a, S: IdempotencyStore> {
+    store: &'a S,
+    key: &'a str,
+    owner: &'a str,
+    armed: bool,
+}
+
+impl<S: IdempotencyStore> Drop for ReleaseOnDrop<'_, S> {
+    fn drop(&mut self) {
+        if self.armed {
+            let _ = self.store.release(self.key, self.owner);
+        }
+    }
+}
+
+// Example Usage
+/*
+use std::sync::atomic::AtomicU32;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+fn post(path: &str, key: Option<&str>, body: &str) -> HttpRequest {
+    let mut headers = vec![("content-type".to_string(), "application/json".to_string())];
+    if let Some(key) = key {
+        headers.push(("idempotency-key".to_string(), key.to_string()));
+    }
+    HttpRequest { method: "POST".into(), path: path.into(), headers, body: body.as_bytes().to_vec() }
+}
+
+fn show(label: &str, response: &HttpResponse) {
+    let replayed = response.headers.iter().any(|(n, _)| n == "Idempotent-Replayed");
+    println!("{:<28} {} {}{}", label, response.status, String::from_utf8_lossy(&response.body), if replayed { " (replayed)" } else { "" });
+}
+
+fn main() {
+    let idempotency = Arc::new(Idempotency::new(MemoryIdempotencyStore::default()).ttl(Duration::from_millis(300)));
+    let charges = Arc::new(AtomicU32::new(0));
+    let charge = {
+        let charges = Arc::clone(&charges);
+        move |request: &HttpRequest| {
+            thread::sleep(Duration::from_millis(50)); // Talking to the payment provider.
+            let n = charges.fetch_add(1, Ordering::SeqCst) + 1;
+            let amount: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            HttpResponse::json(201, &serde_json::json!({ "charge_id": format!("ch_{}", n), "amount": amount["amount"] }))
+        }
+    };
+
+    let request = post("/charges", Some("key-1"), r#"{"amount":1999}"#);
+    show("first request", &idempotency.handle("client-a", &request, &charge));
+    show("retry after a timeout", &idempotency.handle("client-a", &request, &charge));
+    show("same key, other client", &idempotency.handle("client-b", &request, &charge));
+    show("same key, different body", &idempotency.handle("client-a", &post("/charges", Some("key-1"), r#"{"amount":5}"#), &charge));
+
+    // Two copies of a request arrive at once: one runs, the other is told to retry.
+    let barrier = Arc::new(Barrier::new(2));
+    let racers: Vec<_> = (0..2)
+        .map(|_| {
+            let (idempotency, barrier, charge) = (Arc::clone(&idempotency), Arc::clone(&barrier), charge.clone());
+            thread::spawn(move || {
+                barrier.wait();
+                idempotency.handle("client-a", &post("/charges", Some("key-2"), r#"{"amount":500}"#), &charge)
+            })
+        })
+        .collect();
+    for racer in racers {
+        show("concurrent duplicate", &racer.join().unwrap());
+    }
+
+    // Server errors aren't stored, so the retry runs the handler again.
+    let flaky = |_: &HttpRequest| HttpResponse::json(502, &serde_json::json!({ "error": "provider down" }));
+    let request = post("/charges", Some("key-3"), r#"{"amount":42}"#);
+    show("provider down", &idempotency.handle("client-a", &request, flaky));
+    show("retry once it's back", &idempotency.handle("client-a", &request, &charge));
+
+    // Panics release the key too.
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        idempotency.handle("client-a", &post("/refunds", Some("key-4"), "{}"), |_| panic!("bug"))
+    }));
+    std::panic::set_hook(previous_hook);
+    println!("handler panicked: {}", result.is_err());
+    show("retry after the panic", &idempotency.handle("client-a", &post("/refunds", Some("key-4"), "{}"), |_| {
+        HttpResponse::json(200, &serde_json::json!({ "refunded": true }))
+    }));
+
+    // A handler that outlives `lock_ttl` can't overwrite the request that took the key over.
+    let short_lock = Arc::new(Idempotency::new(MemoryIdempotencyStore::default()).lock_ttl(Duration::from_millis(20)));
+    let request = post("/exports", Some("key-5"), "{}");
+    let slow = {
+        let (short_lock, request) = (Arc::clone(&short_lock), request.clone());
+        thread::spawn(move || {
+            short_lock.handle("client-a", &request, |_| {
+                thread::sleep(Duration::from_millis(60));
+                HttpResponse::json(200, &serde_json::json!({ "export": "slow" }))
+            })
+        })
+    };
+    thread::sleep(Duration::from_millis(30));
+    show("after the marker expired", &short_lock.handle("client-a", &request, |_| {
+        HttpResponse::json(200, &serde_json::json!({ "export": "fast" }))
+    }));
+    show("slow handler finishes", &slow.join().unwrap());
+    show("replay keeps the new one", &short_lock.handle("client-a", &request, |_| unreachable!()));
+
+    // After the TTL, the key is forgotten.
+    thread::sleep(Duration::from_millis(350));
+    println!("purged {} expired records", idempotency.store.purge_expired());
+    show("same key after expiry", &idempotency.handle("client-a", &post("/charges", Some("key-1"), r#"{"amount":1999}"#), &charge));
+    println!("charges made: {}", charges.load(Ordering::SeqCst));
+
+    // Webhooks: the sender's delivery id is the key, and a key is required.
+    let webhooks = Idempotency::new(MemoryIdempotencyStore::default()).header("X-GitHub-Delivery").require_key();
+    let delivery = HttpRequest {
+        method: "POST".into(),
+        path: "/webhooks/github".into(),
+        headers: vec![("x-github-delivery".into(), "72d3162e-cc78-11e3-81ab-4c9367dc0958".into())],
+        body: br#"{"action":"opened"}"#.to_vec(),
+    };
+    let process = |_: &HttpRequest| HttpResponse::json(200, &serde_json::json!({ "processed": true }));
+    show("webhook delivery", &webhooks.handle("github", &delivery, process));
+    show("webhook redelivery", &webhooks.handle("github", &delivery, process));
+    show("webhook without id", &webhooks.handle("github", &post("/webhooks/github", None, "{}"), process));
+
+    // Shared across instances:
+    // let store = RedisIdempotencyStore::new(redis::Client::open("redis://127.0.0.1/").unwrap(), "idem");
+    // let idempotency = Idempotency::new(store);
+}
+*/
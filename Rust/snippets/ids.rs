@@ -0,0 +1,479 @@
+// Note: This example requires adding the `rand`, `serde`, `serde_json`, and `thiserror` crates
+// to your Cargo.toml:
+// [dependencies]
+// rand = "0.8"
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0" # Only for the example
+// thiserror = "1.0"
+//
+// Three ID formats for different needs (for UUID v4/v7, see `rand_utils.rs`):
+// * ULID - 128 bits, 26 characters (`01JA8Z3K7Q...`), sorts by creation time as a string.
+//   Generated monotonically: IDs made in the same millisecond still sort in creation order.
+// * Snowflake - 64-bit integers (timestamp | node id | sequence) that fit a `BIGINT` column,
+//   sort by time, and need no coordination beyond a unique node id per process.
+// * nanoid - short random URL-safe strings for public slugs and share links.
+//
+// `Ulid` and `SnowflakeId` implement `Display`, `FromStr`, and serde. Snowflakes serialize as
+// JSON *strings*: JavaScript numbers lose precision above 2^53, which Snowflakes pass within
+// days of their epoch.
+
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IdError {
+    #[error("Invalid ULID '{0}': expected 26 Crockford base32 characters")]
+    InvalidUlid(String),
+    #[error("Invalid Snowflake id '{0}'")]
+    InvalidSnowflake(String),
+    #[error("Node id {node_id} does not fit in {bits} bits (max {max})")]
+    NodeIdOutOfRange { node_id: u64, bits: u8, max: u64 },
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Clock moved backwards by {0:?}, more than the allowed drift")]
+    ClockMovedBackwards(Duration),
+    #[error("ID space exhausted: {0}")]
+    Exhausted(&'static str),
+    #[error("Invalid alphabet: {0}")]
+    InvalidAlphabet(String),
+}
+
+type Result<T> = std::result::Result<T, IdError>;
+
+fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+// --- ULID ---
+
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// A ULID: 48-bit Unix millisecond timestamp followed by 80 random bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+struct Ulid(u128);
+
+impl Ulid {
+    const RANDOM_BITS: u32 = 80;
+    const RANDOM_MASK: u128 = (1 << Self::RANDOM_BITS) - 1;
+
+    /// A new ULID with fresh randomness. Not monotonic within a millisecond; use
+    /// `UlidGenerator` when order matters.
+    fn new() -> Self {
+        Self::from_parts(unix_millis(), rand::thread_rng().gen())
+    }
+
+    fn from_parts(timestamp_ms: u64, random: u128) -> Self {
+        Self((u128::from(timestamp_ms & 0xFFFF_FFFF_FFFF) << Self::RANDOM_BITS) | (random & Self::RANDOM_MASK))
+    }
+
+    fn timestamp_ms(&self) -> u64 {
+        (self.0 >> Self::RANDOM_BITS) as u64
+    }
+
+    /// When the ULID was generated, to the millisecond.
+    fn datetime(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp_ms())
+    }
+
+    /// The 16 bytes, big-endian, e.g. for a `BLOB(16)`/`uuid` column that keeps time order.
+    fn to_bytes(self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+}
+
+impl fmt::Display for Ulid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = [0u8; 26];
+        for (i, c) in out.iter_mut().enumerate() {
+            // 26 characters * 5 bits = 130 bits; the first character holds only 3.
+            let shift = 125 - 5 * i as u32;
+            *c = CROCKFORD[((self.0 >> shift) & 0x1F) as usize];
+        }
+        f.write_str(std::str::from_utf8(&out).expect("alphabet is ASCII"))
+    }
+}
+
+impl FromStr for Ulid {
+    type Err = IdError;
+
+    /// Parses case-insensitively and accepts Crockford's look-alikes (`I`/`L` as 1, `O` as 0).
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || IdError::InvalidUlid(s.to_string());
+        if s.len() != 26 {
+            return Err(invalid());
+        }
+        let mut value: u128 = 0;
+        for (i, c) in s.bytes().enumerate() {
+            let digit = match c.to_ascii_uppercase() {
+                b'I' | b'L' => 1,
+                b'O' => 0,
+                c => CROCKFORD.iter().position(|&a| a == c).ok_or_else(invalid)? as u128,
+            };
+            // The first character can only be 0-7, or the value would exceed 128 bits.
+            if i == 0 && digit > 7 {
+                return Err(invalid());
+            }
+            value = (value << 5) | digit;
+        }
+        Ok(Self(value))
+    }
+}
+
+impl From<Ulid> for String {
+    fn from(id: Ulid) -> Self {
+        id.to_string()
+    }
+}
+
+impl TryFrom<String> for Ulid {
+    type Error = IdError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+/// Generates strictly increasing ULIDs. Within one millisecond (or if the clock steps
+/// backwards) the previous ULID's random part is incremented instead of redrawn.
+/// Share one generator per process, e.g. in a `static` or an `Arc`.
+#[derive(Debug, Default)]
+struct UlidGenerator {
+    last: Mutex<Option<Ulid>>,
+}
+
+impl UlidGenerator {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn generate(&self) -> Result<Ulid> {
+        self.generate_at(unix_millis())
+    }
+
+    /// # Returns
+    ///
+    /// A ULID greater than every earlier one from this generator, or `Exhausted` in the
+    /// (practically impossible) case of 2^80 IDs in one millisecond.
+    fn generate_at(&self, now_ms: u64) -> Result<Ulid> {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        let next = match *last {
+            Some(previous) if previous.timestamp_ms() >= now_ms => {
+                if previous.0 & Ulid::RANDOM_MASK == Ulid::RANDOM_MASK {
+                    return Err(IdError::Exhausted("ULID random part overflowed within one millisecond"));
+                }
+                Ulid(previous.0 + 1)
+            }
+            _ => Ulid::from_parts(now_ms, rand::thread_rng().gen()),
+        };
+        *last = Some(next);
+        Ok(next)
+    }
+}
+
+// --- Snowflake ---
+
+/// A 64-bit Snowflake id. Decode it with the generator's `decompose`; the bit layout
+/// and epoch belong to the generator, not the id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "SnowflakeRepr")]
+struct SnowflakeId(u64);
+
+/// Accepts both `"1234"` and `1234` when deserializing; always serializes as a string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SnowflakeRepr {
+    Number(u64),
+    Text(String),
+}
+
+impl TryFrom<SnowflakeRepr> for SnowflakeId {
+    type Error = IdError;
+
+    fn try_from(repr: SnowflakeRepr) -> Result<Self> {
+        match repr {
+            SnowflakeRepr::Number(n) => Ok(Self(n)),
+            SnowflakeRepr::Text(s) => s.parse(),
+        }
+    }
+}
+
+impl From<SnowflakeId> for String {
+    fn from(id: SnowflakeId) -> Self {
+        id.to_string()
+    }
+}
+
+impl fmt::Display for SnowflakeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for SnowflakeId {
+    type Err = IdError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.parse().map(Self).map_err(|_| IdError::InvalidSnowflake(s.to_string()))
+    }
+}
+
+/// The fields packed into a `SnowflakeId`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SnowflakeParts {
+    /// Unix time in milliseconds.
+    timestamp_ms: u64,
+    node_id: u64,
+    sequence: u64,
+}
+
+#[derive(Debug)]
+struct SnowflakeState {
+    last_ms: u64,
+    sequence: u64,
+}
+
+/// Produces `SnowflakeId`s: `timestamp_bits` of milliseconds since `epoch`, then
+/// `node_bits` of node id, then `sequence_bits` of per-millisecond counter.
+/// The default layout (41/10/12, Twitter's) lasts 69 years with 1024 nodes producing up to
+/// 4096 ids per millisecond each.
+struct SnowflakeGenerator {
+    epoch_ms: u64,
+    node_id: u64,
+    node_bits: u8,
+    sequence_bits: u8,
+    max_backward_drift: Duration,
+    clock: Box<dyn Fn() -> u64 + Send + Sync>,
+    state: Mutex<SnowflakeState>,
+}
+
+impl fmt::Debug for SnowflakeGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnowflakeGenerator")
+            .field("epoch_ms", &self.epoch_ms)
+            .field("node_id", &self.node_id)
+            .field("node_bits", &self.node_bits)
+            .field("sequence_bits", &self.sequence_bits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SnowflakeGenerator {
+    /// 2024-01-01T00:00:00Z. A recent epoch keeps ids short and extends the usable range.
+    const DEFAULT_EPOCH_MS: u64 = 1_704_067_200_000;
+
+    /// A generator for `node_id` with the default layout and epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_id` - Unique per running process (from config, a pod ordinal, or a lease);
+    ///   two processes with the same node id *will* produce duplicate ids.
+    fn new(node_id: u64) -> Result<Self> {
+        Self::with_layout(node_id, Self::DEFAULT_EPOCH_MS, 10, 12)
+    }
+
+    /// A generator with a custom epoch and bit layout. The timestamp gets whatever remains
+    /// of the 63 usable bits (the sign bit stays 0 so ids are positive in signed columns).
+    fn with_layout(node_id: u64, epoch_ms: u64, node_bits: u8, sequence_bits: u8) -> Result<Self> {
+        let layout_bits = u32::from(node_bits) + u32::from(sequence_bits);
+        if layout_bits > 32 {
+            return Err(IdError::InvalidConfig(format!(
+                "node_bits + sequence_bits must be at most 32, got {}",
+                layout_bits
+            )));
+        }
+        let max = (1u64 << node_bits) - 1;
+        if node_id > max {
+            return Err(IdError::NodeIdOutOfRange { node_id, bits: node_bits, max });
+        }
+        if epoch_ms > unix_millis() {
+            return Err(IdError::InvalidConfig("epoch is in the future".to_string()));
+        }
+        Ok(Self {
+            epoch_ms,
+            node_id,
+            node_bits,
+            sequence_bits,
+            max_backward_drift: Duration::from_millis(10),
+            clock: Box::new(unix_millis),
+            state: Mutex::new(SnowflakeState { last_ms: 0, sequence: 0 }),
+        })
+    }
+
+    /// How far the clock may step backwards (NTP slew, VM migration) before `next_id`
+    /// fails. Within the limit, `next_id` waits for the clock to catch up. Default 10 ms.
+    fn max_backward_drift(mut self, drift: Duration) -> Self {
+        self.max_backward_drift = drift;
+        self
+    }
+
+    /// Replaces the clock (Unix milliseconds), for tests.
+    fn clock(mut self, clock: impl Fn() -> u64 + Send + Sync + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    fn timestamp_bits(&self) -> u8 {
+        63 - self.node_bits - self.sequence_bits
+    }
+
+    /// The next id, strictly greater than all previous ids from this generator.
+    ///
+    /// # Returns
+    ///
+    /// The id, `ClockMovedBackwards` if the clock went back further than allowed, or
+    /// `Exhausted` once the timestamp no longer fits its bits.
+    fn next_id(&self) -> Result<SnowflakeId> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut now = (self.clock)();
+        if now < state.last_ms {
+            let behind = Duration::from_millis(state.last_ms - now);
+            if behind > self.max_backward_drift {
+                return Err(IdError::ClockMovedBackwards(behind));
+            }
+            now = self.wait_until(state.last_ms);
+        }
+        if now == state.last_ms {
+            state.sequence = (state.sequence + 1) & ((1 << self.sequence_bits) - 1);
+            if state.sequence == 0 {
+                // This millisecond's sequence is used up; move on to the next one.
+                now = self.wait_until(state.last_ms + 1);
+            }
+        } else {
+            state.sequence = 0;
+        }
+        state.last_ms = now;
+
+        let elapsed = now.saturating_sub(self.epoch_ms);
+        if elapsed >> self.timestamp_bits() != 0 {
+            return Err(IdError::Exhausted("Snowflake timestamp overflowed; choose a later epoch"));
+        }
+        let id = (elapsed << (self.node_bits + self.sequence_bits))
+            | (self.node_id << self.sequence_bits)
+            | state.sequence;
+        Ok(SnowflakeId(id))
+    }
+
+    fn wait_until(&self, target_ms: u64) -> u64 {
+        loop {
+            let now = (self.clock)();
+            if now >= target_ms {
+                return now;
+            }
+            std::thread::sleep(Duration::from_micros(200));
+        }
+    }
+
+    /// Splits an id produced with this generator's layout and epoch back into its fields.
+    fn decompose(&self, id: SnowflakeId) -> SnowflakeParts {
+        let sequence_mask = (1u64 << self.sequence_bits) - 1;
+        let node_mask = (1u64 << self.node_bits) - 1;
+        SnowflakeParts {
+            timestamp_ms: (id.0 >> (self.node_bits + self.sequence_bits)) + self.epoch_ms,
+            node_id: (id.0 >> self.sequence_bits) & node_mask,
+            sequence: id.0 & sequence_mask,
+        }
+    }
+}
+
+// --- nanoid ---
+
+/// nanoid's default alphabet: URL-safe, 64 symbols (6 bits per character).
+const URL_SAFE_ALPHABET: &str = "_-0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// A 21-character URL-safe id (~126 random bits, comparable to a UUID v4) from the OS CSPRNG.
+fn nanoid() -> String {
+    nanoid_with(21, URL_SAFE_ALPHABET).expect("default alphabet is valid")
+}
+
+/// A random id of `len` characters drawn uniformly from `alphabet`.
+///
+/// # Arguments
+///
+/// * `len` - Number of characters. Entropy is `len * log2(alphabet size)` bits; aim for
+///   at least 64 bits for ids that must not collide, 96+ for ones that must not be guessed.
+/// * `alphabet` - 2 to 256 distinct characters, e.g. `"23456789ABCDEFGHJKMNPQRSTUVWXYZ"`
+///   for codes people read aloud.
+///
+/// # Returns
+///
+/// The id, or `InvalidAlphabet` if the alphabet is too small, too large, or has repeats
+/// (which would make some characters more likely than others).
+fn nanoid_with(len: usize, alphabet: &str) -> Result<String> {
+    let symbols: Vec<char> = alphabet.chars().collect();
+    if !(2..=256).contains(&symbols.len()) {
+        return Err(IdError::InvalidAlphabet(format!("needs 2-256 characters, got {}", symbols.len())));
+    }
+    let mut sorted = symbols.clone();
+    sorted.sort_unstable();
+    if let Some(pair) = sorted.windows(2).find(|w| w[0] == w[1]) {
+        return Err(IdError::InvalidAlphabet(format!("'{}' appears more than once", pair[0])));
+    }
+    // `gen_range` is uniform (no modulo bias), so every symbol is equally likely.
+    Ok((0..len).map(|_| symbols[OsRng.gen_range(0..symbols.len())]).collect())
+}
+
+// Example Usage
+/*
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Order {
+    id: SnowflakeId,
+    trace: Ulid,
+}
+
+fn main() -> Result<()> {
+    // ULIDs: time-sortable strings, monotonic within a millisecond.
+    let ulids = UlidGenerator::new();
+    let batch: Vec<Ulid> = (0..3).map(|_| ulids.generate()).collect::<Result<_>>()?;
+    for id in &batch {
+        println!("ulid {} at {:?}", id, id.datetime());
+    }
+    println!("sorted as strings: {}", batch.windows(2).all(|w| w[0].to_string() < w[1].to_string()));
+    let parsed: Ulid = batch[0].to_string().to_lowercase().parse()?;
+    println!("parse round-trip: {}, bytes {:02x?}", parsed == batch[0], &parsed.to_bytes()[..6]);
+    println!("random ulid: {}", Ulid::new());
+
+    // Snowflakes: 64-bit integers from node 7.
+    let snowflakes = SnowflakeGenerator::new(7)?;
+    let id = snowflakes.next_id()?;
+    println!("snowflake {} -> {:?}", id, snowflakes.decompose(id));
+
+    let json = serde_json::to_string(&Order { id, trace: batch[0] }).expect("serializable");
+    println!("json: {}", json);
+    let order: Order = serde_json::from_str(&format!(r#"{{"id": {}, "trace": "{}"}}"#, id, batch[0])).expect("valid");
+    println!("from numeric id: {:?}", order);
+
+    // Clock drift: a test clock that jumps back 5 ms (tolerated) and then 1 s (rejected).
+    let fake_now = Arc::new(AtomicU64::new(1_750_000_000_000));
+    let now = Arc::clone(&fake_now);
+    let gen = SnowflakeGenerator::new(1)?.clock(move || now.load(Ordering::SeqCst));
+    let a = gen.next_id()?;
+    fake_now.fetch_sub(5, Ordering::SeqCst);
+    let advance = Arc::clone(&fake_now);
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        advance.fetch_add(6, Ordering::SeqCst);
+    });
+    let b = gen.next_id()?;
+    println!("after 5 ms step back: {} > {}: {}", b, a, b > a);
+    fake_now.fetch_sub(1_000, Ordering::SeqCst);
+    println!("after 1 s step back: {}", gen.next_id().unwrap_err());
+
+    println!("node 5000: {}", SnowflakeGenerator::new(5000).unwrap_err());
+
+    // nanoid: short random ids.
+    println!("nanoid: {}", nanoid());
+    println!("invite code: {}", nanoid_with(8, "23456789ABCDEFGHJKMNPQRSTUVWXYZ")?);
+    println!("bad alphabet: {}", nanoid_with(8, "abca").unwrap_err());
+    Ok(())
+}
+*/
@@ -0,0 +1,419 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// image = { version = "0.25", default-features = false, features = ["png", "jpeg", "webp"] }
+// rayon = "1.5"
+// thiserror = "1.0"
+//
+// Thumbnails and format conversion for uploads, galleries, and static sites:
+// * `open_oriented` decodes a file and applies its EXIF orientation, so a portrait phone photo
+//   (stored sideways with a "rotate 90°" tag) comes out upright instead of lying on its side.
+//   Decoding is capped by `image::Limits`, so a 50,000 x 50,000 "decompression bomb" fails fast
+//   instead of allocating gigabytes.
+// * `Fit` describes the target size: `Contain` (fit inside a box, keep aspect), `Cover` (fill the
+//   box and crop the overflow from the center), `Width`, or `Height`. Images are never
+//   upscaled unless asked.
+// * `OutputFormat` writes PNG, JPEG (with a quality setting; transparency is flattened onto
+//   white rather than turning black), or WebP (lossless only in the `image` crate).
+// * `Thumbnailer::run_batch` processes many files in parallel with rayon (see
+//   `rayon_parallel_iteration.rs`), on a bounded pool because every worker holds a fully decoded
+//   image in memory. Failures are collected per file instead of stopping the batch.
+//
+// Outputs are written to a temp file and renamed, so a half-written thumbnail is never served.
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageDecoder, ImageReader, Limits, Rgb, RgbImage};
+use rayon::prelude::*;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum ImagesError {
+    #[error("cannot read image {}: {source}", path.display())]
+    Decode { path: PathBuf, source: image::ImageError },
+    #[error("cannot encode {}: {source}", path.display())]
+    Encode { path: PathBuf, source: image::ImageError },
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("unsupported output format {0:?} (expected png, jpg/jpeg, or webp)")]
+    UnsupportedFormat(String),
+    #[error("failed to build the thread pool: {0}")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+}
+
+type Result<T> = std::result::Result<T, ImagesError>;
+
+/// Largest width or height accepted when decoding.
+const MAX_DIMENSION: u32 = 16_384;
+/// Largest decode buffer, which bounds memory per worker in a batch.
+const MAX_ALLOC: u64 = 512 * 1024 * 1024;
+
+// --- Decoding ---
+
+/// Decodes `path` (format sniffed from the content, not the extension) and rotates or flips
+/// it according to its EXIF orientation tag.
+///
+/// # Returns
+/// The upright image, or `ImagesError::Decode` for unreadable, unsupported, or oversized files.
+fn open_oriented(path: &Path) -> Result<DynamicImage> {
+    let decode_err = |source| ImagesError::Decode { path: path.to_path_buf(), source };
+    let io_err = |source| ImagesError::Io { path: path.to_path_buf(), source };
+
+    let mut reader = ImageReader::open(path).map_err(io_err)?.with_guessed_format().map_err(io_err)?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_ALLOC);
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder().map_err(decode_err)?;
+    // Formats without EXIF report `NoTransforms`.
+    let orientation = decoder.orientation().map_err(decode_err)?;
+    let mut img = DynamicImage::from_decoder(decoder).map_err(decode_err)?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
+
+// --- Resizing ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fit {
+    /// Scale to fit inside the box, keeping the aspect ratio. One side may come out smaller.
+    Contain(u32, u32),
+    /// Scale to cover the box, then crop the overflow from the center. Exactly the box size.
+    Cover(u32, u32),
+    /// Scale to this width; height follows the aspect ratio.
+    Width(u32),
+    /// Scale to this height; width follows the aspect ratio.
+    Height(u32),
+}
+
+/// Resizes `img` as described by `fit`.
+///
+/// # Arguments
+/// * `filter` - `Triangle` is fast and fine for small thumbnails; `Lanczos3` is the sharpest
+///   and slowest. `CatmullRom` is a good default between the two.
+/// * `upscale` - When false, images already smaller than the target are returned unchanged.
+fn resize(img: &DynamicImage, fit: Fit, filter: FilterType, upscale: bool) -> DynamicImage {
+    let (w, h) = img.dimensions();
+    let (box_w, box_h) = match fit {
+        Fit::Contain(bw, bh) | Fit::Cover(bw, bh) => (bw, bh),
+        // `resize` keeps the aspect ratio inside the box, so the free side can be unbounded.
+        Fit::Width(bw) => (bw, u32::MAX),
+        Fit::Height(bh) => (u32::MAX, bh),
+    };
+    let too_small = match fit {
+        Fit::Cover(..) => w < box_w || h < box_h,
+        _ => w <= box_w && h <= box_h,
+    };
+    if too_small && !upscale {
+        return img.clone();
+    }
+    match fit {
+        Fit::Cover(..) => img.resize_to_fill(box_w, box_h, filter),
+        _ => img.resize(box_w, box_h, filter),
+    }
+}
+
+// --- Encoding ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Png,
+    /// Quality 1-100; 80-85 is the usual size/quality sweet spot for photos.
+    Jpeg { quality: u8 },
+    /// Lossless; the `image` crate has no lossy WebP encoder.
+    WebP,
+}
+
+impl OutputFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg { .. } => "jpg",
+            OutputFormat::WebP => "webp",
+        }
+    }
+
+    /// The format implied by `path`'s extension, with JPEG at quality 85.
+    fn from_path(path: &Path) -> Result<Self> {
+        path.extension().and_then(|e| e.to_str()).unwrap_or("").parse()
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = ImagesError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "png" => Ok(OutputFormat::Png),
+            "jpg" | "jpeg" => Ok(OutputFormat::Jpeg { quality: 85 }),
+            "webp" => Ok(OutputFormat::WebP),
+            _ => Err(ImagesError::UnsupportedFormat(s.to_string())),
+        }
+    }
+}
+
+/// Composites any transparency onto white. JPEG has no alpha channel, and simply dropping it
+/// turns transparent pixels (usually stored as black) into a black background.
+fn flatten_onto_white(img: &DynamicImage) -> RgbImage {
+    if !img.color().has_alpha() {
+        return img.to_rgb8();
+    }
+    let rgba = img.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+        Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+/// Encodes `img` as `format` into `out`.
+fn encode(img: &DynamicImage, format: OutputFormat, out: &mut impl Write) -> image::ImageResult<()> {
+    match format {
+        OutputFormat::Png => img.write_with_encoder(PngEncoder::new(out)),
+        OutputFormat::Jpeg { quality } => {
+            let rgb = DynamicImage::ImageRgb8(flatten_onto_white(img));
+            rgb.write_with_encoder(JpegEncoder::new_with_quality(out, quality.clamp(1, 100)))
+        }
+        // The WebP encoder takes 8-bit RGB(A) only.
+        OutputFormat::WebP => {
+            let img = match img.color().has_alpha() {
+                true => DynamicImage::ImageRgba8(img.to_rgba8()),
+                false => DynamicImage::ImageRgb8(img.to_rgb8()),
+            };
+            img.write_with_encoder(WebPEncoder::new_lossless(out))
+        }
+    }
+}
+
+/// Encodes to `<path>.tmp` and renames over `path`, so readers never see a partial file.
+fn save_atomic(img: &DynamicImage, format: OutputFormat, path: &Path) -> Result<u64> {
+    let io_err = |source| ImagesError::Io { path: path.to_path_buf(), source };
+    let tmp = path.with_extension(format!("{}.tmp", format.extension()));
+    let result = (|| {
+        let mut out = BufWriter::new(File::create(&tmp).map_err(io_err)?);
+        encode(img, format, &mut out).map_err(|source| ImagesError::Encode { path: path.to_path_buf(), source })?;
+        out.into_inner().map_err(|e| io_err(e.into_error()))?.sync_all().map_err(io_err)?;
+        fs::rename(&tmp, path).map_err(io_err)?;
+        fs::metadata(path).map(|m| m.len()).map_err(io_err)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Converts `input` to the format implied by `output`'s extension, keeping its size.
+fn convert(input: &Path, output: &Path) -> Result<u64> {
+    let format = OutputFormat::from_path(output)?;
+    save_atomic(&open_oriented(input)?, format, output)
+}
+
+// --- Thumbnails and Batches ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    Written { path: PathBuf, from: (u32, u32), to: (u32, u32), bytes: u64 },
+    /// The output already existed and was newer than the input.
+    Skipped { path: PathBuf },
+}
+
+#[derive(Debug, Default)]
+struct BatchReport {
+    done: Vec<Outcome>,
+    failed: Vec<(PathBuf, ImagesError)>,
+}
+
+#[derive(Debug, Clone)]
+struct Thumbnailer {
+    fit: Fit,
+    format: OutputFormat,
+    filter: FilterType,
+    upscale: bool,
+    skip_up_to_date: bool,
+    threads: usize,
+}
+
+impl Thumbnailer {
+    /// Thumbnails fitting inside `fit`, as JPEG quality 85, never upscaled.
+    fn new(fit: Fit) -> Self {
+        Self {
+            fit,
+            format: OutputFormat::Jpeg { quality: 85 },
+            filter: FilterType::CatmullRom,
+            upscale: false,
+            skip_up_to_date: true,
+            threads: std::thread::available_parallelism().map_or(4, |n| n.get()).min(8),
+        }
+    }
+
+    fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    fn filter(mut self, filter: FilterType) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    fn upscale(mut self, upscale: bool) -> Self {
+        self.upscale = upscale;
+        self
+    }
+
+    /// Re-process files even when the output is newer than the input.
+    fn force(mut self) -> Self {
+        self.skip_up_to_date = false;
+        self
+    }
+
+    /// Parallel workers for `run_batch`. Each holds one decoded image, so this bounds memory.
+    fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// `<out_dir>/<input stem>.<format extension>`. Inputs differing only by extension collide.
+    fn output_path(&self, input: &Path, out_dir: &Path) -> PathBuf {
+        let stem = input.file_stem().unwrap_or(input.as_os_str());
+        out_dir.join(stem).with_extension(self.format.extension())
+    }
+
+    fn is_up_to_date(input: &Path, output: &Path) -> bool {
+        let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+        matches!((modified(input), modified(output)), (Some(i), Some(o)) if o >= i)
+    }
+
+    /// Makes one thumbnail of `input` in `out_dir`.
+    fn process(&self, input: &Path, out_dir: &Path) -> Result<Outcome> {
+        let path = self.output_path(input, out_dir);
+        if self.skip_up_to_date && Self::is_up_to_date(input, &path) {
+            return Ok(Outcome::Skipped { path });
+        }
+        let img = open_oriented(input)?;
+        let thumb = resize(&img, self.fit, self.filter, self.upscale);
+        let bytes = save_atomic(&thumb, self.format, &path)?;
+        Ok(Outcome::Written { path, from: img.dimensions(), to: thumb.dimensions(), bytes })
+    }
+
+    /// Thumbnails every input in parallel, collecting failures instead of stopping.
+    ///
+    /// # Returns
+    /// A report of written/skipped outputs and per-file errors, or an error only if
+    /// `out_dir` can't be created or the thread pool can't start.
+    fn run_batch(&self, inputs: &[PathBuf], out_dir: &Path) -> Result<BatchReport> {
+        fs::create_dir_all(out_dir).map_err(|source| ImagesError::Io { path: out_dir.to_path_buf(), source })?;
+        // A dedicated pool, so a batch doesn't compete with (or get starved by) other rayon work.
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(self.threads).build()?;
+        let results: Vec<(PathBuf, Result<Outcome>)> =
+            pool.install(|| inputs.par_iter().map(|input| (input.clone(), self.process(input, out_dir))).collect());
+
+        let mut report = BatchReport::default();
+        for (input, result) in results {
+            match result {
+                Ok(outcome) => report.done.push(outcome),
+                Err(e) => report.failed.push((input, e)),
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Image files directly inside `dir`, by extension, sorted by name.
+fn list_images(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    const EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "gif", "bmp", "tif", "tiff"];
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    use image::{ImageEncoder, Rgba, RgbaImage};
+
+    let dir = std::env::temp_dir().join(format!("images-demo-{}", std::process::id()));
+    let originals = dir.join("originals");
+    fs::create_dir_all(&originals).map_err(|source| ImagesError::Io { path: originals.clone(), source })?;
+
+    // --- Sample inputs: a landscape gradient, a transparent logo, and a "phone photo" ---
+    let gradient = RgbImage::from_fn(1600, 900, |x, y| Rgb([(x / 7) as u8, (y / 4) as u8, 160]));
+    DynamicImage::ImageRgb8(gradient.clone()).save(originals.join("landscape.png")).unwrap();
+
+    let logo = RgbaImage::from_fn(400, 400, |x, y| {
+        let inside = (x as i32 - 200).pow(2) + (y as i32 - 200).pow(2) < 150 * 150;
+        if inside { Rgba([220, 40, 40, 255]) } else { Rgba([0, 0, 0, 0]) }
+    });
+    DynamicImage::ImageRgba8(logo).save(originals.join("logo.png")).unwrap();
+
+    // Stored 1600x900 with EXIF orientation 6: "rotate 90° clockwise to display".
+    let exif: Vec<u8> = vec![
+        b'M', b'M', 0, 42, 0, 0, 0, 8, // big-endian TIFF header, first IFD at offset 8
+        0, 1, // one entry
+        0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0, // Orientation (SHORT) = 6
+        0, 0, 0, 0, // no next IFD
+    ];
+    let mut encoder = JpegEncoder::new_with_quality(File::create(originals.join("phone.jpg")).unwrap(), 90);
+    encoder.set_exif_metadata(exif).unwrap();
+    encoder.write_image(gradient.as_raw(), 1600, 900, image::ExtendedColorType::Rgb8).unwrap();
+
+    fs::write(originals.join("broken.jpg"), b"not really a jpeg").unwrap();
+
+    // --- Orientation and single conversions ---
+    let phone = open_oriented(&originals.join("phone.jpg"))?;
+    println!("phone.jpg decodes upright as {:?} (stored as 1600x900)", phone.dimensions());
+    let bytes = convert(&originals.join("logo.png"), &dir.join("logo.webp"))?;
+    println!("logo.png -> logo.webp, {} bytes", bytes);
+    match convert(&originals.join("logo.png"), &dir.join("logo.bmp")) {
+        Err(e) => println!("{}", e),
+        Ok(_) => println!("unexpected success"),
+    }
+
+    // --- Fit modes ---
+    for fit in [Fit::Contain(300, 300), Fit::Cover(300, 300), Fit::Width(200), Fit::Height(2000)] {
+        let out = resize(&phone, fit, FilterType::Triangle, false);
+        println!("{:?} of 900x1600 -> {:?}", fit, out.dimensions());
+    }
+
+    // --- Parallel batch ---
+    let inputs = list_images(&originals).map_err(|source| ImagesError::Io { path: originals.clone(), source })?;
+    let thumbs = dir.join("thumbs");
+    let thumbnailer = Thumbnailer::new(Fit::Contain(320, 320)).threads(2);
+    let report = thumbnailer.run_batch(&inputs, &thumbs)?;
+    for outcome in &report.done {
+        if let Outcome::Written { path, from, to, bytes } = outcome {
+            println!("{:<12} {:?} -> {:?}, {} bytes", path.file_name().unwrap().to_string_lossy(), from, to, bytes);
+        }
+    }
+    for (input, e) in &report.failed {
+        println!("failed {}: {}", input.file_name().unwrap().to_string_lossy(), e);
+    }
+
+    // A second run skips everything that is already up to date.
+    let again = thumbnailer.run_batch(&inputs, &thumbs)?;
+    let skipped = again.done.iter().filter(|o| matches!(o, Outcome::Skipped { .. })).count();
+    println!("second run: {} skipped, {} failed", skipped, again.failed.len());
+
+    // The flattened logo is red on white, not red on black.
+    let logo_thumb = image::open(thumbs.join("logo.jpg")).unwrap().to_rgb8();
+    println!("logo.jpg corner pixel: {:?}", logo_thumb.get_pixel(0, 0));
+
+    let _ = fs::remove_dir_all(&dir);
+    Ok(())
+}
+*/
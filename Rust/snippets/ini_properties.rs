@@ -0,0 +1,601 @@
+// This example only uses the standard library.
+//
+// Reading and editing INI and Java `.properties` files without losing their comments, for the
+// legacy services and vendor tools that still configure themselves this way (Tomcat, Kafka,
+// Log4j, Windows-style `.ini` files, Samba, MySQL's `my.cnf`):
+// * `Document::parse_ini` and `Document::parse_properties` keep every line. Comments, blank
+//   lines, key order, and the exact text of untouched entries are written back byte for byte,
+//   so a diff of an edited file shows only the values that changed.
+// * INI: `[section]` headers, `key = value` or `key: value`, and `;`/`#` comment lines. Section
+//   and key names are matched case-insensitively, as most INI readers do. Keys before the
+//   first header live in the global section, `""`.
+// * `.properties`: `=`, `:`, or whitespace separators, `#`/`!` comments, backslash line
+//   continuations, and `\t`, `\n`, `\uXXXX` escapes. New values are written with non-ASCII
+//   characters escaped, because `Properties.load(InputStream)` reads ISO-8859-1. Files that are
+//   not valid UTF-8 are read as ISO-8859-1.
+// * `get` expands `${key}` (same section, then the global section), `${section:key}` (INI),
+//   and `${VAR}` from the environment, with `${name:-default}` for fallbacks and `$$` for a
+//   literal `$`. An unresolvable reference is an error rather than an empty string. `get_raw`
+//   returns the text as written.
+// * `get_bool`, `get_parsed`, and `get_list` convert values and name the section, key, and
+//   value on failure. `set` and `remove` edit in place; `save` writes atomically.
+//
+// For `.env` files and typed startup configuration from the environment, see `env.rs`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug)]
+struct ParseError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A value that is missing, fails to interpolate, or doesn't convert to the requested type.
+#[derive(Debug)]
+struct ValueError {
+    section: String,
+    key: String,
+    message: String,
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.section.as_str() {
+            "" => write!(f, "{}: {}", self.key, self.message),
+            section => write!(f, "[{}] {}: {}", section, self.key, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+// --- Document model ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Ini,
+    Properties,
+}
+
+#[derive(Debug, Clone)]
+enum Line {
+    /// A blank line or comment, kept verbatim.
+    Verbatim(String),
+    /// `raw` is the original text (several physical lines for a continued property), or `None`
+    /// once the entry has been changed and must be re-rendered.
+    Entry { key: String, value: String, raw: Option<String> },
+}
+
+#[derive(Debug, Clone)]
+struct Section {
+    /// `""` for the global section, which has no header line.
+    name: String,
+    header: Option<String>,
+    lines: Vec<Line>,
+}
+
+impl Section {
+    fn new(name: &str) -> Self {
+        Self { name: name.to_string(), header: None, lines: Vec::new() }
+    }
+}
+
+/// An INI or `.properties` file, with every line kept for round-tripping.
+#[derive(Debug, Clone)]
+struct Document {
+    format: Format,
+    /// `sections[0]` is always the global section.
+    sections: Vec<Section>,
+    /// Overrides the process environment for `${VAR}` lookups, mainly for tests.
+    env: Option<BTreeMap<String, String>>,
+    trailing_newline: bool,
+}
+
+// --- Parsing ---
+
+impl Document {
+    fn new(format: Format) -> Self {
+        Self { format, sections: vec![Section::new("")], env: None, trailing_newline: true }
+    }
+
+    fn parse_ini(text: &str) -> Result<Self, ParseError> {
+        let mut doc = Self::new(Format::Ini);
+        doc.trailing_newline = text.is_empty() || text.ends_with('\n');
+        for (index, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with(';') || trimmed.starts_with('#') {
+                doc.last_section().lines.push(Line::Verbatim(line.to_string()));
+            } else if let Some(name) = trimmed.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                let mut section = Section::new(name.trim());
+                section.header = Some(line.to_string());
+                doc.sections.push(section);
+            } else {
+                let split = trimmed.find(['=', ':']).ok_or_else(|| ParseError {
+                    line: index + 1,
+                    message: format!("expected `key = value` or `[section]`, found {:?}", trimmed),
+                })?;
+                let (key, value) = (trimmed[..split].trim(), trimmed[split + 1..].trim());
+                if key.is_empty() {
+                    return Err(ParseError { line: index + 1, message: "missing key before separator".into() });
+                }
+                let entry = Line::Entry { key: key.to_string(), value: value.to_string(), raw: Some(line.to_string()) };
+                doc.last_section().lines.push(entry);
+            }
+        }
+        Ok(doc)
+    }
+
+    fn parse_properties(text: &str) -> Result<Self, ParseError> {
+        let mut doc = Self::new(Format::Properties);
+        doc.trailing_newline = text.is_empty() || text.ends_with('\n');
+        let mut lines = text.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            let content = line.trim_start();
+            if content.is_empty() || content.starts_with('#') || content.starts_with('!') {
+                doc.sections[0].lines.push(Line::Verbatim(line.to_string()));
+                continue;
+            }
+            // A line ending in an odd number of backslashes continues on the next one.
+            let mut raw = line.to_string();
+            let mut logical = content.to_string();
+            while ends_with_continuation(&logical) {
+                logical.pop();
+                match lines.next() {
+                    Some((_, next)) => {
+                        raw.push('\n');
+                        raw.push_str(next);
+                        logical.push_str(next.trim_start());
+                    }
+                    None => break,
+                }
+            }
+            let (key, value) = split_property(&logical);
+            let err = |message: String| ParseError { line: index + 1, message };
+            let entry = Line::Entry { key: unescape_property(key).map_err(err)?, value: unescape_property(value).map_err(err)?, raw: Some(raw) };
+            doc.sections[0].lines.push(entry);
+        }
+        Ok(doc)
+    }
+
+    /// Reads `path` as `.properties` if it has that extension, and as INI otherwise.
+    fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        let text = match String::from_utf8(bytes) {
+            Ok(text) => text,
+            // Pre-UTF-8 Java tooling writes ISO-8859-1, whose bytes map 1:1 to code points.
+            Err(e) => e.into_bytes().iter().map(|&b| b as char).collect(),
+        };
+        let result = if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("properties")) {
+            Self::parse_properties(&text)
+        } else {
+            Self::parse_ini(&text)
+        };
+        result.map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Writes to a temporary file and renames it over `path`.
+    fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.to_string())?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Resolves `${VAR}` from `vars` instead of the process environment.
+    fn with_env(mut self, vars: BTreeMap<String, String>) -> Self {
+        self.env = Some(vars);
+        self
+    }
+
+    fn last_section(&mut self) -> &mut Section {
+        self.sections.last_mut().expect("the global section always exists")
+    }
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// Splits a logical `.properties` line at the first unescaped `=`, `:`, or whitespace.
+fn split_property(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '=' | ':' | ' ' | '\t' | '\x0c' => {
+                let rest = line[i..].trim_start_matches([' ', '\t', '\x0c']);
+                let rest = rest.strip_prefix(['=', ':']).unwrap_or(rest);
+                return (&line[..i], rest.trim_start_matches([' ', '\t', '\x0c']));
+            }
+            _ => {}
+        }
+    }
+    (line, "")
+}
+
+fn unescape_property(text: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    let mut pending_high: Option<u16> = None;
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('f') => out.push('\x0c'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let unit = u16::from_str_radix(&hex, 16).ok().filter(|_| hex.len() == 4).ok_or_else(|| format!("invalid escape \\u{}", hex))?;
+                // Characters outside the BMP arrive as a \uD8xx\uDCxx surrogate pair.
+                match (pending_high.take(), unit) {
+                    (None, 0xD800..=0xDBFF) => pending_high = Some(unit),
+                    (Some(high), 0xDC00..=0xDFFF) => out.extend(char::decode_utf16([high, unit]).map(|r| r.unwrap_or('\u{fffd}'))),
+                    (_, unit) => out.push(char::from_u32(unit as u32).unwrap_or('\u{fffd}')),
+                }
+            }
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    Ok(out)
+}
+
+fn escape_property(text: &str, is_key: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\x0c' => out.push_str("\\f"),
+            '=' | ':' if is_key => out.extend(['\\', c]),
+            ' ' if is_key || i == 0 => out.push_str("\\ "),
+            '#' | '!' if i == 0 => out.extend(['\\', c]),
+            c if c.is_ascii() => out.push(c),
+            c => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    out.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+        }
+    }
+    out
+}
+
+// --- Lookup and editing ---
+
+impl Document {
+    fn same_name(&self, a: &str, b: &str) -> bool {
+        match self.format {
+            Format::Ini => a.eq_ignore_ascii_case(b),
+            Format::Properties => a == b,
+        }
+    }
+
+    /// Section names in file order, including `""` for the global section.
+    fn sections(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for section in &self.sections {
+            if !names.iter().any(|n| self.same_name(n, &section.name)) {
+                names.push(&section.name);
+            }
+        }
+        names
+    }
+
+    /// `(key, raw value)` pairs of a section in file order.
+    fn entries(&self, section: &str) -> Vec<(&str, &str)> {
+        self.sections
+            .iter()
+            .filter(|s| self.same_name(&s.name, section))
+            .flat_map(|s| &s.lines)
+            .filter_map(|line| match line {
+                Line::Entry { key, value, .. } => Some((key.as_str(), value.as_str())),
+                Line::Verbatim(_) => None,
+            })
+            .collect()
+    }
+
+    /// The value as written, without interpolation. A repeated key returns its last value.
+    fn get_raw(&self, section: &str, key: &str) -> Option<&str> {
+        self.entries(section).into_iter().rev().find(|(k, _)| self.same_name(k, key)).map(|(_, v)| v)
+    }
+
+    /// Sets a value, keeping the entry's position if it exists. New keys go after the
+    /// section's last entry, and a missing section is appended to the file.
+    fn set(&mut self, section: &str, key: &str, value: &str) {
+        let format = self.format;
+        let same = |a: &str, b: &str| if format == Format::Ini { a.eq_ignore_ascii_case(b) } else { a == b };
+        let existing = self.sections.iter_mut().rev().filter(|s| same(&s.name, section)).find_map(|s| {
+            s.lines.iter_mut().rev().find(|line| matches!(line, Line::Entry { key: k, .. } if same(k, key)))
+        });
+        if let Some(Line::Entry { value: v, raw, .. }) = existing {
+            if v != value {
+                *v = value.to_string();
+                *raw = None;
+            }
+            return;
+        }
+
+        let index = match self.sections.iter().rposition(|s| same(&s.name, section)) {
+            Some(index) => index,
+            None => {
+                if let Some(Line::Entry { .. }) = self.last_section().lines.last() {
+                    self.last_section().lines.push(Line::Verbatim(String::new()));
+                }
+                self.sections.push(Section::new(section));
+                self.sections.len() - 1
+            }
+        };
+        let lines = &mut self.sections[index].lines;
+        let at = lines.iter().rposition(|line| matches!(line, Line::Entry { .. })).map_or(0, |i| i + 1);
+        lines.insert(at, Line::Entry { key: key.to_string(), value: value.to_string(), raw: None });
+    }
+
+    /// Removes every occurrence of `key` in `section`, returning the last value.
+    fn remove(&mut self, section: &str, key: &str) -> Option<String> {
+        let format = self.format;
+        let same = |a: &str, b: &str| if format == Format::Ini { a.eq_ignore_ascii_case(b) } else { a == b };
+        let mut removed = None;
+        for s in self.sections.iter_mut().filter(|s| same(&s.name, section)) {
+            s.lines.retain(|line| match line {
+                Line::Entry { key: k, value, .. } if same(k, key) => {
+                    removed = Some(value.clone());
+                    false
+                }
+                _ => true,
+            });
+        }
+        removed
+    }
+}
+
+// --- Interpolation and typed getters ---
+
+const MAX_DEPTH: usize = 16;
+
+impl Document {
+    /// The value with `${...}` references expanded.
+    ///
+    /// # Returns
+    /// `Ok(None)` if the key is absent; `Err` if a reference can't be resolved or refers back
+    /// to itself.
+    fn get(&self, section: &str, key: &str) -> Result<Option<String>, ValueError> {
+        let Some(raw) = self.get_raw(section, key) else {
+            return Ok(None);
+        };
+        self.expand(section, raw, 0)
+            .map(Some)
+            .map_err(|message| ValueError { section: section.to_string(), key: key.to_string(), message })
+    }
+
+    fn expand(&self, section: &str, value: &str, depth: usize) -> Result<String, String> {
+        if depth > MAX_DEPTH {
+            return Err("interpolation nested too deeply (is a value referring to itself?)".into());
+        }
+        let mut out = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(at) = rest.find('$') {
+            out.push_str(&rest[..at]);
+            rest = &rest[at..];
+            if let Some(after) = rest.strip_prefix("$$") {
+                out.push('$');
+                rest = after;
+            } else if let Some(body) = rest.strip_prefix("${") {
+                let end = body.find('}').ok_or_else(|| format!("unterminated reference in {:?}", value))?;
+                let (reference, default) = match body[..end].split_once(":-") {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (&body[..end], None),
+                };
+                match (self.resolve(section, reference, depth)?, default) {
+                    (Some(resolved), _) => out.push_str(&resolved),
+                    (None, Some(default)) => out.push_str(&self.expand(section, default, depth + 1)?),
+                    (None, None) => return Err(format!("unresolved reference ${{{}}}", reference)),
+                }
+                rest = &body[end + 1..];
+            } else {
+                out.push('$');
+                rest = &rest[1..];
+            }
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// `section:key` (INI only), then `key` in this section, the global section, and finally
+    /// the environment.
+    fn resolve(&self, section: &str, reference: &str, depth: usize) -> Result<Option<String>, String> {
+        if self.format == Format::Ini {
+            if let Some((other, key)) = reference.split_once(':') {
+                return match self.get_raw(other, key) {
+                    Some(raw) => self.expand(other, raw, depth + 1).map(Some),
+                    None => Ok(None),
+                };
+            }
+        }
+        for scope in [section, ""] {
+            if let Some(raw) = self.get_raw(scope, reference) {
+                return self.expand(scope, raw, depth + 1).map(Some);
+            }
+        }
+        Ok(match &self.env {
+            Some(vars) => vars.get(reference).cloned(),
+            None => std::env::var(reference).ok(),
+        })
+    }
+
+    fn value_error(&self, section: &str, key: &str, message: String) -> ValueError {
+        ValueError { section: section.to_string(), key: key.to_string(), message }
+    }
+
+    /// Like `get`, but a missing key is an error.
+    fn require(&self, section: &str, key: &str) -> Result<String, ValueError> {
+        self.get(section, key)?.ok_or_else(|| self.value_error(section, key, "is required but not set".into()))
+    }
+
+    /// Parses the expanded value with `FromStr`: numbers, `IpAddr`, `PathBuf`, and so on.
+    fn get_parsed<T: FromStr>(&self, section: &str, key: &str) -> Result<Option<T>, ValueError>
+    where
+        T::Err: fmt::Display,
+    {
+        match self.get(section, key)? {
+            None => Ok(None),
+            Some(value) => value.trim().parse().map(Some).map_err(|e| self.value_error(section, key, format!("invalid value {:?}: {}", value, e))),
+        }
+    }
+
+    /// Accepts the spellings legacy files use: true/false, yes/no, on/off, 1/0.
+    fn get_bool(&self, section: &str, key: &str) -> Result<Option<bool>, ValueError> {
+        match self.get(section, key)? {
+            None => Ok(None),
+            Some(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Ok(Some(true)),
+                "false" | "no" | "off" | "0" => Ok(Some(false)),
+                _ => Err(self.value_error(section, key, format!("expected true/false, yes/no, on/off, or 1/0, found {:?}", value))),
+            },
+        }
+    }
+
+    /// Splits a comma-separated value, trimming items and dropping empty ones.
+    fn get_list(&self, section: &str, key: &str) -> Result<Vec<String>, ValueError> {
+        Ok(self
+            .get(section, key)?
+            .map(|value| value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect())
+            .unwrap_or_default())
+    }
+}
+
+// --- Writing ---
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines: Vec<String> = Vec::new();
+        for section in &self.sections {
+            match (&section.header, section.name.as_str()) {
+                (Some(header), _) => lines.push(header.clone()),
+                (None, "") => {}
+                (None, name) => lines.push(format!("[{}]", name)),
+            }
+            for line in &section.lines {
+                lines.push(match line {
+                    Line::Verbatim(text) => text.clone(),
+                    Line::Entry { raw: Some(raw), .. } => raw.clone(),
+                    Line::Entry { key, value, raw: None } => match self.format {
+                        Format::Ini => format!("{} = {}", key, value),
+                        Format::Properties => format!("{}={}", escape_property(key, true), escape_property(value, false)),
+                    },
+                });
+            }
+        }
+        write!(f, "{}", lines.join("\n"))?;
+        if self.trailing_newline && !lines.is_empty() {
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Example Usage
+/*
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // --- INI ---
+    let ini = "\
+; Legacy billing service
+log_dir = /var/log/billing
+
+[Database]
+host = db.internal
+port = 5432
+; credentials come from the environment
+user = ${BILLING_DB_USER}
+url = postgres://${user}@${host}:${port}/billing
+pool = ${POOL_SIZE:-10}
+
+[export]
+enabled = yes
+targets = s3, sftp ,
+path = ${log_dir}/exports
+archive = ${database:host}-archive
+";
+    let vars = BTreeMap::from([("BILLING_DB_USER".to_string(), "billing_app".to_string())]);
+    let mut doc = Document::parse_ini(ini)?.with_env(vars);
+
+    println!("sections: {:?}", doc.sections());
+    println!("url = {}", doc.require("database", "url")?);
+    println!("port = {:?}", doc.get_parsed::<u16>("Database", "PORT")?);
+    println!("pool = {:?}", doc.get_parsed::<u32>("database", "pool")?);
+    println!("export enabled = {:?}, targets = {:?}", doc.get_bool("export", "enabled")?, doc.get_list("export", "targets")?);
+    println!("export path = {}, archive = {}", doc.require("export", "path")?, doc.require("export", "archive")?);
+
+    doc.set("database", "port", "6432");
+    doc.set("database", "timeout", "30");
+    doc.set("monitoring", "endpoint", "http://localhost:9100");
+    doc.remove("export", "archive");
+    print!("--- edited INI ---\n{}", doc);
+
+    doc.set("database", "port", "not-a-port");
+    doc.set("export", "loop", "${loop}");
+    for error in [
+        doc.get_parsed::<u16>("database", "port").err(),
+        doc.get_bool("export", "targets").err(),
+        doc.get("export", "loop").err(),
+        doc.require("export", "missing").err(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        println!("error: {}", error);
+    }
+    match Document::parse_ini("[ok]\nthis line has no separator\n") {
+        Err(e) => println!("error: {}", e),
+        Ok(_) => println!("unexpected success"),
+    }
+
+    // --- .properties ---
+    let props = "\
+# Kafka consumer
+bootstrap.servers=broker1:9092,\\
+                  broker2:9092
+group.id : billing
+client.id billing-${HOSTNAME:-local}
+greeting = Gr\\u00fc\\u00dfe
+path\\ with\\ spaces = C:\\\\data
+";
+    let mut doc = Document::parse_properties(props)?.with_env(BTreeMap::new());
+    println!("servers = {:?}", doc.get_list("", "bootstrap.servers")?);
+    println!("group.id = {:?}, client.id = {:?}", doc.get("", "group.id")?, doc.get("", "client.id")?);
+    println!("greeting = {:?}, {:?} = {:?}", doc.get("", "greeting")?, "path with spaces", doc.get_raw("", "path with spaces"));
+
+    doc.set("", "group.id", "billing-v2");
+    doc.set("", "motto", "Ünïcödé & tabs\there");
+    print!("--- edited properties ---\n{}", doc);
+
+    let path = std::env::temp_dir().join("consumer.properties");
+    doc.save(&path)?;
+    let reloaded = Document::load(&path)?;
+    println!("round trip: motto = {:?}", reloaded.get_raw("", "motto"));
+    fs::remove_file(&path)?;
+    Ok(())
+}
+*/
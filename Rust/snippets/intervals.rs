@@ -0,0 +1,360 @@
+// This example only uses the standard library.
+//
+// Working with ranges of ordered keys (times, IP addresses, byte offsets, version numbers):
+// * `merge_ranges` - collapse overlapping and touching ranges ("which hours are busy?").
+// * `overlapping_pairs` - every pair of ranges that overlap (double-booked meetings).
+// * `IntervalTree` - ranges that may overlap, each with a value; find all ranges overlapping
+//   a query range or containing a point in O(log n + matches).
+// * `RangeMap` - non-overlapping ranges mapped to values; inserting a range overwrites
+//   whatever it covers. Good for IP-range tables, price tiers, and ownership maps.
+//
+// All ranges are half-open (`start..end`), like Rust's `Range`: `9..10` and `10..11` touch
+// but do not overlap, and an empty range (`start >= end`) overlaps nothing.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+// --- Merging and conflicts ---
+
+/// Merges overlapping and touching ranges into a sorted list of disjoint ranges.
+/// Empty ranges are dropped.
+///
+/// # Arguments
+///
+/// * `ranges` - Ranges in any order.
+///
+/// # Returns
+///
+/// Disjoint ranges sorted by start, with gaps between consecutive ranges.
+fn merge_ranges<K: Ord + Clone>(ranges: impl IntoIterator<Item = Range<K>>) -> Vec<Range<K>> {
+    let mut ranges: Vec<Range<K>> = ranges.into_iter().filter(|r| r.start < r.end).collect();
+    ranges.sort_by(|a, b| a.start.cmp(&b.start));
+    let mut merged: Vec<Range<K>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => {
+                if range.end > last.end {
+                    last.end = range.end;
+                }
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// The gaps between merged `ranges` within `within`, e.g. the free slots in a day.
+fn gaps<K: Ord + Clone>(ranges: impl IntoIterator<Item = Range<K>>, within: Range<K>) -> Vec<Range<K>> {
+    let mut free = Vec::new();
+    let mut cursor = within.start;
+    for busy in merge_ranges(ranges) {
+        if busy.end <= cursor {
+            continue;
+        }
+        if busy.start >= within.end {
+            break;
+        }
+        if busy.start > cursor {
+            free.push(cursor.clone()..busy.start.clone());
+        }
+        cursor = busy.end;
+    }
+    if cursor < within.end {
+        free.push(cursor..within.end);
+    }
+    free
+}
+
+/// Index pairs `(i, j)` with `i < j` of every two ranges that overlap. Sorts once and sweeps,
+/// so the cost is O(n log n + pairs) rather than comparing every pair.
+fn overlapping_pairs<K: Ord>(ranges: &[Range<K>]) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..ranges.len()).filter(|&i| ranges[i].start < ranges[i].end).collect();
+    order.sort_by(|&a, &b| ranges[a].start.cmp(&ranges[b].start));
+    let mut active: Vec<usize> = Vec::new();
+    let mut pairs = Vec::new();
+    for i in order {
+        // Anything that ended at or before this start cannot overlap this or later ranges.
+        active.retain(|&j| ranges[j].end > ranges[i].start);
+        pairs.extend(active.iter().map(|&j| (i.min(j), i.max(j))));
+        active.push(i);
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+// --- Interval tree ---
+
+/// Possibly overlapping ranges with values, for "what overlaps this?" queries.
+///
+/// Stored as a sorted array viewed as a balanced binary tree, where each node remembers the
+/// largest `end` in its subtree so searches skip subtrees that end too early. Built in
+/// O(n log n); `insert` is O(n), so it suits data loaded once and queried often (calendars,
+/// genomic features, IP allocations). Rebuild with `collect()` after bulk changes.
+#[derive(Debug, Clone)]
+struct IntervalTree<K, V> {
+    entries: Vec<(Range<K>, V)>,
+    /// `max_end[i]` is the largest end in the implicit subtree rooted at `i`.
+    max_end: Vec<K>,
+}
+
+impl<K: Ord + Clone, V> IntervalTree<K, V> {
+    fn new() -> Self {
+        Self { entries: Vec::new(), max_end: Vec::new() }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds a range. Empty ranges are kept but never match a query.
+    fn insert(&mut self, range: Range<K>, value: V) {
+        let at = self.entries.partition_point(|(r, _)| r.start <= range.start);
+        self.entries.insert(at, (range, value));
+        self.rebuild_index();
+    }
+
+    fn rebuild_index(&mut self) {
+        self.max_end = self.entries.iter().map(|(r, _)| r.end.clone()).collect();
+        fill_max_end(&mut self.max_end, 0, self.entries.len());
+    }
+
+    /// All entries overlapping `query`, in order of start.
+    fn overlapping(&self, query: &Range<K>) -> Vec<(&Range<K>, &V)> {
+        let mut out = Vec::new();
+        if query.start < query.end {
+            self.search(0, self.entries.len(), &|end| *end > query.start, &|start| *start < query.end, &mut out);
+        }
+        out
+    }
+
+    /// All entries containing `point` (`start <= point < end`), in order of start.
+    fn containing(&self, point: &K) -> Vec<(&Range<K>, &V)> {
+        let mut out = Vec::new();
+        self.search(0, self.entries.len(), &|end| end > point, &|start| start <= point, &mut out);
+        out
+    }
+
+    /// In-order walk of the implicit subtree `lo..hi`. A range matches when
+    /// `ends_after(end) && starts_before(start)`; both are monotonic, which is what makes
+    /// the pruning valid.
+    fn search<'a>(
+        &'a self,
+        lo: usize,
+        hi: usize,
+        ends_after: &dyn Fn(&K) -> bool,
+        starts_before: &dyn Fn(&K) -> bool,
+        out: &mut Vec<(&'a Range<K>, &'a V)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if !ends_after(&self.max_end[mid]) {
+            return; // Everything below ends too early.
+        }
+        self.search(lo, mid, ends_after, starts_before, out);
+        let (range, value) = &self.entries[mid];
+        if !starts_before(&range.start) {
+            return; // Entries are sorted by start, so the right subtree starts too late as well.
+        }
+        if range.start < range.end && ends_after(&range.end) {
+            out.push((range, value));
+        }
+        self.search(mid + 1, hi, ends_after, starts_before, out);
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&Range<K>, &V)> {
+        self.entries.iter().map(|(r, v)| (r, v))
+    }
+}
+
+fn fill_max_end<K: Ord + Clone>(max_end: &mut [K], lo: usize, hi: usize) {
+    if lo >= hi {
+        return;
+    }
+    let mid = lo + (hi - lo) / 2;
+    fill_max_end(max_end, lo, mid);
+    fill_max_end(max_end, mid + 1, hi);
+    for child in [lo + (mid - lo) / 2, mid + 1 + (hi - mid - 1) / 2] {
+        let in_subtree = (lo..mid).contains(&child) || (mid + 1..hi).contains(&child);
+        if in_subtree && max_end[child] > max_end[mid] {
+            max_end[mid] = max_end[child].clone();
+        }
+    }
+}
+
+impl<K: Ord + Clone, V> FromIterator<(Range<K>, V)> for IntervalTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut entries: Vec<(Range<K>, V)> = iter.into_iter().collect();
+        entries.sort_by(|a, b| a.0.start.cmp(&b.0.start));
+        let mut tree = Self { entries, max_end: Vec::new() };
+        tree.rebuild_index();
+        tree
+    }
+}
+
+// --- Range map ---
+
+/// Non-overlapping ranges mapped to values. Inserting over existing ranges replaces the
+/// covered parts and keeps the rest, and touching ranges with equal values are merged, so
+/// the map always holds the fewest ranges that describe it.
+#[derive(Debug, Clone)]
+struct RangeMap<K, V> {
+    /// start -> (end, value)
+    map: BTreeMap<K, (K, V)>,
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> RangeMap<K, V> {
+    fn new() -> Self {
+        Self { map: BTreeMap::new() }
+    }
+
+    /// Number of stored (merged) ranges.
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// The value whose range contains `key`. O(log n).
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get_range(key).map(|(_, v)| v)
+    }
+
+    /// The range containing `key` and its value.
+    fn get_range(&self, key: &K) -> Option<(Range<K>, &V)> {
+        let (start, (end, value)) = self.map.range(..=key.clone()).next_back()?;
+        (end > key).then(|| (start.clone()..end.clone(), value))
+    }
+
+    /// Assigns `value` to every key in `range`, overwriting what was there.
+    fn insert(&mut self, range: Range<K>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+        self.remove(range.clone());
+        let Range { mut start, mut end } = range;
+
+        // Merge with a neighbour that touches this range and holds the same value.
+        let left = self.map.range(..start.clone()).next_back();
+        if let Some((left_start, (left_end, left_value))) = left {
+            if *left_end == start && *left_value == value {
+                start = left_start.clone();
+                self.map.remove(&start);
+            }
+        }
+        if let Some((right_end, right_value)) = self.map.get(&end) {
+            if *right_value == value {
+                let right_end = right_end.clone();
+                self.map.remove(&end);
+                end = right_end;
+            }
+        }
+        self.map.insert(start, (end, value));
+    }
+
+    /// Clears every key in `range`, splitting ranges that extend past either side.
+    fn remove(&mut self, range: Range<K>) {
+        if range.start >= range.end {
+            return;
+        }
+        // A range starting before `range` may reach into it.
+        let straddling = self.map.range(..range.start.clone()).next_back();
+        if let Some((start, (end, value))) = straddling {
+            if *end > range.start {
+                let (start, end, value) = (start.clone(), end.clone(), value.clone());
+                if end > range.end {
+                    self.map.insert(range.end.clone(), (end, value.clone()));
+                }
+                self.map.insert(start, (range.start.clone(), value));
+            }
+        }
+        // Ranges starting inside `range`: drop them, keeping any part past the end.
+        let inside: Vec<K> = self.map.range(range.start.clone()..range.end.clone()).map(|(k, _)| k.clone()).collect();
+        for start in inside {
+            if let Some((end, value)) = self.map.remove(&start) {
+                if end > range.end {
+                    self.map.insert(range.end.clone(), (end, value));
+                }
+            }
+        }
+    }
+
+    /// Stored ranges overlapping `query`, in order. Ranges are not clipped to `query`.
+    fn overlapping(&self, query: Range<K>) -> impl Iterator<Item = (Range<K>, &V)> {
+        // The range containing query.start (if any) starts before it; find where to begin.
+        let begin = self.get_range(&query.start).map_or(query.start.clone(), |(r, _)| r.start);
+        self.map
+            .range(begin..)
+            .take_while(move |(start, _)| **start < query.end)
+            .map(|(start, (end, value))| (start.clone()..end.clone(), value))
+    }
+
+    /// Parts of `within` no stored range covers.
+    fn gaps(&self, within: Range<K>) -> Vec<Range<K>> {
+        gaps(self.overlapping(within.clone()).map(|(r, _)| r), within)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Range<K>, &V)> {
+        self.map.iter().map(|(start, (end, value))| (start.clone()..end.clone(), value))
+    }
+}
+
+// Example Usage
+/*
+use std::net::Ipv4Addr;
+
+/// An IPv4 CIDR block as a half-open range of u64 (u32 would overflow at 255.255.255.255).
+fn cidr(net: &str) -> Range<u64> {
+    let (addr, bits) = net.split_once('/').expect("a.b.c.d/n");
+    let addr = u32::from(addr.parse::<Ipv4Addr>().expect("valid IPv4")) as u64;
+    let size = 1u64 << (32 - bits.parse::<u32>().expect("prefix length"));
+    let start = addr & !(size - 1);
+    start..start + size
+}
+
+fn ip(s: &str) -> u64 {
+    u32::from(s.parse::<Ipv4Addr>().unwrap()) as u64
+}
+
+fn main() {
+    // Busy time (minutes since midnight) -> merged blocks and free slots between 9:00 and 17:00.
+    let meetings = [540..600, 570..630, 630..660, 780..840, 900..960];
+    println!("busy: {:?}", merge_ranges(meetings.clone()));
+    println!("free: {:?}", gaps(meetings.clone(), 540..1020));
+    println!("double-booked: {:?}", overlapping_pairs(&meetings));
+
+    // Interval tree: which bookings overlap a proposed slot, and who is in the room at 10:15?
+    let bookings: IntervalTree<u32, &str> =
+        [(540..600, "standup"), (570..630, "design review"), (780..840, "1:1"), (600..720, "workshop")]
+            .into_iter()
+            .collect();
+    println!("{} bookings", bookings.len());
+    println!("overlaps 9:45-10:30: {:?}", bookings.overlapping(&(585..630)));
+    println!("at 10:15: {:?}", bookings.containing(&615));
+
+    // Range map: IP ranges -> network names; later, more specific inserts carve out holes.
+    let mut networks = RangeMap::new();
+    networks.insert(cidr("10.0.0.0/8"), "corp");
+    networks.insert(cidr("10.20.0.0/16"), "lab");
+    networks.insert(cidr("192.168.0.0/16"), "home");
+    networks.insert(cidr("10.21.0.0/16"), "lab"); // Touches 10.20/16 with the same value: merged.
+    for addr in ["10.1.2.3", "10.20.5.5", "10.21.0.1", "192.168.1.10", "8.8.8.8"] {
+        println!("{:<13} -> {:?}", addr, networks.get(&ip(addr)));
+    }
+    for (range, name) in networks.iter() {
+        println!("{:>15} - {:<15} {}", Ipv4Addr::from(range.start as u32), Ipv4Addr::from((range.end - 1) as u32), name);
+    }
+    networks.remove(cidr("10.20.0.0/15"));
+    println!("after removing 10.20.0.0/15: {} ranges, 10.20.5.5 -> {:?}", networks.len(), networks.get(&ip("10.20.5.5")));
+    for gap in networks.gaps(cidr("10.0.0.0/8")) {
+        println!("unassigned: {} - {}", Ipv4Addr::from(gap.start as u32), Ipv4Addr::from((gap.end - 1) as u32));
+    }
+}
+*/
@@ -0,0 +1,919 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// rusqlite = { version = "0.32", features = ["bundled"] }
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+// clap = { version = "4.0", features = ["derive"] } // Only for the `jobs` admin CLI
+// redis = "0.27" // Only for `RedisJobStore`
+//
+// A persistent background job queue with at-least-once execution:
+// * A job is a serde payload plus a type tag (`Job::TYPE`). `JobQueue::enqueue` stores it in a
+//   `JobStore`: `SqliteJobStore` (one table; fine for a single host) or `RedisJobStore` (sorted
+//   sets plus a hash per job; for workers on many hosts).
+// * Workers claim a job with a visibility timeout. If the worker crashes, the job becomes
+//   claimable again once the timeout passes, so every job runs at least once. Handlers must
+//   therefore be idempotent, and the timeout must exceed the longest run.
+// * A failed (or panicking) handler schedules a retry with exponential backoff. After
+//   `max_attempts` the job is dead-lettered and stays put until someone requeues it.
+// * `JobsCommand` is a clap subcommand (`stats`, `list`, `show`, `requeue`) for inspecting the
+//   queue and requeueing dead jobs from an admin binary.
+
+use clap::Subcommand;
+use redis::ConnectionLike;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+enum JobError {
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("Payload (de)serialization failed: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("Corrupt job record {0}: {1}")]
+    Corrupt(i64, String),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+}
+
+type Result<T> = std::result::Result<T, JobError>;
+
+// --- Jobs ---
+
+/// A kind of background work. `TYPE` is stored with each job and picks its handler, so keep
+/// it stable across deploys even if the Rust type is renamed.
+trait Job: Serialize + DeserializeOwned {
+    const TYPE: &'static str;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum JobState {
+    /// Waiting for `run_at`, then for a worker.
+    Queued,
+    /// Claimed by a worker until `lease_until`.
+    Running,
+    Done,
+    /// Out of attempts; only `requeue` brings it back.
+    Dead,
+}
+
+impl JobState {
+    const ALL: [JobState; 4] = [JobState::Queued, JobState::Running, JobState::Done, JobState::Dead];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Done => "done",
+            JobState::Dead => "dead",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|state| state.as_str() == s)
+    }
+}
+
+impl fmt::Display for JobState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// A stored job, as the store and the admin CLI see it.
+#[derive(Debug, Clone)]
+struct JobRecord {
+    id: i64,
+    job_type: String,
+    /// The job serialized as JSON.
+    payload: String,
+    state: JobState,
+    /// Claims so far, including the current one. Doubles as the lease token: a worker may only
+    /// finish the job if `attempts` still matches the value it claimed it with.
+    attempts: u32,
+    max_attempts: u32,
+    /// Unix milliseconds: when a queued job becomes claimable.
+    run_at_ms: i64,
+    /// Unix milliseconds: when a running job's claim expires.
+    lease_until_ms: i64,
+    last_error: Option<String>,
+    created_at_ms: i64,
+}
+
+/// Per-job settings for `JobQueue::enqueue_with`.
+#[derive(Debug, Clone)]
+struct EnqueueOptions {
+    max_attempts: u32,
+    /// Run no earlier than this far in the future.
+    delay: Duration,
+}
+
+impl Default for EnqueueOptions {
+    fn default() -> Self {
+        Self { max_attempts: 5, delay: Duration::ZERO }
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0)
+}
+
+// --- Storage ---
+
+/// Persistence for jobs. Every method must be atomic in the backing store, so several
+/// workers (threads or processes) can share one store.
+trait JobStore: Send + Sync {
+    fn insert(&self, job_type: &str, payload: &str, options: &EnqueueOptions) -> Result<i64>;
+
+    /// Claims the next due job, marking it running for `visibility` and counting an attempt.
+    /// Running jobs whose lease has expired count as due; if one has no attempts left, it is
+    /// dead-lettered instead.
+    fn claim(&self, visibility: Duration) -> Result<Option<JobRecord>>;
+
+    /// Marks a claimed job done. `false` if the claim was lost (the lease expired and another
+    /// worker took the job), in which case nothing changes.
+    fn complete(&self, id: i64, attempt: u32) -> Result<bool>;
+
+    /// Records a failure: back to the queue at `retry_at_ms`, or dead-lettered if `None`.
+    fn fail(&self, id: i64, attempt: u32, error: &str, retry_at_ms: Option<i64>) -> Result<bool>;
+
+    fn get(&self, id: i64) -> Result<Option<JobRecord>>;
+
+    /// Jobs in `state`, oldest first.
+    fn list(&self, state: JobState, limit: usize) -> Result<Vec<JobRecord>>;
+
+    fn counts(&self) -> Result<HashMap<JobState, u64>>;
+
+    /// Moves a dead job back to the queue with a fresh set of attempts. `false` if it isn't dead.
+    fn requeue(&self, id: i64) -> Result<bool>;
+}
+
+const JOBS_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    state TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL,
+    run_at_ms INTEGER NOT NULL,
+    lease_until_ms INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at_ms INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS jobs_due ON jobs (state, run_at_ms);
+";
+
+const JOB_COLUMNS: &str =
+    "id, job_type, payload, state, attempts, max_attempts, run_at_ms, lease_until_ms, last_error, created_at_ms";
+
+/// Jobs in a SQLite table. Several processes may share the file; SQLite serializes the writes.
+struct SqliteJobStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteJobStore {
+    fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(JOBS_SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<JobRecord> {
+        let id: i64 = row.get(0)?;
+        let state: String = row.get(3)?;
+        Ok(JobRecord {
+            id,
+            job_type: row.get(1)?,
+            payload: row.get(2)?,
+            state: JobState::parse(&state).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, format!("bad state {:?}", state).into())
+            })?,
+            attempts: row.get(4)?,
+            max_attempts: row.get(5)?,
+            run_at_ms: row.get(6)?,
+            lease_until_ms: row.get(7)?,
+            last_error: row.get(8)?,
+            created_at_ms: row.get(9)?,
+        })
+    }
+}
+
+impl JobStore for SqliteJobStore {
+    fn insert(&self, job_type: &str, payload: &str, options: &EnqueueOptions) -> Result<i64> {
+        let now = now_ms();
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO jobs (job_type, payload, state, max_attempts, run_at_ms, created_at_ms)
+             VALUES (?1, ?2, 'queued', ?3, ?4, ?5)",
+            params![job_type, payload, options.max_attempts, now + options.delay.as_millis() as i64, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    fn claim(&self, visibility: Duration) -> Result<Option<JobRecord>> {
+        let now = now_ms();
+        let conn = self.conn();
+        conn.execute(
+            "UPDATE jobs SET state = 'dead', last_error = 'visibility timeout expired on the last attempt'
+             WHERE state = 'running' AND lease_until_ms <= ?1 AND attempts >= max_attempts",
+            params![now],
+        )?;
+        // One statement, so two processes can't claim the same row.
+        let sql = format!(
+            "UPDATE jobs SET state = 'running', attempts = attempts + 1, lease_until_ms = ?2
+             WHERE id = (
+                 SELECT id FROM jobs
+                 WHERE (state = 'queued' AND run_at_ms <= ?1) OR (state = 'running' AND lease_until_ms <= ?1)
+                 ORDER BY run_at_ms, id LIMIT 1
+             )
+             RETURNING {}",
+            JOB_COLUMNS
+        );
+        let job = conn
+            .query_row(&sql, params![now, now + visibility.as_millis() as i64], Self::from_row)
+            .optional()?;
+        Ok(job)
+    }
+
+    fn complete(&self, id: i64, attempt: u32) -> Result<bool> {
+        let changed = self.conn().execute(
+            "UPDATE jobs SET state = 'done', last_error = NULL WHERE id = ?1 AND attempts = ?2 AND state = 'running'",
+            params![id, attempt],
+        )?;
+        Ok(changed == 1)
+    }
+
+    fn fail(&self, id: i64, attempt: u32, error: &str, retry_at_ms: Option<i64>) -> Result<bool> {
+        let state = if retry_at_ms.is_some() { JobState::Queued } else { JobState::Dead };
+        let changed = self.conn().execute(
+            "UPDATE jobs SET state = ?3, last_error = ?4, run_at_ms = COALESCE(?5, run_at_ms)
+             WHERE id = ?1 AND attempts = ?2 AND state = 'running'",
+            params![id, attempt, state.as_str(), error, retry_at_ms],
+        )?;
+        Ok(changed == 1)
+    }
+
+    fn get(&self, id: i64) -> Result<Option<JobRecord>> {
+        let sql = format!("SELECT {} FROM jobs WHERE id = ?1", JOB_COLUMNS);
+        Ok(self.conn().query_row(&sql, params![id], Self::from_row).optional()?)
+    }
+
+    fn list(&self, state: JobState, limit: usize) -> Result<Vec<JobRecord>> {
+        let sql = format!("SELECT {} FROM jobs WHERE state = ?1 ORDER BY run_at_ms, id LIMIT ?2", JOB_COLUMNS);
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&sql)?;
+        let jobs = stmt.query_map(params![state.as_str(), i64::try_from(limit).unwrap_or(i64::MAX)], Self::from_row)?.collect::<rusqlite::Result<_>>()?;
+        Ok(jobs)
+    }
+
+    fn counts(&self) -> Result<HashMap<JobState, u64>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT state, COUNT(*) FROM jobs GROUP BY state")?;
+        let mut counts: HashMap<JobState, u64> = JobState::ALL.into_iter().map(|s| (s, 0)).collect();
+        for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))? {
+            let (state, count) = row?;
+            if let Some(state) = JobState::parse(&state) {
+                counts.insert(state, count as u64);
+            }
+        }
+        Ok(counts)
+    }
+
+    fn requeue(&self, id: i64) -> Result<bool> {
+        let changed = self.conn().execute(
+            "UPDATE jobs SET state = 'queued', attempts = 0, run_at_ms = ?2 WHERE id = ?1 AND state = 'dead'",
+            params![id, now_ms()],
+        )?;
+        Ok(changed == 1)
+    }
+}
+
+/// Claims the earliest due job. Expired leases are first returned to the queue, or
+/// dead-lettered if they were the last attempt. KEYS: queued, running, dead (sorted sets).
+/// ARGV: now, visibility (ms), job hash key prefix.
+const REDIS_CLAIM: &str = r"
+local now = tonumber(ARGV[1])
+for _, id in ipairs(redis.call('ZRANGEBYSCORE', KEYS[2], '-inf', now, 'LIMIT', 0, 100)) do
+    local key = ARGV[3] .. id
+    redis.call('ZREM', KEYS[2], id)
+    if tonumber(redis.call('HGET', key, 'attempts')) >= tonumber(redis.call('HGET', key, 'max_attempts')) then
+        redis.call('HSET', key, 'state', 'dead', 'last_error', 'visibility timeout expired on the last attempt')
+        redis.call('ZADD', KEYS[3], now, id)
+    else
+        redis.call('HSET', key, 'state', 'queued', 'last_error', 'visibility timeout expired')
+        redis.call('ZADD', KEYS[1], now, id)
+    end
+end
+local ids = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', now, 'LIMIT', 0, 1)
+if #ids == 0 then
+    return false
+end
+local id = ids[1]
+local lease_until = now + tonumber(ARGV[2])
+redis.call('ZREM', KEYS[1], id)
+redis.call('ZADD', KEYS[2], lease_until, id)
+redis.call('HINCRBY', ARGV[3] .. id, 'attempts', 1)
+redis.call('HSET', ARGV[3] .. id, 'state', 'running', 'lease_until_ms', lease_until)
+return id
+";
+
+/// Moves a running job to a new state if the caller still holds the claim.
+/// KEYS: running, target sorted set, job hash. ARGV: attempt, state, score, error, run_at.
+const REDIS_FINISH: &str = r"
+if redis.call('HGET', KEYS[3], 'state') ~= 'running' or redis.call('HGET', KEYS[3], 'attempts') ~= ARGV[1] then
+    return 0
+end
+local id = string.match(KEYS[3], '(%d+)$')
+redis.call('ZREM', KEYS[1], id)
+redis.call('ZADD', KEYS[2], tonumber(ARGV[3]), id)
+redis.call('HSET', KEYS[3], 'state', ARGV[2], 'last_error', ARGV[4], 'run_at_ms', ARGV[5])
+return 1
+";
+
+/// KEYS: dead, queued, job hash. ARGV: now.
+const REDIS_REQUEUE: &str = r"
+local id = string.match(KEYS[3], '(%d+)$')
+if redis.call('ZREM', KEYS[1], id) == 0 then
+    return 0
+end
+redis.call('ZADD', KEYS[2], tonumber(ARGV[1]), id)
+redis.call('HSET', KEYS[3], 'state', 'queued', 'attempts', 0, 'run_at_ms', ARGV[1])
+return 1
+";
+
+/// Jobs in Redis under `prefix`: a hash per job (`{prefix}:job:{id}`) and one sorted set per
+/// state, scored by run time (queued), lease expiry (running), or finish time (done, dead).
+/// The scripts build job keys at runtime, so on Redis Cluster put the prefix in a hash tag,
+/// e.g. `{jobs}`, to keep every key on one shard.
+struct RedisJobStore {
+    client: redis::Client,
+    /// Connections not in use. Calls reuse one of these instead of connecting every time.
+    idle: Mutex<Vec<redis::Connection>>,
+    prefix: String,
+    /// How many finished jobs to keep for inspection; older ones are deleted.
+    keep_done: isize,
+}
+
+impl RedisJobStore {
+    fn new(client: redis::Client, prefix: &str) -> Self {
+        Self { client, idle: Mutex::new(Vec::new()), prefix: prefix.to_string(), keep_done: 1000 }
+    }
+
+    fn set_key(&self, state: JobState) -> String {
+        format!("{}:{}", self.prefix, state)
+    }
+
+    fn job_key(&self, id: i64) -> String {
+        format!("{}:job:{}", self.prefix, id)
+    }
+
+    /// An idle connection, or a new one if every connection is in use.
+    fn conn(&self) -> Result<PooledConnection<'_>> {
+        let idle = self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => self.client.get_connection()?,
+        };
+        Ok(PooledConnection { store: self, conn: Some(conn) })
+    }
+
+    fn parse(id: i64, fields: HashMap<String, String>) -> Result<JobRecord> {
+        let text = |name: &str| fields.get(name).cloned().ok_or_else(|| JobError::Corrupt(id, format!("missing {}", name)));
+        let number = |name: &str| -> Result<i64> {
+            text(name)?.parse().map_err(|_| JobError::Corrupt(id, format!("bad {}", name)))
+        };
+        let state = text("state")?;
+        Ok(JobRecord {
+            id,
+            job_type: text("job_type")?,
+            payload: text("payload")?,
+            state: JobState::parse(&state).ok_or_else(|| JobError::Corrupt(id, format!("bad state {:?}", state)))?,
+            attempts: number("attempts")? as u32,
+            max_attempts: number("max_attempts")? as u32,
+            run_at_ms: number("run_at_ms")?,
+            lease_until_ms: number("lease_until_ms").unwrap_or(0),
+            last_error: fields.get("last_error").filter(|e| !e.is_empty()).cloned(),
+            created_at_ms: number("created_at_ms")?,
+        })
+    }
+
+    fn finish(&self, id: i64, attempt: u32, state: JobState, score: i64, error: &str, run_at_ms: i64) -> Result<bool> {
+        let mut conn = self.conn()?;
+        let moved: i64 = redis::Script::new(REDIS_FINISH)
+            .key(self.set_key(JobState::Running))
+            .key(self.set_key(state))
+            .key(self.job_key(id))
+            .arg(attempt)
+            .arg(state.as_str())
+            .arg(score)
+            .arg(error)
+            .arg(run_at_ms)
+            .invoke(&mut *conn)?;
+        if moved == 1 && state == JobState::Done {
+            let old: Vec<i64> = redis::cmd("ZRANGE").arg(self.set_key(JobState::Done)).arg(0).arg(-self.keep_done - 1).query(&mut *conn)?;
+            for old_id in old {
+                let _: () = redis::pipe()
+                    .cmd("ZREM").arg(self.set_key(JobState::Done)).arg(old_id).ignore()
+                    .cmd("DEL").arg(self.job_key(old_id)).ignore()
+                    .query(&mut *conn)?;
+            }
+        }
+        Ok(moved == 1)
+    }
+}
+
+/// A connection borrowed from `RedisJobStore::idle`. It goes back on drop, unless an I/O error
+/// has closed it.
+struct PooledConnection<'a> {
+    store: &'a RedisJobStore,
+    conn: Option<redis::Connection>,
+}
+
+impl std::ops::Deref for PooledConnection<'_> {
+    type Target = redis::Connection;
+
+    fn deref(&self) -> &redis::Connection {
+        self.conn.as_ref().expect("present until dropped")
+    }
+}
+
+impl std::ops::DerefMut for PooledConnection<'_> {
+    fn deref_mut(&mut self) -> &mut redis::Connection {
+        self.conn.as_mut().expect("present until dropped")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take().filter(|conn| conn.is_open()) {
+            self.store.idle.lock().unwrap_or_else(|e| e.into_inner()).push(conn);
+        }
+    }
+}
+
+impl JobStore for RedisJobStore {
+    fn insert(&self, job_type: &str, payload: &str, options: &EnqueueOptions) -> Result<i64> {
+        let mut conn = self.conn()?;
+        let now = now_ms();
+        let run_at = now + options.delay.as_millis() as i64;
+        let id: i64 = redis::cmd("INCR").arg(format!("{}:seq", self.prefix)).query(&mut *conn)?;
+        let _: () = redis::pipe()
+            .atomic()
+            .hset_multiple(
+                self.job_key(id),
+                &[
+                    ("job_type", job_type.to_string()),
+                    ("payload", payload.to_string()),
+                    ("state", JobState::Queued.as_str().to_string()),
+                    ("attempts", "0".to_string()),
+                    ("max_attempts", options.max_attempts.to_string()),
+                    ("run_at_ms", run_at.to_string()),
+                    ("created_at_ms", now.to_string()),
+                ],
+            )
+            .ignore()
+            .zadd(self.set_key(JobState::Queued), id, run_at)
+            .ignore()
+            .query(&mut *conn)?;
+        Ok(id)
+    }
+
+    fn claim(&self, visibility: Duration) -> Result<Option<JobRecord>> {
+        let mut conn = self.conn()?;
+        let id: Option<i64> = redis::Script::new(REDIS_CLAIM)
+            .key(self.set_key(JobState::Queued))
+            .key(self.set_key(JobState::Running))
+            .key(self.set_key(JobState::Dead))
+            .arg(now_ms())
+            .arg(visibility.as_millis() as i64)
+            .arg(format!("{}:job:", self.prefix))
+            .invoke(&mut *conn)?;
+        match id {
+            Some(id) => self.get(id),
+            None => Ok(None),
+        }
+    }
+
+    fn complete(&self, id: i64, attempt: u32) -> Result<bool> {
+        let now = now_ms();
+        self.finish(id, attempt, JobState::Done, now, "", now)
+    }
+
+    fn fail(&self, id: i64, attempt: u32, error: &str, retry_at_ms: Option<i64>) -> Result<bool> {
+        match retry_at_ms {
+            Some(at) => self.finish(id, attempt, JobState::Queued, at, error, at),
+            None => {
+                let now = now_ms();
+                self.finish(id, attempt, JobState::Dead, now, error, now)
+            }
+        }
+    }
+
+    fn get(&self, id: i64) -> Result<Option<JobRecord>> {
+        let fields: HashMap<String, String> = redis::cmd("HGETALL").arg(self.job_key(id)).query(&mut *self.conn()?)?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        Self::parse(id, fields).map(Some)
+    }
+
+    fn list(&self, state: JobState, limit: usize) -> Result<Vec<JobRecord>> {
+        // ZRANGE's stop index is inclusive, and a stop of -1 means "to the end".
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let stop = isize::try_from(limit).unwrap_or(isize::MAX) - 1;
+        let ids: Vec<i64> = redis::cmd("ZRANGE").arg(self.set_key(state)).arg(0).arg(stop).query(&mut *self.conn()?)?;
+        ids.into_iter().filter_map(|id| self.get(id).transpose()).collect()
+    }
+
+    fn counts(&self) -> Result<HashMap<JobState, u64>> {
+        let mut conn = self.conn()?;
+        JobState::ALL
+            .into_iter()
+            .map(|state| Ok((state, redis::cmd("ZCARD").arg(self.set_key(state)).query(&mut *conn)?)))
+            .collect()
+    }
+
+    fn requeue(&self, id: i64) -> Result<bool> {
+        let moved: i64 = redis::Script::new(REDIS_REQUEUE)
+            .key(self.set_key(JobState::Dead))
+            .key(self.set_key(JobState::Queued))
+            .key(self.job_key(id))
+            .arg(now_ms())
+            .invoke(&mut *self.conn()?)?;
+        Ok(moved == 1)
+    }
+}
+
+// --- Queue and workers ---
+
+/// The producer-side handle: typed enqueueing on top of a store.
+#[derive(Clone)]
+struct JobQueue {
+    store: Arc<dyn JobStore>,
+}
+
+impl JobQueue {
+    fn new(store: impl JobStore + 'static) -> Self {
+        Self { store: Arc::new(store) }
+    }
+
+    /// Stores `job` to run as soon as a worker is free. Returns its id.
+    fn enqueue<J: Job>(&self, job: &J) -> Result<i64> {
+        self.enqueue_with(job, &EnqueueOptions::default())
+    }
+
+    fn enqueue_with<J: Job>(&self, job: &J, options: &EnqueueOptions) -> Result<i64> {
+        let payload = serde_json::to_string(job)?;
+        self.store.insert(J::TYPE, &payload, options)
+    }
+}
+
+type Handler = Box<dyn Fn(&str) -> std::result::Result<(), String> + Send + Sync>;
+
+/// What happened to one claimed job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RunOutcome {
+    Completed { id: i64 },
+    Retrying { id: i64, attempt: u32, delay: Duration },
+    DeadLettered { id: i64, error: String },
+    /// The lease expired mid-run and another worker owns the job now; the result was dropped.
+    LostClaim { id: i64 },
+}
+
+/// Claims jobs and dispatches them to handlers by type tag.
+struct Worker {
+    store: Arc<dyn JobStore>,
+    handlers: HashMap<&'static str, Handler>,
+    visibility: Duration,
+    backoff_base: Duration,
+    backoff_max: Duration,
+    poll_interval: Duration,
+}
+
+impl Worker {
+    fn new(queue: &JobQueue) -> Self {
+        Self {
+            store: Arc::clone(&queue.store),
+            handlers: HashMap::new(),
+            visibility: Duration::from_secs(300),
+            backoff_base: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(3600),
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+
+    /// Registers the handler for jobs of type `J`. An `Err` (or a panic) counts as a failed attempt.
+    fn handle<J: Job + 'static>(mut self, handler: impl Fn(J) -> std::result::Result<(), String> + Send + Sync + 'static) -> Self {
+        self.handlers.insert(
+            J::TYPE,
+            Box::new(move |payload| {
+                let job: J = serde_json::from_str(payload).map_err(|e| format!("bad payload: {}", e))?;
+                handler(job)
+            }),
+        );
+        self
+    }
+
+    /// How long a claim lasts; longer than any handler run, or the job runs twice.
+    fn visibility(mut self, visibility: Duration) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Retry delays: `base`, `2 * base`, `4 * base`, ... up to `max`.
+    fn backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.backoff_base = base;
+        self.backoff_max = max;
+        self
+    }
+
+    /// How long to sleep when the queue is empty.
+    fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.backoff_base.saturating_mul(factor).min(self.backoff_max)
+    }
+
+    /// Claims and runs one job.
+    ///
+    /// # Returns
+    ///
+    /// `None` if nothing was due.
+    fn run_once(&self) -> Result<Option<RunOutcome>> {
+        let Some(job) = self.store.claim(self.visibility)? else { return Ok(None) };
+        let result = match self.handlers.get(job.job_type.as_str()) {
+            // Retry rather than dead-letter: a newer worker version may know the type.
+            None => Err(format!("no handler registered for job type {:?}", job.job_type)),
+            Some(handler) => panic::catch_unwind(AssertUnwindSafe(|| handler(&job.payload))).unwrap_or_else(|panic| {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                Err(format!("handler panicked: {}", message))
+            }),
+        };
+
+        let outcome = match result {
+            Ok(()) if self.store.complete(job.id, job.attempts)? => RunOutcome::Completed { id: job.id },
+            Err(error) if job.attempts >= job.max_attempts => {
+                if !self.store.fail(job.id, job.attempts, &error, None)? {
+                    return Ok(Some(RunOutcome::LostClaim { id: job.id }));
+                }
+                RunOutcome::DeadLettered { id: job.id, error }
+            }
+            Err(error) => {
+                let delay = self.retry_delay(job.attempts);
+                if !self.store.fail(job.id, job.attempts, &error, Some(now_ms() + delay.as_millis() as i64))? {
+                    return Ok(Some(RunOutcome::LostClaim { id: job.id }));
+                }
+                RunOutcome::Retrying { id: job.id, attempt: job.attempts, delay }
+            }
+            Ok(()) => RunOutcome::LostClaim { id: job.id },
+        };
+        Ok(Some(outcome))
+    }
+
+    /// Runs jobs until `shutdown` is set, sleeping `poll_interval` whenever the queue is empty.
+    /// A job in progress finishes before the loop exits. Store errors are logged and retried.
+    fn run(&self, shutdown: &AtomicBool, mut on_outcome: impl FnMut(&RunOutcome)) {
+        while !shutdown.load(Ordering::Relaxed) {
+            match self.run_once() {
+                Ok(Some(outcome)) => on_outcome(&outcome),
+                Ok(None) => thread::sleep(self.poll_interval),
+                Err(e) => {
+                    eprintln!("job worker: {}", e);
+                    thread::sleep(self.poll_interval);
+                }
+            }
+        }
+    }
+}
+
+// --- Admin CLI ---
+
+/// Queue administration; embed as a subcommand, e.g. `myapp jobs list --state dead`.
+#[derive(Subcommand, Debug)]
+enum JobsCommand {
+    /// Count jobs in each state.
+    Stats,
+    /// List jobs in one state, oldest first.
+    List {
+        #[arg(long, default_value = "queued", value_parser = parse_state)]
+        state: JobState,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Show one job, including its payload and last error.
+    Show { id: i64 },
+    /// Move dead jobs back to the queue with fresh attempts.
+    Requeue {
+        ids: Vec<i64>,
+        /// Requeue every dead job.
+        #[arg(long, conflicts_with = "ids")]
+        all_dead: bool,
+    },
+}
+
+fn parse_state(s: &str) -> std::result::Result<JobState, String> {
+    JobState::parse(s).ok_or_else(|| format!("expected one of: queued, running, done, dead (got {:?})", s))
+}
+
+/// Formats Unix milliseconds as seconds relative to now, e.g. `in 30s` or `12s ago`.
+fn relative(ms: i64) -> String {
+    let delta = (ms - now_ms()) / 1000;
+    if delta >= 0 {
+        format!("in {}s", delta)
+    } else {
+        format!("{}s ago", -delta)
+    }
+}
+
+/// Runs one admin command against `store`, writing a human-readable report to `out`.
+fn run_jobs_command(store: &dyn JobStore, command: &JobsCommand, out: &mut impl Write) -> Result<()> {
+    match command {
+        JobsCommand::Stats => {
+            let counts = store.counts()?;
+            for state in JobState::ALL {
+                writeln!(out, "{:<8} {}", state, counts.get(&state).copied().unwrap_or(0))?;
+            }
+        }
+        JobsCommand::List { state, limit } => {
+            let jobs = store.list(*state, *limit)?;
+            writeln!(out, "{:>6}  {:<16} {:>8}  {:<12} last error", "id", "type", "attempts", "run at")?;
+            for job in &jobs {
+                let attempts = format!("{}/{}", job.attempts, job.max_attempts);
+                let error = job.last_error.as_deref().unwrap_or("-");
+                writeln!(out, "{:>6}  {:<16} {:>8}  {:<12} {}", job.id, job.job_type, attempts, relative(job.run_at_ms), error)?;
+            }
+            writeln!(out, "({} {} jobs shown)", jobs.len(), state)?;
+        }
+        JobsCommand::Show { id } => match store.get(*id)? {
+            None => writeln!(out, "job {} not found", id)?,
+            Some(job) => {
+                writeln!(out, "id:         {}", job.id)?;
+                writeln!(out, "type:       {}", job.job_type)?;
+                writeln!(out, "state:      {}", job.state)?;
+                writeln!(out, "attempts:   {}/{}", job.attempts, job.max_attempts)?;
+                writeln!(out, "created:    {}", relative(job.created_at_ms))?;
+                writeln!(out, "run at:     {}", relative(job.run_at_ms))?;
+                if job.state == JobState::Running {
+                    writeln!(out, "lease ends: {}", relative(job.lease_until_ms))?;
+                }
+                writeln!(out, "last error: {}", job.last_error.as_deref().unwrap_or("-"))?;
+                writeln!(out, "payload:    {}", job.payload)?;
+            }
+        },
+        JobsCommand::Requeue { ids, all_dead } => {
+            let ids: Vec<i64> = if *all_dead {
+                store.list(JobState::Dead, usize::MAX >> 1)?.into_iter().map(|job| job.id).collect()
+            } else {
+                ids.clone()
+            };
+            for id in ids {
+                let verdict = if store.requeue(id)? { "requeued" } else { "not dead; skipped" };
+                writeln!(out, "job {}: {}", id, verdict)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Example Usage
+/*
+use clap::Parser;
+use serde::Deserialize;
+use std::sync::atomic::AtomicU32;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct SendWelcomeEmail {
+    user_id: u64,
+    email: String,
+}
+
+impl Job for SendWelcomeEmail {
+    const TYPE: &'static str = "send_welcome_email";
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ResizeImage {
+    path: String,
+}
+
+impl Job for ResizeImage {
+    const TYPE: &'static str = "resize_image";
+}
+
+/// Stands in for the app's own CLI, with the queue admin commands under `jobs`.
+#[derive(Parser)]
+enum AdminCli {
+    #[command(subcommand)]
+    Jobs(JobsCommand),
+}
+
+fn admin(store: &dyn JobStore, args: &[&str]) -> Result<()> {
+    println!("$ myapp {}", args.join(" "));
+    let AdminCli::Jobs(command) = AdminCli::parse_from(std::iter::once("myapp").chain(args.iter().copied()));
+    run_jobs_command(store, &command, &mut io::stdout())
+}
+
+fn main() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("jobs-{}.db", std::process::id()));
+    let queue = JobQueue::new(SqliteJobStore::open(&path)?);
+
+    for user_id in 1..=4 {
+        queue.enqueue(&SendWelcomeEmail { user_id, email: format!("user{}@example.com", user_id) })?;
+    }
+    queue.enqueue_with(&ResizeImage { path: "corrupt.png".into() }, &EnqueueOptions { max_attempts: 3, ..Default::default() })?;
+    queue.enqueue_with(&ResizeImage { path: "later.png".into() }, &EnqueueOptions { delay: Duration::from_secs(3600), ..Default::default() })?;
+    admin(queue.store.as_ref(), &["jobs", "stats"])?;
+
+    // Two worker threads. The mail server flakes on the first try for user 2.
+    let flaky = Arc::new(AtomicU32::new(0));
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let workers: Vec<_> = (0..2)
+        .map(|n| {
+            let flaky = Arc::clone(&flaky);
+            let worker = Worker::new(&queue)
+                .backoff(Duration::from_millis(50), Duration::from_secs(1))
+                .poll_interval(Duration::from_millis(20))
+                .handle(move |job: SendWelcomeEmail| {
+                    if job.user_id == 2 && flaky.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return Err("SMTP 421: try again later".to_string());
+                    }
+                    println!("  worker {} emailed {}", n, job.email);
+                    Ok(())
+                })
+                .handle(|job: ResizeImage| {
+                    if job.path.starts_with("corrupt") {
+                        panic!("invalid PNG header in {}", job.path);
+                    }
+                    Ok(())
+                });
+            let shutdown = Arc::clone(&shutdown);
+            thread::spawn(move || worker.run(&shutdown, |outcome| println!("  worker {}: {:?}", n, outcome)))
+        })
+        .collect();
+
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {})); // The panics are reported as job errors instead.
+    thread::sleep(Duration::from_millis(600));
+    shutdown.store(true, Ordering::Relaxed);
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    panic::set_hook(previous_hook);
+
+    // A worker that crashed mid-job: the claim expires and the job runs again.
+    let id = queue.enqueue(&SendWelcomeEmail { user_id: 9, email: "user9@example.com".into() })?;
+    let crashed = queue.store.claim(Duration::from_millis(50))?.expect("job 9 is due");
+    println!("claimed job {} and crashed", crashed.id);
+    thread::sleep(Duration::from_millis(60));
+    let reclaimed = queue.store.claim(Duration::from_secs(30))?.expect("lease expired");
+    println!("job {} reclaimed, attempt {}", reclaimed.id, reclaimed.attempts);
+    println!("stale worker may complete it: {}", queue.store.complete(id, crashed.attempts)?);
+    println!("new owner completes it: {}", queue.store.complete(id, reclaimed.attempts)?);
+
+    admin(queue.store.as_ref(), &["jobs", "stats"])?;
+    admin(queue.store.as_ref(), &["jobs", "list", "--state", "dead"])?;
+    let dead = queue.store.list(JobState::Dead, 1)?[0].id;
+    admin(queue.store.as_ref(), &["jobs", "show", &dead.to_string()])?;
+    admin(queue.store.as_ref(), &["jobs", "requeue", "--all-dead"])?;
+    admin(queue.store.as_ref(), &["jobs", "list", "--state", "queued"])?;
+
+    // With Redis, only the store changes:
+    // let queue = JobQueue::new(RedisJobStore::new(redis::Client::open("redis://127.0.0.1/")?, "jobs"));
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+*/
@@ -3,8 +3,10 @@
 // serde = { version = "1.0", features = ["derive"] }
 // serde_json = "1.0"
 
+use serde::de::{DeserializeOwned, Error as _};
 use serde::{Deserialize, Serialize};
 use serde_json::{Result as JsonResult, Value};
+use std::collections::BTreeMap;
 
 /// A struct demonstrating serialization and deserialization.
 #[derive(Serialize, Deserialize, Debug, PartialEq)] // PartialEq for easy comparison in example
@@ -33,12 +35,78 @@ fn serialize_struct_to_json_value<T: Serialize>(data: &T) -> JsonResult<Value> {
 }
 
 /// Deserializes a `serde_json::Value` into a Rust struct.
-fn deserialize_json_value_to_struct<T: Deserialize<'static>>(json_value: Value) -> JsonResult<T> {
-    // Note: If the Value contains borrowed data (&str), this might need lifetime adjustments.
-    // Using Deserialize<'static> is common when the Value owns its data.
+fn deserialize_json_value_to_struct<T: DeserializeOwned>(json_value: Value) -> JsonResult<T> {
+    // A Value owns its data, so the target can't borrow from it: `T` must be `DeserializeOwned`.
     serde_json::from_value(json_value)
 }
 
+// --- Versioned envelopes ---
+
+/// A payload tagged with the schema version it was written with: `{"version": 3, "data": {...}}`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Versioned<T> {
+    version: u32,
+    data: T,
+}
+
+/// Rewrites the `data` of one version into the shape of the next.
+type UpgradeFn = fn(Value) -> JsonResult<Value>;
+
+/// The current schema version of a type and the upgrade steps that lead to it. Upgrades run
+/// on every read.
+struct SchemaVersions {
+    current: u32,
+    upgrades: BTreeMap<u32, UpgradeFn>,
+}
+
+impl SchemaVersions {
+    fn new(current: u32) -> Self {
+        SchemaVersions { current, upgrades: BTreeMap::new() }
+    }
+
+    /// Registers the step from version `from` to `from + 1`.
+    fn upgrade(mut self, from: u32, step: UpgradeFn) -> Self {
+        self.upgrades.insert(from, step);
+        self
+    }
+}
+
+/// Serializes `data` inside an envelope stamped with the current version.
+fn serialize_versioned<T: Serialize>(data: &T, versions: &SchemaVersions) -> JsonResult<String> {
+    serde_json::to_string_pretty(&Versioned { version: versions.current, data })
+}
+
+/// Deserializes a payload written with any known version, running the upgrade steps in order
+/// (v1 -> v2 -> v3 ...) before converting it to `T`. A payload without an envelope is treated
+/// as version 1, i.e. data written before versioning was introduced.
+///
+/// Returns the value and the version it was stored with, so callers can rewrite old files.
+/// Versions newer than `versions.current`, or with a missing step, are errors.
+fn deserialize_any_version<T: DeserializeOwned>(json_string: &str, versions: &SchemaVersions) -> JsonResult<(T, u32)> {
+    let value: Value = serde_json::from_str(json_string)?;
+    let is_envelope = value.as_object().is_some_and(|o| o.len() == 2 && o.contains_key("data") && o.get("version").is_some_and(Value::is_u64));
+    let (stored_version, mut data) = if is_envelope {
+        let envelope: Versioned<Value> = serde_json::from_value(value)?;
+        (envelope.version, envelope.data)
+    } else {
+        (1, value)
+    };
+
+    if stored_version > versions.current {
+        return Err(serde_json::Error::custom(format!(
+            "data is version {}, newer than the supported version {}",
+            stored_version, versions.current
+        )));
+    }
+    for version in stored_version..versions.current {
+        let step = versions
+            .upgrades
+            .get(&version)
+            .ok_or_else(|| serde_json::Error::custom(format!("no upgrade registered from version {}", version)))?;
+        data = step(data)?;
+    }
+    Ok((serde_json::from_value(data)?, stored_version))
+}
 
 // Example Usage (within a main function or test)
 /*
@@ -101,11 +169,41 @@ fn main() {
     
     // Example of handling invalid JSON
     println!("\n--- Handling Invalid JSON ---");
-    let invalid_json = "{ \"x\": 5, \"label\": \"Incomplete" "; // Missing comma, closing brace
+    let invalid_json = "{ \"x\": 5, \"label\": \"Incomplete"; // Unterminated string, missing closing brace
     let bad_deserialization: JsonResult<Point> = deserialize_json_string_to_struct(invalid_json);
     match bad_deserialization {
         Ok(_) => println!("This shouldn't happen!"),
         Err(e) => eprintln!("Successfully caught expected error for invalid JSON: {}", e),
     }
+
+    // Versioned envelopes: `Point` is schema version 3. Version 1 called the label `name`
+    // and version 2 renamed it to `label`; version 3 added `tags`.
+    println!("\n--- Versioned Envelopes ---");
+    let versions = SchemaVersions::new(3)
+        .upgrade(1, |mut data| {
+            if let Some(name) = data.as_object_mut().and_then(|o| o.remove("name")) {
+                data["label"] = name;
+            }
+            Ok(data)
+        })
+        .upgrade(2, |mut data| {
+            data["tags"] = serde_json::json!([]);
+            Ok(data)
+        });
+
+    let stored = serialize_versioned(&point, &versions).expect("Point serializes");
+    println!("Current format:\n{}", stored);
+    let legacy = r#"{ "x": 1, "y": 2, "name": "Before versioning" }"#;
+    let v2 = r#"{ "version": 2, "data": { "x": 3, "y": 4, "label": "Version 2" } }"#;
+    for payload in [stored.as_str(), legacy, v2] {
+        match deserialize_any_version::<Point>(payload, &versions) {
+            Ok((point, from)) => println!("Read v{} payload: {:?}", from, point),
+            Err(e) => eprintln!("Error reading versioned payload: {}", e),
+        }
+    }
+    let too_new = r#"{ "version": 4, "data": {} }"#;
+    if let Err(e) = deserialize_any_version::<Point>(too_new, &versions) {
+        eprintln!("Rejected as expected: {}", e);
+    }
 }
 */ 
\ No newline at end of file
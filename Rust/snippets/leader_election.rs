@@ -0,0 +1,541 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+// thiserror = "1.0"
+// redis = { version = "0.27", features = ["tokio-comp", "aio"] } // Only for `RedisLeaseStore`
+// rusqlite = { version = "0.32", features = ["bundled"] } // Only for `SqlLeaseStore`
+//
+// Lease-based leader election, so singleton work (a scheduler, a cleanup job) runs on exactly
+// one replica at a time:
+// * Candidates race to write a lease `(holder, token, expires_at)` into a shared store. The
+//   winner renews it every `ttl / 3`; if it dies, the lease expires and another candidate wins.
+// * Every new leadership term gets a larger fencing token. Attach it to writes so the resource
+//   can reject a deposed leader that hasn't noticed yet (see "fencing tokens" in `redis.rs`).
+// * A leader steps down on its own once it can't confirm a renewal for `ttl - safety_margin`,
+//   which is before the store lets anyone else in, so two leaders never overlap as long as
+//   the replicas' clocks run at the same rate.
+// * `LeaseStore` backends: `MemoryLeaseStore` (one process; tests), `RedisLeaseStore` (the
+//   Lua-script approach from `redis.rs`), and `SqlLeaseStore` on SQLite. Its upsert is plain
+//   SQL that also runs on Postgres (with `$1` placeholders and `now()` instead of the app's
+//   clock), so a Postgres store is the same two statements on a `tokio-postgres` client.
+// * `on_gained` / `on_lost` callbacks, plus `ElectionHandle::is_leader` and `changed()` for
+//   async code.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+#[derive(Error, Debug)]
+enum ElectionError {
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("SQL error: {0}")]
+    Sql(#[from] rusqlite::Error),
+
+    #[error("Lease store task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+type Result<T> = std::result::Result<T, ElectionError>;
+
+// --- Lease stores ---
+
+/// A shared store that grants one holder per lease name at a time.
+///
+/// Implementations must make each method a single atomic operation in the store.
+trait LeaseStore: Send + Sync + 'static {
+    /// Takes the lease if it is free or expired, or extends it if `holder` already has it.
+    /// Returns the fencing token: unchanged while the same holder keeps the lease, incremented
+    /// whenever a new holder takes it. `None` if someone else holds a live lease.
+    fn try_acquire(&self, name: &str, holder: &str, ttl: Duration) -> impl Future<Output = Result<Option<u64>>> + Send;
+
+    /// Extends the lease only if `holder` still has a live one. `false` means it was lost.
+    fn renew(&self, name: &str, holder: &str, ttl: Duration) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Gives the lease up early if `holder` has it, so the next leader doesn't wait for the TTL.
+    fn release(&self, name: &str, holder: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+struct MemoryLease {
+    holder: String,
+    token: u64,
+    expires_at: Instant,
+}
+
+/// An in-process store, for tests and for several elector tasks inside one binary.
+#[derive(Default)]
+struct MemoryLeaseStore {
+    leases: Mutex<HashMap<String, MemoryLease>>,
+}
+
+impl LeaseStore for MemoryLeaseStore {
+    async fn try_acquire(&self, name: &str, holder: &str, ttl: Duration) -> Result<Option<u64>> {
+        let now = Instant::now();
+        let mut leases = self.leases.lock().unwrap_or_else(|e| e.into_inner());
+        let lease = leases
+            .entry(name.to_string())
+            .or_insert_with(|| MemoryLease { holder: String::new(), token: 0, expires_at: now });
+        if lease.holder == holder && !lease.holder.is_empty() {
+            lease.expires_at = now + ttl;
+            return Ok(Some(lease.token));
+        }
+        if lease.expires_at > now {
+            return Ok(None);
+        }
+        lease.holder = holder.to_string();
+        lease.token += 1;
+        lease.expires_at = now + ttl;
+        Ok(Some(lease.token))
+    }
+
+    async fn renew(&self, name: &str, holder: &str, ttl: Duration) -> Result<bool> {
+        let now = Instant::now();
+        let mut leases = self.leases.lock().unwrap_or_else(|e| e.into_inner());
+        match leases.get_mut(name) {
+            Some(lease) if lease.holder == holder && lease.expires_at > now => {
+                lease.expires_at = now + ttl;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release(&self, name: &str, holder: &str) -> Result<()> {
+        let mut leases = self.leases.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(lease) = leases.get_mut(name).filter(|lease| lease.holder == holder) {
+            // Keep the entry so the next holder's token is still larger.
+            lease.holder.clear();
+            lease.expires_at = Instant::now();
+        }
+        Ok(())
+    }
+}
+
+/// Atomically: renew if we hold it, else take it if free and bump the fencing counter.
+/// The lease value is `holder`; `KEYS[2]` is a separate counter that never expires.
+const ACQUIRE_SCRIPT: &str = r"
+local current = redis.call('GET', KEYS[1])
+if current == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return tonumber(redis.call('GET', KEYS[2]))
+end
+if current then
+    return false
+end
+redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+return redis.call('INCR', KEYS[2])
+";
+
+const RENEW_SCRIPT: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+";
+
+const RELEASE_SCRIPT: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+";
+
+/// Leases as Redis keys with a TTL (`leader:<name>`, counter in `leader:<name>:fence`).
+struct RedisLeaseStore {
+    conn: redis::aio::MultiplexedConnection,
+}
+
+impl RedisLeaseStore {
+    fn keys(name: &str) -> (String, String) {
+        (format!("leader:{}", name), format!("leader:{}:fence", name))
+    }
+}
+
+impl LeaseStore for RedisLeaseStore {
+    async fn try_acquire(&self, name: &str, holder: &str, ttl: Duration) -> Result<Option<u64>> {
+        let (lease_key, fence_key) = Self::keys(name);
+        let token: Option<u64> = redis::Script::new(ACQUIRE_SCRIPT)
+            .key(lease_key)
+            .key(fence_key)
+            .arg(holder)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut self.conn.clone())
+            .await?;
+        Ok(token)
+    }
+
+    async fn renew(&self, name: &str, holder: &str, ttl: Duration) -> Result<bool> {
+        let renewed: i64 = redis::Script::new(RENEW_SCRIPT)
+            .key(Self::keys(name).0)
+            .arg(holder)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut self.conn.clone())
+            .await?;
+        Ok(renewed == 1)
+    }
+
+    async fn release(&self, name: &str, holder: &str) -> Result<()> {
+        let _: i64 = redis::Script::new(RELEASE_SCRIPT)
+            .key(Self::keys(name).0)
+            .arg(holder)
+            .invoke_async(&mut self.conn.clone())
+            .await?;
+        Ok(())
+    }
+}
+
+/// Takes or renews the lease in one statement. Postgres: same SQL with `$n` placeholders.
+/// `RETURNING` yields no row when another holder's lease is still live.
+const SQL_ACQUIRE: &str = "
+INSERT INTO leader_leases (name, holder, token, expires_at_ms) VALUES (?1, ?2, 1, ?3 + ?4)
+ON CONFLICT (name) DO UPDATE SET
+    token = CASE WHEN leader_leases.holder = excluded.holder THEN leader_leases.token
+                 ELSE leader_leases.token + 1 END,
+    holder = excluded.holder,
+    expires_at_ms = excluded.expires_at_ms
+WHERE leader_leases.holder = excluded.holder OR leader_leases.expires_at_ms <= ?3
+RETURNING token";
+
+const SQL_RENEW: &str =
+    "UPDATE leader_leases SET expires_at_ms = ?3 + ?4 WHERE name = ?1 AND holder = ?2 AND expires_at_ms > ?3";
+
+/// Keeps the row (and its token) but makes it immediately available.
+const SQL_RELEASE: &str = "UPDATE leader_leases SET holder = '', expires_at_ms = 0 WHERE name = ?1 AND holder = ?2";
+
+/// Leases in a SQL table. SQLite suits replicas on one host sharing a database file; use the
+/// same statements on Postgres for replicas on different machines.
+struct SqlLeaseStore {
+    conn: Arc<Mutex<rusqlite::Connection>>,
+}
+
+impl SqlLeaseStore {
+    fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let conn = rusqlite::Connection::open(path)?;
+        // Other replicas may hold the write lock for a moment; wait instead of failing.
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS leader_leases (
+                name TEXT PRIMARY KEY,
+                holder TEXT NOT NULL,
+                token INTEGER NOT NULL,
+                expires_at_ms INTEGER NOT NULL
+            )",
+        )?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    /// Runs a blocking rusqlite call off the async runtime's worker threads.
+    async fn with_conn<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&rusqlite::Connection) -> rusqlite::Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let conn = Arc::clone(&self.conn);
+        let result = tokio::task::spawn_blocking(move || f(&conn.lock().unwrap_or_else(|e| e.into_inner()))).await?;
+        Ok(result?)
+    }
+}
+
+/// Milliseconds since the Unix epoch, by this machine's clock.
+fn now_ms() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0)
+}
+
+impl LeaseStore for SqlLeaseStore {
+    async fn try_acquire(&self, name: &str, holder: &str, ttl: Duration) -> Result<Option<u64>> {
+        let (name, holder, ttl_ms) = (name.to_string(), holder.to_string(), ttl.as_millis() as i64);
+        self.with_conn(move |conn| {
+            use rusqlite::OptionalExtension;
+            conn.query_row(SQL_ACQUIRE, rusqlite::params![name, holder, now_ms(), ttl_ms], |row| row.get::<_, i64>(0))
+                .optional()
+                .map(|token| token.map(|t| t as u64))
+        })
+        .await
+    }
+
+    async fn renew(&self, name: &str, holder: &str, ttl: Duration) -> Result<bool> {
+        let (name, holder, ttl_ms) = (name.to_string(), holder.to_string(), ttl.as_millis() as i64);
+        self.with_conn(move |conn| conn.execute(SQL_RENEW, rusqlite::params![name, holder, now_ms(), ttl_ms]).map(|n| n == 1))
+            .await
+    }
+
+    async fn release(&self, name: &str, holder: &str) -> Result<()> {
+        let (name, holder) = (name.to_string(), holder.to_string());
+        self.with_conn(move |conn| conn.execute(SQL_RELEASE, rusqlite::params![name, holder]).map(|_| ())).await
+    }
+}
+
+// --- Elector ---
+
+/// Timing for one election. `ttl` bounds how long a dead leader blocks failover.
+#[derive(Debug, Clone)]
+struct ElectionConfig {
+    /// The lease name; candidates with the same name compete.
+    name: String,
+    /// This candidate's unique id, e.g. `hostname-pid` or a UUID.
+    candidate: String,
+    ttl: Duration,
+    /// How often the leader renews; must be well under `ttl - safety_margin`.
+    renew_every: Duration,
+    /// How often followers try to take over.
+    retry_every: Duration,
+    /// Step down this long before the lease would expire in the store, to absorb clock
+    /// drift and the latency of the renewal call itself.
+    safety_margin: Duration,
+}
+
+impl ElectionConfig {
+    fn new(name: &str, candidate: &str, ttl: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            candidate: candidate.to_string(),
+            ttl,
+            renew_every: ttl / 3,
+            retry_every: ttl / 3,
+            safety_margin: ttl / 10,
+        }
+    }
+}
+
+/// Whether this candidate leads, and with which fencing token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Leadership {
+    Follower,
+    Leader { token: u64 },
+}
+
+type Callback = Box<dyn Fn(u64) + Send + Sync>;
+
+/// Runs one candidate's election loop; build with `new`, add callbacks, then `start`.
+struct LeaderElector<S> {
+    store: Arc<S>,
+    config: ElectionConfig,
+    on_gained: Option<Callback>,
+    on_lost: Option<Callback>,
+}
+
+impl<S: LeaseStore> LeaderElector<S> {
+    fn new(store: Arc<S>, config: ElectionConfig) -> Self {
+        Self { store, config, on_gained: None, on_lost: None }
+    }
+
+    /// Called with the fencing token each time this candidate becomes leader.
+    fn on_gained(mut self, callback: impl Fn(u64) + Send + Sync + 'static) -> Self {
+        self.on_gained = Some(Box::new(callback));
+        self
+    }
+
+    /// Called with the old token when leadership ends (lost, expired, or shut down).
+    /// Stop leader-only work here; it may keep running briefly otherwise.
+    fn on_lost(mut self, callback: impl Fn(u64) + Send + Sync + 'static) -> Self {
+        self.on_lost = Some(Box::new(callback));
+        self
+    }
+
+    /// Spawns the election loop on the current tokio runtime.
+    fn start(self) -> ElectionHandle {
+        let (state_tx, state) = watch::channel(Leadership::Follower);
+        let (shutdown_tx, shutdown) = watch::channel(false);
+        let task = tokio::spawn(self.run(state_tx, shutdown));
+        ElectionHandle { state, shutdown: shutdown_tx, task }
+    }
+
+    /// Publishes a state change. `on_gained` runs before `is_leader` turns true, and `on_lost`
+    /// after it turns false.
+    fn set(&self, state: &watch::Sender<Leadership>, next: Leadership) {
+        let previous = *state.borrow();
+        match (previous, next) {
+            (Leadership::Follower, Leadership::Leader { token }) => {
+                if let Some(callback) = &self.on_gained {
+                    callback(token);
+                }
+                state.send_replace(next);
+            }
+            (Leadership::Leader { token }, Leadership::Follower) => {
+                state.send_replace(next);
+                if let Some(callback) = &self.on_lost {
+                    callback(token);
+                }
+            }
+            _ => {
+                state.send_replace(next);
+            }
+        }
+    }
+
+    async fn run(self, state: watch::Sender<Leadership>, mut shutdown: watch::Receiver<bool>) {
+        let config = &self.config;
+        // The latest moment we may still act as leader without a successful renewal.
+        let mut lead_until = Instant::now();
+        loop {
+            let leading = matches!(*state.borrow(), Leadership::Leader { .. });
+            let attempt_started = Instant::now();
+            if leading {
+                match self.store.renew(&config.name, &config.candidate, config.ttl).await {
+                    Ok(true) => lead_until = attempt_started + config.ttl - config.safety_margin,
+                    Ok(false) => self.set(&state, Leadership::Follower),
+                    // Transient error: keep leading until the lease could have expired.
+                    Err(e) => eprintln!("{}: renewing lease {} failed: {}", config.candidate, config.name, e),
+                }
+            } else {
+                match self.store.try_acquire(&config.name, &config.candidate, config.ttl).await {
+                    Ok(Some(token)) => {
+                        lead_until = attempt_started + config.ttl - config.safety_margin;
+                        self.set(&state, Leadership::Leader { token });
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("{}: acquiring lease {} failed: {}", config.candidate, config.name, e),
+                }
+            }
+            if matches!(*state.borrow(), Leadership::Leader { .. }) && Instant::now() >= lead_until {
+                self.set(&state, Leadership::Follower);
+            }
+
+            let leading = matches!(*state.borrow(), Leadership::Leader { .. });
+            let wait = if leading { config.renew_every } else { config.retry_every };
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+        let leading = matches!(*state.borrow(), Leadership::Leader { .. });
+        if leading {
+            if let Err(e) = self.store.release(&config.name, &config.candidate).await {
+                eprintln!("{}: releasing lease {} failed: {}", config.candidate, config.name, e);
+            }
+        }
+        self.set(&state, Leadership::Follower);
+    }
+}
+
+/// A running election. Dropping it also ends the loop and releases the lease, but in the
+/// background; `shutdown` waits for the release.
+struct ElectionHandle {
+    state: watch::Receiver<Leadership>,
+    shutdown: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl ElectionHandle {
+    fn is_leader(&self) -> bool {
+        matches!(*self.state.borrow(), Leadership::Leader { .. })
+    }
+
+    /// The fencing token of the current term, if leading.
+    fn fencing_token(&self) -> Option<u64> {
+        match *self.state.borrow() {
+            Leadership::Leader { token } => Some(token),
+            Leadership::Follower => None,
+        }
+    }
+
+    /// A receiver to `select!` on, e.g. to cancel leader-only work the moment leadership ends.
+    fn subscribe(&self) -> watch::Receiver<Leadership> {
+        self.state.clone()
+    }
+
+    /// Stops campaigning and releases the lease so another candidate takes over right away.
+    async fn shutdown(self) {
+        let _ = self.shutdown.send(true);
+        let _ = self.task.await;
+    }
+
+    /// Stops without releasing or calling `on_lost`, like a crash: the lease stays taken
+    /// until its TTL runs out. For failover tests.
+    fn abort(self) {
+        self.task.abort();
+    }
+}
+
+// Example Usage
+/*
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A resource that only accepts writes from the newest leadership term.
+#[derive(Default)]
+struct FencedJobTable {
+    highest_token: AtomicU64,
+}
+
+impl FencedJobTable {
+    fn run_due_jobs(&self, replica: &str, token: u64) -> bool {
+        let accepted = self.highest_token.fetch_max(token, Ordering::SeqCst) <= token;
+        println!("  {} runs the scheduler with token {}: {}", replica, token, if accepted { "ok" } else { "REJECTED (stale)" });
+        accepted
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let store = Arc::new(MemoryLeaseStore::default());
+    let table = Arc::new(FencedJobTable::default());
+    let ttl = Duration::from_millis(300);
+
+    let mut replicas = Vec::new();
+    for name in ["replica-a", "replica-b", "replica-c"] {
+        let handle = LeaderElector::new(store.clone(), ElectionConfig::new("scheduler", name, ttl))
+            .on_gained(move |token| println!("{} became leader (token {})", name, token))
+            .on_lost(move |token| println!("{} lost leadership (token {})", name, token))
+            .start();
+        replicas.push((name, handle));
+    }
+
+    for round in 0..3 {
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let leaders: Vec<&str> = replicas.iter().filter(|(_, h)| h.is_leader()).map(|(n, _)| *n).collect();
+        println!("round {}: leaders {:?}", round, leaders);
+        for (name, handle) in &replicas {
+            if let Some(token) = handle.fencing_token() {
+                table.run_due_jobs(name, token);
+            }
+        }
+    }
+
+    // Graceful handover: the leader releases, a follower takes over within `retry_every`.
+    let leader = replicas.iter().position(|(_, h)| h.is_leader()).expect("someone leads");
+    let (name, handle) = replicas.remove(leader);
+    handle.shutdown().await;
+    println!("{} shut down", name);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    // Crash: renewals stop without a release, so failover waits for the TTL.
+    let leader = replicas.iter().position(|(_, h)| h.is_leader()).expect("someone took over");
+    let (crashed, handle) = replicas.remove(leader);
+    let stale_token = handle.fencing_token().unwrap();
+    table.run_due_jobs(crashed, stale_token);
+    handle.abort();
+    println!("{} crashed", crashed);
+    let crashed_at = Instant::now();
+    let mut state = replicas[0].1.subscribe();
+    let new_term = *state.wait_for(|s| matches!(s, Leadership::Leader { .. })).await.unwrap();
+    println!("failover after {:?}", crashed_at.elapsed());
+    if let Leadership::Leader { token } = new_term {
+        table.run_due_jobs(replicas[0].0, token);
+    }
+
+    // A deposed leader waking up from a long pause is fenced off.
+    table.run_due_jobs(crashed, stale_token);
+    for (_, handle) in replicas {
+        handle.shutdown().await;
+    }
+
+    // The same election on SQLite, shared by every process that opens the file.
+    let path = std::env::temp_dir().join(format!("leases-{}.db", std::process::id()));
+    let sql = Arc::new(SqlLeaseStore::open(&path)?);
+    println!("sql a: {:?}", sql.try_acquire("scheduler", "a", ttl).await?);
+    println!("sql b: {:?}", sql.try_acquire("scheduler", "b", ttl).await?);
+    println!("sql a renew: {:?}", sql.renew("scheduler", "a", ttl).await?);
+    sql.release("scheduler", "a").await?;
+    println!("sql b after release: {:?}", sql.try_acquire("scheduler", "b", ttl).await?);
+    let _ = std::fs::remove_file(path);
+
+    // With Redis (e.g. `docker run -p 6379:6379 redis:7`):
+    // let conn = redis::Client::open("redis://127.0.0.1/")?.get_multiplexed_async_connection().await?;
+    // let store = Arc::new(RedisLeaseStore { conn });
+    Ok(())
+}
+*/
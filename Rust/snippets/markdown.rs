@@ -0,0 +1,345 @@
+// Note: This example requires adding the `pulldown-cmark`, `ammonia`, `serde`, `serde_json`,
+// `serde_yaml`, and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// pulldown-cmark = "0.13"
+// ammonia = "4"
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = { version = "1.0", features = ["preserve_order"] } # keeps struct field order for tables
+// serde_yaml = "0.9"
+// thiserror = "1.0"
+//
+// Markdown in both directions: user- or repo-authored `.md` files (READMEs, docs, blog
+// posts with YAML front matter) rendered to HTML that is safe to embed in a page, and
+// Markdown tables generated from data for reports, PR comments, and CLI `--format md`.
+
+use pulldown_cmark::{html, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MarkdownError {
+    #[error("Invalid front matter: {0}")]
+    FrontMatter(#[from] serde_yaml::Error),
+    #[error("Table rows must serialize to objects: {0}")]
+    TableRow(String),
+    #[error("Failed to serialize table row: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+type Result<T> = std::result::Result<T, MarkdownError>;
+
+/// GitHub-flavored extensions: tables, footnotes, strikethrough, task lists, and
+/// `# Heading {#custom-id}` attributes.
+fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_HEADING_ATTRIBUTES
+}
+
+// --- Front matter ---
+
+/// Splits a leading `---` YAML block from the Markdown body.
+///
+/// # Returns
+///
+/// `(Some(yaml), body)` when the document starts with `---` on its own line and has a
+/// closing `---` (or `...`) line; otherwise `(None, input)`.
+fn split_front_matter(input: &str) -> (Option<&str>, &str) {
+    let rest = match input.strip_prefix("---\n").or_else(|| input.strip_prefix("---\r\n")) {
+        Some(rest) => rest,
+        None => return (None, input),
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, input)
+}
+
+/// Parses the front matter into `T` (e.g. a `Post { title, date, tags }` struct).
+///
+/// # Returns
+///
+/// The parsed front matter, if present, and the remaining Markdown body.
+fn parse_front_matter<T: DeserializeOwned>(input: &str) -> Result<(Option<T>, &str)> {
+    match split_front_matter(input) {
+        (Some(yaml), body) => Ok((Some(serde_yaml::from_str(yaml)?), body)),
+        (None, body) => Ok((None, body)),
+    }
+}
+
+// --- Table of contents ---
+
+/// One heading, in document order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct TocEntry {
+    /// 1 for `#`, 2 for `##`, and so on.
+    level: u8,
+    text: String,
+    /// The `id` given to the heading in the rendered HTML, for `#anchor` links.
+    anchor: String,
+}
+
+/// GitHub-style anchor: lowercase, spaces to hyphens, punctuation dropped.
+fn anchor_for(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c.to_lowercase().next().unwrap_or(c)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// Parses `markdown` and gives every heading a unique `id`, returning the events and the
+/// table of contents. Explicit `{#id}` attributes are kept; repeated anchors get `-1`, `-2`.
+fn parse_with_anchors(markdown: &str) -> (Vec<Event<'_>>, Vec<TocEntry>) {
+    let mut events: Vec<Event> = Parser::new_ext(markdown, options()).collect();
+    let mut toc = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    let mut i = 0;
+    while i < events.len() {
+        let Event::Start(Tag::Heading { level, id, .. }) = &events[i] else {
+            i += 1;
+            continue;
+        };
+        let (level, explicit_id) = (heading_level(*level), id.as_ref().map(|id| id.to_string()));
+        let mut text = String::new();
+        let mut j = i + 1;
+        while j < events.len() && !matches!(events[j], Event::End(TagEnd::Heading(_))) {
+            if let Event::Text(t) | Event::Code(t) = &events[j] {
+                text.push_str(t);
+            }
+            j += 1;
+        }
+        let base = explicit_id.unwrap_or_else(|| anchor_for(&text));
+        let count = seen.entry(base.clone()).or_insert(0);
+        let anchor = if *count == 0 { base.clone() } else { format!("{}-{}", base, count) };
+        *count += 1;
+        if let Event::Start(Tag::Heading { id, .. }) = &mut events[i] {
+            *id = Some(CowStr::from(anchor.clone()));
+        }
+        toc.push(TocEntry { level, text, anchor });
+        i = j;
+    }
+    (events, toc)
+}
+
+/// The table of contents of a Markdown document, without rendering it.
+fn table_of_contents(markdown: &str) -> Vec<TocEntry> {
+    parse_with_anchors(markdown).1
+}
+
+/// Renders the table of contents as a nested Markdown list of links, skipping headings
+/// deeper than `max_level`.
+fn toc_markdown(toc: &[TocEntry], max_level: u8) -> String {
+    let top = toc.iter().map(|e| e.level).min().unwrap_or(1);
+    toc.iter()
+        .filter(|e| e.level <= max_level)
+        .map(|e| format!("{}- [{}](#{})\n", "  ".repeat(usize::from(e.level - top)), e.text, e.anchor))
+        .collect()
+}
+
+// --- HTML ---
+
+/// Renders Markdown to HTML *without* sanitizing. Only for trusted input (your own docs);
+/// raw HTML and `javascript:` links in the source pass straight through.
+fn to_html_trusted(markdown: &str) -> String {
+    let (events, _) = parse_with_anchors(markdown);
+    let mut out = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut out, events.into_iter());
+    out
+}
+
+/// Renders Markdown to HTML and sanitizes it with `ammonia`: scripts, event handlers,
+/// `javascript:` URLs, and unknown tags are removed; links get `rel="noopener noreferrer"`.
+/// Heading `id`s are kept so table-of-contents links still work.
+///
+/// Use this for anything a user can write (comments, issues, profile bios).
+fn to_html(markdown: &str) -> String {
+    ammonia::Builder::default()
+        .add_tag_attributes("h1", &["id"])
+        .add_tag_attributes("h2", &["id"])
+        .add_tag_attributes("h3", &["id"])
+        .add_tag_attributes("h4", &["id"])
+        .add_tag_attributes("h5", &["id"])
+        .add_tag_attributes("h6", &["id"])
+        .add_tags(&["input"]) // Task list checkboxes
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        .clean(&to_html_trusted(markdown))
+        .to_string()
+}
+
+/// A rendered document: front matter, table of contents, and sanitized HTML body.
+#[derive(Debug)]
+struct Document<T> {
+    front_matter: Option<T>,
+    toc: Vec<TocEntry>,
+    html: String,
+}
+
+/// Front matter + TOC + sanitized HTML in one call, e.g. for a static site or docs page.
+fn render_document<T: DeserializeOwned>(input: &str) -> Result<Document<T>> {
+    let (front_matter, body) = parse_front_matter(input)?;
+    Ok(Document {
+        front_matter,
+        toc: table_of_contents(body),
+        html: to_html(body),
+    })
+}
+
+// --- Markdown tables from data ---
+
+/// Renders rows as a GitHub-flavored Markdown table.
+///
+/// Columns come from the first row's field names, in declaration order. Numeric columns are
+/// right-aligned, `null`/`None` renders empty, and `|` and newlines inside cells are escaped
+/// so they don't break the table.
+///
+/// # Arguments
+///
+/// * `rows` - Structs (or maps) that serialize to JSON objects.
+///
+/// # Returns
+///
+/// An empty string for no rows, or an error if a row is not an object.
+fn markdown_table<T: Serialize>(rows: &[T]) -> Result<String> {
+    let objects = rows
+        .iter()
+        .map(|row| match serde_json::to_value(row)? {
+            Value::Object(map) => Ok(map),
+            other => Err(MarkdownError::TableRow(format!("got {}", other))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let Some(first) = objects.first() else {
+        return Ok(String::new());
+    };
+    let headers: Vec<&String> = first.keys().collect();
+
+    let cell = |value: Option<&Value>| match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.replace('|', "\\|").replace('\n', "<br>"),
+        Some(other) => other.to_string().replace('|', "\\|"),
+    };
+    let cells: Vec<Vec<String>> = objects
+        .iter()
+        .map(|obj| headers.iter().map(|h| cell(obj.get(*h))).collect())
+        .collect();
+    let numeric: Vec<bool> = headers
+        .iter()
+        .map(|h| objects.iter().all(|obj| matches!(obj.get(*h), Some(Value::Number(_)) | Some(Value::Null) | None)))
+        .collect();
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(c, h)| cells.iter().map(|row| row[c].chars().count()).chain([h.chars().count(), 3]).max().unwrap_or(3))
+        .collect();
+
+    let pad = |text: &str, c: usize| {
+        let fill = " ".repeat(widths[c] - text.chars().count());
+        if numeric[c] { format!("{}{}", fill, text) } else { format!("{}{}", text, fill) }
+    };
+    let mut out = String::new();
+    let header_cells: Vec<String> = headers.iter().enumerate().map(|(c, h)| pad(h, c)).collect();
+    out.push_str(&format!("| {} |\n", header_cells.join(" | ")));
+    let rule: Vec<String> = widths
+        .iter()
+        .zip(&numeric)
+        .map(|(&w, &num)| if num { format!("{}:", "-".repeat(w - 1)) } else { "-".repeat(w) })
+        .collect();
+    out.push_str(&format!("| {} |\n", rule.join(" | ")));
+    for row in &cells {
+        let padded: Vec<String> = row.iter().enumerate().map(|(c, text)| pad(text, c)).collect();
+        out.push_str(&format!("| {} |\n", padded.join(" | ")));
+    }
+    Ok(out)
+}
+
+// Example Usage
+/*
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct PostMeta {
+    title: String,
+    date: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    draft: bool,
+}
+
+#[derive(Serialize)]
+struct BenchRow {
+    name: &'static str,
+    ops_per_sec: u64,
+    p99_ms: f64,
+    notes: Option<&'static str>,
+}
+
+fn main() -> Result<()> {
+    let source = r#"---
+title: Shipping 2.0
+date: 2024-05-01
+tags: [release, rust]
+---
+# Shipping 2.0
+
+Release notes with ~~bugs~~ features.
+
+## What's new
+
+- [x] Faster builds
+- [ ] World peace
+
+## What's new
+
+Duplicate headings get unique anchors.
+
+### Upgrading `v1` configs {#upgrading}
+
+<script>alert("xss")</script>
+<a href="javascript:alert(1)" onclick="steal()">click me</a> and [a safe link](https://example.com).
+"#;
+
+    let doc: Document<PostMeta> = render_document(source)?;
+    println!("{:?}\n", doc.front_matter);
+    print!("{}", toc_markdown(&doc.toc, 3));
+    println!("\n{}", doc.html);
+
+    println!("Trusted (unsanitized) output keeps the script:");
+    println!("{}", to_html_trusted("<script>alert(1)</script>"));
+
+    let bad: Result<(Option<PostMeta>, &str)> = parse_front_matter("---\ndate: 2024-05-01\n---\nbody");
+    println!("{}\n", bad.unwrap_err());
+
+    let rows = [
+        BenchRow { name: "parse", ops_per_sec: 1_250_000, p99_ms: 0.8, notes: None },
+        BenchRow { name: "render | html", ops_per_sec: 98_000, p99_ms: 12.5, notes: Some("sanitized") },
+        BenchRow { name: "toc", ops_per_sec: 3_400_000, p99_ms: 0.1, notes: Some("two\nlines") },
+    ];
+    print!("{}", markdown_table(&rows)?);
+    Ok(())
+}
+*/
@@ -0,0 +1,340 @@
+// Note: This example requires adding the `async-nats`, `tokio`, `futures`, `serde`, `serde_json`,
+// and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// async-nats = "0.42"
+// tokio = { version = "1", features = ["full"] }
+// futures = "0.3"
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+//
+// Uses NATS JetStream (run a server with `nats-server -js`). The concepts map directly to Kafka:
+//   Kafka topic / partition log     -> JetStream stream (subjects persisted in order)
+//   Consumer group + committed offset -> Durable consumer (the server tracks the ack floor)
+//   Rebalance across group members  -> Every process binding the same durable pull consumer
+//                                      shares the work; no partition assignment to manage.
+
+use async_nats::jetstream::{self, consumer, stream, AckKind};
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::watch;
+
+#[derive(Debug, Error)]
+pub enum MessagingError {
+    #[error("NATS error: {0}")]
+    Nats(async_nats::Error),
+    #[error("Payload encoding error: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+type Result<T> = std::result::Result<T, MessagingError>;
+
+/// async-nats has a distinct error type per operation; they all box into `async_nats::Error`.
+fn nats<E: Into<async_nats::Error>>(err: E) -> MessagingError {
+    MessagingError::Nats(err.into())
+}
+
+// --- Setup ---
+
+/// Connects to NATS and returns a JetStream context.
+async fn connect_jetstream(url: &str) -> Result<jetstream::Context> {
+    let client = async_nats::connect(url).await.map_err(nats)?;
+    Ok(jetstream::new(client))
+}
+
+/// Creates (or binds to) a file-backed stream capturing `subjects`.
+///
+/// `duplicate_window` makes publishing idempotent: a message re-sent with the same
+/// `Nats-Msg-Id` within the window is stored only once.
+async fn ensure_stream(js: &jetstream::Context, name: &str, subjects: &[&str]) -> Result<stream::Stream> {
+    js.get_or_create_stream(stream::Config {
+        name: name.to_string(),
+        subjects: subjects.iter().map(|s| s.to_string()).collect(),
+        storage: stream::StorageType::File,
+        max_age: Duration::from_secs(7 * 24 * 3600),
+        duplicate_window: Duration::from_secs(120),
+        ..Default::default()
+    })
+    .await
+    .map_err(nats)
+}
+
+/// Settings for a durable consumer ("consumer group").
+struct ConsumerOptions {
+    /// Shared by every worker in the group; the server remembers progress under this name.
+    durable_name: String,
+    filter_subject: String,
+    /// How long the server waits for an ack before redelivering.
+    ack_wait: Duration,
+    /// Total delivery attempts before the server gives up on a message.
+    max_deliver: i64,
+    /// Upper bound on unacknowledged messages in flight across the group.
+    max_ack_pending: i64,
+}
+
+impl ConsumerOptions {
+    fn new(durable_name: &str, filter_subject: &str) -> Self {
+        Self {
+            durable_name: durable_name.to_string(),
+            filter_subject: filter_subject.to_string(),
+            ack_wait: Duration::from_secs(30),
+            max_deliver: 5,
+            max_ack_pending: 256,
+        }
+    }
+}
+
+/// Creates the durable pull consumer, or binds to it if it already exists.
+/// New groups start from the beginning of the stream (`DeliverPolicy::All`).
+async fn ensure_consumer(stream: &stream::Stream, opts: &ConsumerOptions) -> Result<consumer::PullConsumer> {
+    stream
+        .get_or_create_consumer(
+            &opts.durable_name,
+            consumer::pull::Config {
+                durable_name: Some(opts.durable_name.clone()),
+                filter_subject: opts.filter_subject.clone(),
+                ack_policy: consumer::AckPolicy::Explicit,
+                deliver_policy: consumer::DeliverPolicy::All,
+                ack_wait: opts.ack_wait,
+                max_deliver: opts.max_deliver,
+                max_ack_pending: opts.max_ack_pending,
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(nats)
+}
+
+// --- Producer ---
+
+/// Publishes a JSON-encoded event and waits for the stream to persist it.
+///
+/// # Arguments
+///
+/// * `message_id` - Stable id used for server-side de-duplication. Reuse it when retrying
+///   a publish so a retry after a lost ack doesn't store the event twice.
+///
+/// # Returns
+///
+/// The stream sequence number (the "offset") assigned to the event.
+async fn publish_event<T: Serialize>(
+    js: &jetstream::Context,
+    subject: &str,
+    message_id: &str,
+    event: &T,
+) -> Result<u64> {
+    let payload = serde_json::to_vec(event)?;
+    let publish = jetstream::context::Publish::build()
+        .payload(payload.into())
+        .message_id(message_id);
+    // The first await sends; the second waits for the stream's persistence ack.
+    let ack = js
+        .send_publish(subject.to_string(), publish)
+        .await
+        .map_err(nats)?
+        .await
+        .map_err(nats)?;
+    Ok(ack.sequence)
+}
+
+// --- Consumer ---
+
+/// A decoded message handed to the handler.
+struct Delivery<T> {
+    subject: String,
+    payload: T,
+    /// Position in the stream (Kafka: offset).
+    stream_sequence: u64,
+    /// 1 on first delivery; higher values mean this is a redelivery.
+    delivered: i64,
+}
+
+/// What the handler wants done with a message.
+enum Outcome {
+    /// Processed; advance past it.
+    Ack,
+    /// Transient failure; redeliver after the given delay.
+    Retry(Duration),
+    /// Poison message; never redeliver. Pair with an advisory subscription or DLQ stream.
+    Reject,
+}
+
+#[derive(Debug, Default)]
+struct ConsumerStats {
+    acked: u64,
+    retried: u64,
+    rejected: u64,
+}
+
+/// Processes messages from a durable consumer until `shutdown` flips to `true`.
+///
+/// Delivery is at-least-once: a message is acked only after `handler` returns, so a crash
+/// mid-handler means the server redelivers it after `ack_wait`. Handlers must be idempotent
+/// (see `idempotency.rs`-style keys, or check `delivered > 1`).
+///
+/// While a slow handler runs, an in-progress ack is sent every `ack_wait / 2` so the server
+/// doesn't redeliver the message to another group member.
+///
+/// Shutdown is graceful: the in-flight message finishes and is acked before returning.
+/// Messages the client had pulled but not yet handed out are redelivered to the
+/// remaining members of the group.
+///
+/// # Returns
+///
+/// Per-outcome counters for this worker.
+async fn run_consumer<T, F, Fut>(
+    consumer: &consumer::PullConsumer,
+    ack_wait: Duration,
+    mut shutdown: watch::Receiver<bool>,
+    mut handler: F,
+) -> Result<ConsumerStats>
+where
+    T: DeserializeOwned,
+    F: FnMut(Delivery<T>) -> Fut,
+    Fut: Future<Output = Outcome>,
+{
+    let mut messages = consumer
+        .stream()
+        .max_messages_per_batch(32)
+        .heartbeat(Duration::from_secs(5))
+        .messages()
+        .await
+        .map_err(nats)?;
+    let mut stats = ConsumerStats::default();
+
+    loop {
+        let message = tokio::select! {
+            // Stop pulling as soon as shutdown is requested.
+            _ = shutdown.wait_for(|stop| *stop) => break,
+            next = messages.next() => match next {
+                Some(Ok(message)) => message,
+                Some(Err(e)) => {
+                    // Missed heartbeats etc. are transient; the stream keeps retrying.
+                    eprintln!("Consumer stream error: {}", e);
+                    continue;
+                }
+                None => break,
+            },
+        };
+
+        let (stream_sequence, delivered) = match message.info() {
+            Ok(info) => (info.stream_sequence, info.delivered),
+            Err(_) => (0, 1),
+        };
+
+        let payload = match serde_json::from_slice::<T>(&message.payload) {
+            Ok(payload) => payload,
+            Err(e) => {
+                // Undecodable payloads will never succeed; terminate instead of looping.
+                eprintln!("Rejecting undecodable message #{}: {}", stream_sequence, e);
+                message.ack_with(AckKind::Term).await.map_err(nats)?;
+                stats.rejected += 1;
+                continue;
+            }
+        };
+
+        let delivery = Delivery {
+            subject: message.subject.to_string(),
+            payload,
+            stream_sequence,
+            delivered,
+        };
+
+        // Run the handler, extending the ack deadline while it works.
+        let work = handler(delivery);
+        tokio::pin!(work);
+        let mut progress = tokio::time::interval(ack_wait / 2);
+        progress.tick().await; // The first tick completes immediately.
+        let outcome = loop {
+            tokio::select! {
+                outcome = &mut work => break outcome,
+                _ = progress.tick() => {
+                    let _ = message.ack_with(AckKind::Progress).await;
+                }
+            }
+        };
+
+        match outcome {
+            Outcome::Ack => {
+                // `double_ack` waits for the server to confirm, so a successful return
+                // means the message will not be redelivered.
+                message.double_ack().await.map_err(nats)?;
+                stats.acked += 1;
+            }
+            Outcome::Retry(delay) => {
+                message.ack_with(AckKind::Nak(Some(delay))).await.map_err(nats)?;
+                stats.retried += 1;
+            }
+            Outcome::Reject => {
+                message.ack_with(AckKind::Term).await.map_err(nats)?;
+                stats.rejected += 1;
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Reports how far behind a consumer group is (Kafka: consumer lag).
+async fn consumer_lag(consumer: &mut consumer::PullConsumer) -> Result<(u64, usize)> {
+    let info = consumer.info().await.map_err(nats)?;
+    Ok((info.num_pending, info.num_ack_pending))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OrderPlaced {
+    order_id: u64,
+    customer: String,
+    total_cents: u64,
+}
+
+// Example Usage (requires a NATS server with JetStream: `nats-server -js`)
+/*
+#[tokio::main]
+async fn main() -> Result<()> {
+    let js = connect_jetstream("nats://127.0.0.1:4222").await?;
+    let stream = ensure_stream(&js, "ORDERS", &["orders.>"]).await?;
+
+    // Producer: the message id makes retries safe.
+    for id in 1..=5u64 {
+        let event = OrderPlaced { order_id: id, customer: format!("customer-{}", id), total_cents: id * 1_000 };
+        let seq = publish_event(&js, "orders.placed", &format!("order-{}", id), &event).await?;
+        println!("Published order {} at sequence {}", id, seq);
+    }
+    // Re-publishing with the same id is de-duplicated by the server.
+    let dup = OrderPlaced { order_id: 1, customer: "customer-1".into(), total_cents: 1_000 };
+    publish_event(&js, "orders.placed", "order-1", &dup).await?;
+
+    // Consumer group "billing": start this binary twice and the work is shared.
+    let opts = ConsumerOptions::new("billing", "orders.placed");
+    let mut consumer = ensure_consumer(&stream, &opts).await?;
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        tokio::signal::ctrl_c().await.ok();
+        println!("Shutting down after the in-flight message...");
+        let _ = shutdown_tx.send(true);
+    });
+
+    let stats = run_consumer(&consumer, opts.ack_wait, shutdown_rx, |d: Delivery<OrderPlaced>| async move {
+        println!("[{} #{} try {}] {:?}", d.subject, d.stream_sequence, d.delivered, d.payload);
+        if d.payload.total_cents == 0 {
+            Outcome::Reject
+        } else if d.payload.order_id == 3 && d.delivered < 2 {
+            Outcome::Retry(Duration::from_secs(1)) // Simulate a transient failure
+        } else {
+            Outcome::Ack
+        }
+    })
+    .await?;
+
+    println!("Stats: {:?}", stats);
+    let (pending, unacked) = consumer_lag(&mut consumer).await?;
+    println!("Lag: {} pending, {} awaiting ack", pending, unacked);
+    Ok(())
+}
+*/
@@ -0,0 +1,202 @@
+// Note: This example requires adding the `tokio` and `futures` crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+// futures = "0.3"
+//
+// Only probe hosts you own or are authorized to test.
+
+use futures::stream::{self, StreamExt};
+use std::fmt;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+use tokio::net::{lookup_host, TcpStream};
+
+/// Outcome of a single TCP connect attempt.
+#[derive(Debug, Clone, PartialEq)]
+enum PortStatus {
+    /// Connected; includes the time the TCP handshake took.
+    Open(Duration),
+    /// The host answered with a reset (nothing listening).
+    Closed,
+    /// No answer within the timeout (firewall dropping packets, host down).
+    TimedOut,
+    /// Some other connect error (network unreachable, permission denied, ...).
+    Error(String),
+    /// The host name did not resolve.
+    Unresolved(String),
+}
+
+impl PortStatus {
+    fn is_open(&self) -> bool {
+        matches!(self, PortStatus::Open(_))
+    }
+}
+
+/// Result for one `host:port` target.
+#[derive(Debug, Clone)]
+struct ProbeResult {
+    target: String,
+    /// The resolved address that was tried.
+    addr: Option<SocketAddr>,
+    status: PortStatus,
+}
+
+impl fmt::Display for ProbeResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let addr = self.addr.map(|a| a.to_string()).unwrap_or_else(|| "-".to_string());
+        match &self.status {
+            PortStatus::Open(rtt) => write!(f, "{:<28} {:<24} open ({:.1} ms)", self.target, addr, rtt.as_secs_f64() * 1000.0),
+            PortStatus::Closed => write!(f, "{:<28} {:<24} closed", self.target, addr),
+            PortStatus::TimedOut => write!(f, "{:<28} {:<24} timed out", self.target, addr),
+            PortStatus::Error(e) => write!(f, "{:<28} {:<24} error: {}", self.target, addr, e),
+            PortStatus::Unresolved(e) => write!(f, "{:<28} {:<24} unresolved: {}", self.target, addr, e),
+        }
+    }
+}
+
+/// Checks whether a TCP connection to `target` (`"host:port"`) can be established.
+///
+/// Only the first resolved address is tried, so results are predictable for dual-stack hosts.
+/// The connection is closed immediately after the handshake; no data is sent.
+///
+/// # Arguments
+///
+/// * `target` - `"host:port"`, e.g. `"db.internal:5432"` or `"10.0.0.5:22"`.
+/// * `timeout` - Upper bound for the connect (resolution has its own OS timeout).
+async fn check_port(target: &str, timeout: Duration) -> ProbeResult {
+    let addr = match lookup_host(target).await {
+        Ok(mut addrs) => match addrs.next() {
+            Some(addr) => addr,
+            None => return unresolved(target, "no addresses".to_string()),
+        },
+        Err(e) => return unresolved(target, e.to_string()),
+    };
+
+    let started = Instant::now();
+    let status = match tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+        Ok(Ok(_stream)) => PortStatus::Open(started.elapsed()),
+        Ok(Err(e)) if e.kind() == io::ErrorKind::ConnectionRefused => PortStatus::Closed,
+        Ok(Err(e)) => PortStatus::Error(e.to_string()),
+        Err(_) => PortStatus::TimedOut,
+    };
+    ProbeResult { target: target.to_string(), addr: Some(addr), status }
+}
+
+fn unresolved(target: &str, reason: String) -> ProbeResult {
+    ProbeResult {
+        target: target.to_string(),
+        addr: None,
+        status: PortStatus::Unresolved(reason),
+    }
+}
+
+/// Checks many targets with at most `concurrency` connects in flight.
+///
+/// Bounded concurrency keeps the scan from exhausting file descriptors or tripping
+/// rate limits; results come back in the same order as `targets`.
+///
+/// # Returns
+///
+/// One `ProbeResult` per target.
+async fn check_ports<S: AsRef<str>>(targets: &[S], concurrency: usize, timeout: Duration) -> Vec<ProbeResult> {
+    stream::iter(targets)
+        .map(|target| check_port(target.as_ref(), timeout))
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Checks a range of ports on one host and returns only the open ones.
+///
+/// `host` is a name or an IP address; IPv6 addresses may be given with or without brackets.
+async fn open_ports(host: &str, ports: RangeInclusive<u16>, concurrency: usize, timeout: Duration) -> Vec<u16> {
+    // `::1:80` doesn't parse as an address; a bare IPv6 host needs brackets before the port.
+    let host = if host.parse::<Ipv6Addr>().is_ok() { format!("[{}]", host) } else { host.to_string() };
+    let targets: Vec<String> = ports.clone().map(|p| format!("{}:{}", host, p)).collect();
+    check_ports(&targets, concurrency, timeout)
+        .await
+        .into_iter()
+        .zip(ports)
+        .filter(|(result, _)| result.status.is_open())
+        .map(|(_, port)| port)
+        .collect()
+}
+
+/// Polls until something accepts connections on `addr`, e.g. a server started in a test.
+///
+/// Returns an error if nothing is listening before `deadline` elapses.
+async fn wait_for_port(addr: &str, deadline: Duration) -> io::Result<Duration> {
+    let started = Instant::now();
+    loop {
+        if check_port(addr, Duration::from_millis(250)).await.status.is_open() {
+            return Ok(started.elapsed());
+        }
+        if started.elapsed() >= deadline {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, format!("{} not reachable after {:?}", addr, deadline)));
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+/// Returns a TCP port on 127.0.0.1 that is free right now.
+///
+/// There is a small race: another process may take the port before you bind it. When you
+/// control the server code, prefer binding `127.0.0.1:0` and reading `local_addr()`
+/// (as `tcp.rs` does). Use this when the port must be passed as configuration, e.g. to a
+/// child process or a server that only accepts a port number.
+fn find_free_port() -> io::Result<u16> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+/// Returns `n` distinct free ports (all are held open until every port is chosen, so the OS
+/// can't hand out the same one twice).
+fn find_free_ports(n: usize) -> io::Result<Vec<u16>> {
+    let listeners = (0..n)
+        .map(|_| TcpListener::bind((Ipv4Addr::LOCALHOST, 0)))
+        .collect::<io::Result<Vec<_>>>()?;
+    listeners.iter().map(|l| Ok(l.local_addr()?.port())).collect()
+}
+
+// Example Usage
+/*
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    // Something to find: a local listener on a free port.
+    let port = find_free_port()?;
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    tokio::spawn(async move { while listener.accept().await.is_ok() {} });
+    println!("Free ports for a test cluster: {:?}", find_free_ports(3)?);
+
+    let targets = vec![
+        format!("127.0.0.1:{}", port),
+        "127.0.0.1:1".to_string(),             // Almost certainly closed
+        "localhost:22".to_string(),            // Open if sshd runs locally
+        "10.255.255.1:80".to_string(),         // Non-routable: times out
+        "no-such-host.invalid:443".to_string(), // Does not resolve
+    ];
+    for result in check_ports(&targets, 16, Duration::from_millis(500)).await {
+        println!("{}", result);
+    }
+
+    println!("\nOpen ports 1-1024 + ours on localhost:");
+    let mut open = open_ports("127.0.0.1", 1..=1024, 200, Duration::from_millis(200)).await;
+    if check_port(&format!("127.0.0.1:{}", port), Duration::from_millis(200)).await.status.is_open() {
+        open.push(port);
+    }
+    println!("{:?}", open);
+
+    // In tests: start a server in the background, then wait until it is ready.
+    let late_port = find_free_port()?;
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", late_port)).await.unwrap();
+        while listener.accept().await.is_ok() {}
+    });
+    let waited = wait_for_port(&format!("127.0.0.1:{}", late_port), Duration::from_secs(5)).await?;
+    println!("\nServer on {} was ready after {:?}", late_port, waited);
+    Ok(())
+}
+*/
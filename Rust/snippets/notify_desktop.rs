@@ -0,0 +1,385 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// notify-rust = "4"
+// thiserror = "1.0"
+//
+// Native desktop notifications for "tell me when it's done" jobs: builds, exports, backups,
+// long test runs. `notify-rust` speaks the freedesktop D-Bus protocol (the one `notify-send`
+// uses) on Linux/BSD, NSUserNotification on macOS, and toasts on Windows. On top of it:
+// * `DesktopNotification::new(title).body(..).urgency(..).send()` sends one, with a portable
+//   `Urgency` that maps to what each platform supports.
+// * `.action(id, label)` and `.on_response(callback)` add buttons and a click handler where the
+//   platform delivers them. The callback runs on a background thread with `Response::Clicked`,
+//   `Response::Action(id)`, or `Response::Dismissed`; `Sent::wait` blocks until it has run.
+// * When there is no desktop session (SSH, CI, a container, a systemd service) or sending fails,
+//   the notification goes to stderr with a terminal bell instead, and `Sent::via` says so. Turn
+//   that off with `.terminal_fallback(false)` to get the error.
+// * `JobNotifier::start("nightly export")` times a job and only notifies if it took longer than
+//   a threshold, with a critical notification on failure, so quick runs stay quiet.
+//
+// Platform caveats: Windows shows toasts under PowerShell's name unless the app registers its
+// own AppUserModelID, and macOS only reports clicks while the main run loop is running, so
+// `on_response` there may never fire from a plain CLI. Timeouts are a hint that some desktops
+// ignore.
+
+use std::env;
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum NotifyError {
+    #[error("no desktop session: {0}")]
+    NoSession(String),
+    #[error("desktop notification failed: {0}")]
+    Desktop(#[from] notify_rust::error::Error),
+    #[error("failed to start the notification response thread: {0}")]
+    Thread(io::Error),
+}
+
+type Result<T> = std::result::Result<T, NotifyError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    /// Stays on screen until dismissed on most Linux desktops and on Windows.
+    Critical,
+}
+
+/// What the user did with a notification that had `on_response` set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Response {
+    /// The notification body itself was clicked.
+    Clicked,
+    /// One of the `action` buttons, by id.
+    Action(String),
+    /// Closed, expired, or no answer is coming on this platform.
+    Dismissed,
+}
+
+/// Where a notification ended up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Via {
+    Desktop,
+    Terminal { reason: String },
+}
+
+type ResponseHandler = Box<dyn FnOnce(Response) + Send + 'static>;
+
+// --- Session Detection ---
+
+/// Returns why desktop notifications can't work here, or `None` if they might.
+fn no_session_reason() -> Option<String> {
+    if env::var_os("CI").is_some() {
+        return Some("running under CI".to_string());
+    }
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        let has_bus = env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some_and(|v| !v.is_empty())
+            || env::var_os("XDG_RUNTIME_DIR").is_some_and(|dir| std::path::Path::new(&dir).join("bus").exists());
+        if !has_bus {
+            return Some("no D-Bus session bus".to_string());
+        }
+    }
+    None
+}
+
+// --- Notifications ---
+
+struct DesktopNotification {
+    title: String,
+    body: String,
+    urgency: Urgency,
+    /// `None` uses the desktop's default; `Some(Duration::ZERO)` asks for it to never expire.
+    timeout: Option<Duration>,
+    app_name: Option<String>,
+    icon: Option<String>,
+    actions: Vec<(String, String)>,
+    on_response: Option<ResponseHandler>,
+    terminal_fallback: bool,
+}
+
+impl fmt::Debug for DesktopNotification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DesktopNotification")
+            .field("title", &self.title)
+            .field("body", &self.body)
+            .field("urgency", &self.urgency)
+            .field("actions", &self.actions)
+            .field("on_response", &self.on_response.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl DesktopNotification {
+    fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: String::new(),
+            urgency: Urgency::Normal,
+            timeout: None,
+            app_name: None,
+            icon: None,
+            actions: Vec::new(),
+            on_response: None,
+            terminal_fallback: true,
+        }
+    }
+
+    fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    fn urgency(mut self, urgency: Urgency) -> Self {
+        self.urgency = urgency;
+        self
+    }
+
+    fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The application name shown with the notification (Linux and macOS).
+    fn app_name(mut self, name: impl Into<String>) -> Self {
+        self.app_name = Some(name.into());
+        self
+    }
+
+    /// A freedesktop icon name such as `"dialog-information"` on Linux, or an image path.
+    fn icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Adds a button. Its `id` comes back as `Response::Action(id)`.
+    fn action(mut self, id: impl Into<String>, label: impl Into<String>) -> Self {
+        self.actions.push((id.into(), label.into()));
+        self
+    }
+
+    /// Runs `handler` on a background thread once the user responds.
+    fn on_response(mut self, handler: impl FnOnce(Response) + Send + 'static) -> Self {
+        self.on_response = Some(Box::new(handler));
+        self
+    }
+
+    fn terminal_fallback(mut self, enabled: bool) -> Self {
+        self.terminal_fallback = enabled;
+        self
+    }
+
+    fn build(&self) -> notify_rust::Notification {
+        let mut n = notify_rust::Notification::new();
+        n.summary(&self.title).body(&self.body);
+        if let Some(name) = &self.app_name {
+            n.appname(name);
+        }
+        if let Some(icon) = &self.icon {
+            n.icon(icon);
+        }
+        for (id, label) in &self.actions {
+            n.action(id, label);
+        }
+        // A click on the body is reported as the "default" action.
+        if self.on_response.is_some() && !self.actions.iter().any(|(id, _)| id == "default") {
+            n.action("default", "Open");
+        }
+        if let Some(timeout) = self.timeout {
+            n.timeout(match timeout {
+                Duration::ZERO => notify_rust::Timeout::Never,
+                t => notify_rust::Timeout::Milliseconds(t.as_millis().min(u32::MAX as u128) as u32),
+            });
+        }
+        // macOS only supports urgency through the `preview-macos-un` feature.
+        #[cfg(not(target_os = "macos"))]
+        n.urgency(match self.urgency {
+            Urgency::Low => notify_rust::Urgency::Low,
+            Urgency::Normal => notify_rust::Urgency::Normal,
+            Urgency::Critical => notify_rust::Urgency::Critical,
+        });
+        n
+    }
+
+    /// Shows the notification, falling back to the terminal when there is no desktop.
+    ///
+    /// # Returns
+    /// A `Sent` that reports where the notification went and, when `on_response` was set,
+    /// can wait for the handler to run.
+    fn send(mut self) -> Result<Sent> {
+        let handler = self.on_response.take();
+        let shown = match no_session_reason() {
+            Some(reason) => Err(NotifyError::NoSession(reason)),
+            None => self.build().show().map_err(NotifyError::from),
+        };
+        let handle = match shown {
+            Ok(handle) => handle,
+            Err(e) if self.terminal_fallback => {
+                self.print_to_terminal();
+                if let Some(handler) = handler {
+                    handler(Response::Dismissed);
+                }
+                return Ok(Sent { via: Via::Terminal { reason: e.to_string() }, waiter: None });
+            }
+            Err(e) => return Err(e),
+        };
+        let waiter = match handler {
+            Some(handler) => Some(
+                thread::Builder::new()
+                    .name("notification-response".into())
+                    .spawn(move || handle.wait_for_action(|action| handler(parse_action(action))))
+                    .map_err(NotifyError::Thread)?,
+            ),
+            None => None,
+        };
+        Ok(Sent { via: Via::Desktop, waiter })
+    }
+
+    fn print_to_terminal(&self) {
+        let mut stderr = io::stderr().lock();
+        let bell = if stderr.is_terminal() { "\x07" } else { "" };
+        let marker = match self.urgency {
+            Urgency::Critical => "!! ",
+            _ => "",
+        };
+        let _ = match self.body.is_empty() {
+            true => writeln!(stderr, "{}[notify] {}{}", bell, marker, self.title),
+            false => writeln!(stderr, "{}[notify] {}{}: {}", bell, marker, self.title, self.body),
+        };
+    }
+}
+
+fn parse_action(action: &str) -> Response {
+    match action {
+        "default" => Response::Clicked,
+        "__closed" | "" => Response::Dismissed,
+        id => Response::Action(id.to_string()),
+    }
+}
+
+#[derive(Debug)]
+struct Sent {
+    via: Via,
+    waiter: Option<thread::JoinHandle<()>>,
+}
+
+impl Sent {
+    fn via(&self) -> &Via {
+        &self.via
+    }
+
+    /// Blocks until the `on_response` handler has run. Returns at once if there was none.
+    fn wait(self) {
+        if let Some(waiter) = self.waiter {
+            let _ = waiter.join();
+        }
+    }
+}
+
+// --- Job Completion ---
+
+/// Times a job and notifies when it finishes, if it ran long enough for the user to look away.
+struct JobNotifier {
+    name: String,
+    started: Instant,
+    threshold: Duration,
+}
+
+impl JobNotifier {
+    /// Starts timing `name` with a 10 second threshold.
+    fn start(name: impl Into<String>) -> Self {
+        Self { name: name.into(), started: Instant::now(), threshold: Duration::from_secs(10) }
+    }
+
+    fn threshold(mut self, threshold: Duration) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Notifies about `outcome`: normal urgency on success, critical with the error on failure.
+    ///
+    /// # Returns
+    /// `None` when the job finished under the threshold and nothing was sent.
+    fn finish<T, E: fmt::Display>(self, outcome: &std::result::Result<T, E>) -> Option<Sent> {
+        let elapsed = self.started.elapsed();
+        if elapsed < self.threshold {
+            return None;
+        }
+        let notification = match outcome {
+            Ok(_) => DesktopNotification::new(format!("{} finished", self.name))
+                .body(format!("Completed in {}", format_elapsed(elapsed)))
+                .icon("dialog-information"),
+            Err(e) => DesktopNotification::new(format!("{} failed", self.name))
+                .body(format!("After {}: {}", format_elapsed(elapsed), e))
+                .icon("dialog-error")
+                .urgency(Urgency::Critical),
+        };
+        // With the terminal fallback on, `send` only fails if a thread can't be spawned.
+        notification.send().ok()
+    }
+}
+
+/// Compact elapsed time; see `humanize.rs` for the full formatter.
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..=59 => format!("{:.1}s", elapsed.as_secs_f64()),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    // --- A one-off notification ---
+    let sent = DesktopNotification::new("Backup complete")
+        .body("42 files, 1.3 GiB written to /mnt/backup")
+        .app_name("backup")
+        .icon("drive-harddisk")
+        .timeout(Duration::from_secs(8))
+        .send()?;
+    println!("backup notice via {:?}", sent.via());
+
+    // --- Buttons and a click handler ---
+    let sent = DesktopNotification::new("Deploy ready")
+        .body("staging passed all checks")
+        .action("promote", "Promote to prod")
+        .action("logs", "Show logs")
+        .on_response(|response| match response {
+            Response::Action(id) if id == "promote" => println!("promoting..."),
+            Response::Action(id) => println!("action: {}", id),
+            Response::Clicked => println!("opened the deploy page"),
+            Response::Dismissed => println!("deploy notice dismissed"),
+        })
+        .send()?;
+    // A real tool would keep working here; the demo waits so the handler's output is visible.
+    if sent.via() == &Via::Desktop {
+        println!("waiting for a click (close the notification to continue)");
+    }
+    sent.wait();
+
+    // --- No fallback: surface the error instead ---
+    match DesktopNotification::new("strict").terminal_fallback(false).send() {
+        Ok(sent) => println!("strict notice via {:?}", sent.via()),
+        Err(e) => println!("strict notice failed: {}", e),
+    }
+
+    // --- Long-running jobs ---
+    let job = JobNotifier::start("quick lint");
+    let outcome: std::result::Result<(), String> = Ok(());
+    println!("quick job notified: {}", job.finish(&outcome).is_some());
+
+    let job = JobNotifier::start("nightly export").threshold(Duration::from_millis(200));
+    thread::sleep(Duration::from_millis(250));
+    let outcome: std::result::Result<u64, String> = Err("disk full writing part 7".to_string());
+    if let Some(sent) = job.finish(&outcome) {
+        println!("export failure via {:?}", sent.via());
+    }
+    Ok(())
+}
+*/
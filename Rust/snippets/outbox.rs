@@ -0,0 +1,447 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// rusqlite = { version = "0.32", features = ["bundled"] }
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+// tokio = { version = "1", features = ["full"] }
+// async-nats = "0.42" // Only for `JetStreamPublisher`
+//
+// The transactional outbox: never lose an event between "the order was saved" and "the
+// OrderPlaced message was published".
+// * Publishing straight after `COMMIT` loses the event if the process dies in between, and
+//   publishing before it announces orders that were rolled back. Instead, `add_to_outbox`
+//   inserts the event into an `outbox` table inside the same transaction as the business
+//   rows, so both are saved or neither is.
+// * `OutboxRelay` runs in the background, reads unsent rows in insertion order, publishes them,
+//   and marks them sent. A crash between publish and mark means the row is published again on
+//   restart: delivery is at-least-once. Every message carries a stable `message_id`
+//   (`outbox-<row id>`), which JetStream de-duplicates within its `duplicate_window` (see
+//   `ensure_stream` in `messaging.rs`). Consumers should still be idempotent.
+// * If a publish fails, the relay stops at that row and backs off, so events for the same
+//   aggregate are never published out of order.
+// * A row that can never be published (its stored headers don't decode) is poisoned instead:
+//   `poisoned_at_ms` and `last_error` are set, it's logged and counted, and the relay moves on.
+// * Run one relay per database. With several replicas, gate it with `LeaderElector` from
+//   `leader_election.rs`; a second relay is harmless, but it doubles the duplicates.
+
+use rusqlite::{params, Connection, Transaction};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::{watch, Notify};
+
+#[derive(Error, Debug)]
+enum OutboxError {
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("Event serialization failed: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("Publish failed: {0}")]
+    Publish(String),
+
+    #[error("Database task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+type Result<T> = std::result::Result<T, OutboxError>;
+
+fn now_ms() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0)
+}
+
+// --- Writing events ---
+
+/// Add this to the application's migrations (e.g. `MIGRATIONS` in `sqlite.rs`).
+const OUTBOX_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS outbox (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    topic TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    headers TEXT NOT NULL DEFAULT '{}',
+    created_at_ms INTEGER NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    sent_at_ms INTEGER,
+    poisoned_at_ms INTEGER
+);
+CREATE INDEX IF NOT EXISTS outbox_unsent ON outbox (id) WHERE sent_at_ms IS NULL AND poisoned_at_ms IS NULL;
+";
+
+/// Records `event` for publishing on `topic` once `tx` commits. Taking a `Transaction` (not a
+/// `Connection`) is deliberate: outside a transaction the guarantee is gone.
+///
+/// # Arguments
+///
+/// * `headers` - Message headers to publish with the event, e.g. a `traceparent` from
+///   `inject` in `trace_context.rs`, so consumers continue the request's trace.
+///
+/// # Returns
+///
+/// The outbox row id; the published message id is `outbox-<id>`.
+fn add_to_outbox<T: Serialize>(
+    tx: &Transaction<'_>,
+    topic: &str,
+    event: &T,
+    headers: &HashMap<String, String>,
+) -> Result<i64> {
+    tx.execute(
+        "INSERT INTO outbox (topic, payload, headers, created_at_ms) VALUES (?1, ?2, ?3, ?4)",
+        params![topic, serde_json::to_string(event)?, serde_json::to_string(headers)?, now_ms()],
+    )?;
+    Ok(tx.last_insert_rowid())
+}
+
+// --- Publishing ---
+
+/// An event read back from the outbox, ready to publish.
+#[derive(Debug, Clone)]
+struct OutboxMessage {
+    id: i64,
+    /// Stable across retries; brokers and consumers de-duplicate on it.
+    message_id: String,
+    topic: String,
+    payload: Vec<u8>,
+    headers: HashMap<String, String>,
+    attempts: u32,
+}
+
+/// Where the relay sends events. `Ok` must mean the broker has durably accepted the message.
+trait Publisher: Send + Sync + 'static {
+    fn publish(&self, message: &OutboxMessage) -> impl Future<Output = std::result::Result<(), String>> + Send;
+}
+
+/// Publishes to NATS JetStream and waits for the stream's ack. The outbox topic is the subject.
+struct JetStreamPublisher {
+    js: async_nats::jetstream::Context,
+}
+
+impl Publisher for JetStreamPublisher {
+    async fn publish(&self, message: &OutboxMessage) -> std::result::Result<(), String> {
+        let mut headers = async_nats::HeaderMap::new();
+        for (name, value) in &message.headers {
+            headers.insert(name.as_str(), value.as_str());
+        }
+        let publish = async_nats::jetstream::context::Publish::build()
+            .payload(message.payload.clone().into())
+            .message_id(&message.message_id)
+            .headers(headers);
+        self.js
+            .send_publish(message.topic.clone(), publish)
+            .await
+            .map_err(|e| e.to_string())?
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+// --- Relay ---
+
+/// Counters for monitoring; alert when `pending` keeps growing.
+#[derive(Debug, Default, Clone, Copy)]
+struct RelayStats {
+    published: u64,
+    failed: u64,
+    /// Rows set aside because they can't be decoded; find them with
+    /// `SELECT * FROM outbox WHERE poisoned_at_ms IS NOT NULL`.
+    poisoned: u64,
+    /// Unsent rows as of the last pass.
+    pending: u64,
+}
+
+/// Moves outbox rows to the broker. Owns its own connection to the database file, so the
+/// application's writes never wait on publishing.
+struct OutboxRelay<P> {
+    conn: Arc<Mutex<Connection>>,
+    publisher: P,
+    batch_size: usize,
+    poll_interval: Duration,
+    backoff_max: Duration,
+    wake: Arc<Notify>,
+    stats: Arc<Mutex<RelayStats>>,
+}
+
+impl<P: Publisher> OutboxRelay<P> {
+    fn open(path: impl AsRef<Path>, publisher: P) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(OUTBOX_SCHEMA)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            publisher,
+            batch_size: 100,
+            poll_interval: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60),
+            wake: Arc::new(Notify::new()),
+            stats: Arc::new(Mutex::new(RelayStats::default())),
+        })
+    }
+
+    /// How often to look for new rows when nobody wakes the relay through `notifier`.
+    fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// The cap on the delay between retries while the broker is failing.
+    fn backoff_max(mut self, max: Duration) -> Self {
+        self.backoff_max = max;
+        self
+    }
+
+    /// A handle the application calls after committing, so events go out without waiting
+    /// for the next poll. Purely an optimization; polling alone loses nothing.
+    fn notifier(&self) -> Arc<Notify> {
+        Arc::clone(&self.wake)
+    }
+
+    fn stats_handle(&self) -> Arc<Mutex<RelayStats>> {
+        Arc::clone(&self.stats)
+    }
+
+    async fn with_conn<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let conn = Arc::clone(&self.conn);
+        let value = tokio::task::spawn_blocking(move || f(&mut conn.lock().unwrap_or_else(|e| e.into_inner()))).await??;
+        Ok(value)
+    }
+
+    /// The next unsent rows in order. Rows whose headers don't decode are poisoned and left
+    /// out, so one bad row can't stall every row behind it.
+    async fn unsent(&self) -> Result<Vec<OutboxMessage>> {
+        let limit = self.batch_size as i64;
+        loop {
+            let (messages, poisoned) = self
+                .with_conn(move |conn| {
+                    let mut stmt = conn.prepare(
+                        "SELECT id, topic, payload, headers, attempts FROM outbox
+                         WHERE sent_at_ms IS NULL AND poisoned_at_ms IS NULL ORDER BY id LIMIT ?1",
+                    )?;
+                    let rows = stmt
+                        .query_map(params![limit], |row| {
+                            Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?, row.get(4)?))
+                        })?
+                        .collect::<rusqlite::Result<Vec<(i64, String, String, String, u32)>>>()?;
+                    let mut messages = Vec::with_capacity(rows.len());
+                    let mut poisoned = Vec::new();
+                    for (id, topic, payload, headers, attempts) in rows {
+                        match serde_json::from_str(&headers) {
+                            Ok(headers) => messages.push(OutboxMessage {
+                                id,
+                                message_id: format!("outbox-{}", id),
+                                topic,
+                                payload: payload.into_bytes(),
+                                headers,
+                                attempts,
+                            }),
+                            Err(e) => {
+                                let error = format!("malformed headers: {}", e);
+                                conn.execute(
+                                    "UPDATE outbox SET poisoned_at_ms = ?2, last_error = ?3 WHERE id = ?1",
+                                    params![id, now_ms(), error],
+                                )?;
+                                poisoned.push(format!("outbox-{} ({}): {}", id, topic, error));
+                            }
+                        }
+                    }
+                    Ok((messages, poisoned))
+                })
+                .await?;
+            for row in &poisoned {
+                eprintln!("outbox relay: poisoned {}", row);
+            }
+            self.stats.lock().unwrap_or_else(|e| e.into_inner()).poisoned += poisoned.len() as u64;
+            // A batch of nothing but poisoned rows says nothing about the rows after it.
+            if !messages.is_empty() || poisoned.is_empty() {
+                return Ok(messages);
+            }
+        }
+    }
+
+    /// Publishes unsent rows in order until the outbox is empty or a publish fails.
+    ///
+    /// # Returns
+    ///
+    /// How many rows were published. A failure is recorded on its row and returned as an
+    /// error after the rows before it have been marked sent.
+    async fn relay_once(&self) -> Result<usize> {
+        let mut published = 0;
+        loop {
+            let batch = self.unsent().await?;
+            if batch.is_empty() {
+                self.stats.lock().unwrap_or_else(|e| e.into_inner()).pending = 0;
+                return Ok(published);
+            }
+            for message in batch {
+                if let Err(error) = self.publisher.publish(&message).await {
+                    let (id, recorded) = (message.id, error.clone());
+                    self.with_conn(move |conn| {
+                        conn.execute(
+                            "UPDATE outbox SET attempts = attempts + 1, last_error = ?2 WHERE id = ?1",
+                            params![id, recorded],
+                        )
+                    })
+                    .await?;
+                    let pending = self
+                        .with_conn(|conn| {
+                            conn.query_row("SELECT COUNT(*) FROM outbox WHERE sent_at_ms IS NULL AND poisoned_at_ms IS NULL", [], |r| r.get(0))
+                        })
+                        .await?;
+                    let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+                    stats.failed += 1;
+                    stats.pending = pending;
+                    return Err(OutboxError::Publish(format!("{} ({}): {}", message.message_id, message.topic, error)));
+                }
+                // If we crash right here, the row is published again on restart.
+                let id = message.id;
+                self.with_conn(move |conn| {
+                    conn.execute(
+                        "UPDATE outbox SET sent_at_ms = ?2, attempts = attempts + 1 WHERE id = ?1",
+                        params![id, now_ms()],
+                    )
+                })
+                .await?;
+                published += 1;
+                self.stats.lock().unwrap_or_else(|e| e.into_inner()).published += 1;
+            }
+        }
+    }
+
+    /// Relays until `shutdown` flips to `true`: on every `notify`, every `poll_interval`, and
+    /// after failures with exponential backoff (starting at `poll_interval`).
+    async fn run(self, mut shutdown: watch::Receiver<bool>) {
+        let mut delay = self.poll_interval;
+        while !*shutdown.borrow() {
+            match self.relay_once().await {
+                Ok(_) => delay = self.poll_interval,
+                Err(e) => {
+                    eprintln!("outbox relay: {}", e);
+                    delay = (delay * 2).min(self.backoff_max);
+                }
+            }
+            tokio::select! {
+                _ = self.wake.notified(), if delay == self.poll_interval => {}
+                _ = tokio::time::sleep(delay) => {}
+                _ = shutdown.changed() => {}
+            }
+        }
+    }
+
+    /// Deletes rows sent more than `older_than` ago. Keeping them for a while helps when
+    /// debugging "was this event ever published?".
+    async fn purge_sent(&self, older_than: Duration) -> Result<usize> {
+        let cutoff = now_ms() - older_than.as_millis() as i64;
+        self.with_conn(move |conn| conn.execute("DELETE FROM outbox WHERE sent_at_ms IS NOT NULL AND sent_at_ms < ?1", params![cutoff]))
+            .await
+    }
+}
+
+/// Opens the application's connection to the same database, with the outbox table created.
+fn open_app_database(path: impl AsRef<Path>) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    conn.execute_batch(OUTBOX_SCHEMA)?;
+    Ok(conn)
+}
+
+// Example Usage
+/*
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Serialize, Deserialize, Debug)]
+struct OrderPlaced {
+    order_id: i64,
+    total_cents: i64,
+}
+
+/// Stands in for the broker: records messages and can be switched to failing.
+#[derive(Clone, Default)]
+struct RecordingPublisher {
+    sent: Arc<Mutex<Vec<String>>>,
+    down: Arc<AtomicBool>,
+}
+
+impl Publisher for RecordingPublisher {
+    async fn publish(&self, message: &OutboxMessage) -> std::result::Result<(), String> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err("connection refused".to_string());
+        }
+        let line = format!("{} {} {}", message.message_id, message.topic, String::from_utf8_lossy(&message.payload));
+        println!("  published {}", line);
+        self.sent.lock().unwrap().push(line);
+        Ok(())
+    }
+}
+
+/// Business logic: the order row and its event commit together, or not at all.
+fn place_order(conn: &mut Connection, total_cents: i64, fail_after_insert: bool) -> Result<i64> {
+    let tx = conn.transaction()?;
+    tx.execute("INSERT INTO orders (total_cents) VALUES (?1)", params![total_cents])?;
+    let order_id = tx.last_insert_rowid();
+    let headers = HashMap::from([("traceparent".to_string(), "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string())]);
+    add_to_outbox(&tx, "orders.placed", &OrderPlaced { order_id, total_cents }, &headers)?;
+    if fail_after_insert {
+        return Err(OutboxError::Publish("payment declined".into())); // Dropping `tx` rolls back both rows.
+    }
+    tx.commit()?;
+    Ok(order_id)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("outbox-{}.db", std::process::id()));
+    let mut app = open_app_database(&path)?;
+    app.execute_batch("CREATE TABLE orders (id INTEGER PRIMARY KEY, total_cents INTEGER NOT NULL)")?;
+
+    let publisher = RecordingPublisher::default();
+    let relay = OutboxRelay::open(&path, publisher.clone())?.poll_interval(Duration::from_millis(50)).backoff_max(Duration::from_millis(200));
+    let wake = relay.notifier();
+    let stats = relay.stats_handle();
+    let (shutdown_tx, shutdown) = watch::channel(false);
+    let relay_task = tokio::spawn(relay.run(shutdown));
+
+    place_order(&mut app, 2500, false)?;
+    wake.notify_one();
+    println!("rolled back: {:?}", place_order(&mut app, 999, true).err());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // The broker goes down; orders keep committing and their events wait in the outbox.
+    publisher.down.store(true, Ordering::SeqCst);
+    for total in [100, 200, 300] {
+        place_order(&mut app, total, false)?;
+    }
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    println!("while down: {:?}", *stats.lock().unwrap());
+
+    // A row written by a buggy older release: it is set aside instead of blocking the rest.
+    app.execute("INSERT INTO outbox (topic, payload, headers, created_at_ms) VALUES ('orders.placed', '{}', 'not json', 0)", [])?;
+    place_order(&mut app, 400, false)?;
+
+    publisher.down.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    println!("recovered: {:?}", *stats.lock().unwrap());
+
+    let _ = shutdown_tx.send(true);
+    relay_task.await?;
+    let orders: i64 = app.query_row("SELECT COUNT(*) FROM orders", [], |r| r.get(0))?;
+    println!("{} orders, {} events published", orders, publisher.sent.lock().unwrap().len());
+
+    // With NATS JetStream (`ensure_stream` from messaging.rs must cover `orders.>`):
+    // let js = async_nats::jetstream::new(async_nats::connect("nats://127.0.0.1:4222").await?);
+    // let relay = OutboxRelay::open(&path, JetStreamPublisher { js })?;
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+*/
@@ -0,0 +1,492 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// arrow = { version = "57", default-features = false }
+// parquet = { version = "57", default-features = false, features = ["arrow", "zstd", "snap"] }
+// serde_arrow = { version = "0.15", features = ["arrow-57"] }
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+// chrono = { version = "0.4", features = ["serde"] } # Only for the example's timestamps
+//
+// Parquet files from plain Rust structs, for handing ETL output to data-lake tooling (DuckDB,
+// Spark, Athena, pandas/polars) and reading their exports back:
+// * `ParquetWriter<T>` writes any `Serialize` type. The column schema is derived from the
+//   type itself with `serde_arrow`, so `Option<T>` becomes a nullable column, integers keep
+//   their width, `Vec<T>` becomes a list, and unit-only enums become dictionary-encoded
+//   strings. Types that only describe themselves through their data (chrono timestamps,
+//   `serde_json::Value`) are traced from the first batch instead, where RFC 3339 strings become
+//   timestamp columns. `WriterOptions::timestamp` and `column_type` pin a column's type.
+// * Rows are buffered into Arrow batches and written in row groups of `row_group_rows`,
+//   compressed with zstd by default. The file is written under a temporary name and renamed
+//   by `finish`, so readers never see a half-written file.
+// * `ParquetReader<T>` iterates rows back one batch at a time, reading only the columns `T`
+//   has, and optionally only selected row groups. `inspect` prints the layout for debugging.
+// * `ParquetSink` plugs into the `etl.rs` pipeline: it takes JSON records, infers the schema
+//   from the first batch, and writes them the same way.
+//
+// For one-off queries over Parquet files, DuckDB (`duckdb` crate) or DataFusion read them
+// directly; this snippet covers producing and consuming them from application code.
+
+use arrow::datatypes::{DataType, Field, FieldRef, TimeUnit};
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_arrow::schema::{SchemaLike, TracingOptions};
+use serde_json::{Map, Value};
+use std::fs::{self, File};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum ParquetError {
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error("Arrow conversion error: {0}")]
+    Arrow(#[from] serde_arrow::Error),
+    #[error("schema error: {0}")]
+    Schema(String),
+}
+
+type Result<T> = std::result::Result<T, ParquetError>;
+
+// --- Schema ---
+
+/// Tracing options that match what other Parquet tools expect: plain `Utf8` strings and
+/// `List` arrays rather than Arrow's 64-bit-offset variants.
+fn tracing_options() -> TracingOptions {
+    TracingOptions::default()
+        .strings_as_large_utf8(false)
+        .bytes_as_large_binary(false)
+        .sequence_as_large_list(false)
+        .enums_without_data_as_strings(true)
+}
+
+/// The Arrow fields for `T`, traced from the type alone. Fails for types that only describe
+/// themselves through their data, such as chrono timestamps and `serde_json::Value`.
+fn fields_for_type<T: DeserializeOwned>() -> Result<Vec<FieldRef>> {
+    Ok(Vec::<FieldRef>::from_type::<T>(tracing_options())?)
+}
+
+/// The Arrow fields seen in `rows`. RFC 3339 strings become timestamps, and a column that is
+/// null in every sample gets the `Null` type unless an override names it.
+fn fields_for_samples<T: Serialize>(rows: &[T]) -> Result<Vec<FieldRef>> {
+    Ok(Vec::<FieldRef>::from_samples(rows, tracing_options().allow_null_fields(true).guess_dates(true))?)
+}
+
+/// Replaces the traced type of each named column.
+fn apply_overrides(fields: Vec<FieldRef>, overrides: &[(String, DataType)]) -> Result<Vec<FieldRef>> {
+    for (name, _) in overrides {
+        if !fields.iter().any(|f| f.name() == name) {
+            return Err(ParquetError::Schema(format!("no column named {:?} to override", name)));
+        }
+    }
+    Ok(fields
+        .into_iter()
+        .map(|field| match overrides.iter().find(|(name, _)| name == field.name()) {
+            Some((_, data_type)) => Arc::new(Field::new(field.name(), data_type.clone(), field.is_nullable())),
+            None => field,
+        })
+        .collect())
+}
+
+// --- Writing ---
+
+#[derive(Debug, Clone)]
+struct WriterOptions {
+    compression: Compression,
+    row_group_rows: usize,
+    batch_rows: usize,
+    column_types: Vec<(String, DataType)>,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        Self {
+            compression: Compression::ZSTD(ZstdLevel::default()),
+            row_group_rows: 128 * 1024,
+            batch_rows: 8 * 1024,
+            column_types: Vec::new(),
+        }
+    }
+}
+
+impl WriterOptions {
+    /// Snappy is faster to write; zstd (the default) makes noticeably smaller files.
+    fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Rows per row group: the unit readers can skip using column statistics. Larger groups
+    /// compress better; smaller ones let readers skip more precisely.
+    fn row_group_rows(mut self, rows: usize) -> Self {
+        self.row_group_rows = rows.max(1);
+        self
+    }
+
+    /// Rows buffered in memory before being converted to an Arrow batch.
+    fn batch_rows(mut self, rows: usize) -> Self {
+        self.batch_rows = rows.max(1);
+        self
+    }
+
+    /// Stores the named column as `data_type` instead of the type traced from the struct.
+    fn column_type(mut self, name: &str, data_type: DataType) -> Self {
+        self.column_types.push((name.to_string(), data_type));
+        self
+    }
+
+    /// Stores an RFC 3339 string column (e.g. `chrono::DateTime<Utc>`) as a UTC timestamp in
+    /// milliseconds, so query engines can filter and bucket it by time.
+    fn timestamp(self, name: &str) -> Self {
+        self.column_type(name, DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())))
+    }
+
+    fn properties(&self) -> WriterProperties {
+        WriterProperties::builder()
+            .set_compression(self.compression)
+            .set_max_row_group_size(self.row_group_rows)
+            .build()
+    }
+}
+
+/// What `finish` wrote.
+#[derive(Debug, Clone)]
+struct Written {
+    path: PathBuf,
+    rows: u64,
+    row_groups: usize,
+    bytes: u64,
+}
+
+/// Writes rows of `T` to a Parquet file. Dropping it without calling `finish` discards the
+/// partial file.
+struct ParquetWriter<T> {
+    writer: Option<ArrowWriter<File>>,
+    /// Known up front when traced from `T`; otherwise set from the first batch.
+    fields: Option<Vec<FieldRef>>,
+    options: WriterOptions,
+    buffer: Vec<T>,
+    rows: u64,
+    path: PathBuf,
+    tmp: PathBuf,
+}
+
+impl<T: Serialize + DeserializeOwned> ParquetWriter<T> {
+    /// Uses the schema of `T` when it can be traced from the type, and otherwise infers it
+    /// from the first batch of rows as `from_samples` does.
+    ///
+    /// # Arguments
+    /// * `path` - Destination; replaced atomically by `finish`.
+    /// * `options` - Compression, row group size, and column type overrides.
+    fn create(path: impl AsRef<Path>, options: WriterOptions) -> Result<Self> {
+        let mut writer = Self::from_samples(path, options);
+        if let Ok(fields) = fields_for_type::<T>() {
+            writer.fields = Some(apply_overrides(fields, &writer.options.column_types)?);
+        }
+        Ok(writer)
+    }
+}
+
+impl<T: Serialize> ParquetWriter<T> {
+    /// Infers the schema from the first `batch_rows` rows, for dynamic records.
+    fn from_samples(path: impl AsRef<Path>, options: WriterOptions) -> Self {
+        let path = path.as_ref().to_path_buf();
+        Self {
+            writer: None,
+            fields: None,
+            buffer: Vec::with_capacity(options.batch_rows),
+            options,
+            rows: 0,
+            tmp: path.with_extension("parquet.tmp"),
+            path,
+        }
+    }
+
+    fn write(&mut self, row: T) -> Result<()> {
+        self.buffer.push(row);
+        if self.buffer.len() >= self.options.batch_rows {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    fn write_all(&mut self, rows: impl IntoIterator<Item = T>) -> Result<()> {
+        rows.into_iter().try_for_each(|row| self.write(row))
+    }
+
+    fn open_writer(&mut self) -> Result<&mut ArrowWriter<File>> {
+        if self.writer.is_none() {
+            let fields = match self.fields.take() {
+                Some(fields) => fields,
+                None if self.buffer.is_empty() => return Err(ParquetError::Schema("no rows to infer a schema from".into())),
+                None => apply_overrides(fields_for_samples(&self.buffer)?, &self.options.column_types)?,
+            };
+            let file = File::create(&self.tmp).map_err(|source| ParquetError::Io { path: self.tmp.clone(), source })?;
+            let schema = Arc::new(arrow::datatypes::Schema::new(fields.clone()));
+            self.writer = Some(ArrowWriter::try_new(file, schema, Some(self.options.properties()))?);
+            self.fields = Some(fields);
+        }
+        Ok(self.writer.as_mut().expect("writer was just opened"))
+    }
+
+    fn flush_batch(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.open_writer()?;
+        let fields = self.fields.as_deref().expect("fields are set when the writer opens");
+        let batch = serde_arrow::to_record_batch(fields, &self.buffer)?;
+        self.writer.as_mut().expect("writer is open").write(&batch)?;
+        self.rows += self.buffer.len() as u64;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Writes buffered rows and the file footer, then moves the file into place. With no rows
+    /// at all, this needs a schema traced from `T`.
+    fn finish(mut self) -> Result<Written> {
+        self.flush_batch()?;
+        self.open_writer()?;
+        let metadata = self.writer.take().expect("writer is open").close()?;
+        fs::rename(&self.tmp, &self.path).map_err(|source| ParquetError::Io { path: self.path.clone(), source })?;
+        let bytes = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        Ok(Written { path: self.path.clone(), rows: self.rows, row_groups: metadata.num_row_groups(), bytes })
+    }
+}
+
+impl<T> Drop for ParquetWriter<T> {
+    fn drop(&mut self) {
+        if self.writer.take().is_some() {
+            let _ = fs::remove_file(&self.tmp);
+        }
+    }
+}
+
+/// Writes `rows` to `path` in one call.
+fn write_parquet<T: Serialize + DeserializeOwned>(path: impl AsRef<Path>, rows: impl IntoIterator<Item = T>, options: WriterOptions) -> Result<Written> {
+    let mut writer = ParquetWriter::create(path, options)?;
+    writer.write_all(rows)?;
+    writer.finish()
+}
+
+// --- Reading ---
+
+/// Iterates the rows of a Parquet file as `T`, decoding one batch at a time.
+struct ParquetReader<T> {
+    batches: ParquetRecordBatchReader,
+    pending: std::vec::IntoIter<T>,
+    _row: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> ParquetReader<T> {
+    fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_row_groups(path, None)
+    }
+
+    /// Reads only the given row groups if any, and only the columns `T` has when its schema
+    /// can be traced from the type (otherwise all columns are decoded and extras ignored).
+    ///
+    /// # Returns
+    /// `ParquetError::Schema` if the file lacks a column `T` requires. Columns for `Option`
+    /// fields may be missing; they read as `None`.
+    fn open_row_groups(path: impl AsRef<Path>, row_groups: Option<Vec<usize>>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|source| ParquetError::Io { path: path.to_path_buf(), source })?;
+        let mut builder = ParquetRecordBatchReaderBuilder::try_new(file)?.with_batch_size(8 * 1024);
+
+        if let Ok(wanted) = fields_for_type::<T>() {
+            let mut indices = Vec::new();
+            for field in &wanted {
+                match builder.schema().index_of(field.name()) {
+                    Ok(index) => indices.push(index),
+                    Err(_) if field.is_nullable() => {}
+                    Err(_) => return Err(ParquetError::Schema(format!("{} has no column {:?}", path.display(), field.name()))),
+                }
+            }
+            let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+            builder = builder.with_projection(mask);
+        }
+        if let Some(groups) = row_groups {
+            builder = builder.with_row_groups(groups);
+        }
+        Ok(Self { batches: builder.build()?, pending: Vec::new().into_iter(), _row: PhantomData })
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for ParquetReader<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.pending.next() {
+                return Some(Ok(row));
+            }
+            let batch = match self.batches.next()? {
+                Ok(batch) => batch,
+                Err(e) => return Some(Err(ParquetError::Parquet(e.into()))),
+            };
+            match serde_arrow::from_record_batch::<Vec<T>>(&batch) {
+                Ok(rows) => self.pending = rows.into_iter(),
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+/// Row count, row groups, and schema of a Parquet file, read from its footer only.
+fn inspect(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|source| ParquetError::Io { path: path.to_path_buf(), source })?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let metadata = builder.metadata();
+    let mut out = format!(
+        "{}: {} rows in {} row groups, created by {}\n",
+        path.display(),
+        metadata.file_metadata().num_rows(),
+        metadata.num_row_groups(),
+        metadata.file_metadata().created_by().unwrap_or("unknown")
+    );
+    for field in builder.schema().fields() {
+        out.push_str(&format!("  {}: {}{}\n", field.name(), field.data_type(), if field.is_nullable() { " (nullable)" } else { "" }));
+    }
+    for (i, group) in metadata.row_groups().iter().enumerate() {
+        out.push_str(&format!("  row group {}: {} rows, {} bytes compressed\n", i, group.num_rows(), group.compressed_size()));
+    }
+    Ok(out)
+}
+
+// --- ETL bridge ---
+
+/// One record, as in `etl.rs`: a JSON object keyed by column name.
+type Record = Map<String, Value>;
+
+/// The sink half of the `etl.rs` pipeline, repeated here so this file stands alone.
+trait Sink: Send {
+    fn write(&mut self, record: &Record) -> Result<()>;
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes pipeline records to Parquet. The schema is inferred from the first `batch_rows`
+/// records, so give a `column_type` for any column that is null throughout that sample.
+struct ParquetSink {
+    writer: Option<ParquetWriter<Record>>,
+    written: Option<Written>,
+}
+
+impl ParquetSink {
+    fn create(path: impl AsRef<Path>, options: WriterOptions) -> Self {
+        Self { writer: Some(ParquetWriter::from_samples(path, options)), written: None }
+    }
+}
+
+impl Sink for ParquetSink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        self.writer.as_mut().ok_or_else(|| ParquetError::Schema("sink already finished".into()))?.write(record.clone())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(writer) = self.writer.take() {
+            self.written = Some(writer.finish()?);
+        }
+        Ok(())
+    }
+}
+
+// Example Usage
+/*
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+enum Channel {
+    Web,
+    Mobile,
+    Store,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Order {
+    id: u64,
+    customer: String,
+    channel: Channel,
+    total_cents: i64,
+    coupon: Option<String>,
+    items: Vec<String>,
+    placed_at: DateTime<Utc>,
+}
+
+/// A narrower view of the same file: only these columns are read.
+#[derive(Debug, Deserialize)]
+struct OrderTotal {
+    id: u64,
+    total_cents: i64,
+}
+
+fn main() -> Result<()> {
+    let dir = std::env::temp_dir().join("parquet-demo");
+    fs::create_dir_all(&dir).map_err(|source| ParquetError::Io { path: dir.clone(), source })?;
+    let path = dir.join("orders.parquet");
+
+    let start = Utc.with_ymd_and_hms(2024, 10, 1, 9, 0, 0).unwrap();
+    let orders = (0..25_000u64).map(|i| Order {
+        id: i,
+        customer: format!("cust-{:04}", i % 1_000),
+        channel: [Channel::Web, Channel::Mobile, Channel::Store][(i % 3) as usize],
+        total_cents: 500 + (i as i64 * 7_919) % 20_000,
+        coupon: (i % 10 == 0).then(|| "FALL10".to_string()),
+        items: (0..1 + i % 3).map(|n| format!("sku-{}", (i + n) % 50)).collect(),
+        placed_at: start + chrono::Duration::minutes(i as i64),
+    });
+    let options = WriterOptions::default().row_group_rows(10_000).timestamp("placed_at");
+    let written = write_parquet(&path, orders, options)?;
+    println!("wrote {} rows in {} row groups ({} bytes)", written.rows, written.row_groups, written.bytes);
+    print!("{}", inspect(&path)?);
+
+    // Full rows back, including the timestamp and enum.
+    let first: Vec<Order> = ParquetReader::open(&path)?.take(2).collect::<Result<_>>()?;
+    println!("{:?}", first[0]);
+
+    // Only two columns, only the last row group.
+    let totals: Vec<OrderTotal> = ParquetReader::open_row_groups(&path, Some(vec![2]))?.collect::<Result<_>>()?;
+    let sum: i64 = totals.iter().map(|o| o.total_cents).sum();
+    println!("last row group: {} orders from id {}, {} cents total", totals.len(), totals[0].id, sum);
+
+    // Reading a struct whose required column the file lacks.
+    #[derive(Debug, Deserialize)]
+    struct Missing {
+        #[allow(dead_code)]
+        region: String,
+    }
+    match ParquetReader::<Missing>::open(&path) {
+        Err(e) => println!("{}", e),
+        Ok(_) => println!("unexpected success"),
+    }
+
+    // JSON records from an ETL pipeline.
+    let mut sink = ParquetSink::create(dir.join("events.parquet"), WriterOptions::default().batch_rows(2));
+    for line in [
+        r#"{"event": "signup", "user": 1, "at": "2024-10-01T09:00:00Z", "score": 0.5}"#,
+        r#"{"event": "login", "user": 1, "at": "2024-10-01T09:05:00Z", "score": null}"#,
+        r#"{"event": "logout", "user": 2, "at": "2024-10-01T10:00:00Z", "score": 1.5}"#,
+    ] {
+        sink.write(&serde_json::from_str(line).unwrap())?;
+    }
+    sink.finish()?;
+    println!("{:?}", sink.written);
+    print!("{}", inspect(dir.join("events.parquet"))?);
+
+    fs::remove_dir_all(&dir).ok();
+    Ok(())
+}
+*/
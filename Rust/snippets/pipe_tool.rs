@@ -0,0 +1,465 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// crossbeam-channel = "0.5"
+// csv = "1"
+// serde_json = "1.0"
+// thiserror = "1.0"
+//
+// A harness for Unix-filter style tools: read records from stdin, transform them, write them
+// to stdout, and behave well inside a shell pipeline (`cat big.ndjson | mytool | head`).
+// `etl.rs` is the bigger sibling for file/database/HTTP pipelines with reject files; this is
+// the small version for a single `stdin -> stdout` step.
+// * Records are read as plain lines, NDJSON, or CSV with a header row (each row becomes a JSON
+//   object of strings, as in `etl.rs`), and written in any of the three.
+// * The transform runs on `workers` threads, but output stays in input order: finished
+//   records wait in a reorder buffer until everything before them has been written.
+// * Memory is bounded by `max_in_flight`: the reader takes a permit before reading each
+//   record and the writer returns it after writing, so a slow consumer (or one slow record)
+//   stops the reading instead of piling records up in memory.
+// * Output is buffered and flushed whenever the workers have nothing ready, so a tool fed
+//   interactively still answers line by line.
+// * Rust ignores SIGPIPE, so writing to a closed pipe returns `BrokenPipe` and `println!`
+//   would panic. Here a closed stdout just stops the pipeline: `PipeStats::closed_early` is
+//   set and the process can exit 0, like `grep` in `... | grep x | head -1`.
+
+use crossbeam_channel::{bounded, Receiver, Sender};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum PipeError {
+    /// One record couldn't be parsed or transformed; skipped under `OnError::Skip`.
+    #[error("record {record}: {message}")]
+    Record { record: u64, message: String },
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+}
+
+type Result<T> = std::result::Result<T, PipeError>;
+
+/// What the transform returns: `Ok(Some(v))` to write `v`, `Ok(None)` to drop the record, or
+/// `Err(message)` for a bad record.
+type TransformResult = std::result::Result<Option<Value>, String>;
+
+// --- Formats ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// One string per line. On output, strings are written as-is and anything else as JSON.
+    Lines,
+    /// One JSON value per line; blank lines are skipped.
+    Ndjson,
+    /// A header row, then one JSON object per row. Output columns come from the first record.
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s {
+            "lines" | "text" => Ok(Format::Lines),
+            "ndjson" | "jsonl" | "json" => Ok(Format::Ndjson),
+            "csv" => Ok(Format::Csv),
+            other => Err(format!("unknown format {:?} (expected lines, ndjson, or csv)", other)),
+        }
+    }
+}
+
+/// Pulls records from the input; `record` is the line number (the CSV row's line for CSV).
+type RecordIter = Box<dyn Iterator<Item = Result<(u64, Value)>> + Send>;
+
+fn read_records(format: Format, input: impl Read + Send + 'static) -> Result<RecordIter> {
+    let input = BufReader::new(input);
+    Ok(match format {
+        Format::Lines => Box::new(input.lines().zip(1..).map(|(line, n)| Ok((n, Value::String(line?))))),
+        Format::Ndjson => Box::new(input.lines().zip(1..).filter_map(|(line, n)| match line {
+            Err(e) => Some(Err(e.into())),
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(
+                serde_json::from_str(&line)
+                    .map(|value| (n, value))
+                    .map_err(|e| PipeError::Record { record: n, message: format!("invalid JSON: {}", e) }),
+            ),
+        })),
+        Format::Csv => {
+            let mut reader = csv::Reader::from_reader(input);
+            let headers: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+            Box::new(reader.into_records().map(move |row| match row {
+                Ok(row) => {
+                    let line = row.position().map_or(0, |p| p.line());
+                    let object: Map<String, Value> = headers.iter().cloned().zip(row.iter().map(|v| Value::String(v.to_string()))).collect();
+                    Ok((line, Value::Object(object)))
+                }
+                // Wrong field count etc. is a bad record; I/O errors stop the pipeline.
+                Err(e) if !matches!(e.kind(), csv::ErrorKind::Io(_)) => {
+                    let line = e.position().map_or(0, |p| p.line());
+                    Err(PipeError::Record { record: line, message: e.to_string() })
+                }
+                Err(e) => Err(e.into()),
+            }))
+        }
+    })
+}
+
+enum RecordWriter<W: Write> {
+    Lines(BufWriter<W>),
+    Ndjson(BufWriter<W>),
+    Csv { writer: Box<csv::Writer<W>>, columns: Option<Vec<String>> },
+}
+
+impl<W: Write> RecordWriter<W> {
+    fn new(format: Format, output: W) -> Self {
+        match format {
+            Format::Lines => RecordWriter::Lines(BufWriter::new(output)),
+            Format::Ndjson => RecordWriter::Ndjson(BufWriter::new(output)),
+            Format::Csv => RecordWriter::Csv { writer: Box::new(csv::Writer::from_writer(output)), columns: None },
+        }
+    }
+
+    fn write(&mut self, value: &Value) -> io::Result<()> {
+        match self {
+            RecordWriter::Lines(out) => match value {
+                Value::String(s) => writeln!(out, "{}", s),
+                other => writeln!(out, "{}", other),
+            },
+            RecordWriter::Ndjson(out) => writeln!(out, "{}", value),
+            RecordWriter::Csv { writer, columns } => {
+                let single;
+                let object = match value {
+                    Value::Object(object) => object,
+                    other => {
+                        single = Map::from_iter([("value".to_string(), other.clone())]);
+                        &single
+                    }
+                };
+                let columns = match columns {
+                    Some(columns) => columns,
+                    None => {
+                        let names: Vec<String> = object.keys().cloned().collect();
+                        writer.write_record(&names)?;
+                        columns.insert(names)
+                    }
+                };
+                let cells = columns.iter().map(|c| match object.get(c) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => other.to_string(),
+                });
+                Ok(writer.write_record(cells)?)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            RecordWriter::Lines(out) | RecordWriter::Ndjson(out) => out.flush(),
+            RecordWriter::Csv { writer, .. } => writer.flush(),
+        }
+    }
+}
+
+// --- The pipeline ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OnError {
+    /// Stop at the first bad record and return its error.
+    Fail,
+    /// Report bad records on stderr and carry on.
+    Skip,
+}
+
+#[derive(Debug, Default, Clone)]
+struct PipeStats {
+    read: u64,
+    /// Passed to the writer. After `closed_early`, the last few may have died in its buffer.
+    written: u64,
+    filtered: u64,
+    skipped: u64,
+    /// stdout was closed by the reader (e.g. `head`) before the input ran out.
+    closed_early: bool,
+}
+
+impl fmt::Display for PipeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} read, {} written, {} filtered, {} skipped", self.read, self.written, self.filtered, self.skipped)?;
+        if self.closed_early {
+            f.write_str(" (output closed early)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Sequence number, source record number, and the parsed record or its error.
+type Item = (u64, u64, Result<Value>);
+type Done = (u64, u64, Result<Option<Value>>);
+
+#[derive(Debug, Clone)]
+struct PipeTool {
+    input: Format,
+    output: Format,
+    workers: usize,
+    max_in_flight: usize,
+    on_error: OnError,
+}
+
+impl PipeTool {
+    fn new(input: Format, output: Format) -> Self {
+        Self { input, output, workers: 1, max_in_flight: 256, on_error: OnError::Fail }
+    }
+
+    /// Runs the transform on `n` threads; output order still matches input order.
+    fn workers(mut self, n: usize) -> Self {
+        self.workers = n.max(1);
+        self
+    }
+
+    /// The most records read but not yet written, including those in the reorder buffer.
+    fn max_in_flight(mut self, n: usize) -> Self {
+        self.max_in_flight = n.max(1);
+        self
+    }
+
+    fn on_error(mut self, policy: OnError) -> Self {
+        self.on_error = policy;
+        self
+    }
+
+    /// Filters stdin to stdout.
+    fn run_stdio<F>(self, transform: F) -> Result<PipeStats>
+    where
+        F: Fn(Value) -> TransformResult + Send + Sync + 'static,
+    {
+        self.run(io::stdin(), io::stdout().lock(), transform)
+    }
+
+    /// Reads records from `input`, applies `transform`, and writes the results to `output`.
+    ///
+    /// The reader and workers run on their own threads and the writer on the calling thread.
+    /// The reader is not joined if the output closes early, since it may be blocked reading a
+    /// terminal or a producer that never ends; it exits at its next record.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Any reader; `io::stdin()` for a real filter.
+    /// * `output` - Any writer; a `BrokenPipe` error from it ends the run successfully.
+    /// * `transform` - Called once per record, possibly from several threads at once. A panic
+    ///   counts as a bad record.
+    ///
+    /// # Returns
+    ///
+    /// Counts for the run, or the first fatal error (or bad record, under `OnError::Fail`).
+    fn run<R, W, F>(self, input: R, output: W, transform: F) -> Result<PipeStats>
+    where
+        R: Read + Send + 'static,
+        W: Write,
+        F: Fn(Value) -> TransformResult + Send + Sync + 'static,
+    {
+        let records = read_records(self.input, input)?;
+        let transform = Arc::new(transform);
+
+        // Each record in flight holds one permit, from reading until it's written.
+        let (permit_tx, permit_rx) = bounded::<()>(self.max_in_flight);
+        for _ in 0..self.max_in_flight {
+            permit_tx.send(()).expect("channel has room for every permit");
+        }
+        let (raw_tx, raw_rx) = bounded::<Item>(self.max_in_flight);
+        let (done_tx, done_rx) = bounded::<Done>(self.max_in_flight);
+
+        let reader = thread::spawn(move || read_into(records, permit_rx, raw_tx));
+        let workers: Vec<_> = (0..self.workers)
+            .map(|_| {
+                let (rx, tx, transform) = (raw_rx.clone(), done_tx.clone(), Arc::clone(&transform));
+                thread::spawn(move || transform_records(transform.as_ref(), rx, tx))
+            })
+            .collect();
+        // Close our copies so `done_rx` ends when the workers are done.
+        drop((raw_rx, done_tx));
+
+        let mut out = RecordWriter::new(self.output, output);
+        let mut stats = PipeStats::default();
+        let mut pending: BTreeMap<u64, (u64, Result<Option<Value>>)> = BTreeMap::new();
+        let mut next = 0;
+        let written = (|| -> Result<()> {
+            for (seq, record, outcome) in done_rx.iter() {
+                pending.insert(seq, (record, outcome));
+                while let Some((record, outcome)) = pending.remove(&next) {
+                    next += 1;
+                    stats.read += 1;
+                    match outcome {
+                        Ok(Some(value)) => {
+                            out.write(&value)?;
+                            stats.written += 1;
+                        }
+                        Ok(None) => stats.filtered += 1,
+                        Err(PipeError::Record { message, .. }) if self.on_error == OnError::Skip => {
+                            eprintln!("skipping record {}: {}", record, message);
+                            stats.skipped += 1;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                    // The reader may have exited already; a lost permit doesn't matter then.
+                    let _ = permit_tx.try_send(());
+                }
+                if done_rx.is_empty() {
+                    out.flush()?;
+                }
+            }
+            Ok(out.flush()?)
+        })();
+
+        match written {
+            Err(PipeError::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => {
+                // Dropping the channels stops the workers and, at its next record, the reader.
+                stats.closed_early = true;
+                Ok(stats)
+            }
+            Err(e) => Err(e),
+            Ok(()) => {
+                for worker in workers {
+                    worker.join().expect("worker threads catch transform panics");
+                }
+                // A fatal read error ends the stream early; report it rather than partial success.
+                reader.join().expect("reader thread does not panic")?;
+                Ok(stats)
+            }
+        }
+    }
+}
+
+fn read_into(records: RecordIter, permits: Receiver<()>, tx: Sender<Item>) -> Result<()> {
+    for (seq, item) in (0..).zip(records) {
+        if permits.recv().is_err() {
+            return Ok(());
+        }
+        match item {
+            Ok((record, value)) => {
+                if tx.send((seq, record, Ok(value))).is_err() {
+                    return Ok(());
+                }
+            }
+            Err(PipeError::Record { record, message }) => {
+                if tx.send((seq, record, Err(PipeError::Record { record, message }))).is_err() {
+                    return Ok(());
+                }
+            }
+            Err(fatal) => return Err(fatal),
+        }
+    }
+    Ok(())
+}
+
+fn transform_records(transform: &(dyn Fn(Value) -> TransformResult + Sync), rx: Receiver<Item>, tx: Sender<Done>) {
+    for (seq, record, item) in rx.iter() {
+        let outcome = item.and_then(|value| match panic::catch_unwind(AssertUnwindSafe(|| transform(value))) {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(message)) => Err(PipeError::Record { record, message }),
+            Err(panic) => {
+                let message = panic.downcast_ref::<&str>().map(|s| s.to_string()).or_else(|| panic.downcast_ref::<String>().cloned());
+                Err(PipeError::Record { record, message: format!("transform panicked: {}", message.unwrap_or_default()) })
+            }
+        });
+        if tx.send((seq, record, outcome)).is_err() {
+            return;
+        }
+    }
+}
+
+// Example Usage
+/*
+use std::io::Cursor;
+use std::time::Duration;
+
+/// Stands in for `yes`: an input that never ends.
+struct Endless(u64);
+
+impl Read for Endless {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0 += 1;
+        let line = format!("line {}\n", self.0);
+        let n = line.len().min(buf.len());
+        buf[..n].copy_from_slice(&line.as_bytes()[..n]);
+        Ok(n)
+    }
+}
+
+/// Stands in for `head -3`: accepts three lines, then closes the pipe.
+struct Head {
+    lines: usize,
+}
+
+impl Write for Head {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.lines >= 3 {
+            return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+        }
+        // Take whole lines up to the limit; the next write finds the pipe closed.
+        let mut taken = 0;
+        for line in buf.split_inclusive(|&b| b == b'\n').take(3 - self.lines) {
+            io::stdout().write_all(line)?;
+            taken += line.len();
+            self.lines += 1;
+        }
+        Ok(taken)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+fn main() -> Result<()> {
+    // A real tool takes the formats from flags and filters stdin:
+    //   let tool = PipeTool::new("ndjson".parse()?, "csv".parse()?).workers(8);
+    //   let stats = tool.run_stdio(|value| Ok(Some(value)))?;
+
+    // Slow, uneven work on 4 threads; the output is still in input order.
+    println!("--- lines, 4 workers ---");
+    let input = "alpha\nbravo\ncharlie\ndelta\necho\nfoxtrot\n";
+    let stats = PipeTool::new(Format::Lines, Format::Lines).workers(4).run(Cursor::new(input), io::stdout(), |value| {
+        let word = value.as_str().unwrap_or_default().to_string();
+        thread::sleep(Duration::from_millis(80 - 10 * word.len() as u64));
+        Ok((word != "delta").then(|| Value::String(word.to_uppercase())))
+    })?;
+    println!("{}", stats);
+
+    // NDJSON in, CSV out, skipping a malformed line and a record the transform rejects.
+    println!("\n--- ndjson -> csv ---");
+    let input = r#"{"user":"ada","ms":120}
+{"user":"bob","ms":"slow"}
+{"user":"cy",
+{"user":"dee","ms":45}
+"#;
+    let stats = PipeTool::new(Format::Ndjson, Format::Csv).on_error(OnError::Skip).run(Cursor::new(input), io::stdout(), |mut value| {
+        let ms = value["ms"].as_u64().ok_or("ms is not a number")?;
+        value["slow"] = Value::Bool(ms > 100);
+        Ok(Some(value))
+    })?;
+    println!("{}", stats);
+
+    // CSV in, NDJSON out; the same bad record now fails the run.
+    println!("\n--- csv -> ndjson, OnError::Fail ---");
+    let input = "id,qty\n1,5\n2,x\n3,7\n";
+    let result = PipeTool::new(Format::Csv, Format::Ndjson).run(Cursor::new(input), io::stdout(), |mut value| {
+        let qty: u32 = value["qty"].as_str().unwrap_or_default().parse().map_err(|_| "qty is not a number".to_string())?;
+        value["qty"] = qty.into();
+        Ok(Some(value))
+    });
+    println!("{:?}", result.map_err(|e| e.to_string()));
+
+    // `yes | tool | head -3`: the closed pipe stops the pipeline instead of panicking.
+    println!("\n--- endless input into head -3 ---");
+    let stats = PipeTool::new(Format::Lines, Format::Lines).max_in_flight(16).run(Endless(0), Head { lines: 0 }, |value| Ok(Some(value)))?;
+    println!("{}", stats);
+    Ok(())
+}
+*/
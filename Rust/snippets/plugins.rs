@@ -0,0 +1,442 @@
+// Note: This example requires adding the `libloading`, `clap`, and `thiserror` crates to your
+// Cargo.toml:
+// [dependencies]
+// libloading = "0.9"
+// clap = { version = "4", features = ["string"] } # Only for the example's CLI
+// thiserror = "1.0"
+//
+// Native plugins: a host program discovers shared libraries (`.so` / `.dylib` / `.dll`) in a
+// directory, loads them, and exposes the commands they provide as subcommands of its CLI.
+//
+// Rust has no stable ABI, so passing trait objects, `String`s, or `Vec`s between separately
+// compiled libraries is undefined behavior as soon as the compiler versions or flags differ.
+// The contract here is a `#[repr(C)]` table of `extern "C"` functions using only C types, with
+// a version number the host checks before touching anything else. The file has three parts:
+// * ABI - shared by both sides (copy it into the plugin crate unchanged).
+// * Plugin side - the `Plugin` trait and `PluginVTable::new::<P>()`, which wraps every call in
+//   `catch_unwind` so a panicking plugin returns an error instead of aborting the host.
+// * Host side - `PluginManager`: discovery, loading, ABI checks, command dispatch.
+//
+// Loading a library runs its code with the host's full privileges; only load plugins from
+// directories you trust. For untrusted code, or to survive plugin segfaults, run plugins in a
+// subprocess instead.
+
+use libloading::Library;
+use std::collections::BTreeMap;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use thiserror::Error;
+
+// --- ABI (shared with plugins) ---
+
+/// Bumped whenever `PluginVTable` changes shape. The host refuses other versions.
+const ABI_VERSION: u32 = 1;
+
+/// The exported symbol every plugin defines: `static PLUGIN_VTABLE: PluginVTable`.
+const VTABLE_SYMBOL: &[u8] = b"PLUGIN_VTABLE\0";
+
+const STATUS_OK: i32 = 0;
+const STATUS_ERROR: i32 = 1;
+const STATUS_PANIC: i32 = 2;
+
+/// The plugin's function table. `abi_version` must stay the first field so any host can read
+/// it safely before trusting the rest.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct PluginVTable {
+    abi_version: u32,
+    /// NUL-terminated, static for the life of the library.
+    name: extern "C" fn() -> *const c_char,
+    version: extern "C" fn() -> *const c_char,
+    command_count: extern "C" fn() -> usize,
+    /// Null for an out-of-range index.
+    command_name: extern "C" fn(index: usize) -> *const c_char,
+    command_help: extern "C" fn(index: usize) -> *const c_char,
+    /// Runs command `index` with `argc` C-string arguments. Writes an owned output or error
+    /// message to `*out` (release it with `free_string`) and returns a `STATUS_*` code.
+    run: extern "C" fn(index: usize, argc: usize, argv: *const *const c_char, out: *mut *mut c_char) -> i32,
+    free_string: extern "C" fn(s: *mut c_char),
+}
+
+// --- Plugin side ---
+
+/// One command a plugin offers.
+struct CommandSpec {
+    name: &'static CStr,
+    help: &'static CStr,
+}
+
+/// Implemented by the plugin crate. Everything is static: keep plugin state in `static`s
+/// (`OnceLock`, `Mutex`) if it needs any.
+trait Plugin {
+    const NAME: &'static CStr;
+    const VERSION: &'static CStr;
+    const COMMANDS: &'static [CommandSpec];
+
+    /// Runs `command` (one of `COMMANDS`) and returns its output or an error message.
+    fn run(command: &str, args: &[String]) -> std::result::Result<String, String>;
+}
+
+impl PluginVTable {
+    /// The table for plugin `P`. In the plugin crate:
+    /// `#[no_mangle] static PLUGIN_VTABLE: PluginVTable = PluginVTable::new::<MyPlugin>();`
+    const fn new<P: Plugin>() -> Self {
+        PluginVTable {
+            abi_version: ABI_VERSION,
+            name: plugin_name::<P>,
+            version: plugin_version::<P>,
+            command_count: command_count::<P>,
+            command_name: command_name::<P>,
+            command_help: command_help::<P>,
+            run: run_command::<P>,
+            free_string,
+        }
+    }
+}
+
+extern "C" fn plugin_name<P: Plugin>() -> *const c_char {
+    P::NAME.as_ptr()
+}
+
+extern "C" fn plugin_version<P: Plugin>() -> *const c_char {
+    P::VERSION.as_ptr()
+}
+
+extern "C" fn command_count<P: Plugin>() -> usize {
+    P::COMMANDS.len()
+}
+
+extern "C" fn command_name<P: Plugin>(index: usize) -> *const c_char {
+    P::COMMANDS.get(index).map_or(ptr::null(), |c| c.name.as_ptr())
+}
+
+extern "C" fn command_help<P: Plugin>(index: usize) -> *const c_char {
+    P::COMMANDS.get(index).map_or(ptr::null(), |c| c.help.as_ptr())
+}
+
+extern "C" fn run_command<P: Plugin>(index: usize, argc: usize, argv: *const *const c_char, out: *mut *mut c_char) -> i32 {
+    // A panic must not unwind out of an `extern "C"` function (Rust aborts the process if
+    // it tries), so it is caught here and reported as a status code.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let command = P::COMMANDS.get(index).ok_or_else(|| format!("no command at index {}", index))?;
+        let args: Vec<String> = (0..argc)
+            // SAFETY: the host passes `argc` valid NUL-terminated strings.
+            .map(|i| unsafe { CStr::from_ptr(*argv.add(i)) }.to_string_lossy().into_owned())
+            .collect();
+        P::run(&command.name.to_string_lossy(), &args)
+    }));
+    let (status, text) = match result {
+        Ok(Ok(output)) => (STATUS_OK, output),
+        Ok(Err(message)) => (STATUS_ERROR, message),
+        Err(payload) => (STATUS_PANIC, panic_message(payload.as_ref())),
+    };
+    if !out.is_null() {
+        let text = CString::new(text.replace('\0', "\\0")).expect("NULs were escaped");
+        // SAFETY: `out` points to a writable pointer owned by the host.
+        unsafe { *out = text.into_raw() };
+    }
+    status
+}
+
+/// Frees strings from `run` with the allocator that made them (the plugin's), not the host's.
+extern "C" fn free_string(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: `s` came from `CString::into_raw` in `run_command`.
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string())
+}
+
+// --- Host side ---
+
+#[derive(Debug, Error)]
+pub enum PluginError {
+    #[error("Failed to read plugin directory {path}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("Failed to load {path}: {source}")]
+    Load { path: PathBuf, source: libloading::Error },
+    #[error("{path} uses plugin ABI v{found}, but this host supports v{expected}")]
+    AbiMismatch { path: PathBuf, expected: u32, found: u32 },
+    #[error("{path} returned an invalid string for its {what}")]
+    InvalidString { path: PathBuf, what: &'static str },
+    #[error("Command '{command}' from {plugin} is already provided by {existing}; skipped")]
+    DuplicateCommand { command: String, plugin: String, existing: String },
+    #[error("Unknown command '{0}'")]
+    UnknownCommand(String),
+    #[error("{command}: {message}")]
+    CommandFailed { command: String, message: String },
+    #[error("{command}: plugin panicked: {message}")]
+    Panicked { command: String, message: String },
+}
+
+type Result<T> = std::result::Result<T, PluginError>;
+
+/// What the CLI needs to show a plugin command.
+#[derive(Debug, Clone)]
+struct PluginCommand {
+    name: String,
+    help: String,
+    plugin: String,
+}
+
+/// A loaded library and the metadata read from it. The library stays loaded (and the
+/// function pointers valid) for as long as this value lives.
+struct LoadedPlugin {
+    path: PathBuf,
+    name: String,
+    version: String,
+    commands: Vec<(String, String)>,
+    vtable: PluginVTable,
+    _library: Library,
+}
+
+/// Reads a plugin-owned static C string.
+fn read_c_str(ptr: *const c_char, path: &Path, what: &'static str) -> Result<String> {
+    if ptr.is_null() {
+        return Err(PluginError::InvalidString { path: path.to_path_buf(), what });
+    }
+    // SAFETY: the ABI promises a NUL-terminated string that lives as long as the library.
+    let s = unsafe { CStr::from_ptr(ptr) };
+    s.to_str().map(str::to_string).map_err(|_| PluginError::InvalidString { path: path.to_path_buf(), what })
+}
+
+impl LoadedPlugin {
+    /// Loads the library at `path` and validates its vtable.
+    fn load(path: &Path) -> Result<Self> {
+        let load_err = |source| PluginError::Load { path: path.to_path_buf(), source };
+        // SAFETY: loading runs the library's initializers; callers only load trusted plugins.
+        let library = unsafe { Library::new(path) }.map_err(load_err)?;
+        // SAFETY: the symbol is a `static PluginVTable`; its address is what `get` returns.
+        let table: *const PluginVTable = unsafe { library.get::<*const PluginVTable>(VTABLE_SYMBOL) }
+            .map(|symbol| *symbol)
+            .map_err(load_err)?;
+
+        // Read only the version first: a different ABI may lay out the rest differently.
+        // SAFETY: every ABI version starts with a `u32` version field.
+        let found = unsafe { ptr::addr_of!((*table).abi_version).read() };
+        if found != ABI_VERSION {
+            return Err(PluginError::AbiMismatch { path: path.to_path_buf(), expected: ABI_VERSION, found });
+        }
+        // SAFETY: same ABI version, so the table has exactly our layout.
+        let vtable = unsafe { *table };
+
+        let name = read_c_str((vtable.name)(), path, "name")?;
+        let version = read_c_str((vtable.version)(), path, "version")?;
+        let commands = (0..(vtable.command_count)())
+            .map(|i| {
+                Ok((
+                    read_c_str((vtable.command_name)(i), path, "command name")?,
+                    read_c_str((vtable.command_help)(i), path, "command help")?,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { path: path.to_path_buf(), name, version, commands, vtable, _library: library })
+    }
+
+    fn run(&self, index: usize, args: &[String]) -> Result<String> {
+        let command = self.commands[index].0.clone();
+        let c_args: Vec<CString> = args.iter().map(|a| CString::new(a.replace('\0', "")).expect("NULs removed")).collect();
+        let argv: Vec<*const c_char> = c_args.iter().map(|a| a.as_ptr()).collect();
+        let mut out: *mut c_char = ptr::null_mut();
+        let status = (self.vtable.run)(index, argv.len(), argv.as_ptr(), &mut out);
+        let text = if out.is_null() {
+            String::new()
+        } else {
+            // SAFETY: `out` is a NUL-terminated string from the plugin, freed right after.
+            let text = unsafe { CStr::from_ptr(out) }.to_string_lossy().into_owned();
+            (self.vtable.free_string)(out);
+            text
+        };
+        match status {
+            STATUS_OK => Ok(text),
+            STATUS_PANIC => Err(PluginError::Panicked { command, message: text }),
+            _ => Err(PluginError::CommandFailed { command, message: text }),
+        }
+    }
+}
+
+/// All loaded plugins and a command index across them.
+#[derive(Default)]
+struct PluginManager {
+    plugins: Vec<LoadedPlugin>,
+    /// Command name -> (plugin index, command index).
+    commands: BTreeMap<String, (usize, usize)>,
+}
+
+impl PluginManager {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Platform library files in `dir` (`lib*.so`, `lib*.dylib`, `*.dll`), sorted by name.
+    fn discover(dir: &Path) -> Result<Vec<PathBuf>> {
+        let io_err = |source| PluginError::Io { path: dir.to_path_buf(), source };
+        let mut found = Vec::new();
+        for entry in std::fs::read_dir(dir).map_err(io_err)? {
+            let path = entry.map_err(io_err)?.path();
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            if path.is_file()
+                && file_name.starts_with(std::env::consts::DLL_PREFIX)
+                && file_name.ends_with(std::env::consts::DLL_SUFFIX)
+            {
+                found.push(path);
+            }
+        }
+        found.sort();
+        Ok(found)
+    }
+
+    /// Loads one plugin and registers its commands. A command name already taken by an
+    /// earlier plugin is skipped and reported; the rest of the plugin still loads.
+    ///
+    /// # Returns
+    ///
+    /// The skipped-command warnings, or the error that prevented loading.
+    fn load(&mut self, path: &Path) -> Result<Vec<PluginError>> {
+        let plugin = LoadedPlugin::load(path)?;
+        let index = self.plugins.len();
+        let mut warnings = Vec::new();
+        for (command_index, (command, _)) in plugin.commands.iter().enumerate() {
+            if let Some(&(existing, _)) = self.commands.get(command) {
+                warnings.push(PluginError::DuplicateCommand {
+                    command: command.clone(),
+                    plugin: plugin.name.clone(),
+                    existing: self.plugins[existing].name.clone(),
+                });
+                continue;
+            }
+            self.commands.insert(command.clone(), (index, command_index));
+        }
+        self.plugins.push(plugin);
+        Ok(warnings)
+    }
+
+    /// Loads every plugin in `dir`. One bad plugin doesn't stop the others.
+    ///
+    /// # Returns
+    ///
+    /// Problems with individual plugins (to log as warnings), or an error if the directory
+    /// itself can't be read.
+    fn load_dir(&mut self, dir: &Path) -> Result<Vec<PluginError>> {
+        let mut problems = Vec::new();
+        for path in Self::discover(dir)? {
+            match self.load(&path) {
+                Ok(warnings) => problems.extend(warnings),
+                Err(e) => problems.push(e),
+            }
+        }
+        Ok(problems)
+    }
+
+    /// Every registered command, sorted by name.
+    fn commands(&self) -> Vec<PluginCommand> {
+        self.commands
+            .iter()
+            .map(|(name, &(plugin, command))| PluginCommand {
+                name: name.clone(),
+                help: self.plugins[plugin].commands[command].1.clone(),
+                plugin: self.plugins[plugin].name.clone(),
+            })
+            .collect()
+    }
+
+    /// `(name, version, path)` of each loaded plugin.
+    fn plugins(&self) -> impl Iterator<Item = (&str, &str, &Path)> {
+        self.plugins.iter().map(|p| (p.name.as_str(), p.version.as_str(), p.path.as_path()))
+    }
+
+    /// Runs a plugin command by name.
+    fn dispatch(&self, command: &str, args: &[String]) -> Result<String> {
+        let &(plugin, index) = self.commands.get(command).ok_or_else(|| PluginError::UnknownCommand(command.to_string()))?;
+        self.plugins[plugin].run(index, args)
+    }
+}
+
+// Example Usage
+/*
+// ----- Plugin crate (e.g. plugins/greeter) -----
+// Cargo.toml:
+//   [lib]
+//   crate-type = ["cdylib"]
+// src/lib.rs: paste the "ABI" and "Plugin side" sections of this file, then:
+//
+//   struct Greeter;
+//
+//   impl Plugin for Greeter {
+//       const NAME: &'static CStr = c"greeter";
+//       const VERSION: &'static CStr = c"0.1.0";
+//       const COMMANDS: &'static [CommandSpec] = &[
+//           CommandSpec { name: c"hello", help: c"Greet someone" },
+//           CommandSpec { name: c"shout", help: c"Greet someone loudly (panics on empty input)" },
+//       ];
+//
+//       fn run(command: &str, args: &[String]) -> Result<String, String> {
+//           let who = args.first().ok_or("usage: hello <name>")?;
+//           match command {
+//               "hello" => Ok(format!("Hello, {}!", who)),
+//               "shout" => {
+//                   assert!(!who.is_empty(), "nothing to shout"); // Becomes a PluginError::Panicked.
+//                   Ok(format!("HELLO, {}!", who.to_uppercase()))
+//               }
+//               _ => Err(format!("unknown command {}", command)),
+//           }
+//       }
+//   }
+//
+//   #[no_mangle] // `#[unsafe(no_mangle)]` in edition 2024
+//   static PLUGIN_VTABLE: PluginVTable = PluginVTable::new::<Greeter>(); // exported by no_mangle
+//
+// Build with `cargo build --release` and copy target/release/libgreeter.so (or .dylib/.dll)
+// into the host's plugin directory.
+
+// ----- Host -----
+use clap::{Arg, Command};
+
+fn main() {
+    let dir = PathBuf::from(std::env::var("PLUGIN_DIR").unwrap_or_else(|_| "plugins".to_string()));
+    let mut manager = PluginManager::new();
+    match manager.load_dir(&dir) {
+        Ok(problems) => problems.iter().for_each(|p| eprintln!("warning: {}", p)),
+        Err(e) => eprintln!("warning: {}", e),
+    }
+
+    // Built-in subcommands plus one per plugin command; trailing args are passed through.
+    let mut cli = Command::new("mytool").subcommand(Command::new("plugins").about("List loaded plugins"));
+    for command in manager.commands() {
+        cli = cli.subcommand(
+            Command::new(command.name)
+                .about(format!("{} [plugin: {}]", command.help, command.plugin))
+                .arg(Arg::new("args").num_args(0..).trailing_var_arg(true).allow_hyphen_values(true)),
+        );
+    }
+
+    let matches = cli.get_matches();
+    match matches.subcommand() {
+        Some(("plugins", _)) => {
+            for (name, version, path) in manager.plugins() {
+                println!("{} {} ({})", name, version, path.display());
+            }
+        }
+        Some((command, sub)) => {
+            let args: Vec<String> = sub.get_many::<String>("args").map(|v| v.cloned().collect()).unwrap_or_default();
+            match manager.dispatch(command, &args) {
+                Ok(output) => println!("{}", output),
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => eprintln!("no command given; try --help"),
+    }
+}
+*/
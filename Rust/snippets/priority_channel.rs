@@ -0,0 +1,341 @@
+// Note: The async half of this example requires adding the `tokio` crate to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] } // Only "sync" is needed by the channel itself
+//
+// The blocking half (`std_channel`) uses only the standard library.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Message priority. Higher lanes are served first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Priority {
+    Low = 0,
+    Normal = 1,
+    High = 2,
+}
+
+/// Returned by `send` when the receiver has been dropped; gives the message back.
+#[derive(Debug, PartialEq)]
+struct SendError<T>(T);
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "priority channel receiver has been dropped")
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for SendError<T> {}
+
+/// The queueing logic shared by the std and tokio channels.
+///
+/// Strict priority is starvation-prone: a steady stream of `High` messages would keep
+/// `Low` messages waiting forever. Aging fixes that: if the oldest message in a lower lane
+/// has waited longer than `starvation_limit`, it is served before higher lanes.
+struct Lanes<T> {
+    // Indexed by `Priority as usize`.
+    queues: [VecDeque<(Instant, T)>; 3],
+    starvation_limit: Duration,
+    senders: usize,
+    receiver_alive: bool,
+}
+
+impl<T> Lanes<T> {
+    fn new(starvation_limit: Duration) -> Self {
+        Self {
+            queues: [VecDeque::new(), VecDeque::new(), VecDeque::new()],
+            starvation_limit,
+            senders: 1,
+            receiver_alive: true,
+        }
+    }
+
+    fn push(&mut self, priority: Priority, value: T) {
+        self.queues[priority as usize].push_back((Instant::now(), value));
+    }
+
+    fn pop(&mut self) -> Option<(Priority, T)> {
+        let now = Instant::now();
+        // 1. Starving messages first, oldest lane-head wins (lowest priority first on ties).
+        let starving = [Priority::Low, Priority::Normal]
+            .into_iter()
+            .filter_map(|p| {
+                let (enqueued, _) = self.queues[p as usize].front()?;
+                let waited = now.duration_since(*enqueued);
+                (waited >= self.starvation_limit).then_some((waited, p))
+            })
+            .max_by_key(|(waited, _)| *waited)
+            .map(|(_, p)| p);
+
+        // 2. Otherwise strict priority.
+        let lane = starving.or_else(|| {
+            [Priority::High, Priority::Normal, Priority::Low]
+                .into_iter()
+                .find(|p| !self.queues[*p as usize].is_empty())
+        })?;
+        self.queues[lane as usize].pop_front().map(|(_, v)| (lane, v))
+    }
+
+    fn len(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    fn lane_len(&self, priority: Priority) -> usize {
+        self.queues[priority as usize].len()
+    }
+}
+
+// --- Blocking (std) implementation ---
+
+mod std_channel {
+    use super::{Lanes, Priority, SendError};
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::time::Duration;
+
+    struct Shared<T> {
+        lanes: Mutex<Lanes<T>>,
+        available: Condvar,
+    }
+
+    /// Sending half; cloneable (multi-producer).
+    pub(super) struct PrioritySender<T> {
+        shared: Arc<Shared<T>>,
+    }
+
+    /// Receiving half (single consumer).
+    pub(super) struct PriorityReceiver<T> {
+        shared: Arc<Shared<T>>,
+    }
+
+    /// Creates an unbounded blocking priority channel.
+    pub(super) fn priority_channel<T>(starvation_limit: Duration) -> (PrioritySender<T>, PriorityReceiver<T>) {
+        let shared = Arc::new(Shared {
+            lanes: Mutex::new(Lanes::new(starvation_limit)),
+            available: Condvar::new(),
+        });
+        (
+            PrioritySender { shared: Arc::clone(&shared) },
+            PriorityReceiver { shared },
+        )
+    }
+
+    impl<T> PrioritySender<T> {
+        pub(super) fn send(&self, priority: Priority, value: T) -> Result<(), SendError<T>> {
+            let mut lanes = self.shared.lanes.lock().unwrap();
+            if !lanes.receiver_alive {
+                return Err(SendError(value));
+            }
+            lanes.push(priority, value);
+            drop(lanes);
+            self.shared.available.notify_one();
+            Ok(())
+        }
+    }
+
+    impl<T> Clone for PrioritySender<T> {
+        fn clone(&self) -> Self {
+            self.shared.lanes.lock().unwrap().senders += 1;
+            Self { shared: Arc::clone(&self.shared) }
+        }
+    }
+
+    impl<T> Drop for PrioritySender<T> {
+        fn drop(&mut self) {
+            let mut lanes = self.shared.lanes.lock().unwrap();
+            lanes.senders -= 1;
+            if lanes.senders == 0 {
+                // Wake the receiver so it can observe the disconnect.
+                self.shared.available.notify_all();
+            }
+        }
+    }
+
+    impl<T> PriorityReceiver<T> {
+        /// Blocks until a message is available. Returns `None` once all senders are
+        /// dropped and the queue is drained.
+        pub(super) fn recv(&self) -> Option<(Priority, T)> {
+            let mut lanes = self.shared.lanes.lock().unwrap();
+            loop {
+                if let Some(item) = lanes.pop() {
+                    return Some(item);
+                }
+                if lanes.senders == 0 {
+                    return None;
+                }
+                lanes = self.shared.available.wait(lanes).unwrap();
+            }
+        }
+
+        /// Like `recv`, but gives up after `timeout` (returns `None` on timeout or disconnect).
+        pub(super) fn recv_timeout(&self, timeout: Duration) -> Option<(Priority, T)> {
+            let lanes = self.shared.lanes.lock().unwrap();
+            let (mut lanes, _) = self
+                .shared
+                .available
+                .wait_timeout_while(lanes, timeout, |l| l.len() == 0 && l.senders > 0)
+                .unwrap();
+            lanes.pop()
+        }
+
+        /// Non-blocking receive.
+        pub(super) fn try_recv(&self) -> Option<(Priority, T)> {
+            self.shared.lanes.lock().unwrap().pop()
+        }
+
+        /// Number of queued messages in a lane (for metrics/backlog alerts).
+        pub(super) fn backlog(&self, priority: Priority) -> usize {
+            self.shared.lanes.lock().unwrap().lane_len(priority)
+        }
+    }
+
+    impl<T> Drop for PriorityReceiver<T> {
+        fn drop(&mut self) {
+            self.shared.lanes.lock().unwrap().receiver_alive = false;
+        }
+    }
+}
+
+// --- Async (tokio) implementation ---
+
+mod tokio_channel {
+    use super::{Lanes, Priority, SendError};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::Notify;
+
+    // A std Mutex is fine here: it is never held across an `.await`.
+    struct Shared<T> {
+        lanes: Mutex<Lanes<T>>,
+        available: Notify,
+    }
+
+    /// Sending half; cloneable. `send` never blocks (the channel is unbounded).
+    pub(super) struct AsyncPrioritySender<T> {
+        shared: Arc<Shared<T>>,
+    }
+
+    /// Receiving half (single consumer).
+    pub(super) struct AsyncPriorityReceiver<T> {
+        shared: Arc<Shared<T>>,
+    }
+
+    /// Creates an unbounded async priority channel.
+    pub(super) fn priority_channel<T>(
+        starvation_limit: Duration,
+    ) -> (AsyncPrioritySender<T>, AsyncPriorityReceiver<T>) {
+        let shared = Arc::new(Shared {
+            lanes: Mutex::new(Lanes::new(starvation_limit)),
+            available: Notify::new(),
+        });
+        (
+            AsyncPrioritySender { shared: Arc::clone(&shared) },
+            AsyncPriorityReceiver { shared },
+        )
+    }
+
+    impl<T> AsyncPrioritySender<T> {
+        pub(super) fn send(&self, priority: Priority, value: T) -> Result<(), SendError<T>> {
+            {
+                let mut lanes = self.shared.lanes.lock().unwrap();
+                if !lanes.receiver_alive {
+                    return Err(SendError(value));
+                }
+                lanes.push(priority, value);
+            }
+            // `notify_one` stores a permit if the receiver isn't waiting yet,
+            // so the wakeup cannot be lost.
+            self.shared.available.notify_one();
+            Ok(())
+        }
+    }
+
+    impl<T> Clone for AsyncPrioritySender<T> {
+        fn clone(&self) -> Self {
+            self.shared.lanes.lock().unwrap().senders += 1;
+            Self { shared: Arc::clone(&self.shared) }
+        }
+    }
+
+    impl<T> Drop for AsyncPrioritySender<T> {
+        fn drop(&mut self) {
+            let last = {
+                let mut lanes = self.shared.lanes.lock().unwrap();
+                lanes.senders -= 1;
+                lanes.senders == 0
+            };
+            if last {
+                self.shared.available.notify_one();
+            }
+        }
+    }
+
+    impl<T> AsyncPriorityReceiver<T> {
+        /// Waits for the next message. Returns `None` once all senders are dropped
+        /// and the queue is drained.
+        pub(super) async fn recv(&mut self) -> Option<(Priority, T)> {
+            loop {
+                {
+                    let mut lanes = self.shared.lanes.lock().unwrap();
+                    if let Some(item) = lanes.pop() {
+                        return Some(item);
+                    }
+                    if lanes.senders == 0 {
+                        return None;
+                    }
+                } // Lock released before awaiting.
+                self.shared.available.notified().await;
+            }
+        }
+
+        pub(super) fn backlog(&self, priority: Priority) -> usize {
+            self.shared.lanes.lock().unwrap().lane_len(priority)
+        }
+    }
+
+    impl<T> Drop for AsyncPriorityReceiver<T> {
+        fn drop(&mut self) {
+            self.shared.lanes.lock().unwrap().receiver_alive = false;
+        }
+    }
+}
+
+// Example Usage
+/*
+fn main() {
+    println!("--- std: urgent messages bypass the backlog ---");
+    let (tx, rx) = std_channel::priority_channel::<String>(Duration::from_millis(200));
+    for i in 0..5 {
+        tx.send(Priority::Low, format!("report #{}", i)).unwrap();
+    }
+    tx.send(Priority::Normal, "email".to_string()).unwrap();
+    tx.send(Priority::High, "page on-call!".to_string()).unwrap();
+    println!("Low backlog: {}", rx.backlog(Priority::Low));
+
+    let worker = std::thread::spawn(move || {
+        while let Some((priority, msg)) = rx.recv_timeout(Duration::from_millis(500)) {
+            println!("{:?}: {}", priority, msg);
+        }
+    });
+    drop(tx); // Disconnect so the worker exits once drained
+    worker.join().unwrap();
+
+    println!("\n--- tokio: starvation protection ---");
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let (tx, mut rx) = tokio_channel::priority_channel::<u32>(Duration::from_millis(50));
+        // A backlog of high-priority work queued ahead of one low-priority message...
+        for i in 1..=10 {
+            tx.send(Priority::High, i).unwrap();
+        }
+        tx.send(Priority::Low, 0).unwrap();
+        drop(tx);
+        // ...the low-priority message is served as soon as it has waited 50ms,
+        // instead of after all ten high-priority ones.
+        while let Some((priority, value)) = rx.recv().await {
+            println!("{:?} {}", priority, value);
+            tokio::time::sleep(Duration::from_millis(15)).await; // Slow consumer
+        }
+    });
+}
+*/
@@ -0,0 +1,249 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dev-dependencies] (property tests only run under `cargo test`)
+// proptest = "1"
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// url = "2"
+//
+// Reusable proptest strategies for the kinds of values the other snippets handle, plus
+// harnesses for "encode then decode gives back the original" properties:
+// * `json_value(depth)`: arbitrary JSON with bounded nesting (for the serialization snippets).
+// * `url()`: http(s) URLs with ports, paths, queries, and fragments (for `url_utils.rs`).
+// * `relative_path()`: multi-component relative paths with spaces, dots, and Unicode.
+// * `cron_expression()`: valid 5-field cron expressions (for `cron.rs`).
+// * `check_roundtrip` / `check_json_roundtrip`: run a property with a `TestRunner` and return
+//   the *shrunk* failing input, which is usually far smaller than the one first found.
+//
+// Use the strategies inside `proptest! { ... }` tests or with the harnesses directly.
+// Failing cases are shrunk automatically. `proptest!` tests honor `PROPTEST_CASES=10000` for
+// a longer run; the harnesses take the case count as an argument.
+
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestError, TestRunner};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::fmt::Debug;
+use std::path::PathBuf;
+use url::Url;
+
+// --- JSON ---
+
+/// Finite floats that print and parse back exactly: multiples of 1/256 in a moderate range.
+///
+/// serde_json's default float parser can be off by one ULP for long decimal inputs, so
+/// arbitrary `f64`s make roundtrip properties flaky. Enable serde_json's `float_roundtrip`
+/// feature and swap in `any::<f64>()` (filtered to finite values) to test the full range.
+fn json_float() -> impl Strategy<Value = f64> {
+    (-1_000_000_i64..1_000_000, 0_i32..=8).prop_map(|(mantissa, shift)| mantissa as f64 / f64::from(1 << shift))
+}
+
+/// JSON scalars: null, booleans, integers across the full `i64`/`u64` range, floats, and
+/// strings with escapes and non-ASCII characters.
+fn json_leaf() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        json_float().prop_map(|f| Value::Number(Number::from_f64(f).expect("finite"))),
+        // `\PC` is any printable character; quotes, backslashes, and emoji all appear.
+        "\\PC{0,16}".prop_map(Value::String),
+        Just(Value::String("line\nbreak \"quoted\" \\ \u{0}".to_string())),
+    ]
+}
+
+/// Arbitrary JSON documents nested at most `max_depth` arrays or objects deep.
+///
+/// # Arguments
+///
+/// * `max_depth` - Nesting limit; 3-4 finds most bugs without huge values.
+///
+/// # Returns
+///
+/// A strategy whose values shrink toward smaller, shallower documents.
+fn json_value(max_depth: u32) -> impl Strategy<Value = Value> {
+    // `prop_recursive(depth, desired total size, items per collection, ...)`.
+    json_leaf().prop_recursive(max_depth, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+            prop::collection::btree_map("[a-z_]{1,8}|\\PC{0,6}", inner, 0..8)
+                .prop_map(|entries| Value::Object(entries.into_iter().collect::<Map<String, Value>>())),
+        ]
+    })
+}
+
+// --- URLs and paths ---
+
+fn host() -> impl Strategy<Value = String> {
+    prop_oneof![
+        prop::collection::vec("[a-z][a-z0-9-]{0,10}[a-z0-9]", 1..4).prop_map(|labels| labels.join(".") + ".example"),
+        Just("localhost".to_string()),
+        any::<[u8; 4]>().prop_map(|octets| std::net::Ipv4Addr::from(octets).to_string()),
+    ]
+}
+
+/// http(s) URLs with an optional port, percent-encoded path segments, query pairs, and
+/// fragment. Built through `Url`'s API, so every value is valid by construction.
+fn url() -> impl Strategy<Value = Url> {
+    (
+        prop_oneof![Just("http"), Just("https")],
+        host(),
+        prop::option::of(1_u16..),
+        prop::collection::vec("\\PC{1,10}", 0..4),
+        prop::collection::vec(("[a-z]{1,6}", "\\PC{0,10}"), 0..3),
+        prop::option::of("[a-zA-Z0-9-]{1,8}"),
+    )
+        .prop_map(|(scheme, host, port, segments, query, fragment)| {
+            let mut url = Url::parse(&format!("{}://{}", scheme, host)).expect("scheme and host are valid");
+            url.set_port(port).expect("http(s) URLs take ports");
+            url.path_segments_mut().expect("http(s) URLs have paths").extend(&segments);
+            if !query.is_empty() {
+                url.query_pairs_mut().extend_pairs(&query);
+            }
+            url.set_fragment(fragment.as_deref());
+            url
+        })
+}
+
+/// One file or directory name: never empty, `.`, `..`, or containing a separator or NUL.
+fn path_component() -> impl Strategy<Value = String> {
+    "[^/\\\\\\x00]{1,12}".prop_filter("not a special directory name", |name| name != "." && name != "..")
+}
+
+/// Relative paths of one to five components, e.g. `reports/Q3 summary.final/日本.txt`.
+fn relative_path() -> impl Strategy<Value = PathBuf> {
+    prop::collection::vec(path_component(), 1..=5).prop_map(|components| components.iter().collect())
+}
+
+// --- Cron ---
+
+/// One cron field over `min..=max`: `*`, a value, a range, a step, or a list of those.
+fn cron_field(min: u32, max: u32) -> impl Strategy<Value = String> {
+    let value = min..=max;
+    let range = (min..=max, min..=max).prop_map(|(a, b)| format!("{}-{}", a.min(b), a.max(b)));
+    let item = prop_oneof![
+        value.clone().prop_map(|v| v.to_string()),
+        range.clone(),
+        (range, 1..=(max - min).max(1)).prop_map(|(range, step)| format!("{}/{}", range, step)),
+    ];
+    prop_oneof![
+        1 => Just("*".to_string()),
+        1 => (1..=(max - min).max(1)).prop_map(|step| format!("*/{}", step)),
+        3 => prop::collection::vec(item, 1..4).prop_map(|items| items.join(",")),
+    ]
+}
+
+/// Valid 5-field expressions (`minute hour day-of-month month day-of-week`).
+fn cron_expression() -> impl Strategy<Value = String> {
+    (cron_field(0, 59), cron_field(0, 23), cron_field(1, 31), cron_field(1, 12), cron_field(0, 6))
+        .prop_map(|(minute, hour, dom, month, dow)| format!("{} {} {} {} {}", minute, hour, dom, month, dow))
+}
+
+// --- Roundtrip harnesses ---
+
+/// Checks that `decode(encode(x)) == x` for values from `strategy`.
+///
+/// # Arguments
+///
+/// * `cases` - How many values to try (proptest's default is 256).
+/// * `strategy` - Where values come from.
+/// * `encode`, `decode` - The pair under test, e.g. `to_string` and `parse`.
+///
+/// # Returns
+///
+/// `Err` with the smallest failing value proptest could shrink to, and the reason.
+fn check_roundtrip<T, E, D>(
+    cases: u32,
+    strategy: impl Strategy<Value = T>,
+    encode: impl Fn(&T) -> E,
+    decode: impl Fn(&E) -> Result<T, D>,
+) -> Result<(), TestError<T>>
+where
+    T: PartialEq + Debug + Clone,
+    E: Debug,
+    D: Debug,
+{
+    // Outside `proptest!` there is no source file to save failing seeds next to.
+    let mut runner = TestRunner::new(Config { cases, failure_persistence: None, ..Config::default() });
+    runner.run(&strategy, |value| {
+        let encoded = encode(&value);
+        let decoded = decode(&encoded)
+            .map_err(|e| TestCaseError::fail(format!("decoding {:?} failed: {:?}", encoded, e)))?;
+        prop_assert_eq!(&decoded, &value, "encoded as {:?}", encoded);
+        Ok(())
+    })
+}
+
+/// `check_roundtrip` through `serde_json::to_string` and `serde_json::from_str`.
+fn check_json_roundtrip<T>(cases: u32, strategy: impl Strategy<Value = T>) -> Result<(), TestError<T>>
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug + Clone,
+{
+    check_roundtrip(
+        cases,
+        strategy,
+        |value| serde_json::to_string(value).expect("serializable"),
+        |json| serde_json::from_str::<T>(json),
+    )
+}
+
+// Example Usage
+/*
+use proptest::strategy::ValueTree;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Event {
+    id: u64,
+    kind: String,
+    payload: Value,
+    tags: Vec<String>,
+}
+
+fn event() -> impl Strategy<Value = Event> {
+    (any::<u64>(), "[a-z.]{1,12}", json_value(3), prop::collection::vec("\\PC{0,8}", 0..4))
+        .prop_map(|(id, kind, payload, tags)| Event { id, kind, payload, tags })
+}
+
+/// A buggy CSV cell encoder: it forgets that quotes inside a quoted cell must be doubled.
+fn to_csv_cell(value: &String) -> String {
+    if value.contains([',', '"', '\n']) { format!("\"{}\"", value) } else { value.clone() }
+}
+
+fn from_csv_cell(cell: &String) -> Result<String, String> {
+    match cell.strip_prefix('"').and_then(|c| c.strip_suffix('"')) {
+        Some(inner) if inner.contains('"') => Err("unescaped quote inside quoted cell".into()),
+        Some(inner) => Ok(inner.replace("\"\"", "\"")),
+        None => Ok(cell.clone()),
+    }
+}
+
+fn main() {
+    println!("JSON values: {:?}", check_json_roundtrip(300, json_value(4)));
+    println!("Event structs: {:?}", check_json_roundtrip(300, event()));
+    println!("URLs: {:?}", check_roundtrip(300, url(), |u| u.to_string(), |s| Url::parse(s)));
+    let to_text = |p: &PathBuf| p.to_string_lossy().into_owned();
+    println!("Paths: {:?}", check_roundtrip(300, relative_path(), to_text, |s| Ok::<_, ()>(PathBuf::from(s))));
+
+    let mut runner = TestRunner::deterministic();
+    let samples: Vec<String> = (0..3).map(|_| cron_expression().new_tree(&mut runner).unwrap().current()).collect();
+    println!("cron samples: {:?}", samples);
+    println!("sample URL: {}", url().new_tree(&mut runner).unwrap().current());
+
+    // A real bug: proptest finds it and shrinks the input to a minimal counterexample.
+    match check_roundtrip(1000, "\\PC{0,20}", to_csv_cell, from_csv_cell) {
+        Ok(()) => println!("CSV cells: ok"),
+        Err(TestError::Fail(reason, minimal)) => println!("CSV cells FAILED for {:?}: {}", minimal, reason),
+        Err(e) => println!("CSV cells: {}", e),
+    }
+}
+
+// In a test module, the same strategies plug into `proptest!`:
+// proptest! {
+//     #[test]
+//     fn cron_expressions_parse(expr in cron_expression()) {
+//         prop_assert!(CronSchedule::parse(&expr).is_ok(), "{}", expr); // cron.rs
+//     }
+// }
+*/
@@ -0,0 +1,302 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// qrcode = { version = "0.14", default-features = false }
+// png = "0.18"
+// thiserror = "1.0"
+//
+// QR codes for pairing and enrollment flows: "scan this to connect your phone", TOTP setup
+// (`otpauth://` URIs with a base32 secret from `encoding.rs`), or a one-time link signed with
+// `signing.rs` that the phone then uses to open a WebSocket session.
+// * `Qr::new(data, Correction::Medium)` encodes a string or bytes, picking the smallest version
+//   that fits. `Qr::best_fit` instead picks the strongest error correction that still fits a
+//   size limit, which helps a code shown on a dim or scratched screen.
+// * `to_terminal` draws it with Unicode half blocks, two modules per character row, so a code
+//   fits in an SSH session; `to_svg` and `to_png` produce files for web pages and emails.
+// * Every renderer keeps the quiet zone (4 modules by default). Cropping it is the usual reason
+//   a code "doesn't scan".
+//
+// The qrcode crate's own renderers need its `image`/`svg` features; rendering from the module
+// grid here keeps the dependency tree small and the three outputs identical. For 1D barcodes
+// on shipping labels and inventory (Code 128, EAN-13), the `barcoders` crate fills the same role.
+
+use qrcode::{Color, EcLevel, QrCode, Version};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum QrError {
+    #[error("cannot encode QR code: {0}")]
+    Encode(#[from] qrcode::types::QrError),
+    #[error("{len} bytes do not fit in a QR code of at most {max_width} modules")]
+    TooLarge { len: usize, max_width: usize },
+    #[error("unknown error correction level {0:?} (expected L, M, Q, or H)")]
+    InvalidCorrection(String),
+    #[error("unsupported QR output {0:?} (expected .png, .svg, or .txt)")]
+    UnsupportedFormat(String),
+    #[error("PNG encoding failed: {0}")]
+    Png(#[from] png::EncodingError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+type Result<T> = std::result::Result<T, QrError>;
+
+/// How much of the code can be damaged or covered and still scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Correction {
+    /// ~7% recovery: the smallest code, for clean screens.
+    Low,
+    /// ~15% recovery: the usual default.
+    Medium,
+    /// ~25% recovery.
+    Quartile,
+    /// ~30% recovery: printed codes, or a logo placed over the center.
+    High,
+}
+
+impl Correction {
+    const STRONGEST_FIRST: [Correction; 4] = [Correction::High, Correction::Quartile, Correction::Medium, Correction::Low];
+
+    fn level(self) -> EcLevel {
+        match self {
+            Correction::Low => EcLevel::L,
+            Correction::Medium => EcLevel::M,
+            Correction::Quartile => EcLevel::Q,
+            Correction::High => EcLevel::H,
+        }
+    }
+}
+
+impl FromStr for Correction {
+    type Err = QrError;
+
+    /// Accepts the standard letters or names: `L`, `m`, `quartile`, `high`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "l" | "low" => Ok(Correction::Low),
+            "m" | "medium" => Ok(Correction::Medium),
+            "q" | "quartile" => Ok(Correction::Quartile),
+            "h" | "high" => Ok(Correction::High),
+            _ => Err(QrError::InvalidCorrection(s.to_string())),
+        }
+    }
+}
+
+// --- Encoding ---
+
+struct Qr {
+    code: QrCode,
+    correction: Correction,
+    /// Light border, in modules, on every side. The spec asks for at least 4.
+    quiet_zone: usize,
+}
+
+impl fmt::Debug for Qr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Qr")
+            .field("version", &self.version())
+            .field("width", &self.code.width())
+            .field("correction", &self.correction)
+            .finish()
+    }
+}
+
+impl Qr {
+    /// Encodes `data` at the given correction level, in the smallest version that fits.
+    ///
+    /// # Arguments
+    /// * `data` - Text or bytes. Digits-only and uppercase alphanumeric data encode more
+    ///   compactly, so `HTTPS://EXAMPLE.COM/P/AB12` yields a smaller code than lowercase.
+    ///
+    /// # Returns
+    /// `QrError::Encode` when the data is too long for any version (about 2.9 KB at `Low`).
+    fn new(data: impl AsRef<[u8]>, correction: Correction) -> Result<Self> {
+        let code = QrCode::with_error_correction_level(data, correction.level())?;
+        Ok(Self { code, correction, quiet_zone: 4 })
+    }
+
+    /// Encodes `data` with the strongest correction whose code is at most `max_width` modules
+    /// wide (21 for version 1, plus 4 per version), before the quiet zone.
+    fn best_fit(data: impl AsRef<[u8]>, max_width: usize) -> Result<Self> {
+        let data = data.as_ref();
+        for correction in Correction::STRONGEST_FIRST {
+            match Qr::new(data, correction) {
+                Ok(qr) if qr.code.width() <= max_width => return Ok(qr),
+                Ok(_) | Err(QrError::Encode(qrcode::types::QrError::DataTooLong)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(QrError::TooLarge { len: data.len(), max_width })
+    }
+
+    fn quiet_zone(mut self, modules: usize) -> Self {
+        self.quiet_zone = modules;
+        self
+    }
+
+    /// The version number, 1 to 40.
+    fn version(&self) -> i16 {
+        match self.code.version() {
+            Version::Normal(v) | Version::Micro(v) => v,
+        }
+    }
+
+    fn correction(&self) -> Correction {
+        self.correction
+    }
+
+    /// Side length in modules, including the quiet zone on both sides.
+    fn size(&self) -> usize {
+        self.code.width() + 2 * self.quiet_zone
+    }
+
+    /// The module grid with the quiet zone applied, row by row; `true` is dark.
+    fn modules(&self) -> Vec<Vec<bool>> {
+        let width = self.code.width();
+        let colors = self.code.to_colors();
+        let size = self.size();
+        let mut grid = vec![vec![false; size]; size];
+        for (i, color) in colors.iter().enumerate() {
+            grid[i / width + self.quiet_zone][i % width + self.quiet_zone] = *color == Color::Dark;
+        }
+        grid
+    }
+
+    // --- Rendering ---
+
+    /// Draws the code with half-block characters, two module rows per line.
+    ///
+    /// # Arguments
+    /// * `invert` - Most terminals are light-on-dark, so by default dark modules are drawn as
+    ///   spaces and light ones as blocks. Pass `true` for dark-on-light terminals.
+    fn to_terminal(&self, invert: bool) -> String {
+        let grid = self.modules();
+        // Whether a module is drawn with foreground "ink".
+        let ink = |row: Option<&Vec<bool>>, x: usize| row.is_some_and(|r| r[x] == invert);
+        let mut out = String::with_capacity(self.size() * (self.size() / 2 + 1) * 3);
+        for pair in (0..grid.len()).step_by(2) {
+            let (top, bottom) = (grid.get(pair), grid.get(pair + 1));
+            for x in 0..self.size() {
+                out.push(match (ink(top, x), ink(bottom, x)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// An SVG with one path for all dark modules, scaled to `module_px` pixels per module.
+    /// `shape-rendering="crispEdges"` keeps browsers from anti-aliasing module edges into gray.
+    fn to_svg(&self, module_px: u32) -> String {
+        let size = self.size();
+        let mut path = String::new();
+        for (y, row) in self.modules().iter().enumerate() {
+            for (x, _) in row.iter().enumerate().filter(|(_, dark)| **dark) {
+                path.push_str(&format!("M{} {}h1v1h-1z", x, y));
+            }
+        }
+        let px = size as u32 * module_px;
+        format!(
+            concat!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{px}\" height=\"{px}\" viewBox=\"0 0 {size} {size}\" ",
+                "shape-rendering=\"crispEdges\">\n",
+                "<rect width=\"{size}\" height=\"{size}\" fill=\"#fff\"/>\n<path fill=\"#000\" d=\"{path}\"/>\n</svg>\n",
+            ),
+            px = px,
+            size = size,
+            path = path,
+        )
+    }
+
+    /// A 1-bit grayscale PNG, `module_px` pixels per module. A few hundred bytes for most codes.
+    fn to_png(&self, module_px: u32) -> Result<Vec<u8>> {
+        let scale = module_px.max(1) as usize;
+        let side = self.size() * scale;
+        let row_bytes = side.div_ceil(8);
+        let mut pixels = vec![0u8; row_bytes * side];
+        for (y, row) in self.modules().iter().enumerate() {
+            for (x, dark) in row.iter().enumerate() {
+                if *dark {
+                    continue;
+                }
+                // Light modules set bits (1 = white in 1-bit grayscale).
+                for py in y * scale..(y + 1) * scale {
+                    for px in x * scale..(x + 1) * scale {
+                        pixels[py * row_bytes + px / 8] |= 0x80 >> (px % 8);
+                    }
+                }
+            }
+        }
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, side as u32, side as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels)?;
+        writer.finish()?;
+        Ok(out)
+    }
+
+    /// Writes the code to `path`, choosing the format from the extension (`png`, `svg`, `txt`).
+    fn save(&self, path: &Path, module_px: u32) -> Result<()> {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+        let bytes = match ext.as_str() {
+            "png" => self.to_png(module_px)?,
+            "svg" => self.to_svg(module_px).into_bytes(),
+            "txt" => self.to_terminal(false).into_bytes(),
+            _ => return Err(QrError::UnsupportedFormat(path.display().to_string())),
+        };
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    // --- Pairing a phone: show a short-lived link in the terminal ---
+    let pairing = "https://example.com/pair?code=7KQ2-XM4P&host=build-01";
+    let qr = Qr::new(pairing, Correction::Medium)?;
+    println!("{:?}", qr);
+    print!("{}", qr.to_terminal(false));
+    println!("Scan to pair, or open: {}", pairing);
+
+    // --- TOTP enrollment: strongest correction that fits in 41 modules (version 6) ---
+    let otpauth = "otpauth://totp/Example:alice@example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=Example";
+    let totp = Qr::best_fit(otpauth, 41)?;
+    println!("TOTP code: version {}, {:?} correction, {} modules with quiet zone", totp.version(), totp.correction(), totp.size());
+
+    // --- Files for a web page or an email ---
+    let dir = std::env::temp_dir();
+    let png = dir.join("pairing-qr.png");
+    let svg = dir.join("pairing-qr.svg");
+    qr.save(&png, 8)?;
+    qr.save(&svg, 8)?;
+    println!("wrote {} ({} bytes) and {} ({} bytes)", png.display(), fs::metadata(&png)?.len(), svg.display(), fs::metadata(&svg)?.len());
+    match qr.save(&dir.join("pairing-qr.gif"), 8) {
+        Err(e) => println!("{}", e),
+        Ok(()) => println!("unexpected success"),
+    }
+
+    // --- Limits and options ---
+    let tiny = Qr::new("HELLO", "q".parse()?)?.quiet_zone(2);
+    println!("HELLO at Q: version {}, {} modules", tiny.version(), tiny.size());
+    match Qr::best_fit("x".repeat(500), 41) {
+        Err(e) => println!("{}", e),
+        Ok(qr) => println!("unexpected fit: {:?}", qr),
+    }
+    match "Z".parse::<Correction>() {
+        Err(e) => println!("{}", e),
+        Ok(c) => println!("unexpected level {:?}", c),
+    }
+    Ok(())
+}
+*/
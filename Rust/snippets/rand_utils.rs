@@ -0,0 +1,187 @@
+// Note: This example requires adding the `rand`, `rand_chacha`, and `uuid` crates to your Cargo.toml:
+// [dependencies]
+// rand = "0.8"
+// rand_chacha = "0.3"
+// uuid = { version = "1", features = ["v4", "v7"] }
+//
+// Two kinds of randomness with different rules:
+// * Secrets (tokens, session ids, reset links) come from `OsRng`, the operating system's CSPRNG.
+// * Test data and simulations use a *seeded* generator so a failing run can be reproduced.
+//   `ChaCha8Rng` is used because its output is stable across platforms and rand versions;
+//   `StdRng` is explicitly allowed to change between releases.
+
+use rand::distributions::{Alphanumeric, Distribution, WeightedError, WeightedIndex};
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use uuid::Uuid;
+
+// --- Secure tokens ---
+
+/// A random alphanumeric string (`[A-Za-z0-9]`) from the OS CSPRNG.
+///
+/// Each character carries ~5.95 bits, so 32 characters is ~190 bits of entropy.
+fn random_alphanumeric(len: usize) -> String {
+    OsRng.sample_iter(&Alphanumeric).take(len).map(char::from).collect()
+}
+
+/// An API token with a recognizable prefix, e.g. `sk_live_3fQ9...`.
+///
+/// The prefix makes leaked tokens easy to spot in logs and secret scanners; the random part
+/// is what provides the security.
+///
+/// # Arguments
+///
+/// * `prefix` - e.g. `"sk_live"`, `"pat"`.
+fn api_token(prefix: &str) -> String {
+    format!("{}_{}", prefix, random_alphanumeric(32))
+}
+
+/// `n_bytes` random bytes as lowercase hex (e.g. 32 bytes -> 64 characters).
+fn token_hex(n_bytes: usize) -> String {
+    let mut bytes = vec![0u8; n_bytes];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A random string drawn from a custom alphabet, e.g. human-friendly codes without 0/O/1/I.
+fn random_from_alphabet(len: usize, alphabet: &[u8]) -> String {
+    (0..len).map(|_| *alphabet.choose(&mut OsRng).expect("alphabet must be non-empty") as char).collect()
+}
+
+// --- UUIDs ---
+
+/// A random (v4) UUID: 122 random bits, no ordering.
+fn uuid_v4() -> Uuid {
+    Uuid::new_v4()
+}
+
+/// A time-ordered (v7) UUID: millisecond timestamp + random bits.
+///
+/// Sorts by creation time, so it makes a far better database primary key than v4 (new rows
+/// append to the end of the B-tree index instead of landing on random pages).
+fn uuid_v7() -> Uuid {
+    Uuid::now_v7()
+}
+
+// --- Weighted selection ---
+
+/// Picks items with probability proportional to their weight.
+///
+/// Useful for load balancing across backends of different capacity, A/B splits, and
+/// generating realistic test data (80% small orders, 20% large).
+struct WeightedPicker<T> {
+    items: Vec<T>,
+    index: WeightedIndex<f64>,
+}
+
+impl<T> WeightedPicker<T> {
+    /// Fails if the list is empty, a weight is negative/NaN, or all weights are zero.
+    fn new(weighted: Vec<(T, f64)>) -> Result<Self, WeightedError> {
+        let (items, weights): (Vec<T>, Vec<f64>) = weighted.into_iter().unzip();
+        Ok(Self {
+            items,
+            index: WeightedIndex::new(weights)?,
+        })
+    }
+
+    fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> &T {
+        &self.items[self.index.sample(rng)]
+    }
+}
+
+/// Picks `amount` distinct items, each draw weighted by `weight` (no item is chosen twice).
+/// Fails if a weight is negative or NaN, like `WeightedPicker::new`.
+fn pick_distinct_weighted<'a, T, R: Rng + ?Sized>(
+    items: &'a [T],
+    amount: usize,
+    weight: impl Fn(&T) -> f64,
+    rng: &mut R,
+) -> Result<Vec<&'a T>, WeightedError> {
+    Ok(items.choose_multiple_weighted(rng, amount, weight)?.collect())
+}
+
+// --- Deterministic generators for tests ---
+
+/// A generator that produces the same sequence for the same seed, on every platform.
+fn seeded_rng(seed: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
+/// Seeds from `TEST_SEED` if set, otherwise picks a random seed and prints it.
+///
+/// Tests that use random data should call this: when one fails in CI, rerun it locally with
+/// `TEST_SEED=<printed value>` to get exactly the same data.
+fn seeded_rng_from_env() -> (ChaCha8Rng, u64) {
+    let seed = std::env::var("TEST_SEED")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| OsRng.next_u64());
+    eprintln!("Using TEST_SEED={}", seed);
+    (seeded_rng(seed), seed)
+}
+
+/// A fake user record for fixtures.
+#[derive(Debug, Clone, PartialEq)]
+struct FakeUser {
+    id: Uuid,
+    name: String,
+    age: u8,
+    plan: &'static str,
+}
+
+/// Generates a reproducible user from `rng`.
+///
+/// The id is built from the RNG too (`Builder::from_random_bytes` sets the v4 bits), so the whole
+/// record, id included, is stable for a given seed.
+fn fake_user<R: Rng + ?Sized>(rng: &mut R, plans: &WeightedPicker<&'static str>) -> FakeUser {
+    const FIRST: &[&str] = &["Ada", "Grace", "Linus", "Barbara", "Ken", "Margaret", "Dennis", "Frances"];
+    const LAST: &[&str] = &["Lovelace", "Hopper", "Torvalds", "Liskov", "Thompson", "Hamilton", "Ritchie", "Allen"];
+    let id = uuid::Builder::from_random_bytes(rng.gen()).into_uuid();
+    FakeUser {
+        id,
+        name: format!("{} {}", FIRST.choose(rng).unwrap(), LAST.choose(rng).unwrap()),
+        age: rng.gen_range(18..=80),
+        plan: plans.pick(rng),
+    }
+}
+
+// Example Usage
+/*
+fn main() -> Result<(), WeightedError> {
+    println!("API token:     {}", api_token("sk_live"));
+    println!("Hex token:     {}", token_hex(32));
+    println!("Invite code:   {}", random_from_alphabet(8, b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789"));
+    println!("UUID v4:       {}", uuid_v4());
+    let (a, b) = (uuid_v7(), uuid_v7());
+    println!("UUID v7:       {} < {} = {}", a, b, a < b);
+
+    // Weighted selection: roughly 70/20/10.
+    let backends = WeightedPicker::new(vec![("big", 7.0), ("medium", 2.0), ("small", 1.0)])?;
+    let mut counts = std::collections::HashMap::new();
+    for _ in 0..10_000 {
+        *counts.entry(*backends.pick(&mut rand::thread_rng())).or_insert(0) += 1;
+    }
+    println!("Weighted picks: {:?}", counts);
+
+    let features = ["search", "export", "sso", "audit", "api"];
+    let rollout = pick_distinct_weighted(&features, 3, |f| if *f == "sso" { 10.0 } else { 1.0 }, &mut rand::thread_rng())?;
+    println!("Distinct weighted: {:?}", rollout);
+
+    // Reproducible fixtures: the same seed always yields the same users.
+    let plans = WeightedPicker::new(vec![("free", 8.0), ("pro", 1.5), ("enterprise", 0.5)])?;
+    let first: Vec<FakeUser> = { let mut rng = seeded_rng(42); (0..3).map(|_| fake_user(&mut rng, &plans)).collect() };
+    let again: Vec<FakeUser> = { let mut rng = seeded_rng(42); (0..3).map(|_| fake_user(&mut rng, &plans)).collect() };
+    for user in &first {
+        println!("{:?}", user);
+    }
+    println!("Same seed, same data: {}", first == again);
+
+    let (mut rng, seed) = seeded_rng_from_env();
+    let mut deck: Vec<u32> = (1..=10).collect();
+    deck.shuffle(&mut rng);
+    println!("Shuffled with seed {}: {:?}", seed, deck);
+    Ok(())
+}
+*/
@@ -0,0 +1,350 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// redis = { version = "0.27", features = ["tokio-comp", "aio"] }
+// tokio = { version = "1", features = ["full"] }
+// futures = "0.3" // For StreamExt on the pub/sub message stream
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+
+use futures::{Stream, StreamExt};
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, Client, Script};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("Failed to encode/decode value: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("Lock {0} is held by another owner")]
+    LockBusy(String),
+
+    #[error("Lock {0} was lost (expired or taken over) before release")]
+    LockLost(String),
+}
+
+type Result<T> = std::result::Result<T, CacheError>;
+
+/// Connects and returns a multiplexed connection.
+/// It is cheap to clone and safe to share between tasks (requests are pipelined
+/// over a single TCP connection), so one per process is usually enough.
+async fn connect(url: &str) -> Result<(Client, MultiplexedConnection)> {
+    let client = Client::open(url)?; // e.g. "redis://127.0.0.1:6379/0"
+    let conn = client.get_multiplexed_async_connection().await?;
+    Ok((client, conn))
+}
+
+// --- Typed caching helpers ---
+
+/// Stores any serializable value as JSON, optionally with an expiry.
+async fn set_json<T: Serialize>(
+    conn: &mut MultiplexedConnection,
+    key: &str,
+    value: &T,
+    ttl: Option<Duration>,
+) -> Result<()> {
+    let payload = serde_json::to_string(value)?;
+    match ttl {
+        // PX = expiry in milliseconds
+        Some(ttl) => conn.pset_ex::<_, _, ()>(key, payload, ttl.as_millis() as u64).await?,
+        None => conn.set::<_, _, ()>(key, payload).await?,
+    }
+    Ok(())
+}
+
+/// Reads a JSON value; `Ok(None)` if the key doesn't exist (or has expired).
+async fn get_json<T: DeserializeOwned>(conn: &mut MultiplexedConnection, key: &str) -> Result<Option<T>> {
+    let payload: Option<String> = conn.get(key).await?;
+    payload
+        .map(|p| serde_json::from_str(&p))
+        .transpose()
+        .map_err(CacheError::from)
+}
+
+/// Cache-aside: return the cached value or compute, store, and return it.
+async fn get_or_set_json<T, F, Fut>(
+    conn: &mut MultiplexedConnection,
+    key: &str,
+    ttl: Duration,
+    compute: F,
+) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = T>,
+{
+    if let Some(cached) = get_json(conn, key).await? {
+        return Ok(cached);
+    }
+    let value = compute().await;
+    set_json(conn, key, &value, Some(ttl)).await?;
+    Ok(value)
+}
+
+// --- Pub/Sub ---
+
+/// Publishes a serializable event to a channel. Returns the number of subscribers that got it.
+async fn publish_json<T: Serialize>(conn: &mut MultiplexedConnection, channel: &str, event: &T) -> Result<u64> {
+    let payload = serde_json::to_string(event)?;
+    Ok(conn.publish(channel, payload).await?)
+}
+
+/// Subscribes to channels (glob patterns like `orders.*` are allowed) and returns a stream of
+/// `(channel, decoded event)`. Messages that fail to decode are yielded as errors rather
+/// than ending the stream.
+///
+/// Pub/sub needs its own dedicated connection, which is why this takes the `Client`.
+async fn subscribe_json<T: DeserializeOwned>(
+    client: &Client,
+    patterns: &[&str],
+) -> Result<impl Stream<Item = (String, Result<T>)>> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    for pattern in patterns {
+        pubsub.psubscribe(*pattern).await?;
+    }
+    Ok(pubsub.into_on_message().map(|msg| {
+        let channel = msg.get_channel_name().to_string();
+        let decoded = msg
+            .get_payload::<String>()
+            .map_err(CacheError::from)
+            .and_then(|p| serde_json::from_str(&p).map_err(CacheError::from));
+        (channel, decoded)
+    }))
+}
+
+// --- Distributed lock (SET NX PX) with renewal and fencing tokens ---
+//
+// A lock alone cannot protect a resource from a client that *thinks* it still holds the lock
+// (e.g. after a long GC pause its TTL expired and another client acquired it). Fencing tokens
+// fix that: every successful acquisition gets a strictly increasing number, and the protected
+// resource rejects writes carrying a token older than the newest it has seen.
+
+/// Atomically: take the lock if free, and if so bump and return the fencing counter.
+const ACQUIRE_SCRIPT: &str = r"
+if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+    return redis.call('INCR', KEYS[2])
+end
+return false
+";
+
+/// Extend the TTL only if we still own the lock.
+const RENEW_SCRIPT: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+";
+
+/// Delete the lock only if we still own it (never release someone else's lock).
+const RELEASE_SCRIPT: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+";
+
+/// A held lock. Renewed in the background until released or dropped.
+struct RedisLock {
+    key: String,
+    owner: String,
+    fencing_token: u64,
+    conn: MultiplexedConnection,
+    held: watch::Receiver<bool>,
+    renewer: JoinHandle<()>,
+}
+
+impl RedisLock {
+    /// Tries once to acquire `key`. Returns `CacheError::LockBusy` if another owner holds it.
+    ///
+    /// # Arguments
+    /// * `ttl` - Lock lifetime if this process dies; renewed every `ttl / 3` while held.
+    async fn try_acquire(conn: &MultiplexedConnection, key: &str, ttl: Duration) -> Result<Self> {
+        let mut conn = conn.clone();
+        let owner = unique_owner_id();
+        let lock_key = format!("lock:{}", key);
+        let fence_key = format!("lock:{}:fence", key);
+        let ttl_ms = ttl.as_millis() as u64;
+
+        let token: Option<u64> = Script::new(ACQUIRE_SCRIPT)
+            .key(&lock_key)
+            .key(&fence_key)
+            .arg(&owner)
+            .arg(ttl_ms)
+            .invoke_async(&mut conn)
+            .await?;
+        let fencing_token = token.ok_or_else(|| CacheError::LockBusy(key.to_string()))?;
+
+        let (held_tx, held) = watch::channel(true);
+        let renewer = tokio::spawn(renew_loop(conn.clone(), lock_key.clone(), owner.clone(), ttl, held_tx));
+
+        Ok(Self {
+            key: lock_key,
+            owner,
+            fencing_token,
+            conn,
+            held,
+            renewer,
+        })
+    }
+
+    /// Retries `try_acquire` until it succeeds or `wait` elapses.
+    async fn acquire(conn: &MultiplexedConnection, key: &str, ttl: Duration, wait: Duration) -> Result<Self> {
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            match Self::try_acquire(conn, key, ttl).await {
+                Err(CacheError::LockBusy(_)) if tokio::time::Instant::now() < deadline => {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                other => return other,
+            }
+        }
+    }
+
+    /// The fencing token to attach to every write made under this lock.
+    fn fencing_token(&self) -> u64 {
+        self.fencing_token
+    }
+
+    /// False once a renewal found the lock expired or owned by someone else.
+    fn is_held(&self) -> bool {
+        *self.held.borrow()
+    }
+
+    /// Resolves when the lock is lost; race it against your critical section with `select!`.
+    async fn lost(&mut self) {
+        let _ = self.held.wait_for(|held| !*held).await;
+    }
+
+    /// Stops renewing and deletes the lock if we still own it.
+    async fn release(mut self) -> Result<()> {
+        self.renewer.abort();
+        let deleted: i64 = Script::new(RELEASE_SCRIPT)
+            .key(&self.key)
+            .arg(&self.owner)
+            .invoke_async(&mut self.conn)
+            .await?;
+        if deleted == 1 {
+            Ok(())
+        } else {
+            Err(CacheError::LockLost(self.key.clone()))
+        }
+    }
+}
+
+impl Drop for RedisLock {
+    fn drop(&mut self) {
+        // Can't run async code in Drop: stop renewing and let the TTL expire the lock.
+        // Call `release().await` to free it immediately.
+        self.renewer.abort();
+    }
+}
+
+async fn renew_loop(
+    mut conn: MultiplexedConnection,
+    lock_key: String,
+    owner: String,
+    ttl: Duration,
+    held: watch::Sender<bool>,
+) {
+    let mut interval = tokio::time::interval(ttl / 3);
+    interval.tick().await; // First tick fires immediately; skip it.
+    loop {
+        interval.tick().await;
+        let renewed: redis::RedisResult<i64> = Script::new(RENEW_SCRIPT)
+            .key(&lock_key)
+            .arg(&owner)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut conn)
+            .await;
+        match renewed {
+            Ok(1) => {}
+            // 0 = expired or stolen. Transient errors are retried on the next tick,
+            // but the TTL keeps running, so a long outage still ends in a lost lock.
+            Ok(_) => {
+                let _ = held.send(false);
+                return;
+            }
+            Err(e) => eprintln!("Lock renewal for {} failed: {}", lock_key, e),
+        }
+    }
+}
+
+/// A process-unique random-ish owner id (hostname/pid/time). Use a UUID in production.
+fn unique_owner_id() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    format!("{}-{}", std::process::id(), nanos)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct UserProfile {
+    id: u64,
+    name: String,
+    plan: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct OrderEvent {
+    order_id: u64,
+    status: String,
+}
+
+// Example Usage (requires a Redis server, e.g. `docker run -p 6379:6379 redis:7`)
+/*
+#[tokio::main]
+async fn main() -> Result<()> {
+    let (client, mut conn) = connect("redis://127.0.0.1:6379/0").await?;
+
+    println!("--- Typed cache ---");
+    let profile = UserProfile { id: 7, name: "Ada".into(), plan: "pro".into() };
+    set_json(&mut conn, "user:7", &profile, Some(Duration::from_secs(60))).await?;
+    let cached: Option<UserProfile> = get_json(&mut conn, "user:7").await?;
+    println!("Cached profile: {:?}", cached);
+
+    let value: UserProfile = get_or_set_json(&mut conn, "user:8", Duration::from_secs(60), || async {
+        println!("Cache miss: loading user 8 from the database...");
+        UserProfile { id: 8, name: "Grace".into(), plan: "free".into() }
+    })
+    .await?;
+    println!("Loaded: {:?}", value);
+
+    println!("\n--- Pub/Sub ---");
+    let mut events = subscribe_json::<OrderEvent>(&client, &["orders.*"]).await?;
+    let listener = tokio::spawn(async move {
+        while let Some((channel, event)) = events.next().await {
+            println!("[{}] {:?}", channel, event);
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await; // Let the subscription register
+    publish_json(&mut conn, "orders.created", &OrderEvent { order_id: 1, status: "created".into() }).await?;
+    publish_json(&mut conn, "orders.shipped", &OrderEvent { order_id: 1, status: "shipped".into() }).await?;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    listener.abort();
+
+    println!("\n--- Distributed lock with fencing ---");
+    let mut lock = RedisLock::acquire(&conn, "nightly-report", Duration::from_secs(3), Duration::from_secs(5)).await?;
+    println!("Acquired lock, fencing token {}", lock.fencing_token());
+
+    // A second contender fails fast while the first holds the lock.
+    match RedisLock::try_acquire(&conn, "nightly-report", Duration::from_secs(3)).await {
+        Err(CacheError::LockBusy(key)) => println!("Second contender: {} is busy", key),
+        other => println!("Unexpected: {:?}", other.map(|l| l.fencing_token())),
+    }
+
+    // Do work longer than the TTL; background renewal keeps the lock alive.
+    tokio::select! {
+        _ = tokio::time::sleep(Duration::from_secs(5)) => println!("Work done, lock held: {}", lock.is_held()),
+        _ = lock.lost() => println!("Lock lost mid-work, aborting!"),
+    }
+    lock.release().await?;
+    Ok(())
+}
+*/
@@ -0,0 +1,605 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// minijinja = "2"
+// serde = { version = "1.0", features = ["derive"] }
+// thiserror = "1.0"
+//
+// HTML and PDF reports from structured data, for the weekly-sales or nightly-SLA email that a
+// scheduled job (`cron.rs`) produces:
+// * `ReportEngine` renders any `Serialize` context through Jinja templates (as in
+//   `templates.rs`) that extend a print-ready `base.html`: A4 pages, tables with tabular
+//   numbers, and charts that never split across a page break.
+// * Templates draw charts from the data they're given: `{{ bar_chart(rows, "region", "revenue") }}`,
+//   `{{ line_chart(days, "day", "orders") }}`, and `{{ histogram(latencies, 12) }}` emit inline
+//   SVG, so the HTML is a single self-contained file that mail clients and PDF renderers
+//   display identically. `{% set s = summary(latencies) %}` gives count, mean, and percentiles
+//   (see `stats_math.rs` for the full statistics).
+// * PDF conversion goes through the `PdfBackend` trait. `CommandBackend` drives headless
+//   Chromium, wkhtmltopdf, or WeasyPrint with a timeout, and `CommandBackend::detect` finds
+//   whichever is installed. Implement the trait for a rendering service (e.g. Gotenberg) instead.
+// * `publish` writes `<stem>.html` and, given a backend, `<stem>.pdf`, each atomically. The HTML
+//   is written first, so a PDF failure still leaves a usable report behind.
+
+use minijinja::value::Value;
+use minijinja::{Environment, ErrorKind};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum ReportError {
+    #[error("template error: {0:#}")]
+    Template(#[from] minijinja::Error),
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("PDF conversion with {backend} failed: {message}")]
+    Pdf { backend: String, message: String },
+}
+
+type Result<T> = std::result::Result<T, ReportError>;
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> ReportError + '_ {
+    move |source| ReportError::Io { path: path.to_path_buf(), source }
+}
+
+// --- Layout ---
+
+const BASE_TEMPLATE: &str = r#"<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{ title }}</title>
+<style>
+  @page { size: A4; margin: 16mm 14mm; }
+  body { font: 10.5pt/1.45 -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: #1f2933; margin: 0 auto; max-width: 190mm; }
+  header { border-bottom: 2px solid #3867d6; margin-bottom: 12pt; }
+  h1 { font-size: 20pt; margin: 0 0 2pt; }
+  h2 { font-size: 13pt; margin: 16pt 0 6pt; }
+  .meta { color: #616e7c; margin: 0 0 8pt; }
+  table { border-collapse: collapse; width: 100%; margin: 6pt 0; }
+  th, td { padding: 4pt 6pt; border-bottom: 1px solid #e4e7eb; text-align: left; }
+  th { background: #f5f7fa; font-weight: 600; }
+  td.num, th.num { text-align: right; font-variant-numeric: tabular-nums; }
+  .chart, table, .kpis { break-inside: avoid; page-break-inside: avoid; }
+  .kpis { display: flex; gap: 10pt; }
+  .kpi { flex: 1; border: 1px solid #e4e7eb; border-radius: 4pt; padding: 6pt 8pt; }
+  .kpi b { display: block; font-size: 15pt; }
+  footer { color: #9aa5b1; font-size: 8.5pt; margin-top: 18pt; }
+</style>
+</head>
+<body>
+<header>
+  <h1>{{ title }}</h1>
+  <p class="meta">{% if subtitle %}{{ subtitle }} · {% endif %}Generated {{ generated_at }}</p>
+</header>
+{% block content %}{% endblock %}
+<footer>{% block footer %}{{ title }}{% endblock %}</footer>
+</body>
+</html>
+"#;
+
+// --- Charts ---
+
+const CHART_W: f64 = 640.0;
+const CHART_H: f64 = 240.0;
+/// Left, right, top, bottom padding around the plot area.
+const PAD: (f64, f64, f64, f64) = (56.0, 12.0, 28.0, 36.0);
+const BAR_COLOR: &str = "#3867d6";
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Compact axis labels: 950, 12.5k, 3.2M.
+fn short_number(v: f64) -> String {
+    match v.abs() {
+        a if a >= 1e6 => format!("{:.1}M", v / 1e6),
+        a if a >= 1e4 => format!("{:.0}k", v / 1e3),
+        a if a >= 1e3 => format!("{:.1}k", v / 1e3),
+        a if a >= 10.0 || a == 0.0 => format!("{:.0}", v),
+        _ => format!("{:.2}", v),
+    }
+}
+
+/// A "nice" axis maximum (1, 2, or 5 times a power of ten) at or above `max`.
+fn nice_max(max: f64) -> f64 {
+    if max <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(max.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].iter().map(|m| m * magnitude).find(|v| *v >= max).unwrap_or(10.0 * magnitude)
+}
+
+/// Opens an SVG with a title, horizontal grid lines, and y-axis labels up to `y_max`.
+fn chart_frame(title: Option<&str>, y_max: f64) -> String {
+    let (left, right, top, bottom) = PAD;
+    let plot_h = CHART_H - top - bottom;
+    let mut svg = format!(
+        r#"<div class="chart"><svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="100%" font-family="Helvetica, Arial, sans-serif" font-size="11">"#,
+        w = CHART_W,
+        h = CHART_H
+    );
+    if let Some(title) = title {
+        let _ = write!(svg, r#"<text x="{}" y="16" font-size="13" font-weight="600">{}</text>"#, left, escape_xml(title));
+    }
+    for i in 0..=4 {
+        let y = top + plot_h * (1.0 - i as f64 / 4.0);
+        let _ = write!(
+            svg,
+            r##"<line x1="{l}" x2="{r}" y1="{y:.1}" y2="{y:.1}" stroke="#e4e7eb"/><text x="{tx}" y="{ty:.1}" text-anchor="end" fill="#616e7c">{label}</text>"##,
+            l = left,
+            r = CHART_W - right,
+            y = y,
+            tx = left - 6.0,
+            ty = y + 4.0,
+            label = short_number(y_max * i as f64 / 4.0)
+        );
+    }
+    svg
+}
+
+/// Vertical bars, one per `(label, value)`. Negative values are drawn as zero.
+fn bar_chart_svg(title: Option<&str>, bars: &[(String, f64)]) -> String {
+    let (left, right, top, bottom) = PAD;
+    let (plot_w, plot_h) = (CHART_W - left - right, CHART_H - top - bottom);
+    let y_max = nice_max(bars.iter().map(|(_, v)| *v).fold(0.0, f64::max));
+    let mut svg = chart_frame(title, y_max);
+    let slot = plot_w / bars.len().max(1) as f64;
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let h = plot_h * value.max(0.0) / y_max;
+        let x = left + slot * i as f64 + slot * 0.15;
+        let _ = write!(
+            svg,
+            r##"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" fill="{c}"><title>{label}: {v}</title></rect><text x="{cx:.1}" y="{ly:.1}" text-anchor="middle" fill="#323f4b">{label}</text>"##,
+            x = x,
+            y = top + plot_h - h,
+            w = slot * 0.7,
+            h = h,
+            c = BAR_COLOR,
+            label = escape_xml(label),
+            v = short_number(*value),
+            cx = x + slot * 0.35,
+            ly = CHART_H - bottom + 16.0
+        );
+    }
+    svg.push_str("</svg></div>");
+    svg
+}
+
+/// A line through `(label, value)` points in order, with every nth label shown to avoid overlap.
+fn line_chart_svg(title: Option<&str>, points: &[(String, f64)]) -> String {
+    let (left, right, top, bottom) = PAD;
+    let (plot_w, plot_h) = (CHART_W - left - right, CHART_H - top - bottom);
+    let y_max = nice_max(points.iter().map(|(_, v)| *v).fold(0.0, f64::max));
+    let mut svg = chart_frame(title, y_max);
+    let step = plot_w / (points.len().max(2) - 1) as f64;
+    let label_every = points.len().div_ceil(10).max(1);
+    let mut path = String::new();
+    for (i, (label, value)) in points.iter().enumerate() {
+        let (x, y) = (left + step * i as f64, top + plot_h * (1.0 - value.max(0.0) / y_max));
+        let _ = write!(path, "{}{:.1},{:.1} ", if i == 0 { "M" } else { "L" }, x, y);
+        if i % label_every == 0 {
+            let _ = write!(
+                svg,
+                r##"<text x="{x:.1}" y="{ly:.1}" text-anchor="middle" fill="#323f4b">{label}</text>"##,
+                x = x,
+                ly = CHART_H - bottom + 16.0,
+                label = escape_xml(label)
+            );
+        }
+    }
+    let _ = write!(svg, r#"<path d="{}" fill="none" stroke="{}" stroke-width="2"/>"#, path.trim_end(), BAR_COLOR);
+    svg.push_str("</svg></div>");
+    svg
+}
+
+// --- Statistics ---
+
+/// The numbers a report usually quotes; see `stats_math.rs` for the full `Summary`.
+#[derive(Debug, Clone, Serialize)]
+struct Summary {
+    count: usize,
+    mean: f64,
+    min: f64,
+    p50: f64,
+    p95: f64,
+    max: f64,
+}
+
+fn summarize(values: &[f64]) -> Option<Summary> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f64::total_cmp);
+    // Nearest-rank percentile.
+    let rank = |p: f64| sorted[((p / 100.0 * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len()) - 1];
+    Some(Summary {
+        count: sorted.len(),
+        mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        min: sorted[0],
+        p50: rank(50.0),
+        p95: rank(95.0),
+        max: sorted[sorted.len() - 1],
+    })
+}
+
+/// Equal-width bins over the range of `values`, labeled by their lower bound.
+fn histogram_bins(values: &[f64], bins: usize) -> Vec<(String, f64)> {
+    let finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let (min, max) = finite.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(*v), hi.max(*v)));
+    if finite.is_empty() {
+        return Vec::new();
+    }
+    let bins = if max > min { bins.max(1) } else { 1 };
+    let width = if max > min { (max - min) / bins as f64 } else { 1.0 };
+    let mut counts = vec![0usize; bins];
+    for v in &finite {
+        counts[(((v - min) / width) as usize).min(bins - 1)] += 1;
+    }
+    counts.into_iter().enumerate().map(|(i, c)| (short_number(min + width * i as f64), c as f64)).collect()
+}
+
+// --- Template Functions ---
+
+/// Reads `(label, value)` pairs from a list of objects, e.g. `bar_chart(rows, "region", "revenue")`.
+fn pairs(rows: &Value, label_key: &str, value_key: &str) -> std::result::Result<Vec<(String, f64)>, minijinja::Error> {
+    rows.try_iter()?
+        .map(|row| {
+            let label = row.get_attr(label_key)?.to_string();
+            let value = f64::try_from(row.get_attr(value_key)?).map_err(|_| {
+                minijinja::Error::new(ErrorKind::InvalidOperation, format!("{:?} of {:?} is not a number", value_key, label))
+            })?;
+            Ok((label, value))
+        })
+        .collect()
+}
+
+fn numbers(values: &Value) -> std::result::Result<Vec<f64>, minijinja::Error> {
+    values.try_iter()?.map(f64::try_from).collect()
+}
+
+fn register_report_helpers(env: &mut Environment<'static>) {
+    env.add_function("bar_chart", |rows: Value, label: String, value: String, title: Option<String>| {
+        Ok::<_, minijinja::Error>(Value::from_safe_string(bar_chart_svg(title.as_deref(), &pairs(&rows, &label, &value)?)))
+    });
+    env.add_function("line_chart", |rows: Value, label: String, value: String, title: Option<String>| {
+        Ok::<_, minijinja::Error>(Value::from_safe_string(line_chart_svg(title.as_deref(), &pairs(&rows, &label, &value)?)))
+    });
+    env.add_function("histogram", |values: Value, bins: Option<usize>, title: Option<String>| {
+        let bins = histogram_bins(&numbers(&values)?, bins.unwrap_or(10));
+        Ok::<_, minijinja::Error>(Value::from_safe_string(bar_chart_svg(title.as_deref(), &bins)))
+    });
+    env.add_function("summary", |values: Value| Ok::<_, minijinja::Error>(Value::from_serialize(summarize(&numbers(&values)?))));
+
+    // {{ 1234567.891 | num }} -> 1,234,568 ; {{ x | num(2) }} -> 1,234,567.89
+    env.add_filter("num", |value: f64, decimals: Option<usize>| -> String {
+        let formatted = format!("{:.*}", decimals.unwrap_or(0), value.abs());
+        let (whole, frac) = formatted.split_once('.').map_or((formatted.as_str(), None), |(w, f)| (w, Some(f)));
+        let mut out = String::from(if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" });
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                out.push(',');
+            }
+            out.push(digit);
+        }
+        if let Some(frac) = frac {
+            out.push('.');
+            out.push_str(frac);
+        }
+        out
+    });
+    // {{ 0.0731 | pct }} -> 7.3%
+    env.add_filter("pct", |value: f64| format!("{:.1}%", value * 100.0));
+}
+
+// --- Engine ---
+
+struct ReportEngine {
+    env: Environment<'static>,
+}
+
+impl ReportEngine {
+    /// An engine with `base.html`, chart functions, and number filters registered.
+    /// HTML is auto-escaped and undefined variables are errors, since a report with a silently
+    /// blank figure is worse than a failed job.
+    fn new() -> Self {
+        let mut env = Environment::new();
+        env.set_undefined_behavior(minijinja::UndefinedBehavior::Strict);
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        register_report_helpers(&mut env);
+        env.add_template("base.html", BASE_TEMPLATE).expect("base template is valid");
+        Self { env }
+    }
+
+    /// Registers a report template; name it `*.html` so output is escaped.
+    fn add_template(&mut self, name: &str, source: &str) -> Result<()> {
+        self.env.add_template_owned(name.to_string(), source.to_string())?;
+        Ok(())
+    }
+
+    /// Renders `template` with `context`. The layout needs `title`, `subtitle` (may be null),
+    /// and `generated_at` in the context.
+    fn render_html<S: Serialize>(&self, template: &str, context: S) -> Result<String> {
+        Ok(self.env.get_template(template)?.render(context)?)
+    }
+
+    /// Renders and writes `<dir>/<stem>.html`, then `<stem>.pdf` when a backend is given.
+    ///
+    /// # Returns
+    /// The written paths. On `ReportError::Pdf` the HTML file has already been written.
+    fn publish<S: Serialize>(
+        &self,
+        template: &str,
+        context: S,
+        dir: &Path,
+        stem: &str,
+        pdf: Option<&dyn PdfBackend>,
+    ) -> Result<ReportFiles> {
+        let html = self.render_html(template, context)?;
+        fs::create_dir_all(dir).map_err(io_error(dir))?;
+        let html_path = dir.join(format!("{}.html", stem));
+        write_atomic(&html_path, html.as_bytes())?;
+
+        let pdf_path = match pdf {
+            Some(backend) => {
+                let path = dir.join(format!("{}.pdf", stem));
+                let tmp = dir.join(format!(".{}.pdf.tmp", stem));
+                let result = backend.html_to_pdf(&html_path, &tmp).and_then(|()| fs::rename(&tmp, &path).map_err(io_error(&path)));
+                if result.is_err() {
+                    let _ = fs::remove_file(&tmp);
+                }
+                result?;
+                Some(path)
+            }
+            None => None,
+        };
+        Ok(ReportFiles { html: html_path, pdf: pdf_path })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ReportFiles {
+    html: PathBuf,
+    pdf: Option<PathBuf>,
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes).and_then(|()| fs::rename(&tmp, path)).map_err(|source| {
+        let _ = fs::remove_file(&tmp);
+        ReportError::Io { path: path.to_path_buf(), source }
+    })
+}
+
+// --- PDF Backends ---
+
+/// Converts a rendered HTML file to PDF.
+trait PdfBackend {
+    fn name(&self) -> &str;
+    fn html_to_pdf(&self, html: &Path, pdf: &Path) -> Result<()>;
+}
+
+/// Runs an external converter. In `args`, `{input}` is the HTML path, `{input_url}` the same as
+/// a `file://` URL, and `{output}` the PDF path.
+#[derive(Debug, Clone)]
+struct CommandBackend {
+    name: String,
+    program: PathBuf,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl CommandBackend {
+    fn new(name: &str, program: impl Into<PathBuf>, args: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            program: program.into(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// Headless Chrome/Chromium. Add `--no-sandbox` via `arg` only when running as root in a
+    /// container, where Chrome's sandbox cannot start.
+    fn chromium(program: impl Into<PathBuf>) -> Self {
+        Self::new(
+            "chromium",
+            program,
+            &["--headless", "--disable-gpu", "--no-pdf-header-footer", "--print-to-pdf={output}", "{input_url}"],
+        )
+    }
+
+    fn wkhtmltopdf(program: impl Into<PathBuf>) -> Self {
+        Self::new("wkhtmltopdf", program, &["--quiet", "--enable-local-file-access", "--print-media-type", "{input}", "{output}"])
+    }
+
+    fn weasyprint(program: impl Into<PathBuf>) -> Self {
+        Self::new("weasyprint", program, &["{input}", "{output}"])
+    }
+
+    fn arg(mut self, arg: &str) -> Self {
+        self.args.insert(0, arg.to_string());
+        self
+    }
+
+    fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The first converter found on `PATH`, preferring Chromium for its CSS support.
+    fn detect() -> Option<Self> {
+        let path = std::env::var_os("PATH")?;
+        ["chromium", "chromium-browser", "google-chrome", "chrome", "weasyprint", "wkhtmltopdf"].iter().find_map(|program| {
+            let found = std::env::split_paths(&path).map(|dir| dir.join(program)).find(|p| p.is_file())?;
+            Some(match *program {
+                "weasyprint" => Self::weasyprint(found),
+                "wkhtmltopdf" => Self::wkhtmltopdf(found),
+                _ => Self::chromium(found),
+            })
+        })
+    }
+}
+
+impl PdfBackend for CommandBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn html_to_pdf(&self, html: &Path, pdf: &Path) -> Result<()> {
+        let fail = |message: String| ReportError::Pdf { backend: self.name.clone(), message };
+        let html = html.canonicalize().map_err(io_error(html))?;
+        let url = format!("file://{}", html.display());
+        let args = self.args.iter().map(|a| {
+            a.replace("{input_url}", &url).replace("{input}", &html.to_string_lossy()).replace("{output}", &pdf.to_string_lossy())
+        });
+        let mut child = Command::new(&self.program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| fail(format!("cannot start {}: {}", self.program.display(), e)))?;
+
+        // Drain stderr on a thread so a chatty converter can't block on a full pipe.
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let reader = thread::spawn(move || {
+            let mut text = String::new();
+            let _ = stderr.read_to_string(&mut text);
+            text
+        });
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            match child.try_wait().map_err(|e| fail(e.to_string()))? {
+                Some(status) => break status,
+                None if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(fail(format!("timed out after {:?}", self.timeout)));
+                }
+                None => thread::sleep(Duration::from_millis(50)),
+            }
+        };
+        let stderr = reader.join().unwrap_or_default();
+        let produced = fs::read(pdf).map(|bytes| bytes.starts_with(b"%PDF")).unwrap_or(false);
+        if !status.success() || !produced {
+            let last_line = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("no output");
+            return Err(fail(format!("{} ({})", status, last_line.trim())));
+        }
+        Ok(())
+    }
+}
+
+// Example Usage
+/*
+#[derive(Serialize)]
+struct RegionSales {
+    region: String,
+    orders: u32,
+    revenue: f64,
+    refund_rate: f64,
+}
+
+#[derive(Serialize)]
+struct DailyOrders {
+    day: String,
+    orders: u32,
+}
+
+#[derive(Serialize)]
+struct WeeklyReport<'a> {
+    title: &'a str,
+    subtitle: Option<&'a str>,
+    generated_at: &'a str,
+    regions: Vec<RegionSales>,
+    daily: Vec<DailyOrders>,
+    latencies_ms: Vec<f64>,
+}
+
+const WEEKLY: &str = r#"{% extends "base.html" %}
+{% block content %}
+{% set lat = summary(latencies_ms) %}
+<div class="kpis">
+  <div class="kpi">Revenue<b>${{ regions | map(attribute="revenue") | sum | num }}</b></div>
+  <div class="kpi">Orders<b>{{ regions | map(attribute="orders") | sum | num }}</b></div>
+  <div class="kpi">p95 checkout<b>{{ lat.p95 | num(0) }} ms</b></div>
+</div>
+
+<h2>Revenue by region</h2>
+{{ bar_chart(regions, "region", "revenue") }}
+<table>
+  <tr><th>Region</th><th class="num">Orders</th><th class="num">Revenue</th><th class="num">Refunds</th></tr>
+  {% for r in regions %}
+  <tr><td>{{ r.region }}</td><td class="num">{{ r.orders | num }}</td><td class="num">${{ r.revenue | num(2) }}</td><td class="num">{{ r.refund_rate | pct }}</td></tr>
+  {% endfor %}
+</table>
+
+<h2>Orders per day</h2>
+{{ line_chart(daily, "day", "orders") }}
+
+<h2>Checkout latency</h2>
+{{ histogram(latencies_ms, 12, "Distribution (ms)") }}
+<p>{{ lat.count | num }} requests; median {{ lat.p50 | num(1) }} ms, p95 {{ lat.p95 | num(1) }} ms, worst {{ lat.max | num(1) }} ms.</p>
+{% endblock %}
+"#;
+
+fn main() -> Result<()> {
+    let mut engine = ReportEngine::new();
+    engine.add_template("weekly.html", WEEKLY)?;
+
+    let regions = [("North", 412, 48_210.5, 0.021), ("South", 388, 39_950.0, 0.034), ("East", 290, 31_004.25, 0.012), ("West <Coast>", 505, 61_877.8, 0.027)];
+    let report = WeeklyReport {
+        title: "Weekly Sales",
+        subtitle: Some("Week 41"),
+        generated_at: "2024-10-14 06:00 UTC",
+        regions: regions
+            .iter()
+            .map(|(region, orders, revenue, refund_rate)| RegionSales { region: region.to_string(), orders: *orders, revenue: *revenue, refund_rate: *refund_rate })
+            .collect(),
+        daily: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+            .iter()
+            .enumerate()
+            .map(|(i, day)| DailyOrders { day: day.to_string(), orders: 180 + (i as u32 * 37) % 90 })
+            .collect(),
+        latencies_ms: (0..2_000).map(|i| 80.0 + ((i * 7919) % 1000) as f64 / 10.0 + if i % 97 == 0 { 400.0 } else { 0.0 }).collect(),
+    };
+
+    let html = engine.render_html("weekly.html", &report)?;
+    println!("rendered {} bytes of HTML with {} inline charts", html.len(), html.matches("<svg").count());
+    println!("region names are escaped: {}", html.contains("West &lt;Coast&gt;"));
+
+    let dir = std::env::temp_dir().join("reports-demo");
+    let backend = CommandBackend::detect().map(|b| b.timeout(Duration::from_secs(30)));
+    match &backend {
+        Some(b) => println!("PDF backend: {}", b.name()),
+        None => println!("no PDF backend on PATH (install chromium, weasyprint, or wkhtmltopdf); writing HTML only"),
+    }
+    let files = engine.publish("weekly.html", &report, &dir, "weekly-2024-41", backend.as_ref().map(|b| b as &dyn PdfBackend))?;
+    println!("wrote {}", files.html.display());
+    if let Some(pdf) = &files.pdf {
+        println!("wrote {}", pdf.display());
+    }
+
+    // A converter that fails leaves the HTML in place and reports why.
+    let broken = CommandBackend::new("false", "false", &[]);
+    match engine.publish("weekly.html", &report, &dir, "weekly-broken", Some(&broken)) {
+        Err(e) => println!("{} (HTML kept: {})", e, dir.join("weekly-broken.html").exists()),
+        Ok(_) => println!("unexpected success"),
+    }
+
+    // Strict mode: a missing field fails the job instead of printing a blank.
+    match engine.render_html("weekly.html", minijinja::context! { title => "Missing data" }) {
+        Err(e) => println!("{}", e.to_string().lines().next().unwrap_or("")),
+        Ok(_) => println!("unexpected success"),
+    }
+    Ok(())
+}
+*/
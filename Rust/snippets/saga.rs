@@ -0,0 +1,389 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+//
+// Sagas: a multi-step workflow across services that can't share a transaction (reserve a
+// flight, reserve a hotel, charge the card). Each step registers a forward action and a
+// compensation that undoes it:
+// * `Saga::execute` runs the forward actions in order, each with its own retry policy and
+//   optional timeout. If one fails for good, the compensations of the steps that already
+//   succeeded run in reverse order.
+// * Compensations are retried with backoff, since leaving a hotel booked is worse than a few
+//   extra calls. If one still fails, the rest run anyway and the outcome is
+//   `CompensationFailed`, which names the steps that need a human.
+// * The failed step itself is not compensated. If it can fail after taking effect (a timeout
+//   after the request landed), make its forward action idempotent and retry it instead.
+// * `SagaReport` records every attempt, error, and duration; print it, or serialize it to JSON
+//   for logs and audit trails.
+//
+// Actions receive `&mut C`, a context struct that carries ids from one step to the next (the
+// flight's reservation id, for example, which its compensation needs).
+
+use serde::Serialize;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+type Action<C> = Box<dyn for<'a> Fn(&'a mut C) -> BoxFuture<'a, Result<(), String>> + Send + Sync>;
+
+// --- Definition ---
+
+/// How often to try an action and how long to wait between tries.
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// One attempt, no retries.
+    fn none() -> Self {
+        Self { max_attempts: 1, initial_backoff: Duration::ZERO, max_backoff: Duration::ZERO }
+    }
+
+    /// `max_attempts` tries, waiting `initial_backoff`, then twice that, and so on up to 30s.
+    fn exponential(max_attempts: u32, initial_backoff: Duration) -> Self {
+        Self { max_attempts: max_attempts.max(1), initial_backoff, max_backoff: Duration::from_secs(30) }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+struct Step<C> {
+    name: String,
+    forward: Action<C>,
+    compensate: Option<Action<C>>,
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+}
+
+/// A named sequence of steps over a context `C`.
+struct Saga<C> {
+    name: String,
+    steps: Vec<Step<C>>,
+    compensation_retry: RetryPolicy,
+}
+
+impl<C: Send> Saga<C> {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            steps: Vec::new(),
+            compensation_retry: RetryPolicy::exponential(5, Duration::from_millis(200)),
+        }
+    }
+
+    /// Adds a step and the action that undoes it. Both get the shared context; compensations
+    /// should be idempotent, since a retry may follow a call that succeeded but timed out.
+    ///
+    /// Write actions as `|ctx| Box::pin(async move { ... })`.
+    fn step<F, G>(mut self, name: &str, forward: F, compensate: G) -> Self
+    where
+        F: for<'a> Fn(&'a mut C) -> BoxFuture<'a, Result<(), String>> + Send + Sync + 'static,
+        G: for<'a> Fn(&'a mut C) -> BoxFuture<'a, Result<(), String>> + Send + Sync + 'static,
+    {
+        self.steps.push(Step {
+            name: name.to_string(),
+            forward: Box::new(forward),
+            compensate: Some(Box::new(compensate)),
+            retry: RetryPolicy::none(),
+            timeout: None,
+        });
+        self
+    }
+
+    /// Adds a step with nothing to undo: a read, a validation, or the final step (the "pivot")
+    /// after which the saga can only go forward.
+    fn step_without_compensation<F>(mut self, name: &str, forward: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut C) -> BoxFuture<'a, Result<(), String>> + Send + Sync + 'static,
+    {
+        self.steps.push(Step { name: name.to_string(), forward: Box::new(forward), compensate: None, retry: RetryPolicy::none(), timeout: None });
+        self
+    }
+
+    /// Retries the most recently added step's forward action. Only for idempotent actions.
+    fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        if let Some(step) = self.steps.last_mut() {
+            step.retry = retry;
+        }
+        self
+    }
+
+    /// Fails an attempt of the most recently added step's forward action after `timeout`.
+    fn with_timeout(mut self, timeout: Duration) -> Self {
+        if let Some(step) = self.steps.last_mut() {
+            step.timeout = Some(timeout);
+        }
+        self
+    }
+
+    /// The retry policy for every compensation (default: 5 attempts from 200ms).
+    fn compensation_retry(mut self, retry: RetryPolicy) -> Self {
+        self.compensation_retry = retry;
+        self
+    }
+}
+
+// --- Execution ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Phase {
+    Forward,
+    Compensate,
+}
+
+/// One action's run, including all of its attempts.
+#[derive(Debug, Clone, Serialize)]
+struct StepRecord {
+    step: String,
+    phase: Phase,
+    attempts: u32,
+    succeeded: bool,
+    /// Each failed attempt's error, in order.
+    errors: Vec<String>,
+    duration_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum SagaOutcome {
+    Completed,
+    /// A step failed and everything before it was undone.
+    Compensated { failed_step: String, error: String },
+    /// A step failed and some compensations failed too; `stuck` lists the steps whose
+    /// effects are still in place.
+    CompensationFailed { failed_step: String, error: String, stuck: Vec<String> },
+}
+
+/// What happened during one `execute`, for logs, alerts, and audit trails.
+#[derive(Debug, Clone, Serialize)]
+struct SagaReport {
+    saga: String,
+    outcome: SagaOutcome,
+    records: Vec<StepRecord>,
+    duration_ms: u64,
+}
+
+impl SagaReport {
+    fn is_completed(&self) -> bool {
+        self.outcome == SagaOutcome::Completed
+    }
+
+    /// `true` if someone has to clean up by hand.
+    fn needs_attention(&self) -> bool {
+        matches!(self.outcome, SagaOutcome::CompensationFailed { .. })
+    }
+}
+
+impl fmt::Display for SagaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = match &self.outcome {
+            SagaOutcome::Completed => "completed".to_string(),
+            SagaOutcome::Compensated { failed_step, error } => format!("compensated after {} failed: {}", failed_step, error),
+            SagaOutcome::CompensationFailed { failed_step, error, stuck } => {
+                format!("COMPENSATION FAILED after {} failed: {}; stuck: {}", failed_step, error, stuck.join(", "))
+            }
+        };
+        writeln!(f, "saga {}: {} ({}ms)", self.saga, outcome, self.duration_ms)?;
+        for record in &self.records {
+            let phase = match record.phase {
+                Phase::Forward => "->",
+                Phase::Compensate => "<-",
+            };
+            let status = if record.succeeded { "ok" } else { "FAILED" };
+            write!(f, "  {} {:<20} {:<6} attempts={} {}ms", phase, record.step, status, record.attempts, record.duration_ms)?;
+            if let Some(last) = record.errors.last() {
+                write!(f, "  last error: {}", last)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `action` under `retry` (and `timeout`, per attempt), recording every attempt.
+async fn run_action<C>(
+    name: &str,
+    phase: Phase,
+    action: &Action<C>,
+    ctx: &mut C,
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+) -> StepRecord {
+    let started = Instant::now();
+    let mut record = StepRecord { step: name.to_string(), phase, attempts: 0, succeeded: false, errors: Vec::new(), duration_ms: 0 };
+    while record.attempts < retry.max_attempts {
+        record.attempts += 1;
+        let result = match timeout {
+            Some(limit) => tokio::time::timeout(limit, action(ctx)).await.unwrap_or_else(|_| Err(format!("timed out after {:?}", limit))),
+            None => action(ctx).await,
+        };
+        match result {
+            Ok(()) => {
+                record.succeeded = true;
+                break;
+            }
+            Err(error) => {
+                record.errors.push(error);
+                if record.attempts < retry.max_attempts {
+                    tokio::time::sleep(retry.backoff(record.attempts)).await;
+                }
+            }
+        }
+    }
+    record.duration_ms = started.elapsed().as_millis() as u64;
+    record
+}
+
+impl<C: Send> Saga<C> {
+    /// Runs the saga against `ctx`.
+    ///
+    /// # Returns
+    ///
+    /// A report; it never panics or returns early on a failed step. Check
+    /// `report.is_completed()`, and alert on `report.needs_attention()`.
+    async fn execute(&self, ctx: &mut C) -> SagaReport {
+        let started = Instant::now();
+        let mut records = Vec::new();
+        let mut failure = None;
+        for (index, step) in self.steps.iter().enumerate() {
+            let record = run_action(&step.name, Phase::Forward, &step.forward, ctx, step.retry, step.timeout).await;
+            let ok = record.succeeded;
+            let error = record.errors.last().cloned().unwrap_or_default();
+            records.push(record);
+            if !ok {
+                failure = Some((index, error));
+                break;
+            }
+        }
+
+        let outcome = match failure {
+            None => SagaOutcome::Completed,
+            Some((failed_index, error)) => {
+                let mut stuck = Vec::new();
+                for step in self.steps[..failed_index].iter().rev() {
+                    let Some(compensate) = &step.compensate else { continue };
+                    let record = run_action(&step.name, Phase::Compensate, compensate, ctx, self.compensation_retry, None).await;
+                    if !record.succeeded {
+                        stuck.push(step.name.clone());
+                    }
+                    records.push(record);
+                }
+                let failed_step = self.steps[failed_index].name.clone();
+                if stuck.is_empty() {
+                    SagaOutcome::Compensated { failed_step, error }
+                } else {
+                    SagaOutcome::CompensationFailed { failed_step, error, stuck }
+                }
+            }
+        };
+        SagaReport { saga: self.name.clone(), outcome, records, duration_ms: started.elapsed().as_millis() as u64 }
+    }
+}
+
+// Example Usage
+/*
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Ids that later steps and compensations need.
+#[derive(Debug, Default)]
+struct TripBooking {
+    customer: String,
+    card_declines: bool,
+    flight_id: Option<String>,
+    hotel_id: Option<String>,
+    payment_id: Option<String>,
+}
+
+fn book_trip(hotel_cancel_failures: Arc<AtomicU32>) -> Saga<TripBooking> {
+    Saga::new("book_trip")
+        .step_without_compensation("validate_customer", |ctx: &mut TripBooking| {
+            Box::pin(async move {
+                if ctx.customer.is_empty() { Err("customer is required".to_string()) } else { Ok(()) }
+            })
+        })
+        .step(
+            "reserve_flight",
+            |ctx: &mut TripBooking| {
+                Box::pin(async move {
+                    ctx.flight_id = Some("FL-1001".to_string());
+                    Ok(())
+                })
+            },
+            |ctx: &mut TripBooking| {
+                Box::pin(async move {
+                    println!("  cancelling flight {:?}", ctx.flight_id.take());
+                    Ok(())
+                })
+            },
+        )
+        .step(
+            "reserve_hotel",
+            |ctx: &mut TripBooking| {
+                Box::pin(async move {
+                    ctx.hotel_id = Some("HT-77".to_string());
+                    Ok(())
+                })
+            },
+            move |ctx: &mut TripBooking| {
+                let failures = Arc::clone(&hotel_cancel_failures);
+                Box::pin(async move {
+                    // The hotel API is flaky; the compensation retry policy absorbs it.
+                    if failures.load(Ordering::SeqCst) > 0 {
+                        failures.fetch_sub(1, Ordering::SeqCst);
+                        return Err("hotel API: 503 Service Unavailable".to_string());
+                    }
+                    println!("  cancelling hotel {:?}", ctx.hotel_id.take());
+                    Ok(())
+                })
+            },
+        )
+        .step_without_compensation("charge_card", |ctx: &mut TripBooking| {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                if ctx.card_declines {
+                    return Err("card declined".to_string());
+                }
+                ctx.payment_id = Some("PAY-9".to_string());
+                Ok(())
+            })
+        })
+        .with_retry(RetryPolicy::exponential(2, Duration::from_millis(10)))
+        .with_timeout(Duration::from_secs(2))
+        .compensation_retry(RetryPolicy::exponential(3, Duration::from_millis(10)))
+}
+
+#[tokio::main]
+async fn main() {
+    let mut trip = TripBooking { customer: "ada".into(), ..Default::default() };
+    let report = book_trip(Arc::new(AtomicU32::new(0))).execute(&mut trip).await;
+    print!("{}", report);
+    println!("booked: {:?} {:?} {:?}\n", trip.flight_id, trip.hotel_id, trip.payment_id);
+
+    // The card is declined; the hotel cancellation fails once, then succeeds on retry.
+    let mut trip = TripBooking { customer: "ada".into(), card_declines: true, ..Default::default() };
+    let report = book_trip(Arc::new(AtomicU32::new(1))).execute(&mut trip).await;
+    print!("{}", report);
+    println!("left behind: {:?} {:?}\n", trip.flight_id, trip.hotel_id);
+
+    // The hotel API stays down: the flight is still cancelled, and the report says what's stuck.
+    let mut trip = TripBooking { customer: "ada".into(), card_declines: true, ..Default::default() };
+    let report = book_trip(Arc::new(AtomicU32::new(10))).execute(&mut trip).await;
+    print!("{}", report);
+    if report.needs_attention() {
+        println!("ALERT: {}", serde_json::to_string(&report.outcome).unwrap());
+    }
+    println!("{}", serde_json::to_string_pretty(&report.records[..2]).unwrap());
+}
+*/
@@ -0,0 +1,240 @@
+// Note: This example requires adding the `crossbeam-channel` crate to your Cargo.toml:
+// [dependencies]
+// crossbeam-channel = "0.5"
+//
+// `std::thread::scope` is part of the standard library (Rust 1.63+).
+
+use crossbeam_channel::{after, bounded, select, tick, unbounded, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Sums chunks of a slice in parallel using scoped threads.
+/// Unlike `thread::spawn`, scoped threads may borrow data from the enclosing stack frame,
+/// because the scope guarantees every thread is joined before `scope` returns.
+/// No `Arc`, no `'static` bound, no cloning of the input.
+///
+/// # Arguments
+/// * `data` - The numbers to sum (borrowed, not moved).
+/// * `num_threads` - How many threads to split the work across.
+///
+/// # Returns
+/// * `u64` - The total sum.
+fn parallel_sum_scoped(data: &[u64], num_threads: usize) -> u64 {
+    let chunk_size = data.len().div_ceil(num_threads.max(1)).max(1);
+
+    thread::scope(|s| {
+        // Each spawned closure borrows its own chunk of `data`.
+        let handles: Vec<_> = data
+            .chunks(chunk_size)
+            .map(|chunk| s.spawn(move || chunk.iter().sum::<u64>()))
+            .collect();
+
+        // Join inside the scope to collect results.
+        // (Threads not joined explicitly are joined automatically at the end of the scope.)
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("worker thread panicked"))
+            .sum()
+    })
+}
+
+/// Mutates disjoint parts of a buffer from several threads at once.
+/// `chunks_mut` hands out non-overlapping `&mut` slices, so no locking is needed.
+fn parallel_fill_scoped(buffer: &mut [u32], num_threads: usize) {
+    let chunk_size = buffer.len().div_ceil(num_threads.max(1)).max(1);
+
+    thread::scope(|s| {
+        for (i, chunk) in buffer.chunks_mut(chunk_size).enumerate() {
+            s.spawn(move || {
+                for slot in chunk.iter_mut() {
+                    *slot = i as u32;
+                }
+            });
+        }
+    }); // All threads are joined here; `buffer` is usable again afterwards.
+}
+
+/// Demonstrates a bounded crossbeam channel (backpressure) feeding a pool of workers.
+/// Crossbeam channels are MPMC: the receiver can be cloned, so several workers
+/// can pull from the same queue (std's `mpsc::Receiver` cannot be cloned).
+///
+/// # Arguments
+/// * `jobs` - Work items to process.
+/// * `num_workers` - Number of consumer threads; at least one is started.
+/// * `capacity` - Channel capacity; producers block when the channel is full.
+///
+/// # Returns
+/// * `Vec<u64>` - Results, in completion order (not input order).
+fn bounded_worker_pool(jobs: Vec<u64>, num_workers: usize, capacity: usize) -> Vec<u64> {
+    let (job_tx, job_rx) = bounded::<u64>(capacity);
+    let (result_tx, result_rx) = unbounded::<u64>();
+
+    thread::scope(|s| {
+        for worker_id in 0..num_workers.max(1) {
+            let job_rx = job_rx.clone();
+            let result_tx = result_tx.clone();
+            s.spawn(move || {
+                // `iter()` ends once all senders are dropped and the channel is drained.
+                for job in job_rx.iter() {
+                    thread::sleep(Duration::from_millis(5)); // Simulate work
+                    println!("Worker {} processed job {}", worker_id, job);
+                    // Ignore send errors: the receiver only disappears if the caller panicked.
+                    let _ = result_tx.send(job * job);
+                }
+            });
+        }
+        // Drop the originals: `result_rx` then closes when the workers finish, and `send`
+        // below fails instead of blocking forever if every worker has panicked.
+        drop(job_rx);
+        drop(result_tx);
+
+        // Produce jobs from the current thread; `send` blocks while the channel is full.
+        for job in jobs {
+            job_tx.send(job).expect("all workers exited unexpectedly");
+        }
+        // Dropping the sender signals "no more jobs" to the workers.
+        drop(job_tx);
+
+        result_rx.iter().collect()
+    })
+}
+
+/// Events produced by `select_over_receivers`.
+#[derive(Debug, PartialEq)]
+enum SelectEvent {
+    Message(String),
+    Control(u32),
+    Tick,
+    TimedOut,
+}
+
+/// Waits on several receivers at once using `select!`, with a periodic tick and an overall timeout.
+/// Whichever channel becomes ready first wins; if none is ready before the deadline,
+/// the `after` channel fires and the loop stops.
+///
+/// # Arguments
+/// * `messages` - Receiver for data messages.
+/// * `control` - Receiver for control codes (code `0` means "stop").
+/// * `timeout` - Total time to listen before giving up.
+///
+/// # Returns
+/// * `Vec<SelectEvent>` - Every event observed, in order.
+fn select_over_receivers(
+    messages: Receiver<String>,
+    control: Receiver<u32>,
+    timeout: Duration,
+) -> Vec<SelectEvent> {
+    let deadline = after(timeout); // Fires once after `timeout`
+    let ticker = tick(Duration::from_millis(100)); // Fires every 100 ms
+    let mut events = Vec::new();
+    let mut messages_open = true;
+
+    loop {
+        select! {
+            recv(messages) -> msg => match msg {
+                Ok(text) => events.push(SelectEvent::Message(text)),
+                // Disconnected: stop polling it (handled after the `select!`).
+                Err(_) => messages_open = false,
+            },
+            recv(control) -> code => match code {
+                Ok(0) | Err(_) => break,
+                Ok(code) => events.push(SelectEvent::Control(code)),
+            },
+            recv(ticker) -> _ => events.push(SelectEvent::Tick),
+            recv(deadline) -> _ => {
+                events.push(SelectEvent::TimedOut);
+                break;
+            },
+        }
+
+        if !messages_open {
+            // A disconnected receiver is always "ready", which would spin the loop.
+            // Continue with control/timeout only.
+            return events_until_control_or_timeout(events, control, deadline);
+        }
+    }
+    events
+}
+
+/// Helper used once the message channel closes: keep waiting on the remaining receivers.
+fn events_until_control_or_timeout(
+    mut events: Vec<SelectEvent>,
+    control: Receiver<u32>,
+    deadline: Receiver<Instant>,
+) -> Vec<SelectEvent> {
+    loop {
+        select! {
+            recv(control) -> code => match code {
+                Ok(0) | Err(_) => break,
+                Ok(code) => events.push(SelectEvent::Control(code)),
+            },
+            recv(deadline) -> _ => {
+                events.push(SelectEvent::TimedOut);
+                break;
+            },
+        }
+    }
+    events
+}
+
+/// Receives a single value, giving up after `timeout`.
+/// Distinguishes "nothing arrived in time" from "all senders are gone".
+fn recv_with_timeout<T>(rx: &Receiver<T>, timeout: Duration) -> Result<T, RecvTimeoutError> {
+    rx.recv_timeout(timeout)
+}
+
+/// Sends without blocking; returns the value back if the bounded channel is full.
+fn try_send_or_return<T>(tx: &Sender<T>, value: T) -> Result<(), T> {
+    tx.try_send(value).map_err(|e| e.into_inner())
+}
+
+// Example Usage (within a main function or test)
+/*
+fn main() {
+    println!("--- Scoped threads borrowing stack data ---");
+    let numbers: Vec<u64> = (1..=1_000).collect();
+    let total = parallel_sum_scoped(&numbers, 4);
+    println!("Sum = {} (numbers is still usable: len {})", total, numbers.len());
+
+    let mut buffer = vec![0u32; 10];
+    parallel_fill_scoped(&mut buffer, 3);
+    println!("Filled buffer: {:?}", buffer);
+
+    println!("\n--- Bounded channel worker pool ---");
+    let results = bounded_worker_pool((1..=10).collect(), 3, 2);
+    println!("Results (completion order): {:?}", results);
+
+    println!("\n--- select! over multiple receivers ---");
+    let (msg_tx, msg_rx) = unbounded::<String>();
+    let (ctl_tx, ctl_rx) = bounded::<u32>(1);
+    let producer = thread::spawn(move || {
+        for i in 0..3 {
+            msg_tx.send(format!("message {}", i)).unwrap();
+            thread::sleep(Duration::from_millis(150));
+        }
+        ctl_tx.send(42).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        ctl_tx.send(0).unwrap(); // Stop
+    });
+    let events = select_over_receivers(msg_rx, ctl_rx, Duration::from_secs(2));
+    producer.join().unwrap();
+    for event in &events {
+        println!("{:?}", event);
+    }
+
+    println!("\n--- Receive with timeout ---");
+    let (_tx, rx) = bounded::<i32>(1);
+    match recv_with_timeout(&rx, Duration::from_millis(50)) {
+        Ok(v) => println!("Got {}", v),
+        Err(RecvTimeoutError::Timeout) => println!("Nothing arrived within 50ms"),
+        Err(RecvTimeoutError::Disconnected) => println!("All senders dropped"),
+    }
+
+    println!("\n--- Non-blocking send on a full channel ---");
+    let (tx, _rx) = bounded::<&str>(1);
+    try_send_or_return(&tx, "first").unwrap();
+    if let Err(rejected) = try_send_or_return(&tx, "second") {
+        println!("Channel full, got back: {}", rejected);
+    }
+}
+*/
@@ -0,0 +1,596 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// reqwest = { version = "0.12", features = ["blocking"] }
+// scraper = "0.27"
+// thiserror = "1.0"
+// url = "2"
+//
+// Polite HTML scraping on top of the blocking client from `http_get_request.rs`: fetch a
+// page, query it with CSS selectors, turn repeated elements into structs, and follow "next"
+// links, while keeping to what the site's robots.txt allows and how fast it allows it.
+// * `Page` wraps a parsed document and its URL. Relative links are resolved against the
+//   page (or its `<base href>`), so `Fields::url` always returns an absolute `Url`.
+// * `FromElement` is the extraction hook: implement it for a struct and `Page::extract` maps
+//   every element matching an item selector to one. `Fields` gives it required and optional
+//   text, attributes, URLs, and numbers relative to that element, and a missing required field
+//   is an error that names the selector rather than a silent empty string.
+// * `RobotsTxt` follows RFC 9309: the group for our product token (or `*`), longest match
+//   wins, `Allow` wins ties, `*` and `$` wildcards. A missing robots.txt (4xx) allows
+//   everything; a server error (5xx) disallows everything until the next run.
+// * Requests to the same host are spaced by `min_delay` or the site's `Crawl-delay`,
+//   whichever is longer, and a `429`/`503` with `Retry-After` pushes the next request back.
+//   Hosts are tracked independently, so scraping two sites doesn't slow either down.
+// * `Scraper::pages` follows pagination links, stopping at `max_pages` or when a link points
+//   back at a page it has already seen.
+//
+// To test a scraper offline, serve fixtures with `MockServer` from `test_http_server.rs`.
+// For building URLs with query parameters, see `url_utils.rs`.
+
+use reqwest::blocking::Client;
+use reqwest::header::{RETRY_AFTER, USER_AGENT};
+use scraper::{ElementRef, Html, Selector};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use url::Url;
+
+#[derive(Debug, Error)]
+enum ScrapeError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("{url} returned HTTP {status}")]
+    Status { url: Url, status: u16 },
+    #[error("invalid URL: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("invalid CSS selector {selector:?}: {message}")]
+    Selector { selector: String, message: String },
+    #[error("robots.txt disallows {0}")]
+    Disallowed(Url),
+    #[error("no element matches {selector:?}")]
+    Missing { selector: String },
+    #[error("cannot parse {value:?} from {selector:?}: {message}")]
+    Parse { selector: String, value: String, message: String },
+}
+
+type Result<T> = std::result::Result<T, ScrapeError>;
+
+fn selector(css: &str) -> Result<Selector> {
+    Selector::parse(css).map_err(|err| ScrapeError::Selector { selector: css.to_string(), message: err.to_string() })
+}
+
+/// Joins an element's text nodes and collapses runs of whitespace, the way a browser shows it.
+fn element_text(element: ElementRef<'_>) -> String {
+    element.text().flat_map(str::split_whitespace).collect::<Vec<_>>().join(" ")
+}
+
+// --- Pages and fields ---
+
+/// A fetched, parsed HTML document.
+struct Page {
+    url: Url,
+    status: u16,
+    html: Html,
+}
+
+impl Page {
+    fn parse(url: Url, body: &str) -> Self {
+        Self { url, status: 200, html: Html::parse_document(body) }
+    }
+
+    /// The URL relative links resolve against: `<base href>` if present, else the page URL.
+    fn base(&self) -> Url {
+        let base = Selector::parse("base[href]").expect("static selector");
+        self.html
+            .select(&base)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .and_then(|href| self.url.join(href).ok())
+            .unwrap_or_else(|| self.url.clone())
+    }
+
+    /// Every element matching `css`, in document order.
+    fn select(&self, css: &str) -> Result<Vec<ElementRef<'_>>> {
+        Ok(self.html.select(&selector(css)?).collect())
+    }
+
+    /// Field access relative to the whole document, for one-off values like a page title.
+    fn fields(&self) -> Fields<'_> {
+        Fields { element: self.html.root_element(), base: self.base() }
+    }
+
+    /// Builds a `T` from every element matching `item_css`.
+    ///
+    /// # Returns
+    ///
+    /// One `T` per matching element, or the first extraction error.
+    fn extract<T: FromElement>(&self, item_css: &str) -> Result<Vec<T>> {
+        let base = self.base();
+        self.html
+            .select(&selector(item_css)?)
+            .map(|element| T::from_element(&Fields { element, base: base.clone() }))
+            .collect()
+    }
+
+    /// The absolute URL in the `href` of the first element matching `css`, if any. Used for
+    /// "next page" links such as `a[rel=next]` or `li.next a`.
+    fn link(&self, css: &str) -> Result<Option<Url>> {
+        self.fields().url_opt(css, "href")
+    }
+}
+
+/// Builds a value from one matched element. Implement this for each record type.
+trait FromElement: Sized {
+    fn from_element(fields: &Fields<'_>) -> Result<Self>;
+}
+
+/// Lookups relative to one element. A selector of `""` means the element itself, so
+/// `fields.attr("", "data-id")` reads the item's own attribute.
+struct Fields<'a> {
+    element: ElementRef<'a>,
+    base: Url,
+}
+
+impl<'a> Fields<'a> {
+    fn first(&self, css: &str) -> Result<Option<ElementRef<'a>>> {
+        if css.is_empty() {
+            return Ok(Some(self.element));
+        }
+        Ok(self.element.select(&selector(css)?).next())
+    }
+
+    fn require(&self, css: &str) -> Result<ElementRef<'a>> {
+        self.first(css)?.ok_or_else(|| ScrapeError::Missing { selector: css.to_string() })
+    }
+
+    /// The whitespace-collapsed text of the first match.
+    fn text(&self, css: &str) -> Result<String> {
+        Ok(element_text(self.require(css)?))
+    }
+
+    /// Like `text`, but `None` when nothing matches or the text is empty.
+    fn text_opt(&self, css: &str) -> Result<Option<String>> {
+        Ok(self.first(css)?.map(element_text).filter(|t| !t.is_empty()))
+    }
+
+    /// The text of every match, e.g. all the tags on a card.
+    fn texts(&self, css: &str) -> Result<Vec<String>> {
+        Ok(self.element.select(&selector(css)?).map(element_text).collect())
+    }
+
+    fn attr(&self, css: &str, name: &str) -> Result<String> {
+        self.attr_opt(css, name)?
+            .ok_or_else(|| ScrapeError::Missing { selector: format!("{} [{}]", css, name) })
+    }
+
+    fn attr_opt(&self, css: &str, name: &str) -> Result<Option<String>> {
+        Ok(self.first(css)?.and_then(|el| el.value().attr(name)).map(|v| v.trim().to_string()))
+    }
+
+    /// An attribute (usually `href` or `src`) resolved to an absolute URL.
+    fn url(&self, css: &str, name: &str) -> Result<Url> {
+        Ok(self.base.join(&self.attr(css, name)?)?)
+    }
+
+    fn url_opt(&self, css: &str, name: &str) -> Result<Option<Url>> {
+        Ok(self.attr_opt(css, name)?.map(|href| self.base.join(&href)).transpose()?)
+    }
+
+    /// The text of the first match parsed with `FromStr`.
+    fn parse<T>(&self, css: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let value = self.text(css)?;
+        value.parse().map_err(|err: T::Err| ScrapeError::Parse {
+            selector: css.to_string(),
+            value: value.clone(),
+            message: err.to_string(),
+        })
+    }
+
+    /// A number from text that carries units or formatting, such as `£1,299.00`,
+    /// `4.5 out of 5`, or `(12 reviews)`: the first run of digits, `.` and `-` wins, and
+    /// thousands separators are dropped.
+    fn number(&self, css: &str) -> Result<f64> {
+        let value = self.text(css)?;
+        let start = value.find(|c: char| c.is_ascii_digit() || c == '-' || c == '.');
+        let digits: String = start
+            .map(|i| &value[i..])
+            .unwrap_or_default()
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | ','))
+            .filter(|&c| c != ',')
+            .collect();
+        digits.parse().map_err(|err: std::num::ParseFloatError| ScrapeError::Parse {
+            selector: css.to_string(),
+            value: value.clone(),
+            message: err.to_string(),
+        })
+    }
+}
+
+// --- robots.txt ---
+
+/// The rules from a robots.txt that apply to one user agent.
+#[derive(Debug, Clone, Default)]
+struct RobotsTxt {
+    /// `(allow, pattern)` pairs.
+    rules: Vec<(bool, String)>,
+    crawl_delay: Option<Duration>,
+    sitemaps: Vec<String>,
+}
+
+impl RobotsTxt {
+    fn allow_all() -> Self {
+        Self::default()
+    }
+
+    fn disallow_all() -> Self {
+        Self { rules: vec![(false, "/".to_string())], ..Self::default() }
+    }
+
+    /// Parses `text` and keeps the group for `user_agent`'s product token (`MyBot` in
+    /// `MyBot/1.0 (+https://example.com/bot)`), falling back to the `*` group.
+    fn parse(text: &str, user_agent: &str) -> Self {
+        let token = user_agent.split(['/', ' ']).next().unwrap_or_default().to_ascii_lowercase();
+        let mut specific = RobotsTxt::default();
+        let mut wildcard = RobotsTxt::default();
+        let mut found_specific = false;
+        let mut sitemaps = Vec::new();
+
+        // The agents of the group being read, and whether its rules have started (a
+        // `user-agent` line after a rule starts a new group).
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+            match key.as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        agents.clear();
+                        in_rules = false;
+                    }
+                    agents.push(value.to_ascii_lowercase());
+                }
+                "allow" | "disallow" | "crawl-delay" => {
+                    in_rules = true;
+                    let mut targets = Vec::new();
+                    if agents.contains(&token) {
+                        found_specific = true;
+                        targets.push(&mut specific);
+                    } else if agents.iter().any(|a| a == "*") {
+                        targets.push(&mut wildcard);
+                    }
+                    for group in targets {
+                        if key == "crawl-delay" {
+                            // Negative, NaN, infinite, and overflowing values are ignored.
+                            if let Some(delay) = value.parse().ok().and_then(|s| Duration::try_from_secs_f64(s).ok()) {
+                                group.crawl_delay = Some(delay);
+                            }
+                        } else if !value.is_empty() {
+                            // An empty `Disallow:` means "nothing is disallowed".
+                            group.rules.push((key == "allow", value.to_string()));
+                        }
+                    }
+                }
+                "sitemap" => sitemaps.push(value.to_string()),
+                _ => {}
+            }
+        }
+
+        let mut robots = if found_specific { specific } else { wildcard };
+        robots.sitemaps = sitemaps;
+        robots
+    }
+
+    /// Whether `url`'s path and query may be fetched.
+    fn is_allowed(&self, url: &Url) -> bool {
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if path == "/robots.txt" {
+            return true;
+        }
+        let mut best: Option<(usize, bool)> = None;
+        for (allow, pattern) in &self.rules {
+            if robots_match(pattern, &path) {
+                let candidate = (pattern.len(), *allow);
+                if best.is_none_or(|b| candidate > b) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        best.is_none_or(|(_, allow)| allow)
+    }
+}
+
+/// robots.txt path matching: a prefix match where `*` matches any run of characters and a
+/// trailing `$` anchors the end.
+fn robots_match(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or_default()) else { return false };
+    let parts: Vec<&str> = parts.collect();
+    if parts.is_empty() {
+        return !anchored || rest.is_empty();
+    }
+    for (i, part) in parts.iter().enumerate() {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+// --- Scraper ---
+
+/// A polite fetcher: one robots.txt per origin, one request schedule per host.
+struct Scraper {
+    client: Client,
+    user_agent: String,
+    min_delay: Duration,
+    respect_robots: bool,
+    max_pages: usize,
+    robots: HashMap<String, RobotsTxt>,
+    next_allowed: HashMap<String, Instant>,
+}
+
+impl Scraper {
+    /// # Arguments
+    ///
+    /// * `user_agent` - Sent with every request and matched against robots.txt groups.
+    ///   Identify yourself, e.g. `"PriceBot/1.0 (+https://example.com/bot)"`.
+    fn new(user_agent: &str) -> Result<Self> {
+        let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+        Ok(Self {
+            client,
+            user_agent: user_agent.to_string(),
+            min_delay: Duration::from_secs(1),
+            respect_robots: true,
+            max_pages: 50,
+            robots: HashMap::new(),
+            next_allowed: HashMap::new(),
+        })
+    }
+
+    /// The least time between two requests to the same host (default 1s). A longer
+    /// `Crawl-delay` in robots.txt takes precedence.
+    fn min_delay(mut self, delay: Duration) -> Self {
+        self.min_delay = delay;
+        self
+    }
+
+    /// Skips robots.txt, for sites you own or have permission to crawl.
+    fn ignore_robots(mut self) -> Self {
+        self.respect_robots = false;
+        self
+    }
+
+    /// The most pages `pages` will follow (default 50).
+    fn max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// Fetches and parses `url`, waiting for the host's turn and checking robots.txt first.
+    ///
+    /// # Returns
+    ///
+    /// The page, `ScrapeError::Disallowed` if robots.txt forbids it, or
+    /// `ScrapeError::Status` for a non-2xx response.
+    fn fetch(&mut self, url: &str) -> Result<Page> {
+        self.fetch_url(Url::parse(url)?)
+    }
+
+    fn fetch_url(&mut self, url: Url) -> Result<Page> {
+        let delay = if self.respect_robots {
+            let robots = self.robots_for(&url)?;
+            if !robots.is_allowed(&url) {
+                return Err(ScrapeError::Disallowed(url));
+            }
+            robots.crawl_delay.unwrap_or_default().max(self.min_delay)
+        } else {
+            self.min_delay
+        };
+        let response = self.get(&url, delay)?;
+        let status = response.status().as_u16();
+        if !response.status().is_success() {
+            return Err(ScrapeError::Status { url, status });
+        }
+        // Redirects change the URL that relative links resolve against.
+        let final_url = response.url().clone();
+        let body = response.text()?;
+        Ok(Page { status, ..Page::parse(final_url, &body) })
+    }
+
+    /// Iterates over `start` and every page reached by following `next_css` links.
+    fn pages(&mut self, start: &str, next_css: &str) -> Result<Pages<'_>> {
+        Ok(Pages {
+            next: Some(Url::parse(start)?),
+            next_css: next_css.to_string(),
+            seen: HashSet::new(),
+            remaining: self.max_pages,
+            scraper: self,
+        })
+    }
+
+    /// Extracts `item_css` items from every page in a paginated listing.
+    ///
+    /// # Returns
+    ///
+    /// All items in page order, or the first fetch or extraction error.
+    fn scrape_all<T: FromElement>(&mut self, start: &str, item_css: &str, next_css: &str) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        for page in self.pages(start, next_css)? {
+            items.extend(page?.extract::<T>(item_css)?);
+        }
+        Ok(items)
+    }
+
+    fn robots_for(&mut self, url: &Url) -> Result<&RobotsTxt> {
+        let origin = url.origin().ascii_serialization();
+        if !self.robots.contains_key(&origin) {
+            let robots_url = url.join("/robots.txt")?;
+            let response = self.get(&robots_url, self.min_delay)?;
+            let status = response.status();
+            let robots = if status.is_success() {
+                RobotsTxt::parse(&response.text()?, &self.user_agent)
+            } else if status.is_server_error() {
+                RobotsTxt::disallow_all()
+            } else {
+                RobotsTxt::allow_all()
+            };
+            self.robots.insert(origin.clone(), robots);
+        }
+        Ok(&self.robots[&origin])
+    }
+
+    /// Sends a GET once the host's next slot arrives, then books the following slot `delay`
+    /// later (or at `Retry-After`, if the server asked for longer).
+    fn get(&mut self, url: &Url, delay: Duration) -> Result<reqwest::blocking::Response> {
+        let host = url.host_str().unwrap_or_default().to_string();
+        if let Some(&at) = self.next_allowed.get(&host) {
+            let now = Instant::now();
+            if at > now {
+                thread::sleep(at - now);
+            }
+        }
+        let response = self.client.get(url.clone()).header(USER_AGENT, &self.user_agent).send();
+        let mut next = Instant::now() + delay;
+        if let Ok(response) = &response {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok());
+            if let Some(seconds) = retry_after.filter(|_| matches!(response.status().as_u16(), 429 | 503)) {
+                next = next.max(Instant::now() + Duration::from_secs(seconds));
+            }
+        }
+        self.next_allowed.insert(host, next);
+        Ok(response?)
+    }
+}
+
+/// Pages of a paginated listing; see `Scraper::pages`.
+struct Pages<'s> {
+    scraper: &'s mut Scraper,
+    next: Option<Url>,
+    next_css: String,
+    seen: HashSet<Url>,
+    remaining: usize,
+}
+
+impl Iterator for Pages<'_> {
+    type Item = Result<Page>;
+
+    fn next(&mut self) -> Option<Result<Page>> {
+        let url = self.next.take()?;
+        if self.remaining == 0 || !self.seen.insert(url.clone()) {
+            return None;
+        }
+        self.remaining -= 1;
+        let page = match self.scraper.fetch_url(url) {
+            Ok(page) => page,
+            Err(err) => return Some(Err(err)),
+        };
+        match page.link(&self.next_css) {
+            Ok(next) => self.next = next,
+            Err(err) => return Some(Err(err)),
+        }
+        Some(Ok(page))
+    }
+}
+
+// Example Usage
+/*
+#[derive(Debug)]
+struct Book {
+    title: String,
+    url: Url,
+    price: f64,
+    rating: Option<String>,
+    in_stock: bool,
+}
+
+impl FromElement for Book {
+    fn from_element(fields: &Fields<'_>) -> Result<Self> {
+        Ok(Book {
+            title: fields.attr("h3 a", "title")?,
+            url: fields.url("h3 a", "href")?,
+            price: fields.number(".price_color")?,
+            // `<p class="star-rating Three">`: the rating is the second class.
+            rating: fields.attr_opt(".star-rating", "class")?.and_then(|c| c.split_whitespace().nth(1).map(String::from)),
+            in_stock: fields.text_opt(".availability")?.is_some_and(|t| t.contains("In stock")),
+        })
+    }
+}
+
+fn main() -> Result<()> {
+    // Offline: parse a fixture and extract typed records.
+    let html = r#"
+        <html><head><base href="/catalogue/"><title>All products | Books</title></head><body>
+          <article class="product_pod">
+            <h3><a href="a-light-in-the-attic_1000/index.html" title="A Light in the Attic">A Light in the ...</a></h3>
+            <p class="star-rating Three"></p>
+            <p class="price_color">£51.77</p>
+            <p class="instock availability"> In stock </p>
+          </article>
+          <article class="product_pod">
+            <h3><a href="tipping-the-velvet_999/index.html" title="Tipping the Velvet">Tipping the ...</a></h3>
+            <p class="price_color">£1,053.74</p>
+            <p class="availability">Out of stock</p>
+          </article>
+          <ul class="pager"><li class="next"><a href="page-2.html">next</a></li></ul>
+        </body></html>
+    "#;
+    let page = Page::parse(Url::parse("https://books.example/index.html")?, html);
+    println!("title: {}", page.fields().text("title")?);
+    for book in page.extract::<Book>("article.product_pod")? {
+        println!("{} | {} | {:.2} | {:?} | in stock: {}", book.title, book.url, book.price, book.rating, book.in_stock);
+    }
+    println!("next page: {:?}", page.link("li.next a")?.map(|u| u.to_string()));
+    match page.extract::<Book>("ul.pager") {
+        Err(err) => println!("wrong item selector: {}", err),
+        Ok(books) => println!("unexpected: {:?}", books),
+    }
+
+    // robots.txt rules for our agent. BookBot has its own group, so the `*` group's
+    // `Disallow: /` doesn't apply to it.
+    let robots = RobotsTxt::parse(
+        "User-agent: *\nDisallow: /\n\nUser-agent: BookBot\nUser-agent: OtherBot\nAllow: /catalogue/\nDisallow: /catalogue/page-*?sort=\nDisallow: *.pdf$\nCrawl-delay: 2\n\nSitemap: https://books.example/sitemap.xml",
+        "BookBot/1.0 (+https://example.com/bot)",
+    );
+    for path in ["/catalogue/page-2.html", "/catalogue/page-2.html?sort=price", "/guide.pdf", "/admin/"] {
+        println!("{:<38} allowed={}", path, robots.is_allowed(&Url::parse("https://books.example")?.join(path)?));
+    }
+    println!("crawl delay {:?}, sitemaps {:?}", robots.crawl_delay, robots.sitemaps);
+
+    // Online: a public sandbox built for scraping practice. Two pages, one second apart.
+    let mut scraper = Scraper::new("BookBot/1.0 (+https://example.com/bot)")?
+        .min_delay(Duration::from_secs(1))
+        .max_pages(2);
+    match scraper.scrape_all::<Book>("https://books.toscrape.com/", "article.product_pod", "li.next a") {
+        Ok(books) => {
+            println!("scraped {} books; first: {:?}", books.len(), books.first().map(|b| &b.title));
+        }
+        Err(err) => println!("live scrape skipped: {}", err),
+    }
+    Ok(())
+}
+*/
@@ -0,0 +1,274 @@
+// Note: This example requires adding the `rhai`, `serde_json`, and `thiserror` crates to your
+// Cargo.toml:
+// [dependencies]
+// rhai = { version = "1", features = ["sync", "serde"] } # "sync" makes the engine Send + Sync
+// serde_json = "1.0"
+// thiserror = "1.0"
+//
+// Embedding Rhai, a small scripting language written in Rust, so users can customize a
+// program (filters, transforms, validation and pricing rules) without recompiling it. Rhai
+// scripts can only call what the host registers: no file system, network, or process
+// access unless you add functions for them.
+//
+// `ScriptHost` wraps an `Engine` with:
+// * limits - operation count, wall-clock timeout, call depth, and string/array/map sizes, so
+//   a runaway or hostile script fails with an error instead of hanging the host;
+// * a JSON bridge - `serde_json::Value` in and out, so scripts work directly on the
+//   `Record`s from `etl.rs` or any API payload;
+// * captured output - `print` and `debug` go into the run's result, not stdout.
+
+use rhai::{Dynamic, Engine, EvalAltResult, Position, Scope, AST};
+use serde_json::{Map, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("Syntax error: {message}")]
+    Parse { message: String, line: Option<usize> },
+    #[error("Script error: {message}")]
+    Runtime { message: String, line: Option<usize> },
+    #[error("Script exceeded its time limit of {0:?}")]
+    Timeout(Duration),
+    #[error("Script exceeded its limit of {0} operations")]
+    TooManyOperations(u64),
+    #[error("Cannot convert value: {0}")]
+    Conversion(String),
+}
+
+type Result<T> = std::result::Result<T, ScriptError>;
+
+/// Resource limits applied to every run.
+#[derive(Debug, Clone)]
+struct ScriptLimits {
+    /// Rough unit of work (an expression, a loop iteration). 1 million runs in ~10-50 ms.
+    max_operations: u64,
+    /// Wall-clock limit per run, checked between operations.
+    timeout: Duration,
+    max_call_depth: usize,
+    max_string_size: usize,
+    max_array_size: usize,
+    max_map_size: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 1_000_000,
+            timeout: Duration::from_secs(1),
+            max_call_depth: 32,
+            max_string_size: 1 << 20,
+            max_array_size: 100_000,
+            max_map_size: 10_000,
+        }
+    }
+}
+
+/// The result of one run: the returned value plus everything the script printed.
+#[derive(Debug, Clone, PartialEq)]
+struct ScriptOutput {
+    value: Value,
+    printed: Vec<String>,
+}
+
+/// A script compiled once, to run many times (e.g. once per record).
+#[derive(Debug, Clone)]
+struct Script {
+    name: String,
+    ast: AST,
+}
+
+/// An engine with limits, a JSON bridge, and output capture.
+struct ScriptHost {
+    engine: Engine,
+    limits: ScriptLimits,
+    started: Arc<Mutex<Instant>>,
+    printed: Arc<Mutex<Vec<String>>>,
+}
+
+impl ScriptHost {
+    /// Creates a host with `limits`. Scripts also get `parse_json(text)` and `to_json(value)`.
+    fn new(limits: ScriptLimits) -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(limits.max_operations)
+            .set_max_call_levels(limits.max_call_depth)
+            .set_max_string_size(limits.max_string_size)
+            .set_max_array_size(limits.max_array_size)
+            .set_max_map_size(limits.max_map_size);
+
+        let started = Arc::new(Mutex::new(Instant::now()));
+        let timeout = limits.timeout;
+        let clock = Arc::clone(&started);
+        // Called every operation; returning `Some` stops the script with `ErrorTerminated`.
+        engine.on_progress(move |_| {
+            let started = *clock.lock().unwrap_or_else(|e| e.into_inner());
+            (started.elapsed() > timeout).then(|| Dynamic::from("timeout"))
+        });
+
+        let printed = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&printed);
+        engine.on_print(move |text| sink.lock().unwrap_or_else(|e| e.into_inner()).push(text.to_string()));
+        let sink = Arc::clone(&printed);
+        engine.on_debug(move |text, _source, position| {
+            let line = position.line().map_or(String::new(), |l| format!(" line {}", l));
+            sink.lock().unwrap_or_else(|e| e.into_inner()).push(format!("[debug{}] {}", line, text));
+        });
+
+        engine.register_fn("parse_json", |text: &str| -> std::result::Result<Dynamic, Box<EvalAltResult>> {
+            let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+            rhai::serde::to_dynamic(value)
+        });
+        engine.register_fn("to_json", |value: Dynamic| -> std::result::Result<String, Box<EvalAltResult>> {
+            let value: Value = rhai::serde::from_dynamic(&value)?;
+            Ok(value.to_string())
+        });
+
+        Self { engine, limits, started, printed }
+    }
+
+    /// The underlying engine, to register the host's own functions and types.
+    fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    /// Parses `source` without running it, so syntax errors surface at load time.
+    fn compile(&self, name: &str, source: &str) -> Result<Script> {
+        let ast = self.engine.compile(source).map_err(|e| ScriptError::Parse {
+            message: format!("{}: {}", name, e),
+            line: e.1.line(),
+        })?;
+        Ok(Script { name: name.to_string(), ast })
+    }
+
+    /// Runs the script's top-level statements with `vars` defined as variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `script` - A compiled script.
+    /// * `vars` - Variables visible to the script, e.g. `{"order": {...}, "threshold": 100}`.
+    ///
+    /// # Returns
+    ///
+    /// The value of the last statement (as JSON) and the printed lines.
+    fn run(&mut self, script: &Script, vars: &Map<String, Value>) -> Result<ScriptOutput> {
+        let mut scope = Scope::new();
+        for (name, value) in vars {
+            scope.push_dynamic(name.clone(), to_dynamic(value)?);
+        }
+        self.start();
+        let result = self.engine.eval_ast_with_scope::<Dynamic>(&mut scope, &script.ast);
+        self.finish(script, result)
+    }
+
+    /// Calls the function `function` defined in the script with one JSON argument, e.g. a
+    /// `fn transform(record) { ... }` applied to each record of a pipeline.
+    fn call(&mut self, script: &Script, function: &str, arg: &Value) -> Result<ScriptOutput> {
+        let arg = to_dynamic(arg)?;
+        self.start();
+        let result = self.engine.call_fn::<Dynamic>(&mut Scope::new(), &script.ast, function, (arg,));
+        self.finish(script, result)
+    }
+
+    fn start(&self) {
+        *self.started.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+        self.printed.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    fn finish(&self, script: &Script, result: std::result::Result<Dynamic, Box<EvalAltResult>>) -> Result<ScriptOutput> {
+        let value = result.map_err(|e| self.convert_error(script, *e))?;
+        let value: Value = rhai::serde::from_dynamic(&value).map_err(|e| ScriptError::Conversion(e.to_string()))?;
+        let printed = std::mem::take(&mut *self.printed.lock().unwrap_or_else(|e| e.into_inner()));
+        Ok(ScriptOutput { value, printed })
+    }
+
+    fn convert_error(&self, script: &Script, error: EvalAltResult) -> ScriptError {
+        match error {
+            EvalAltResult::ErrorTerminated(..) => ScriptError::Timeout(self.limits.timeout),
+            EvalAltResult::ErrorTooManyOperations(..) => ScriptError::TooManyOperations(self.limits.max_operations),
+            other => {
+                let line = Some(other.position()).filter(|p| *p != Position::NONE).and_then(|p| p.line());
+                ScriptError::Runtime { message: format!("{}: {}", script.name, other), line }
+            }
+        }
+    }
+}
+
+fn to_dynamic(value: &Value) -> Result<Dynamic> {
+    rhai::serde::to_dynamic(value).map_err(|e| ScriptError::Conversion(e.to_string()))
+}
+
+// Example Usage
+/*
+use serde_json::json;
+
+/// A host type exposed to scripts with getters and a method.
+#[derive(Debug, Clone)]
+struct Customer {
+    name: String,
+    tier: String,
+}
+
+impl Customer {
+    fn is_vip(&mut self) -> bool {
+        self.tier == "gold"
+    }
+}
+
+fn main() -> Result<()> {
+    let mut host = ScriptHost::new(ScriptLimits { max_operations: 100_000, ..ScriptLimits::default() });
+
+    // Register host functions and types before compiling scripts that use them.
+    host.engine_mut()
+        .register_fn("tax_rate", |country: &str| match country {
+            "DE" => 0.19,
+            "US" => 0.07,
+            _ => 0.0,
+        })
+        .register_type_with_name::<Customer>("Customer")
+        .register_get("name", |c: &mut Customer| c.name.clone())
+        .register_fn("is_vip", Customer::is_vip)
+        .register_fn("customer", |name: &str, tier: &str| Customer { name: name.into(), tier: tier.into() });
+
+    // A user-supplied transform, applied per record like an ETL step.
+    let transform = host.compile(
+        "pricing.rhai",
+        r#"
+        fn transform(order) {
+            let c = customer(order.customer, order.tier);
+            let discount = if c.is_vip() { 0.1 } else { 0.0 };
+            order.total = order.subtotal * (1.0 - discount) * (1.0 + tax_rate(order.country));
+            if order.total > 1000.0 { print(`large order for ${c.name}`); }
+            order
+        }
+        "#,
+    )?;
+    for order in [
+        json!({"customer": "Ada", "tier": "gold", "country": "DE", "subtotal": 1200.0}),
+        json!({"customer": "Bob", "tier": "free", "country": "US", "subtotal": 40.0}),
+    ] {
+        let out = host.call(&transform, "transform", &order)?;
+        println!("{} -> total {:.2} {:?}", out.value["customer"], out.value["total"].as_f64().unwrap(), out.printed);
+    }
+
+    // Top-level scripts see variables and can use the JSON helpers.
+    let rule = host.compile("rule.rhai", r#"let cfg = parse_json(config); debug(cfg.limit); amount <= cfg.limit"#)?;
+    let vars = json!({"amount": 250, "config": r#"{"limit": 500}"#});
+    println!("rule: {:?}", host.run(&rule, vars.as_object().unwrap())?);
+
+    // Errors: syntax, runtime (with line), runaway loops, and timeouts.
+    println!("{}", host.compile("bad.rhai", "let x = ;").unwrap_err());
+    let failing = host.compile("fail.rhai", "let a = 1;\nlet b = a.missing_method();")?;
+    let err = host.run(&failing, &Map::new()).unwrap_err();
+    println!("{} (line {:?})", err, match &err { ScriptError::Runtime { line, .. } => *line, _ => None });
+    let spin = host.compile("spin.rhai", "let n = 0; loop { n += 1; }")?;
+    println!("{}", host.run(&spin, &Map::new()).unwrap_err());
+
+    let mut slow_host = ScriptHost::new(ScriptLimits { max_operations: u64::MAX, timeout: Duration::from_millis(50), ..ScriptLimits::default() });
+    let spin = slow_host.compile("spin.rhai", "loop { }")?;
+    let started = Instant::now();
+    println!("{} after {:?}", slow_host.run(&spin, &Map::new()).unwrap_err(), started.elapsed());
+    Ok(())
+}
+*/
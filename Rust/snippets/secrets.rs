@@ -0,0 +1,328 @@
+// Note: This example requires adding the `zeroize`, `chacha20poly1305`, `argon2`, `keyring`,
+// `serde_json`, and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// zeroize = "1"
+// chacha20poly1305 = "0.10"
+// argon2 = "0.5"
+// keyring = { version = "3", features = ["apple-native", "windows-native", "linux-native"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+//
+// One `SecretProvider` trait with three backends: environment variables (containers, CI),
+// an encrypted file (secrets checked into a repo or shipped with a deployment), and the OS
+// keyring (desktop tools and CLIs). `ChainProvider` tries them in order.
+
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("Secret '{0}' not found in any provider")]
+    NotFound(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Secrets file is not in the expected format: {0}")]
+    Format(String),
+    #[error("Could not decrypt secrets file (wrong passphrase or file was modified)")]
+    Decrypt,
+    #[error("Key derivation failed: {0}")]
+    Kdf(argon2::Error),
+    #[error("Keyring error: {0}")]
+    Keyring(#[from] keyring::Error),
+}
+
+type Result<T> = std::result::Result<T, SecretError>;
+
+// --- SecretString ---
+
+/// A string that is wiped from memory on drop and never printed.
+///
+/// `Debug` and `Display` both print `[REDACTED]`, so a secret that ends up in a log line,
+/// an error message, or a `{:?}` of a config struct does not leak. Call `expose()` only at
+/// the point where the value is actually used (e.g. building a connection string).
+#[derive(Clone, Default)]
+struct SecretString(String);
+
+impl SecretString {
+    fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+// --- Provider trait ---
+
+/// A source of secrets looked up by a logical key such as `"db_password"`.
+trait SecretProvider: Send + Sync {
+    /// Short name for diagnostics ("env", "file", "keyring").
+    fn name(&self) -> &str;
+
+    /// Returns `Ok(None)` if this provider does not have the key; `Err` only for real failures.
+    fn get(&self, key: &str) -> Result<Option<SecretString>>;
+}
+
+/// Reads `<PREFIX>_<KEY>` from the environment, e.g. `db_password` -> `MYAPP_DB_PASSWORD`.
+struct EnvProvider {
+    prefix: String,
+}
+
+impl EnvProvider {
+    fn new(prefix: &str) -> Self {
+        Self { prefix: prefix.to_string() }
+    }
+
+    fn var_name(&self, key: &str) -> String {
+        format!("{}_{}", self.prefix, key).to_uppercase().replace(['-', '.'], "_")
+    }
+}
+
+impl SecretProvider for EnvProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<SecretString>> {
+        Ok(std::env::var(self.var_name(key)).ok().map(SecretString::from))
+    }
+}
+
+// --- Encrypted file ---
+//
+// Layout: MAGIC (8) | salt (16) | nonce (12) | ChaCha20-Poly1305 ciphertext of a JSON object.
+// The key is derived from a passphrase with Argon2id; the header is authenticated as
+// associated data, so any modification of the file fails decryption.
+
+const MAGIC: &[u8; 8] = b"SECRETS1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+fn derive_key(passphrase: &SecretString, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(passphrase.expose().as_bytes(), salt, key.as_mut())
+        .map_err(SecretError::Kdf)?;
+    Ok(key)
+}
+
+/// Secrets decrypted from a file into memory.
+struct EncryptedFileProvider {
+    secrets: HashMap<String, SecretString>,
+}
+
+impl EncryptedFileProvider {
+    /// Decrypts `path` with `passphrase`.
+    ///
+    /// # Returns
+    ///
+    /// `SecretError::Decrypt` for a wrong passphrase or a tampered file (indistinguishable by design).
+    fn open(path: &Path, passphrase: &SecretString) -> Result<Self> {
+        let data = fs::read(path)?;
+        let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+        if data.len() < header_len || &data[..MAGIC.len()] != MAGIC {
+            return Err(SecretError::Format("missing header".to_string()));
+        }
+        let (header, ciphertext) = data.split_at(header_len);
+        let salt = &header[MAGIC.len()..MAGIC.len() + SALT_LEN];
+        let nonce = Nonce::from_slice(&header[MAGIC.len() + SALT_LEN..]);
+
+        let key = derive_key(passphrase, salt)?;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+        let plaintext = Zeroizing::new(
+            cipher
+                .decrypt(nonce, Payload { msg: ciphertext, aad: header })
+                .map_err(|_| SecretError::Decrypt)?,
+        );
+        let map: HashMap<String, String> =
+            serde_json::from_slice(&plaintext).map_err(|e| SecretError::Format(e.to_string()))?;
+        Ok(Self {
+            secrets: map.into_iter().map(|(k, v)| (k, SecretString::from(v))).collect(),
+        })
+    }
+
+    /// Encrypts `secrets` to `path` with a fresh salt and nonce, replacing the file atomically.
+    fn save(path: &Path, passphrase: &SecretString, secrets: &HashMap<String, SecretString>) -> Result<()> {
+        let plain_map: HashMap<&str, &str> = secrets.iter().map(|(k, v)| (k.as_str(), v.expose())).collect();
+        let plaintext = Zeroizing::new(serde_json::to_vec(&plain_map).map_err(|e| SecretError::Format(e.to_string()))?);
+
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let mut header = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&nonce);
+
+        let key = derive_key(passphrase, &salt)?;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_ref()));
+        let ciphertext = cipher
+            .encrypt(&nonce, Payload { msg: &plaintext, aad: &header })
+            .map_err(|_| SecretError::Format("encryption failed".to_string()))?;
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, [header, ciphertext].concat())?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+impl SecretProvider for EncryptedFileProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<SecretString>> {
+        Ok(self.secrets.get(key).cloned())
+    }
+}
+
+// --- OS keyring ---
+
+/// macOS Keychain, Windows Credential Manager, or the Linux kernel keyring.
+struct KeyringProvider {
+    service: String,
+}
+
+impl KeyringProvider {
+    fn new(service: &str) -> Self {
+        Self { service: service.to_string() }
+    }
+
+    /// Stores a secret (e.g. from a `mytool login` command).
+    fn set(&self, key: &str, value: &SecretString) -> Result<()> {
+        keyring::Entry::new(&self.service, key)?.set_password(value.expose())?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        match keyring::Entry::new(&self.service, key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl SecretProvider for KeyringProvider {
+    fn name(&self) -> &str {
+        "keyring"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<SecretString>> {
+        match keyring::Entry::new(&self.service, key)?.get_password() {
+            Ok(value) => Ok(Some(SecretString::from(value))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+// --- Chain ---
+
+/// Tries providers in order; the first one that has the key wins.
+struct ChainProvider {
+    providers: Vec<Box<dyn SecretProvider>>,
+}
+
+impl ChainProvider {
+    fn new(providers: Vec<Box<dyn SecretProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// Like `get`, but a missing secret is an error. Returns which provider supplied it.
+    fn require(&self, key: &str) -> Result<(SecretString, &str)> {
+        for provider in &self.providers {
+            if let Some(secret) = provider.get(key)? {
+                return Ok((secret, provider.name()));
+            }
+        }
+        Err(SecretError::NotFound(key.to_string()))
+    }
+}
+
+impl SecretProvider for ChainProvider {
+    fn name(&self) -> &str {
+        "chain"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<SecretString>> {
+        for provider in &self.providers {
+            if let Some(secret) = provider.get(key)? {
+                return Ok(Some(secret));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    let passphrase = SecretString::new("correct horse battery staple");
+    let path = std::env::temp_dir().join("app-secrets.enc");
+
+    // Create the encrypted file (normally done once by an admin tool).
+    let mut initial = HashMap::new();
+    initial.insert("db_password".to_string(), SecretString::new("s3cr3t-db"));
+    initial.insert("api_token".to_string(), SecretString::new("tok_from_file"));
+    EncryptedFileProvider::save(&path, &passphrase, &initial)?;
+    println!("Wrote {} ({} bytes)", path.display(), fs::metadata(&path)?.len());
+
+    // Env overrides the file; the keyring is the last resort.
+    std::env::set_var("MYAPP_API_TOKEN", "tok_from_env");
+    let secrets = ChainProvider::new(vec![
+        Box::new(EnvProvider::new("myapp")),
+        Box::new(EncryptedFileProvider::open(&path, &passphrase)?),
+        Box::new(KeyringProvider::new("myapp-example")),
+    ]);
+
+    for key in ["api_token", "db_password"] {
+        let (value, source) = secrets.require(key)?;
+        println!("{} from {}: {:?} (length {})", key, source, value, value.expose().len());
+    }
+    match secrets.require("smtp_password") {
+        Ok((_, source)) => println!("smtp_password found in {}", source),
+        Err(e) => println!("{}", e), // Not found, or the keyring is unavailable on this machine
+    }
+
+    // Wrong passphrase and tampering both fail closed.
+    let wrong = EncryptedFileProvider::open(&path, &SecretString::new("guess"));
+    println!("Wrong passphrase: {}", wrong.err().unwrap());
+
+    fs::remove_file(&path)?;
+    Ok(())
+}
+*/
@@ -0,0 +1,401 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// semver = "1"
+// thiserror = "1.0"
+//
+// Version numbers, version requirements, and release notes for self-updating binaries and
+// plugin hosts. Typical questions: "is there a newer release than mine?", "does this plugin
+// accept host 2.3.0?", "what changed between my version and the latest?", and "what should the
+// next version be?".
+// * `parse_version` accepts what release tooling actually produces: `v1.4.2` git tags, `1.4`
+//   shorthand, and full SemVer with pre-release and build metadata. Comparison follows SemVer
+//   (`1.0.0-rc.1 < 1.0.0`, build metadata ignored for precedence).
+// * `Requirement` is Cargo's requirement syntax (`^1.2`, `~1.2.3`, `>=1.0, <2`, `1.*`) plus
+//   the two npm forms plugin manifests often use: `||` alternatives and `1.2 - 1.4` ranges.
+//   As in Cargo, pre-releases only match a requirement that names a pre-release of the same
+//   version, so `^1.2` never picks up `1.3.0-beta.1` by accident.
+// * `bump` implements `npm version`'s vocabulary: major, minor, patch, premajor, preminor,
+//   prepatch, prerelease, and release. `1.2.0-rc.1` → prerelease → `1.2.0-rc.2`; → release →
+//   `1.2.0`.
+// * `latest` picks the newest version out of a list of tags, skipping anything unparseable.
+// * `parse_changelog` reads a Keep a Changelog file (`## [1.2.0] - 2024-10-01` headings with
+//   `### Added` / `### Fixed` groups). `latest_release` returns the newest non-yanked release,
+//   and `releases_between` collects the notes a self-updater shows for an upgrade.
+
+use semver::{BuildMetadata, Prerelease, Version, VersionReq};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum SemverError {
+    #[error("invalid version {input:?}: {source}")]
+    Version { input: String, source: semver::Error },
+    #[error("invalid version requirement {input:?}: {source}")]
+    Requirement { input: String, source: semver::Error },
+    #[error("cannot bump {version} to a {label:?} pre-release: it would sort before the current one")]
+    PreBackwards { version: Version, label: String },
+    #[error("unknown bump {0:?} (expected major, minor, patch, premajor, preminor, prepatch, prerelease, or release)")]
+    UnknownBump(String),
+}
+
+type Result<T> = std::result::Result<T, SemverError>;
+
+// --- Versions ---
+
+/// Parses a version leniently: a leading `v` is dropped and a missing minor or patch is zero,
+/// so `v2`, `2.1`, and `2.1.0` all work.
+fn parse_version(input: &str) -> Result<Version> {
+    let trimmed = input.trim();
+    let trimmed = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+    let core_end = trimmed.find(['-', '+']).unwrap_or(trimmed.len());
+    let (core, suffix) = trimmed.split_at(core_end);
+    let padded = match core.matches('.').count() {
+        0 => format!("{}.0.0{}", core, suffix),
+        1 => format!("{}.0{}", core, suffix),
+        _ => trimmed.to_string(),
+    };
+    Version::parse(&padded).map_err(|source| SemverError::Version { input: input.to_string(), source })
+}
+
+/// The newest version among `candidates`, ignoring strings that aren't versions (such as
+/// `nightly` tags). Pre-releases count only if `include_prerelease` is set.
+fn latest<'a, I>(candidates: I, include_prerelease: bool) -> Option<(&'a str, Version)>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    candidates
+        .into_iter()
+        .filter_map(|tag| parse_version(tag).ok().map(|v| (tag, v)))
+        .filter(|(_, v)| include_prerelease || v.pre.is_empty())
+        .max_by(|(_, a), (_, b)| a.cmp_precedence(b))
+}
+
+// --- Requirements ---
+
+/// One or more Cargo-style requirements joined by `||`; a version matches if any alternative does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Requirement {
+    alternatives: Vec<VersionReq>,
+}
+
+impl Requirement {
+    fn matches(&self, version: &Version) -> bool {
+        self.alternatives.iter().any(|req| req.matches(version))
+    }
+
+    /// The highest of `versions` that satisfies the requirement.
+    fn max_satisfying<'a>(&self, versions: impl IntoIterator<Item = &'a Version>) -> Option<&'a Version> {
+        versions.into_iter().filter(|v| self.matches(v)).max_by(|a, b| a.cmp_precedence(b))
+    }
+}
+
+impl FromStr for Requirement {
+    type Err = SemverError;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let err = |source| SemverError::Requirement { input: input.to_string(), source };
+        let alternatives = input
+            .split("||")
+            .map(|alternative| {
+                let alternative = alternative.trim();
+                // `1.2 - 1.4` is inclusive at both ends; an upper bound like `1.4` means `<1.5.0`,
+                // as in npm.
+                if let Some((low, high)) = alternative.split_once(" - ") {
+                    let (low, high) = (low.trim().trim_start_matches(['v', 'V']), high.trim());
+                    let upper = match high.trim_start_matches(['v', 'V']).matches('.').count() {
+                        0 => format!("<{}", parse_version(high)?.major + 1),
+                        1 => {
+                            let v = parse_version(high)?;
+                            format!("<{}.{}.0", v.major, v.minor + 1)
+                        }
+                        _ => format!("<={}", parse_version(high)?),
+                    };
+                    return VersionReq::parse(&format!(">={}, {}", low, upper)).map_err(err);
+                }
+                VersionReq::parse(alternative).map_err(err)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { alternatives })
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self.alternatives.iter().map(|req| req.to_string()).collect();
+        write!(f, "{}", parts.join(" || "))
+    }
+}
+
+// --- Bumping ---
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Bump {
+    Major,
+    Minor,
+    Patch,
+    /// Next major as a pre-release: `1.4.2` → `2.0.0-<label>.1`.
+    PreMajor(String),
+    PreMinor(String),
+    PrePatch(String),
+    /// The next pre-release: `2.0.0-rc.1` → `2.0.0-rc.2`, or from a release, the next patch's
+    /// first pre-release.
+    Prerelease(String),
+    /// Drops the pre-release: `2.0.0-rc.2` → `2.0.0`.
+    Release,
+}
+
+impl FromStr for Bump {
+    type Err = SemverError;
+
+    /// `major`, `minor`, `patch`, or a pre-release kind with an optional label:
+    /// `prerelease`, `preminor=beta`. The label defaults to `rc`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, label) = s.split_once('=').unwrap_or((s, "rc"));
+        let label = label.to_string();
+        Ok(match kind.to_ascii_lowercase().as_str() {
+            "major" => Bump::Major,
+            "minor" => Bump::Minor,
+            "patch" => Bump::Patch,
+            "premajor" => Bump::PreMajor(label),
+            "preminor" => Bump::PreMinor(label),
+            "prepatch" => Bump::PrePatch(label),
+            "prerelease" | "pre" => Bump::Prerelease(label),
+            "release" => Bump::Release,
+            _ => return Err(SemverError::UnknownBump(s.to_string())),
+        })
+    }
+}
+
+/// `<label>.<n>`, failing if `label` isn't a valid pre-release identifier (e.g. has spaces).
+fn pre(label: &str, n: u64) -> Result<Prerelease> {
+    let text = format!("{}.{}", label, n);
+    Prerelease::new(&text).map_err(|source| SemverError::Version { input: text, source })
+}
+
+/// Returns the version after `version` for the given kind of release. Build metadata is
+/// always dropped.
+///
+/// # Returns
+/// `SemverError::PreBackwards` when a pre-release label would sort before the current one,
+/// such as `rc` → `alpha` on the same version.
+fn bump(version: &Version, kind: &Bump) -> Result<Version> {
+    let is_pre = !version.pre.is_empty();
+    let mut next = Version::new(version.major, version.minor, version.patch);
+    match kind {
+        // A pre-release of x.0.0 is released as x.0.0 itself, as `npm version` does.
+        Bump::Major if is_pre && version.minor == 0 && version.patch == 0 => {}
+        Bump::Major => next = Version::new(version.major + 1, 0, 0),
+        Bump::Minor if is_pre && version.patch == 0 => {}
+        Bump::Minor => next = Version::new(version.major, version.minor + 1, 0),
+        Bump::Patch if is_pre => {}
+        Bump::Patch => next.patch += 1,
+        Bump::PreMajor(label) => next = Version { pre: pre(label, 1)?, ..Version::new(version.major + 1, 0, 0) },
+        Bump::PreMinor(label) => next = Version { pre: pre(label, 1)?, ..Version::new(version.major, version.minor + 1, 0) },
+        Bump::PrePatch(label) => next = Version { pre: pre(label, 1)?, ..Version::new(version.major, version.minor, version.patch + 1) },
+        Bump::Prerelease(label) if !is_pre => next = Version { pre: pre(label, 1)?, ..Version::new(version.major, version.minor, version.patch + 1) },
+        Bump::Prerelease(label) => {
+            // `rc.3` → `rc.4`; a different label restarts at 1 if it sorts later (`beta` → `rc`).
+            let counter = version.pre.as_str().strip_prefix(label.as_str()).and_then(|rest| rest.strip_prefix('.'));
+            next.pre = match counter.and_then(|n| n.parse::<u64>().ok()) {
+                Some(n) => pre(label, n + 1)?,
+                None => match pre(label, 1)? {
+                    first if first > version.pre => first,
+                    _ => return Err(SemverError::PreBackwards { version: version.clone(), label: label.clone() }),
+                },
+            };
+        }
+        Bump::Release => {}
+    }
+    next.build = BuildMetadata::EMPTY;
+    Ok(next)
+}
+
+// --- Changelogs ---
+
+/// One `## ` section of a Keep a Changelog file.
+#[derive(Debug, Clone)]
+struct Release {
+    /// `None` for the `[Unreleased]` section.
+    version: Option<Version>,
+    date: Option<String>,
+    yanked: bool,
+    /// The Markdown between this heading and the next, trimmed.
+    body: String,
+}
+
+impl Release {
+    /// Change entries grouped by their `### ` heading (Added, Changed, Fixed, ...), in file
+    /// order. Entries before any group heading are grouped under `""`.
+    fn groups(&self) -> Vec<(String, Vec<String>)> {
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        for line in self.body.lines() {
+            if let Some(heading) = line.strip_prefix("### ") {
+                groups.push((heading.trim().to_string(), Vec::new()));
+            } else if let Some(item) = line.trim_start().strip_prefix("- ").or_else(|| line.trim_start().strip_prefix("* ")) {
+                if groups.is_empty() {
+                    groups.push((String::new(), Vec::new()));
+                }
+                groups.last_mut().expect("pushed above").1.push(item.trim().to_string());
+            } else if let (Some((_, items)), false) = (groups.last_mut(), line.trim().is_empty()) {
+                // A wrapped continuation of the previous entry.
+                if let Some(last) = items.last_mut() {
+                    last.push(' ');
+                    last.push_str(line.trim());
+                }
+            }
+        }
+        groups
+    }
+}
+
+/// Splits a changelog into its `## ` sections, in file order. Headings look like
+/// `## [1.2.0] - 2024-10-01`, `## v1.2.0 (2024-10-01)`, `## [Unreleased]`, or
+/// `## [0.9.1] - 2024-06-01 [YANKED]`. Sections whose heading holds no version are skipped.
+fn parse_changelog(text: &str) -> Vec<Release> {
+    let mut releases: Vec<Release> = Vec::new();
+    let mut body: Option<String> = None;
+    for line in text.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            if let (Some(release), Some(text)) = (releases.last_mut(), body.take()) {
+                release.body = text.trim().to_string();
+            }
+            let words: Vec<&str> = heading.split_whitespace().map(|w| w.trim_matches(['[', ']', '(', ')'])).collect();
+            let unreleased = words.first().is_some_and(|w| w.eq_ignore_ascii_case("unreleased"));
+            let version = words.first().and_then(|w| parse_version(w).ok());
+            if version.is_none() && !unreleased {
+                continue;
+            }
+            let date = words.iter().find(|w| w.len() == 10 && w.as_bytes()[4] == b'-' && w.as_bytes()[7] == b'-').map(|d| d.to_string());
+            let yanked = words.iter().any(|w| w.eq_ignore_ascii_case("yanked"));
+            releases.push(Release { version, date, yanked, body: String::new() });
+            body = Some(String::new());
+        } else if let Some(text) = body.as_mut() {
+            // A link-reference footer (`[1.2.0]: https://...`) ends the last section.
+            if line.starts_with('[') && line.contains("]: ") {
+                continue;
+            }
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    if let (Some(release), Some(text)) = (releases.last_mut(), body) {
+        release.body = text.trim().to_string();
+    }
+    releases
+}
+
+/// The newest non-yanked, non-prerelease release, by version rather than file position.
+fn latest_release(releases: &[Release]) -> Option<&Release> {
+    releases
+        .iter()
+        .filter(|r| !r.yanked)
+        .filter_map(|r| r.version.as_ref().filter(|v| v.pre.is_empty()).map(|v| (v, r)))
+        .max_by(|(a, _), (b, _)| a.cmp_precedence(b))
+        .map(|(_, r)| r)
+}
+
+/// Releases newer than `current` up to and including `target`, newest first: the notes to show
+/// before a self-update from `current` to `target`.
+fn releases_between<'a>(releases: &'a [Release], current: &Version, target: &Version) -> Vec<&'a Release> {
+    let mut selected: Vec<&Release> = releases
+        .iter()
+        .filter(|r| {
+            r.version.as_ref().is_some_and(|v| {
+                v.cmp_precedence(current) == Ordering::Greater && v.cmp_precedence(target) != Ordering::Greater
+            })
+        })
+        .collect();
+    selected.sort_by(|a, b| b.version.as_ref().cmp(&a.version.as_ref()));
+    selected
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    // --- Parsing and comparing ---
+    for input in ["v1.4", "2", "1.0.0-rc.1+build.7", "1.2.3.4"] {
+        match parse_version(input) {
+            Ok(v) => println!("{:<20} -> {}", input, v),
+            Err(e) => println!("{}", e),
+        }
+    }
+    let tags = ["v1.9.0", "v1.10.0", "v2.0.0-beta.2", "nightly", "v1.10.1"];
+    println!("latest stable tag: {:?}", latest(tags, false).map(|(tag, _)| tag));
+    println!("latest including pre-releases: {:?}", latest(tags, true).map(|(tag, _)| tag));
+
+    // --- Requirements, as a plugin manifest would declare them ---
+    let available: Vec<Version> = ["1.2.0", "1.3.5", "1.4.0-beta.1", "1.4.0", "1.5.2", "2.0.0"].iter().map(|v| parse_version(v)).collect::<Result<_>>()?;
+    for req in ["^1.3", "~1.3.0", ">=1.2, <1.5", "1.2 - 1.4", "^1.3 || ^2", "=1.4.0-beta.1", "^3"] {
+        let requirement: Requirement = req.parse()?;
+        let matching: Vec<String> = available.iter().filter(|v| requirement.matches(v)).map(|v| v.to_string()).collect();
+        println!("{:<16} ({}) -> best {:?}, all {:?}", req, requirement, requirement.max_satisfying(&available).map(|v| v.to_string()), matching);
+    }
+    if let Err(e) = "^1.x.banana".parse::<Requirement>() {
+        println!("{}", e);
+    }
+
+    // --- Bumping ---
+    let steps = ["prerelease=beta", "prerelease=beta", "prerelease", "prerelease", "release", "patch", "preminor=beta", "minor", "premajor", "major"];
+    let mut version = parse_version("1.4.2")?;
+    print!("{}", version);
+    for step in steps {
+        version = bump(&version, &step.parse()?)?;
+        print!(" -{}-> {}", step, version);
+    }
+    println!();
+    let rc = parse_version("3.0.0-rc.2")?;
+    match bump(&rc, &Bump::Prerelease("alpha".into())) {
+        Err(e) => println!("{}", e),
+        Ok(v) => println!("unexpected {}", v),
+    }
+    if let Err(e) = "hotfix".parse::<Bump>() {
+        println!("{}", e);
+    }
+
+    // --- Changelog: what a self-updater shows before upgrading ---
+    let changelog = "\
+# Changelog
+
+## [Unreleased]
+### Added
+- Dark mode.
+
+## [1.5.0] - 2024-10-01
+### Added
+- `sync --dry-run` shows what would change
+  without touching the remote.
+### Fixed
+- Crash when the config directory is missing.
+
+## [1.4.1] - 2024-09-12 [YANKED]
+### Fixed
+- Broken Windows paths (reverted, see 1.5.0).
+
+## [1.4.0] - 2024-09-01
+### Changed
+- Requires Rust 1.75.
+
+[1.5.0]: https://example.com/compare/v1.4.1...v1.5.0
+";
+    let releases = parse_changelog(changelog);
+    let newest = latest_release(&releases).expect("changelog has a release");
+    println!("latest release: {} ({})", newest.version.as_ref().unwrap(), newest.date.as_deref().unwrap_or("undated"));
+    let installed = parse_version("1.4.0")?;
+    let target = newest.version.clone().unwrap();
+    if target > installed {
+        println!("update available: {} -> {}", installed, target);
+        for release in releases_between(&releases, &installed, &target) {
+            let marker = if release.yanked { " (yanked)" } else { "" };
+            println!("  {}{}", release.version.as_ref().unwrap(), marker);
+            for (group, items) in release.groups() {
+                for item in items {
+                    println!("    [{}] {}", group, item);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+*/
@@ -0,0 +1,223 @@
+// Note: This example requires adding the `tokio` crate to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] } // "sync", "time", "macros", "rt-multi-thread"
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex, RwLock};
+use tokio::time::{sleep, Duration};
+
+// --- Part 1: tokio::sync::Mutex vs tokio::sync::RwLock ---
+//
+// Rules of thumb:
+// * `std::sync::Mutex` is fine (and faster) when the lock is never held across an `.await`.
+// * `tokio::sync::Mutex` is needed when the guard must live across an `.await` point.
+// * `tokio::sync::RwLock` allows many concurrent readers OR one writer. Use it for
+//   read-heavy data (config, routing tables, caches) where writes are rare.
+// * Tokio's RwLock is fair (write-preferring): once a writer is waiting, new readers queue
+//   behind it, so a steady stream of readers cannot starve writers.
+
+/// A read-heavy registry guarded by an async `RwLock`.
+#[derive(Clone, Default)]
+struct ServiceRegistry {
+    inner: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl ServiceRegistry {
+    /// Looks up an endpoint. Many tasks can hold read guards at the same time.
+    async fn lookup(&self, name: &str) -> Option<String> {
+        let guard = self.inner.read().await;
+        guard.get(name).cloned()
+        // Read guard dropped here.
+    }
+
+    /// Registers an endpoint. Waits for all current readers to finish first.
+    async fn register(&self, name: &str, endpoint: &str) {
+        let mut guard = self.inner.write().await;
+        guard.insert(name.to_string(), endpoint.to_string());
+    }
+}
+
+/// A write-heavy counter guarded by an async `Mutex`.
+/// The guard is held across an `.await` (simulated I/O), which is only allowed with tokio's Mutex.
+#[derive(Clone, Default)]
+struct AuditLog {
+    entries: Arc<Mutex<Vec<String>>>,
+}
+
+impl AuditLog {
+    async fn append(&self, entry: String) {
+        let mut guard = self.entries.lock().await;
+        // Simulate flushing to storage while holding the lock to keep entries ordered.
+        sleep(Duration::from_millis(1)).await;
+        guard.push(entry);
+    }
+
+    async fn len(&self) -> usize {
+        self.entries.lock().await.len()
+    }
+}
+
+// --- Part 2: StateCell<T> - watch-based state broadcasting ---
+
+/// Holds the latest value of some state and lets any number of tasks
+/// read snapshots or wait for the next change.
+///
+/// Built on `tokio::sync::watch`: readers never block writers, and a slow reader
+/// simply skips intermediate values and sees the most recent one (which is exactly
+/// what you want for config/status style state).
+struct StateCell<T> {
+    tx: watch::Sender<T>,
+}
+
+/// A subscription to a `StateCell`. Cheap to clone; each clone tracks its own "seen" version.
+#[derive(Clone)]
+struct StateWatcher<T> {
+    rx: watch::Receiver<T>,
+}
+
+impl<T: Clone + Send + Sync + 'static> StateCell<T> {
+    /// Creates a new cell holding `initial`.
+    fn new(initial: T) -> Self {
+        let (tx, _rx) = watch::channel(initial);
+        Self { tx }
+    }
+
+    /// Returns a clone of the current value.
+    fn snapshot(&self) -> T {
+        self.tx.borrow().clone()
+    }
+
+    /// Replaces the value and notifies all watchers.
+    /// Works even when there are no subscribers (unlike `watch::Sender::send`).
+    fn set(&self, value: T) {
+        self.tx.send_replace(value);
+    }
+
+    /// Modifies the value in place. Watchers are only notified if `f` returns `true`.
+    fn update<F>(&self, f: F) -> bool
+    where
+        F: FnOnce(&mut T) -> bool,
+    {
+        self.tx.send_if_modified(f)
+    }
+
+    /// Creates a new watcher. Its first `changed()` call waits for the next update.
+    fn subscribe(&self) -> StateWatcher<T> {
+        StateWatcher { rx: self.tx.subscribe() }
+    }
+
+    /// Number of live watchers (useful for metrics or to skip work nobody observes).
+    fn watcher_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl<T: Clone> StateWatcher<T> {
+    /// Returns a clone of the latest value without marking it as seen.
+    fn snapshot(&self) -> T {
+        self.rx.borrow().clone()
+    }
+
+    /// Waits until the value changes, then returns the new value.
+    /// Returns `None` once the `StateCell` has been dropped.
+    async fn changed(&mut self) -> Option<T> {
+        self.rx.changed().await.ok()?;
+        Some(self.rx.borrow_and_update().clone())
+    }
+
+    /// Waits until the value satisfies `predicate` (checking the current value first).
+    /// Returns `None` if the `StateCell` is dropped before that happens.
+    async fn wait_for<F>(&mut self, predicate: F) -> Option<T>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.rx.wait_for(predicate).await.ok().map(|v| v.clone())
+    }
+}
+
+/// Example state shared across a service.
+#[derive(Clone, Debug, PartialEq)]
+enum ServiceStatus {
+    Starting,
+    Ready { version: u32 },
+    Draining,
+}
+
+// Example Usage (requires a Tokio runtime)
+/*
+#[tokio::main]
+async fn main() {
+    println!("--- RwLock for read-heavy data ---");
+    let registry = ServiceRegistry::default();
+    registry.register("users", "http://10.0.0.5:8080").await;
+    let mut readers = Vec::new();
+    for i in 0..5 {
+        let registry = registry.clone();
+        readers.push(tokio::spawn(async move {
+            // All five lookups can proceed concurrently.
+            let endpoint = registry.lookup("users").await;
+            println!("Reader {} sees {:?}", i, endpoint);
+        }));
+    }
+    for r in readers {
+        r.await.unwrap();
+    }
+
+    println!("\n--- Mutex held across .await ---");
+    let log = AuditLog::default();
+    let handles: Vec<_> = (0..10)
+        .map(|i| {
+            let log = log.clone();
+            tokio::spawn(async move { log.append(format!("event {}", i)).await })
+        })
+        .collect();
+    for h in handles {
+        h.await.unwrap();
+    }
+    println!("Audit log has {} entries", log.len().await);
+
+    println!("\n--- StateCell broadcasting ---");
+    let status = Arc::new(StateCell::new(ServiceStatus::Starting));
+
+    // A task that waits until the service is ready.
+    let mut ready_watcher = status.subscribe();
+    let waiter = tokio::spawn(async move {
+        let state = ready_watcher
+            .wait_for(|s| matches!(s, ServiceStatus::Ready { .. }))
+            .await;
+        println!("Waiter: service became {:?}", state);
+    });
+
+    // A task that logs every change it observes (may skip intermediate values if slow).
+    let mut logger = status.subscribe();
+    let logger_task = tokio::spawn(async move {
+        while let Some(state) = logger.changed().await {
+            println!("Logger: status -> {:?}", state);
+            if state == ServiceStatus::Draining {
+                break;
+            }
+        }
+    });
+
+    println!("Watchers: {}", status.watcher_count());
+    sleep(Duration::from_millis(50)).await;
+    status.set(ServiceStatus::Ready { version: 1 });
+    sleep(Duration::from_millis(50)).await;
+
+    // Bump the version in place; notify only if something actually changed.
+    let changed = status.update(|s| match s {
+        ServiceStatus::Ready { version } => {
+            *version += 1;
+            true
+        }
+        _ => false,
+    });
+    println!("Update applied: {}, snapshot: {:?}", changed, status.snapshot());
+    sleep(Duration::from_millis(50)).await;
+
+    status.set(ServiceStatus::Draining);
+    waiter.await.unwrap();
+    logger_task.await.unwrap();
+}
+*/
@@ -0,0 +1,352 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["signal", "sync", "rt-multi-thread", "macros", "time"] }
+//
+// [target.'cfg(unix)'.dependencies]
+// libc = "0.2" // Only for sending SIGTERM to children in `Children::terminate_all`
+//
+// Signals beyond Ctrl-C for long-running services, as one stream of events:
+// * `SignalEvent::Reload` (SIGHUP): re-read config without restarting.
+// * `SignalEvent::DumpStats` (SIGUSR1; Ctrl-Break on Windows): log counters or internal state.
+// * `SignalEvent::Interrupt` (SIGINT / Ctrl-C) and `SignalEvent::Terminate` (SIGTERM; console
+//   close, logoff, or shutdown on Windows): shut down gracefully.
+// * `SignalEvent::ChildExited` (SIGCHLD): one or more child processes have exited; call
+//   `Children::reap` to collect them so they don't linger as zombies.
+//
+// Windows has no equivalent of SIGHUP or SIGCHLD: `Signals::only` returns an `Unsupported`
+// error for those, and `Signals::new` listens for whatever the platform supports. On Windows,
+// reload from a file watcher or an admin endpoint and poll `Children::reap` on a timer.
+//
+// Signals of one kind that arrive close together are merged by the OS (and by this module), so
+// treat an event as "at least one happened", never as a count. The same applies to SIGCHLD:
+// one event may mean several children exited.
+//
+// `Children::reap` uses `Child::try_wait` on the children it spawned, so it doesn't steal exit
+// statuses from other code that waits on its own children (`execute_command.rs`). A process
+// running as PID 1 in a container also inherits orphaned grandchildren and must reap those with
+// `waitpid(-1)`; running under `tini` or `docker run --init` is usually simpler.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+// --- Events ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SignalEvent {
+    Reload,
+    DumpStats,
+    Interrupt,
+    Terminate,
+    ChildExited,
+}
+
+impl SignalEvent {
+    const ALL: [SignalEvent; 5] =
+        [SignalEvent::Reload, SignalEvent::DumpStats, SignalEvent::Interrupt, SignalEvent::Terminate, SignalEvent::ChildExited];
+
+    fn is_supported(self) -> bool {
+        cfg!(unix) || !matches!(self, SignalEvent::Reload | SignalEvent::ChildExited)
+    }
+
+    /// The OS-level name, for logs.
+    fn signal_name(self) -> &'static str {
+        match (self, cfg!(unix)) {
+            (SignalEvent::Reload, _) => "SIGHUP",
+            (SignalEvent::DumpStats, true) => "SIGUSR1",
+            (SignalEvent::DumpStats, false) => "CTRL_BREAK",
+            (SignalEvent::Interrupt, true) => "SIGINT",
+            (SignalEvent::Interrupt, false) => "CTRL_C",
+            (SignalEvent::Terminate, true) => "SIGTERM",
+            (SignalEvent::Terminate, false) => "CTRL_CLOSE/LOGOFF/SHUTDOWN",
+            (SignalEvent::ChildExited, _) => "SIGCHLD",
+        }
+    }
+
+    /// Interrupt and Terminate both mean "stop".
+    fn is_shutdown(self) -> bool {
+        matches!(self, SignalEvent::Interrupt | SignalEvent::Terminate)
+    }
+}
+
+impl fmt::Display for SignalEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.signal_name())
+    }
+}
+
+// --- Listening ---
+
+/// Spawns a task that forwards every `recv()` from a tokio signal stream as `event`.
+macro_rules! forward {
+    ($stream:expr, $event:expr, $tx:expr) => {{
+        let mut stream = $stream;
+        let tx = $tx.clone();
+        tokio::spawn(async move {
+            while stream.recv().await.is_some() {
+                // A full channel already holds undelivered events; dropping this one merges it
+                // with them, as the OS does.
+                if let Err(mpsc::error::TrySendError::Closed(_)) = tx.try_send($event) {
+                    break;
+                }
+            }
+        })
+    }};
+}
+
+#[cfg(unix)]
+fn listen(event: SignalEvent, tx: &mpsc::Sender<SignalEvent>) -> io::Result<Vec<JoinHandle<()>>> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let kind = match event {
+        SignalEvent::Reload => SignalKind::hangup(),
+        SignalEvent::DumpStats => SignalKind::user_defined1(),
+        SignalEvent::Interrupt => SignalKind::interrupt(),
+        SignalEvent::Terminate => SignalKind::terminate(),
+        SignalEvent::ChildExited => SignalKind::child(),
+    };
+    Ok(vec![forward!(signal(kind)?, event, tx)])
+}
+
+#[cfg(windows)]
+fn listen(event: SignalEvent, tx: &mpsc::Sender<SignalEvent>) -> io::Result<Vec<JoinHandle<()>>> {
+    use tokio::signal::windows;
+    Ok(match event {
+        SignalEvent::DumpStats => vec![forward!(windows::ctrl_break()?, event, tx)],
+        SignalEvent::Interrupt => vec![forward!(windows::ctrl_c()?, event, tx)],
+        // Windows ends the process a few seconds after these, whatever the handler does, so
+        // shutdown work must be quick.
+        SignalEvent::Terminate => vec![
+            forward!(windows::ctrl_close()?, event, tx),
+            forward!(windows::ctrl_logoff()?, event, tx),
+            forward!(windows::ctrl_shutdown()?, event, tx),
+        ],
+        SignalEvent::Reload | SignalEvent::ChildExited => {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} has no Windows equivalent", event)));
+        }
+    })
+}
+
+/// A stream of the signals a process has asked for. Dropping it stops listening, although
+/// the OS-level handlers stay installed for the life of the process (a tokio limitation), so a
+/// listened-for signal no longer kills the process with its default action.
+struct Signals {
+    rx: mpsc::Receiver<SignalEvent>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Signals {
+    /// Listens for every event this platform supports. Must be called inside a tokio runtime.
+    fn new() -> io::Result<Self> {
+        let supported: Vec<_> = SignalEvent::ALL.into_iter().filter(|e| e.is_supported()).collect();
+        Self::only(&supported)
+    }
+
+    /// Listens for just `events`; fails with `ErrorKind::Unsupported` for an event the platform
+    /// doesn't have.
+    fn only(events: &[SignalEvent]) -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel(events.len().max(1) * 4);
+        let mut tasks = Vec::new();
+        for &event in events {
+            tasks.extend(listen(event, &tx)?);
+        }
+        Ok(Signals { rx, tasks })
+    }
+
+    /// The next event; use it as a branch of `tokio::select!` in the main loop.
+    async fn recv(&mut self) -> Option<SignalEvent> {
+        self.rx.recv().await
+    }
+}
+
+impl Drop for Signals {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+// --- Child processes ---
+
+#[derive(Debug)]
+struct Exited {
+    name: String,
+    pid: u32,
+    status: ExitStatus,
+}
+
+/// Child processes spawned by a process manager, reaped when `SignalEvent::ChildExited` fires.
+#[derive(Debug, Default)]
+struct Children {
+    running: HashMap<u32, (String, Child)>,
+}
+
+impl Children {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawns `command` and tracks it under `name`.
+    fn spawn(&mut self, name: impl Into<String>, command: &mut Command) -> io::Result<u32> {
+        let child = command.spawn()?;
+        let pid = child.id();
+        self.running.insert(pid, (name.into(), child));
+        Ok(pid)
+    }
+
+    fn len(&self) -> usize {
+        self.running.len()
+    }
+
+    /// Collects every child that has exited, without blocking.
+    ///
+    /// # Returns
+    ///
+    /// The exited children, in no particular order; empty if the event was for someone else's
+    /// child or the children were already reaped.
+    fn reap(&mut self) -> Vec<Exited> {
+        let mut exited = Vec::new();
+        self.running.retain(|&pid, (name, child)| match child.try_wait() {
+            Ok(Some(status)) => {
+                exited.push(Exited { name: name.clone(), pid, status });
+                false
+            }
+            Ok(None) => true,
+            // Someone else waited on it (e.g. `waitpid(-1)` elsewhere); nothing left to reap.
+            Err(_) => false,
+        });
+        exited
+    }
+
+    /// Asks every child to stop (SIGTERM on Unix, `TerminateProcess` on Windows), waits up to
+    /// `grace` for them to exit, then kills the rest. Blocks the calling thread, which is fine
+    /// during shutdown; use `spawn_blocking` to call it while the runtime is still serving.
+    ///
+    /// # Returns
+    ///
+    /// Every child's exit, including those that had to be killed.
+    fn terminate_all(&mut self, grace: Duration) -> Vec<Exited> {
+        #[cfg(unix)]
+        for &pid in self.running.keys() {
+            // SAFETY: `kill` has no memory-safety preconditions; the pid is our own child,
+            // which stays a zombie (so the pid can't be reused) until we reap it below.
+            unsafe {
+                libc::kill(pid as libc::pid_t, libc::SIGTERM);
+            }
+        }
+        #[cfg(not(unix))]
+        for (_, child) in self.running.values_mut() {
+            let _ = child.kill();
+        }
+        let deadline = Instant::now() + grace;
+        let mut exited = self.reap();
+        while !self.running.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+            exited.extend(self.reap());
+        }
+        for (pid, (name, mut child)) in self.running.drain() {
+            let _ = child.kill();
+            if let Ok(status) = child.wait() {
+                exited.push(Exited { name, pid, status });
+            }
+        }
+        exited
+    }
+}
+
+// Example Usage
+/*
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+#[derive(Debug, Clone)]
+struct Config {
+    greeting: String,
+}
+
+fn load_config(path: &std::path::Path) -> Result<Config, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let greeting = text.strip_prefix("greeting=").ok_or("expected greeting=...")?.trim().to_string();
+    Ok(Config { greeting })
+}
+
+/// Stands in for `kill -<signal> <pid>` from an operator's shell.
+#[cfg(unix)]
+fn send_self(signal: &str) {
+    Command::new("kill").args([&format!("-{}", signal), &std::process::id().to_string()]).status().unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let config_path = std::env::temp_dir().join(format!("signals-demo-{}.conf", std::process::id()));
+    std::fs::write(&config_path, "greeting=hello\n")?;
+    let config = Arc::new(RwLock::new(load_config(&config_path).unwrap()));
+    let handled = Arc::new(AtomicU64::new(0));
+
+    let mut signals = Signals::new()?;
+    let mut children = Children::new();
+    children.spawn("quick-job", Command::new("sh").args(["-c", "sleep 0.2; exit 3"]))?;
+    children.spawn("worker", Command::new("sleep").arg("30"))?;
+
+    // An operator pokes the process from outside while it runs.
+    tokio::spawn({
+        let config_path = config_path.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(400)).await;
+            std::fs::write(&config_path, "greeting=bonjour\n").unwrap();
+            send_self("HUP");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            std::fs::write(&config_path, "oops\n").unwrap();
+            send_self("HUP");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            send_self("USR1");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            send_self("TERM");
+        }
+    });
+
+    let mut tick = tokio::time::interval(Duration::from_millis(150));
+    loop {
+        tokio::select! {
+            _ = tick.tick() => {
+                handled.fetch_add(1, Ordering::Relaxed);
+            }
+            Some(event) = signals.recv() => match event {
+                SignalEvent::Reload => match load_config(&config_path) {
+                    Ok(new) => {
+                        println!("{}: reloaded config: {:?}", event, new);
+                        *config.write().unwrap() = new;
+                    }
+                    // A bad edit must not take down a running service; keep the old config.
+                    Err(e) => println!("{}: reload failed, keeping {:?}: {}", event, config.read().unwrap(), e),
+                },
+                SignalEvent::DumpStats => {
+                    println!("{}: {} ticks handled, {} children running", event, handled.load(Ordering::Relaxed), children.len());
+                }
+                SignalEvent::ChildExited => {
+                    for exited in children.reap() {
+                        println!("{}: {} (pid {}) exited with {}", event, exited.name, exited.pid, exited.status);
+                    }
+                }
+                event if event.is_shutdown() => {
+                    println!("{}: shutting down with {} child(ren) running", event, children.len());
+                    break;
+                }
+                _ => {}
+            },
+        }
+    }
+
+    for exited in children.terminate_all(Duration::from_secs(2)) {
+        println!("stopped {} (pid {}): {}", exited.name, exited.pid, exited.status);
+    }
+    println!("final greeting: {}", config.read().unwrap().greeting);
+    std::fs::remove_file(&config_path)?;
+    Ok(())
+}
+*/
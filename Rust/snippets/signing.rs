@@ -0,0 +1,191 @@
+// Note: This example requires adding the `hmac`, `sha2`, `hex`, `base64`, and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// hmac = "0.12"
+// sha2 = "0.10"
+// hex = "0.4"
+// base64 = "0.22"
+// thiserror = "1.0"
+//
+// HMAC-SHA256 signatures for webhooks and signed API requests. The sender and receiver share
+// a secret; the receiver recomputes the MAC over the *raw* request body (before any JSON
+// parsing) and compares it in constant time.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SignatureError {
+    #[error("Signature is not valid {0}")]
+    InvalidEncoding(&'static str),
+    #[error("Malformed signature header: {0}")]
+    MalformedHeader(String),
+    #[error("Signature does not match")]
+    Mismatch,
+    #[error("Timestamp is {0:?} away from now, outside the allowed tolerance")]
+    OutsideTolerance(Duration),
+}
+
+type Result<T> = std::result::Result<T, SignatureError>;
+
+fn mac(secret: &[u8]) -> HmacSha256 {
+    // HMAC accepts keys of any length, so this cannot fail.
+    HmacSha256::new_from_slice(secret).expect("HMAC accepts any key length")
+}
+
+/// Computes the raw HMAC-SHA256 of `payload`.
+fn sign_payload(secret: &[u8], payload: &[u8]) -> [u8; 32] {
+    let mut mac = mac(secret);
+    mac.update(payload);
+    mac.finalize().into_bytes().into()
+}
+
+/// Verifies a raw signature in constant time.
+///
+/// Never compare signatures with `==`: it returns at the first differing byte, which lets an
+/// attacker recover a valid signature one byte at a time from response timings.
+fn verify_payload(secret: &[u8], payload: &[u8], signature: &[u8]) -> Result<()> {
+    let mut mac = mac(secret);
+    mac.update(payload);
+    mac.verify_slice(signature).map_err(|_| SignatureError::Mismatch)
+}
+
+/// Lowercase hex signature, e.g. for `X-Signature: 5d41...`.
+fn sign_hex(secret: &[u8], payload: &[u8]) -> String {
+    hex::encode(sign_payload(secret, payload))
+}
+
+fn verify_hex(secret: &[u8], payload: &[u8], signature_hex: &str) -> Result<()> {
+    let signature = hex::decode(signature_hex.trim()).map_err(|_| SignatureError::InvalidEncoding("hex"))?;
+    verify_payload(secret, payload, &signature)
+}
+
+/// Standard base64 signature (Shopify webhooks use this encoding, for example).
+fn sign_base64(secret: &[u8], payload: &[u8]) -> String {
+    BASE64.encode(sign_payload(secret, payload))
+}
+
+fn verify_base64(secret: &[u8], payload: &[u8], signature_b64: &str) -> Result<()> {
+    let signature = BASE64.decode(signature_b64.trim()).map_err(|_| SignatureError::InvalidEncoding("base64"))?;
+    verify_payload(secret, payload, &signature)
+}
+
+// --- GitHub style: `X-Hub-Signature-256: sha256=<hex>` ---
+
+/// Builds the `X-Hub-Signature-256` header value for `body`.
+fn github_signature(secret: &[u8], body: &[u8]) -> String {
+    format!("sha256={}", sign_hex(secret, body))
+}
+
+/// Verifies an `X-Hub-Signature-256` header against the raw request body.
+fn verify_github(secret: &[u8], body: &[u8], header: &str) -> Result<()> {
+    let hex_sig = header
+        .strip_prefix("sha256=")
+        .ok_or_else(|| SignatureError::MalformedHeader("expected 'sha256=<hex>'".to_string()))?;
+    verify_hex(secret, body, hex_sig)
+}
+
+// --- Stripe style: `Stripe-Signature: t=<unix>,v1=<hex>[,v1=<hex>...]` ---
+//
+// The timestamp is part of the signed content (`"<t>.<body>"`), so a captured request can
+// only be replayed within the tolerance window. Several `v1` entries allow secret rotation.
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Builds a timestamped signature header for `body`.
+///
+/// # Arguments
+///
+/// * `secrets` - One signature is emitted per secret (send old + new while rotating).
+/// * `timestamp` - Unix seconds; normally `unix_now()`.
+fn timestamped_signature(secrets: &[&[u8]], body: &[u8], timestamp: u64) -> String {
+    let signed = [timestamp.to_string().as_bytes(), b".", body].concat();
+    let mut header = format!("t={}", timestamp);
+    for secret in secrets {
+        header.push_str(",v1=");
+        header.push_str(&sign_hex(secret, &signed));
+    }
+    header
+}
+
+/// Verifies a timestamped signature header.
+///
+/// # Arguments
+///
+/// * `secret` - The receiver's current secret.
+/// * `body` - Raw request body.
+/// * `header` - e.g. `t=1700000000,v1=5257a869...`.
+/// * `tolerance` - Maximum allowed clock difference (Stripe uses 5 minutes).
+/// * `now` - Current Unix seconds (a parameter so tests can pin the clock).
+///
+/// # Returns
+///
+/// `Ok(())` if any `v1` signature matches and the timestamp is fresh.
+fn verify_timestamped(secret: &[u8], body: &[u8], header: &str, tolerance: Duration, now: u64) -> Result<()> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => {
+                timestamp = Some(value.parse::<u64>().map_err(|_| SignatureError::MalformedHeader(format!("bad timestamp '{}'", value)))?)
+            }
+            Some(("v1", value)) => signatures.push(value),
+            Some(_) => {} // Unknown schemes (e.g. v0) are ignored
+            None => return Err(SignatureError::MalformedHeader(format!("bad element '{}'", part))),
+        }
+    }
+    let timestamp = timestamp.ok_or_else(|| SignatureError::MalformedHeader("missing t=".to_string()))?;
+    if signatures.is_empty() {
+        return Err(SignatureError::MalformedHeader("missing v1=".to_string()));
+    }
+
+    // Check the MAC before the clock so a forged request can't probe our tolerance.
+    let signed = [timestamp.to_string().as_bytes(), b".", body].concat();
+    if !signatures.iter().any(|sig| verify_hex(secret, &signed, sig).is_ok()) {
+        return Err(SignatureError::Mismatch);
+    }
+    let skew = Duration::from_secs(now.abs_diff(timestamp));
+    if skew > tolerance {
+        return Err(SignatureError::OutsideTolerance(skew));
+    }
+    Ok(())
+}
+
+// Example Usage
+/*
+fn main() {
+    let secret = b"whsec_test_secret";
+    let body = br#"{"event":"invoice.paid","id":"evt_123"}"#;
+
+    // Plain signatures in both encodings.
+    let hex_sig = sign_hex(secret, body);
+    let b64_sig = sign_base64(secret, body);
+    println!("hex:    {}", hex_sig);
+    println!("base64: {}", b64_sig);
+    println!("hex ok: {:?}, base64 ok: {:?}", verify_hex(secret, body, &hex_sig), verify_base64(secret, body, &b64_sig));
+    println!("tampered: {:?}", verify_hex(secret, b"{\"event\":\"invoice.void\"}", &hex_sig));
+
+    // GitHub webhooks.
+    let header = github_signature(secret, body);
+    println!("\nX-Hub-Signature-256: {}", header);
+    println!("verify: {:?}", verify_github(secret, body, &header));
+    println!("wrong secret: {:?}", verify_github(b"other", body, &header));
+
+    // Timestamped (Stripe-style), sent during a secret rotation.
+    let now = unix_now();
+    let header = timestamped_signature(&[b"old_secret", secret], body, now);
+    println!("\nSignature header: {}", header);
+    let tolerance = Duration::from_secs(300);
+    println!("new secret: {:?}", verify_timestamped(secret, body, &header, tolerance, now + 10));
+    println!("old secret: {:?}", verify_timestamped(b"old_secret", body, &header, tolerance, now + 10));
+    println!("replayed an hour later: {:?}", verify_timestamped(secret, body, &header, tolerance, now + 3600));
+    println!("garbage: {:?}", verify_timestamped(secret, body, "v1=abc", tolerance, now));
+}
+*/
@@ -0,0 +1,240 @@
+// Note: This example requires adding the `tokio` crate to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio::time::{Duration, Instant};
+
+/// State kept per key.
+enum Slot<V, E> {
+    /// A call is running; followers subscribe to receive its result.
+    InFlight(broadcast::Sender<Result<V, E>>),
+    /// A successful result cached until `expires_at`.
+    Cached { value: V, expires_at: Instant },
+}
+
+/// Coalesces concurrent calls for the same key into a single execution.
+///
+/// The first caller for a key (the "leader") runs the fetch; callers arriving while it is
+/// in flight ("followers") wait for and share its result. With a TTL, successful results are
+/// also cached so calls within the TTL return immediately. Errors are shared with the
+/// followers of that flight but never cached.
+///
+/// Typical use: in front of HTTP lookups, DNS resolution, or token refreshes, where a burst
+/// of identical requests would otherwise hit the backend N times ("thundering herd").
+struct SingleFlight<K, V, E> {
+    slots: Arc<Mutex<HashMap<K, Slot<V, E>>>>,
+    ttl: Option<Duration>,
+}
+
+impl<K, V, E> Clone for SingleFlight<K, V, E> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: Arc::clone(&self.slots),
+            ttl: self.ttl,
+        }
+    }
+}
+
+/// Removes the in-flight marker if the leader's future is dropped before finishing
+/// (e.g. the caller timed out). Followers then see a closed channel and retry.
+struct LeaderGuard<'a, K: Hash + Eq, V, E> {
+    slots: &'a Mutex<HashMap<K, Slot<V, E>>>,
+    key: Option<K>,
+}
+
+impl<K: Hash + Eq, V, E> Drop for LeaderGuard<'_, K, V, E> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let mut slots = self.slots.lock().unwrap();
+            if matches!(slots.get(&key), Some(Slot::InFlight(_))) {
+                slots.remove(&key);
+            }
+        }
+    }
+}
+
+/// What a caller should do after inspecting the slot for its key.
+enum Role<V, E> {
+    Cached(V),
+    Follower(broadcast::Receiver<Result<V, E>>),
+    Leader,
+}
+
+impl<K, V, E> SingleFlight<K, V, E>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    E: Clone,
+{
+    /// Creates a coalescer without result caching (only concurrent calls are merged).
+    fn new() -> Self {
+        Self {
+            slots: Arc::new(Mutex::new(HashMap::new())),
+            ttl: None,
+        }
+    }
+
+    /// Creates a coalescer that also caches successful results for `ttl`.
+    fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            slots: Arc::new(Mutex::new(HashMap::new())),
+            ttl: Some(ttl),
+        }
+    }
+
+    /// Returns the cached value for `key`, joins an in-flight call, or runs `fetch`.
+    ///
+    /// # Arguments
+    /// * `key` - Identifies the request (e.g. a URL or hostname).
+    /// * `fetch` - Produces the future to run if this caller becomes the leader.
+    ///   It may be called more than once per key only if a previous leader was cancelled.
+    ///
+    /// # Returns
+    /// * `Result<V, E>` - The shared result.
+    async fn get<F, Fut>(&self, key: K, fetch: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        let mut fetch = Some(fetch);
+        loop {
+            match self.role_for(&key) {
+                Role::Cached(value) => return Ok(value),
+                Role::Follower(mut rx) => match rx.recv().await {
+                    Ok(result) => return result,
+                    // Leader was cancelled before producing a value: try again
+                    // (we may become the new leader).
+                    Err(_) => continue,
+                },
+                Role::Leader => {
+                    let fetch = fetch.take().expect("leader role is only taken once per call");
+                    return self.lead(key, fetch).await;
+                }
+            }
+        }
+    }
+
+    /// Inspects (and if necessary claims) the slot for `key` under the lock.
+    fn role_for(&self, key: &K) -> Role<V, E> {
+        let mut slots = self.slots.lock().unwrap();
+        match slots.get(key) {
+            Some(Slot::Cached { value, expires_at }) if *expires_at > Instant::now() => {
+                return Role::Cached(value.clone());
+            }
+            Some(Slot::InFlight(tx)) => return Role::Follower(tx.subscribe()),
+            _ => {}
+        }
+        // Missing or expired: claim leadership.
+        let (tx, _) = broadcast::channel(1);
+        slots.insert(key.clone(), Slot::InFlight(tx));
+        Role::Leader
+    }
+
+    /// Runs the fetch as leader and publishes the result to followers.
+    async fn lead<F, Fut>(&self, key: K, fetch: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        let mut guard = LeaderGuard {
+            slots: &self.slots,
+            key: Some(key.clone()),
+        };
+
+        let result = fetch().await;
+
+        // Publish under the lock so no follower can subscribe after the send.
+        let mut slots = self.slots.lock().unwrap();
+        let previous = match (&result, self.ttl) {
+            (Ok(value), Some(ttl)) => slots.insert(
+                key,
+                Slot::Cached {
+                    value: value.clone(),
+                    expires_at: Instant::now() + ttl,
+                },
+            ),
+            _ => slots.remove(&key),
+        };
+        if let Some(Slot::InFlight(tx)) = previous {
+            // An error here only means there were no followers.
+            let _ = tx.send(result.clone());
+        }
+        guard.key = None; // Completed normally; nothing to clean up.
+        result
+    }
+
+    /// Drops any cached value for `key` (in-flight calls are unaffected).
+    fn invalidate(&self, key: &K) {
+        let mut slots = self.slots.lock().unwrap();
+        if matches!(slots.get(key), Some(Slot::Cached { .. })) {
+            slots.remove(key);
+        }
+    }
+
+    /// Removes expired cache entries. Call periodically if keys are unbounded.
+    fn purge_expired(&self) {
+        let now = Instant::now();
+        self.slots.lock().unwrap().retain(|_, slot| match slot {
+            Slot::Cached { expires_at, .. } => *expires_at > now,
+            Slot::InFlight(_) => true,
+        });
+    }
+}
+
+/// Simulates an expensive lookup (e.g. DNS or an HTTP call) and counts invocations.
+async fn slow_lookup(host: String, calls: Arc<std::sync::atomic::AtomicUsize>) -> Result<String, String> {
+    calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    if host.ends_with(".invalid") {
+        return Err(format!("cannot resolve {}", host));
+    }
+    Ok(format!("{} -> 10.0.0.{}", host, host.len()))
+}
+
+// Example Usage (requires a Tokio runtime)
+/*
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[tokio::main]
+async fn main() {
+    let flights: SingleFlight<String, String, String> = SingleFlight::with_ttl(Duration::from_secs(30));
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    println!("--- 10 concurrent callers, 1 backend call ---");
+    let mut handles = Vec::new();
+    for i in 0..10 {
+        let flights = flights.clone();
+        let calls = calls.clone();
+        handles.push(tokio::spawn(async move {
+            let host = "api.example.com".to_string();
+            let result = flights.get(host.clone(), || slow_lookup(host, calls)).await;
+            println!("Caller {} got {:?}", i, result);
+        }));
+    }
+    for h in handles {
+        h.await.unwrap();
+    }
+    println!("Backend calls so far: {}", calls.load(Ordering::SeqCst)); // 1
+
+    println!("\n--- Within the TTL the cached value is returned ---");
+    let host = "api.example.com".to_string();
+    let cached = flights.get(host.clone(), || slow_lookup(host, calls.clone())).await;
+    println!("Cached: {:?}, backend calls: {}", cached, calls.load(Ordering::SeqCst)); // still 1
+
+    println!("\n--- Errors are shared but not cached ---");
+    for _ in 0..2 {
+        let host = "broken.invalid".to_string();
+        let result = flights.get(host.clone(), || slow_lookup(host, calls.clone())).await;
+        println!("{:?}", result);
+    }
+    println!("Backend calls: {}", calls.load(Ordering::SeqCst)); // 3
+
+    flights.invalidate(&"api.example.com".to_string());
+    flights.purge_expired();
+}
+*/
@@ -0,0 +1,427 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// rusqlite = { version = "0.32", features = ["bundled"] } // "bundled" compiles SQLite in; no system lib needed
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, Connection, OpenFlags, Transaction};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Errors produced by the storage helpers.
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("Row (de)serialization failed: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("Invalid SQL identifier: {0:?}")]
+    InvalidIdentifier(String),
+
+    #[error("Expected a struct that serializes to a JSON object")]
+    NotAnObject,
+
+    #[error("Nothing to update: the record has no columns besides `id`")]
+    NoColumnsToUpdate,
+}
+
+type Result<T> = std::result::Result<T, StoreError>;
+
+// --- Connection setup ---
+
+/// Opens (or creates) a database file with sensible defaults for application use.
+///
+/// * WAL journal mode: readers don't block the writer (and vice versa).
+/// * `foreign_keys = ON`: SQLite ignores FK constraints unless asked.
+/// * busy timeout: wait for locks instead of failing immediately with `SQLITE_BUSY`.
+fn open_database(path: impl AsRef<Path>) -> Result<Connection> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE | OpenFlags::SQLITE_OPEN_URI,
+    )?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?; // Safe with WAL, much faster than FULL
+    Ok(conn)
+}
+
+/// In-memory database, handy for tests.
+fn open_in_memory() -> Result<Connection> {
+    let conn = Connection::open_in_memory()?;
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+    Ok(conn)
+}
+
+// --- Embedded migrations ---
+
+/// Migrations compiled into the binary. Append new entries; never edit old ones.
+/// In a real project you might use `include_str!("../migrations/0001_init.sql")`.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE users (
+        id         INTEGER PRIMARY KEY AUTOINCREMENT,
+        email      TEXT NOT NULL UNIQUE,
+        name       TEXT NOT NULL,
+        age        INTEGER,
+        created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+    );",
+    // 2: add a status column and an index
+    "ALTER TABLE users ADD COLUMN status TEXT NOT NULL DEFAULT 'active';
+     CREATE INDEX idx_users_status ON users(status);",
+];
+
+/// Applies pending migrations, tracking progress in SQLite's built-in `user_version` pragma.
+/// Each migration runs in its own transaction, so a failure leaves the schema at the last
+/// successfully applied version.
+///
+/// # Returns
+/// * `Result<Vec<u32>>` - The versions applied by this call, oldest first (empty if the
+///   schema was already current).
+fn run_migrations(conn: &mut Connection, migrations: &[&str]) -> Result<Vec<u32>> {
+    let current: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    let mut applied = Vec::new();
+    for (index, sql) in migrations.iter().enumerate().skip(current as usize) {
+        let version = index as u32 + 1;
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+        applied.push(version);
+    }
+    Ok(applied)
+}
+
+// --- Row <-> serde mapping ---
+
+/// Only allow plain identifiers for table/column names, since they can't be bound as parameters.
+fn check_identifier(name: &str) -> Result<&str> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    if valid {
+        Ok(name)
+    } else {
+        Err(StoreError::InvalidIdentifier(name.to_string()))
+    }
+}
+
+fn json_to_sql(value: JsonValue) -> SqlValue {
+    match value {
+        JsonValue::Null => SqlValue::Null,
+        JsonValue::Bool(b) => SqlValue::Integer(b as i64),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        JsonValue::String(s) => SqlValue::Text(s),
+        // Nested data is stored as JSON text (query it with SQLite's json_extract).
+        other @ (JsonValue::Array(_) | JsonValue::Object(_)) => SqlValue::Text(other.to_string()),
+    }
+}
+
+fn sql_to_json(value: ValueRef<'_>) -> JsonValue {
+    match value {
+        ValueRef::Null => JsonValue::Null,
+        ValueRef::Integer(i) => JsonValue::from(i),
+        ValueRef::Real(f) => JsonValue::from(f),
+        // Always a string, even if it looks like JSON; see `json_text` for nested fields.
+        ValueRef::Text(bytes) => JsonValue::String(String::from_utf8_lossy(bytes).into_owned()),
+        ValueRef::Blob(bytes) => JsonValue::from(bytes.to_vec()),
+    }
+}
+
+/// Reads a field that `json_to_sql` stored as JSON text (a `Vec`, map, or nested struct).
+/// Mark the field with `#[serde(deserialize_with = "json_text")]`; unmarked TEXT columns stay
+/// strings, so a name like `"[draft]"` is never reparsed.
+fn json_text<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let text = String::deserialize(deserializer)?;
+    serde_json::from_str(&text).map_err(serde::de::Error::custom)
+}
+
+/// Serializes a struct into (column names, values).
+fn to_columns<T: Serialize>(record: &T) -> Result<(Vec<String>, Vec<SqlValue>)> {
+    let JsonValue::Object(map) = serde_json::to_value(record)? else {
+        return Err(StoreError::NotAnObject);
+    };
+    let mut columns = Vec::with_capacity(map.len());
+    let mut values = Vec::with_capacity(map.len());
+    for (column, value) in map {
+        check_identifier(&column)?;
+        values.push(json_to_sql(value));
+        columns.push(column);
+    }
+    Ok((columns, values))
+}
+
+/// Runs a query and deserializes every row into `T` by column name.
+/// Booleans stored as 0/1 are not converted automatically; use `u8`/`i64` or a
+/// `#[serde(deserialize_with = ...)]` helper for them. The same goes for nested values,
+/// which need `json_text`.
+fn query_as<T: DeserializeOwned>(conn: &Connection, sql: &str, params: &[SqlValue]) -> Result<Vec<T>> {
+    let mut stmt = conn.prepare(sql)?;
+    let names: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+    let mut rows = stmt.query(params_from_iter(params.iter()))?;
+    let mut out = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut object = Map::with_capacity(names.len());
+        for (i, name) in names.iter().enumerate() {
+            object.insert(name.clone(), sql_to_json(row.get_ref(i)?));
+        }
+        out.push(serde_json::from_value(JsonValue::Object(object))?);
+    }
+    Ok(out)
+}
+
+// --- CRUD helpers ---
+
+/// Inserts a record; fields named in `skip` (e.g. an auto-increment `id`) are left out.
+/// Returns the new rowid.
+fn insert<T: Serialize>(conn: &Connection, table: &str, record: &T, skip: &[&str]) -> Result<i64> {
+    let (columns, values): (Vec<_>, Vec<_>) = {
+        let (cols, vals) = to_columns(record)?;
+        cols.into_iter()
+            .zip(vals)
+            .filter(|(c, _)| !skip.contains(&c.as_str()))
+            .unzip()
+    };
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        check_identifier(table)?,
+        columns.join(", "),
+        placeholders
+    );
+    conn.execute(&sql, params_from_iter(values.iter()))?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Fetches a single row by primary key.
+fn get_by_id<T: DeserializeOwned>(conn: &Connection, table: &str, id: i64) -> Result<Option<T>> {
+    let sql = format!("SELECT * FROM {} WHERE id = ?", check_identifier(table)?);
+    Ok(query_as(conn, &sql, &[SqlValue::Integer(id)])?.into_iter().next())
+}
+
+/// Updates every serialized field (except `id`) of the row with the given id.
+/// Returns the number of rows changed (0 if the id doesn't exist), or
+/// `StoreError::NoColumnsToUpdate` if `id` is the record's only field.
+fn update_by_id<T: Serialize>(conn: &Connection, table: &str, id: i64, record: &T) -> Result<usize> {
+    let (columns, mut values) = to_columns(record)?;
+    let mut assignments = Vec::new();
+    let mut kept = Vec::new();
+    for (column, value) in columns.into_iter().zip(values.drain(..)) {
+        if column != "id" {
+            assignments.push(format!("{} = ?", column));
+            kept.push(value);
+        }
+    }
+    if assignments.is_empty() {
+        return Err(StoreError::NoColumnsToUpdate);
+    }
+    kept.push(SqlValue::Integer(id));
+    let sql = format!(
+        "UPDATE {} SET {} WHERE id = ?",
+        check_identifier(table)?,
+        assignments.join(", ")
+    );
+    Ok(conn.execute(&sql, params_from_iter(kept.iter()))?)
+}
+
+fn delete_by_id(conn: &Connection, table: &str, id: i64) -> Result<usize> {
+    let sql = format!("DELETE FROM {} WHERE id = ?", check_identifier(table)?);
+    Ok(conn.execute(&sql, [id])?)
+}
+
+/// Runs `f` inside a transaction: committed if `f` returns `Ok`, rolled back otherwise
+/// (rusqlite rolls back automatically when an uncommitted `Transaction` is dropped).
+fn with_transaction<T, F>(conn: &mut Connection, f: F) -> Result<T>
+where
+    F: FnOnce(&Transaction<'_>) -> Result<T>,
+{
+    let tx = conn.transaction()?;
+    let value = f(&tx)?;
+    tx.commit()?;
+    Ok(value)
+}
+
+// --- A small WHERE-clause builder ---
+
+/// Builds a parameterized WHERE clause. Values are always bound, never interpolated.
+/// Values are anything convertible to `rusqlite::types::Value` (`i64`, `f64`, `String`, `bool`, ...).
+#[derive(Default, Debug)]
+struct Filter {
+    clauses: Vec<String>,
+    params: Vec<SqlValue>,
+    order_by: Option<String>,
+    limit: Option<u64>,
+}
+
+impl Filter {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn cmp(mut self, column: &str, op: &str, value: impl Into<SqlValue>) -> Result<Self> {
+        self.clauses.push(format!("{} {} ?", check_identifier(column)?, op));
+        self.params.push(value.into());
+        Ok(self)
+    }
+
+    fn eq(self, column: &str, value: impl Into<SqlValue>) -> Result<Self> {
+        self.cmp(column, "=", value)
+    }
+
+    fn gt(self, column: &str, value: impl Into<SqlValue>) -> Result<Self> {
+        self.cmp(column, ">", value)
+    }
+
+    fn lt(self, column: &str, value: impl Into<SqlValue>) -> Result<Self> {
+        self.cmp(column, "<", value)
+    }
+
+    fn like(self, column: &str, pattern: &str) -> Result<Self> {
+        self.cmp(column, "LIKE", pattern.to_string())
+    }
+
+    fn where_null(mut self, column: &str) -> Result<Self> {
+        self.clauses.push(format!("{} IS NULL", check_identifier(column)?));
+        Ok(self)
+    }
+
+    fn in_list<V: Into<SqlValue>>(mut self, column: &str, values: impl IntoIterator<Item = V>) -> Result<Self> {
+        let column = check_identifier(column)?;
+        let values: Vec<SqlValue> = values.into_iter().map(Into::into).collect();
+        if values.is_empty() {
+            // `IN ()` is a syntax error; an empty list matches nothing.
+            self.clauses.push("0".to_string());
+        } else {
+            let placeholders = vec!["?"; values.len()].join(", ");
+            self.clauses.push(format!("{} IN ({})", column, placeholders));
+            self.params.extend(values);
+        }
+        Ok(self)
+    }
+
+    fn order_by(mut self, column: &str, descending: bool) -> Result<Self> {
+        let dir = if descending { "DESC" } else { "ASC" };
+        self.order_by = Some(format!("{} {}", check_identifier(column)?, dir));
+        Ok(self)
+    }
+
+    fn limit(mut self, n: u64) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Renders the SQL suffix (` WHERE ... ORDER BY ... LIMIT ...`).
+    fn to_sql(&self) -> String {
+        let mut sql = String::new();
+        if !self.clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.clauses.join(" AND "));
+        }
+        if let Some(order) = &self.order_by {
+            sql.push_str(" ORDER BY ");
+            sql.push_str(order);
+        }
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        sql
+    }
+}
+
+/// `SELECT * FROM table` + filter, deserialized into `T`.
+fn find<T: DeserializeOwned>(conn: &Connection, table: &str, filter: &Filter) -> Result<Vec<T>> {
+    let sql = format!("SELECT * FROM {}{}", check_identifier(table)?, filter.to_sql());
+    query_as(conn, &sql, &filter.params)
+}
+
+/// Example record type.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct User {
+    #[serde(default)]
+    id: i64,
+    email: String,
+    name: String,
+    age: Option<i64>,
+    #[serde(default = "default_status")]
+    status: String,
+}
+
+fn default_status() -> String {
+    "active".to_string()
+}
+
+/// Projection with only some columns; extra columns in the row are ignored by serde.
+#[derive(Deserialize, Debug)]
+struct UserSummary {
+    email: String,
+    status: String,
+}
+
+// Example Usage
+/*
+fn main() -> Result<()> {
+    let mut conn = open_in_memory()?; // or open_database("app.db")?
+    let applied = run_migrations(&mut conn, MIGRATIONS)?;
+    println!("Applied migrations {:?}; schema version: {}", applied, MIGRATIONS.len());
+
+    // Create
+    let alice = User { id: 0, email: "alice@example.com".into(), name: "Alice".into(), age: Some(34), status: default_status() };
+    let alice_id = insert(&conn, "users", &alice, &["id"])?;
+    insert(&conn, "users", &User { id: 0, email: "bob@example.com".into(), name: "Bob".into(), age: None, status: "disabled".into() }, &["id"])?;
+    insert(&conn, "users", &User { id: 0, email: "carol@example.com".into(), name: "Carol".into(), age: Some(19), status: default_status() }, &["id"])?;
+
+    // Read
+    let loaded: Option<User> = get_by_id(&conn, "users", alice_id)?;
+    println!("Loaded: {:?}", loaded);
+
+    // Update
+    let mut updated = loaded.expect("alice exists");
+    updated.age = Some(35);
+    println!("Rows updated: {}", update_by_id(&conn, "users", alice_id, &updated)?);
+
+    // Query builder
+    let filter = Filter::new()
+        .eq("status", "active".to_string())?
+        .gt("age", 18)?
+        .order_by("age", true)?
+        .limit(10);
+    let adults: Vec<User> = find(&conn, "users", &filter)?;
+    println!("Active adults: {:#?}", adults);
+
+    let summaries: Vec<UserSummary> = find(&conn, "users", &Filter::new().in_list("name", ["Bob".to_string(), "Carol".to_string()])?)?;
+    println!("Summaries: {:?}", summaries);
+
+    // Transaction: both inserts succeed or neither does.
+    let result = with_transaction(&mut conn, |tx| {
+        insert(tx, "users", &User { id: 0, email: "dave@example.com".into(), name: "Dave".into(), age: None, status: default_status() }, &["id"])?;
+        // Duplicate email violates the UNIQUE constraint -> the whole transaction rolls back.
+        insert(tx, "users", &alice, &["id"])?;
+        Ok(())
+    });
+    println!("Transaction result: {:?}", result.map_err(|e| e.to_string()));
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM users", [], |r| r.get(0))?;
+    println!("Users after rollback: {}", count); // 3
+
+    // Delete
+    println!("Rows deleted: {}", delete_by_id(&conn, "users", alice_id)?);
+
+    // Identifiers are validated to prevent SQL injection through table/column names.
+    assert!(Filter::new().eq("name; DROP TABLE users", 1).is_err());
+    Ok(())
+}
+*/
@@ -0,0 +1,327 @@
+// Note: This example requires adding the `ssh2` and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// ssh2 = "0.9" // Builds libssh2 from source; needs OpenSSL headers on Linux
+// thiserror = "1.0"
+//
+// `ssh2` is blocking. From async code, run these helpers inside `spawn_blocking`
+// (see `blocking_async_bridge.rs`).
+
+use ssh2::{CheckResult, KnownHostFileKind, Session};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SshError {
+    #[error("SSH error: {0}")]
+    Ssh(#[from] ssh2::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Host key for {0} does not match known_hosts (possible MITM)")]
+    HostKeyMismatch(String),
+    #[error("Host {0} is not in known_hosts")]
+    UnknownHost(String),
+    #[error("Authentication failed for user '{0}'")]
+    AuthFailed(String),
+}
+
+type Result<T> = std::result::Result<T, SshError>;
+
+/// The result of running a command, locally or remotely.
+///
+/// Mirrors the fields of `std::process::Output` returned by `execute_command` (see
+/// `execute_command.rs`), so callers can treat local and remote runs the same way.
+#[derive(Debug, Clone)]
+struct CommandResult {
+    /// `None` if the process was killed by a signal.
+    exit_code: Option<i32>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl CommandResult {
+    fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    fn stdout_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.stdout).into_owned()
+    }
+
+    fn stderr_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.stderr).into_owned()
+    }
+}
+
+impl From<Output> for CommandResult {
+    fn from(output: Output) -> Self {
+        Self {
+            exit_code: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }
+    }
+}
+
+/// How to authenticate.
+enum SshAuth {
+    /// Use keys loaded in the running ssh-agent (`SSH_AUTH_SOCK`).
+    Agent,
+    /// A private key file, optionally passphrase-protected.
+    KeyFile { private_key: PathBuf, passphrase: Option<String> },
+    Password(String),
+}
+
+/// What to do when the server's host key is not in `known_hosts`.
+#[derive(Clone, Copy, PartialEq)]
+enum HostKeyPolicy {
+    /// Refuse unknown hosts (the safe default for automation).
+    Strict,
+    /// Trust on first use: record the key, then enforce it on later connections.
+    AcceptNew,
+}
+
+struct SshConfig {
+    host: String,
+    port: u16,
+    username: String,
+    auth: SshAuth,
+    known_hosts: PathBuf,
+    host_key_policy: HostKeyPolicy,
+    timeout: Duration,
+}
+
+impl SshConfig {
+    /// Agent auth, port 22, `~/.ssh/known_hosts`, strict host key checking.
+    fn new(host: &str, username: &str) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        Self {
+            host: host.to_string(),
+            port: 22,
+            username: username.to_string(),
+            auth: SshAuth::Agent,
+            known_hosts: home.join(".ssh").join("known_hosts"),
+            host_key_policy: HostKeyPolicy::Strict,
+            timeout: Duration::from_secs(15),
+        }
+    }
+}
+
+/// Checks the server's host key against `known_hosts`, enforcing `policy`.
+fn verify_host_key(session: &Session, config: &SshConfig) -> Result<()> {
+    let (key, key_type) = session
+        .host_key()
+        .ok_or_else(|| SshError::HostKeyMismatch(config.host.clone()))?;
+    let mut known_hosts = session.known_hosts()?;
+    if config.known_hosts.exists() {
+        known_hosts.read_file(&config.known_hosts, KnownHostFileKind::OpenSSH)?;
+    }
+
+    match known_hosts.check_port(&config.host, config.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(SshError::HostKeyMismatch(config.host.clone())),
+        CheckResult::NotFound | CheckResult::Failure => {
+            if config.host_key_policy == HostKeyPolicy::Strict {
+                return Err(SshError::UnknownHost(config.host.clone()));
+            }
+            // Non-default ports are stored as "[host]:port", like OpenSSH does.
+            let entry = if config.port == 22 {
+                config.host.clone()
+            } else {
+                format!("[{}]:{}", config.host, config.port)
+            };
+            known_hosts.add(&entry, key, "added by ssh.rs", key_type.into())?;
+            if let Some(dir) = config.known_hosts.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            known_hosts.write_file(&config.known_hosts, KnownHostFileKind::OpenSSH)?;
+            Ok(())
+        }
+    }
+}
+
+/// Opens an authenticated SSH session.
+///
+/// # Arguments
+///
+/// * `config` - Host, credentials, and host key policy.
+///
+/// # Returns
+///
+/// A blocking `Session` ready for `run_command`, `upload`, and `download`.
+fn connect(config: &SshConfig) -> Result<Session> {
+    let addr = (config.host.as_str(), config.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host did not resolve"))?;
+    let tcp = TcpStream::connect_timeout(&addr, config.timeout)?;
+
+    let mut session = Session::new()?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(config.timeout.as_millis() as u32);
+    session.handshake()?;
+    verify_host_key(&session, config)?;
+
+    match &config.auth {
+        SshAuth::Agent => session.userauth_agent(&config.username)?,
+        SshAuth::KeyFile { private_key, passphrase } => {
+            session.userauth_pubkey_file(&config.username, None, private_key, passphrase.as_deref())?
+        }
+        SshAuth::Password(password) => session.userauth_password(&config.username, password)?,
+    }
+    if !session.authenticated() {
+        return Err(SshError::AuthFailed(config.username.clone()));
+    }
+
+    // Long-running transfers behind NAT need keepalives.
+    session.set_keepalive(true, 30);
+    Ok(session)
+}
+
+/// Runs a command on the remote host and captures its output.
+///
+/// The command is interpreted by the remote user's login shell, so quote arguments
+/// accordingly. A non-zero exit is returned as a `CommandResult`, not as an error.
+fn run_command(session: &Session, command: &str, input: Option<&[u8]>) -> Result<CommandResult> {
+    let mut channel = session.channel_session()?;
+    channel.exec(command)?;
+
+    if let Some(input) = input {
+        channel.write_all(input)?;
+    }
+    channel.send_eof()?;
+
+    // Reading stdout fully before stderr is fine for typical commands; a command that
+    // writes megabytes to stderr first could stall (use `ExtendedData::Merge` then).
+    let mut stdout = Vec::new();
+    channel.read_to_end(&mut stdout)?;
+    let mut stderr = Vec::new();
+    channel.stderr().read_to_end(&mut stderr)?;
+
+    channel.wait_close()?;
+    let exit_code = match channel.exit_signal()?.exit_signal {
+        Some(_) => None, // Killed by a signal, like `ExitStatus::code()` on Unix
+        None => Some(channel.exit_status()?),
+    };
+    Ok(CommandResult { exit_code, stdout, stderr })
+}
+
+/// Copies `reader` to `writer` in chunks, reporting `(bytes_done, total)` after each chunk.
+fn copy_with_progress(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    total: u64,
+    progress: &mut impl FnMut(u64, u64),
+) -> io::Result<u64> {
+    let mut buf = vec![0u8; 32 * 1024];
+    let mut done = 0u64;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n])?;
+        done += n as u64;
+        progress(done, total);
+    }
+    writer.flush()?;
+    Ok(done)
+}
+
+/// Uploads a local file over SFTP, replacing the remote file if it exists.
+///
+/// Writes to `<remote>.part` first and renames on completion, so readers never see a
+/// half-written file.
+fn upload(session: &Session, local: &Path, remote: &Path, mut progress: impl FnMut(u64, u64)) -> Result<u64> {
+    let sftp = session.sftp()?;
+    let mut source = File::open(local)?;
+    let total = source.metadata()?.len();
+
+    let mut partial = remote.as_os_str().to_owned();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+
+    let mut target = sftp.create(&partial)?;
+    let written = copy_with_progress(&mut source, &mut target, total, &mut progress)?;
+    drop(target);
+
+    // SFTP rename fails if the target exists on some servers; remove it first.
+    let _ = sftp.unlink(remote);
+    sftp.rename(&partial, remote, None)?;
+    Ok(written)
+}
+
+/// Downloads a remote file over SFTP with progress reporting.
+fn download(session: &Session, remote: &Path, local: &Path, mut progress: impl FnMut(u64, u64)) -> Result<u64> {
+    let sftp = session.sftp()?;
+    let total = sftp.stat(remote)?.size.unwrap_or(0);
+    let mut source = sftp.open(remote)?;
+    let mut target = File::create(local)?;
+    Ok(copy_with_progress(&mut source, &mut target, total, &mut progress)?)
+}
+
+/// Lists a remote directory as (name, size, is_dir).
+fn list_dir(session: &Session, remote: &Path) -> Result<Vec<(String, u64, bool)>> {
+    let sftp = session.sftp()?;
+    let mut entries: Vec<_> = sftp
+        .readdir(remote)?
+        .into_iter()
+        .map(|(path, stat)| {
+            let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            (name, stat.size.unwrap_or(0), stat.is_dir())
+        })
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+/// Prints a simple progress line; pass as the `progress` callback.
+fn print_progress(done: u64, total: u64) {
+    if total > 0 {
+        print!("\r{:>5.1}% ({}/{} bytes)", done as f64 * 100.0 / total as f64, done, total);
+    } else {
+        print!("\r{} bytes", done);
+    }
+    let _ = io::stdout().flush();
+    if done >= total {
+        println!();
+    }
+}
+
+// Example Usage (requires an SSH server; set SSH_HOST and SSH_USER, keys in ssh-agent)
+/*
+fn main() -> Result<()> {
+    let host = std::env::var("SSH_HOST").unwrap_or_else(|_| "localhost".into());
+    let user = std::env::var("SSH_USER").unwrap_or_else(|_| "deploy".into());
+
+    let mut config = SshConfig::new(&host, &user);
+    config.host_key_policy = HostKeyPolicy::AcceptNew; // TOFU for this demo
+    let session = connect(&config)?;
+
+    let result = run_command(&session, "uname -a && ls /nonexistent", None)?;
+    println!("exit={:?} success={}", result.exit_code, result.success());
+    println!("stdout: {}", result.stdout_lossy().trim());
+    println!("stderr: {}", result.stderr_lossy().trim());
+
+    // Piping input, like `execute_command(.., Some(input))`.
+    let counted = run_command(&session, "wc -l", Some(b"a\nb\nc\n"))?;
+    println!("wc -l says: {}", counted.stdout_lossy().trim());
+
+    // Local and remote results share a type.
+    let local: CommandResult = std::process::Command::new("hostname").output()?.into();
+    println!("local host: {}", local.stdout_lossy().trim());
+
+    std::fs::write("/tmp/ssh_demo.txt", "hello over sftp\n".repeat(10_000))?;
+    upload(&session, Path::new("/tmp/ssh_demo.txt"), Path::new("/tmp/ssh_demo_remote.txt"), print_progress)?;
+    download(&session, Path::new("/tmp/ssh_demo_remote.txt"), Path::new("/tmp/ssh_demo_back.txt"), print_progress)?;
+
+    for (name, size, is_dir) in list_dir(&session, Path::new("/tmp"))?.iter().take(5) {
+        println!("{}{} ({} bytes)", name, if *is_dir { "/" } else { "" }, size);
+    }
+    Ok(())
+}
+*/
@@ -0,0 +1,382 @@
+// Note: This example requires adding the `serde`, `serde_json`, and `thiserror` crates to your
+// Cargo.toml:
+// [dependencies]
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0" # Only for the example
+// thiserror = "1.0"
+//
+// A small table-driven state machine. States and events are plain enums; the allowed
+// transitions are declared once, with optional guards (conditions on the context) and
+// actions, plus entry/exit actions per state. Firing an event the table doesn't allow
+// returns a typed error instead of silently doing nothing, which is what the hand-written
+// `match (state, event)` from `advanced_pattern_matching.rs` tends to do once it grows a
+// `_ => {}` arm.
+//
+// The definition is built once and shared (`Arc`); each `StateMachine` instance holds just
+// its current state and context, so orders, jobs, or sessions can each have one and be
+// saved/restored with serde via `Snapshot`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Write as _};
+use std::hash::Hash;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum StateMachineError<S: Debug, E: Debug> {
+    #[error("No transition from {from:?} on {event:?}")]
+    InvalidTransition { from: S, event: E },
+    #[error("Transition from {from:?} on {event:?} was rejected by its guard")]
+    GuardRejected { from: S, event: E },
+    #[error("State {0:?} is not part of this machine's definition")]
+    UnknownState(S),
+    #[error("Invalid machine definition: {0}")]
+    Definition(String),
+}
+
+type Result<T, S, E> = std::result::Result<T, StateMachineError<S, E>>;
+
+type Guard<C> = Box<dyn Fn(&C) -> bool + Send + Sync>;
+type Action<C> = Box<dyn Fn(&mut C) + Send + Sync>;
+
+struct Transition<S, C> {
+    to: S,
+    guard: Option<Guard<C>>,
+    action: Option<Action<C>>,
+}
+
+// --- Definition ---
+
+/// The transition table plus entry/exit actions. Build with `MachineDefinition::builder()`.
+struct MachineDefinition<S, E, C> {
+    initial: S,
+    /// Candidates per (state, event), tried in declaration order; the first whose guard
+    /// passes wins.
+    transitions: HashMap<(S, E), Vec<Transition<S, C>>>,
+    on_enter: HashMap<S, Action<C>>,
+    on_exit: HashMap<S, Action<C>>,
+    /// Every state mentioned in the table, in declaration order.
+    states: Vec<S>,
+    /// (from, event, to, guarded) rows in declaration order, for `to_dot`.
+    rows: Vec<(S, E, S, bool)>,
+}
+
+/// Declares transitions fluently: `.on(from, event, to)` adds a row, and `.when(..)` /
+/// `.then(..)` attach a guard or action to the row just added.
+struct DefinitionBuilder<S, E, C> {
+    definition: MachineDefinition<S, E, C>,
+    last: Option<(S, E)>,
+    error: Option<String>,
+}
+
+impl<S, E, C> MachineDefinition<S, E, C>
+where
+    S: Copy + Eq + Hash + Debug,
+    E: Copy + Eq + Hash + Debug,
+{
+    fn builder(initial: S) -> DefinitionBuilder<S, E, C> {
+        DefinitionBuilder {
+            definition: MachineDefinition {
+                initial,
+                transitions: HashMap::new(),
+                on_enter: HashMap::new(),
+                on_exit: HashMap::new(),
+                states: vec![initial],
+                rows: Vec::new(),
+            },
+            last: None,
+            error: None,
+        }
+    }
+
+    fn knows(&self, state: S) -> bool {
+        self.states.contains(&state)
+    }
+
+    /// Events with at least one transition out of `state` (guards not evaluated).
+    fn events_from(&self, state: S) -> Vec<E> {
+        let mut events: Vec<E> = Vec::new();
+        for &(from, event, _, _) in &self.rows {
+            if from == state && !events.contains(&event) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// The machine as a Graphviz `dot` graph; guarded edges are dashed.
+    /// Render with `dot -Tsvg machine.dot -o machine.svg`.
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph StateMachine {\n  rankdir=LR;\n");
+        let _ = writeln!(dot, "  \"{:?}\" [shape=doublecircle];", self.initial);
+        for (from, event, to, guarded) in &self.rows {
+            let style = if *guarded { " style=dashed" } else { "" };
+            let _ = writeln!(dot, "  \"{:?}\" -> \"{:?}\" [label=\"{:?}\"{}];", from, to, event, style);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<S, E, C> DefinitionBuilder<S, E, C>
+where
+    S: Copy + Eq + Hash + Debug,
+    E: Copy + Eq + Hash + Debug,
+{
+    /// Allows `event` to move the machine from `from` to `to`.
+    fn on(mut self, from: S, event: E, to: S) -> Self {
+        let candidates = self.definition.transitions.entry((from, event)).or_default();
+        if candidates.iter().any(|t| t.guard.is_none()) && self.error.is_none() {
+            self.error = Some(format!(
+                "{:?} on {:?} already has an unguarded transition, so this one could never fire",
+                from, event
+            ));
+        }
+        candidates.push(Transition { to, guard: None, action: None });
+        for state in [from, to] {
+            if !self.definition.states.contains(&state) {
+                self.definition.states.push(state);
+            }
+        }
+        self.definition.rows.push((from, event, to, false));
+        self.last = Some((from, event));
+        self
+    }
+
+    /// Allows `event` from each state in `from` (e.g. "cancel" from several states).
+    fn on_any(mut self, from: &[S], event: E, to: S) -> Self {
+        for &state in from {
+            self = self.on(state, event, to);
+        }
+        self
+    }
+
+    fn last_transition(&mut self) -> Option<&mut Transition<S, C>> {
+        let key = self.last?;
+        self.definition.transitions.get_mut(&key)?.last_mut()
+    }
+
+    /// Only take the previous transition when `guard` returns `true` for the context.
+    /// Declare a later `.on(..)` for the same state and event as the fallback.
+    fn when(mut self, guard: impl Fn(&C) -> bool + Send + Sync + 'static) -> Self {
+        match self.last_transition() {
+            Some(transition) => transition.guard = Some(Box::new(guard)),
+            None => self.error = self.error.or(Some("when() must follow on()".to_string())),
+        }
+        if let Some(row) = self.definition.rows.last_mut() {
+            row.3 = true;
+        }
+        self
+    }
+
+    /// Runs `action` while taking the previous transition (after exit, before entry).
+    fn then(mut self, action: impl Fn(&mut C) + Send + Sync + 'static) -> Self {
+        match self.last_transition() {
+            Some(transition) => transition.action = Some(Box::new(action)),
+            None => self.error = self.error.or(Some("then() must follow on()".to_string())),
+        }
+        self
+    }
+
+    /// Runs `action` whenever the machine enters `state`, including self-transitions.
+    fn on_enter(mut self, state: S, action: impl Fn(&mut C) + Send + Sync + 'static) -> Self {
+        self.definition.on_enter.insert(state, Box::new(action));
+        self
+    }
+
+    /// Runs `action` whenever the machine leaves `state`, including self-transitions.
+    fn on_exit(mut self, state: S, action: impl Fn(&mut C) + Send + Sync + 'static) -> Self {
+        self.definition.on_exit.insert(state, Box::new(action));
+        self
+    }
+
+    /// Finishes the definition, rejecting unreachable transitions (a second row for the same
+    /// state and event after an unguarded one).
+    fn build(self) -> Result<Arc<MachineDefinition<S, E, C>>, S, E> {
+        match self.error {
+            Some(message) => Err(StateMachineError::Definition(message)),
+            None => Ok(Arc::new(self.definition)),
+        }
+    }
+}
+
+// --- Instances ---
+
+/// The persistent part of a machine: which state it is in and its context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Snapshot<S, C> {
+    state: S,
+    context: C,
+}
+
+/// One running instance of a definition.
+struct StateMachine<S, E, C> {
+    definition: Arc<MachineDefinition<S, E, C>>,
+    state: S,
+    context: C,
+}
+
+impl<S, E, C> StateMachine<S, E, C>
+where
+    S: Copy + Eq + Hash + Debug,
+    E: Copy + Eq + Hash + Debug,
+{
+    /// A new instance in the definition's initial state. The initial state's entry action
+    /// is *not* run; call `fire` for anything that should happen on creation.
+    fn new(definition: Arc<MachineDefinition<S, E, C>>, context: C) -> Self {
+        let state = definition.initial;
+        Self { definition, state, context }
+    }
+
+    /// Resumes an instance saved with `snapshot`, checking its state still exists in the
+    /// (possibly newer) definition.
+    fn restore(definition: Arc<MachineDefinition<S, E, C>>, snapshot: Snapshot<S, C>) -> Result<Self, S, E> {
+        if !definition.knows(snapshot.state) {
+            return Err(StateMachineError::UnknownState(snapshot.state));
+        }
+        Ok(Self { definition, state: snapshot.state, context: snapshot.context })
+    }
+
+    fn state(&self) -> S {
+        self.state
+    }
+
+    fn context(&self) -> &C {
+        &self.context
+    }
+
+    /// Applies `event`: picks the first transition for (state, event) whose guard passes,
+    /// then runs the current state's exit action, the transition's action, and the new
+    /// state's entry action, in that order.
+    ///
+    /// # Returns
+    ///
+    /// The new state, or `InvalidTransition` / `GuardRejected` with the machine unchanged.
+    fn fire(&mut self, event: E) -> Result<S, S, E> {
+        let from = self.state;
+        let definition = Arc::clone(&self.definition);
+        let candidates = definition
+            .transitions
+            .get(&(from, event))
+            .ok_or(StateMachineError::InvalidTransition { from, event })?;
+        let transition = candidates
+            .iter()
+            .find(|t| t.guard.as_ref().is_none_or(|guard| guard(&self.context)))
+            .ok_or(StateMachineError::GuardRejected { from, event })?;
+
+        if let Some(exit) = definition.on_exit.get(&from) {
+            exit(&mut self.context);
+        }
+        if let Some(action) = &transition.action {
+            action(&mut self.context);
+        }
+        self.state = transition.to;
+        if let Some(enter) = definition.on_enter.get(&transition.to) {
+            enter(&mut self.context);
+        }
+        Ok(self.state)
+    }
+
+    /// Whether `event` would currently succeed (guards are evaluated, nothing runs).
+    fn can_fire(&self, event: E) -> bool {
+        self.definition
+            .transitions
+            .get(&(self.state, event))
+            .is_some_and(|candidates| candidates.iter().any(|t| t.guard.as_ref().is_none_or(|g| g(&self.context))))
+    }
+
+    /// Events that would currently succeed, e.g. to enable buttons in a UI.
+    fn available_events(&self) -> Vec<E> {
+        self.definition.events_from(self.state).into_iter().filter(|&e| self.can_fire(e)).collect()
+    }
+}
+
+impl<S: Clone, E, C: Clone> StateMachine<S, E, C> {
+    fn snapshot(&self) -> Snapshot<S, C> {
+        Snapshot { state: self.state.clone(), context: self.context.clone() }
+    }
+}
+
+impl<S: Debug, E, C: Debug> fmt::Debug for StateMachine<S, E, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateMachine").field("state", &self.state).field("context", &self.context).finish()
+    }
+}
+
+// Example Usage
+/*
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+enum OrderState {
+    Pending,
+    Paid,
+    Shipped,
+    Delivered,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum OrderEvent {
+    Pay,
+    Ship,
+    Deliver,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Order {
+    total_cents: u64,
+    paid_cents: u64,
+    refunded_cents: u64,
+    log: Vec<String>,
+}
+
+fn order_machine() -> Arc<MachineDefinition<OrderState, OrderEvent, Order>> {
+    use OrderEvent::*;
+    use OrderState::*;
+    MachineDefinition::builder(Pending)
+        .on(Pending, Pay, Paid)
+        .when(|o: &Order| o.total_cents > 0)
+        .then(|o: &mut Order| o.paid_cents = o.total_cents)
+        .on(Paid, Ship, Shipped)
+        .on(Shipped, Deliver, Delivered)
+        .on(Paid, Cancel, Cancelled)
+        .then(|o: &mut Order| o.refunded_cents = o.paid_cents)
+        .on(Pending, Cancel, Cancelled)
+        .on_enter(Shipped, |o: &mut Order| o.log.push("send tracking email".into()))
+        .on_enter(Cancelled, |o: &mut Order| o.log.push("send cancellation email".into()))
+        .on_exit(Pending, |o: &mut Order| o.log.push("release cart reservation".into()))
+        .build()
+        .expect("valid definition")
+}
+
+fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let definition = order_machine();
+
+    let mut order = StateMachine::new(Arc::clone(&definition), Order { total_cents: 4_999, ..Order::default() });
+    println!("{:?}: can do {:?}", order.state(), order.available_events());
+    println!("pay -> {:?}", order.fire(OrderEvent::Pay)?);
+    println!("deliver -> {}", order.fire(OrderEvent::Deliver).unwrap_err());
+
+    // Persist mid-lifecycle, then resume elsewhere.
+    let saved = serde_json::to_string(&order.snapshot())?;
+    println!("saved: {}", saved);
+    let mut order = StateMachine::restore(Arc::clone(&definition), serde_json::from_str(&saved)?)?;
+    println!("cancel -> {:?}; refunded {} cents", order.fire(OrderEvent::Cancel)?, order.context().refunded_cents);
+    println!("log: {:?}", order.context().log);
+
+    // Guards: a zero-total order cannot be paid.
+    let mut free = StateMachine::new(Arc::clone(&definition), Order::default());
+    println!("pay free order -> {}", free.fire(OrderEvent::Pay).unwrap_err());
+
+    // A definition with a transition that could never fire is rejected up front.
+    let broken = MachineDefinition::<OrderState, OrderEvent, Order>::builder(OrderState::Pending)
+        .on(OrderState::Pending, OrderEvent::Pay, OrderState::Paid)
+        .on(OrderState::Pending, OrderEvent::Pay, OrderState::Cancelled)
+        .build();
+    println!("broken: {}", broken.err().unwrap());
+
+    print!("{}", definition.to_dot());
+    Ok(())
+}
+*/
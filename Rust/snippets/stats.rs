@@ -0,0 +1,425 @@
+// This example only uses the standard library (`std::sync::atomic`).
+//
+// Lock-free metrics primitives that can be updated from many threads without a Mutex:
+// * `Counter` / `Gauge`  - single atomics, the replacement for `Arc<Mutex<i32>>` counters.
+// * `RateMeter`          - events/second with 1/5/15-minute EWMAs (Unix load-average style).
+// * `Histogram`          - HDR-style log-linear buckets for latency percentiles.
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// --- Counters and gauges ---
+
+/// A monotonically increasing counter.
+/// `Relaxed` ordering is enough: we only need each increment to be atomic,
+/// not to order other memory operations around it.
+#[derive(Default, Debug)]
+struct Counter(AtomicU64);
+
+impl Counter {
+    fn inc(&self) {
+        self.add(1);
+    }
+
+    fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Returns the current value and resets to zero in one atomic step
+    /// (useful for "per reporting interval" counters).
+    fn take(&self) -> u64 {
+        self.0.swap(0, Ordering::Relaxed)
+    }
+}
+
+/// A value that can go up and down (in-flight requests, queue depth, ...),
+/// also tracking the high-water mark.
+#[derive(Default, Debug)]
+struct Gauge {
+    value: AtomicI64,
+    max: AtomicI64,
+}
+
+impl Gauge {
+    fn set(&self, v: i64) {
+        self.value.store(v, Ordering::Relaxed);
+        self.max.fetch_max(v, Ordering::Relaxed);
+    }
+
+    fn inc(&self) -> i64 {
+        let v = self.value.fetch_add(1, Ordering::Relaxed) + 1;
+        self.max.fetch_max(v, Ordering::Relaxed);
+        v
+    }
+
+    fn dec(&self) -> i64 {
+        self.value.fetch_sub(1, Ordering::Relaxed) - 1
+    }
+
+    fn get(&self) -> i64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    fn high_water_mark(&self) -> i64 {
+        self.max.load(Ordering::Relaxed)
+    }
+}
+
+// --- EWMA rate tracking ---
+
+/// An `f64` stored in an `AtomicU64` via its bit pattern.
+#[derive(Default, Debug)]
+struct AtomicF64(AtomicU64);
+
+impl AtomicF64 {
+    fn load(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn store(&self, v: f64) {
+        self.0.store(v.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Exponentially weighted moving average of a per-second rate.
+/// Updated once per tick interval by whichever thread wins the tick (see `RateMeter`).
+#[derive(Debug)]
+struct Ewma {
+    alpha: f64,
+    rate: AtomicF64,
+    initialized: std::sync::atomic::AtomicBool,
+}
+
+impl Ewma {
+    /// `window` is the averaging period (e.g. 1 minute); `tick` is the update interval.
+    fn new(window: Duration, tick: Duration) -> Self {
+        Self {
+            alpha: 1.0 - (-tick.as_secs_f64() / window.as_secs_f64()).exp(),
+            rate: AtomicF64::default(),
+            initialized: Default::default(),
+        }
+    }
+
+    fn tick(&self, count: u64, tick: Duration) {
+        let instant_rate = count as f64 / tick.as_secs_f64();
+        if self.initialized.swap(true, Ordering::Relaxed) {
+            let rate = self.rate.load();
+            self.rate.store(rate + self.alpha * (instant_rate - rate));
+        } else {
+            self.rate.store(instant_rate);
+        }
+    }
+
+    fn rate_per_sec(&self) -> f64 {
+        self.rate.load()
+    }
+}
+
+/// Measures event throughput: total count, mean rate, and 1/5/15-minute moving averages.
+///
+/// `mark` is a single `fetch_add` in the common case. Every `TICK` one caller wins a
+/// compare-and-swap on `last_tick` and folds the pending count into the EWMAs, so no
+/// background thread or lock is required.
+#[derive(Debug)]
+struct RateMeter {
+    start: Instant,
+    count: AtomicU64,
+    uncounted: AtomicU64,
+    last_tick_nanos: AtomicU64,
+    m1: Ewma,
+    m5: Ewma,
+    m15: Ewma,
+}
+
+impl RateMeter {
+    const TICK: Duration = Duration::from_secs(5);
+
+    fn new() -> Self {
+        let min = |m: u64| Duration::from_secs(60 * m);
+        Self {
+            start: Instant::now(),
+            count: AtomicU64::new(0),
+            uncounted: AtomicU64::new(0),
+            last_tick_nanos: AtomicU64::new(0),
+            m1: Ewma::new(min(1), Self::TICK),
+            m5: Ewma::new(min(5), Self::TICK),
+            m15: Ewma::new(min(15), Self::TICK),
+        }
+    }
+
+    /// Records `n` events.
+    fn mark(&self, n: u64) {
+        self.tick_if_necessary();
+        self.count.fetch_add(n, Ordering::Relaxed);
+        self.uncounted.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn tick_if_necessary(&self) {
+        let now = self.start.elapsed().as_nanos() as u64;
+        let tick = Self::TICK.as_nanos() as u64;
+        let old = self.last_tick_nanos.load(Ordering::Relaxed);
+        let age = now.saturating_sub(old);
+        if age < tick {
+            return;
+        }
+        let new_tick = now - age % tick;
+        // Only one thread wins the CAS and performs the (rare) EWMA update.
+        if self
+            .last_tick_nanos
+            .compare_exchange(old, new_tick, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok()
+        {
+            for i in 0..age / tick {
+                // The first tick gets the pending events; missed ticks (idle periods) decay with 0.
+                let count = if i == 0 { self.uncounted.swap(0, Ordering::Relaxed) } else { 0 };
+                for ewma in [&self.m1, &self.m5, &self.m15] {
+                    ewma.tick(count, Self::TICK);
+                }
+            }
+        }
+    }
+
+    fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    fn mean_rate(&self) -> f64 {
+        let secs = self.start.elapsed().as_secs_f64();
+        if secs == 0.0 {
+            0.0
+        } else {
+            self.count() as f64 / secs
+        }
+    }
+
+    fn one_minute_rate(&self) -> f64 {
+        self.tick_if_necessary();
+        self.m1.rate_per_sec()
+    }
+
+    fn five_minute_rate(&self) -> f64 {
+        self.tick_if_necessary();
+        self.m5.rate_per_sec()
+    }
+
+    fn fifteen_minute_rate(&self) -> f64 {
+        self.tick_if_necessary();
+        self.m15.rate_per_sec()
+    }
+}
+
+// --- HDR-style histogram ---
+
+/// A concurrent histogram with log-linear buckets (the layout used by HdrHistogram).
+///
+/// Values below `2^precision_bits` are counted exactly. Larger values keep their top
+/// `precision_bits` significant bits, so the relative error is bounded by roughly
+/// `1 / 2^(precision_bits - 1)` across the whole `u64` range with a fixed bucket array.
+/// With the default of 7 bits that is under 1.6% using ~3.8k buckets (~30 KiB).
+#[derive(Debug)]
+struct Histogram {
+    precision_bits: u32,
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    sum: AtomicU64,
+    min: AtomicU64,
+    max: AtomicU64,
+}
+
+/// A point-in-time summary of a `Histogram`.
+#[derive(Debug, Clone, PartialEq)]
+struct HistogramSnapshot {
+    count: u64,
+    min: u64,
+    max: u64,
+    mean: f64,
+    p50: u64,
+    p90: u64,
+    p99: u64,
+    p999: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new(7)
+    }
+}
+
+impl Histogram {
+    /// Creates a histogram; `precision_bits` (2..=16) trades memory for accuracy.
+    fn new(precision_bits: u32) -> Self {
+        let s = precision_bits.clamp(2, 16);
+        let len = (1usize << s) + (64 - s as usize) * (1usize << (s - 1));
+        Self {
+            precision_bits: s,
+            buckets: (0..len).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+            min: AtomicU64::new(u64::MAX),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    fn bucket_index(&self, value: u64) -> usize {
+        let s = self.precision_bits;
+        if value < (1u64 << s) {
+            return value as usize;
+        }
+        let msb = 63 - value.leading_zeros();
+        let shift = msb - (s - 1);
+        let mantissa = (value >> shift) as usize; // in [2^(s-1), 2^s)
+        let half = 1usize << (s - 1);
+        (1usize << s) + (shift as usize - 1) * half + (mantissa - half)
+    }
+
+    /// Returns the (lowest, highest) value that maps to bucket `index`.
+    fn bucket_bounds(&self, index: usize) -> (u64, u64) {
+        let s = self.precision_bits;
+        let exact = 1usize << s;
+        if index < exact {
+            return (index as u64, index as u64);
+        }
+        let half = 1usize << (s - 1);
+        let k = index - exact;
+        let shift = (k / half + 1) as u32;
+        let mantissa = (k % half + half) as u64;
+        let low = mantissa << shift;
+        let high = low.saturating_add((1u64 << shift) - 1);
+        (low, high)
+    }
+
+    /// Records one observation (e.g. a latency in microseconds).
+    fn record(&self, value: u64) {
+        self.buckets[self.bucket_index(value)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed); // Wraps on overflow
+        self.min.fetch_min(value, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+    }
+
+    /// Records the elapsed time since `start`, in microseconds.
+    fn record_since(&self, start: Instant) {
+        self.record(start.elapsed().as_micros() as u64);
+    }
+
+    fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Estimates the value at quantile `q` (0.0..=1.0), e.g. 0.99 for p99.
+    /// Concurrent writers may make the result slightly stale, never wildly wrong.
+    fn value_at_quantile(&self, q: f64) -> u64 {
+        let total = self.count();
+        if total == 0 {
+            return 0;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= rank {
+                let (low, high) = self.bucket_bounds(i);
+                // Report the bucket midpoint, clamped to what was actually observed.
+                let mid = low + (high - low) / 2;
+                return mid.clamp(self.min.load(Ordering::Relaxed), self.max.load(Ordering::Relaxed));
+            }
+        }
+        self.max.load(Ordering::Relaxed)
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        let count = self.count();
+        HistogramSnapshot {
+            count,
+            min: if count == 0 { 0 } else { self.min.load(Ordering::Relaxed) },
+            max: self.max.load(Ordering::Relaxed),
+            mean: if count == 0 { 0.0 } else { self.sum.load(Ordering::Relaxed) as f64 / count as f64 },
+            p50: self.value_at_quantile(0.50),
+            p90: self.value_at_quantile(0.90),
+            p99: self.value_at_quantile(0.99),
+            p999: self.value_at_quantile(0.999),
+        }
+    }
+
+    /// Clears all buckets (not atomic with respect to concurrent `record` calls).
+    fn reset(&self) {
+        for b in &self.buckets {
+            b.store(0, Ordering::Relaxed);
+        }
+        self.count.store(0, Ordering::Relaxed);
+        self.sum.store(0, Ordering::Relaxed);
+        self.min.store(u64::MAX, Ordering::Relaxed);
+        self.max.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Metrics for a request handler, shared across threads with a plain `Arc` (no Mutex).
+#[derive(Debug)]
+struct RequestMetrics {
+    requests: Counter,
+    errors: Counter,
+    in_flight: Gauge,
+    throughput: RateMeter,
+    latency_us: Histogram,
+}
+
+impl RequestMetrics {
+    fn new() -> Self {
+        Self {
+            requests: Counter::default(),
+            errors: Counter::default(),
+            in_flight: Gauge::default(),
+            throughput: RateMeter::new(),
+            latency_us: Histogram::default(),
+        }
+    }
+}
+
+// Example Usage (within a main function or test)
+/*
+fn main() {
+    let metrics = Arc::new(RequestMetrics::new());
+    let mut handles = Vec::new();
+
+    // 8 threads hammer the same metrics with no locking.
+    for t in 0..8u64 {
+        let metrics = Arc::clone(&metrics);
+        handles.push(thread::spawn(move || {
+            for i in 0..10_000u64 {
+                metrics.in_flight.inc();
+                metrics.requests.inc();
+                metrics.throughput.mark(1);
+
+                // Simulated latency: mostly fast, occasionally slow.
+                // In real code: `let start = Instant::now(); ...; metrics.latency_us.record_since(start);`
+                let simulated_us = if i % 100 == 0 { 5_000 + t * 10 } else { 100 + (i % 50) };
+                metrics.latency_us.record(simulated_us);
+                if i % 250 == 0 {
+                    metrics.errors.inc();
+                }
+
+                metrics.in_flight.dec();
+            }
+        }));
+    }
+    for h in handles {
+        h.join().unwrap();
+    }
+
+    println!("Requests:       {}", metrics.requests.get());
+    println!("Errors:         {}", metrics.errors.get());
+    println!("Peak in-flight: {}", metrics.in_flight.high_water_mark());
+    println!("Mean rate:      {:.0}/s", metrics.throughput.mean_rate());
+    println!("1m EWMA:        {:.2}/s (needs >5s of data)", metrics.throughput.one_minute_rate());
+    println!("Latency (us):   {:?}", metrics.latency_us.snapshot());
+
+    // Per-interval reporting: read and reset atomically.
+    println!("Errors this interval: {}", metrics.errors.take());
+}
+*/
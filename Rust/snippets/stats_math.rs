@@ -0,0 +1,430 @@
+// This example only uses the standard library.
+//
+// Descriptive statistics over samples: summaries (mean, median, standard deviation,
+// percentiles), histograms, and simple linear regression. Two flavors:
+// * Exact functions over a slice, for data that fits in memory (benchmark runs, a CSV column).
+// * Streaming estimators (`OnlineStats`, `P2Quantile`) with O(1) memory, for unbounded
+//   streams such as request latencies in a long-running service.
+//
+// For lock-free, multi-threaded latency recording, see the `Histogram` in `stats.rs`.
+
+use std::fmt;
+
+// --- Exact statistics over slices ---
+
+fn mean(data: &[f64]) -> Option<f64> {
+    (!data.is_empty()).then(|| data.iter().sum::<f64>() / data.len() as f64)
+}
+
+/// Sample variance (divides by `n - 1`), the usual choice when `data` is a sample of a
+/// larger population (benchmark iterations, a survey). `None` for fewer than two values.
+fn variance(data: &[f64]) -> Option<f64> {
+    if data.len() < 2 {
+        return None;
+    }
+    let m = mean(data)?;
+    Some(data.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (data.len() - 1) as f64)
+}
+
+fn stddev(data: &[f64]) -> Option<f64> {
+    variance(data).map(f64::sqrt)
+}
+
+/// The `p`-th percentile (0-100) of already-sorted data, interpolating linearly between
+/// the two nearest ranks (the same method as NumPy's default and Excel's `PERCENTILE.INC`).
+fn percentile_sorted(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() || !(0.0..=100.0).contains(&p) {
+        return None;
+    }
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64))
+}
+
+/// Several percentiles at once (sorts a copy of `data` once). NaNs are ignored.
+fn percentiles(data: &[f64], ps: &[f64]) -> Vec<Option<f64>> {
+    let mut sorted: Vec<f64> = data.iter().copied().filter(|x| !x.is_nan()).collect();
+    sorted.sort_by(f64::total_cmp);
+    ps.iter().map(|&p| percentile_sorted(&sorted, p)).collect()
+}
+
+fn median(data: &[f64]) -> Option<f64> {
+    percentiles(data, &[50.0])[0]
+}
+
+/// Everything you usually want to print about a sample.
+#[derive(Debug, Clone, PartialEq)]
+struct Summary {
+    count: usize,
+    mean: f64,
+    stddev: f64,
+    min: f64,
+    p50: f64,
+    p90: f64,
+    p95: f64,
+    p99: f64,
+    max: f64,
+}
+
+/// Summarizes `data`, ignoring NaNs.
+///
+/// # Returns
+///
+/// `None` when there are no values. `stddev` is 0 for a single value.
+fn summarize(data: &[f64]) -> Option<Summary> {
+    let mut sorted: Vec<f64> = data.iter().copied().filter(|x| !x.is_nan()).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f64::total_cmp);
+    let p = |q| percentile_sorted(&sorted, q).expect("non-empty and in range");
+    Some(Summary {
+        count: sorted.len(),
+        mean: mean(&sorted)?,
+        stddev: stddev(&sorted).unwrap_or(0.0),
+        min: sorted[0],
+        p50: p(50.0),
+        p90: p(90.0),
+        p95: p(95.0),
+        p99: p(99.0),
+        max: sorted[sorted.len() - 1],
+    })
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} mean={:.3} sd={:.3} min={:.3} p50={:.3} p90={:.3} p95={:.3} p99={:.3} max={:.3}",
+            self.count, self.mean, self.stddev, self.min, self.p50, self.p90, self.p95, self.p99, self.max
+        )
+    }
+}
+
+// --- Histograms ---
+
+#[derive(Debug, Clone, PartialEq)]
+struct Bucket {
+    /// Inclusive lower bound.
+    lo: f64,
+    /// Exclusive upper bound (inclusive for the last bucket, so the maximum is counted).
+    hi: f64,
+    count: usize,
+}
+
+/// Splits the range `[min, max]` of `data` into `bins` equal-width buckets.
+fn histogram(data: &[f64], bins: usize) -> Vec<Bucket> {
+    let values: Vec<f64> = data.iter().copied().filter(|x| x.is_finite()).collect();
+    let (Some(min), Some(max)) = (
+        values.iter().copied().min_by(f64::total_cmp),
+        values.iter().copied().max_by(f64::total_cmp),
+    ) else {
+        return Vec::new();
+    };
+    let bins = bins.max(1);
+    // All values equal: one bucket holding everything.
+    let width = if max > min { (max - min) / bins as f64 } else { 1.0 };
+    let bins = if max > min { bins } else { 1 };
+    let mut buckets: Vec<Bucket> = (0..bins)
+        .map(|i| Bucket { lo: min + width * i as f64, hi: min + width * (i + 1) as f64, count: 0 })
+        .collect();
+    for x in values {
+        let i = (((x - min) / width) as usize).min(bins - 1);
+        buckets[i].count += 1;
+    }
+    buckets
+}
+
+/// Renders buckets as horizontal bars scaled to `width` characters.
+fn render_histogram(buckets: &[Bucket], width: usize) -> String {
+    let peak = buckets.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+    buckets
+        .iter()
+        .map(|b| {
+            let bar = "#".repeat(b.count * width / peak);
+            format!("[{:>9.2}, {:>9.2}) {:>6} {}\n", b.lo, b.hi, b.count, bar)
+        })
+        .collect()
+}
+
+// --- Linear regression ---
+
+/// A least-squares line `y = slope * x + intercept`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LinearFit {
+    slope: f64,
+    intercept: f64,
+    /// Fraction of the variance in `y` explained by the line (1.0 = perfect fit).
+    r_squared: f64,
+}
+
+impl LinearFit {
+    fn predict(&self, x: f64) -> f64 {
+        self.slope * x + self.intercept
+    }
+}
+
+/// Ordinary least squares over paired samples, e.g. benchmark time vs input size.
+///
+/// # Returns
+///
+/// `None` if there are fewer than two points or all `x` values are equal.
+fn linear_regression(points: impl IntoIterator<Item = (f64, f64)>) -> Option<LinearFit> {
+    let points: Vec<(f64, f64)> = points.into_iter().collect();
+    let n = points.len() as f64;
+    if points.len() < 2 {
+        return None;
+    }
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in &points {
+        sxx += (x - mean_x).powi(2);
+        sxy += (x - mean_x) * (y - mean_y);
+        syy += (y - mean_y).powi(2);
+    }
+    if sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    let r_squared = if syy == 0.0 { 1.0 } else { (sxy * sxy) / (sxx * syy) };
+    Some(LinearFit { slope, intercept: mean_y - slope * mean_x, r_squared })
+}
+
+// --- Streaming: mean / variance / min / max ---
+
+/// Running count, mean, variance, min, and max in O(1) memory (Welford's algorithm, which
+/// stays accurate where the naive sum-of-squares formula loses precision).
+#[derive(Debug, Clone, Copy, Default)]
+struct OnlineStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl OnlineStats {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            return;
+        }
+        self.count += 1;
+        if self.count == 1 {
+            self.min = x;
+            self.max = x;
+        } else {
+            self.min = self.min.min(x);
+            self.max = self.max.max(x);
+        }
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Combines two partial results, e.g. from per-thread or per-shard accumulators.
+    fn merge(&mut self, other: &OnlineStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count as f64 * other.count as f64) / count as f64;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.count = count;
+    }
+
+    fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// Sample variance, as `variance()` above.
+    fn variance(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.m2 / (self.count - 1) as f64)
+    }
+
+    fn stddev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+}
+
+impl Extend<f64> for OnlineStats {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+impl FromIterator<f64> for OnlineStats {
+    fn from_iter<I: IntoIterator<Item = f64>>(iter: I) -> Self {
+        let mut stats = OnlineStats::new();
+        stats.extend(iter);
+        stats
+    }
+}
+
+// --- Streaming: one quantile ---
+
+/// Estimates one quantile of a stream with five markers and no stored samples
+/// (the P² algorithm by Jain & Chlamtac, 1985).
+///
+/// Accuracy is typically within a few percent for smooth distributions after a few hundred
+/// values; use one estimator per quantile you track (p50, p99, ...).
+#[derive(Debug, Clone)]
+struct P2Quantile {
+    p: f64,
+    /// Marker heights (the estimates) and actual / desired marker positions.
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+    /// The first five values, before the markers are initialized.
+    initial: Vec<f64>,
+    count: u64,
+}
+
+impl P2Quantile {
+    /// Tracks the `quantile` (0.0-1.0, e.g. 0.99 for p99).
+    fn new(quantile: f64) -> Self {
+        let p = quantile.clamp(0.0, 1.0);
+        Self {
+            p,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+            initial: Vec::with_capacity(5),
+            count: 0,
+        }
+    }
+
+    fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            return;
+        }
+        self.count += 1;
+        if self.count <= 5 {
+            self.initial.push(x);
+            if self.count == 5 {
+                self.initial.sort_by(f64::total_cmp);
+                self.heights.copy_from_slice(&self.initial);
+            }
+            return;
+        }
+
+        // Find the cell containing x, extending the extremes if needed.
+        let h = &mut self.heights;
+        let k = if x < h[0] {
+            h[0] = x;
+            0
+        } else if x >= h[4] {
+            h[4] = x;
+            3
+        } else {
+            (0..4).find(|&i| x < h[i + 1]).unwrap_or(3)
+        };
+        for position in &mut self.positions[k + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(&self.increments) {
+            *desired += increment;
+        }
+
+        // Nudge the three middle markers toward their desired positions.
+        for i in 1..4 {
+            let d = self.desired[i] - self.positions[i];
+            let (n, q) = (&mut self.positions, &mut self.heights);
+            if (d >= 1.0 && n[i + 1] - n[i] > 1.0) || (d <= -1.0 && n[i - 1] - n[i] < -1.0) {
+                let d = d.signum();
+                let parabolic = q[i]
+                    + d / (n[i + 1] - n[i - 1])
+                        * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                            + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]));
+                q[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    let j = if d > 0.0 { i + 1 } else { i - 1 };
+                    q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                n[i] += d;
+            }
+        }
+    }
+
+    /// The current estimate; exact while fewer than five values have been seen.
+    fn estimate(&self) -> Option<f64> {
+        if self.count >= 5 {
+            return Some(self.heights[2]);
+        }
+        let mut sorted = self.initial.clone();
+        sorted.sort_by(f64::total_cmp);
+        percentile_sorted(&sorted, self.p * 100.0)
+    }
+}
+
+// Example Usage
+/*
+fn main() {
+    // Deterministic pseudo-random latencies (ms): mostly 10-30ms with a slow tail.
+    let mut seed = 42u64;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % 10_000) as f64 / 10_000.0
+    };
+    let latencies: Vec<f64> = (0..10_000)
+        .map(|_| {
+            let base = 10.0 + 20.0 * next();
+            if next() < 0.05 { base + 200.0 * next() } else { base }
+        })
+        .collect();
+
+    println!("{}", summarize(&latencies).unwrap());
+    println!("median={:.3} sd={:.3}", median(&latencies).unwrap(), stddev(&latencies).unwrap());
+    println!("{:?}", percentiles(&[1.0, 2.0, 3.0, 4.0], &[0.0, 25.0, 50.0, 100.0, 101.0]));
+    println!("{:?}\n", summarize(&[]));
+
+    print!("{}", render_histogram(&histogram(&latencies, 8), 40));
+
+    // Streaming: same answers without keeping the samples.
+    let mut online: OnlineStats = latencies[..5_000].iter().copied().collect();
+    let other: OnlineStats = latencies[5_000..].iter().copied().collect();
+    online.merge(&other);
+    let (mut p50, mut p99) = (P2Quantile::new(0.50), P2Quantile::new(0.99));
+    for &x in &latencies {
+        p50.push(x);
+        p99.push(x);
+    }
+    println!(
+        "\nstreaming: n={} mean={:.3} sd={:.3} min={:.3} max={:.3} p50~{:.3} p99~{:.3}",
+        online.count,
+        online.mean().unwrap(),
+        online.stddev().unwrap(),
+        online.min,
+        online.max,
+        p50.estimate().unwrap(),
+        p99.estimate().unwrap()
+    );
+
+    // Regression: how does run time scale with input size?
+    let runs = [(1_000.0, 2.1), (2_000.0, 4.0), (4_000.0, 8.3), (8_000.0, 16.1), (16_000.0, 32.4)];
+    let fit = linear_regression(runs).unwrap();
+    println!(
+        "\ntime_ms = {:.5} * n + {:.3} (r^2 = {:.4}); predicted for n=32000: {:.1}ms",
+        fit.slope, fit.intercept, fit.r_squared, fit.predict(32_000.0)
+    );
+    println!("{:?}", linear_regression([(1.0, 2.0), (1.0, 3.0)]));
+}
+*/
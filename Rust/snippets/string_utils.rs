@@ -0,0 +1,255 @@
+// Note: This example requires adding the `unicode-segmentation`, `unicode-width`,
+// `unicode-normalization`, `unicase`, and `deunicode` crates to your Cargo.toml:
+// [dependencies]
+// unicode-segmentation = "1"
+// unicode-width = "0.2"
+// unicode-normalization = "0.1"
+// unicase = "2"
+// deunicode = "1"
+//
+// `&s[..n]` slices bytes and panics inside a multibyte character; `s.chars().take(n)` avoids
+// the panic but still splits "é" written as e + combining accent, or a family emoji, into
+// pieces. User-visible text should be cut on grapheme clusters (what a reader sees as one
+// character) and measured in terminal columns (CJK and most emoji are two columns wide).
+
+use deunicode::deunicode;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use unicase::UniCase;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// --- Truncation ---
+
+/// Shortens `s` to at most `max` grapheme clusters, ending with `ellipsis` when cut.
+///
+/// # Arguments
+///
+/// * `s` - The text to shorten.
+/// * `max` - Maximum number of graphemes in the result, including the ellipsis.
+/// * `ellipsis` - Appended when text is removed, e.g. `"…"` or `"..."`.
+///
+/// # Returns
+///
+/// The input unchanged (borrowed) when it already fits.
+fn truncate_graphemes<'a>(s: &'a str, max: usize, ellipsis: &str) -> Cow<'a, str> {
+    if s.graphemes(true).nth(max).is_none() {
+        return Cow::Borrowed(s);
+    }
+    let keep = max.saturating_sub(ellipsis.graphemes(true).count());
+    let end = s.grapheme_indices(true).nth(keep).map(|(i, _)| i).unwrap_or(s.len());
+    Cow::Owned(format!("{}{}", &s[..end], ellipsis))
+}
+
+/// Shortens `s` to fit in `max_bytes` bytes without splitting a grapheme, e.g. for a
+/// database column or header with a byte limit. No ellipsis is added.
+fn truncate_bytes(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let end = s
+        .grapheme_indices(true)
+        .map(|(i, g)| i + g.len())
+        .take_while(|&end| end <= max_bytes)
+        .last()
+        .unwrap_or(0);
+    &s[..end]
+}
+
+// --- Display width ---
+
+/// Number of terminal columns `s` occupies (CJK and emoji count as 2, combining marks as 0).
+fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// Shortens `s` to at most `max_width` terminal columns, ending with `ellipsis` when cut.
+///
+/// Never splits a grapheme; if a wide character would overflow by one column it is
+/// dropped, so the result may be one column narrower than `max_width`.
+fn truncate_to_width<'a>(s: &'a str, max_width: usize, ellipsis: &str) -> Cow<'a, str> {
+    if display_width(s) <= max_width {
+        return Cow::Borrowed(s);
+    }
+    let budget = max_width.saturating_sub(display_width(ellipsis));
+    let mut used = 0;
+    let mut end = 0;
+    for (i, g) in s.grapheme_indices(true) {
+        let w = display_width(g);
+        if used + w > budget {
+            break;
+        }
+        used += w;
+        end = i + g.len();
+    }
+    Cow::Owned(format!("{}{}", &s[..end], ellipsis))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Pads `s` with spaces to exactly `width` columns, truncating with `…` if it is wider.
+///
+/// `format!("{:<10}", s)` pads by `char` count, which misaligns columns containing
+/// CJK text, emoji, or combining accents; this pads by display width.
+fn pad_to_width(s: &str, width: usize, align: Align) -> String {
+    let text = truncate_to_width(s, width, "…");
+    let fill = width.saturating_sub(display_width(&text));
+    let (left, right) = match align {
+        Align::Left => (0, fill),
+        Align::Right => (fill, 0),
+        Align::Center => (fill / 2, fill - fill / 2),
+    };
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+/// Renders rows as aligned columns separated by two spaces, sizing each column to its
+/// widest cell (capped at `max_col_width`). The first row is treated like any other.
+fn format_columns(rows: &[Vec<&str>], max_col_width: usize) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| {
+            rows.iter()
+                .filter_map(|row| row.get(c))
+                .map(|cell| display_width(cell).min(max_col_width))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut out = String::new();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &w)| pad_to_width(cell, w, Align::Left))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+// --- Normalization ---
+
+/// Canonical composition (NFC): "e" + U+0301 becomes "é". Use before storing or comparing
+/// user input, since macOS file names and some keyboards produce the decomposed form.
+fn nfc(s: &str) -> Cow<'_, str> {
+    match is_nfc_quick(s.chars()) {
+        IsNormalized::Yes => Cow::Borrowed(s),
+        _ => Cow::Owned(s.nfc().collect()),
+    }
+}
+
+/// Compatibility composition (NFKC): also folds look-alikes such as "ﬁ" -> "fi",
+/// full-width "Ａ" -> "A", and "²" -> "2". Use for identifiers, usernames, and search keys,
+/// not for text you display back, since it loses formatting distinctions.
+fn nfkc(s: &str) -> String {
+    s.nfkc().collect()
+}
+
+// --- Case-insensitive comparison ---
+
+/// Unicode case-insensitive equality using full case folding ("STRASSE" == "straße"),
+/// after NFC so precomposed and decomposed accents compare equal.
+///
+/// `eq_ignore_ascii_case` only folds A-Z; `to_lowercase()` on both sides allocates and
+/// misses multi-character folds.
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    UniCase::new(nfc(a)) == UniCase::new(nfc(b))
+}
+
+/// Case-insensitive ordering for sorting names: `names.sort_by(|a, b| cmp_ignore_case(a, b))`.
+/// Equal-ignoring-case strings fall back to a byte comparison so the order is stable.
+/// This is not locale-aware collation: accented letters sort after "z".
+fn cmp_ignore_case(a: &str, b: &str) -> Ordering {
+    UniCase::new(nfc(a)).cmp(&UniCase::new(nfc(b))).then_with(|| a.cmp(b))
+}
+
+// --- Slugs ---
+
+/// A URL- and file-name-safe slug: ASCII lowercase letters, digits, and single hyphens.
+///
+/// Non-Latin text is transliterated ("Crème Brûlée" -> "creme-brulee", "北京" -> "bei-jing")
+/// rather than dropped. The result is cut at a hyphen when possible to stay within
+/// `max_len` bytes, and may be empty if the input has no letters or digits.
+fn slugify(s: &str, max_len: usize) -> String {
+    let ascii = deunicode(s);
+    let mut slug = String::with_capacity(ascii.len());
+    for c in ascii.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    if slug.len() > max_len {
+        // The slug is ASCII, so any byte index is a char boundary.
+        let cut = slug[..=max_len].rfind('-').filter(|&i| i > 0).unwrap_or(max_len);
+        slug.truncate(cut);
+        while slug.ends_with('-') {
+            slug.pop();
+        }
+    }
+    slug
+}
+
+// Example Usage
+/*
+fn main() {
+    let samples = [
+        "Hello, world!",
+        "Cafe\u{301} au lait", // "é" as e + combining acute accent
+        "日本語のテキストです",
+        "Family: 👨‍👩‍👧‍👦 and flags 🇩🇪🇯🇵",
+    ];
+    for s in samples {
+        println!(
+            "{:?}: {} bytes, {} chars, {} graphemes, {} columns",
+            s,
+            s.len(),
+            s.chars().count(),
+            s.graphemes(true).count(),
+            display_width(s)
+        );
+        println!("  truncate_graphemes(8): {}", truncate_graphemes(s, 8, "…"));
+        println!("  truncate_to_width(10): {}", truncate_to_width(s, 10, "…"));
+        println!("  truncate_bytes(12):    {}", truncate_bytes(s, 12));
+    }
+
+    println!();
+    let rows = vec![
+        vec!["NAME", "CITY", "NOTE"],
+        vec!["Zoë", "Zürich", "ok"],
+        vec!["山田太郎", "東京", "wide characters"],
+        vec!["Ana 🚀", "São Paulo", "emoji"],
+        vec!["A very long name that will not fit", "Berlin", "truncated"],
+    ];
+    print!("{}", format_columns(&rows, 16));
+    println!("[{}] [{}]", pad_to_width("東京", 8, Align::Right), pad_to_width("東京", 8, Align::Center));
+
+    println!();
+    let composed = "Caf\u{e9}";
+    let decomposed = "Cafe\u{301}";
+    println!("composed == decomposed: {}", composed == decomposed);
+    println!("nfc(composed) == nfc(decomposed): {}", nfc(composed) == nfc(decomposed));
+    println!("nfkc: {:?} -> {:?}", "ﬁle Ｎｏ.²", nfkc("ﬁle Ｎｏ.²"));
+    println!("eq_ignore_case(\"STRASSE\", \"straße\"): {}", eq_ignore_case("STRASSE", "straße"));
+    println!("eq_ignore_case(\"CAFÉ\", decomposed): {}", eq_ignore_case("CAFÉ", decomposed));
+    let mut names = vec!["émile", "Zoe", "adam", "Émile", "zoe", "Bob"];
+    names.sort_by(|a, b| cmp_ignore_case(a, b));
+    println!("sorted: {:?}", names);
+
+    println!();
+    for title in ["Crème Brûlée Recipe!", "  Rust -- 2024 Edition ", "北京 Travel Guide", "Ünïcödé & Émoji 🎉", "???"] {
+        println!("{:?} -> {:?}", title, slugify(title, 20));
+    }
+}
+*/
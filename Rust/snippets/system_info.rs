@@ -0,0 +1,429 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// sysinfo = "0.38"
+//
+// Host and process resource reporting on top of `sysinfo`, which reads `/proc` on Linux and
+// the native APIs on macOS and Windows:
+// * `host_info()` gathers the facts that don't change while the process runs: hostname, OS,
+//   kernel, architecture, CPU model, and logical/physical core counts. Log it once at startup.
+// * `SystemMonitor::sample()` returns a `Snapshot`: CPU usage (overall and per core), memory
+//   and swap (plus the cgroup limit inside a container), space on each mounted disk, the load
+//   average (not available on Windows), and RSS/CPU for the processes being watched.
+// * `Snapshot::metrics()` flattens a reading into `(name, value)` pairs with Prometheus-style
+//   names and labels, ready for a metrics registry such as the `Gauge`s in `stats.rs` (which
+//   hold integers, so scale percentages, e.g. to basis points).
+// * `Sampler::spawn` samples on a background thread at a fixed interval, hands each reading to
+//   a publish callback, and keeps the latest one for health or status endpoints (`health.rs`).
+//
+// CPU usage is measured between two refreshes, so the first reading after creating a monitor
+// compares against nothing and is meaningless; `SystemMonitor::new` takes a baseline and waits
+// `sysinfo::MINIMUM_CPU_UPDATE_INTERVAL` so the first `sample()` is already useful. Per-process
+// CPU is in percent of one core, so a busy process on 8 cores can report up to 800%.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+use sysinfo::{Disks, Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+// --- Static host facts ---
+
+#[derive(Debug, Clone)]
+struct HostInfo {
+    hostname: String,
+    os: String,
+    kernel: String,
+    arch: String,
+    cpu_brand: String,
+    logical_cpus: usize,
+    physical_cores: Option<usize>,
+    total_memory: u64,
+    uptime: Duration,
+}
+
+/// Facts about the machine, gathered once.
+fn host_info() -> HostInfo {
+    let mut sys = System::new();
+    sys.refresh_cpu_list(sysinfo::CpuRefreshKind::nothing());
+    sys.refresh_memory();
+    HostInfo {
+        hostname: System::host_name().unwrap_or_else(|| "unknown".to_string()),
+        os: System::long_os_version().unwrap_or_else(|| std::env::consts::OS.to_string()),
+        kernel: System::kernel_version().unwrap_or_default(),
+        arch: System::cpu_arch(),
+        cpu_brand: sys.cpus().first().map(|cpu| cpu.brand().trim().to_string()).unwrap_or_default(),
+        logical_cpus: sys.cpus().len(),
+        physical_cores: System::physical_core_count(),
+        total_memory: sys.total_memory(),
+        uptime: Duration::from_secs(System::uptime()),
+    }
+}
+
+impl fmt::Display for HostInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}, kernel {}, {}): {} CPUs", self.hostname, self.os, self.kernel, self.arch, self.logical_cpus)?;
+        if let Some(cores) = self.physical_cores {
+            write!(f, " / {} cores", cores)?;
+        }
+        write!(f, " [{}], {} memory, up {}", self.cpu_brand, Bytes(self.total_memory), Uptime(self.uptime))
+    }
+}
+
+/// Binary units for logs; see `humanize.rs` for the full formatter.
+struct Bytes(u64);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let mut value = self.0 as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            write!(f, "{} B", self.0)
+        } else {
+            write!(f, "{:.1} {}", value, UNITS[unit])
+        }
+    }
+}
+
+struct Uptime(Duration);
+
+impl fmt::Display for Uptime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0.as_secs();
+        match (secs / 86_400, secs / 3600 % 24, secs / 60 % 60) {
+            (0, 0, m) => write!(f, "{}m", m),
+            (0, h, m) => write!(f, "{}h{}m", h, m),
+            (d, h, _) => write!(f, "{}d{}h", d, h),
+        }
+    }
+}
+
+// --- Readings ---
+
+#[derive(Debug, Clone)]
+struct CpuReading {
+    /// Across all CPUs, 0-100.
+    usage_percent: f32,
+    per_cpu: Vec<f32>,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryReading {
+    total: u64,
+    used: u64,
+    available: u64,
+    swap_total: u64,
+    swap_used: u64,
+    /// The container's memory limit and usage, when running under a cgroup limit (Linux only).
+    cgroup_limit: Option<u64>,
+    cgroup_rss: Option<u64>,
+}
+
+impl MemoryReading {
+    /// Used memory as a percentage of the tighter of the host total and the cgroup limit.
+    fn used_percent(&self) -> f64 {
+        let (used, limit) = match (self.cgroup_rss, self.cgroup_limit) {
+            (Some(rss), Some(limit)) if limit < self.total => (rss, limit),
+            _ => (self.used, self.total),
+        };
+        if limit == 0 {
+            0.0
+        } else {
+            used as f64 * 100.0 / limit as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DiskReading {
+    mount_point: PathBuf,
+    file_system: String,
+    total: u64,
+    available: u64,
+}
+
+impl DiskReading {
+    fn used_percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.total - self.available) as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LoadAverage {
+    one: f64,
+    five: f64,
+    fifteen: f64,
+}
+
+#[derive(Debug, Clone)]
+struct ProcessReading {
+    pid: u32,
+    name: String,
+    /// Resident set size: the physical memory the process is using right now.
+    rss: u64,
+    virtual_memory: u64,
+    /// Percent of one core since the previous sample.
+    cpu_percent: f32,
+    run_time: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    taken_at: SystemTime,
+    cpu: CpuReading,
+    memory: MemoryReading,
+    disks: Vec<DiskReading>,
+    /// `None` on Windows, which has no load average.
+    load: Option<LoadAverage>,
+    /// Watched processes that are still alive.
+    processes: Vec<ProcessReading>,
+}
+
+impl Snapshot {
+    /// The reading as named gauge values, e.g. `system_memory_used_bytes` or
+    /// `process_resident_memory_bytes{pid="42",name="myapp"}`.
+    fn metrics(&self) -> Vec<(String, f64)> {
+        let mut out = vec![
+            ("system_cpu_usage_percent".to_string(), self.cpu.usage_percent as f64),
+            ("system_cpu_count".to_string(), self.cpu.per_cpu.len() as f64),
+            ("system_memory_total_bytes".to_string(), self.memory.total as f64),
+            ("system_memory_used_bytes".to_string(), self.memory.used as f64),
+            ("system_memory_available_bytes".to_string(), self.memory.available as f64),
+            ("system_swap_used_bytes".to_string(), self.memory.swap_used as f64),
+        ];
+        if let Some(limit) = self.memory.cgroup_limit {
+            out.push(("cgroup_memory_limit_bytes".to_string(), limit as f64));
+        }
+        if let Some(load) = self.load {
+            out.push(("system_load1".to_string(), load.one));
+            out.push(("system_load5".to_string(), load.five));
+            out.push(("system_load15".to_string(), load.fifteen));
+        }
+        for disk in &self.disks {
+            let mount = disk.mount_point.display();
+            out.push((format!("system_disk_total_bytes{{mount=\"{}\"}}", mount), disk.total as f64));
+            out.push((format!("system_disk_available_bytes{{mount=\"{}\"}}", mount), disk.available as f64));
+        }
+        for process in &self.processes {
+            let labels = format!("{{pid=\"{}\",name=\"{}\"}}", process.pid, process.name);
+            out.push((format!("process_resident_memory_bytes{}", labels), process.rss as f64));
+            out.push((format!("process_virtual_memory_bytes{}", labels), process.virtual_memory as f64));
+            out.push((format!("process_cpu_usage_percent{}", labels), process.cpu_percent as f64));
+        }
+        out
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cpu {:.1}%", self.cpu.usage_percent)?;
+        if let Some(load) = self.load {
+            write!(f, " (load {:.2} {:.2} {:.2})", load.one, load.five, load.fifteen)?;
+        }
+        write!(
+            f,
+            ", memory {} / {} ({:.0}%), swap {}",
+            Bytes(self.memory.used),
+            Bytes(self.memory.total),
+            self.memory.used_percent(),
+            Bytes(self.memory.swap_used)
+        )?;
+        for disk in &self.disks {
+            write!(f, "\n  disk {} ({}): {} free of {}", disk.mount_point.display(), disk.file_system, Bytes(disk.available), Bytes(disk.total))?;
+        }
+        for p in &self.processes {
+            write!(f, "\n  pid {} {}: rss {}, cpu {:.1}%, up {}", p.pid, p.name, Bytes(p.rss), p.cpu_percent, Uptime(p.run_time))?;
+        }
+        Ok(())
+    }
+}
+
+// --- Sampling ---
+
+/// Keeps the `sysinfo` state between samples, which CPU usage needs.
+struct SystemMonitor {
+    sys: System,
+    disks: Disks,
+    watched: Vec<Pid>,
+}
+
+impl SystemMonitor {
+    /// Creates a monitor with a CPU baseline. Blocks for `MINIMUM_CPU_UPDATE_INTERVAL`
+    /// (about 200ms) so the first `sample()` has a real usage figure.
+    fn new() -> Self {
+        let mut sys = System::new();
+        sys.refresh_cpu_usage();
+        let monitor = SystemMonitor { sys, disks: Disks::new_with_refreshed_list(), watched: Vec::new() };
+        thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        monitor
+    }
+
+    /// Adds a process to report on; the current process is `std::process::id()`.
+    fn watch(mut self, pid: u32) -> Self {
+        let pid = Pid::from_u32(pid);
+        self.watched.push(pid);
+        self.refresh_processes();
+        self
+    }
+
+    fn refresh_processes(&mut self) {
+        if !self.watched.is_empty() {
+            let kind = ProcessRefreshKind::nothing().with_memory().with_cpu();
+            self.sys.refresh_processes_specifics(ProcessesToUpdate::Some(&self.watched), true, kind);
+        }
+    }
+
+    /// Refreshes everything and returns a reading.
+    fn sample(&mut self) -> Snapshot {
+        self.sys.refresh_cpu_usage();
+        self.sys.refresh_memory();
+        // Re-reads the mount table: new mounts appear and, with `true`, unmounted ones go away.
+        self.disks.refresh(true);
+        self.refresh_processes();
+
+        let cgroup = self.sys.cgroup_limits();
+        let load = System::load_average();
+        Snapshot {
+            taken_at: SystemTime::now(),
+            cpu: CpuReading {
+                usage_percent: self.sys.global_cpu_usage(),
+                per_cpu: self.sys.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
+            },
+            memory: MemoryReading {
+                total: self.sys.total_memory(),
+                used: self.sys.used_memory(),
+                available: self.sys.available_memory(),
+                swap_total: self.sys.total_swap(),
+                swap_used: self.sys.used_swap(),
+                cgroup_limit: cgroup.as_ref().map(|c| c.total_memory),
+                cgroup_rss: cgroup.as_ref().map(|c| c.rss),
+            },
+            disks: self
+                .disks
+                .list()
+                .iter()
+                .map(|disk| DiskReading {
+                    mount_point: disk.mount_point().to_path_buf(),
+                    file_system: disk.file_system().to_string_lossy().into_owned(),
+                    total: disk.total_space(),
+                    available: disk.available_space(),
+                })
+                .collect(),
+            load: (!cfg!(windows)).then_some(LoadAverage { one: load.one, five: load.five, fifteen: load.fifteen }),
+            processes: self
+                .watched
+                .iter()
+                .filter_map(|&pid| self.sys.process(pid))
+                .map(|p| ProcessReading {
+                    pid: p.pid().as_u32(),
+                    name: p.name().to_string_lossy().into_owned(),
+                    rss: p.memory(),
+                    virtual_memory: p.virtual_memory(),
+                    cpu_percent: p.cpu_usage(),
+                    run_time: Duration::from_secs(p.run_time()),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Samples on a background thread until stopped or dropped.
+struct Sampler {
+    latest: Arc<Mutex<Option<Snapshot>>>,
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<SystemMonitor>>,
+}
+
+impl Sampler {
+    /// Starts sampling every `interval`, beginning immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `monitor` - What to sample; returned by `stop`.
+    /// * `interval` - Time between samples. Values under `MINIMUM_CPU_UPDATE_INTERVAL` give
+    ///   noisy CPU figures.
+    /// * `publish` - Called on the sampler thread with each reading, e.g. to update gauges.
+    fn spawn(mut monitor: SystemMonitor, interval: Duration, mut publish: impl FnMut(&Snapshot) + Send + 'static) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let thread = thread::spawn({
+            let latest = Arc::clone(&latest);
+            move || loop {
+                let snapshot = monitor.sample();
+                publish(&snapshot);
+                *latest.lock().unwrap() = Some(snapshot);
+                match stop_rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => return monitor,
+                }
+            }
+        });
+        Sampler { latest, stop: Some(stop_tx), thread: Some(thread) }
+    }
+
+    /// The most recent reading, for status endpoints; `None` until the first sample lands.
+    fn latest(&self) -> Option<Snapshot> {
+        self.latest.lock().unwrap().clone()
+    }
+
+    /// Stops sampling and returns the monitor.
+    fn stop(mut self) -> SystemMonitor {
+        self.stop.take();
+        self.thread.take().expect("thread is only taken here").join().expect("sampler thread panicked")
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        // Disconnecting the channel wakes the thread; no need to wait for it.
+        self.stop.take();
+    }
+}
+
+// Example Usage
+/*
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+fn main() {
+    println!("{}", host_info());
+
+    let mut monitor = SystemMonitor::new().watch(std::process::id());
+    // Burn some CPU and memory so the process numbers move.
+    let ballast: Vec<u8> = vec![1; 64 * 1024 * 1024];
+    let started = Instant::now();
+    let mut x = 0u64;
+    while started.elapsed() < Duration::from_millis(300) {
+        x = x.wrapping_mul(6364136223846793005).wrapping_add(ballast[x as usize % ballast.len()] as u64);
+    }
+    println!("{}", monitor.sample());
+
+    // Stands in for a metrics registry such as the gauges in `stats.rs`.
+    let registry: Arc<Mutex<BTreeMap<String, f64>>> = Arc::default();
+    let sampler = Sampler::spawn(monitor, Duration::from_millis(250), {
+        let registry = Arc::clone(&registry);
+        move |snapshot| registry.lock().unwrap().extend(snapshot.metrics())
+    });
+    thread::sleep(Duration::from_millis(800));
+    if let Some(latest) = sampler.latest() {
+        let age = latest.taken_at.elapsed().unwrap_or_default();
+        println!("latest reading is {:?} old: cpu {:.1}%", age, latest.cpu.usage_percent);
+    }
+    sampler.stop();
+
+    for (name, value) in registry.lock().unwrap().iter().filter(|(name, _)| !name.starts_with("system_disk")).take(14) {
+        println!("{} {}", name, value);
+    }
+    println!("(x = {})", x % 10);
+}
+*/
@@ -0,0 +1,328 @@
+// Note: This example requires adding the `tokio`, `tokio-util`, `futures`, `bytes`, `serde`,
+// and `serde_json` crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+// tokio-util = { version = "0.7", features = ["codec"] }
+// futures = "0.3"
+// bytes = "1"
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+//
+// TCP is a byte stream, not a message stream: one `write` can arrive as several reads and
+// several writes as one. A codec restores message boundaries:
+//   LinesCodec            - newline-delimited text (human-friendly, telnet/nc-compatible)
+//   LengthDelimitedCodec  - 4-byte big-endian length prefix + payload (binary-safe)
+
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio_util::codec::{Decoder, Encoder, Framed, LengthDelimitedCodec, LinesCodec};
+
+/// Longest line accepted by the line codec; protects the server from unbounded buffering.
+const MAX_LINE: usize = 8 * 1024;
+/// Largest frame accepted by the length-prefixed codec.
+const MAX_FRAME: usize = 1024 * 1024;
+
+fn line_codec() -> LinesCodec {
+    LinesCodec::new_with_max_length(MAX_LINE)
+}
+
+fn frame_codec() -> LengthDelimitedCodec {
+    LengthDelimitedCodec::builder()
+        .length_field_length(4)
+        .max_frame_length(MAX_FRAME)
+        .new_codec()
+}
+
+fn to_io<E: Display>(e: E) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+// --- Server ---
+
+/// Runs a TCP server that decodes requests with a codec and answers each one via `handler`.
+///
+/// Each connection gets its own task. When `shutdown` flips to `true` the listener stops
+/// accepting, every connection finishes the request it is handling and closes, and the
+/// function returns once all connection tasks are done (or `drain_timeout` passes).
+///
+/// # Arguments
+///
+/// * `listener` - A bound listener (bind to port 0 in tests; see `net_diagnostics.rs`).
+/// * `make_codec` - Creates a fresh codec per connection (codecs hold per-stream state).
+/// * `handler` - Maps a request to `Some(response)`, or `None` to close the connection.
+/// * `shutdown` - Flip to `true` to stop the server.
+/// * `drain_timeout` - How long to wait for open connections before aborting them.
+async fn run_server<C, Out, H>(
+    listener: TcpListener,
+    make_codec: impl Fn() -> C + Send,
+    handler: H,
+    mut shutdown: watch::Receiver<bool>,
+    drain_timeout: Duration,
+) -> io::Result<()>
+where
+    C: Decoder + Encoder<Out> + Send + 'static,
+    <C as Decoder>::Item: Send,
+    <C as Decoder>::Error: Display + Send,
+    <C as Encoder<Out>>::Error: Display,
+    Out: Send + 'static,
+    H: Fn(SocketAddr, <C as Decoder>::Item) -> Option<Out> + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    let mut connections = JoinSet::new();
+    let connection_shutdown = shutdown.clone();
+
+    loop {
+        // `select!` only picks the event; the work happens after it, so no borrow of
+        // `shutdown` is held across the awaits below.
+        let accepted = tokio::select! {
+            _ = shutdown.wait_for(|stop| *stop) => break,
+            accepted = listener.accept() => accepted,
+            // Reap finished connection tasks so the set doesn't grow forever.
+            Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+        };
+        let (stream, peer) = match accepted {
+            Ok(conn) => conn,
+            Err(e) => {
+                // E.g. EMFILE (too many open files): back off instead of spinning.
+                eprintln!("accept failed: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let _ = stream.set_nodelay(true);
+        let framed = Framed::new(stream, make_codec());
+        let handler = Arc::clone(&handler);
+        let shutdown = connection_shutdown.clone();
+        connections.spawn(async move {
+            if let Err(e) = handle_connection(framed, peer, handler, shutdown).await {
+                eprintln!("[{}] connection error: {}", peer, e);
+            }
+        });
+    }
+
+    drop(listener); // Stop accepting immediately.
+    let drained = tokio::time::timeout(drain_timeout, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        eprintln!("{} connection(s) did not close in time; aborting", connections.len());
+        connections.shutdown().await;
+    }
+    Ok(())
+}
+
+async fn handle_connection<C, Out, H>(
+    mut framed: Framed<TcpStream, C>,
+    peer: SocketAddr,
+    handler: Arc<H>,
+    mut shutdown: watch::Receiver<bool>,
+) -> io::Result<()>
+where
+    C: Decoder + Encoder<Out>,
+    <C as Decoder>::Error: Display,
+    <C as Encoder<Out>>::Error: Display,
+    H: Fn(SocketAddr, <C as Decoder>::Item) -> Option<Out>,
+{
+    loop {
+        let request = tokio::select! {
+            _ = shutdown.wait_for(|stop| *stop) => break,
+            next = framed.next() => match next {
+                Some(Ok(request)) => request,
+                Some(Err(e)) => return Err(to_io(e)), // Malformed frame, line too long, I/O error
+                None => break, // Peer closed the connection
+            },
+        };
+        match handler(peer, request) {
+            Some(response) => framed.send(response).await.map_err(to_io)?,
+            None => break,
+        }
+    }
+    // Flush anything buffered and send FIN.
+    SinkExt::<Out>::close(&mut framed).await.map_err(to_io)
+}
+
+/// A small line-based command protocol: `PING`, `ECHO <text>`, `UPPER <text>`, `QUIT`.
+fn line_commands(peer: SocketAddr, line: String) -> Option<String> {
+    let (command, arg) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+    match command.to_ascii_uppercase().as_str() {
+        "PING" => Some("PONG".to_string()),
+        "ECHO" => Some(arg.to_string()),
+        "UPPER" => Some(arg.to_uppercase()),
+        "WHOAMI" => Some(peer.to_string()),
+        "QUIT" => None,
+        "" => Some("ERR empty command".to_string()),
+        other => Some(format!("ERR unknown command '{}'", other)),
+    }
+}
+
+// --- Typed binary messages over length-prefixed frames ---
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Request {
+    Add { a: i64, b: i64 },
+    Store { key: String, bytes: Vec<u8> },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Reply {
+    Sum(i64),
+    Stored { key: String, len: usize },
+    Error(String),
+}
+
+/// Encodes a value as one length-prefixed frame (JSON here; bincode/postcard work the same).
+fn encode_frame<T: Serialize>(value: &T) -> io::Result<Bytes> {
+    serde_json::to_vec(value).map(Bytes::from).map_err(to_io)
+}
+
+fn decode_frame<T: DeserializeOwned>(frame: &[u8]) -> io::Result<T> {
+    serde_json::from_slice(frame).map_err(to_io)
+}
+
+fn binary_handler(_peer: SocketAddr, frame: bytes::BytesMut) -> Option<Bytes> {
+    let reply = match decode_frame::<Request>(&frame) {
+        Ok(Request::Add { a, b }) => Reply::Sum(a.saturating_add(b)),
+        Ok(Request::Store { key, bytes }) => Reply::Stored { key, len: bytes.len() },
+        Err(e) => Reply::Error(e.to_string()),
+    };
+    encode_frame(&reply).ok()
+}
+
+// --- Client ---
+
+/// A line-protocol client that reconnects transparently.
+///
+/// Each `request` makes sure a connection exists (reconnecting with exponential backoff
+/// if needed), sends one line, and waits for one reply line. If the connection breaks
+/// mid-request it reconnects and retries once, so only use it for idempotent commands.
+struct ReconnectingClient {
+    addr: String,
+    conn: Option<Framed<TcpStream, LinesCodec>>,
+    max_connect_attempts: u32,
+    request_timeout: Duration,
+}
+
+impl ReconnectingClient {
+    fn new(addr: &str) -> Self {
+        Self {
+            addr: addr.to_string(),
+            conn: None,
+            max_connect_attempts: 5,
+            request_timeout: Duration::from_secs(5),
+        }
+    }
+
+    async fn connection(&mut self) -> io::Result<&mut Framed<TcpStream, LinesCodec>> {
+        if self.conn.is_none() {
+            let mut backoff = Duration::from_millis(50);
+            let mut attempt = 1;
+            let stream = loop {
+                match TcpStream::connect(&self.addr).await {
+                    Ok(stream) => break stream,
+                    Err(e) if attempt < self.max_connect_attempts => {
+                        eprintln!("connect to {} failed ({}); retrying in {:?}", self.addr, e, backoff);
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(Duration::from_secs(5));
+                        attempt += 1;
+                    }
+                    Err(e) => return Err(e),
+                }
+            };
+            stream.set_nodelay(true)?;
+            self.conn = Some(Framed::new(stream, line_codec()));
+        }
+        Ok(self.conn.as_mut().expect("connection was just established"))
+    }
+
+    async fn try_request(&mut self, line: &str) -> io::Result<String> {
+        let timeout = self.request_timeout;
+        let conn = self.connection().await?;
+        let exchange = async {
+            conn.send(line).await.map_err(to_io)?;
+            match conn.next().await {
+                Some(reply) => reply.map_err(to_io),
+                None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "server closed the connection")),
+            }
+        };
+        tokio::time::timeout(timeout, exchange)
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "request timed out"))?
+    }
+
+    /// Sends one command line and returns the reply line.
+    async fn request(&mut self, line: &str) -> io::Result<String> {
+        match self.try_request(line).await {
+            Ok(reply) => Ok(reply),
+            Err(e) => {
+                // The connection is in an unknown state; drop it and retry once on a new one.
+                eprintln!("request failed ({}); reconnecting", e);
+                self.conn = None;
+                let result = self.try_request(line).await;
+                if result.is_err() {
+                    self.conn = None;
+                }
+                result
+            }
+        }
+    }
+}
+
+/// One-shot typed call over the length-prefixed protocol.
+async fn call_binary(addr: &str, request: &Request) -> io::Result<Reply> {
+    let mut framed = Framed::new(TcpStream::connect(addr).await?, frame_codec());
+    framed.send(encode_frame(request)?).await?;
+    let frame = framed
+        .next()
+        .await
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "no reply"))??;
+    decode_frame(&frame)
+}
+
+// Example Usage
+/*
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+    // Line server on an OS-assigned port.
+    let lines = TcpListener::bind("127.0.0.1:0").await?;
+    let line_addr = lines.local_addr()?.to_string();
+    let line_server = tokio::spawn(run_server(lines, line_codec, line_commands, shutdown_rx.clone(), Duration::from_secs(2)));
+
+    // Binary server.
+    let binary = TcpListener::bind("127.0.0.1:0").await?;
+    let binary_addr = binary.local_addr()?.to_string();
+    let binary_server = tokio::spawn(run_server(binary, frame_codec, binary_handler, shutdown_rx, Duration::from_secs(2)));
+
+    println!("--- Line protocol on {} (also works with `nc`) ---", line_addr);
+    let mut client = ReconnectingClient::new(&line_addr);
+    for cmd in ["PING", "ECHO hello tcp", "UPPER shout", "WHOAMI", "NOPE"] {
+        println!("> {}\n< {}", cmd, client.request(cmd).await?);
+    }
+    // QUIT makes the server close our connection; the next request reconnects by itself.
+    let _ = client.request("QUIT").await;
+    println!("> PING (after QUIT)\n< {}", client.request("PING").await?);
+
+    println!("\n--- Length-prefixed binary protocol ---");
+    println!("{:?}", call_binary(&binary_addr, &Request::Add { a: 40, b: 2 }).await?);
+    println!("{:?}", call_binary(&binary_addr, &Request::Store { key: "blob".into(), bytes: vec![0; 4096] }).await?);
+
+    println!("\n--- Graceful shutdown ---");
+    shutdown_tx.send(true).ok();
+    line_server.await??;
+    binary_server.await??;
+    println!("Servers stopped; connecting now fails: {:?}", TcpStream::connect(&line_addr).await.err().map(|e| e.kind()));
+    Ok(())
+}
+*/
@@ -0,0 +1,208 @@
+// Note: This example requires adding the `minijinja`, `serde`, and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// minijinja = { version = "2", features = ["loader"] }
+// serde = { version = "1.0", features = ["derive"] }
+// thiserror = "1.0"
+//
+// Jinja2-style templates (`{{ var }}`, `{% for %}`, `{% extends %}`) for emails, reports,
+// and generated code. `email.rs` has a tiny `{{var}}` renderer for single messages; use this
+// when templates need loops, conditionals, inheritance, or live in files.
+//
+// Compiled templates are cached inside the `Environment`: build one engine at startup,
+// share it (`Arc<TemplateEngine>` or a `static`), and render many times.
+
+use minijinja::value::Value;
+use minijinja::{path_loader, AutoEscape, Environment, ErrorKind, UndefinedBehavior};
+use serde::Serialize;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    /// Includes the template name, line, and (via `detailed()`) the surrounding source.
+    #[error("{0}")]
+    Template(#[from] minijinja::Error),
+}
+
+impl TemplateError {
+    /// Multi-line description with the offending template lines, for CLI output and logs.
+    fn detailed(&self) -> String {
+        match self {
+            TemplateError::Template(e) => format!("{:#}", e.display_debug_info()),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, TemplateError>;
+
+/// A configured template environment.
+struct TemplateEngine {
+    env: Environment<'static>,
+}
+
+impl TemplateEngine {
+    /// An engine with templates registered from strings.
+    ///
+    /// # Arguments
+    ///
+    /// * `strict` - When `true`, using an undefined variable is an error instead of
+    ///   rendering as an empty string. Use strict mode for emails and codegen, where a
+    ///   silently missing value is a bug.
+    fn new(strict: bool) -> Self {
+        let mut env = Environment::new();
+        env.set_undefined_behavior(if strict { UndefinedBehavior::Strict } else { UndefinedBehavior::Lenient });
+        // HTML-escape output for `.html` templates only; text and code are left alone.
+        env.set_auto_escape_callback(|name| {
+            if name.ends_with(".html") {
+                AutoEscape::Html
+            } else {
+                AutoEscape::None
+            }
+        });
+        env.set_trim_blocks(true); // No blank line left behind by `{% ... %}` tags
+        env.set_lstrip_blocks(true);
+        register_helpers(&mut env);
+        Self { env }
+    }
+
+    /// An engine that loads templates from `dir` on first use (e.g. `templates/email.html`
+    /// is rendered as `"email.html"`). Each file is read and compiled once, then cached.
+    fn from_dir(dir: impl AsRef<Path>, strict: bool) -> Self {
+        let mut engine = Self::new(strict);
+        engine.env.set_loader(path_loader(dir.as_ref()));
+        engine
+    }
+
+    /// Registers (or replaces) a named template. The source is compiled immediately, so
+    /// syntax errors surface here rather than at first render.
+    fn add_template(&mut self, name: &str, source: &str) -> Result<()> {
+        self.env.add_template_owned(name.to_string(), source.to_string())?;
+        Ok(())
+    }
+
+    /// A value visible to every template, e.g. the product name or support address.
+    fn add_global(&mut self, name: &'static str, value: impl Serialize) {
+        self.env.add_global(name, Value::from_serialize(value));
+    }
+
+    /// Renders a registered or loaded template with any `Serialize` context.
+    fn render<S: Serialize>(&self, name: &str, context: S) -> Result<String> {
+        Ok(self.env.get_template(name)?.render(context)?)
+    }
+
+    /// Renders a one-off template string without caching it.
+    fn render_str<S: Serialize>(&self, source: &str, context: S) -> Result<String> {
+        Ok(self.env.render_str(source, context)?)
+    }
+
+    /// Drops cached templates so files are re-read from disk (for development reloads).
+    fn reload(&mut self) {
+        self.env.clear_templates();
+    }
+}
+
+/// Custom filters and functions shared by all templates.
+fn register_helpers(env: &mut Environment<'static>) {
+    // {{ total_cents | money }} -> $1,234.50 ; {{ amount | money("EUR ") }}
+    env.add_filter("money", |cents: i64, symbol: Option<String>| -> String {
+        let symbol = symbol.unwrap_or_else(|| "$".to_string());
+        let sign = if cents < 0 { "-" } else { "" };
+        let cents = cents.unsigned_abs();
+        let whole = (cents / 100).to_string();
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        format!("{}{}{}.{:02}", sign, symbol, grouped, cents % 100)
+    });
+
+    // {{ count }} {{ count | pluralize("item", "items") }}
+    env.add_filter("pluralize", |count: i64, singular: String, plural: Option<String>| -> String {
+        if count == 1 {
+            singular
+        } else {
+            plural.unwrap_or_else(|| format!("{}s", singular))
+        }
+    });
+
+    // {{ require(customer.email, "customer email") }}: fail loudly even in lenient mode.
+    env.add_function("require", |value: Value, what: String| -> std::result::Result<Value, minijinja::Error> {
+        if value.is_undefined() || value.is_none() {
+            Err(minijinja::Error::new(ErrorKind::InvalidOperation, format!("{} is required", what)))
+        } else {
+            Ok(value)
+        }
+    });
+}
+
+// Example Usage
+/*
+use minijinja::context;
+
+#[derive(Serialize)]
+struct LineItem {
+    name: String,
+    quantity: u32,
+    price_cents: i64,
+}
+
+#[derive(Serialize)]
+struct Invoice {
+    number: u32,
+    customer: String,
+    items: Vec<LineItem>,
+    total_cents: i64,
+}
+
+fn main() -> Result<()> {
+    let mut engine = TemplateEngine::new(true);
+    engine.add_global("company", "Acme Corp");
+    engine.add_template("base.html", "<h1>{{ company }}</h1>\n{% block body %}{% endblock %}\n")?;
+    engine.add_template(
+        "invoice.html",
+        r#"{% extends "base.html" %}
+{% block body %}
+<p>Invoice #{{ number }} for {{ customer }} ({{ items | length }} {{ items | length | pluralize("item") }})</p>
+<ul>
+{% for item in items %}
+  <li>{{ item.quantity }} x {{ item.name }}: {{ item.price_cents | money }}</li>
+{% endfor %}
+</ul>
+<p>Total: {{ total_cents | money }}</p>
+{% endblock %}"#,
+    )?;
+
+    let invoice = Invoice {
+        number: 1042,
+        customer: "Ben & Jerry's".to_string(), // Escaped in .html templates
+        items: vec![
+            LineItem { name: "Widget".into(), quantity: 3, price_cents: 1999 },
+            LineItem { name: "Support plan".into(), quantity: 1, price_cents: 120000 },
+        ],
+        total_cents: 125997,
+    };
+    println!("{}", engine.render("invoice.html", &invoice)?);
+
+    // Strict mode: a typo in a variable name is an error, not an empty string.
+    let err = engine.render_str("Hello {{ custmer }}!", context! { customer => "Ada" }).unwrap_err();
+    println!("Strict mode error:\n{}\n", err.detailed());
+
+    // Lenient mode renders undefined values as empty, but `require` still guards critical fields.
+    let lenient = TemplateEngine::new(false);
+    println!("{:?}", lenient.render_str("Hi {{ name }}!", context! {})?);
+    println!("{}", lenient.render_str("To: {{ require(email, 'recipient email') }}", context! {}).unwrap_err());
+
+    // Templates from a directory, loaded and compiled on first use.
+    let dir = std::env::temp_dir().join("template-example");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("welcome.txt"), "Welcome, {{ name }}! You have {{ n }} new {{ n | pluralize('message') }}.\n").unwrap();
+    let files = TemplateEngine::from_dir(&dir, true);
+    println!("{}", files.render("welcome.txt", context! { name => "Grace", n => 1 })?);
+    println!("{}", files.render("welcome.txt", context! { name => "Linus", n => 5 })?);
+    std::fs::remove_dir_all(&dir).unwrap();
+    Ok(())
+}
+*/
@@ -0,0 +1,506 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// crossterm = { version = "0.28", features = ["event-stream"] }
+// futures = "0.3"
+// tokio = { version = "1", features = ["full"] }
+// thiserror = "1.0"
+//
+// Raw keyboard input for interactive tools (REPLs, pickers, TUIs) without blocking a thread:
+// * `RawTerminal::enter(TermOptions::default())` switches the terminal to raw mode (no line
+//   buffering, no echo, no Ctrl-C signal) and optionally the alternate screen, mouse capture,
+//   bracketed paste, and focus events. Dropping the guard undoes exactly what was enabled, in
+//   reverse order; a panic hook does the same before the panic message is printed.
+// * `KeyEvents` is a `futures::Stream` of `InputEvent`s: key presses as a normalized `Key`
+//   (code plus Ctrl/Alt/Shift/Super), pastes, resizes, mouse, and focus changes. Key releases
+//   are dropped and auto-repeats are opt-in, so each press arrives exactly once everywhere.
+// * `Key` parses and prints the same spelling (`"ctrl+c"`, `"alt+enter"`, `"shift+tab"`,
+//   `"f5"`), so `Keymap<Action>` can load bindings from config and list them in a help screen.
+//
+// Because raw mode swallows SIGINT, Ctrl-C arrives as `Key::CTRL_C`; handle it explicitly.
+// `tui_dashboard.rs` uses the synchronous `event::poll`/`read` pair from the same crate; switch
+// it to `KeyEvents` when the UI loop already runs on tokio.
+
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste, EnableFocusChange,
+    EnableMouseCapture, Event, EventStream, KeyCode, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+    MouseEvent, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute};
+use futures::Stream;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::panic;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Once;
+use std::task::{Context, Poll};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum InputError {
+    #[error("stdin/stdout is not a terminal")]
+    NotATerminal,
+    #[error("the terminal is already in raw mode")]
+    AlreadyActive,
+    #[error("invalid key {spec:?}: {reason}")]
+    InvalidKey { spec: String, reason: String },
+    #[error("terminal I/O failed: {0}")]
+    Io(#[from] io::Error),
+}
+
+type Result<T> = std::result::Result<T, InputError>;
+
+// --- Terminal Modes ---
+
+/// What to switch on besides raw mode. Everything here is undone on drop.
+#[derive(Debug, Clone, Copy)]
+struct TermOptions {
+    alternate_screen: bool,
+    hide_cursor: bool,
+    mouse: bool,
+    bracketed_paste: bool,
+    focus_events: bool,
+    /// Ask for the kitty keyboard protocol where supported, which tells Esc apart from Alt and
+    /// reports Ctrl+Enter, Ctrl+Tab, and other combinations legacy terminals can't express.
+    enhanced_keys: bool,
+}
+
+impl Default for TermOptions {
+    /// Raw mode plus bracketed paste: what a line editor or REPL needs.
+    fn default() -> Self {
+        Self {
+            alternate_screen: false,
+            hide_cursor: false,
+            mouse: false,
+            bracketed_paste: true,
+            focus_events: false,
+            enhanced_keys: true,
+        }
+    }
+}
+
+impl TermOptions {
+    /// Full-screen UI settings: alternate screen, hidden cursor, mouse, and focus events.
+    fn fullscreen() -> Self {
+        Self { alternate_screen: true, hide_cursor: true, mouse: true, focus_events: true, ..Self::default() }
+    }
+}
+
+// Bits for what is currently enabled, shared with the panic hook.
+const RAW: u8 = 1;
+const ALT_SCREEN: u8 = 1 << 1;
+const HIDDEN_CURSOR: u8 = 1 << 2;
+const MOUSE: u8 = 1 << 3;
+const PASTE: u8 = 1 << 4;
+const FOCUS: u8 = 1 << 5;
+const ENHANCED: u8 = 1 << 6;
+
+static ENABLED: AtomicU8 = AtomicU8::new(0);
+static HOOK: Once = Once::new();
+
+/// Undoes whatever is enabled, newest first. Safe to call repeatedly and from a panic hook.
+fn restore_terminal() {
+    let enabled = ENABLED.swap(0, Ordering::SeqCst);
+    let mut out = io::stdout();
+    if enabled & ENHANCED != 0 {
+        let _ = execute!(out, PopKeyboardEnhancementFlags);
+    }
+    if enabled & FOCUS != 0 {
+        let _ = execute!(out, DisableFocusChange);
+    }
+    if enabled & PASTE != 0 {
+        let _ = execute!(out, DisableBracketedPaste);
+    }
+    if enabled & MOUSE != 0 {
+        let _ = execute!(out, DisableMouseCapture);
+    }
+    if enabled & HIDDEN_CURSOR != 0 {
+        let _ = execute!(out, cursor::Show);
+    }
+    if enabled & ALT_SCREEN != 0 {
+        let _ = execute!(out, LeaveAlternateScreen);
+    }
+    if enabled & RAW != 0 {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn install_panic_hook() {
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            previous(info);
+        }));
+    });
+}
+
+/// Holds the terminal in raw mode; restores it when dropped.
+#[derive(Debug)]
+struct RawTerminal {
+    enhanced_keys: bool,
+}
+
+impl RawTerminal {
+    /// Enters raw mode with the requested extras.
+    ///
+    /// # Returns
+    /// * `InputError::NotATerminal` when stdin or stdout is redirected; fall back to line input.
+    /// * `InputError::AlreadyActive` when another guard is alive (raw mode is process-wide).
+    fn enter(options: TermOptions) -> Result<Self> {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            return Err(InputError::NotATerminal);
+        }
+        if ENABLED.compare_exchange(0, RAW, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            return Err(InputError::AlreadyActive);
+        }
+        install_panic_hook();
+        if let Err(e) = terminal::enable_raw_mode() {
+            ENABLED.store(0, Ordering::SeqCst);
+            return Err(e.into());
+        }
+        // From here on, an early return drops the guard and restores what was enabled so far.
+        let mut guard = Self { enhanced_keys: false };
+        let mut out = io::stdout();
+        let mut enable = |bit: u8, wanted: bool, apply: &mut dyn FnMut(&mut io::Stdout) -> io::Result<()>| {
+            if wanted {
+                apply(&mut out)?;
+                ENABLED.fetch_or(bit, Ordering::SeqCst);
+            }
+            io::Result::Ok(())
+        };
+        enable(ALT_SCREEN, options.alternate_screen, &mut |o| execute!(o, EnterAlternateScreen))?;
+        enable(HIDDEN_CURSOR, options.hide_cursor, &mut |o| execute!(o, cursor::Hide))?;
+        enable(MOUSE, options.mouse, &mut |o| execute!(o, EnableMouseCapture))?;
+        enable(PASTE, options.bracketed_paste, &mut |o| execute!(o, EnableBracketedPaste))?;
+        enable(FOCUS, options.focus_events, &mut |o| execute!(o, EnableFocusChange))?;
+        // The support query is answered by the terminal, so it only works in raw mode.
+        let enhanced = options.enhanced_keys && terminal::supports_keyboard_enhancement().unwrap_or(false);
+        enable(ENHANCED, enhanced, &mut |o| {
+            execute!(o, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES))
+        })?;
+        guard.enhanced_keys = enhanced;
+        Ok(guard)
+    }
+
+    /// Whether the terminal accepted the enhanced keyboard protocol.
+    fn enhanced_keys(&self) -> bool {
+        self.enhanced_keys
+    }
+
+    /// Current size as (columns, rows).
+    fn size(&self) -> Result<(u16, u16)> {
+        Ok(terminal::size()?)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+// --- Keys ---
+
+/// A key press with its modifiers, normalized so one spelling matches on every terminal.
+///
+/// Shift is folded into printable characters (`Shift+a` is `'A'`, `Shift+/` is `'?'`) because
+/// terminals disagree on whether they report it; for other keys it stays a modifier.
+/// `Ctrl+Shift+a` is `Ctrl+a`, since legacy terminals send the same byte for both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    const CTRL_C: Key = Key::ctrl('c');
+    const CTRL_D: Key = Key::ctrl('d');
+    const ENTER: Key = Key::new(KeyCode::Enter, KeyModifiers::NONE);
+    const ESC: Key = Key::new(KeyCode::Esc, KeyModifiers::NONE);
+
+    const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    const fn ctrl(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn normalize(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT | KeyModifiers::SUPER);
+        match code {
+            KeyCode::Char(c) => {
+                let c = if modifiers.contains(KeyModifiers::SHIFT) { c.to_ascii_uppercase() } else { c };
+                // Ctrl+letter comes through lowercase on most terminals, uppercase on a few.
+                let c = if modifiers.contains(KeyModifiers::CONTROL) { c.to_ascii_lowercase() } else { c };
+                Self::new(KeyCode::Char(c), modifiers - KeyModifiers::SHIFT)
+            }
+            KeyCode::BackTab => Self::new(KeyCode::Tab, modifiers | KeyModifiers::SHIFT),
+            code => Self::new(code, modifiers),
+        }
+    }
+
+    /// The character this key types, if it is plain text input for a line editor.
+    fn as_char(&self) -> Option<char> {
+        match self.code {
+            KeyCode::Char(c) if (self.modifiers - KeyModifiers::SHIFT).is_empty() => Some(c),
+            _ => None,
+        }
+    }
+}
+
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("space", KeyCode::Char(' ')),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+impl fmt::Display for Key {
+    /// Lowercase `ctrl+alt+shift+super+key`, the same form `FromStr` accepts.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = String::new();
+        for (flag, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+            (KeyModifiers::SUPER, "super+"),
+        ] {
+            if self.modifiers.contains(flag) {
+                s.push_str(name);
+            }
+        }
+        match (self.code, NAMED_KEYS.iter().find(|(_, code)| *code == self.code)) {
+            (_, Some((name, _))) => s.push_str(name),
+            (KeyCode::F(n), _) => s.push_str(&format!("f{}", n)),
+            (KeyCode::Char(c), _) => s.push(c),
+            (other, _) => s.push_str(&other.to_string().to_lowercase().replace(' ', "")),
+        }
+        f.pad(&s)
+    }
+}
+
+impl FromStr for Key {
+    type Err = InputError;
+
+    /// Parses `"ctrl+c"`, `"Alt+Enter"`, `"shift+tab"`, `"f5"`, `"?"`, or `"+"`.
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = |reason: &str| InputError::InvalidKey { spec: spec.to_string(), reason: reason.to_string() };
+        // Split on '+' but keep a trailing '+' as the key itself ("ctrl++").
+        let (mods, key) = match spec.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => spec.rsplit_once('+').filter(|(_, k)| !k.is_empty()).unwrap_or(("", spec)),
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" | "c" => KeyModifiers::CONTROL,
+                "alt" | "option" | "meta" | "m" => KeyModifiers::ALT,
+                "shift" | "s" => KeyModifiers::SHIFT,
+                "super" | "cmd" | "win" => KeyModifiers::SUPER,
+                _ => return Err(invalid(&format!("unknown modifier {:?}", m))),
+            };
+        }
+        let lower = key.to_ascii_lowercase();
+        let code = if let Some((_, code)) = NAMED_KEYS.iter().find(|(name, _)| *name == lower) {
+            *code
+        } else if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            if !(1..=24).contains(&n) {
+                return Err(invalid("function keys are f1 to f24"));
+            }
+            KeyCode::F(n)
+        } else {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return Err(invalid("expected a single character or a key name")),
+            }
+        };
+        Ok(Key::normalize(code, modifiers))
+    }
+}
+
+// --- Event Stream ---
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InputEvent {
+    Key(Key),
+    /// A whole paste as one string (with `bracketed_paste`), instead of one key per character.
+    Paste(String),
+    Resize { cols: u16, rows: u16 },
+    Mouse(MouseEvent),
+    FocusGained,
+    FocusLost,
+}
+
+/// An async stream of terminal input. Create it after entering raw mode.
+struct KeyEvents {
+    inner: EventStream,
+    repeats: bool,
+}
+
+impl fmt::Debug for KeyEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyEvents").field("repeats", &self.repeats).finish_non_exhaustive()
+    }
+}
+
+impl KeyEvents {
+    fn new() -> Self {
+        Self { inner: EventStream::new(), repeats: false }
+    }
+
+    /// Also yield auto-repeat events, for held-down keys in games or scrolling.
+    /// Only terminals with the enhanced protocol report repeats separately.
+    fn with_repeats(mut self, repeats: bool) -> Self {
+        self.repeats = repeats;
+        self
+    }
+
+    fn convert(&self, event: Event) -> Option<InputEvent> {
+        match event {
+            Event::Key(key) => match key.kind {
+                KeyEventKind::Press => Some(InputEvent::Key(Key::normalize(key.code, key.modifiers))),
+                KeyEventKind::Repeat if self.repeats => Some(InputEvent::Key(Key::normalize(key.code, key.modifiers))),
+                _ => None,
+            },
+            Event::Paste(text) => Some(InputEvent::Paste(text)),
+            Event::Resize(cols, rows) => Some(InputEvent::Resize { cols, rows }),
+            Event::Mouse(mouse) => Some(InputEvent::Mouse(mouse)),
+            Event::FocusGained => Some(InputEvent::FocusGained),
+            Event::FocusLost => Some(InputEvent::FocusLost),
+        }
+    }
+}
+
+impl Stream for KeyEvents {
+    type Item = io::Result<InputEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(event))) => {
+                    if let Some(event) = self.convert(event) {
+                        return Poll::Ready(Some(Ok(event)));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+// --- Keymaps ---
+
+/// Key bindings for an action enum, loadable from config strings.
+#[derive(Debug, Clone)]
+struct Keymap<A> {
+    bindings: HashMap<Key, A>,
+}
+
+impl<A: Clone> Keymap<A> {
+    fn new() -> Self {
+        Self { bindings: HashMap::new() }
+    }
+
+    /// Binds `spec` (e.g. `"ctrl+s"`) to `action`, replacing any earlier binding for that key.
+    fn bind(mut self, spec: &str, action: A) -> Result<Self> {
+        self.bindings.insert(spec.parse()?, action);
+        Ok(self)
+    }
+
+    fn get(&self, key: &Key) -> Option<&A> {
+        self.bindings.get(key)
+    }
+
+    /// Bindings sorted by key spelling, for a help screen.
+    fn describe(&self) -> Vec<(String, A)> {
+        let mut list: Vec<_> = self.bindings.iter().map(|(k, a)| (k.to_string(), a.clone())).collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        list
+    }
+}
+
+// Example Usage
+/*
+#[tokio::main]
+async fn main() -> Result<()> {
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Action {
+        Save,
+        Quit,
+        Help,
+        NextPane,
+    }
+
+    let keymap = Keymap::new()
+        .bind("ctrl+s", Action::Save)?
+        .bind("ctrl+q", Action::Quit)?
+        .bind("?", Action::Help)?
+        .bind("shift+tab", Action::NextPane)?;
+    for (key, action) in keymap.describe() {
+        println!("{:>10}  {:?}", key, action);
+    }
+    for spec in ["Ctrl+Shift+A", "alt+enter", "f5", "ctrl++", "hyper+x", "f30"] {
+        match spec.parse::<Key>() {
+            Ok(key) => println!("{:?} -> {}", spec, key),
+            Err(e) => println!("{}", e),
+        }
+    }
+
+    let raw = match RawTerminal::enter(TermOptions::default()) {
+        Ok(raw) => raw,
+        Err(InputError::NotATerminal) => {
+            println!("not a terminal; run this in an interactive shell to see key events");
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    // In raw mode "\n" only moves down a line; "\r\n" also returns to the first column.
+    print!("raw mode on (enhanced keys: {}), size {:?}. Type keys; ctrl+q or ctrl+c quits.\r\n", raw.enhanced_keys(), raw.size()?);
+
+    let mut events = KeyEvents::new();
+    let mut idle = tokio::time::interval(Duration::from_secs(5));
+    idle.tick().await;
+    loop {
+        tokio::select! {
+            event = events.next() => match event {
+                Some(Ok(InputEvent::Key(key))) if key == Key::CTRL_C || key == Key::CTRL_D => break,
+                Some(Ok(InputEvent::Key(key))) => match keymap.get(&key) {
+                    Some(Action::Quit) => break,
+                    Some(action) => print!("{} -> {:?}\r\n", key, action),
+                    None => print!("{} (char: {:?})\r\n", key, key.as_char()),
+                },
+                Some(Ok(InputEvent::Paste(text))) => print!("pasted {} bytes\r\n", text.len()),
+                Some(Ok(other)) => print!("{:?}\r\n", other),
+                Some(Err(e)) => return Err(e.into()),
+                None => break,
+            },
+            _ = idle.tick() => print!("(still here)\r\n"),
+        }
+    }
+    drop(raw);
+    println!("terminal restored");
+    Ok(())
+}
+*/
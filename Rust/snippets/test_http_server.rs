@@ -0,0 +1,447 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies] (or [dev-dependencies] when only tests use the server)
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// reqwest = { version = "0.12", features = ["blocking", "json"] } # Only for the example
+//
+// A mock HTTP/1.1 server for tests, so code built on the HTTP client snippets
+// (`http_get_request.rs`) can be tested offline and deterministically:
+// * Binds `127.0.0.1:0`, so parallel tests each get their own ephemeral port.
+// * Routes map a method and path to canned responses. A route can hold a sequence
+//   (e.g. 503, 503, 200) to exercise retry logic; the last response then repeats.
+// * Responses can be delayed (to trigger client timeouts) or replaced by a dropped
+//   connection (to exercise connection-error handling).
+// * Every request is recorded, so tests can assert on the method, path, headers, and body.
+// * Shuts down on `shutdown()` or drop, joining its threads so no test leaks a listener.
+//
+// It uses blocking std threads, so it works with both blocking and async clients. It supports
+// `Content-Length` request bodies and keep-alive, which covers what HTTP clients send in tests;
+// it is not a general-purpose server.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Largest request head or body the server accepts, to keep a misbehaving client from
+/// exhausting test memory.
+const MAX_REQUEST_BYTES: usize = 8 * 1024 * 1024;
+/// How often idle connection threads check for shutdown.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A canned response, with optional latency and failure injection.
+#[derive(Debug, Clone)]
+struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    delay: Duration,
+    /// Close the connection without responding, as a crashed or overloaded server would.
+    drop_connection: bool,
+}
+
+impl MockResponse {
+    fn new(status: u16) -> Self {
+        Self { status, headers: Vec::new(), body: Vec::new(), delay: Duration::ZERO, drop_connection: false }
+    }
+
+    fn text(status: u16, body: &str) -> Self {
+        Self::new(status).header("Content-Type", "text/plain; charset=utf-8").body(body)
+    }
+
+    fn json<T: Serialize + ?Sized>(status: u16, value: &T) -> Self {
+        let body = serde_json::to_vec(value).expect("mock JSON serializes");
+        Self::new(status).header("Content-Type", "application/json").body(body)
+    }
+
+    /// A response the server never sends; the client sees the connection close instead.
+    fn dropped_connection() -> Self {
+        Self { drop_connection: true, ..Self::new(0) }
+    }
+
+    fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Waits this long before responding (or dropping the connection).
+    fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request the server received, for assertions.
+#[derive(Debug, Clone, PartialEq)]
+struct RecordedRequest {
+    method: String,
+    /// The path without the query string, e.g. `/users/7`.
+    path: String,
+    /// The raw query string without `?`, or empty.
+    query: String,
+    /// Header names are lower-cased.
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// The first header named `name` (case-insensitive).
+    fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())
+    }
+
+    fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    fn body_json<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+/// The responses for one route; `next` advances until the last one, which then repeats.
+struct Route {
+    responses: Vec<MockResponse>,
+    next: usize,
+}
+
+#[derive(Default)]
+struct State {
+    routes: HashMap<(String, String), Route>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// A running mock server; stops on drop.
+struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    stopping: Arc<AtomicBool>,
+    acceptor: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server on an ephemeral localhost port with no routes.
+    ///
+    /// Unmatched requests get a `404` whose body names the method and path, which makes a
+    /// missing mock obvious in the client's error.
+    fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let stopping = Arc::new(AtomicBool::new(false));
+        let acceptor = {
+            let (state, stopping) = (Arc::clone(&state), Arc::clone(&stopping));
+            thread::Builder::new()
+                .name(format!("mock-http-{}", addr.port()))
+                .spawn(move || accept_loop(listener, state, stopping))?
+        };
+        Ok(Self { addr, state, stopping, acceptor: Some(acceptor) })
+    }
+
+    fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The full URL of `path` on this server, e.g. `http://127.0.0.1:49152/users`.
+    fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// Serves `response` for every `method` request to `path`, replacing any earlier mock.
+    fn mock(&self, method: &str, path: &str, response: MockResponse) {
+        self.mock_sequence(method, path, vec![response]);
+    }
+
+    /// Serves `responses` in order for `method` requests to `path`; the last one repeats.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - e.g. `"GET"`; matched case-insensitively.
+    /// * `path` - Matched exactly against the request path, ignoring the query string.
+    /// * `responses` - At least one response.
+    fn mock_sequence(&self, method: &str, path: &str, responses: Vec<MockResponse>) {
+        assert!(!responses.is_empty(), "a mocked route needs at least one response");
+        self.lock().routes.insert((method.to_ascii_uppercase(), path.to_string()), Route { responses, next: 0 });
+    }
+
+    /// Every request received so far, in arrival order.
+    fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// How many `method` requests to `path` were received.
+    fn hits(&self, method: &str, path: &str) -> usize {
+        let method = method.to_ascii_uppercase();
+        self.lock().requests.iter().filter(|r| r.method == method && r.path == path).count()
+    }
+
+    /// Forgets recorded requests, keeping the routes.
+    fn clear_requests(&self) {
+        self.lock().requests.clear();
+    }
+
+    /// Stops accepting connections and waits for the server threads to finish.
+    fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        // Wake the blocking `accept` so the thread sees the flag.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(acceptor) = self.acceptor.take() {
+            let _ = acceptor.join();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // A panicking test thread can poison the lock; the state is still usable.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn accept_loop(listener: TcpListener, state: Arc<Mutex<State>>, stopping: Arc<AtomicBool>) {
+    let mut connections = Vec::new();
+    for stream in listener.incoming() {
+        if stopping.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(stream) = stream else { continue };
+        let (state, stopping) = (Arc::clone(&state), Arc::clone(&stopping));
+        connections.push(thread::spawn(move || {
+            let _ = serve_connection(stream, &state, &stopping);
+        }));
+        connections.retain(|c: &JoinHandle<()>| !c.is_finished());
+    }
+    for connection in connections {
+        let _ = connection.join();
+    }
+}
+
+/// Serves requests on one connection until the client closes it, asks to close, or the
+/// server stops.
+fn serve_connection(stream: TcpStream, state: &Mutex<State>, stopping: &AtomicBool) -> io::Result<()> {
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    loop {
+        let request = match read_request(&mut reader, stopping)? {
+            Some(request) => request,
+            None => return Ok(()),
+        };
+        let close = request.header("connection").is_some_and(|v| v.eq_ignore_ascii_case("close"));
+        let response = {
+            let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+            let response = match state.routes.get_mut(&(request.method.clone(), request.path.clone())) {
+                Some(route) => {
+                    let response = route.responses[route.next].clone();
+                    route.next = (route.next + 1).min(route.responses.len() - 1);
+                    response
+                }
+                None => MockResponse::text(404, &format!("no mock for {} {}", request.method, request.path)),
+            };
+            state.requests.push(request);
+            response
+        };
+        thread::sleep(response.delay);
+        if response.drop_connection {
+            return writer.shutdown(Shutdown::Both);
+        }
+        write_response(&mut writer, &response, close)?;
+        if close {
+            return Ok(());
+        }
+    }
+}
+
+/// Reads one request, or `None` when the client closed the connection or the server stopped.
+fn read_request(reader: &mut BufReader<TcpStream>, stopping: &AtomicBool) -> io::Result<Option<RecordedRequest>> {
+    let mut head = Vec::new();
+    // Read the head line by line, retrying read timeouts so idle keep-alive connections can
+    // notice shutdown.
+    loop {
+        if stopping.load(Ordering::SeqCst) {
+            return Ok(None);
+        }
+        match reader.read_until(b'\n', &mut head) {
+            Ok(0) => return Ok(None),
+            Ok(_) if head.len() > MAX_REQUEST_BYTES => return Err(invalid("request head too large")),
+            Ok(_) if head.ends_with(b"\r\n\r\n") || head.ends_with(b"\n\n") => break,
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let head = String::from_utf8(head).map_err(|_| invalid("request head is not UTF-8"))?;
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let mut request = RecordedRequest {
+        method: method.to_ascii_uppercase(),
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body: Vec::new(),
+    };
+
+    if request.header("transfer-encoding").is_some() {
+        return Err(invalid("chunked request bodies are not supported; send Content-Length"));
+    }
+    let length: usize = match request.header("content-length") {
+        Some(value) => value.parse().map_err(|_| invalid("bad Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_REQUEST_BYTES {
+        return Err(invalid("request body too large"));
+    }
+    request.body = vec![0; length];
+    let mut filled = 0;
+    while filled < length {
+        match reader.read(&mut request.body[filled..]) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(Some(request))
+}
+
+fn write_response(writer: &mut TcpStream, response: &MockResponse, close: bool) -> io::Result<()> {
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, reason_phrase(response.status));
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+    if close {
+        head.push_str("Connection: close\r\n");
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes())?;
+    writer.write_all(&response.body)?;
+    writer.flush()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Unknown",
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Example Usage
+/*
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Instant;
+
+#[derive(Debug, Deserialize)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+/// Code under test: fetches a user, retrying 503s up to three times.
+fn fetch_user(client: &reqwest::blocking::Client, base: &str, id: u64) -> reqwest::Result<User> {
+    let mut attempt = 1;
+    loop {
+        let response = client.get(format!("{}/users/{}", base, id)).send()?;
+        if response.status() == 503 && attempt < 3 {
+            attempt += 1;
+            continue;
+        }
+        return response.error_for_status()?.json();
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let server = MockServer::start()?;
+    let base = server.url("");
+    let client = reqwest::blocking::Client::builder().timeout(Duration::from_millis(500)).build()?;
+
+    // Retries: two 503s, then success.
+    server.mock_sequence(
+        "GET",
+        "/users/7",
+        vec![
+            MockResponse::text(503, "busy"),
+            MockResponse::text(503, "busy"),
+            MockResponse::json(200, &json!({ "id": 7, "name": "Ada" })),
+        ],
+    );
+    println!("fetched {:?} after {} requests", fetch_user(&client, &base, 7)?, server.hits("GET", "/users/7"));
+
+    // Request recording: assert on what the client sent.
+    server.mock("POST", "/users", MockResponse::json(201, &json!({ "id": 8, "name": "Grace" })));
+    client
+        .post(server.url("/users?notify=true"))
+        .bearer_auth("test-token")
+        .json(&json!({ "name": "Grace" }))
+        .send()?;
+    let sent = server.requests().pop().unwrap();
+    let body: serde_json::Value = sent.body_json()?;
+    println!("{} {}?{} auth={:?} body={}", sent.method, sent.path, sent.query, sent.header("Authorization"), body);
+
+    // Latency injection: the client's 500ms timeout fires.
+    server.mock("GET", "/slow", MockResponse::text(200, "late").delay(Duration::from_secs(1)));
+    let started = Instant::now();
+    let err = client.get(server.url("/slow")).send().unwrap_err();
+    println!("slow: timeout={} after {:?}", err.is_timeout(), started.elapsed().as_millis() / 100 * 100);
+
+    // Failure injection: the connection drops without a response.
+    server.mock("GET", "/flaky", MockResponse::dropped_connection());
+    println!("flaky: {}", client.get(server.url("/flaky")).send().is_err());
+
+    // Unmocked routes return 404 with a helpful body.
+    let missing = client.get(server.url("/nope")).send()?;
+    println!("{} {}", missing.status(), missing.text()?);
+
+    server.shutdown();
+    Ok(())
+}
+*/
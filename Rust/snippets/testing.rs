@@ -0,0 +1,506 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies] (or [dev-dependencies] when only tests use these helpers)
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// similar = "2"
+//
+// Test helpers for code that reads files or produces JSON and text, such as the serialization
+// and HTTP snippets:
+// * `FixtureDir` gives each test its own scratch directory, optionally copied from a
+//   checked-in template, and deletes it afterwards unless `KEEP_FIXTURES=1` is set.
+// * `assert_json_snapshot!` / `assert_text_snapshot!` compare output with a golden file under
+//   `tests/snapshots/`. Run with `UPDATE_SNAPSHOTS=1` to (re)write the golden files, then
+//   review them with `git diff` before committing.
+// * Mismatches fail with a structured diff: changed JSON paths for JSON, a unified diff for text.
+// * `TestEnv` sets environment variables and the working directory for one test and restores
+//   them on drop, holding a global lock so tests that change them don't race each other.
+//
+// Assertions panic like `assert_eq!`, so they work in any `#[test]` without extra plumbing.
+
+use serde::Serialize;
+use serde_json::Value;
+use similar::TextDiff;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Set to `1` to write golden files instead of comparing against them.
+const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+/// Set to `1` to keep fixture directories after the test for inspection.
+const KEEP_VAR: &str = "KEEP_FIXTURES";
+
+fn env_flag(name: &str) -> bool {
+    matches!(std::env::var(name).as_deref(), Ok("1" | "true" | "yes"))
+}
+
+// --- Fixture directories ---
+
+/// A scratch directory unique to one test, removed on drop.
+struct FixtureDir {
+    root: PathBuf,
+    keep: bool,
+}
+
+impl FixtureDir {
+    /// Creates an empty directory under the system temp dir.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Included in the directory name, e.g. the test name, to find kept fixtures.
+    fn new(label: &str) -> io::Result<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+        let name = format!(
+            "fixture-{}-{}-{}-{}",
+            label.replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            nanos
+        );
+        let root = std::env::temp_dir().join(name);
+        fs::create_dir_all(&root)?;
+        Ok(Self { root, keep: env_flag(KEEP_VAR) })
+    }
+
+    /// Creates a fixture directory holding a copy of `template`, e.g. `tests/fixtures/config`.
+    ///
+    /// Tests can then modify the copy freely without touching the checked-in files.
+    fn from_template(label: &str, template: impl AsRef<Path>) -> io::Result<Self> {
+        let fixture = Self::new(label)?;
+        copy_dir(template.as_ref(), &fixture.root)?;
+        Ok(fixture)
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The absolute path of `relative` inside the fixture.
+    ///
+    /// Panics if `relative` is absolute or contains `..`, so a typo can't touch real files.
+    fn path(&self, relative: impl AsRef<Path>) -> PathBuf {
+        let relative = relative.as_ref();
+        assert!(
+            relative.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)),
+            "fixture paths must be relative and stay inside the fixture: {}",
+            relative.display()
+        );
+        self.root.join(relative)
+    }
+
+    /// Writes a file, creating parent directories, and returns its absolute path.
+    fn write(&self, relative: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<PathBuf> {
+        let path = self.path(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    fn write_json<T: Serialize>(&self, relative: impl AsRef<Path>, value: &T) -> io::Result<PathBuf> {
+        let json = serde_json::to_string_pretty(value).map_err(io::Error::other)?;
+        self.write(relative, json)
+    }
+
+    fn read_to_string(&self, relative: impl AsRef<Path>) -> io::Result<String> {
+        fs::read_to_string(self.path(relative))
+    }
+
+    /// Every file in the fixture, relative to its root, sorted; handy for asserting outputs.
+    fn files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut pending = vec![self.root.clone()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else {
+                    files.push(path.strip_prefix(&self.root).expect("walked from root").to_path_buf());
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Keeps the directory after drop and returns its path.
+    fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.root.clone()
+    }
+}
+
+impl Drop for FixtureDir {
+    fn drop(&mut self) {
+        if self.keep {
+            eprintln!("keeping fixture directory {}", self.root.display());
+        } else {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+// --- Environment and working-directory guards ---
+
+/// Serializes every `TestEnv`, because variables and the working directory are process-wide
+/// while `cargo test` runs tests on parallel threads.
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Temporary environment variables and working directory for one test, restored on drop.
+///
+/// Only one `TestEnv` exists at a time; creating a second one blocks until the first is
+/// dropped. Make all of a test's changes through one guard, and keep it alive for as long
+/// as the code under test runs. Tests that read the environment without taking a guard can
+/// still observe another test's changes, so give those a `TestEnv` too (even an unused one).
+struct TestEnv {
+    /// Original values, recorded the first time each variable is touched.
+    saved_vars: Vec<(OsString, Option<OsString>)>,
+    saved_dir: Option<PathBuf>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    /// Waits for exclusive access to the environment.
+    fn lock() -> Self {
+        // A test that panicked while holding the guard still restored everything on drop.
+        let lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        Self { saved_vars: Vec::new(), saved_dir: None, _lock: lock }
+    }
+
+    fn save_var(&mut self, key: &OsStr) {
+        if !self.saved_vars.iter().any(|(saved, _)| saved == key) {
+            self.saved_vars.push((key.to_os_string(), std::env::var_os(key)));
+        }
+    }
+
+    /// Sets `key` until the guard drops.
+    fn set_var(&mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> &mut Self {
+        self.save_var(key.as_ref());
+        // Edition 2024 marks this `unsafe` because another thread may read the environment
+        // concurrently; the lock makes that sound for tests that use `TestEnv`.
+        std::env::set_var(key, value);
+        self
+    }
+
+    /// Unsets `key` until the guard drops, e.g. to test a missing-variable error.
+    fn remove_var(&mut self, key: impl AsRef<OsStr>) -> &mut Self {
+        self.save_var(key.as_ref());
+        std::env::remove_var(key);
+        self
+    }
+
+    /// Changes the working directory until the guard drops, e.g. to a `FixtureDir`.
+    fn set_current_dir(&mut self, dir: impl AsRef<Path>) -> io::Result<&mut Self> {
+        let original = std::env::current_dir()?;
+        std::env::set_current_dir(dir)?;
+        self.saved_dir.get_or_insert(original);
+        Ok(self)
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        for (key, value) in self.saved_vars.drain(..).rev() {
+            match value {
+                Some(value) => std::env::set_var(&key, value),
+                None => std::env::remove_var(&key),
+            }
+        }
+        if let Some(dir) = self.saved_dir.take() {
+            if let Err(e) = std::env::set_current_dir(&dir) {
+                eprintln!("could not restore working directory {}: {}", dir.display(), e);
+            }
+        }
+    }
+}
+
+/// Runs `body` with the given variables set (`Some`) or unset (`None`), then restores them.
+fn with_env<R>(vars: &[(&str, Option<&str>)], body: impl FnOnce() -> R) -> R {
+    let mut env = TestEnv::lock();
+    for (key, value) in vars {
+        match value {
+            Some(value) => env.set_var(key, value),
+            None => env.remove_var(key),
+        };
+    }
+    body()
+}
+
+// --- Structured JSON diffs ---
+
+/// One difference between the expected and actual JSON, located by JSON Pointer.
+#[derive(Debug, Clone, PartialEq)]
+enum JsonChange {
+    Added { path: String, actual: Value },
+    Removed { path: String, expected: Value },
+    Changed { path: String, expected: Value, actual: Value },
+}
+
+impl fmt::Display for JsonChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let root = |path: &str| if path.is_empty() { "/".to_string() } else { path.to_string() };
+        match self {
+            Self::Added { path, actual } => write!(f, "+ {}: {}", root(path), actual),
+            Self::Removed { path, expected } => write!(f, "- {}: {}", root(path), expected),
+            Self::Changed { path, expected, actual } => write!(f, "~ {}: {} -> {}", root(path), expected, actual),
+        }
+    }
+}
+
+/// Lists every difference between two JSON values, recursing into objects and arrays.
+///
+/// # Returns
+///
+/// Changes in document order; empty when the values are equal. Array elements are compared
+/// by index, so an insertion shows up as changes to every later element.
+fn json_diff(expected: &Value, actual: &Value) -> Vec<JsonChange> {
+    fn walk(expected: &Value, actual: &Value, path: &str, out: &mut Vec<JsonChange>) {
+        // JSON Pointer escaping (RFC 6901), so keys containing `/` stay unambiguous.
+        let child = |key: &str| format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+        match (expected, actual) {
+            (Value::Object(e), Value::Object(a)) => {
+                for (key, e_value) in e {
+                    match a.get(key) {
+                        Some(a_value) => walk(e_value, a_value, &child(key), out),
+                        None => out.push(JsonChange::Removed { path: child(key), expected: e_value.clone() }),
+                    }
+                }
+                for (key, a_value) in a.iter().filter(|(key, _)| !e.contains_key(*key)) {
+                    out.push(JsonChange::Added { path: child(key), actual: a_value.clone() });
+                }
+            }
+            (Value::Array(e), Value::Array(a)) => {
+                for i in 0..e.len().max(a.len()) {
+                    let path = child(&i.to_string());
+                    match (e.get(i), a.get(i)) {
+                        (Some(e_value), Some(a_value)) => walk(e_value, a_value, &path, out),
+                        (Some(e_value), None) => out.push(JsonChange::Removed { path, expected: e_value.clone() }),
+                        (None, Some(a_value)) => out.push(JsonChange::Added { path, actual: a_value.clone() }),
+                        (None, None) => unreachable!("index is below one of the lengths"),
+                    }
+                }
+            }
+            (e, a) if e != a => out.push(JsonChange::Changed {
+                path: path.to_string(),
+                expected: e.clone(),
+                actual: a.clone(),
+            }),
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk(expected, actual, "", &mut out);
+    out
+}
+
+/// Replaces the values at each JSON Pointer (e.g. `/created_at`) with `"[redacted]"`.
+///
+/// Use it for timestamps, generated ids, and other values that change on every run, so the
+/// snapshot checks that they exist without pinning their value. Missing pointers are ignored.
+fn redact(value: &mut Value, pointers: &[&str]) {
+    for pointer in pointers {
+        if let Some(slot) = value.pointer_mut(pointer) {
+            *slot = Value::String("[redacted]".to_string());
+        }
+    }
+}
+
+/// Sorts object keys recursively, so `HashMap` fields (and serde_json's `preserve_order`
+/// feature) can't reorder a golden file between runs.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(k, v)| (k, sort_keys(v))).collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
+
+// --- Golden files ---
+
+/// Compares values with golden files in one directory.
+struct Snapshots {
+    dir: PathBuf,
+    update: bool,
+}
+
+impl Snapshots {
+    /// Golden files live in `dir`; `UPDATE_SNAPSHOTS=1` switches to update mode.
+    fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), update: env_flag(UPDATE_VAR) }
+    }
+
+    /// Compares `value`, serialized as pretty JSON with sorted keys, with `<name>.json`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The golden file's name without extension, usually the test name.
+    /// * `value` - Anything serializable; redact volatile fields first (see `redact`).
+    ///
+    /// # Panics
+    ///
+    /// If the golden file is missing or differs, outside update mode. The message lists each
+    /// differing JSON path.
+    fn assert_json<T: Serialize + ?Sized>(&self, name: &str, value: &T) {
+        let actual = sort_keys(serde_json::to_value(value).expect("value serializes to JSON"));
+        let rendered = serde_json::to_string_pretty(&actual).expect("JSON values always render") + "\n";
+        let path = self.dir.join(format!("{}.json", name));
+        let Some(golden) = self.read_or_update(&path, &rendered) else { return };
+        let expected: Value = serde_json::from_str(&golden)
+            .unwrap_or_else(|e| panic!("golden file {} is not valid JSON: {}", path.display(), e));
+        let changes = json_diff(&expected, &actual);
+        if !changes.is_empty() {
+            let listed: Vec<String> = changes.iter().map(ToString::to_string).collect();
+            panic!(
+                "JSON snapshot `{}` does not match {} ({} change(s)):\n{}\n\nRerun with {}=1 to accept the new output.",
+                name,
+                path.display(),
+                changes.len(),
+                listed.join("\n"),
+                UPDATE_VAR
+            );
+        }
+    }
+
+    /// Compares `actual` with `<name>.txt`, failing with a unified diff.
+    fn assert_text(&self, name: &str, actual: &str) {
+        let path = self.dir.join(format!("{}.txt", name));
+        let Some(golden) = self.read_or_update(&path, actual) else { return };
+        if golden != actual {
+            let diff = TextDiff::from_lines(golden.as_str(), actual)
+                .unified_diff()
+                .context_radius(2)
+                .header("expected", "actual")
+                .to_string();
+            panic!(
+                "text snapshot `{}` does not match {}:\n{}\nRerun with {}=1 to accept the new output.",
+                name,
+                path.display(),
+                diff,
+                UPDATE_VAR
+            );
+        }
+    }
+
+    /// In update mode, writes `rendered` and returns `None`; otherwise returns the golden text.
+    fn read_or_update(&self, path: &Path, rendered: &str) -> Option<String> {
+        if self.update {
+            fs::create_dir_all(&self.dir).expect("create snapshot directory");
+            fs::write(path, rendered).unwrap_or_else(|e| panic!("write {}: {}", path.display(), e));
+            return None;
+        }
+        match fs::read_to_string(path) {
+            Ok(golden) => Some(golden),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => panic!(
+                "missing snapshot {}; run with {}=1 to create it. Actual output:\n{}",
+                path.display(),
+                UPDATE_VAR,
+                rendered
+            ),
+            Err(e) => panic!("read {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// `assert_json_snapshot!("name", &value)` against `tests/snapshots/name.json` in this crate.
+macro_rules! assert_json_snapshot {
+    ($name:expr, $value:expr $(,)?) => {
+        Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots")).assert_json($name, $value)
+    };
+}
+
+/// `assert_text_snapshot!("name", text)` against `tests/snapshots/name.txt` in this crate.
+macro_rules! assert_text_snapshot {
+    ($name:expr, $text:expr $(,)?) => {
+        Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots")).assert_text($name, $text)
+    };
+}
+
+// Example Usage
+/*
+use serde_json::json;
+
+#[derive(Serialize)]
+struct User {
+    id: u64,
+    name: String,
+    roles: Vec<&'static str>,
+}
+
+fn main() {
+    // A test that reads a config file from its own scratch directory.
+    let fixture = FixtureDir::new("reads_config").unwrap();
+    fixture.write("config/app.toml", "port = 8080\n").unwrap();
+    fixture.write_json("data/users.json", &json!([{ "id": 1 }])).unwrap();
+    println!("fixture files: {:?}", fixture.files().unwrap());
+    println!("app.toml: {:?}", fixture.read_to_string("config/app.toml").unwrap());
+
+    // Golden files normally live in tests/snapshots; this demo keeps them in the fixture.
+    let snapshots = Snapshots { dir: fixture.path("snapshots"), update: true };
+    let user = User { id: 7, name: "Ada".into(), roles: vec!["admin"] };
+    snapshots.assert_json("user", &user); // Update mode: writes snapshots/user.json.
+    snapshots.assert_text("greeting", "Hello, Ada!\nYou have 3 new messages.\n");
+
+    let snapshots = Snapshots { update: false, ..snapshots };
+    snapshots.assert_json("user", &user); // Matches: passes silently.
+
+    // An HTTP-style response with a volatile timestamp, redacted before comparing.
+    let mut response = json!({ "user": user, "served_at": "2024-05-01T12:00:00Z" });
+    redact(&mut response, &["/served_at"]);
+    println!("redacted: {}", response);
+
+    // Mismatches panic with the changed paths (caught here only to print them).
+    std::panic::set_hook(Box::new(|_| {}));
+    let changed = User { id: 7, name: "Ada Lovelace".into(), roles: vec!["admin", "ops"] };
+    let failure = std::panic::catch_unwind(|| snapshots.assert_json("user", &changed)).unwrap_err();
+    println!("\n{}", failure.downcast_ref::<String>().unwrap());
+    let failure =
+        std::panic::catch_unwind(|| snapshots.assert_text("greeting", "Hello, Ada!\nYou have 4 new messages.\n"))
+            .unwrap_err();
+    println!("\n{}", failure.downcast_ref::<String>().unwrap());
+
+    // Environment and working directory, restored when `env` drops.
+    std::env::set_var("APP_MODE", "production");
+    {
+        let mut env = TestEnv::lock();
+        env.set_var("APP_MODE", "test").remove_var("HOME");
+        env.set_current_dir(fixture.root()).unwrap();
+        let config = std::fs::read_to_string("config/app.toml").unwrap(); // Relative to the fixture.
+        println!("\ninside: APP_MODE={:?} HOME set={} config={:?}", std::env::var("APP_MODE"), std::env::var_os("HOME").is_some(), config);
+    }
+    println!("after: APP_MODE={:?} HOME set={} cwd={}", std::env::var("APP_MODE"), std::env::var_os("HOME").is_some(), std::env::current_dir().unwrap().display());
+    let port = with_env(&[("PORT", Some("9090"))], || std::env::var("PORT"));
+    println!("with_env: {:?}, afterwards: {:?}", port, std::env::var("PORT"));
+
+    // In a real test (which also cleans up the fixture on drop):
+    // #[test]
+    // fn user_json_is_stable() {
+    //     assert_json_snapshot!("user", &User { id: 7, name: "Ada".into(), roles: vec!["admin"] });
+    // }
+    // Set KEEP_FIXTURES=1 (or call `fixture.keep()`) to inspect the files after a failure.
+}
+*/
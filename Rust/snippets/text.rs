@@ -0,0 +1,215 @@
+// Note: This example requires adding the `similar` and `strsim` crates to your Cargo.toml:
+// [dependencies]
+// similar = "2"
+// strsim = "0.11"
+//
+// Comparing text: diffs for humans (unified and word-level), similarity scores for
+// ranking, fuzzy search for "close enough" matches, and "did you mean" suggestions for CLIs.
+
+use similar::{ChangeTag, TextDiff};
+use std::fmt::Write as _;
+
+// --- Diffs ---
+
+/// A unified diff (`diff -u` / `git diff` format) between two texts.
+///
+/// # Arguments
+///
+/// * `old`, `new` - The texts to compare, split by line.
+/// * `old_name`, `new_name` - Labels for the `---` / `+++` header lines.
+/// * `context` - Unchanged lines shown around each change (3 is the usual default).
+///
+/// # Returns
+///
+/// An empty string when the inputs are identical.
+fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str, context: usize) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(context)
+        .header(old_name, new_name)
+        .to_string()
+}
+
+/// A word-level diff in `git diff --word-diff` style: `[-removed-]{+added+}`.
+///
+/// Better than a line diff for prose and single-line values such as config strings.
+fn word_diff(old: &str, new: &str) -> String {
+    let diff = TextDiff::from_words(old, new);
+    let mut out = String::new();
+    for change in diff.iter_all_changes() {
+        let value = change.value();
+        match change.tag() {
+            ChangeTag::Equal => out.push_str(value),
+            ChangeTag::Delete => {
+                let _ = write!(out, "[-{}-]", value);
+            }
+            ChangeTag::Insert => {
+                let _ = write!(out, "{{+{}+}}", value);
+            }
+        }
+    }
+    out
+}
+
+/// Line counts like `git diff --stat`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct DiffStats {
+    insertions: usize,
+    deletions: usize,
+}
+
+fn diff_stats(old: &str, new: &str) -> DiffStats {
+    let mut stats = DiffStats::default();
+    for change in TextDiff::from_lines(old, new).iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => stats.insertions += 1,
+            ChangeTag::Delete => stats.deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    stats
+}
+
+// --- Similarity ---
+
+/// Number of single-character edits (insert, delete, substitute) between `a` and `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    strsim::levenshtein(a, b)
+}
+
+/// Levenshtein scaled to 0.0 (nothing alike) - 1.0 (identical), comparable across lengths.
+fn similarity(a: &str, b: &str) -> f64 {
+    strsim::normalized_levenshtein(a, b)
+}
+
+/// Jaro-Winkler similarity (0.0 - 1.0). Rewards a shared prefix, which suits names and
+/// identifiers where typos usually happen later in the word.
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    strsim::jaro_winkler(a, b)
+}
+
+// --- Fuzzy substring search ---
+
+/// A substring of the text that approximately matches a pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FuzzyMatch {
+    /// Byte range in the searched text, usable as `&text[start..end]`.
+    start: usize,
+    end: usize,
+    /// Edits needed to turn the matched substring into the pattern.
+    distance: usize,
+}
+
+/// Finds the substring of `text` with the smallest edit distance to `pattern`
+/// (Sellers' algorithm: Levenshtein where the match may start and end anywhere).
+///
+/// Comparison is case-insensitive. Runs in O(pattern × text) time, fine for lines and
+/// documents up to a few hundred KB.
+///
+/// # Returns
+///
+/// The best match with `distance <= max_distance`, preferring the earliest on ties.
+fn fuzzy_find(pattern: &str, text: &str, max_distance: usize) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch { start: 0, end: 0, distance: 0 });
+    }
+    // Byte offset of each char boundary, so results can slice the original text.
+    let text_chars: Vec<(usize, char)> = text.char_indices().collect();
+    let boundary = |i: usize| text_chars.get(i).map(|(b, _)| *b).unwrap_or(text.len());
+
+    // Each cell holds (cost, index of the text char where this alignment started).
+    let mut prev: Vec<(usize, usize)> = (0..=pattern.len()).map(|i| (i, 0)).collect();
+    let mut best: Option<FuzzyMatch> = None;
+    for (j, (_, tc)) in text_chars.iter().enumerate() {
+        let tc = tc.to_lowercase().next().unwrap_or(*tc);
+        // Cell 0 costs nothing and starts at the next char: this lets a match begin anywhere.
+        let mut cur = vec![(0usize, j + 1); pattern.len() + 1];
+        for i in 1..=pattern.len() {
+            let substitute = (prev[i - 1].0 + usize::from(pattern[i - 1] != tc), prev[i - 1].1);
+            let delete = (cur[i - 1].0 + 1, cur[i - 1].1);
+            let insert = (prev[i].0 + 1, prev[i].1);
+            cur[i] = [substitute, delete, insert].into_iter().min_by_key(|c| c.0).unwrap();
+        }
+        let (distance, start) = cur[pattern.len()];
+        if distance <= max_distance && best.as_ref().is_none_or(|b| distance < b.distance) {
+            best = Some(FuzzyMatch {
+                start: boundary(start),
+                end: boundary(j + 1),
+                distance,
+            });
+        }
+        prev = cur;
+    }
+    best
+}
+
+// --- "Did you mean" ---
+
+/// Candidates that look like likely intended spellings of `input`, best first (max 3).
+///
+/// A candidate qualifies if its Jaro-Winkler score is at least 0.8 or it is within a
+/// small edit distance (one edit per three characters, at least one).
+fn did_you_mean<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let input_lower = input.to_lowercase();
+    let max_edits = (input.chars().count() / 3).max(1);
+    let mut scored: Vec<(f64, &str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let candidate_lower = candidate.to_lowercase();
+            let score = jaro_winkler(&input_lower, &candidate_lower);
+            let close = levenshtein(&input_lower, &candidate_lower) <= max_edits;
+            (score >= 0.8 || close).then_some((score, candidate))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.into_iter().take(3).map(|(_, c)| c).collect()
+}
+
+/// A ready-to-print error for an unknown name, e.g. a CLI subcommand or config key.
+fn unknown_with_suggestions<'a>(kind: &str, input: &str, candidates: impl IntoIterator<Item = &'a str>) -> String {
+    match did_you_mean(input, candidates).as_slice() {
+        [] => format!("unknown {} '{}'", kind, input),
+        [one] => format!("unknown {} '{}'. Did you mean '{}'?", kind, input, one),
+        many => format!(
+            "unknown {} '{}'. Did you mean one of: {}?",
+            kind,
+            input,
+            many.iter().map(|c| format!("'{}'", c)).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+// Example Usage
+/*
+fn main() {
+    let old = "name = \"app\"\nversion = \"1.2.0\"\nedition = \"2021\"\n\n[dependencies]\nserde = \"1\"\n";
+    let new = "name = \"app\"\nversion = \"1.3.0\"\nedition = \"2021\"\n\n[dependencies]\nserde = \"1\"\ntokio = \"1\"\n";
+    print!("{}", unified_diff(old, new, "a/Cargo.toml", "b/Cargo.toml", 1));
+    println!("{:?}", diff_stats(old, new));
+    println!("{}", word_diff("the quick brown fox jumps", "the quick red fox leaps"));
+
+    println!();
+    for (a, b) in [("kitten", "sitting"), ("martha", "marhta"), ("config", "configure")] {
+        println!(
+            "{:<8} vs {:<10} levenshtein={} similarity={:.2} jaro_winkler={:.2}",
+            a, b, levenshtein(a, b), similarity(a, b), jaro_winkler(a, b)
+        );
+    }
+
+    println!();
+    let log = "2024-05-01 ERROR Conection refused while contacting payment-servce";
+    for pattern in ["connection", "payment-service", "database"] {
+        match fuzzy_find(pattern, log, 2) {
+            Some(m) => println!("{:<16} ~ '{}' (distance {})", pattern, &log[m.start..m.end], m.distance),
+            None => println!("{:<16} no match", pattern),
+        }
+    }
+
+    println!();
+    let commands = ["status", "start", "stop", "restart", "logs", "config"];
+    for typo in ["stauts", "strat", "cnfig", "deploy"] {
+        println!("{}", unknown_with_suggestions("command", typo, commands));
+    }
+}
+*/
@@ -0,0 +1,306 @@
+// Note: This example requires adding the `tokio`, `tokio-rustls`, `rustls`, `webpki-roots`,
+// `x509-parser`, and `thiserror` crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+// rustls = { version = "0.23", default-features = false, features = ["ring", "std", "tls12"] }
+// tokio-rustls = { version = "0.26", default-features = false, features = ["ring", "tls12"] }
+// webpki-roots = "0.26"
+// x509-parser = "0.16"
+// thiserror = "1.0"
+//
+// Connects to a TLS endpoint, records the certificate chain the server presents, and reports
+// subject, SANs, issuer, and validity. Chain validation is performed and *reported*, not
+// enforced, so expired or misconfigured certificates can still be inspected; no application
+// data is sent. Pair it with `net_diagnostics.rs` for a reachability + certificate check.
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use x509_parser::prelude::*;
+
+#[derive(Debug, Error)]
+pub enum TlsInspectError {
+    #[error("Invalid server name '{0}'")]
+    InvalidName(String),
+    #[error("Connection to {0} timed out")]
+    Timeout(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("TLS configuration error: {0}")]
+    Tls(#[from] rustls::Error),
+    #[error("Server presented no certificates")]
+    NoCertificates,
+    #[error("Could not parse certificate: {0}")]
+    Parse(String),
+}
+
+type Result<T> = std::result::Result<T, TlsInspectError>;
+
+/// Details of one certificate in the chain.
+#[derive(Debug, Clone)]
+struct CertInfo {
+    subject: String,
+    issuer: String,
+    /// DNS names and IP addresses from the Subject Alternative Name extension.
+    sans: Vec<String>,
+    serial: String,
+    not_before: String,
+    not_after: String,
+    /// `not_after` as Unix seconds, for expiry arithmetic.
+    not_after_unix: i64,
+    is_ca: bool,
+}
+
+/// Everything learned from one handshake.
+#[derive(Debug, Clone)]
+struct TlsReport {
+    host: String,
+    port: u16,
+    /// `Ok` if the chain validated against the Mozilla roots for `host`; otherwise the reason.
+    validation: std::result::Result<(), String>,
+    protocol: String,
+    cipher_suite: String,
+    /// Leaf first, as presented by the server.
+    chain: Vec<CertInfo>,
+}
+
+/// A certificate that needs attention.
+#[derive(Debug, Clone, PartialEq)]
+enum ExpiryWarning {
+    Expired { subject: String, days_ago: i64 },
+    ExpiringSoon { subject: String, days_left: i64 },
+}
+
+impl fmt::Display for ExpiryWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpiryWarning::Expired { subject, days_ago } => write!(f, "EXPIRED {} day(s) ago: {}", days_ago, subject),
+            ExpiryWarning::ExpiringSoon { subject, days_left } => write!(f, "expires in {} day(s): {}", days_left, subject),
+        }
+    }
+}
+
+// --- Verifier that records instead of rejecting ---
+
+/// Runs the normal WebPKI verification, stores the outcome, and lets the handshake finish.
+///
+/// Only suitable for inspection: the connection is closed without sending data.
+#[derive(Debug)]
+struct RecordingVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    outcome: Mutex<Option<std::result::Result<(), String>>>,
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        let result = self
+            .inner
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+        *self.outcome.lock().unwrap() = Some(result);
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+fn parse_cert(der: &CertificateDer<'_>) -> Result<CertInfo> {
+    let (_, cert) = X509Certificate::from_der(der.as_ref()).map_err(|e| TlsInspectError::Parse(e.to_string()))?;
+    let sans = match cert.subject_alternative_name() {
+        Ok(Some(ext)) => ext
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(dns.to_string()),
+                GeneralName::IPAddress(bytes) => match bytes.len() {
+                    4 => Some(std::net::Ipv4Addr::from(<[u8; 4]>::try_from(*bytes).ok()?).to_string()),
+                    16 => Some(std::net::Ipv6Addr::from(<[u8; 16]>::try_from(*bytes).ok()?).to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let validity = cert.validity();
+    Ok(CertInfo {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        sans,
+        serial: cert.raw_serial_as_string(),
+        not_before: validity.not_before.to_string(),
+        not_after: validity.not_after.to_string(),
+        not_after_unix: validity.not_after.timestamp(),
+        is_ca: cert.is_ca(),
+    })
+}
+
+/// Connects to `host:port`, performs a TLS handshake with SNI `host`, and reports the chain.
+///
+/// # Arguments
+///
+/// * `host` - DNS name (used for SNI and name validation) or IP address.
+/// * `port` - Usually 443.
+/// * `timeout` - Upper bound for connect + handshake.
+///
+/// # Returns
+///
+/// A `TlsReport`; validation failures are reported in `validation`, not as `Err`.
+async fn inspect(host: &str, port: u16, timeout: Duration) -> Result<TlsReport> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let roots = Arc::new(RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    });
+    let webpki = WebPkiServerVerifier::builder_with_provider(roots, provider.clone())
+        .build()
+        .map_err(|e| TlsInspectError::Tls(rustls::Error::General(e.to_string())))?;
+    let verifier = Arc::new(RecordingVerifier {
+        inner: webpki,
+        outcome: Mutex::new(None),
+    });
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+
+    let server_name = ServerName::try_from(host.to_string()).map_err(|_| TlsInspectError::InvalidName(host.to_string()))?;
+    let target = format!("{}:{}", host, port);
+    let handshake = async {
+        let tcp = TcpStream::connect(&target).await?;
+        TlsConnector::from(Arc::new(config)).connect(server_name, tcp).await
+    };
+    let tls = tokio::time::timeout(timeout, handshake)
+        .await
+        .map_err(|_| TlsInspectError::Timeout(target.clone()))??;
+
+    let (_, session) = tls.get_ref();
+    let chain = session
+        .peer_certificates()
+        .ok_or(TlsInspectError::NoCertificates)?
+        .iter()
+        .map(parse_cert)
+        .collect::<Result<Vec<_>>>()?;
+    let validation = verifier
+        .outcome
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| Err("certificate was not verified".to_string()));
+    Ok(TlsReport {
+        host: host.to_string(),
+        port,
+        validation,
+        protocol: session.protocol_version().map(|v| format!("{:?}", v)).unwrap_or_default(),
+        cipher_suite: session.negotiated_cipher_suite().map(|s| format!("{:?}", s.suite())).unwrap_or_default(),
+        chain,
+    })
+}
+
+/// Returns a warning for every certificate in the chain that expires within `warn_days`.
+///
+/// # Arguments
+///
+/// * `now_unix` - Current Unix seconds (a parameter so checks are testable).
+fn check_expiry(report: &TlsReport, warn_days: i64, now_unix: i64) -> Vec<ExpiryWarning> {
+    const DAY: i64 = 86_400;
+    report
+        .chain
+        .iter()
+        .filter_map(|cert| {
+            let remaining = cert.not_after_unix - now_unix;
+            if remaining < 0 {
+                Some(ExpiryWarning::Expired {
+                    subject: cert.subject.clone(),
+                    days_ago: -remaining / DAY,
+                })
+            } else if remaining < warn_days * DAY {
+                Some(ExpiryWarning::ExpiringSoon {
+                    subject: cert.subject.clone(),
+                    days_left: remaining / DAY,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+fn print_report(report: &TlsReport) {
+    println!("{}:{} {} {}", report.host, report.port, report.protocol, report.cipher_suite);
+    match &report.validation {
+        Ok(()) => println!("  chain: valid"),
+        Err(reason) => println!("  chain: INVALID ({})", reason),
+    }
+    for (i, cert) in report.chain.iter().enumerate() {
+        println!("  [{}] {}{}", i, cert.subject, if cert.is_ca { " (CA)" } else { "" });
+        println!("      issuer:  {}", cert.issuer);
+        if !cert.sans.is_empty() {
+            println!("      SANs:    {}", cert.sans.join(", "));
+        }
+        println!("      valid:   {} -> {}", cert.not_before, cert.not_after);
+        println!("      serial:  {}", cert.serial);
+    }
+}
+
+// Example Usage
+/*
+#[tokio::main]
+async fn main() {
+    let hosts = ["www.rust-lang.org", "expired.badssl.com", "wrong.host.badssl.com"];
+    let warn_days = 30;
+
+    for host in hosts {
+        match inspect(host, 443, Duration::from_secs(10)).await {
+            Ok(report) => {
+                print_report(&report);
+                for warning in check_expiry(&report, warn_days, unix_now()) {
+                    println!("  WARNING: {}", warning);
+                }
+            }
+            Err(e) => println!("{}: {}", host, e),
+        }
+        println!();
+    }
+}
+*/
@@ -0,0 +1,378 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// tracing = "0.1"
+// tracing-subscriber = { version = "0.3", features = ["fmt", "registry"] }
+// rand = "0.8"
+// http = "1" // Header maps for reqwest and tungstenite requests
+// reqwest = { version = "0.12", features = ["blocking"] } // Only for `traced_get`
+// tokio-tungstenite = "0.24" // Only for the WebSocket handshake helpers
+// async-nats = "0.42" // Only for the NATS carrier (see `messaging.rs`)
+// lapin = "2.5" // Only for the AMQP carrier (see `rabbitmq.rs`)
+//
+// W3C Trace Context propagation, so a trace that starts in one service continues through the
+// HTTP calls, WebSocket connections, and queued messages it causes:
+// * `TraceContextLayer` gives every `tracing` span a `TraceContext` (trace id, span id, sampled
+//   flag). Child spans inherit the trace id and get a fresh span id; a span with no parent
+//   starts a new trace.
+// * `inject(&mut carrier)` writes the current span's context as `traceparent` (and
+//   `tracestate`, if any). `extract(&carrier)` reads it back, and `set_parent(&span, ctx)` makes
+//   a consumer-side span continue that trace.
+// * `Carrier` is implemented for `http::HeaderMap` (reqwest, WebSocket handshakes, servers),
+//   NATS headers, AMQP `FieldTable`s, and plain `HashMap<String, String>` (e.g. headers stored in
+//   an outbox table).
+// * The header format is the standard one, so these services interoperate with anything using
+//   OpenTelemetry's `TraceContextPropagator`. To export spans to a tracing backend, use
+//   `tracing-opentelemetry` instead of this layer; the injection points stay the same.
+
+use std::collections::HashMap;
+use std::fmt;
+use tracing::span::{Attributes, Id};
+use tracing::{Span, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::{LookupSpan, Registry};
+
+const TRACEPARENT: &str = "traceparent";
+const TRACESTATE: &str = "tracestate";
+
+// --- Trace context ---
+
+/// One position in a distributed trace, as carried by the `traceparent` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TraceContext {
+    trace_id: u128,
+    /// The id of the span this context belongs to, i.e. the parent of any remote child.
+    span_id: u64,
+    sampled: bool,
+    /// Vendor-specific `tracestate` entries, passed along unchanged.
+    trace_state: Option<String>,
+}
+
+impl TraceContext {
+    /// The first span of a new trace.
+    fn new_root() -> Self {
+        Self { trace_id: nonzero_u128(), span_id: nonzero_u64(), sampled: true, trace_state: None }
+    }
+
+    /// A new span in the same trace.
+    fn child(&self) -> Self {
+        Self { span_id: nonzero_u64(), ..self.clone() }
+    }
+
+    /// Parses a `traceparent` header: `{version}-{trace-id}-{parent-id}-{flags}` in lowercase hex.
+    ///
+    /// # Returns
+    ///
+    /// `None` for anything malformed, including all-zero ids and the forbidden version `ff`.
+    /// Per the spec, a receiver then starts a new trace rather than failing the request.
+    fn parse(traceparent: &str, trace_state: Option<&str>) -> Option<Self> {
+        let header = traceparent.trim();
+        let mut parts = header.splitn(5, '-');
+        let (version, trace_id, span_id, flags) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+        let is_hex = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+        if !is_hex(version, 2) || version == "ff" || !is_hex(trace_id, 32) || !is_hex(span_id, 16) || !is_hex(flags, 2) {
+            return None;
+        }
+        // Version 00 has exactly four fields; later versions may append more.
+        if version == "00" && parts.next().is_some() {
+            return None;
+        }
+        let trace_id = u128::from_str_radix(trace_id, 16).ok().filter(|&id| id != 0)?;
+        let span_id = u64::from_str_radix(span_id, 16).ok().filter(|&id| id != 0)?;
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        Some(Self {
+            trace_id,
+            span_id,
+            sampled: flags & 1 == 1,
+            trace_state: trace_state.map(str::trim).filter(|s| !s.is_empty()).map(str::to_string),
+        })
+    }
+
+    fn trace_id_hex(&self) -> String {
+        format!("{:032x}", self.trace_id)
+    }
+
+    fn span_id_hex(&self) -> String {
+        format!("{:016x}", self.span_id)
+    }
+}
+
+impl fmt::Display for TraceContext {
+    /// Formats as a version-00 `traceparent` value.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "00-{:032x}-{:016x}-{:02x}", self.trace_id, self.span_id, u8::from(self.sampled))
+    }
+}
+
+fn nonzero_u128() -> u128 {
+    loop {
+        let id = rand::random::<u128>();
+        if id != 0 {
+            return id;
+        }
+    }
+}
+
+fn nonzero_u64() -> u64 {
+    loop {
+        let id = rand::random::<u64>();
+        if id != 0 {
+            return id;
+        }
+    }
+}
+
+// --- Span integration ---
+
+/// Assigns a `TraceContext` to every new span, stored in the span's extensions.
+///
+/// Add it next to the formatting layer:
+/// `tracing_subscriber::registry().with(TraceContextLayer).with(fmt::layer()).init()`.
+struct TraceContextLayer;
+
+impl<S> Layer<S> for TraceContextLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let context = span
+            .parent()
+            .and_then(|parent| parent.extensions().get::<TraceContext>().map(TraceContext::child))
+            .unwrap_or_else(TraceContext::new_root);
+        span.extensions_mut().insert(context);
+    }
+}
+
+/// Runs `f` on the registry entry behind `span`, if the subscriber has one.
+fn with_span_data<T>(span: &Span, f: impl FnOnce(&tracing_subscriber::registry::SpanRef<'_, Registry>) -> T) -> Option<T> {
+    span.with_subscriber(|(id, dispatch)| {
+        let registry = dispatch.downcast_ref::<Registry>()?;
+        registry.span(id).map(|data| f(&data))
+    })
+    .flatten()
+}
+
+/// The trace context of `span`, or `None` if it is disabled or `TraceContextLayer` isn't installed.
+fn span_context(span: &Span) -> Option<TraceContext> {
+    with_span_data(span, |data| data.extensions().get::<TraceContext>().cloned()).flatten()
+}
+
+/// The trace context of the span currently entered; handy for adding `trace_id` to logs.
+fn current_context() -> Option<TraceContext> {
+    span_context(&Span::current())
+}
+
+/// Makes `span` continue a remote trace: it keeps the remote trace id, and its parent becomes
+/// the remote span. Call it right after creating the span, before entering it or creating
+/// children, since those copy the context they see at creation.
+fn set_parent(span: &Span, remote: &TraceContext) {
+    with_span_data(span, |data| {
+        data.extensions_mut().replace(remote.child());
+    });
+}
+
+// --- Carriers ---
+
+/// Somewhere trace headers can be written to and read from.
+trait Carrier {
+    fn get(&self, key: &str) -> Option<&str>;
+    fn set(&mut self, key: &str, value: String);
+}
+
+impl Carrier for http::HeaderMap {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (http::HeaderName::from_bytes(key.as_bytes()), http::HeaderValue::from_str(&value)) {
+            self.insert(name, value);
+        }
+    }
+}
+
+impl Carrier for HashMap<String, String> {
+    fn get(&self, key: &str) -> Option<&str> {
+        HashMap::get(self, key).map(String::as_str)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        self.insert(key.to_string(), value);
+    }
+}
+
+impl Carrier for async_nats::HeaderMap {
+    fn get(&self, key: &str) -> Option<&str> {
+        async_nats::HeaderMap::get(self, key).map(|value| value.as_str())
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        self.insert(key, value);
+    }
+}
+
+impl Carrier for lapin::types::FieldTable {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.inner()
+            .get(key)
+            .and_then(|value| value.as_long_string())
+            .and_then(|value| std::str::from_utf8(value.as_bytes()).ok())
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        self.insert(key.into(), lapin::types::AMQPValue::LongString(value.into()));
+    }
+}
+
+/// Writes the current span's context into `carrier`. Does nothing outside a traced span.
+fn inject(carrier: &mut impl Carrier) {
+    if let Some(context) = current_context() {
+        inject_context(&context, carrier);
+    }
+}
+
+fn inject_context(context: &TraceContext, carrier: &mut impl Carrier) {
+    carrier.set(TRACEPARENT, context.to_string());
+    if let Some(state) = &context.trace_state {
+        carrier.set(TRACESTATE, state.clone());
+    }
+}
+
+/// Reads a remote context from `carrier`; `None` if absent or malformed.
+fn extract(carrier: &impl Carrier) -> Option<TraceContext> {
+    TraceContext::parse(carrier.get(TRACEPARENT)?, carrier.get(TRACESTATE))
+}
+
+/// Creates the consumer-side span for an incoming request or message: a child of the remote
+/// caller if `carrier` has a valid `traceparent`, otherwise the root of a new trace.
+///
+/// `make_span` builds the span so the name and fields stay at the call site, e.g.
+/// `|| tracing::info_span!("handle_order", order_id)`.
+fn continue_trace(carrier: &impl Carrier, make_span: impl FnOnce() -> Span) -> Span {
+    let span = make_span();
+    if let Some(remote) = extract(carrier) {
+        set_parent(&span, &remote);
+    }
+    span
+}
+
+// --- Transport helpers ---
+
+/// Trace headers for the current span, ready for `RequestBuilder::headers`.
+fn trace_headers() -> http::HeaderMap {
+    let mut headers = http::HeaderMap::new();
+    inject(&mut headers);
+    headers
+}
+
+/// A blocking GET that carries the current trace.
+fn traced_get(client: &reqwest::blocking::Client, url: &str) -> reqwest::Result<reqwest::blocking::Response> {
+    client.get(url).headers(trace_headers()).send()
+}
+
+/// A WebSocket handshake request carrying the current trace; pass it to `connect_async`
+/// instead of the URL.
+fn traced_ws_request(
+    url: &str,
+) -> Result<tokio_tungstenite::tungstenite::handshake::client::Request, Box<dyn std::error::Error + Send + Sync>> {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    let mut request = url.into_client_request()?;
+    inject(request.headers_mut());
+    Ok(request)
+}
+
+/// A NATS publish carrying the current trace; the `messaging.rs` consumer reads it back with
+/// `continue_trace(message.headers.as_ref().unwrap_or(&Default::default()), ...)`.
+fn traced_nats_publish(payload: Vec<u8>) -> async_nats::jetstream::context::Publish {
+    let mut headers = async_nats::HeaderMap::new();
+    inject(&mut headers);
+    async_nats::jetstream::context::Publish::build().payload(payload.into()).headers(headers)
+}
+
+/// AMQP properties carrying the current trace, for `basic_publish` in `rabbitmq.rs`.
+fn traced_amqp_properties(properties: lapin::BasicProperties) -> lapin::BasicProperties {
+    let mut headers = properties.headers().clone().unwrap_or_default();
+    inject(&mut headers);
+    properties.with_headers(headers)
+}
+
+// Example Usage
+/*
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tracing::info;
+use tracing_subscriber::prelude::*;
+
+/// Logs the ids of the current span, as a tracing backend would show them.
+fn log_ids(what: &str) {
+    let ctx = current_context().expect("inside a traced span");
+    info!(trace_id = %ctx.trace_id_hex(), span_id = %ctx.span_id_hex(), "{}", what);
+}
+
+fn main() {
+    tracing_subscriber::registry()
+        .with(TraceContextLayer)
+        .with(tracing_subscriber::fmt::layer().with_target(false))
+        .with(tracing_subscriber::filter::LevelFilter::INFO)
+        .init();
+
+    // Service A handles a request and enqueues a message.
+    let mut message_headers = HashMap::new();
+    let mut http_headers = http::HeaderMap::new();
+    {
+        let _request = tracing::info_span!("checkout").entered();
+        log_ids("service A: checkout");
+        {
+            let _publish = tracing::info_span!("publish_order").entered();
+            inject(&mut message_headers);
+            inject(&mut http_headers);
+        }
+    }
+    println!("message headers: {:?}", message_headers);
+
+    // Service B, possibly much later, continues the same trace.
+    let consumer = continue_trace(&message_headers, || tracing::info_span!("handle_order", order_id = 42));
+    consumer.in_scope(|| {
+        log_ids("service B: handling order");
+        let _db = tracing::info_span!("db_write").entered();
+        log_ids("service B: nested span, same trace");
+    });
+
+    // A message without trace headers (or with garbage) starts a fresh trace.
+    let broken = HashMap::from([(TRACEPARENT.to_string(), "00-zzzz-1234-01".to_string())]);
+    continue_trace(&broken, || tracing::info_span!("handle_order")).in_scope(|| log_ids("new trace"));
+
+    // Outgoing requests: reqwest and WebSocket handshakes get the same header.
+    let _span = tracing::info_span!("outgoing").entered();
+    let client = reqwest::blocking::Client::new();
+    let request = client.get("http://localhost:8080/orders").headers(trace_headers()).build().unwrap();
+    println!("reqwest traceparent: {:?}", request.headers().get(TRACEPARENT));
+    let ws = traced_ws_request("ws://localhost:9001/feed").unwrap();
+    println!("websocket traceparent: {:?}", ws.headers().get(TRACEPARENT));
+
+    // A WebSocket server reads it in the handshake callback of `accept_hdr_async`.
+    let mut remote = None;
+    let mut callback = |req: &Request, resp: Response| {
+        remote = extract(req.headers());
+        Ok::<_, tokio_tungstenite::tungstenite::handshake::server::ErrorResponse>(resp)
+    };
+    let _ = callback(&ws, Response::new(()));
+    println!("server sees parent span {}", remote.map(|c| c.span_id_hex()).unwrap_or_default());
+
+    // Broker headers.
+    let props = traced_amqp_properties(lapin::BasicProperties::default());
+    println!("amqp traceparent: {:?}", props.headers().as_ref().and_then(|h| h.get(TRACEPARENT)));
+    let mut nats = async_nats::HeaderMap::new();
+    inject(&mut nats);
+    println!("nats traceparent: {:?}", Carrier::get(&nats, TRACEPARENT));
+
+    // Vendor tracestate survives the hop.
+    let upstream = http::HeaderMap::from_iter([
+        (http::HeaderName::from_static(TRACEPARENT), "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".parse().unwrap()),
+        (http::HeaderName::from_static(TRACESTATE), "congo=t61rcWkgMzE".parse().unwrap()),
+    ]);
+    let span = continue_trace(&upstream, || tracing::info_span!("proxy"));
+    let mut downstream = http::HeaderMap::new();
+    span.in_scope(|| inject(&mut downstream));
+    println!("forwarded: {:?}", downstream);
+}
+*/
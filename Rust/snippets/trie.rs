@@ -0,0 +1,250 @@
+// This example only uses the standard library.
+//
+// A prefix tree (trie) for autocompletion: CLI tab completion, command palettes, REPL
+// keywords, and search-as-you-type over a few thousand to a few million words.
+// Children are kept in a `BTreeMap`, so completions come out in alphabetical order.
+//
+// For ranking whole words by similarity ("did you mean"), see `text.rs`; the trie's
+// fuzzy search is for *prefixes*, i.e. completing a partially typed word with typos.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    children: BTreeMap<char, Node<V>>,
+    value: Option<V>,
+}
+
+impl<V> Default for Node<V> {
+    fn default() -> Self {
+        Self { children: BTreeMap::new(), value: None }
+    }
+}
+
+/// A map from strings to `V`, organized by shared prefixes. Use `Trie<()>` as a set.
+#[derive(Debug, Clone)]
+struct Trie<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self { root: Node::default(), len: 0 }
+    }
+}
+
+impl<V> Trie<V> {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts `key`, returning the previous value if it was already present.
+    fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for c in key.chars() {
+            node = node.children.entry(c).or_default();
+        }
+        let previous = node.value.replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    fn find_node(&self, prefix: &str) -> Option<&Node<V>> {
+        let mut node = &self.root;
+        for c in prefix.chars() {
+            node = node.children.get(&c)?;
+        }
+        Some(node)
+    }
+
+    fn get(&self, key: &str) -> Option<&V> {
+        self.find_node(key)?.value.as_ref()
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Whether any key starts with `prefix`.
+    fn has_prefix(&self, prefix: &str) -> bool {
+        self.find_node(prefix).is_some()
+    }
+
+    /// Removes `key`, pruning nodes that no longer lead to any key.
+    fn remove(&mut self, key: &str) -> Option<V> {
+        fn remove_from<V>(node: &mut Node<V>, mut chars: std::str::Chars<'_>) -> Option<V> {
+            match chars.next() {
+                None => node.value.take(),
+                Some(c) => {
+                    let child = node.children.get_mut(&c)?;
+                    let removed = remove_from(child, chars);
+                    if removed.is_some() && child.value.is_none() && child.children.is_empty() {
+                        node.children.remove(&c);
+                    }
+                    removed
+                }
+            }
+        }
+        let removed = remove_from(&mut self.root, key.chars());
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// All keys starting with `prefix`, with their values, in alphabetical order.
+    /// Stops after `limit` results (completion menus rarely need more than a screenful).
+    fn with_prefix(&self, prefix: &str, limit: usize) -> Vec<(String, &V)> {
+        let mut out = Vec::new();
+        if let Some(node) = self.find_node(prefix) {
+            let mut key = prefix.to_string();
+            collect(node, &mut key, &mut out, limit);
+        }
+        out
+    }
+
+    /// The longest string every key under `prefix` starts with, for shell-style tab
+    /// completion: typing `sta` with `start` and `status` completes to `sta` (ambiguous),
+    /// `star` completes to `start`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if no key starts with `prefix`.
+    fn complete(&self, prefix: &str) -> Option<String> {
+        let mut node = self.find_node(prefix)?;
+        let mut completed = prefix.to_string();
+        // Extend while the path is unambiguous: one child, and no key ends here.
+        while node.value.is_none() && node.children.len() == 1 {
+            let (c, child) = node.children.iter().next().expect("one child");
+            completed.push(*c);
+            node = child;
+        }
+        Some(completed)
+    }
+
+    /// Keys whose *prefix* is within `max_edits` insertions, deletions, or substitutions of
+    /// `query`, so `"sart"` finds `"start"` and `"lgo"` finds `"log"`, `"login"`, and
+    /// `"logout"`. Swapping two adjacent letters counts as two edits.
+    ///
+    /// Walks the trie once, keeping one row of the edit-distance table per node and pruning
+    /// branches that can no longer match. Keep `max_edits` small (1-2); larger values match
+    /// most of the trie for short queries.
+    ///
+    /// # Returns
+    ///
+    /// `(key, edits)` pairs, closest first, then alphabetical; at most `limit` results.
+    fn fuzzy_prefix(&self, query: &str, max_edits: usize, limit: usize) -> Vec<(String, usize)> {
+        let query: Vec<char> = query.chars().collect();
+        let first_row: Vec<usize> = (0..=query.len()).collect();
+        let mut matches = Vec::new();
+        let mut key = String::new();
+        fuzzy_walk(&self.root, &query, &first_row, None, max_edits, &mut key, &mut matches);
+        matches.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        matches.truncate(limit);
+        matches
+    }
+}
+
+fn collect<'a, V>(node: &'a Node<V>, key: &mut String, out: &mut Vec<(String, &'a V)>, limit: usize) {
+    if out.len() >= limit {
+        return;
+    }
+    if let Some(value) = &node.value {
+        out.push((key.clone(), value));
+    }
+    for (c, child) in &node.children {
+        key.push(*c);
+        collect(child, key, out, limit);
+        key.pop();
+    }
+}
+
+/// `row[i]` is the edit distance between `query[..i]` and the path to `node` (`key`).
+/// `best` is the smallest full-query distance on the path so far: once some prefix matches,
+/// every key below it is a completion, but deeper prefixes may match more closely.
+fn fuzzy_walk<V>(
+    node: &Node<V>,
+    query: &[char],
+    row: &[usize],
+    best: Option<usize>,
+    max_edits: usize,
+    key: &mut String,
+    out: &mut Vec<(String, usize)>,
+) {
+    let distance = row[query.len()];
+    let best = match best {
+        Some(b) => Some(b.min(distance)),
+        None => (distance <= max_edits).then_some(distance),
+    };
+    if let (Some(best), Some(_)) = (best, &node.value) {
+        out.push((key.clone(), best));
+    }
+    // Nothing matched yet and no cell can drop back under the limit further down.
+    if best.is_none() && row.iter().min().is_some_and(|&m| m > max_edits) {
+        return;
+    }
+    for (&c, child) in &node.children {
+        let mut next = Vec::with_capacity(row.len());
+        next.push(row[0] + 1);
+        for i in 1..row.len() {
+            let substitute = row[i - 1] + usize::from(query[i - 1] != c);
+            next.push(substitute.min(row[i] + 1).min(next[i - 1] + 1));
+        }
+        key.push(c);
+        fuzzy_walk(child, query, &next, best, max_edits, key, out);
+        key.pop();
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Trie<()> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut trie = Trie::new();
+        for key in iter {
+            trie.insert(key, ());
+        }
+        trie
+    }
+}
+
+// Example Usage
+/*
+fn main() {
+    let commands: Trie<()> = ["start", "status", "stop", "restart", "stats", "log", "login", "logout", "config", "connect"]
+        .into_iter()
+        .collect();
+    println!("{} commands", commands.len());
+
+    for prefix in ["st", "log", "co", "x"] {
+        let found: Vec<String> = commands.with_prefix(prefix, 10).into_iter().map(|(k, _)| k).collect();
+        println!("{:<4} -> {:?} (tab completes to {:?})", prefix, found, commands.complete(prefix));
+    }
+    println!("star -> tab completes to {:?}", commands.complete("star"));
+    println!("logo -> tab completes to {:?}", commands.complete("logo"));
+
+    for typo in ["sart", "rstart", "cnfig", "lgo", "sta"] {
+        println!("fuzzy {:<6} -> {:?}", typo, commands.fuzzy_prefix(typo, 1, 5));
+    }
+
+    // As a map: command -> help text.
+    let mut help: Trie<&str> = Trie::new();
+    help.insert("deploy", "Deploy the current build");
+    help.insert("deploy-preview", "Deploy to a preview environment");
+    help.insert("destroy", "Tear down the environment");
+    for (name, text) in help.with_prefix("dep", 10) {
+        println!("{:<16} {}", name, text);
+    }
+    println!("remove deploy: {:?}; still has deploy-preview: {}", help.remove("deploy"), help.contains("deploy-preview"));
+    println!("has_prefix(\"des\"): {}, len: {}", help.has_prefix("des"), help.len());
+}
+*/
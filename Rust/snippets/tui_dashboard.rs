@@ -0,0 +1,596 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// ratatui = "0.29" // re-exports crossterm as `ratatui::crossterm`, so versions can't drift
+//
+// A terminal dashboard for a long-running service or batch tool: live metrics as sparklines,
+// a table of task status, and a scrollable log tail, all in one screen over SSH.
+// * `Dashboard` is a cheap, cloneable handle. Worker threads call `metric`, `task`, and `log` on
+//   it; the UI reads the same state on each frame. Feed it from `stats.rs` (`Counter::take()`
+//   for rates, histogram percentiles for latency), `system_info.rs` samples, and the restart
+//   counts from `actor.rs`'s `spawn_supervised`.
+// * `run` owns the terminal: raw mode, alternate screen, keyboard navigation (Tab between panes,
+//   arrows/j/k to select or scroll, `l` for log level, `p` to pause, `?` for help, `q` to quit).
+// * The terminal is always restored: by a guard on normal exit or error, and by a panic hook
+//   that runs *before* the panic message is printed, so the message lands on the normal screen
+//   instead of vanishing with the alternate one. A panic in a worker thread stops the UI too.
+// * `render_to_string` draws one frame into an in-memory buffer, for snapshot tests, `--once`
+//   style output, or when stdout isn't a terminal.
+
+use ratatui::backend::{CrosstermBackend, TestBackend};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::crossterm::{cursor, execute};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Clear, Paragraph, Row, Sparkline, Table, TableState};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::io::{self, Stdout};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Once};
+use std::time::{Duration, Instant};
+
+/// Points kept per metric; wider terminals just show more of the left edge empty.
+const HISTORY: usize = 240;
+/// Log lines kept; older ones are dropped and counted.
+const LOG_CAPACITY: usize = 2_000;
+
+// --- Shared State ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum TaskStatus {
+    Running,
+    Idle,
+    Restarting { attempt: u32 },
+    Failed(String),
+    Done,
+}
+
+impl TaskStatus {
+    fn label(&self) -> String {
+        match self {
+            TaskStatus::Running => "running".into(),
+            TaskStatus::Idle => "idle".into(),
+            TaskStatus::Restarting { attempt } => format!("restarting #{}", attempt),
+            TaskStatus::Failed(_) => "failed".into(),
+            TaskStatus::Done => "done".into(),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            TaskStatus::Running => Color::Green,
+            TaskStatus::Idle | TaskStatus::Done => Color::Gray,
+            TaskStatus::Restarting { .. } => Color::Yellow,
+            TaskStatus::Failed(_) => Color::Red,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn style(self) -> Style {
+        match self {
+            Level::Debug => Style::new().fg(Color::DarkGray),
+            Level::Info => Style::new(),
+            Level::Warn => Style::new().fg(Color::Yellow),
+            Level::Error => Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Level::Debug => Level::Info,
+            Level::Info => Level::Warn,
+            Level::Warn => Level::Error,
+            Level::Error => Level::Debug,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LogLine {
+    at: Duration,
+    level: Level,
+    message: String,
+}
+
+#[derive(Debug, Clone)]
+struct Metric {
+    name: String,
+    unit: String,
+    history: VecDeque<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct TaskRow {
+    name: String,
+    status: TaskStatus,
+    restarts: u32,
+    since: Instant,
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    started: Instant,
+    metrics: Vec<Metric>,
+    tasks: Vec<TaskRow>,
+    logs: VecDeque<LogLine>,
+    dropped_logs: u64,
+}
+
+/// The handle producers use to publish to the dashboard. Clone it into each worker.
+#[derive(Debug, Clone)]
+struct Dashboard {
+    state: Arc<Mutex<State>>,
+}
+
+impl Dashboard {
+    fn new() -> Self {
+        let state = State {
+            started: Instant::now(),
+            metrics: Vec::new(),
+            tasks: Vec::new(),
+            logs: VecDeque::new(),
+            dropped_logs: 0,
+        };
+        Self { state: Arc::new(Mutex::new(state)) }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // A producer that panicked mid-update leaves plain data behind; keep drawing it.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Appends a reading to the named metric, creating it (in call order) on first use.
+    fn metric(&self, name: &str, unit: &str, value: f64) {
+        let mut state = self.lock();
+        let index = match state.metrics.iter().position(|m| m.name == name) {
+            Some(i) => i,
+            None => {
+                state.metrics.push(Metric { name: name.to_string(), unit: unit.to_string(), history: VecDeque::new() });
+                state.metrics.len() - 1
+            }
+        };
+        let history = &mut state.metrics[index].history;
+        if history.len() == HISTORY {
+            history.pop_front();
+        }
+        history.push_back(value);
+    }
+
+    /// Sets a task's status. Moving to `Restarting` also bumps its restart count.
+    fn task(&self, name: &str, status: TaskStatus) {
+        let mut state = self.lock();
+        let now = Instant::now();
+        match state.tasks.iter_mut().find(|t| t.name == name) {
+            Some(task) if task.status == status => {}
+            Some(task) => {
+                if matches!(status, TaskStatus::Restarting { .. }) {
+                    task.restarts += 1;
+                }
+                task.status = status;
+                task.since = now;
+            }
+            None => state.tasks.push(TaskRow { name: name.to_string(), status, restarts: 0, since: now }),
+        }
+    }
+
+    fn log(&self, level: Level, message: impl Into<String>) {
+        let mut state = self.lock();
+        let at = state.started.elapsed();
+        if state.logs.len() == LOG_CAPACITY {
+            state.logs.pop_front();
+            state.dropped_logs += 1;
+        }
+        state.logs.push_back(LogLine { at, level, message: message.into() });
+    }
+}
+
+// --- Terminal Setup and Restore ---
+
+static PANICKED: AtomicBool = AtomicBool::new(false);
+static HOOK: Once = Once::new();
+
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, cursor::Show);
+}
+
+/// Restores the terminal before the default hook prints the panic message.
+fn install_panic_hook() {
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            PANICKED.store(true, Ordering::SeqCst);
+            restore_terminal();
+            previous(info);
+        }));
+    });
+}
+
+/// Leaves raw mode and the alternate screen when dropped, including on `?` and unwinding.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+fn enter_terminal() -> io::Result<(Terminal<CrosstermBackend<Stdout>>, TerminalGuard)> {
+    install_panic_hook();
+    enable_raw_mode()?;
+    // From here on, any failure must still restore the terminal.
+    let guard = TerminalGuard;
+    execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+    let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    Ok((terminal, guard))
+}
+
+// --- UI ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Tasks,
+    Logs,
+}
+
+/// View state owned by the UI thread: selection, scrolling, filters, and pause.
+#[derive(Debug)]
+struct App {
+    focus: Focus,
+    tasks: TableState,
+    /// Lines scrolled up from the newest log line; 0 follows the tail.
+    log_scroll: usize,
+    min_level: Level,
+    /// A frozen copy of the state while paused.
+    paused: Option<State>,
+    show_help: bool,
+    quit: bool,
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            focus: Focus::Tasks,
+            tasks: TableState::default().with_selected(Some(0)),
+            log_scroll: 0,
+            min_level: Level::Info,
+            paused: None,
+            show_help: false,
+            quit: false,
+        }
+    }
+
+    fn on_key(&mut self, key: KeyEvent, dashboard: &Dashboard) {
+        // Raw mode turns Ctrl-C into a key press instead of SIGINT.
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+        if self.show_help {
+            self.show_help = false;
+            return;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('?') => self.show_help = true,
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Tasks => Focus::Logs,
+                    Focus::Logs => Focus::Tasks,
+                }
+            }
+            KeyCode::Char('p') => {
+                self.paused = match self.paused {
+                    Some(_) => None,
+                    None => Some(dashboard.lock().clone()),
+                }
+            }
+            KeyCode::Char('l') => {
+                self.min_level = self.min_level.next();
+                self.log_scroll = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-10),
+            KeyCode::PageDown => self.move_by(10),
+            KeyCode::Home | KeyCode::Char('g') if self.focus == Focus::Logs => self.log_scroll = usize::MAX,
+            KeyCode::End | KeyCode::Char('G') if self.focus == Focus::Logs => self.log_scroll = 0,
+            _ => {}
+        }
+    }
+
+    /// Moves the task selection, or scrolls the log (negative is up, i.e. older).
+    fn move_by(&mut self, delta: i32) {
+        match self.focus {
+            // The table clamps the selection to the row count when it renders.
+            Focus::Tasks if delta < 0 => self.tasks.scroll_up_by(delta.unsigned_abs() as u16),
+            Focus::Tasks => self.tasks.scroll_down_by(delta as u16),
+            Focus::Logs if delta < 0 => self.log_scroll = self.log_scroll.saturating_add(delta.unsigned_abs() as usize),
+            Focus::Logs => self.log_scroll = self.log_scroll.saturating_sub(delta as usize),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame, live: &State) {
+        let paused = self.paused.take();
+        let state = paused.as_ref().unwrap_or(live);
+
+        let [header, body, logs, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(8),
+            Constraint::Percentage(40),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [metrics, tasks] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(body);
+
+        self.draw_header(frame, header, state, paused.is_some());
+        draw_metrics(frame, metrics, state);
+        self.draw_tasks(frame, tasks, state);
+        self.draw_logs(frame, logs, state);
+        frame.render_widget(
+            Line::from("q quit  tab switch pane  ↑↓/jk select or scroll  g/G top/tail  l level  p pause  ? help").dark_gray(),
+            footer,
+        );
+        if self.show_help {
+            draw_help(frame);
+        }
+        self.paused = paused;
+    }
+
+    fn draw_header(&self, frame: &mut Frame, area: Rect, state: &State, paused: bool) {
+        let failed = state.tasks.iter().filter(|t| matches!(t.status, TaskStatus::Failed(_))).count();
+        let running = state.tasks.iter().filter(|t| t.status == TaskStatus::Running).count();
+        let mut spans = vec![
+            Span::from(format!(" up {} ", clock(state.started.elapsed()))).bold(),
+            Span::from(format!("│ tasks {} running", running)),
+        ];
+        if failed > 0 {
+            spans.push(Span::from(format!(", {} failed", failed)).red().bold());
+        }
+        spans.push(Span::from(format!(" │ logs {}", state.logs.len())));
+        if state.dropped_logs > 0 {
+            spans.push(Span::from(format!(" ({} dropped)", state.dropped_logs)).dark_gray());
+        }
+        if paused {
+            spans.push(Span::from("  PAUSED ").black().on_yellow());
+        }
+        frame.render_widget(Line::from(spans), area);
+    }
+
+    fn draw_tasks(&mut self, frame: &mut Frame, area: Rect, state: &State) {
+        let rows = state.tasks.iter().map(|t| {
+            let detail = match &t.status {
+                TaskStatus::Failed(reason) => reason.as_str(),
+                _ => "",
+            };
+            Row::new(vec![
+                Cell::from(t.name.as_str()),
+                Cell::from(t.status.label()).style(Style::new().fg(t.status.color())),
+                Cell::from(t.restarts.to_string()),
+                Cell::from(clock(t.since.elapsed())),
+                Cell::from(detail),
+            ])
+        });
+        let widths = [
+            Constraint::Length(14),
+            Constraint::Length(13),
+            Constraint::Length(2),
+            Constraint::Length(6),
+            Constraint::Min(8),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(["task", "status", "↻", "for", "detail"]).bold())
+            .block(pane("Tasks", self.focus == Focus::Tasks))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        frame.render_stateful_widget(table, area, &mut self.tasks);
+    }
+
+    fn draw_logs(&mut self, frame: &mut Frame, area: Rect, state: &State) {
+        let visible: Vec<&LogLine> = state.logs.iter().filter(|l| l.level >= self.min_level).collect();
+        let height = area.height.saturating_sub(2) as usize;
+        // Clamp here, where the line count and pane height are known.
+        self.log_scroll = self.log_scroll.min(visible.len().saturating_sub(height));
+        let end = visible.len() - self.log_scroll;
+        let lines: Vec<Line> = visible[end.saturating_sub(height)..end]
+            .iter()
+            .map(|l| {
+                Line::from(vec![
+                    Span::from(format!("{} ", clock(l.at))).dark_gray(),
+                    Span::styled(format!("{:<5} ", format!("{:?}", l.level).to_uppercase()), l.level.style()),
+                    Span::styled(l.message.as_str(), l.level.style()),
+                ])
+            })
+            .collect();
+        let position = match self.log_scroll {
+            0 => "following".to_string(),
+            n => format!("{} lines up", n),
+        };
+        let title = format!("Logs ≥ {:?} · {}", self.min_level, position);
+        frame.render_widget(Paragraph::new(lines).block(pane(&title, self.focus == Focus::Logs)), area);
+    }
+}
+
+fn pane(title: &str, focused: bool) -> Block<'_> {
+    let style = if focused { Style::new().cyan() } else { Style::new().dark_gray() };
+    Block::bordered().title(title).border_style(style)
+}
+
+fn draw_metrics(frame: &mut Frame, area: Rect, state: &State) {
+    let block = Block::bordered().title("Metrics").border_style(Style::new().dark_gray());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let rows = Layout::vertical(state.metrics.iter().map(|_| Constraint::Length(2))).split(inner);
+    for (metric, row) in state.metrics.iter().zip(rows.iter()) {
+        let [label, spark] = Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(*row);
+        let latest = metric.history.back().copied().unwrap_or(0.0);
+        let peak = metric.history.iter().copied().fold(0.0_f64, f64::max);
+        frame.render_widget(
+            Line::from(vec![
+                Span::from(format!("{:<16}", metric.name)),
+                Span::from(format!("{:>10.1} {}", latest, metric.unit)).bold(),
+                Span::from(format!("  peak {:.1}", peak)).dark_gray(),
+            ]),
+            label,
+        );
+        // Sparklines take integers; scale each series to its own peak.
+        let width = spark.width as usize;
+        let data: Vec<u64> = metric
+            .history
+            .iter()
+            .skip(metric.history.len().saturating_sub(width))
+            .map(|v| if peak > 0.0 { (v / peak * 100.0).round() as u64 } else { 0 })
+            .collect();
+        frame.render_widget(Sparkline::default().data(&data).max(100).style(Style::new().cyan()), spark);
+    }
+}
+
+fn draw_help(frame: &mut Frame) {
+    let text = vec![
+        Line::from("Tab / Shift-Tab   switch between Tasks and Logs"),
+        Line::from("↑ ↓ / j k         select task, or scroll logs"),
+        Line::from("PgUp PgDn         move by 10"),
+        Line::from("g / G             oldest log line / follow the tail"),
+        Line::from("l                 cycle minimum log level"),
+        Line::from("p                 pause or resume updates"),
+        Line::from("q / Esc / Ctrl-C  quit"),
+        Line::from(""),
+        Line::from("any key closes this help").dark_gray(),
+    ];
+    let [area] = Layout::horizontal([Constraint::Length(54)]).flex(Flex::Center).areas(frame.area());
+    let [area] = Layout::vertical([Constraint::Length(text.len() as u16 + 2)]).flex(Flex::Center).areas(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(Block::bordered().title("Keys")), area);
+}
+
+/// `mm:ss` or `h:mm:ss`; see `humanize.rs` (`format_clock`).
+fn clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3_600, secs % 3_600 / 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{:02}:{:02}", m, s)
+    }
+}
+
+// --- Running ---
+
+/// Runs the dashboard until the user quits, redrawing at least every `tick`.
+///
+/// # Arguments
+/// * `dashboard` - The shared handle producers publish to.
+/// * `tick` - Redraw interval when no key is pressed; 250ms feels live without burning CPU.
+///
+/// # Returns
+/// `Ok(())` when the user quits, or an error if the terminal fails or a thread panicked.
+/// The terminal is restored in every case.
+fn run(dashboard: &Dashboard, tick: Duration) -> io::Result<()> {
+    let (mut terminal, _guard) = enter_terminal()?;
+    let mut app = App::new();
+    while !app.quit {
+        if PANICKED.load(Ordering::SeqCst) {
+            return Err(io::Error::other("a thread panicked; dashboard stopped"));
+        }
+        terminal.draw(|frame| app.draw(frame, &dashboard.lock()))?;
+        if event::poll(tick)? {
+            if let Event::Key(key) = event::read()? {
+                // Windows reports releases too; act on presses only.
+                if key.kind == KeyEventKind::Press {
+                    app.on_key(key, dashboard);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Draws one frame of `width` x `height` cells as plain text, without touching the terminal.
+fn render_to_string(dashboard: &Dashboard, app: &mut App, width: u16, height: u16) -> io::Result<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|frame| app.draw(frame, &dashboard.lock()))?;
+    let buffer = terminal.backend().buffer();
+    let mut out = String::new();
+    for y in 0..height {
+        let line: String = (0..width).map(|x| buffer[(x, y)].symbol()).collect();
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+// Example Usage
+/*
+fn main() -> io::Result<()> {
+    use std::io::IsTerminal;
+    use std::thread;
+
+    let dashboard = Dashboard::new();
+
+    // --- Producers: a metrics sampler and a few supervised workers ---
+    {
+        let d = dashboard.clone();
+        thread::spawn(move || {
+            let mut n = 0u64;
+            loop {
+                n += 1;
+                let wave = ((n as f64) / 6.0).sin();
+                d.metric("requests/s", "req/s", 120.0 + 80.0 * wave);
+                d.metric("p99 latency", "ms", 35.0 + 20.0 * (wave * wave) + (n % 7) as f64);
+                d.metric("queue depth", "jobs", ((n * 37) % 50) as f64);
+                thread::sleep(Duration::from_millis(100));
+            }
+        });
+    }
+    for (i, name) in ["ingest", "resize-images", "mailer", "reindex"].into_iter().enumerate() {
+        let d = dashboard.clone();
+        thread::spawn(move || {
+            d.task(name, TaskStatus::Running);
+            for round in 1u64.. {
+                thread::sleep(Duration::from_millis(150 + 90 * i as u64));
+                d.log(Level::Debug, format!("{}: heartbeat {}", name, round));
+                if round % 5 == 0 {
+                    d.log(Level::Info, format!("{}: processed batch {}", name, round / 5));
+                }
+                match (name, round) {
+                    ("mailer", 4) => {
+                        d.log(Level::Warn, "mailer: SMTP timeout, restarting");
+                        d.task(name, TaskStatus::Restarting { attempt: 1 });
+                        thread::sleep(Duration::from_millis(300));
+                        d.task(name, TaskStatus::Running);
+                    }
+                    ("reindex", 3) => {
+                        d.log(Level::Error, "reindex: lost lease on shard 7");
+                        d.task(name, TaskStatus::Failed("lost lease on shard 7".into()));
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    if io::stdout().is_terminal() {
+        run(&dashboard, Duration::from_millis(250))
+    } else {
+        // Piped or in CI: print a single frame instead of taking over the terminal.
+        thread::sleep(Duration::from_millis(1500));
+        let mut app = App::new();
+        print!("{}", render_to_string(&dashboard, &mut app, 110, 32)?);
+        Ok(())
+    }
+}
+*/
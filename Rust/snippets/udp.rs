@@ -0,0 +1,339 @@
+// Note: This example requires adding the `tokio` and `socket2` crates to your Cargo.toml:
+// [dependencies]
+// tokio = { version = "1", features = ["full"] }
+// socket2 = { version = "0.5", features = ["all"] } // For SO_REUSEADDR/SO_REUSEPORT before bind
+//
+// UDP delivers whole datagrams (no framing needed) but with no guarantees: datagrams can be
+// lost, duplicated, or reordered. The helpers below add timeouts, collection windows, and
+// retransmission where an application needs them.
+
+use socket2::{Domain, Protocol, Socket, Type};
+use std::collections::HashMap;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Largest UDP payload over IPv4; a buffer this big never truncates a datagram.
+const MAX_DATAGRAM: usize = 65_507;
+
+// --- Basics ---
+
+/// Receives one datagram, or `None` if nothing arrives within `timeout`.
+async fn recv_with_timeout(socket: &UdpSocket, timeout: Duration) -> io::Result<Option<(Vec<u8>, SocketAddr)>> {
+    let mut buf = vec![0u8; MAX_DATAGRAM];
+    match tokio::time::timeout(timeout, socket.recv_from(&mut buf)).await {
+        Ok(Ok((len, from))) => {
+            buf.truncate(len);
+            Ok(Some((buf, from)))
+        }
+        Ok(Err(e)) => Err(e),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Binds a UDP socket that other processes on this host may bind too.
+///
+/// Needed for multicast and broadcast listeners, where several programs (or several copies
+/// of one program) listen on the same well-known port.
+fn bind_reusable(addr: SocketAddr) -> io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.set_nonblocking(true)?; // Required before handing the socket to tokio
+    socket.bind(&addr.into())?;
+    UdpSocket::from_std(socket.into())
+}
+
+// --- Multicast ---
+
+/// Joins an IPv4 multicast group (e.g. `239.255.0.1`, in the site-local range) on `port`.
+///
+/// # Arguments
+///
+/// * `group` - Multicast group address (224.0.0.0/4).
+/// * `port` - Port to listen on.
+/// * `interface` - Local interface address; `0.0.0.0` lets the OS choose.
+///
+/// # Returns
+///
+/// A socket that receives datagrams sent to `group:port`.
+fn join_multicast(group: Ipv4Addr, port: u16, interface: Ipv4Addr) -> io::Result<UdpSocket> {
+    if !group.is_multicast() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a multicast address", group)));
+    }
+    let socket = bind_reusable(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port)))?;
+    socket.join_multicast_v4(group, interface)?;
+    // Also deliver our own multicast sends to local listeners (handy for tests).
+    socket.set_multicast_loop_v4(true)?;
+    Ok(socket)
+}
+
+/// Sends one datagram to a multicast group. `ttl` limits how many routers it may cross
+/// (1 = local subnet only).
+async fn send_multicast(group: Ipv4Addr, port: u16, ttl: u32, payload: &[u8]) -> io::Result<usize> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.set_multicast_ttl_v4(ttl)?;
+    socket.send_to(payload, (group, port)).await
+}
+
+// --- Broadcast discovery ---
+
+/// Broadcasts a discovery probe and collects every reply that arrives within `window`.
+///
+/// UDP has no "end of replies", so discovery waits a fixed window and returns whatever
+/// answered. Duplicate replies from the same peer are collapsed.
+///
+/// # Arguments
+///
+/// * `target` - Usually `255.255.255.255:<port>` or a subnet broadcast like `192.168.1.255:<port>`.
+/// * `probe` - The request payload responders look for.
+/// * `window` - How long to listen for replies.
+async fn broadcast_discover(target: SocketAddr, probe: &[u8], window: Duration) -> io::Result<Vec<(SocketAddr, Vec<u8>)>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.set_broadcast(true)?;
+    socket.send_to(probe, target).await?;
+
+    let deadline = Instant::now() + window;
+    let mut replies: Vec<(SocketAddr, Vec<u8>)> = Vec::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match recv_with_timeout(&socket, remaining).await? {
+            Some((payload, from)) => {
+                if !replies.iter().any(|(addr, _)| *addr == from) {
+                    replies.push((from, payload));
+                }
+            }
+            None => break,
+        }
+    }
+    Ok(replies)
+}
+
+/// Answers discovery probes: every datagram equal to `probe` gets `reply` sent back.
+fn spawn_discovery_responder(socket: UdpSocket, probe: Vec<u8>, reply: Vec<u8>) -> JoinHandle<io::Result<()>> {
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        loop {
+            let (len, from) = socket.recv_from(&mut buf).await?;
+            if buf[..len] == probe[..] {
+                socket.send_to(&reply, from).await?;
+            }
+        }
+    })
+}
+
+// --- Request/response with correlation and retransmission ---
+//
+// Wire format: 4-byte big-endian request id, then the payload. Replies echo the id, so a
+// client can have many requests in flight on one socket and match replies to requests.
+
+fn encode_message(id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut datagram = Vec::with_capacity(4 + payload.len());
+    datagram.extend_from_slice(&id.to_be_bytes());
+    datagram.extend_from_slice(payload);
+    datagram
+}
+
+fn decode_message(datagram: &[u8]) -> Option<(u32, &[u8])> {
+    let id = u32::from_be_bytes(datagram.get(..4)?.try_into().ok()?);
+    Some((id, &datagram[4..]))
+}
+
+/// Retransmission schedule for `UdpRpcClient::request`.
+#[derive(Clone, Debug)]
+struct RetransmitPolicy {
+    attempts: u32,
+    initial_timeout: Duration,
+    max_timeout: Duration,
+}
+
+impl Default for RetransmitPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 4,
+            initial_timeout: Duration::from_millis(200),
+            max_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+type PendingReplies = Arc<Mutex<HashMap<u32, oneshot::Sender<Vec<u8>>>>>;
+
+/// Removes a request's waiter however the request ends: a reply, a timeout, a failed send,
+/// or the caller dropping the future.
+struct PendingGuard<'a> {
+    pending: &'a PendingReplies,
+    id: u32,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// A UDP client that correlates replies to requests by id and retransmits on timeout.
+struct UdpRpcClient {
+    socket: Arc<UdpSocket>,
+    pending: PendingReplies,
+    next_id: AtomicU32,
+    receiver: JoinHandle<()>,
+}
+
+impl UdpRpcClient {
+    async fn bind() -> io::Result<Self> {
+        let socket = Arc::new(UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?);
+        let pending: PendingReplies = Arc::new(Mutex::new(HashMap::new()));
+
+        // One task reads every reply and routes it to the waiting request.
+        let receiver = {
+            let socket = Arc::clone(&socket);
+            let pending = Arc::clone(&pending);
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; MAX_DATAGRAM];
+                while let Ok((len, _from)) = socket.recv_from(&mut buf).await {
+                    let Some((id, payload)) = decode_message(&buf[..len]) else { continue };
+                    // Late or duplicate replies find no waiter and are dropped.
+                    if let Some(waiter) = pending.lock().unwrap().remove(&id) {
+                        let _ = waiter.send(payload.to_vec());
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            socket,
+            pending,
+            next_id: AtomicU32::new(1),
+            receiver,
+        })
+    }
+
+    /// Sends `payload` to `server` and waits for the matching reply, retransmitting with
+    /// exponential backoff. Retransmissions reuse the request id, so a server can recognise
+    /// duplicates; handlers should still be idempotent because the *reply* may be what got lost.
+    async fn request(&self, server: SocketAddr, payload: &[u8], policy: &RetransmitPolicy) -> io::Result<Vec<u8>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, mut rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        let _guard = PendingGuard { pending: &self.pending, id };
+        let datagram = encode_message(id, payload);
+
+        let mut timeout = policy.initial_timeout;
+        for attempt in 1..=policy.attempts {
+            self.socket.send_to(&datagram, server).await?;
+            match tokio::time::timeout(timeout, &mut rx).await {
+                Ok(Ok(reply)) => return Ok(reply),
+                Ok(Err(_)) => break, // Receiver task stopped
+                Err(_) => {
+                    eprintln!("request {} attempt {} timed out after {:?}", id, attempt, timeout);
+                    timeout = (timeout * 2).min(policy.max_timeout);
+                }
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::TimedOut, format!("no reply from {} after {} attempts", server, policy.attempts)))
+    }
+}
+
+impl Drop for UdpRpcClient {
+    fn drop(&mut self) {
+        self.receiver.abort();
+    }
+}
+
+/// Serves correlated requests: `handler` maps a payload to a reply payload.
+fn spawn_rpc_server<H>(socket: UdpSocket, handler: H) -> JoinHandle<io::Result<()>>
+where
+    H: Fn(&[u8]) -> Vec<u8> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        loop {
+            let (len, from) = socket.recv_from(&mut buf).await?;
+            if let Some((id, payload)) = decode_message(&buf[..len]) {
+                socket.send_to(&encode_message(id, &handler(payload)), from).await?;
+            }
+        }
+    })
+}
+
+// Example Usage
+/*
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    println!("--- Request/response with retransmission ---");
+    let server_socket = UdpSocket::bind("127.0.0.1:0").await?;
+    let server_addr = server_socket.local_addr()?;
+    // Drop the first datagram to force a retransmission.
+    let seen = AtomicU32::new(0);
+    let flaky = UdpSocket::bind("127.0.0.1:0").await?;
+    let flaky_addr = flaky.local_addr()?;
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        loop {
+            let (len, from) = flaky.recv_from(&mut buf).await.unwrap();
+            if seen.fetch_add(1, Ordering::Relaxed) == 0 {
+                continue; // "Lost"
+            }
+            let (id, payload) = decode_message(&buf[..len]).unwrap();
+            let reply = String::from_utf8_lossy(payload).to_uppercase();
+            flaky.send_to(&encode_message(id, reply.as_bytes()), from).await.unwrap();
+        }
+    });
+    spawn_rpc_server(server_socket, |payload| payload.iter().rev().copied().collect());
+
+    let client = UdpRpcClient::bind().await?;
+    let policy = RetransmitPolicy::default();
+    let reply = client.request(server_addr, b"stressed", &policy).await?;
+    println!("reversed: {}", String::from_utf8_lossy(&reply));
+    let reply = client.request(flaky_addr, b"retry me", &policy).await?;
+    println!("flaky server: {}", String::from_utf8_lossy(&reply));
+
+    // Many requests in flight at once on one socket.
+    let client = Arc::new(client);
+    let handles: Vec<_> = (0..5)
+        .map(|i| {
+            let client = Arc::clone(&client);
+            let policy = policy.clone();
+            tokio::spawn(async move { client.request(server_addr, format!("msg{}", i).as_bytes(), &policy).await })
+        })
+        .collect();
+    for h in handles {
+        print!("{} ", String::from_utf8_lossy(&h.await.unwrap()?));
+    }
+    println!();
+
+    println!("\n--- Discovery via broadcast ---");
+    let responder = bind_reusable("0.0.0.0:47000".parse().unwrap())?;
+    spawn_discovery_responder(responder, b"DISCOVER myapp".to_vec(), b"myapp node-a tcp:8080".to_vec());
+    // 127.255.255.255 keeps the demo on loopback; use 255.255.255.255 on a real LAN.
+    let peers = broadcast_discover("127.255.255.255:47000".parse().unwrap(), b"DISCOVER myapp", Duration::from_millis(300)).await?;
+    for (addr, reply) in &peers {
+        println!("found {} -> {}", addr, String::from_utf8_lossy(reply));
+    }
+
+    println!("\n--- Multicast ---");
+    let group = Ipv4Addr::new(239, 255, 42, 99);
+    match join_multicast(group, 47001, Ipv4Addr::UNSPECIFIED) {
+        Ok(listener) => {
+            send_multicast(group, 47001, 1, b"hello group").await?;
+            match recv_with_timeout(&listener, Duration::from_millis(500)).await? {
+                Some((msg, from)) => println!("multicast from {}: {}", from, String::from_utf8_lossy(&msg)),
+                None => println!("no multicast received (no multicast route on this host?)"),
+            }
+        }
+        Err(e) => println!("multicast unavailable here: {}", e),
+    }
+    Ok(())
+}
+*/
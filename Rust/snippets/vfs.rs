@@ -0,0 +1,558 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// flate2 = "1"
+// tar = "0.4"
+// zip = { version = "2", default-features = false, features = ["deflate"] }
+// zstd = "0.13"
+// thiserror = "1.0"
+//
+// Archive-aware file reading: one `open` call for `config.json`, `app.log.gz`,
+// `backup.tar.gz!/etc/config.json`, or even `release.zip!/data.tar.zst!/schema.sql`, so log
+// scanners, importers, and `restore --dry-run` style tools don't care where a file lives.
+// * `!/` separates an archive from a path inside it, the same convention as `jar:` URLs. It
+//   only splits after a name with an archive extension (`.tar`, `.tar.gz`/`.tgz`,
+//   `.tar.zst`/`.tzst`, `.zip`, `.jar`), so an ordinary file name containing `!/` still works.
+// * Compression is detected from magic bytes, not extensions: gzip (including concatenated
+//   members, as written by `pigz` and `bgzip`) and zstd are decompressed on the fly, and
+//   anything else passes through unchanged. `Vfs::raw` turns this off for the final file.
+// * Tar members are streamed: the archive is read once, front to back, until the member is
+//   found, and the returned reader yields its bytes without extracting anything to disk. Long
+//   names (GNU and PAX headers) are handled. This reads archives written by `backup.rs`.
+// * Zip members are read straight from the file at their data offset, through a CRC check.
+//   A zip nested inside another archive has to be buffered in memory first, up to a limit.
+// * `list` shows the members of any archive path, for "did you mean" errors and browsing.
+//
+// Readers report truncation as an `UnexpectedEof` error rather than ending early, so a cut-off
+// download can't be mistaken for a short file. Only regular files can be opened: directories,
+// symlinks, and hard links inside archives are reported as `NotAFile`.
+
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use flate2::Crc;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use tar::{EntryType, Header, PaxExtensions};
+use thiserror::Error;
+use zip::{CompressionMethod, ZipArchive};
+
+#[derive(Debug, Error)]
+enum VfsError {
+    #[error("{path}: {source}")]
+    Io { path: String, source: io::Error },
+    #[error("{archive} has no member {member:?}")]
+    NotFound { archive: String, member: String },
+    #[error("{path} is not a regular file")]
+    NotAFile { path: String },
+    #[error("{path} is not an archive")]
+    NotAnArchive { path: String },
+    #[error("{path}: unsupported {what}")]
+    Unsupported { path: String, what: String },
+    #[error("{path}: nested zip is larger than the {limit}-byte buffer limit")]
+    TooLarge { path: String, limit: u64 },
+    #[error("{path}: {source}")]
+    Zip { path: String, source: zip::result::ZipError },
+}
+
+type Result<T> = std::result::Result<T, VfsError>;
+
+type BoxRead = Box<dyn Read + Send>;
+
+// --- Paths ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    /// Plain or compressed tar; compression is sniffed from the content.
+    Tar,
+    Zip,
+}
+
+const ARCHIVE_EXTENSIONS: [(&str, ArchiveKind); 7] = [
+    (".tar", ArchiveKind::Tar),
+    (".tar.gz", ArchiveKind::Tar),
+    (".tgz", ArchiveKind::Tar),
+    (".tar.zst", ArchiveKind::Tar),
+    (".tzst", ArchiveKind::Tar),
+    (".zip", ArchiveKind::Zip),
+    (".jar", ArchiveKind::Zip),
+];
+
+fn archive_kind(name: &str) -> Option<ArchiveKind> {
+    let name = name.to_ascii_lowercase();
+    ARCHIVE_EXTENSIONS.iter().find(|(ext, _)| name.ends_with(ext)).map(|(_, kind)| *kind)
+}
+
+/// A file on disk plus the chain of archive members leading to the file to read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VirtualPath {
+    file: PathBuf,
+    members: Vec<String>,
+}
+
+impl VirtualPath {
+    /// Splits `a.tar.gz!/dir/b.zip!/c.txt` into `a.tar.gz` and members `["dir/b.zip", "c.txt"]`.
+    /// Member paths are normalized the way archives store them: no leading `/` or `./`.
+    fn parse(path: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut search = 0;
+        while let Some(offset) = path[search..].find("!/") {
+            let bang = search + offset;
+            if archive_kind(&path[start..bang]).is_some() {
+                parts.push(&path[start..bang]);
+                start = bang + 2;
+            }
+            search = bang + 2;
+        }
+        parts.push(&path[start..]);
+        let file = PathBuf::from(parts[0]);
+        let members = parts[1..].iter().map(|m| normalize_member(m)).collect();
+        Self { file, members }
+    }
+
+    /// The archive holding the final member, if any.
+    fn container(&self) -> Option<VirtualPath> {
+        let mut parent = self.clone();
+        parent.members.pop().map(|_| parent)
+    }
+}
+
+impl fmt::Display for VirtualPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        for member in &self.members {
+            write!(f, "!/{}", member)?;
+        }
+        Ok(())
+    }
+}
+
+fn normalize_member(name: &str) -> String {
+    name.split('/').filter(|part| !part.is_empty() && *part != ".").collect::<Vec<_>>().join("/")
+}
+
+// --- Decompression ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// Reads up to four bytes to identify the compression, then hands back a reader that still
+/// starts at the first byte.
+fn sniff(mut reader: BoxRead) -> io::Result<(Compression, BoxRead)> {
+    let mut magic = Vec::with_capacity(4);
+    (&mut reader).take(4).read_to_end(&mut magic)?;
+    let compression = match magic.as_slice() {
+        [0x1f, 0x8b, ..] => Compression::Gzip,
+        [0x28, 0xb5, 0x2f, 0xfd] => Compression::Zstd,
+        _ => Compression::None,
+    };
+    Ok((compression, Box::new(Cursor::new(magic).chain(reader))))
+}
+
+/// Wraps `reader` in the decoder its content calls for.
+fn decompress(reader: BoxRead) -> io::Result<BoxRead> {
+    Ok(match sniff(reader)? {
+        (Compression::None, reader) => reader,
+        (Compression::Gzip, reader) => Box::new(MultiGzDecoder::new(BufReader::new(reader))),
+        (Compression::Zstd, reader) => Box::new(zstd::Decoder::new(reader)?),
+    })
+}
+
+// --- Member readers ---
+
+/// Yields exactly `remaining` bytes from `inner`, failing on early EOF, and compares a CRC-32
+/// at the end when one is expected.
+struct MemberReader<R> {
+    inner: R,
+    remaining: u64,
+    crc: Option<(Crc, u32)>,
+}
+
+impl<R: Read> MemberReader<R> {
+    fn new(inner: R, size: u64, expected_crc: Option<u32>) -> Self {
+        Self { inner, remaining: size, crc: expected_crc.map(|expected| (Crc::new(), expected)) }
+    }
+}
+
+impl<R: Read> Read for MemberReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+        let max = buf.len().min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        let n = self.inner.read(&mut buf[..max])?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("archive member truncated, {} bytes missing", self.remaining)));
+        }
+        self.remaining -= n as u64;
+        if let Some((crc, expected)) = &mut self.crc {
+            crc.update(&buf[..n]);
+            if self.remaining == 0 && crc.sum() != *expected {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "archive member failed its CRC-32 check"));
+            }
+        }
+        Ok(n)
+    }
+}
+
+/// One entry of an archive listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Member {
+    name: String,
+    size: u64,
+    is_file: bool,
+}
+
+// --- Tar ---
+
+/// `size` rounded up to whole 512-byte blocks: the bytes a member's data takes in the archive.
+/// A corrupt header can claim a size this overflows, which is reported as `InvalidData`.
+fn tar_padded_size(size: u64) -> io::Result<u64> {
+    size.checked_next_multiple_of(512)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "tar member size out of range"))
+}
+
+fn skip(reader: &mut dyn Read, bytes: u64) -> io::Result<()> {
+    let skipped = io::copy(&mut reader.take(bytes), &mut io::sink())?;
+    if skipped < bytes {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "tar archive truncated"));
+    }
+    Ok(())
+}
+
+/// Reads the next member's header, applying any GNU long-name or PAX records in front of it,
+/// and leaves `reader` at the start of the member's data. `None` at the end of the archive.
+fn next_tar_member(reader: &mut dyn Read) -> io::Result<Option<(Member, EntryType)>> {
+    let mut long_name: Option<String> = None;
+    let mut pax_size: Option<u64> = None;
+    loop {
+        let mut block = [0u8; 512];
+        match reader.read_exact(&mut block) {
+            Ok(()) => {}
+            // Archives are supposed to end with zero blocks, but many writers stop short.
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        if block.iter().all(|b| *b == 0) {
+            return Ok(None);
+        }
+        let header = Header::from_byte_slice(&block);
+        header.cksum().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "not a tar archive (bad header checksum)"))?;
+        let size = header.entry_size()?;
+        let entry_type = header.entry_type();
+
+        if entry_type == EntryType::GNULongName || entry_type == EntryType::XHeader {
+            let mut data = Vec::new();
+            reader.take(size).read_to_end(&mut data)?;
+            skip(reader, tar_padded_size(size)? - size)?;
+            if entry_type == EntryType::GNULongName {
+                long_name = Some(String::from_utf8_lossy(&data).trim_end_matches('\0').to_string());
+            } else {
+                for extension in PaxExtensions::new(&data) {
+                    let extension = extension?;
+                    match (extension.key(), extension.value()) {
+                        (Ok("path"), Ok(path)) => long_name = Some(path.to_string()),
+                        (Ok("size"), Ok(value)) => pax_size = value.parse().ok(),
+                        _ => {}
+                    }
+                }
+            }
+            continue;
+        }
+        if entry_type == EntryType::XGlobalHeader {
+            skip(reader, tar_padded_size(size)?)?;
+            continue;
+        }
+
+        let name = long_name.take().unwrap_or_else(|| String::from_utf8_lossy(&header.path_bytes()).into_owned());
+        let size = pax_size.take().unwrap_or(size);
+        let is_file = matches!(entry_type, EntryType::Regular | EntryType::Continuous);
+        return Ok(Some((Member { name: normalize_member(&name), size, is_file }, entry_type)));
+    }
+}
+
+/// Scans `reader` for `member` and returns a reader over just its bytes.
+fn open_tar_member(mut reader: BoxRead, member: &str, location: &str) -> Result<BoxRead> {
+    let io_err = |source| VfsError::Io { path: location.to_string(), source };
+    while let Some((entry, _)) = next_tar_member(&mut reader).map_err(io_err)? {
+        if entry.name == member {
+            if !entry.is_file {
+                return Err(VfsError::NotAFile { path: format!("{}!/{}", location, member) });
+            }
+            return Ok(Box::new(MemberReader::new(reader, entry.size, None)));
+        }
+        let padded = tar_padded_size(entry.size).map_err(io_err)?;
+        skip(&mut reader, padded).map_err(io_err)?;
+    }
+    Err(VfsError::NotFound { archive: location.to_string(), member: member.to_string() })
+}
+
+fn list_tar(mut reader: BoxRead, location: &str) -> Result<Vec<Member>> {
+    let io_err = |source| VfsError::Io { path: location.to_string(), source };
+    let mut members = Vec::new();
+    while let Some((entry, _)) = next_tar_member(&mut reader).map_err(io_err)? {
+        let padded = tar_padded_size(entry.size).map_err(io_err)?;
+        skip(&mut reader, padded).map_err(io_err)?;
+        members.push(entry);
+    }
+    Ok(members)
+}
+
+// --- Zip ---
+
+/// Opens `member` by seeking to its data in `source`, so the returned reader owns the file
+/// rather than borrowing a `ZipArchive`.
+fn open_zip_member<R: Read + Seek + Send + 'static>(source: R, member: &str, location: &str) -> Result<BoxRead> {
+    let zip_err = |source| VfsError::Zip { path: location.to_string(), source };
+    let mut archive = ZipArchive::new(source).map_err(zip_err)?;
+    let Some(index) = archive.index_for_name(member) else {
+        if archive.index_for_name(&format!("{}/", member)).is_some() {
+            return Err(VfsError::NotAFile { path: format!("{}!/{}", location, member) });
+        }
+        return Err(VfsError::NotFound { archive: location.to_string(), member: member.to_string() });
+    };
+    let (method, start, compressed, size, crc) = {
+        let file = archive.by_index_raw(index).map_err(zip_err)?;
+        if file.is_dir() {
+            return Err(VfsError::NotAFile { path: format!("{}!/{}", location, member) });
+        }
+        if file.encrypted() {
+            return Err(VfsError::Unsupported { path: format!("{}!/{}", location, member), what: "encrypted zip entry".into() });
+        }
+        (file.compression(), file.data_start(), file.compressed_size(), file.size(), file.crc32())
+    };
+
+    let mut source = archive.into_inner();
+    source.seek(SeekFrom::Start(start)).map_err(|source| VfsError::Io { path: location.to_string(), source })?;
+    let raw = BufReader::new(source).take(compressed);
+    let data: BoxRead = match method {
+        CompressionMethod::Stored => Box::new(raw),
+        CompressionMethod::Deflated => Box::new(DeflateDecoder::new(raw)),
+        other => {
+            return Err(VfsError::Unsupported { path: format!("{}!/{}", location, member), what: format!("zip compression {}", other) })
+        }
+    };
+    Ok(Box::new(MemberReader::new(data, size, Some(crc))))
+}
+
+fn list_zip<R: Read + Seek>(source: R, location: &str) -> Result<Vec<Member>> {
+    let zip_err = |source| VfsError::Zip { path: location.to_string(), source };
+    let mut archive = ZipArchive::new(source).map_err(zip_err)?;
+    (0..archive.len())
+        .map(|i| {
+            let file = archive.by_index_raw(i).map_err(zip_err)?;
+            Ok(Member { name: normalize_member(file.name()), size: file.size(), is_file: !file.is_dir() })
+        })
+        .collect()
+}
+
+// --- Opening virtual paths ---
+
+/// Where the bytes at one level of a virtual path come from. Zip needs `Seek`, which only the
+/// file on disk (or a buffered copy) provides.
+enum Source {
+    File(File),
+    Stream(BoxRead),
+}
+
+impl Source {
+    fn into_stream(self) -> BoxRead {
+        match self {
+            Source::File(file) => Box::new(BufReader::new(file)),
+            Source::Stream(stream) => stream,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Vfs {
+    decompress: bool,
+    max_nested_zip: u64,
+}
+
+impl Default for Vfs {
+    fn default() -> Self {
+        Self { decompress: true, max_nested_zip: 256 * 1024 * 1024 }
+    }
+}
+
+impl Vfs {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the final file's bytes as stored, without decompressing `.gz`/`.zst` content.
+    fn raw(mut self) -> Self {
+        self.decompress = false;
+        self
+    }
+
+    /// How large a zip inside another archive may be; it is read into memory to be searched.
+    fn max_nested_zip(mut self, bytes: u64) -> Self {
+        self.max_nested_zip = bytes;
+        self
+    }
+
+    /// Opens a plain path or an `archive!/member` path for reading.
+    ///
+    /// # Arguments
+    /// * `path` - e.g. `app.log.gz` or `backup.tar.gz!/etc/config.json`.
+    ///
+    /// # Returns
+    /// A streaming reader. Opening finds the member, so `NotFound` and `NotAFile` are reported
+    /// here; corruption later in the data surfaces as an `io::Error` from `read`.
+    fn open(&self, path: &str) -> Result<BoxRead> {
+        let vpath = VirtualPath::parse(path);
+        let reader = self.open_source(&vpath)?.into_stream();
+        if !self.decompress {
+            return Ok(reader);
+        }
+        decompress(reader).map_err(|source| VfsError::Io { path: vpath.to_string(), source })
+    }
+
+    fn read_to_string(&self, path: &str) -> Result<String> {
+        let mut text = String::new();
+        self.open(path)?.read_to_string(&mut text).map_err(|source| VfsError::Io { path: path.to_string(), source })?;
+        Ok(text)
+    }
+
+    /// Lists the members of the archive at `path`, which may itself be inside an archive.
+    fn list(&self, path: &str) -> Result<Vec<Member>> {
+        let vpath = VirtualPath::parse(path.trim_end_matches("!/"));
+        let location = vpath.to_string();
+        let kind = archive_kind(&location).ok_or_else(|| VfsError::NotAnArchive { path: location.clone() })?;
+        let source = self.open_source(&vpath)?;
+        match (kind, source) {
+            (ArchiveKind::Tar, source) => {
+                let stream = decompress(source.into_stream()).map_err(|source| VfsError::Io { path: location.clone(), source })?;
+                list_tar(stream, &location)
+            }
+            (ArchiveKind::Zip, Source::File(file)) => list_zip(BufReader::new(file), &location),
+            (ArchiveKind::Zip, Source::Stream(stream)) => list_zip(self.buffer(stream, &location)?, &location),
+        }
+    }
+
+    /// Walks the member chain, opening each archive inside the previous one.
+    fn open_source(&self, vpath: &VirtualPath) -> Result<Source> {
+        let display = vpath.file.display().to_string();
+        let file = File::open(&vpath.file).map_err(|source| VfsError::Io { path: display.clone(), source })?;
+        let mut source = Source::File(file);
+        let mut location = display;
+        for member in &vpath.members {
+            let kind = archive_kind(&location).ok_or_else(|| VfsError::NotAnArchive { path: location.clone() })?;
+            let reader = match (kind, source) {
+                (ArchiveKind::Tar, source) => {
+                    let stream =
+                        decompress(source.into_stream()).map_err(|source| VfsError::Io { path: location.clone(), source })?;
+                    open_tar_member(stream, member, &location)?
+                }
+                (ArchiveKind::Zip, Source::File(file)) => open_zip_member(file, member, &location)?,
+                (ArchiveKind::Zip, Source::Stream(stream)) => open_zip_member(self.buffer(stream, &location)?, member, &location)?,
+            };
+            source = Source::Stream(reader);
+            location = format!("{}!/{}", location, member);
+        }
+        Ok(source)
+    }
+
+    fn buffer(&self, stream: BoxRead, location: &str) -> Result<Cursor<Vec<u8>>> {
+        let mut bytes = Vec::new();
+        stream
+            .take(self.max_nested_zip + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|source| VfsError::Io { path: location.to_string(), source })?;
+        if bytes.len() as u64 > self.max_nested_zip {
+            return Err(VfsError::TooLarge { path: location.to_string(), limit: self.max_nested_zip });
+        }
+        Ok(Cursor::new(bytes))
+    }
+}
+
+// Example Usage
+/*
+use flate2::write::GzEncoder;
+use std::fs;
+use std::io::Write;
+use zip::write::SimpleFileOptions;
+
+fn build_fixtures(dir: &std::path::Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    // A zip holding a JSON file and a compressed log.
+    let mut zip = zip::ZipWriter::new(File::create(dir.join("release.zip"))?);
+    zip.start_file("config/app.json", SimpleFileOptions::default())?;
+    zip.write_all(br#"{"name": "demo", "port": 8080}"#)?;
+    zip.start_file("logs/app.log.gz", SimpleFileOptions::default().compression_method(CompressionMethod::Stored))?;
+    let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gz.write_all(b"2024-10-01 started\n2024-10-01 ready\n")?;
+    zip.write_all(&gz.finish()?)?;
+    zip.finish()?;
+
+    // A tar.gz with a long path, a directory, and the zip inside it.
+    let mut tar = tar::Builder::new(GzEncoder::new(File::create(dir.join("backup.tar.gz"))?, flate2::Compression::default()));
+    let long = format!("etc/{}/config.json", "nested".repeat(20));
+    for (name, data) in [("etc/hosts", &b"127.0.0.1 localhost\n"[..]), (long.as_str(), b"{\"long\": true}")] {
+        let mut header = Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        tar.append_data(&mut header, name, data)?;
+    }
+    let mut header = Header::new_gnu();
+    header.set_entry_type(EntryType::Directory);
+    header.set_size(0);
+    header.set_mode(0o755);
+    tar.append_data(&mut header, "var/", io::empty())?;
+    tar.append_path_with_name(dir.join("release.zip"), "artifacts/release.zip")?;
+    tar.into_inner()?.finish()?;
+
+    fs::write(dir.join("plain.txt"), "just a file\n")
+}
+
+fn main() -> Result<()> {
+    let dir = std::env::temp_dir().join("vfs-demo");
+    build_fixtures(&dir).map_err(|source| VfsError::Io { path: dir.display().to_string(), source })?;
+    let at = |p: &str| format!("{}/{}", dir.display(), p);
+    let vfs = Vfs::new();
+
+    // The same call for a plain file, a tar member, a zip member, and a zip inside a tar.
+    for path in [
+        at("plain.txt"),
+        at("backup.tar.gz!/etc/hosts"),
+        at("backup.tar.gz!/./etc/hosts"),
+        at("release.zip!/config/app.json"),
+        at("backup.tar.gz!/artifacts/release.zip!/logs/app.log.gz"),
+    ] {
+        let text = vfs.read_to_string(&path)?;
+        println!("{:<70} {:?}", path.replace(&dir.display().to_string(), "…"), text.lines().next().unwrap_or(""));
+    }
+
+    // A GNU long-name member, and the raw (still gzipped) bytes of a log.
+    let long = format!("backup.tar.gz!/etc/{}/config.json", "nested".repeat(20));
+    println!("long name: {}", vfs.read_to_string(&at(&long))?);
+    let mut raw = Vec::new();
+    Vfs::new().raw().open(&at("release.zip!/logs/app.log.gz"))?.read_to_end(&mut raw).unwrap();
+    println!("raw log starts with gzip magic: {}", raw.starts_with(&[0x1f, 0x8b]));
+
+    // Listing.
+    for member in vfs.list(&at("backup.tar.gz"))? {
+        println!("  {:>6} {}{}", member.size, if member.is_file { "" } else { "[dir] " }, &member.name[..member.name.len().min(48)]);
+    }
+
+    // Errors name the archive and member.
+    for path in [at("backup.tar.gz!/etc/passwd"), at("backup.tar.gz!/var"), at("plain.txt!/x"), at("missing.zip!/x")] {
+        match vfs.open(&path) {
+            Err(e) => println!("error: {}", e.to_string().replace(&dir.display().to_string(), "…")),
+            Ok(_) => println!("unexpected success for {}", path),
+        }
+    }
+    match Vfs::new().max_nested_zip(64).open(&at("backup.tar.gz!/artifacts/release.zip!/config/app.json")) {
+        Err(e) => println!("error: {}", e.to_string().replace(&dir.display().to_string(), "…")),
+        Ok(_) => println!("unexpected success"),
+    }
+    fs::remove_dir_all(&dir).ok();
+    Ok(())
+}
+*/
@@ -0,0 +1,423 @@
+// Note: This example requires adding the `wasmtime`, `serde`, `serde_json`, and `thiserror`
+// crates to your Cargo.toml:
+// [dependencies]
+// wasmtime = "41" # Default features include the `wat` text-format parser used below
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// thiserror = "1.0"
+//
+// Running WebAssembly plugins with `wasmtime`. Compared with native plugins (`plugins.rs`),
+// a WASM module can't touch memory, files, or the network outside its own sandbox: it can
+// only call the host functions you link in, it stops when it runs out of fuel, and its
+// linear memory can't grow past a cap. A crashing plugin returns an error instead of taking
+// the process down, so this suits third-party or user-supplied code.
+//
+// Guest ABI (what a plugin module must provide):
+// * exports `memory` and `alloc(len: i32) -> i32`, and optionally `dealloc(ptr: i32, len: i32)`;
+// * each callable function has the signature `(ptr: i32, len: i32) -> i64`, taking a JSON
+//   payload in guest memory and returning its JSON result packed as `(ptr << 32) | len`;
+// * it may import from module "host":
+//     `log(level: i32, ptr: i32, len: i32)`            (0 debug, 1 info, 2 warn, 3 error)
+//     `kv_get(key_ptr: i32, key_len: i32) -> i64`      (packed value, or -1 if missing)
+//     `kv_set(key_ptr: i32, key_len: i32, val_ptr: i32, val_len: i32)`
+//   KV keys are namespaced per plugin, so plugins can't read each other's data.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use wasmtime::{
+    AsContext, AsContextMut, Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, ResourceLimiter,
+    Store, Trap, TypedFunc,
+};
+
+#[derive(Debug, Error)]
+pub enum WasmError {
+    #[error("Failed to compile module '{plugin}': {message}")]
+    Compile { plugin: String, message: String },
+    #[error("Failed to instantiate module '{plugin}': {message}")]
+    Instantiate { plugin: String, message: String },
+    #[error("Module '{plugin}' does not export '{export}' with the expected signature")]
+    MissingExport { plugin: String, export: String },
+    #[error("Plugin '{plugin}' ran out of fuel after {fuel} units")]
+    OutOfFuel { plugin: String, fuel: u64 },
+    #[error("Plugin '{plugin}' tried to grow memory to {requested} bytes (limit {limit})")]
+    MemoryLimit { plugin: String, requested: usize, limit: usize },
+    #[error("Plugin '{plugin}' trapped: {message}")]
+    Trap { plugin: String, message: String },
+    #[error("Invalid payload for plugin '{plugin}': {message}")]
+    Payload { plugin: String, message: String },
+}
+
+type Result<T> = std::result::Result<T, WasmError>;
+
+/// Per-plugin resource limits.
+#[derive(Debug, Clone)]
+struct WasmLimits {
+    /// Fuel per call; roughly one unit per WebAssembly instruction.
+    fuel_per_call: u64,
+    /// Upper bound on each linear memory, in bytes.
+    max_memory_bytes: usize,
+    /// Largest payload accepted from the guest (logs, KV values, results).
+    max_payload_bytes: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self { fuel_per_call: 10_000_000, max_memory_bytes: 16 << 20, max_payload_bytes: 1 << 20 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Store shared by every plugin of a host, keyed by `"<plugin>/<key>"`.
+type KvStore = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+/// Per-instance state that host functions can reach through `Caller::data_mut`.
+struct HostState {
+    plugin: String,
+    limits: WasmLimits,
+    kv: KvStore,
+    logs: Vec<(LogLevel, String)>,
+    /// Set when the limiter refuses a `memory.grow`, to explain the trap that usually follows.
+    denied_memory: Option<usize>,
+}
+
+impl ResourceLimiter for HostState {
+    fn memory_growing(&mut self, _current: usize, desired: usize, _maximum: Option<usize>) -> wasmtime::Result<bool> {
+        if desired > self.limits.max_memory_bytes {
+            self.denied_memory = Some(desired);
+            return Ok(false); // `memory.grow` returns -1 to the guest, as the spec requires.
+        }
+        Ok(true)
+    }
+
+    fn table_growing(&mut self, _current: usize, desired: usize, _maximum: Option<usize>) -> wasmtime::Result<bool> {
+        Ok(desired <= 10_000)
+    }
+}
+
+/// Compiles modules and links the host API. One host can load many plugins.
+struct WasmHost {
+    engine: Engine,
+    linker: Linker<HostState>,
+    limits: WasmLimits,
+    kv: KvStore,
+}
+
+impl WasmHost {
+    fn new(limits: WasmLimits) -> Self {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).expect("a fuel-only config is always valid");
+
+        let mut linker = Linker::new(&engine);
+        linker
+            .func_wrap("host", "log", |mut caller: Caller<'_, HostState>, level: i32, ptr: i32, len: i32| {
+                let message = String::from_utf8_lossy(&read_guest(&mut caller, ptr, len)?).into_owned();
+                let level = match level {
+                    i32::MIN..=0 => LogLevel::Debug,
+                    1 => LogLevel::Info,
+                    2 => LogLevel::Warn,
+                    _ => LogLevel::Error,
+                };
+                caller.data_mut().logs.push((level, message));
+                Ok(())
+            })
+            .and_then(|l| {
+                l.func_wrap("host", "kv_get", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                    let key = kv_key(&mut caller, ptr, len)?;
+                    let value = caller.data().kv.lock().unwrap_or_else(|e| e.into_inner()).get(&key).cloned();
+                    match value {
+                        Some(value) => write_guest(&mut caller, &value),
+                        None => Ok(-1),
+                    }
+                })
+            })
+            .and_then(|l| {
+                l.func_wrap(
+                    "host",
+                    "kv_set",
+                    |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32, ptr: i32, len: i32| {
+                        let key = kv_key(&mut caller, key_ptr, key_len)?;
+                        let value = read_guest(&mut caller, ptr, len)?;
+                        caller.data().kv.lock().unwrap_or_else(|e| e.into_inner()).insert(key, value);
+                        Ok(())
+                    },
+                )
+            })
+            .expect("host function names are unique");
+
+        Self { engine, linker, limits, kv: KvStore::default() }
+    }
+
+    /// Compiles and instantiates a plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The plugin's name, used in errors and as its KV namespace.
+    /// * `bytes` - A `.wasm` binary or WebAssembly text (`.wat`).
+    ///
+    /// # Returns
+    ///
+    /// A `WasmPlugin` ready to `call`, or an error if the module is invalid, imports
+    /// something the host doesn't provide, or misses the ABI exports.
+    fn load(&self, name: &str, bytes: impl AsRef<[u8]>) -> Result<WasmPlugin> {
+        let module = Module::new(&self.engine, bytes)
+            .map_err(|e| WasmError::Compile { plugin: name.to_string(), message: format!("{:#}", e) })?;
+
+        let state = HostState {
+            plugin: name.to_string(),
+            limits: self.limits.clone(),
+            kv: Arc::clone(&self.kv),
+            logs: Vec::new(),
+            denied_memory: None,
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| state);
+        // Start functions and data initialization also burn fuel.
+        store.set_fuel(self.limits.fuel_per_call).expect("fuel is enabled");
+
+        let instance = self.linker.instantiate(&mut store, &module).map_err(|e| match classify(&mut store, e) {
+            WasmError::Trap { plugin, message } => WasmError::Instantiate { plugin, message },
+            other => other,
+        })?;
+        let missing = |export: &str| WasmError::MissingExport { plugin: name.to_string(), export: export.to_string() };
+        let memory = instance.get_memory(&mut store, "memory").ok_or_else(|| missing("memory"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc").map_err(|_| missing("alloc"))?;
+        let dealloc = instance.get_typed_func::<(i32, i32), ()>(&mut store, "dealloc").ok();
+
+        Ok(WasmPlugin { store, instance, memory, alloc, dealloc })
+    }
+
+    /// A copy of the KV data written by all plugins, keyed by `"<plugin>/<key>"`.
+    fn kv_snapshot(&self) -> BTreeMap<String, Vec<u8>> {
+        self.kv.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// A loaded plugin instance with its own memory and fuel budget.
+struct WasmPlugin {
+    store: Store<HostState>,
+    instance: Instance,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    dealloc: Option<TypedFunc<(i32, i32), ()>>,
+}
+
+impl WasmPlugin {
+    fn name(&self) -> &str {
+        &self.store.data().plugin
+    }
+
+    /// Serializes `input` to JSON, calls `export`, and deserializes the JSON it returns.
+    /// Each call gets a fresh `fuel_per_call` budget.
+    fn call<In, Out>(&mut self, export: &str, input: &In) -> Result<Out>
+    where
+        In: Serialize + ?Sized,
+        Out: DeserializeOwned,
+    {
+        let plugin = self.name().to_string();
+        let payload_error = |message: String| WasmError::Payload { plugin: plugin.clone(), message };
+        let input = serde_json::to_vec(input).map_err(|e| payload_error(e.to_string()))?;
+        let len = i32::try_from(input.len()).map_err(|_| payload_error("input too large".into()))?;
+        let func = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&mut self.store, export)
+            .map_err(|_| WasmError::MissingExport { plugin: plugin.clone(), export: export.to_string() })?;
+
+        let fuel = self.store.data().limits.fuel_per_call;
+        self.store.set_fuel(fuel).expect("fuel is enabled");
+        self.store.data_mut().denied_memory = None;
+
+        let ptr = self.alloc.call(&mut self.store, len).map_err(|e| classify(&mut self.store, e))?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, &input)
+            .map_err(|_| payload_error(format!("alloc returned out-of-bounds pointer {}", ptr)))?;
+        let packed = func.call(&mut self.store, (ptr, len)).map_err(|e| classify(&mut self.store, e))?;
+
+        let (out_ptr, out_len) = unpack(packed);
+        let output = self
+            .memory
+            .data(&self.store)
+            .get(out_ptr..out_ptr.saturating_add(out_len))
+            .filter(|bytes| bytes.len() <= self.store.data().limits.max_payload_bytes)
+            .ok_or_else(|| payload_error(format!("result {}+{} is out of bounds or too large", out_ptr, out_len)))?
+            .to_vec();
+        if let Some(dealloc) = &self.dealloc {
+            // Freeing is best-effort: a failure here doesn't invalidate the result.
+            let _ = dealloc.call(&mut self.store, (ptr, len));
+            let _ = dealloc.call(&mut self.store, (out_ptr as i32, out_len as i32));
+        }
+        serde_json::from_slice(&output).map_err(|e| payload_error(format!("bad result JSON: {}", e)))
+    }
+
+    /// Fuel used by the most recent call.
+    fn fuel_consumed(&self) -> u64 {
+        let remaining = self.store.get_fuel().unwrap_or(0);
+        self.store.data().limits.fuel_per_call.saturating_sub(remaining)
+    }
+
+    /// Current size of the plugin's linear memory, in bytes.
+    fn memory_bytes(&self) -> usize {
+        self.memory.data_size(&self.store)
+    }
+
+    /// Drains the messages the plugin has logged so far.
+    fn take_logs(&mut self) -> Vec<(LogLevel, String)> {
+        std::mem::take(&mut self.store.data_mut().logs)
+    }
+}
+
+/// Maps a wasmtime error to a `WasmError`, using the store state to explain traps.
+fn classify(store: &mut Store<HostState>, error: wasmtime::Error) -> WasmError {
+    let state = store.data();
+    let plugin = state.plugin.clone();
+    if let Some(requested) = state.denied_memory {
+        return WasmError::MemoryLimit { plugin, requested, limit: state.limits.max_memory_bytes };
+    }
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => WasmError::OutOfFuel { plugin, fuel: state.limits.fuel_per_call },
+        // The full error carries a wasm backtrace; the trap alone reads better in messages.
+        Some(trap) => WasmError::Trap { plugin, message: trap.to_string() },
+        None => WasmError::Trap { plugin, message: error.root_cause().to_string() },
+    }
+}
+
+fn unpack(packed: i64) -> (usize, usize) {
+    let packed = packed as u64;
+    ((packed >> 32) as usize, (packed & 0xFFFF_FFFF) as usize)
+}
+
+fn guest_memory(caller: &mut Caller<'_, HostState>) -> wasmtime::Result<Memory> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => Err(wasmtime::Error::msg("guest does not export 'memory'")),
+    }
+}
+
+/// Copies `len` bytes at `ptr` out of the guest, bounds-checked and size-limited.
+fn read_guest(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> wasmtime::Result<Vec<u8>> {
+    let (ptr, len) = (ptr as u32 as usize, len as u32 as usize);
+    if len > caller.data().limits.max_payload_bytes {
+        return Err(wasmtime::Error::msg(format!("payload of {} bytes exceeds the host limit", len)));
+    }
+    let memory = guest_memory(caller)?;
+    memory
+        .data(caller.as_context())
+        .get(ptr..ptr + len)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| wasmtime::Error::msg(format!("guest pointer {}+{} is out of bounds", ptr, len)))
+}
+
+/// Copies `bytes` into memory obtained from the guest's `alloc` and returns it packed.
+fn write_guest(caller: &mut Caller<'_, HostState>, bytes: &[u8]) -> wasmtime::Result<i64> {
+    let alloc = match caller.get_export("alloc") {
+        Some(Extern::Func(func)) => func.typed::<i32, i32>(caller.as_context())?,
+        _ => return Err(wasmtime::Error::msg("guest does not export 'alloc'")),
+    };
+    let ptr = alloc.call(caller.as_context_mut(), i32::try_from(bytes.len())?)?;
+    guest_memory(caller)?.write(caller.as_context_mut(), ptr as u32 as usize, bytes)?;
+    Ok(((ptr as u32 as u64) << 32 | bytes.len() as u64) as i64)
+}
+
+fn kv_key(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> wasmtime::Result<String> {
+    let key = String::from_utf8(read_guest(caller, ptr, len)?)?;
+    Ok(format!("{}/{}", caller.data().plugin, key))
+}
+
+// Example Usage
+/*
+use serde::Deserialize;
+
+// A real plugin would usually be Rust compiled with `--target wasm32-unknown-unknown`:
+//
+//   #[no_mangle] pub extern "C" fn alloc(len: i32) -> i32 { leak(Vec::with_capacity(len as usize)) }
+//   #[no_mangle] pub extern "C" fn handle(ptr: i32, len: i32) -> i64 {
+//       let order: Order = serde_json::from_slice(slice(ptr, len)).unwrap();
+//       pack(serde_json::to_vec(&quote(order)).unwrap())
+//   }
+//
+// This hand-written module keeps the demo self-contained. `echo` logs, stores its input
+// under "last", and returns the previous input (or the current one on the first call).
+const GUEST: &str = r#"
+(module
+  (import "host" "log" (func $log (param i32 i32 i32)))
+  (import "host" "kv_get" (func $kv_get (param i32 i32) (result i64)))
+  (import "host" "kv_set" (func $kv_set (param i32 i32 i32 i32)))
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 1024))
+  (data (i32.const 0) "last")
+  (data (i32.const 16) "handling request")
+
+  ;; A bump allocator that grows memory when needed and traps if it can't.
+  (func $alloc (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (block $ok
+      (br_if $ok (i32.le_u (i32.add (local.get $ptr) (local.get $len))
+                           (i32.mul (memory.size) (i32.const 65536))))
+      (br_if $ok (i32.ne (memory.grow (i32.add (i32.shr_u (local.get $len) (i32.const 16)) (i32.const 1)))
+                         (i32.const -1)))
+      unreachable)
+    (global.set $heap (i32.add (local.get $ptr) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "echo") (param $ptr i32) (param $len i32) (result i64)
+    (local $previous i64)
+    (call $log (i32.const 1) (i32.const 16) (i32.const 16))
+    (local.set $previous (call $kv_get (i32.const 0) (i32.const 4)))
+    (call $kv_set (i32.const 0) (i32.const 4) (local.get $ptr) (local.get $len))
+    (if (result i64) (i64.eq (local.get $previous) (i64.const -1))
+      (then (i64.or (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+                    (i64.extend_i32_u (local.get $len))))
+      (else (local.get $previous))))
+
+  (func (export "spin") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0))
+
+  (func (export "hog") (param i32 i32) (result i64)
+    (loop $forever (drop (call $alloc (i32.const 1048576))) (br $forever))
+    (i64.const 0))
+
+  (func (export "crash") (param i32 i32) (result i64)
+    unreachable))
+"#;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Order {
+    id: u32,
+    items: Vec<String>,
+}
+
+fn main() -> Result<()> {
+    let host = WasmHost::new(WasmLimits { fuel_per_call: 1_000_000, max_memory_bytes: 4 << 20, ..WasmLimits::default() });
+    let mut plugin = host.load("orders", GUEST)?;
+
+    let first: Order = plugin.call("echo", &Order { id: 1, items: vec!["book".into()] })?;
+    let second: Order = plugin.call("echo", &Order { id: 2, items: vec!["pen".into(), "ink".into()] })?;
+    println!("first call returned {:?}, second returned {:?}", first, second);
+    println!("fuel used by last call: {}", plugin.fuel_consumed());
+    println!("logs: {:?}", plugin.take_logs());
+    for (key, value) in host.kv_snapshot() {
+        println!("kv {} = {}", key, String::from_utf8_lossy(&value));
+    }
+
+    // Each failure is contained; the instance stays usable for well-behaved calls.
+    for export in ["spin", "hog", "crash", "missing"] {
+        println!("{}: {}", export, plugin.call::<_, serde_json::Value>(export, &()).unwrap_err());
+    }
+    println!("memory after hog: {} KiB", plugin.memory_bytes() / 1024);
+
+    println!("{}", host.load("broken", "(module (import \"os\" \"exec\" (func)))").err().unwrap());
+    Ok(())
+}
+*/
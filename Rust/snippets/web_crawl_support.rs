@@ -0,0 +1,615 @@
+// Note: This example requires adding the following crates to your Cargo.toml:
+// [dependencies]
+// chrono = "0.4"
+// flate2 = "1"
+// futures = "0.3"
+// quick-xml = "0.42"
+// reqwest = "0.12"
+// thiserror = "1.0"
+// tokio = { version = "1", features = ["full"] }
+// url = "2"
+//
+// The two files a well-behaved crawler reads before anything else: robots.txt, for what it may
+// fetch and how fast, and the sitemaps, for what there is to fetch. The URLs that come out are
+// the input for `scrape.rs` (HTML extraction) or a downloader that checks space with
+// `disk_space.rs`.
+// * `Robots::parse` keeps every group in the file, so one parse serves several user agents.
+//   `for_agent` picks the group for a product token (merging groups that repeat it) or falls
+//   back to `*`, per RFC 9309. `RobotsRules::is_allowed` applies the longest matching
+//   `Allow`/`Disallow` with `*` and `$` wildcards; `crawl_delay` and the `Sitemap:` lines
+//   are there too. `scrape.rs` has a smaller single-agent copy of the same matcher.
+// * `fetch_robots` maps HTTP results the way the RFC says: a 4xx means no restrictions, a 5xx
+//   means disallow everything for now. Files are cut off at 500 KiB.
+// * `parse_sitemap` reads `<urlset>`, `<sitemapindex>`, and plain-text sitemaps (one URL per
+//   line), with optional `lastmod`, `changefreq`, and `priority`. Gzipped sitemaps
+//   (`sitemap.xml.gz`) are detected by their magic bytes and decompressed with a size cap, so
+//   a hostile "sitemap" can't expand into gigabytes.
+// * `SitemapCrawler::urls` is an async stream: it fetches one sitemap at a time, only when
+//   the consumer has taken every URL from the previous one, follows indexes to `max_depth`,
+//   never fetches the same sitemap twice, and yields a failing sitemap as an `Err` item
+//   without ending the stream.
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use flate2::read::GzDecoder;
+use futures::stream::{self, Stream};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::{HashSet, VecDeque};
+use std::io::Read;
+use std::time::Duration;
+use thiserror::Error;
+use url::Url;
+
+/// RFC 9309 asks crawlers to read at least 500 KiB of robots.txt; anything past that is ignored.
+const MAX_ROBOTS_BYTES: usize = 500 * 1024;
+
+/// The sitemaps.org limit for one sitemap file, uncompressed.
+const MAX_SITEMAP_BYTES: usize = 50 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+enum CrawlError {
+    #[error("request for {url} failed: {source}")]
+    Http { url: Url, source: reqwest::Error },
+    #[error("{url} returned HTTP {status}")]
+    Status { url: Url, status: u16 },
+    #[error("{url} is larger than {limit} bytes")]
+    TooLarge { url: Url, limit: usize },
+    #[error("invalid sitemap {url}: {message}")]
+    Sitemap { url: Url, message: String },
+    #[error("invalid URL: {0}")]
+    Url(#[from] url::ParseError),
+}
+
+type Result<T> = std::result::Result<T, CrawlError>;
+
+// --- robots.txt ---
+
+/// One `User-agent` group: the agents it names and the rules under them.
+#[derive(Debug, Clone, Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// A parsed robots.txt with all of its groups.
+#[derive(Debug, Clone, Default)]
+struct Robots {
+    groups: Vec<Group>,
+    sitemaps: Vec<String>,
+}
+
+/// The rules that apply to one user agent.
+#[derive(Debug, Clone, Default)]
+struct RobotsRules {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    /// Parses a robots.txt body. Unknown lines and lines before the first `User-agent` are
+    /// ignored, as crawlers are expected to do; `Sitemap:` lines count wherever they appear.
+    fn parse(text: &str) -> Self {
+        let mut robots = Robots::default();
+        let mut current: Option<Group> = None;
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    // Consecutive `User-agent` lines share one group; one after a rule starts
+                    // the next group.
+                    if in_rules || current.is_none() {
+                        robots.groups.extend(current.take());
+                        current = Some(Group::default());
+                        in_rules = false;
+                    }
+                    if let Some(group) = current.as_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    if let Some(group) = current.as_mut() {
+                        // An empty `Disallow:` means "nothing is disallowed".
+                        if !value.is_empty() {
+                            group.rules.push(Rule { allow: key == "allow", pattern: value.to_string() });
+                        }
+                    }
+                }
+                "crawl-delay" => {
+                    in_rules = true;
+                    // Negative, NaN, infinite, and overflowing values are ignored.
+                    let delay = value.parse().ok().and_then(|s| Duration::try_from_secs_f64(s).ok());
+                    if let (Some(group), Some(delay)) = (current.as_mut(), delay) {
+                        group.crawl_delay = Some(delay);
+                    }
+                }
+                "sitemap" => robots.sitemaps.push(value.to_string()),
+                _ => {}
+            }
+        }
+        robots.groups.extend(current);
+        robots
+    }
+
+    /// Allows everything; what a missing robots.txt means.
+    fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Disallows everything; what an unreachable robots.txt means until it can be read.
+    fn disallow_all() -> Self {
+        let rule = Rule { allow: false, pattern: "/".to_string() };
+        Self { groups: vec![Group { agents: vec!["*".to_string()], rules: vec![rule], crawl_delay: None }], sitemaps: Vec::new() }
+    }
+
+    /// The rules for `user_agent`, matched on its product token (`MyBot` in
+    /// `MyBot/1.0 (+https://example.com/bot)`), case-insensitively. Every group naming the
+    /// token is merged; without one, the `*` groups apply.
+    fn for_agent(&self, user_agent: &str) -> RobotsRules {
+        let token = user_agent.split(['/', ' ']).next().unwrap_or_default().to_ascii_lowercase();
+        let matching = |name: &str| {
+            self.groups
+                .iter()
+                .filter(|g| g.agents.iter().any(|a| a == name))
+                .collect::<Vec<_>>()
+        };
+        let mut groups = matching(&token);
+        if groups.is_empty() {
+            groups = matching("*");
+        }
+        RobotsRules {
+            rules: groups.iter().flat_map(|g| g.rules.iter().cloned()).collect(),
+            crawl_delay: groups.iter().filter_map(|g| g.crawl_delay).max(),
+        }
+    }
+}
+
+impl RobotsRules {
+    /// Whether `url` may be fetched. `/robots.txt` itself is always allowed.
+    fn is_allowed(&self, url: &Url) -> bool {
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        self.is_path_allowed(&path)
+    }
+
+    /// Like `is_allowed`, for a path with an optional `?query`.
+    fn is_path_allowed(&self, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        // Longest pattern wins; on a tie, `Allow` wins (`true > false`).
+        self.rules
+            .iter()
+            .filter(|rule| robots_match(&rule.pattern, path))
+            .map(|rule| (rule.pattern.len(), rule.allow))
+            .max()
+            .is_none_or(|(_, allow)| allow)
+    }
+}
+
+/// robots.txt path matching: a prefix match where `*` matches any run of characters and a
+/// trailing `$` anchors the end.
+fn robots_match(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or_default()) else { return false };
+    let parts: Vec<&str> = parts.collect();
+    if parts.is_empty() {
+        return !anchored || rest.is_empty();
+    }
+    for (i, part) in parts.iter().enumerate() {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Reads a response body, failing once it passes `limit` bytes.
+async fn read_limited(url: &Url, mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|source| CrawlError::Http { url: url.clone(), source })? {
+        if body.len() + chunk.len() > limit {
+            return Err(CrawlError::TooLarge { url: url.clone(), limit });
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Fetches `/robots.txt` for `site`'s origin.
+///
+/// # Returns
+///
+/// The parsed file; `Robots::allow_all()` for a 4xx; `Robots::disallow_all()` for a 5xx. A
+/// connection failure is an `Err`: retry later, and don't crawl in the meantime.
+async fn fetch_robots(client: &reqwest::Client, site: &Url) -> Result<Robots> {
+    let url = site.join("/robots.txt")?;
+    let mut response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(|source| CrawlError::Http { url: url.clone(), source })?;
+    let status = response.status();
+    if status.is_client_error() {
+        return Ok(Robots::allow_all());
+    }
+    if !status.is_success() {
+        return Ok(Robots::disallow_all());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|source| CrawlError::Http { url: url.clone(), source })? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_ROBOTS_BYTES {
+            body.truncate(MAX_ROBOTS_BYTES);
+            break;
+        }
+    }
+    Ok(Robots::parse(&String::from_utf8_lossy(&body)))
+}
+
+// --- Sitemaps ---
+
+/// A page listed in a `<urlset>`.
+#[derive(Debug, Clone)]
+struct SitemapUrl {
+    loc: Url,
+    lastmod: Option<DateTime<FixedOffset>>,
+    changefreq: Option<String>,
+    priority: Option<f32>,
+}
+
+/// A child sitemap listed in a `<sitemapindex>`.
+#[derive(Debug, Clone)]
+struct SitemapRef {
+    loc: Url,
+    lastmod: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Debug)]
+enum Sitemap {
+    Urls(Vec<SitemapUrl>),
+    Index(Vec<SitemapRef>),
+}
+
+/// Parses a sitemap body: XML `<urlset>` or `<sitemapindex>`, or a plain-text list of URLs.
+/// Gzipped input is decompressed first. Entries with a missing or invalid `<loc>` are skipped.
+///
+/// # Arguments
+///
+/// * `url` - Where the sitemap came from, for error messages.
+/// * `body` - The raw (possibly gzipped) bytes.
+fn parse_sitemap(url: &Url, body: &[u8]) -> Result<Sitemap> {
+    let invalid = |message: String| CrawlError::Sitemap { url: url.clone(), message };
+    let body = if body.starts_with(&[0x1f, 0x8b]) {
+        let mut out = Vec::new();
+        GzDecoder::new(body)
+            .take(MAX_SITEMAP_BYTES as u64 + 1)
+            .read_to_end(&mut out)
+            .map_err(|e| invalid(format!("bad gzip data: {}", e)))?;
+        if out.len() > MAX_SITEMAP_BYTES {
+            return Err(CrawlError::TooLarge { url: url.clone(), limit: MAX_SITEMAP_BYTES });
+        }
+        out
+    } else {
+        body.to_vec()
+    };
+
+    let text = String::from_utf8_lossy(&body);
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+    if !trimmed.starts_with('<') {
+        let urls = trimmed
+            .lines()
+            .filter_map(|line| Url::parse(line.trim()).ok())
+            .map(|loc| SitemapUrl { loc, lastmod: None, changefreq: None, priority: None })
+            .collect();
+        return Ok(Sitemap::Urls(urls));
+    }
+
+    let mut reader = Reader::from_reader(trimmed.as_bytes());
+    let mut buf = Vec::new();
+    let mut is_index = None;
+    let mut urls = Vec::new();
+    let mut refs = Vec::new();
+    // The fields of the `<url>`/`<sitemap>` entry being read, and the element whose text
+    // is being collected (entity references arrive as separate events).
+    let mut fields: [Option<String>; 4] = Default::default();
+    let mut field: Option<usize> = None;
+    let mut raw = String::new();
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| invalid(format!("at byte {}: {}", reader.buffer_position(), e)))?;
+        match event {
+            Event::Start(start) => {
+                let name = start.local_name();
+                match name.as_ref() {
+                    "urlset" => is_index = Some(false),
+                    "sitemapindex" => is_index = Some(true),
+                    "url" | "sitemap" => fields = Default::default(),
+                    "loc" => field = Some(0),
+                    "lastmod" => field = Some(1),
+                    "changefreq" => field = Some(2),
+                    "priority" => field = Some(3),
+                    _ => {}
+                }
+                raw.clear();
+            }
+            Event::Text(text) if field.is_some() => raw.push_str(&text.xml10_content()),
+            Event::CData(cdata) if field.is_some() => {
+                raw.push_str(&quick_xml::escape::escape(cdata.xml10_content().as_ref()))
+            }
+            Event::GeneralRef(reference) if field.is_some() => {
+                raw.push('&');
+                raw.push_str(&reference.xml10_content());
+                raw.push(';');
+            }
+            Event::End(end) => {
+                match end.local_name().as_ref() {
+                    "loc" | "lastmod" | "changefreq" | "priority" => {
+                        if let Some(i) = field.take() {
+                            let value = quick_xml::escape::unescape(&raw).map(|v| v.trim().to_string()).unwrap_or_default();
+                            fields[i] = Some(value).filter(|v| !v.is_empty());
+                        }
+                    }
+                    "url" => {
+                        if let Some(loc) = fields[0].as_deref().and_then(|l| Url::parse(l).ok()) {
+                            urls.push(SitemapUrl {
+                                loc,
+                                lastmod: fields[1].as_deref().and_then(parse_lastmod),
+                                changefreq: fields[2].take(),
+                                priority: fields[3].as_deref().and_then(|p| p.parse().ok()),
+                            });
+                        }
+                    }
+                    "sitemap" => {
+                        if let Some(loc) = fields[0].as_deref().and_then(|l| Url::parse(l).ok()) {
+                            refs.push(SitemapRef { loc, lastmod: fields[1].as_deref().and_then(parse_lastmod) });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    match is_index {
+        Some(true) => Ok(Sitemap::Index(refs)),
+        Some(false) => Ok(Sitemap::Urls(urls)),
+        None => Err(invalid("no <urlset> or <sitemapindex> root element".to_string())),
+    }
+}
+
+/// Parses a W3C datetime as sitemaps use it: a full timestamp with an offset, or just a date
+/// (taken as midnight UTC).
+fn parse_lastmod(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value).ok().or_else(|| {
+        let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc().fixed_offset())
+    })
+}
+
+// --- Crawling sitemaps ---
+
+/// Walks sitemaps and sitemap indexes and streams the page URLs they list.
+struct SitemapCrawler {
+    client: reqwest::Client,
+    max_depth: usize,
+    max_sitemaps: usize,
+    same_host: bool,
+    rules: Option<RobotsRules>,
+}
+
+impl SitemapCrawler {
+    fn new(client: reqwest::Client) -> Self {
+        Self { client, max_depth: 3, max_sitemaps: 1000, same_host: true, rules: None }
+    }
+
+    /// How many levels of `<sitemapindex>` to follow (default 3; the protocol allows one).
+    fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// The most sitemap files to fetch in one crawl (default 1000).
+    fn max_sitemaps(mut self, max: usize) -> Self {
+        self.max_sitemaps = max;
+        self
+    }
+
+    /// Whether to drop listed URLs on a different host from their sitemap (default `true`;
+    /// the protocol only lets a sitemap list URLs on its own host).
+    fn same_host(mut self, same_host: bool) -> Self {
+        self.same_host = same_host;
+        self
+    }
+
+    /// Drops URLs (and child sitemaps) that `rules` disallow.
+    fn robots(mut self, rules: RobotsRules) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    /// The sitemaps a site advertises in robots.txt, or `/sitemap.xml` if it names none.
+    async fn discover(&self, site: &Url) -> Result<Vec<Url>> {
+        let robots = fetch_robots(&self.client, site).await?;
+        let listed: Vec<Url> = robots.sitemaps.iter().filter_map(|s| site.join(s).ok()).collect();
+        if listed.is_empty() {
+            Ok(vec![site.join("/sitemap.xml")?])
+        } else {
+            Ok(listed)
+        }
+    }
+
+    async fn fetch(&self, url: &Url) -> Result<Sitemap> {
+        let response = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|source| CrawlError::Http { url: url.clone(), source })?;
+        if !response.status().is_success() {
+            return Err(CrawlError::Status { url: url.clone(), status: response.status().as_u16() });
+        }
+        let body = read_limited(url, response, MAX_SITEMAP_BYTES).await?;
+        parse_sitemap(url, &body)
+    }
+
+    fn allowed(&self, url: &Url, sitemap: &Url) -> bool {
+        (!self.same_host || url.host_str() == sitemap.host_str())
+            && self.rules.as_ref().is_none_or(|rules| rules.is_allowed(url))
+    }
+
+    /// Streams the page URLs listed under `roots`, in sitemap order.
+    ///
+    /// # Returns
+    ///
+    /// A stream of page URLs, with an `Err` item for each sitemap that couldn't be fetched
+    /// or parsed. The stream ends when every reachable sitemap has been read or
+    /// `max_sitemaps` have been fetched.
+    fn urls(&self, roots: Vec<Url>) -> impl Stream<Item = Result<SitemapUrl>> + '_ {
+        struct State {
+            queue: VecDeque<(Url, usize)>,
+            pending: VecDeque<SitemapUrl>,
+            visited: HashSet<Url>,
+        }
+        let state = State {
+            visited: roots.iter().cloned().collect(),
+            queue: roots.into_iter().map(|url| (url, 0)).collect(),
+            pending: VecDeque::new(),
+        };
+
+        stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(url) = state.pending.pop_front() {
+                    return Some((Ok(url), state));
+                }
+                if state.visited.len() - state.queue.len() >= self.max_sitemaps {
+                    return None;
+                }
+                let (sitemap_url, depth) = state.queue.pop_front()?;
+                match self.fetch(&sitemap_url).await {
+                    Ok(Sitemap::Urls(urls)) => {
+                        state.pending.extend(urls.into_iter().filter(|u| self.allowed(&u.loc, &sitemap_url)));
+                    }
+                    Ok(Sitemap::Index(children)) if depth < self.max_depth => {
+                        for child in children {
+                            if self.allowed(&child.loc, &sitemap_url) && state.visited.insert(child.loc.clone()) {
+                                state.queue.push_back((child.loc, depth + 1));
+                            }
+                        }
+                    }
+                    Ok(Sitemap::Index(_)) => {}
+                    Err(err) => return Some((Err(err), state)),
+                }
+            }
+        })
+    }
+}
+
+// Example Usage
+/*
+use futures::StreamExt;
+
+const ROBOTS: &str = "\
+User-agent: *
+Disallow: /admin/
+Allow: /admin/help$
+Crawl-delay: 1
+
+User-agent: BookBot
+User-agent: OtherBot
+Disallow: /search
+Disallow: /files*.pdf$
+Crawl-delay: 5
+
+Sitemap: https://shop.example/sitemap_index.xml
+";
+
+const SITEMAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://shop.example/</loc><priority>1.0</priority></url>
+  <url>
+    <loc>https://shop.example/search?q=a&amp;page=2</loc>
+    <lastmod>2024-10-01</lastmod>
+    <changefreq>daily</changefreq>
+  </url>
+  <url><loc><![CDATA[https://shop.example/products/42]]></loc><lastmod>2024-10-05T08:30:00+02:00</lastmod></url>
+</urlset>"#;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // robots.txt: one parse, rules per agent.
+    let robots = Robots::parse(ROBOTS);
+    let bookbot = robots.for_agent("BookBot/1.0 (+https://example.com/bot)");
+    let other = robots.for_agent("curl/8.0");
+    for path in ["/admin/users", "/admin/help", "/search?q=x", "/files/a.pdf", "/files/a.pdf.html"] {
+        println!("{:<20} BookBot={:<5} others={}", path, bookbot.is_path_allowed(path), other.is_path_allowed(path));
+    }
+    println!("crawl delays: {:?} / {:?}; sitemaps {:?}", bookbot.crawl_delay, other.crawl_delay, robots.sitemaps);
+
+    // Sitemaps, plain and gzipped.
+    let url = Url::parse("https://shop.example/sitemap.xml")?;
+    if let Sitemap::Urls(urls) = parse_sitemap(&url, SITEMAP.as_bytes())? {
+        for entry in &urls {
+            let allowed = bookbot.is_allowed(&entry.loc);
+            println!("{} lastmod={:?} allowed={}", entry.loc, entry.lastmod.map(|d| d.to_rfc3339()), allowed);
+        }
+    }
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    std::io::Write::write_all(&mut gz, SITEMAP.as_bytes()).expect("in-memory write");
+    let gz = gz.finish().expect("in-memory write");
+    if let Sitemap::Urls(urls) = parse_sitemap(&url, &gz)? {
+        println!("gzipped sitemap: {} URLs from {} bytes", urls.len(), gz.len());
+    }
+
+    // A live site: discover its sitemaps, then stream the URLs robots.txt lets us fetch.
+    let client = reqwest::Client::builder()
+        .user_agent("BookBot/1.0 (+https://example.com/bot)")
+        .timeout(Duration::from_secs(20))
+        .build()
+        .expect("client config is valid");
+    let site = Url::parse("https://www.rust-lang.org/")?;
+    let rules = match fetch_robots(&client, &site).await {
+        Ok(robots) => robots.for_agent("BookBot"),
+        Err(err) => {
+            println!("live crawl skipped: {}", err);
+            return Ok(());
+        }
+    };
+    let crawler = SitemapCrawler::new(client).robots(rules).max_sitemaps(5);
+    let roots = crawler.discover(&site).await?;
+    let mut urls = std::pin::pin!(crawler.urls(roots).take(10));
+    while let Some(item) = urls.next().await {
+        match item {
+            Ok(entry) => println!("  {}", entry.loc),
+            Err(err) => println!("  error: {}", err),
+        }
+    }
+    Ok(())
+}
+*/